@@ -0,0 +1,86 @@
+//! Storage benchmarks
+//!
+//! `cargo bench --bench storage` lists a page of 1,000 transactions with two
+//! parents each, next to fetching the same transactions one at a time.
+//! Listing reads every parent link of the page in one query, so the page
+//! should cost a small fraction of the per-transaction lookups, which each
+//! query their own parents.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use quantum_dag::core::{DAGNode, NodeStatus, QuantumProof, Transaction};
+use quantum_dag::storage::{DatabaseConfig, DatabaseManager};
+use quantum_dag::TransactionId;
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+const PAGE: usize = 1000;
+
+fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+    let mut transaction = Transaction {
+        id: TransactionId::default(),
+        sender: vec![1u8; 32],
+        receiver: vec![2u8; 32],
+        amount: 10,
+        fee: 1,
+        nonce,
+        timestamp: 1_700_000_000 + nonce,
+        parents,
+        signature: vec![0u8; 64],
+        signature_scheme: Default::default(),
+        quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+        metadata: None,
+    };
+    transaction.id = transaction.compute_id();
+    transaction
+}
+
+/// A database of `PAGE` transactions, each after the two before it
+async fn populate(path: &Path) -> (DatabaseManager, Vec<TransactionId>) {
+    let db = DatabaseManager::new(DatabaseConfig {
+        path: path.to_string_lossy().into_owned(),
+        ..DatabaseConfig::default()
+    }).await.unwrap();
+
+    let mut ids: Vec<TransactionId> = Vec::with_capacity(PAGE);
+    let mut nodes = Vec::with_capacity(PAGE);
+    for nonce in 0..PAGE as u64 {
+        let parents = ids.iter().rev().take(2).cloned().collect();
+        let transaction = transaction(nonce, parents);
+        ids.push(transaction.id.clone());
+        nodes.push(DAGNode { transaction, children: vec![], weight: 1, confidence: 0.0, status: NodeStatus::Pending, quantum_score: 80 });
+    }
+    db.store_bundle(&nodes).await.unwrap();
+    (db, ids)
+}
+
+fn bench_transaction_listing(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let path = std::env::temp_dir().join(format!("qdag-storage-bench-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let (db, ids) = runtime.block_on(populate(&path));
+
+    let mut group = c.benchmark_group("transaction_listing");
+    group.sample_size(20);
+    group.bench_function("get_transactions_page_of_1000", |b| {
+        b.iter(|| {
+            let page = runtime.block_on(db.get_transactions(Some(PAGE), None, None)).unwrap();
+            assert_eq!(page.len(), PAGE);
+        })
+    });
+    group.bench_function("get_transaction_1000_times", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                for id in &ids {
+                    db.get_transaction(id).await.unwrap();
+                }
+            })
+        })
+    });
+    group.finish();
+
+    runtime.block_on(db.close()).unwrap();
+    let _ = std::fs::remove_file(&path);
+}
+
+criterion_group!(benches, bench_transaction_listing);
+criterion_main!(benches);
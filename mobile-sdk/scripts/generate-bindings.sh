@@ -0,0 +1,28 @@
+#!/bin/bash
+# Generate Kotlin and Swift bindings for the mobile SDK from the compiled library.
+set -e
+
+cd "$(dirname "$0")/.."
+
+PROFILE=${PROFILE:-release}
+OUT_DIR=${OUT_DIR:-target/bindings}
+
+case "$(uname)" in
+    Darwin) LIB_EXT=dylib ;;
+    *) LIB_EXT=so ;;
+esac
+
+echo "🔨 Building mobile SDK with FFI support ($PROFILE)..."
+cargo build --features ffi --profile "$PROFILE"
+
+LIB_PATH="target/$PROFILE/libquantum_dag_mobile_sdk.$LIB_EXT"
+
+echo "📦 Generating Kotlin bindings..."
+cargo run --features ffi --bin uniffi-bindgen -- \
+    generate --library "$LIB_PATH" --language kotlin --out-dir "$OUT_DIR/kotlin"
+
+echo "📦 Generating Swift bindings..."
+cargo run --features ffi --bin uniffi-bindgen -- \
+    generate --library "$LIB_PATH" --language swift --out-dir "$OUT_DIR/swift"
+
+echo "✅ Bindings written to $OUT_DIR"
@@ -0,0 +1,118 @@
+//! Token amounts
+//!
+//! Amounts, fees and balances are `u128` base units, matching the node. The
+//! node's API writes them as decimal strings, since JSON numbers above 2^53
+//! lose precision in most clients; plain numbers are still accepted from
+//! older nodes.
+//!
+//! Fields opt in with `#[serde(with = "crate::amount")]`, or
+//! `crate::amount::option` for an `Option<Amount>`.
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+use std::fmt;
+
+use crate::{SDKError, SDKResult};
+
+/// Quantity of the native coin or an issued token, in base units
+pub type Amount = u128;
+
+/// Bytes of an amount in ID and validator set hashes
+///
+/// Mirrors the node's `core::amount::digest_bytes`: amounts below
+/// `u64::MAX` hash as 8 little-endian bytes, larger ones as eight `0xff`
+/// bytes followed by all 16 bytes of the amount.
+pub fn digest_bytes(amount: Amount) -> Vec<u8> {
+    match u64::try_from(amount) {
+        Ok(short) if short < u64::MAX => short.to_le_bytes().to_vec(),
+        _ => [u64::MAX.to_le_bytes().as_slice(), &amount.to_le_bytes()].concat(),
+    }
+}
+
+/// Parse a decimal amount, e.g. entered by a user or passed over FFI
+pub fn parse_amount(value: &str) -> SDKResult<Amount> {
+    value.trim().parse()
+        .map_err(|_| SDKError::Validation(format!("Invalid amount: {}", value)))
+}
+
+pub fn serialize<S: Serializer>(amount: &Amount, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.collect_str(amount)
+    } else {
+        serializer.serialize_u128(*amount)
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Amount, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(AmountVisitor)
+    } else {
+        deserializer.deserialize_u128(AmountVisitor)
+    }
+}
+
+/// Accepts a decimal string or a non-negative integer
+struct AmountVisitor;
+
+impl Visitor<'_> for AmountVisitor {
+    type Value = Amount;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a non-negative integer or a decimal string")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Amount, E> {
+        Ok(value as Amount)
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<Amount, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Amount, E> {
+        Amount::try_from(value).map_err(|_| E::custom(format!("negative amount {}", value)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Amount, E> {
+        value.trim().parse().map_err(|_| E::custom(format!("invalid amount {}", value)))
+    }
+}
+
+/// `Option<Amount>` fields
+pub mod option {
+    use super::Amount;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapped(#[serde(with = "super")] Amount);
+
+    pub fn serialize<S: Serializer>(amount: &Option<Amount>, serializer: S) -> Result<S::Ok, S::Error> {
+        amount.map(Wrapped).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Amount>, D::Error> {
+        Ok(Option::<Wrapped>::deserialize(deserializer)?.map(|Wrapped(amount)| amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Balance {
+        #[serde(with = "crate::amount")]
+        balance: Amount,
+    }
+
+    #[test]
+    fn test_string_and_number_amounts() {
+        let balance = Balance { balance: 10u128.pow(24) };
+        let json = serde_json::to_string(&balance).unwrap();
+        assert_eq!(json, r#"{"balance":"1000000000000000000000000"}"#);
+        assert_eq!(serde_json::from_str::<Balance>(&json).unwrap(), balance);
+        assert_eq!(serde_json::from_str::<Balance>(r#"{"balance":42}"#).unwrap(), Balance { balance: 42 });
+        assert!(parse_amount("-1").is_err());
+    }
+}
@@ -0,0 +1,305 @@
+//! Remote node attestation
+//!
+//! Before trusting a node, the client asks it to sign its software version,
+//! genesis transaction and validator set hash over a fresh random challenge.
+//! The statement is checked against expectations pinned from the network's
+//! trust-anchor file: the validator set hash must match the anchor, and the
+//! signing key must be one of the anchor's validator keys or a key the app
+//! trusts explicitly. The payload layout and the validator set hash must stay
+//! byte-compatible with the node's `identity::attestation` and
+//! `consensus::trust_anchor` modules.
+
+use std::path::Path;
+
+use pqcrypto_dilithium::dilithium3;
+use pqcrypto_traits::sign::{DetachedSignature, PublicKey as _};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::{amount, Amount, SDKError, SDKResult};
+
+/// Domain separator for attestation payloads
+pub const ATTESTATION_DOMAIN: &[u8] = b"qdag-node-attestation-v1";
+
+/// Trust anchor file format version understood by the SDK
+pub const TRUST_ANCHOR_VERSION: u32 = 1;
+
+/// Default age after which an attestation is too old to accept
+pub const DEFAULT_ATTESTATION_MAX_AGE_SECS: u64 = 300;
+
+/// Signature on a node attestation, as serialized by the node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestationSignature {
+    pub signature_type: String,
+    pub signature_data: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+/// Signed statement of a node's software and chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeAttestation {
+    pub software_version: String,
+    /// Hex genesis transaction ID
+    pub genesis_hash: String,
+    /// Hex validator set hash
+    pub validator_set_hash: String,
+    /// Hex challenge the node signed
+    pub challenge: String,
+    pub issued_at: u64,
+    pub signature: AttestationSignature,
+}
+
+impl NodeAttestation {
+    /// Bytes covered by the signature
+    pub fn signing_payload(&self) -> Vec<u8> {
+        attestation_payload(&self.software_version, &self.genesis_hash, &self.validator_set_hash, &self.challenge, self.issued_at)
+    }
+}
+
+/// SHA3-256 over the domain and the length-prefixed attested fields
+pub fn attestation_payload(
+    software_version: &str,
+    genesis_hash: &str,
+    validator_set_hash: &str,
+    challenge: &str,
+    issued_at: u64,
+) -> Vec<u8> {
+    let mut hasher = Sha3_256::new();
+    hasher.update(ATTESTATION_DOMAIN);
+    for field in [software_version, genesis_hash, validator_set_hash, challenge] {
+        hasher.update((field.len() as u32).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(issued_at.to_le_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Fields of a trust-anchor file needed to pin a node
+#[derive(Debug, Deserialize)]
+struct TrustAnchorFile {
+    version: u32,
+    epoch: u64,
+    validators: Vec<AnchorValidator>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AnchorValidator {
+    id: String,
+    public_key: Vec<u8>,
+    #[serde(with = "crate::amount")]
+    stake_amount: Amount,
+}
+
+impl TrustAnchorFile {
+    fn validator_set_hash(&self) -> Vec<u8> {
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"quantum-dag-trust-anchor");
+        hasher.update(self.version.to_le_bytes());
+        hasher.update(self.epoch.to_le_bytes());
+        for validator in &self.validators {
+            hasher.update((validator.id.len() as u32).to_le_bytes());
+            hasher.update(validator.id.as_bytes());
+            hasher.update((validator.public_key.len() as u32).to_le_bytes());
+            hasher.update(&validator.public_key);
+            hasher.update(amount::digest_bytes(validator.stake_amount));
+        }
+        hasher.finalize().to_vec()
+    }
+}
+
+/// Values a node must attest to before the client uses it
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeExpectations {
+    /// Hex validator set hash from the trust anchor
+    pub validator_set_hash: String,
+    /// Dilithium3 keys allowed to sign attestations
+    pub trusted_keys: Vec<Vec<u8>>,
+    /// Hex genesis transaction ID, unchecked when `None`
+    pub genesis_hash: Option<String>,
+    /// Oldest acceptable software version, e.g. "0.2.0"
+    pub min_version: Option<String>,
+    pub max_age_secs: u64,
+}
+
+impl NodeExpectations {
+    /// Pin the validator set and validator keys of a trust anchor
+    pub fn from_trust_anchor(json: &str) -> SDKResult<Self> {
+        let anchor: TrustAnchorFile = serde_json::from_str(json)?;
+        if anchor.version != TRUST_ANCHOR_VERSION {
+            return Err(SDKError::Config(format!("Unsupported trust anchor version {}", anchor.version)));
+        }
+
+        Ok(Self {
+            validator_set_hash: hex::encode(anchor.validator_set_hash()),
+            trusted_keys: anchor.validators.into_iter().map(|v| v.public_key).collect(),
+            genesis_hash: None,
+            min_version: None,
+            max_age_secs: DEFAULT_ATTESTATION_MAX_AGE_SECS,
+        })
+    }
+
+    /// Read a trust-anchor file bundled with the app
+    pub fn from_trust_anchor_file(path: impl AsRef<Path>) -> SDKResult<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| SDKError::Config(format!("Failed to read trust anchor: {}", e)))?;
+        Self::from_trust_anchor(&json)
+    }
+
+    /// Also require the node to follow this genesis transaction
+    pub fn with_genesis_hash(mut self, genesis_hash: &str) -> Self {
+        self.genesis_hash = Some(genesis_hash.to_lowercase());
+        self
+    }
+
+    /// Also require at least this software version
+    pub fn with_min_version(mut self, version: &str) -> Self {
+        self.min_version = Some(version.to_string());
+        self
+    }
+
+    /// Accept attestations from a node whose key is not a validator key
+    pub fn with_trusted_key(mut self, public_key: Vec<u8>) -> Self {
+        self.trusted_keys.push(public_key);
+        self
+    }
+
+    /// Check an attestation answering `challenge`, as of `now` in Unix seconds
+    pub fn verify(&self, attestation: &NodeAttestation, challenge: &str, now: u64) -> SDKResult<()> {
+        if !attestation.challenge.eq_ignore_ascii_case(challenge) {
+            return Err(SDKError::Auth("Attestation answers a different challenge".to_string()));
+        }
+        if attestation.issued_at.abs_diff(now) > self.max_age_secs {
+            return Err(SDKError::Auth(format!("Attestation issued at {} is not current", attestation.issued_at)));
+        }
+
+        let signature = &attestation.signature;
+        if signature.signature_type != "Dilithium3" {
+            return Err(SDKError::Auth(format!("Unsupported attestation signature {}", signature.signature_type)));
+        }
+        if !self.trusted_keys.contains(&signature.public_key) {
+            return Err(SDKError::Auth("Attestation signed by an untrusted key".to_string()));
+        }
+        let public_key = dilithium3::PublicKey::from_bytes(&signature.public_key)
+            .map_err(|e| SDKError::Crypto(e.to_string()))?;
+        let valid = dilithium3::DetachedSignature::from_bytes(&signature.signature_data)
+            .map(|sig| dilithium3::verify_detached_signature(&sig, &attestation.signing_payload(), &public_key).is_ok())
+            .unwrap_or(false);
+        if !valid {
+            return Err(SDKError::Auth("Invalid attestation signature".to_string()));
+        }
+
+        if !attestation.validator_set_hash.eq_ignore_ascii_case(&self.validator_set_hash) {
+            return Err(SDKError::Auth("Node follows a different validator set".to_string()));
+        }
+        if let Some(genesis_hash) = &self.genesis_hash {
+            if !attestation.genesis_hash.eq_ignore_ascii_case(genesis_hash) {
+                return Err(SDKError::Auth("Node follows a different genesis".to_string()));
+            }
+        }
+        if let Some(min_version) = &self.min_version {
+            if !version_at_least(&attestation.software_version, min_version) {
+                return Err(SDKError::Auth(format!(
+                    "Node runs {}, older than {}", attestation.software_version, min_version
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compare dotted numeric versions; pre-release suffixes are ignored
+fn version_at_least(version: &str, minimum: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split(['-', '+']).next().unwrap_or("")
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (mut version, mut minimum) = (parse(version), parse(minimum));
+    let len = version.len().max(minimum.len());
+    version.resize(len, 0);
+    minimum.resize(len, 0);
+    version >= minimum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANCHOR: &str = r#"{
+        "version": 1, "epoch": 3, "consensus_height": 0, "created_at": 0,
+        "validators": [
+            {"id": "v1", "public_key": [1, 2, 3], "stake_amount": 10},
+            {"id": "v2", "public_key": [4, 5], "stake_amount": 20}
+        ],
+        "previous_set_hash": null, "previous_signatures": []
+    }"#;
+
+    fn signed(public_key: &dilithium3::PublicKey, secret_key: &dilithium3::SecretKey, set_hash: &str, challenge: &str) -> NodeAttestation {
+        let mut attestation = NodeAttestation {
+            software_version: "0.2.1".to_string(),
+            genesis_hash: "aa".repeat(32),
+            validator_set_hash: set_hash.to_string(),
+            challenge: challenge.to_string(),
+            issued_at: 1_700_000_000,
+            signature: AttestationSignature {
+                signature_type: "Dilithium3".to_string(),
+                signature_data: Vec::new(),
+                public_key: public_key.as_bytes().to_vec(),
+            },
+        };
+        attestation.signature.signature_data = dilithium3::detached_sign(&attestation.signing_payload(), secret_key)
+            .as_bytes()
+            .to_vec();
+        attestation
+    }
+
+    #[test]
+    fn test_matches_node_encodings() {
+        // Same vectors as the node's attestation and trust anchor tests
+        assert_eq!(
+            hex::encode(attestation_payload("0.1.0", "00ff", "abcd", "0102", 1_700_000_000)),
+            "de77d09dde7d07d6c1a13f914def92876d76736bdd64b9c45f610bf9b7225ded"
+        );
+        let expectations = NodeExpectations::from_trust_anchor(ANCHOR).unwrap();
+        assert_eq!(expectations.validator_set_hash, "d8cfa2954e324d3d693f58eeed6c5cfef9307bc7679e6abae95939f89cc04381");
+        assert_eq!(expectations.trusted_keys, vec![vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_verify_accepts_matching_node() {
+        let (public_key, secret_key) = dilithium3::keypair();
+        let expectations = NodeExpectations::from_trust_anchor(ANCHOR).unwrap()
+            .with_trusted_key(public_key.as_bytes().to_vec())
+            .with_genesis_hash(&"AA".repeat(32))
+            .with_min_version("0.2.0");
+        let attestation = signed(&public_key, &secret_key, &expectations.validator_set_hash, "0102");
+
+        assert!(expectations.verify(&attestation, "0102", 1_700_000_060).is_ok());
+        assert!(expectations.clone().with_min_version("0.10").verify(&attestation, "0102", 1_700_000_060).is_err());
+        // Replayed for another challenge, or too old
+        assert!(expectations.verify(&attestation, "0103", 1_700_000_060).is_err());
+        assert!(expectations.verify(&attestation, "0102", 1_700_001_000).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_untrusted_or_mismatched_nodes() {
+        let (public_key, secret_key) = dilithium3::keypair();
+        let expectations = NodeExpectations::from_trust_anchor(ANCHOR).unwrap();
+
+        // Valid signature, but the key is not pinned
+        let attestation = signed(&public_key, &secret_key, &expectations.validator_set_hash, "0102");
+        assert!(matches!(expectations.verify(&attestation, "0102", 1_700_000_000), Err(SDKError::Auth(_))));
+
+        // Pinned key, but another validator set
+        let expectations = expectations.with_trusted_key(public_key.as_bytes().to_vec());
+        let attestation = signed(&public_key, &secret_key, &"00".repeat(32), "0102");
+        assert!(expectations.verify(&attestation, "0102", 1_700_000_000).is_err());
+
+        // Tampered after signing
+        let mut attestation = signed(&public_key, &secret_key, &expectations.validator_set_hash, "0102");
+        attestation.software_version = "9.9.9".to_string();
+        assert!(expectations.verify(&attestation, "0102", 1_700_000_000).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+//! Binding generator for the Kotlin and Swift wrappers of the mobile SDK
+//!
+//! Usage: `cargo run --features ffi --bin uniffi-bindgen -- generate --library <lib> --language kotlin --out-dir <dir>`
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -1,6 +1,6 @@
 //! Mobile client for Quantum DAG Blockchain network communication
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 use serde::{Serialize, Deserialize};
@@ -11,10 +11,12 @@ use tokio_tungstenite::tungstenite::Message;
 use futures::{StreamExt, SinkExt};
 use uuid::Uuid;
 
+use crate::attestation::{NodeAttestation, NodeExpectations};
+use crate::proof::{verify_inclusion_proof, InclusionProof};
 use crate::types::*;
 use crate::crypto::CryptoService;
-use crate::utils::retry;
-use crate::{SDKConfig, NetworkConfig, SDKResult, SDKError};
+use crate::utils::{retry, EventBus};
+use crate::{Amount, SDKConfig, NetworkConfig, SDKResult, SDKError};
 
 /// Mobile client for blockchain communication
 pub struct MobileClient {
@@ -24,6 +26,11 @@ pub struct MobileClient {
     crypto: Arc<CryptoService>,
     node_index: usize,
     connected_peers: Arc<RwLock<HashMap<String, Peer>>>,
+    event_bus: Arc<std::sync::RwLock<EventBus<BlockchainEvent>>>,
+    /// Values nodes must attest to before they are used
+    expectations: Option<NodeExpectations>,
+    /// Node URLs that passed attestation
+    attested_nodes: Arc<RwLock<HashSet<String>>>,
 }
 
 impl MobileClient {
@@ -41,11 +48,64 @@ impl MobileClient {
             crypto,
             node_index: 0,
             connected_peers: Arc::new(RwLock::new(HashMap::new())),
+            event_bus: Arc::new(std::sync::RwLock::new(EventBus::new())),
+            expectations: None,
+            attested_nodes: Arc::new(RwLock::new(HashSet::new())),
         })
     }
 
+    /// Only use nodes that attest to these values
+    ///
+    /// Each node is attested before its first request; nodes that fail are
+    /// refused with `SDKError::Auth`.
+    pub fn set_node_expectations(&mut self, expectations: NodeExpectations) {
+        self.expectations = Some(expectations);
+        self.attested_nodes = Arc::new(RwLock::new(HashSet::new()));
+    }
+
+    /// Challenge a node and verify its attestation against the pinned expectations
+    pub async fn attest_node(&self, base_url: &str) -> SDKResult<NodeAttestation> {
+        let expectations = self.expectations.as_ref()
+            .ok_or_else(|| SDKError::Config("No node expectations set".to_string()))?;
+        let challenge = hex::encode(rand::random::<[u8; 32]>());
+
+        let url = format!("{}/api/attestation?challenge={}", base_url, challenge);
+        let response = self.http_client.get(&url).send().await?;
+        let attestation: NodeAttestation = Self::api_data(response).await?;
+
+        if let Err(e) = expectations.verify(&attestation, &challenge, chrono::Utc::now().timestamp() as u64) {
+            log::warn!("Node {} failed attestation: {}", base_url, e);
+            return Err(e);
+        }
+        self.attested_nodes.write().await.insert(base_url.to_string());
+        log::info!("Node {} attested software {}", base_url, attestation.software_version);
+        Ok(attestation)
+    }
+
+    /// Attest the current node once, when expectations are set
+    async fn ensure_attested(&self) -> SDKResult<()> {
+        if self.expectations.is_none() {
+            return Ok(());
+        }
+        let base_url = &self.config.node_urls[self.node_index];
+        if self.attested_nodes.read().await.contains(base_url) {
+            return Ok(());
+        }
+        self.attest_node(base_url).await.map(|_| ())
+    }
+
+    /// Register a callback for blockchain events received over the WebSocket
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(&BlockchainEvent) + Send + Sync + 'static,
+    {
+        if let Ok(mut bus) = self.event_bus.write() {
+            bus.subscribe(callback);
+        }
+    }
+
     /// Get wallet balance
-    pub async fn get_balance(&self, address: &str) -> SDKResult<u64> {
+    pub async fn get_balance(&self, address: &str) -> SDKResult<Amount> {
         let url = self.get_node_url("/api/balance");
         let params = serde_json::json!({"address": address});
         
@@ -172,9 +232,10 @@ impl MobileClient {
         // Start listening for messages
         let client = ws_client.clone();
         let peers = self.connected_peers.clone();
+        let event_bus = self.event_bus.clone();
         
         tokio::spawn(async move {
-            if let Err(e) = Self::handle_websocket_messages(client, peers).await {
+            if let Err(e) = Self::handle_websocket_messages(client, peers, event_bus).await {
                 eprintln!("WebSocket error: {}", e);
             }
         });
@@ -186,6 +247,7 @@ impl MobileClient {
     async fn handle_websocket_messages(
         ws_client: Arc<RwLock<WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>>>,
         peers: Arc<RwLock<HashMap<String, Peer>>>,
+        event_bus: Arc<std::sync::RwLock<EventBus<BlockchainEvent>>>,
     ) -> SDKResult<()> {
         let mut ws = ws_client.write().await;
         
@@ -197,10 +259,12 @@ impl MobileClient {
                             "new_transaction" => {
                                 // Handle new transaction event
                                 log::debug!("New transaction: {}", event.data);
+                                Self::publish_event(&event_bus, &event);
                             },
                             "new_block" => {
                                 // Handle new block event
                                 log::debug!("New block: {}", event.data);
+                                Self::publish_event(&event_bus, &event);
                             },
                             "peer_connected" => {
                                 if let Ok(peer) = serde_json::from_value::<Peer>(event.data) {
@@ -237,6 +301,23 @@ impl MobileClient {
         Ok(())
     }
 
+    /// Forward a WebSocket event to registered listeners
+    fn publish_event(event_bus: &std::sync::RwLock<EventBus<BlockchainEvent>>, event: &WebSocketEvent) {
+        let blockchain_event = BlockchainEvent {
+            id: Uuid::new_v4().to_string(),
+            event_type: event.event_type.clone(),
+            data: event.data.clone(),
+            timestamp: chrono::DateTime::from_timestamp(event.timestamp as i64, 0)
+                .unwrap_or_else(chrono::Utc::now),
+            block_number: event.data.get("height").and_then(|h| h.as_u64()),
+            transaction_hash: event.data.get("hash").and_then(|h| h.as_str()).map(|h| h.to_string()),
+        };
+
+        if let Ok(bus) = event_bus.read() {
+            bus.publish(blockchain_event);
+        }
+    }
+
     /// Subscribe to transaction events
     pub async fn subscribe_to_transactions(&self, address: &str) -> SDKResult<()> {
         let subscription = WebSocketSubscription {
@@ -295,6 +376,7 @@ impl MobileClient {
 
     /// Make HTTP GET request
     async fn get(&self, url: &str) -> SDKResult<Response> {
+        self.ensure_attested().await?;
         retry(
             self.config.max_retries,
             Duration::from_millis(self.config.retry_delay_ms),
@@ -310,6 +392,7 @@ impl MobileClient {
 
     /// Make HTTP POST request
     async fn post(&self, url: &str, data: &serde_json::Value) -> SDKResult<Response> {
+        self.ensure_attested().await?;
         retry(
             self.config.max_retries,
             Duration::from_millis(self.config.retry_delay_ms),
@@ -350,6 +433,35 @@ impl MobileClient {
         Ok(Some(transaction))
     }
 
+    /// Fetch and verify proof that a transaction is an ancestor of a trusted checkpoint
+    pub async fn verify_transaction_inclusion(&self, tx_id: &str, checkpoint_id: &str) -> SDKResult<InclusionProof> {
+        let url = self.get_node_url(&format!("/api/transactions/{}/proof?checkpoint={}", tx_id, checkpoint_id));
+
+        let response = self.get(&url).await?;
+        let proof: InclusionProof = Self::api_data(response).await?;
+        verify_inclusion_proof(&proof, tx_id, checkpoint_id)?;
+
+        Ok(proof)
+    }
+
+    /// Get transaction history for an address
+    pub async fn get_transaction_history(
+        &self,
+        address: &str,
+        pagination: &PaginationOptions,
+    ) -> SDKResult<PaginatedResponse<Transaction>> {
+        let url = self.get_node_url(&format!(
+            "/api/transactions?address={}&page={}&per_page={}",
+            address, pagination.page, pagination.per_page
+        ));
+        
+        let response = self.get(&url).await?;
+        let history: PaginatedResponse<Transaction> = response.json().await
+            .map_err(|e| SDKError::Serialization(e.to_string()))?;
+        
+        Ok(history)
+    }
+
     /// Get block by height
     pub async fn get_block(&self, height: u64) -> SDKResult<Option<Block>> {
         let url = self.get_node_url(&format!("/api/blocks/{}", height));
@@ -376,12 +488,106 @@ impl MobileClient {
         
         Ok(block)
     }
+
+    /// Register a Bloom filter of watched addresses, returning its subscription id
+    pub async fn register_address_filter(&self, filter: &crate::sync::BloomFilter) -> SDKResult<String> {
+        let url = self.get_node_url("/api/sync/filters");
+        let filter_data = serde_json::to_value(filter)
+            .map_err(|e| SDKError::Serialization(e.to_string()))?;
+
+        let response = self.post(&url, &filter_data).await?;
+        let registration: FilterRegistrationResponse = Self::api_data(response).await?;
+
+        Ok(registration.subscription_id)
+    }
+
+    /// Get transactions matching a registered filter, starting at `from_segment`
+    pub async fn get_filtered_transactions(
+        &self,
+        subscription_id: &str,
+        from_segment: u64,
+    ) -> SDKResult<crate::sync::FilteredSync> {
+        let url = self.get_node_url(&format!(
+            "/api/sync/filters/{}?from_segment={}",
+            subscription_id, from_segment
+        ));
+
+        let response = self.get(&url).await?;
+        Self::api_data(response).await
+    }
+
+    /// Get filter headers starting at segment `from`
+    pub async fn get_filter_headers(&self, from: u64, count: usize) -> SDKResult<Vec<crate::sync::FilterHeader>> {
+        let url = self.get_node_url(&format!("/api/sync/headers?from={}&count={}", from, count));
+
+        let response = self.get(&url).await?;
+        Self::api_data(response).await
+    }
+
+    /// Get the compact filter of a sealed segment
+    pub async fn get_filter_segment(&self, index: u64) -> SDKResult<crate::sync::FilterSegment> {
+        let url = self.get_node_url(&format!("/api/sync/segments/{}", index));
+
+        let response = self.get(&url).await?;
+        Self::api_data(response).await
+    }
+
+    /// Get the node's mempool depth, confirmation latency and fee rates
+    pub async fn get_congestion_status(&self) -> SDKResult<crate::congestion::CongestionStatus> {
+        let url = self.get_node_url("/api/network/congestion");
+
+        let response = self.get(&url).await?;
+        Self::api_data(response).await
+    }
+
+    /// Request test funds for `address` from the node's faucet
+    pub async fn request_faucet_funds(&self, address: &str, captcha_token: Option<&str>) -> SDKResult<FaucetGrant> {
+        let url = self.get_node_url("/api/faucet");
+        let params = serde_json::json!({"address": address, "captcha_token": captcha_token});
+
+        let response = self.post(&url, &params).await?;
+        Self::api_data(response).await
+    }
+
+    /// Get an atomic swap by its hashlock
+    pub async fn get_swap(&self, hashlock: &str) -> SDKResult<crate::swaps::AtomicSwap> {
+        let url = self.get_node_url(&format!("/api/swaps/{}", hashlock));
+
+        let response = self.get(&url).await?;
+        Self::api_data(response).await
+    }
+
+    /// Get the atomic swaps an address is party to, newest first
+    pub async fn get_swaps(&self, address: &str) -> SDKResult<Vec<crate::swaps::AtomicSwap>> {
+        let url = self.get_node_url(&format!("/api/accounts/{}/swaps", address));
+
+        let response = self.get(&url).await?;
+        Self::api_data(response).await
+    }
+
+    async fn api_data<T: serde::de::DeserializeOwned>(response: Response) -> SDKResult<T> {
+        let api_response: ApiResponse<T> = response.json().await
+            .map_err(|e| SDKError::Serialization(e.to_string()))?;
+
+        match api_response.data {
+            Some(data) if api_response.success => Ok(data),
+            _ => Err(SDKError::Network(
+                api_response.error.unwrap_or_else(|| "Node returned no data".to_string())
+            )),
+        }
+    }
 }
 
 // Response types
+#[derive(Debug, Serialize, Deserialize)]
+struct FilterRegistrationResponse {
+    subscription_id: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct BalanceResponse {
-    balance: u64,
+    #[serde(with = "crate::amount")]
+    balance: Amount,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -496,4 +702,26 @@ mod tests {
         assert_eq!(client.node_index, 0);
         // Note: switch_node is async, but we can't test it easily without a runtime
     }
+
+    #[test]
+    fn test_websocket_events_reach_listeners() {
+        let crypto = Arc::new(CryptoService::new(&crate::SecurityConfig::default()).unwrap());
+        let client = MobileClient::new(&NetworkConfig::default(), crypto).unwrap();
+
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = received.clone();
+        client.on_event(move |event| sink.lock().unwrap().push(event.clone()));
+
+        let event = WebSocketEvent {
+            event_type: "new_block".to_string(),
+            data: serde_json::json!({"height": 42}),
+            timestamp: 1_700_000_000,
+        };
+        MobileClient::publish_event(&client.event_bus, &event);
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].event_type, "new_block");
+        assert_eq!(received[0].block_number, Some(42));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,208 @@
+//! Address risk screening before send
+//!
+//! The SDK asks a `ComplianceProvider` about the sender and receiver of every
+//! outgoing transaction before it is signed and submitted. Denied transfers
+//! fail with a validation error; transfers flagged for review go ahead but are
+//! logged. Every decision is kept for audit.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Amount, SDKError, SDKResult};
+
+/// Outcome of screening a pair of addresses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ComplianceDecision {
+    Allow,
+    Deny { reasons: Vec<String> },
+    Review { reasons: Vec<String> },
+}
+
+/// Screens transaction addresses against a compliance source
+pub trait ComplianceProvider: Send + Sync {
+    /// Provider name recorded in the audit log
+    fn name(&self) -> String;
+
+    /// Screen a transfer from `sender` to `receiver`
+    fn screen(&self, sender: &str, receiver: &str) -> SDKResult<ComplianceDecision>;
+}
+
+/// Provider that allows everything
+#[derive(Debug, Default)]
+pub struct NoopComplianceProvider;
+
+impl ComplianceProvider for NoopComplianceProvider {
+    fn name(&self) -> String {
+        "noop".to_string()
+    }
+
+    fn screen(&self, _sender: &str, _receiver: &str) -> SDKResult<ComplianceDecision> {
+        Ok(ComplianceDecision::Allow)
+    }
+}
+
+/// Provider backed by static deny and review lists
+#[derive(Debug, Default)]
+pub struct ListComplianceProvider {
+    deny: HashMap<String, String>,
+    review: HashMap<String, String>,
+}
+
+impl ListComplianceProvider {
+    /// Create an empty list provider
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deny transfers involving `address`
+    pub fn deny(mut self, address: &str, reason: &str) -> Self {
+        self.deny.insert(address.to_lowercase(), reason.to_string());
+        self
+    }
+
+    /// Flag transfers involving `address` for review
+    pub fn review(mut self, address: &str, reason: &str) -> Self {
+        self.review.insert(address.to_lowercase(), reason.to_string());
+        self
+    }
+
+    fn matches(list: &HashMap<String, String>, sender: &str, receiver: &str) -> Vec<String> {
+        [("sender", sender), ("receiver", receiver)]
+            .iter()
+            .filter_map(|(role, address)| {
+                list.get(&address.to_lowercase()).map(|reason| format!("{} {}: {}", role, address, reason))
+            })
+            .collect()
+    }
+}
+
+impl ComplianceProvider for ListComplianceProvider {
+    fn name(&self) -> String {
+        "list".to_string()
+    }
+
+    fn screen(&self, sender: &str, receiver: &str) -> SDKResult<ComplianceDecision> {
+        let reasons = Self::matches(&self.deny, sender, receiver);
+        if !reasons.is_empty() {
+            return Ok(ComplianceDecision::Deny { reasons });
+        }
+
+        let reasons = Self::matches(&self.review, sender, receiver);
+        if !reasons.is_empty() {
+            return Ok(ComplianceDecision::Review { reasons });
+        }
+
+        Ok(ComplianceDecision::Allow)
+    }
+}
+
+/// Audit record of a screening decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceRecord {
+    pub timestamp: DateTime<Utc>,
+    pub provider: String,
+    pub sender: String,
+    pub receiver: String,
+    pub amount: Amount,
+    pub decision: ComplianceDecision,
+}
+
+/// Runs a provider and keeps an audit log of its decisions
+pub struct ComplianceScreener {
+    provider: Arc<dyn ComplianceProvider>,
+    records: RwLock<VecDeque<ComplianceRecord>>,
+    max_records: usize,
+}
+
+impl ComplianceScreener {
+    /// Create a screener around `provider`
+    pub fn new(provider: Arc<dyn ComplianceProvider>) -> Self {
+        Self {
+            provider,
+            records: RwLock::new(VecDeque::new()),
+            max_records: 1000,
+        }
+    }
+
+    /// Screen an outgoing transfer, failing if it is denied
+    pub fn check(&self, sender: &str, receiver: &str, amount: Amount) -> SDKResult<ComplianceDecision> {
+        let decision = self.provider.screen(sender, receiver)?;
+
+        {
+            let mut records = self.records.write()
+                .map_err(|_| SDKError::Unknown("Compliance log lock poisoned".to_string()))?;
+            if records.len() >= self.max_records {
+                records.pop_front();
+            }
+            records.push_back(ComplianceRecord {
+                timestamp: Utc::now(),
+                provider: self.provider.name(),
+                sender: sender.to_string(),
+                receiver: receiver.to_string(),
+                amount,
+                decision: decision.clone(),
+            });
+        }
+
+        match &decision {
+            ComplianceDecision::Allow => Ok(decision),
+            ComplianceDecision::Review { reasons } => {
+                log::warn!("Transfer to {} flagged for compliance review: {}", receiver, reasons.join("; "));
+                Ok(decision)
+            },
+            ComplianceDecision::Deny { reasons } => Err(SDKError::Validation(format!(
+                "Transfer denied by compliance screening: {}",
+                reasons.join("; ")
+            ))),
+        }
+    }
+
+    /// Recorded decisions, oldest first
+    pub fn records(&self) -> Vec<ComplianceRecord> {
+        self.records.read()
+            .map(|records| records.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for ComplianceScreener {
+    fn default() -> Self {
+        Self::new(Arc::new(NoopComplianceProvider))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denied_transfer_fails() {
+        let screener = ComplianceScreener::new(Arc::new(
+            ListComplianceProvider::new().deny("qdag_bad", "sanctioned"),
+        ));
+
+        assert!(matches!(
+            screener.check("qdag_me", "QDAG_BAD", 100),
+            Err(SDKError::Validation(_))
+        ));
+        assert_eq!(screener.check("qdag_me", "qdag_ok", 100).unwrap(), ComplianceDecision::Allow);
+
+        let records = screener.records();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(records[0].decision, ComplianceDecision::Deny { .. }));
+    }
+
+    #[test]
+    fn test_review_allows_transfer() {
+        let screener = ComplianceScreener::new(Arc::new(
+            ListComplianceProvider::new().review("qdag_exchange", "high risk exchange"),
+        ));
+
+        let decision = screener.check("qdag_me", "qdag_exchange", 5).unwrap();
+        assert!(matches!(decision, ComplianceDecision::Review { .. }));
+    }
+}
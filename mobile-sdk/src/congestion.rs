@@ -0,0 +1,236 @@
+//! Congestion-aware send gating
+//!
+//! Nodes report their intent log depth, recent confirmation latency and fee
+//! rates at `/api/network/congestion`. Before a send, `CongestionGate` turns
+//! that report into a `SendDecision`: send as is, send with a warning,
+//! suggest a higher fee, or defer a low-priority send until congestion eases.
+//! The decision only uses the node's report; nothing is signed or sent here.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Amount;
+
+/// Payload bytes of a plain transfer as the node prices it: sender,
+/// receiver, four integer fields and two parent IDs
+const TRANSFER_PAYLOAD_BYTES: usize = 32 + 32 + 4 * 8 + 2 * 32;
+
+/// Congestion level reported by the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CongestionLevel {
+    Normal,
+    Elevated,
+    High,
+    Severe,
+}
+
+/// Confirmation latency percentiles in seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub samples: usize,
+    pub p50_secs: u64,
+    pub p90_secs: u64,
+    pub p99_secs: u64,
+}
+
+/// Recent fee rates per KiB of payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeRates {
+    #[serde(with = "crate::amount")]
+    pub economy: Amount,
+    #[serde(with = "crate::amount")]
+    pub standard: Amount,
+    #[serde(with = "crate::amount")]
+    pub priority: Amount,
+}
+
+/// Congestion report returned by a node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CongestionStatus {
+    pub level: CongestionLevel,
+    /// Transactions waiting for validation
+    pub mempool_depth: usize,
+    pub mempool_capacity: usize,
+    pub confirmation_latency: LatencyPercentiles,
+    pub target_confirmation_secs: u64,
+    pub fee_rates: FeeRates,
+    pub timestamp: u64,
+}
+
+impl CongestionStatus {
+    /// Fee for a plain transfer at the rate matching `priority`
+    pub fn suggested_fee(&self, priority: SendPriority) -> Amount {
+        self.suggested_fee_for_size(priority, TRANSFER_PAYLOAD_BYTES)
+    }
+
+    /// Fee for `payload_bytes` at the rate matching `priority`, rounded up
+    pub fn suggested_fee_for_size(&self, priority: SendPriority, payload_bytes: usize) -> Amount {
+        let rate = match priority {
+            SendPriority::Low => self.fee_rates.economy,
+            SendPriority::Normal => self.fee_rates.standard,
+            SendPriority::High => self.fee_rates.priority,
+        };
+        rate.saturating_mul(payload_bytes as Amount).div_ceil(1024).max(1)
+    }
+}
+
+/// How urgent a send is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SendPriority {
+    /// Can wait for congestion to ease
+    Low,
+    #[default]
+    Normal,
+    /// Should confirm quickly and pays for it
+    High,
+}
+
+/// When to warn, raise fees and defer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CongestionPolicy {
+    /// Level from which users are warned before sending
+    pub warn_at: CongestionLevel,
+    /// Level from which a fee below the suggested one is flagged
+    pub raise_fee_at: CongestionLevel,
+    /// Level from which low-priority sends are deferred
+    pub defer_low_priority_at: CongestionLevel,
+    /// How often a deferred send checks congestion again
+    pub recheck_interval_secs: u64,
+    /// Longest a deferred send waits before giving up
+    pub max_defer_secs: u64,
+}
+
+impl Default for CongestionPolicy {
+    fn default() -> Self {
+        Self {
+            warn_at: CongestionLevel::Elevated,
+            raise_fee_at: CongestionLevel::Elevated,
+            defer_low_priority_at: CongestionLevel::High,
+            recheck_interval_secs: 30,
+            max_defer_secs: 600,
+        }
+    }
+}
+
+/// What to do with a send under the current congestion
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SendDecision {
+    Proceed,
+    /// Send, but tell the user confirmation may be slow
+    Warn { level: CongestionLevel, expected_confirmation_secs: u64 },
+    /// The offered fee is likely to wait behind better-paying transactions
+    RaiseFee { level: CongestionLevel, offered_fee: Amount, suggested_fee: Amount },
+    /// Hold a low-priority send and check again later
+    Defer { level: CongestionLevel, retry_after_secs: u64 },
+}
+
+/// Outcome of a congestion-gated send
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum GatedSend {
+    /// The transaction was submitted
+    Sent {
+        hash: String,
+        fee: Amount,
+        /// Warning to show the user, if congestion was elevated
+        warning: Option<SendDecision>,
+        /// Time spent waiting for congestion to ease
+        deferred_secs: u64,
+    },
+    /// Nothing was sent; retry with at least `suggested_fee`
+    FeeTooLow { offered_fee: Amount, suggested_fee: Amount },
+    /// Nothing was sent; congestion did not ease within the policy's limit
+    Deferred { level: CongestionLevel, retry_after_secs: u64 },
+}
+
+/// Turns congestion reports into send decisions
+#[derive(Debug, Clone, Default)]
+pub struct CongestionGate {
+    policy: CongestionPolicy,
+}
+
+impl CongestionGate {
+    pub fn new(policy: CongestionPolicy) -> Self {
+        Self { policy }
+    }
+
+    pub fn policy(&self) -> &CongestionPolicy {
+        &self.policy
+    }
+
+    /// Decision for a send paying `fee` at `priority`
+    pub fn evaluate(&self, status: &CongestionStatus, priority: SendPriority, fee: Amount) -> SendDecision {
+        let level = status.level;
+        if priority == SendPriority::Low && level >= self.policy.defer_low_priority_at {
+            return SendDecision::Defer { level, retry_after_secs: self.policy.recheck_interval_secs };
+        }
+        let suggested_fee = status.suggested_fee(priority);
+        if level >= self.policy.raise_fee_at && fee < suggested_fee {
+            return SendDecision::RaiseFee { level, offered_fee: fee, suggested_fee };
+        }
+        if level >= self.policy.warn_at {
+            return SendDecision::Warn { level, expected_confirmation_secs: status.confirmation_latency.p90_secs };
+        }
+        SendDecision::Proceed
+    }
+
+    /// Wait between congestion checks of a deferred send
+    pub fn recheck_interval(&self) -> Duration {
+        Duration::from_secs(self.policy.recheck_interval_secs.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(level: CongestionLevel) -> CongestionStatus {
+        CongestionStatus {
+            level,
+            mempool_depth: 0,
+            mempool_capacity: 100,
+            confirmation_latency: LatencyPercentiles { samples: 10, p50_secs: 8, p90_secs: 30, p99_secs: 60 },
+            target_confirmation_secs: 10,
+            fee_rates: FeeRates { economy: 10, standard: 40, priority: 160 },
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_suggested_fee_scales_with_priority() {
+        let status = status(CongestionLevel::Normal);
+        assert_eq!(status.suggested_fee_for_size(SendPriority::Normal, 1024), 40);
+        assert_eq!(status.suggested_fee_for_size(SendPriority::High, 512), 80);
+        // 160 payload bytes at 10 per KiB rounds up
+        assert_eq!(status.suggested_fee(SendPriority::Low), 2);
+    }
+
+    #[test]
+    fn test_decisions_by_level_and_priority() {
+        let gate = CongestionGate::default();
+
+        assert_eq!(gate.evaluate(&status(CongestionLevel::Normal), SendPriority::Low, 1), SendDecision::Proceed);
+        assert!(matches!(
+            gate.evaluate(&status(CongestionLevel::Elevated), SendPriority::Normal, 100),
+            SendDecision::Warn { expected_confirmation_secs: 30, .. }
+        ));
+        assert_eq!(
+            gate.evaluate(&status(CongestionLevel::High), SendPriority::Normal, 1),
+            SendDecision::RaiseFee { level: CongestionLevel::High, offered_fee: 1, suggested_fee: 7 }
+        );
+        assert!(matches!(
+            gate.evaluate(&status(CongestionLevel::High), SendPriority::Low, 1_000),
+            SendDecision::Defer { retry_after_secs: 30, .. }
+        ));
+        // High priority is never deferred
+        assert!(matches!(
+            gate.evaluate(&status(CongestionLevel::Severe), SendPriority::High, 1_000),
+            SendDecision::Warn { .. }
+        ));
+    }
+}
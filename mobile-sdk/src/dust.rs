@@ -0,0 +1,268 @@
+//! Dust consolidation
+//!
+//! Small balances left on a user's other wallet addresses can cost more in
+//! fees to spend than they are worth at busy times. During background sync,
+//! `DustConsolidator` sweeps balances under the policy's threshold into the
+//! primary address, but only while the network is quiet enough for the
+//! economy fee to be a small share of what is swept. Every run produces a
+//! `ConsolidationReport` with the amounts recovered, the fees paid and the
+//! fees saved against sweeping the same balances at the standard rate.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::congestion::{CongestionLevel, CongestionStatus, SendPriority};
+use crate::Amount;
+
+/// Reports kept for display
+const MAX_REPORTS: usize = 50;
+
+/// When and how dust is consolidated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DustPolicy {
+    pub enabled: bool,
+    /// Balances below this are dust
+    pub threshold: Amount,
+    /// Largest fee, as a percentage of the balance swept, worth paying
+    pub max_fee_percent: u8,
+    /// Highest congestion at which dust is swept
+    pub max_congestion: CongestionLevel,
+    /// Shortest time between consolidation runs
+    pub min_interval_secs: u64,
+}
+
+impl Default for DustPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 10_000,
+            max_fee_percent: 10,
+            max_congestion: CongestionLevel::Normal,
+            min_interval_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Balance on one of the user's addresses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DustCandidate {
+    pub wallet_id: String,
+    pub address: String,
+    pub balance: Amount,
+}
+
+/// Why a dust balance was left in place
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The network was busier than the policy allows
+    Congested { level: CongestionLevel },
+    /// The fee would take more than the policy's share of the balance
+    Uneconomical { fee: Amount },
+    /// The sweep was attempted and failed
+    Failed { error: String },
+}
+
+/// What to do with one dust balance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DustAction {
+    /// Send `amount` to the primary address paying `fee`
+    Sweep { amount: Amount, fee: Amount, standard_fee: Amount },
+    Skip(SkipReason),
+}
+
+/// A dust balance moved to the primary address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SweptDust {
+    pub address: String,
+    pub amount: Amount,
+    pub fee: Amount,
+    pub hash: String,
+}
+
+/// A dust balance left in place
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedDust {
+    pub address: String,
+    pub balance: Amount,
+    #[serde(flatten)]
+    pub reason: SkipReason,
+}
+
+/// Outcome of one consolidation run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsolidationReport {
+    pub run_at: u64,
+    pub primary_address: String,
+    pub swept: Vec<SweptDust>,
+    pub skipped: Vec<SkippedDust>,
+    /// Amount now spendable from the primary address
+    pub recovered: Amount,
+    pub fees_paid: Amount,
+    /// Standard-rate fees for the same sweeps, less the fees paid
+    pub fees_saved: Amount,
+}
+
+impl ConsolidationReport {
+    pub fn new(run_at: u64, primary_address: &str) -> Self {
+        Self {
+            run_at,
+            primary_address: primary_address.to_string(),
+            swept: Vec::new(),
+            skipped: Vec::new(),
+            recovered: 0,
+            fees_paid: 0,
+            fees_saved: 0,
+        }
+    }
+
+    /// Record a completed sweep planned with `standard_fee` as the alternative
+    pub fn record_sweep(&mut self, swept: SweptDust, standard_fee: Amount) {
+        self.recovered += swept.amount;
+        self.fees_paid += swept.fee;
+        self.fees_saved += standard_fee.saturating_sub(swept.fee);
+        self.swept.push(swept);
+    }
+
+    pub fn record_skip(&mut self, candidate: &DustCandidate, reason: SkipReason) {
+        self.skipped.push(SkippedDust {
+            address: candidate.address.clone(),
+            balance: candidate.balance,
+            reason,
+        });
+    }
+}
+
+/// Plans and remembers dust consolidation runs
+#[derive(Debug, Default)]
+pub struct DustConsolidator {
+    policy: DustPolicy,
+    last_run: Mutex<Option<u64>>,
+    reports: Mutex<Vec<ConsolidationReport>>,
+}
+
+impl DustConsolidator {
+    pub fn new(policy: DustPolicy) -> Self {
+        Self {
+            policy,
+            last_run: Mutex::new(None),
+            reports: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &DustPolicy {
+        &self.policy
+    }
+
+    /// Whether a background run is due at `now`
+    pub fn is_due(&self, now: u64) -> bool {
+        if !self.policy.enabled {
+            return false;
+        }
+        match *self.last_run.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(last_run) => now.saturating_sub(last_run) >= self.policy.min_interval_secs,
+            None => true,
+        }
+    }
+
+    /// Whether `candidate` holds dust; empty balances are not dust
+    pub fn is_dust(&self, candidate: &DustCandidate) -> bool {
+        candidate.balance > 0 && candidate.balance < self.policy.threshold
+    }
+
+    /// What to do with a dust balance under the current congestion
+    pub fn plan(&self, status: &CongestionStatus, candidate: &DustCandidate) -> DustAction {
+        if status.level > self.policy.max_congestion {
+            return DustAction::Skip(SkipReason::Congested { level: status.level });
+        }
+        let fee = status.suggested_fee(SendPriority::Low);
+        let economical = fee < candidate.balance
+            && (fee as u128) * 100 <= (candidate.balance as u128) * self.policy.max_fee_percent as u128;
+        if !economical {
+            return DustAction::Skip(SkipReason::Uneconomical { fee });
+        }
+        DustAction::Sweep {
+            amount: candidate.balance - fee,
+            fee,
+            standard_fee: status.suggested_fee(SendPriority::Normal),
+        }
+    }
+
+    /// Keep a finished run's report
+    pub fn record(&self, report: ConsolidationReport) {
+        *self.last_run.lock().unwrap_or_else(|e| e.into_inner()) = Some(report.run_at);
+        let mut reports = self.reports.lock().unwrap_or_else(|e| e.into_inner());
+        if reports.len() == MAX_REPORTS {
+            reports.remove(0);
+        }
+        reports.push(report);
+    }
+
+    /// Reports of recent runs, oldest first
+    pub fn reports(&self) -> Vec<ConsolidationReport> {
+        self.reports.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::congestion::{FeeRates, LatencyPercentiles};
+
+    fn status(level: CongestionLevel) -> CongestionStatus {
+        CongestionStatus {
+            level,
+            mempool_depth: 0,
+            mempool_capacity: 100,
+            confirmation_latency: LatencyPercentiles::default(),
+            target_confirmation_secs: 10,
+            // A plain transfer is 160 bytes: economy fee 16, standard 63
+            fee_rates: FeeRates { economy: 100, standard: 400, priority: 1000 },
+            timestamp: 0,
+        }
+    }
+
+    fn candidate(balance: Amount) -> DustCandidate {
+        DustCandidate { wallet_id: "w2".to_string(), address: "addr2".to_string(), balance }
+    }
+
+    #[test]
+    fn test_plan_sweeps_only_when_economical_and_quiet() {
+        let consolidator = DustConsolidator::new(DustPolicy::default());
+        let quiet = status(CongestionLevel::Normal);
+
+        assert_eq!(
+            consolidator.plan(&quiet, &candidate(500)),
+            DustAction::Sweep { amount: 484, fee: 16, standard_fee: 63 }
+        );
+        // 16 is more than 10% of 100
+        assert_eq!(consolidator.plan(&quiet, &candidate(100)), DustAction::Skip(SkipReason::Uneconomical { fee: 16 }));
+        assert_eq!(
+            consolidator.plan(&status(CongestionLevel::Elevated), &candidate(500)),
+            DustAction::Skip(SkipReason::Congested { level: CongestionLevel::Elevated })
+        );
+        assert!(!consolidator.is_dust(&candidate(0)));
+        assert!(!consolidator.is_dust(&candidate(10_000)));
+    }
+
+    #[test]
+    fn test_reports_track_savings_and_schedule() {
+        let consolidator = DustConsolidator::new(DustPolicy { min_interval_secs: 60, ..DustPolicy::default() });
+        assert!(consolidator.is_due(1_000));
+
+        let mut report = ConsolidationReport::new(1_000, "primary");
+        report.record_sweep(SweptDust { address: "a".to_string(), amount: 484, fee: 16, hash: "h1".to_string() }, 63);
+        report.record_sweep(SweptDust { address: "b".to_string(), amount: 984, fee: 16, hash: "h2".to_string() }, 63);
+        report.record_skip(&candidate(100), SkipReason::Uneconomical { fee: 16 });
+        assert_eq!((report.recovered, report.fees_paid, report.fees_saved), (1_468, 32, 94));
+
+        consolidator.record(report);
+        assert!(!consolidator.is_due(1_030));
+        assert!(consolidator.is_due(1_060));
+        assert_eq!(consolidator.reports().len(), 1);
+        assert!(!DustConsolidator::new(DustPolicy { enabled: false, ..DustPolicy::default() }).is_due(0));
+    }
+}
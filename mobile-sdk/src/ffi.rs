@@ -0,0 +1,454 @@
+//! Foreign function interface for Kotlin and Swift
+//!
+//! Exposes the main `QuantumDAGSDK` surface through UniFFI so that Android and
+//! iOS apps can use generated bindings instead of hand-written bridges.
+//! Blocking methods run on an SDK-owned Tokio runtime; the `*_async` variants
+//! return immediately and report through a callback interface.
+
+use std::sync::Arc;
+
+use crate::types::*;
+use crate::keystore::KeystoreBackend;
+use crate::paths::{PlatformPaths, StaticPlatformPaths};
+use crate::{parse_amount, Amount, NetworkConfig, NetworkType, Networked, QuantumDAGSDK, SDKConfig, SDKError, SDKResult};
+
+/// Errors surfaced to foreign callers
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum FfiError {
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("Wallet error: {0}")]
+    Wallet(String),
+    #[error("Crypto error: {0}")]
+    Crypto(String),
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl From<SDKError> for FfiError {
+    fn from(error: SDKError) -> Self {
+        match error {
+            SDKError::Network(msg) => FfiError::Network(msg),
+            SDKError::Wallet(msg) | SDKError::Auth(msg) => FfiError::Wallet(msg),
+            SDKError::Crypto(msg) => FfiError::Crypto(msg),
+            SDKError::Storage(msg) => FfiError::Storage(msg),
+            SDKError::Config(msg) | SDKError::Validation(msg) => FfiError::InvalidArgument(msg),
+            SDKError::Serialization(msg) | SDKError::Unknown(msg) => FfiError::Internal(msg),
+        }
+    }
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for FfiError {
+    fn from(error: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        FfiError::Internal(error.reason)
+    }
+}
+
+/// SDK configuration passed from the host app
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiConfig {
+    pub node_urls: Vec<String>,
+    pub ws_urls: Vec<String>,
+    /// One of `mainnet`, `testnet`, `devnet`, or a custom network name
+    pub network: String,
+    pub timeout_secs: u64,
+    pub database_path: Option<String>,
+    /// App sandbox directories; desktop data directories when absent
+    pub platform_paths: Option<FfiPlatformPaths>,
+}
+
+/// Sandbox directories reported by the host app
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiPlatformPaths {
+    /// Documents (iOS Application Support, Android `filesDir`) directory
+    pub documents_dir: String,
+    /// Caches (iOS Library/Caches, Android `cacheDir`) directory
+    pub cache_dir: String,
+    /// Directories earlier app releases stored SDK data in
+    pub legacy_dirs: Vec<String>,
+}
+
+impl From<FfiPlatformPaths> for StaticPlatformPaths {
+    fn from(paths: FfiPlatformPaths) -> Self {
+        Self {
+            documents_dir: paths.documents_dir.into(),
+            cache_dir: paths.cache_dir.into(),
+            legacy_dirs: paths.legacy_dirs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<FfiConfig> for SDKConfig {
+    fn from(config: FfiConfig) -> Self {
+        let network_type = match config.network.to_lowercase().as_str() {
+            "mainnet" => NetworkType::Mainnet,
+            "testnet" => NetworkType::Testnet,
+            "devnet" => NetworkType::Devnet,
+            _ => NetworkType::Custom(config.network),
+        };
+
+        let mut sdk_config = SDKConfig::default();
+        sdk_config.network = NetworkConfig {
+            node_urls: config.node_urls,
+            ws_urls: config.ws_urls,
+            network_type,
+            timeout_secs: config.timeout_secs,
+            ..NetworkConfig::default()
+        };
+        sdk_config.storage.database_path = config.database_path;
+        sdk_config
+    }
+}
+
+/// Wallet as seen by the host app
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiWallet {
+    pub id: String,
+    pub name: String,
+    pub address: String,
+    pub public_key: String,
+    pub created_at: i64,
+    /// Slug of the network the wallet belongs to
+    pub network: String,
+}
+
+impl From<Networked<Wallet>> for FfiWallet {
+    fn from(wallet: Networked<Wallet>) -> Self {
+        let network = wallet.network.slug();
+        let wallet = wallet.into_inner();
+        Self {
+            id: wallet.id,
+            name: wallet.name,
+            address: wallet.address,
+            public_key: wallet.public_key,
+            created_at: wallet.created_at.timestamp(),
+            network,
+        }
+    }
+}
+
+/// Transaction history entry
+///
+/// Amounts are decimal strings of base units, as uniffi has no `u128`.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiTransaction {
+    pub hash: String,
+    pub sender: String,
+    pub receiver: String,
+    pub amount: String,
+    pub fee: String,
+    pub timestamp: u64,
+    pub status: String,
+    pub confirmations: u32,
+}
+
+impl From<Transaction> for FfiTransaction {
+    fn from(tx: Transaction) -> Self {
+        Self {
+            hash: tx.hash,
+            sender: tx.sender,
+            receiver: tx.receiver,
+            amount: tx.amount.to_string(),
+            fee: tx.fee.to_string(),
+            timestamp: tx.timestamp,
+            status: format!("{:?}", tx.status),
+            confirmations: tx.confirmations,
+        }
+    }
+}
+
+/// One page of transaction history
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiTransactionPage {
+    pub items: Vec<FfiTransaction>,
+    pub total: u64,
+    pub page: u32,
+    pub total_pages: u32,
+}
+
+/// Blockchain event delivered to listeners; `data_json` is the raw payload
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct FfiEvent {
+    pub event_type: String,
+    pub data_json: String,
+    pub timestamp: i64,
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<String>,
+}
+
+impl From<&BlockchainEvent> for FfiEvent {
+    fn from(event: &BlockchainEvent) -> Self {
+        Self {
+            event_type: event.event_type.clone(),
+            data_json: event.data.to_string(),
+            timestamp: event.timestamp.timestamp(),
+            block_number: event.block_number,
+            transaction_hash: event.transaction_hash.clone(),
+        }
+    }
+}
+
+/// Listener for blockchain events, implemented in Kotlin or Swift
+#[uniffi::export(callback_interface)]
+pub trait FfiEventListener: Send + Sync {
+    fn on_event(&self, event: FfiEvent);
+}
+
+/// Completion callback for asynchronous wallet operations
+#[uniffi::export(callback_interface)]
+pub trait FfiWalletCallback: Send + Sync {
+    fn on_success(&self, wallet: FfiWallet);
+    fn on_error(&self, error: FfiError);
+}
+
+/// Completion callback for asynchronous transaction submission
+#[uniffi::export(callback_interface)]
+pub trait FfiSendCallback: Send + Sync {
+    fn on_success(&self, tx_hash: String);
+    fn on_error(&self, error: FfiError);
+}
+
+/// Completion callback for asynchronous balance queries
+#[uniffi::export(callback_interface)]
+pub trait FfiBalanceCallback: Send + Sync {
+    fn on_success(&self, balance: String);
+    fn on_error(&self, error: FfiError);
+}
+
+/// Platform keystore implemented in Kotlin (Android Keystore) or Swift (Secure Enclave)
+#[uniffi::export(callback_interface)]
+pub trait FfiKeystoreBackend: Send + Sync {
+    fn name(&self) -> String;
+    fn is_hardware_backed(&self) -> bool;
+    fn generate_ed25519_key(&self, alias: String, require_user_auth: bool) -> Result<Vec<u8>, FfiError>;
+    fn ed25519_public_key(&self, alias: String) -> Result<Option<Vec<u8>>, FfiError>;
+    fn sign_ed25519(&self, alias: String, data: Vec<u8>) -> Result<Vec<u8>, FfiError>;
+    fn delete_key(&self, alias: String) -> Result<(), FfiError>;
+}
+
+/// Adapts a foreign keystore to the SDK's `KeystoreBackend`
+struct ForeignKeystore(Box<dyn FfiKeystoreBackend>);
+
+impl KeystoreBackend for ForeignKeystore {
+    fn name(&self) -> String {
+        self.0.name()
+    }
+
+    fn is_hardware_backed(&self) -> bool {
+        self.0.is_hardware_backed()
+    }
+
+    fn generate_ed25519_key(&self, alias: &str, require_user_auth: bool) -> SDKResult<Vec<u8>> {
+        self.0.generate_ed25519_key(alias.to_string(), require_user_auth)
+            .map_err(|e| SDKError::Crypto(e.to_string()))
+    }
+
+    fn ed25519_public_key(&self, alias: &str) -> SDKResult<Option<Vec<u8>>> {
+        self.0.ed25519_public_key(alias.to_string())
+            .map_err(|e| SDKError::Crypto(e.to_string()))
+    }
+
+    fn sign_ed25519(&self, alias: &str, data: &[u8]) -> SDKResult<Vec<u8>> {
+        self.0.sign_ed25519(alias.to_string(), data.to_vec())
+            .map_err(|e| SDKError::Crypto(e.to_string()))
+    }
+
+    fn delete_key(&self, alias: &str) -> SDKResult<()> {
+        self.0.delete_key(alias.to_string())
+            .map_err(|e| SDKError::Crypto(e.to_string()))
+    }
+}
+
+/// SDK handle exported to foreign languages
+#[derive(uniffi::Object)]
+pub struct FfiSdk {
+    sdk: Arc<QuantumDAGSDK>,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[uniffi::export]
+impl FfiSdk {
+    /// Create a new SDK handle with its own runtime
+    #[uniffi::constructor]
+    pub fn new(config: FfiConfig) -> Result<Arc<Self>, FfiError> {
+        Self::build(config, None)
+    }
+
+    /// Create a new SDK handle whose wallet keys live in a platform keystore
+    #[uniffi::constructor]
+    pub fn with_keystore(config: FfiConfig, keystore: Box<dyn FfiKeystoreBackend>) -> Result<Arc<Self>, FfiError> {
+        Self::build(config, Some(Arc::new(ForeignKeystore(keystore))))
+    }
+
+    /// Create a wallet whose Ed25519 key is generated in the platform keystore
+    pub fn create_keystore_wallet(&self, passphrase: String) -> Result<FfiWallet, FfiError> {
+        let wallet = self.runtime.block_on(self.sdk.create_keystore_wallet(&passphrase))?;
+        Ok(wallet.into())
+    }
+
+    /// Create a new wallet
+    pub fn create_wallet(&self, passphrase: String) -> Result<FfiWallet, FfiError> {
+        let wallet = self.runtime.block_on(self.sdk.create_wallet(&passphrase))?;
+        Ok(wallet.into())
+    }
+
+    /// Import a wallet from its mnemonic
+    pub fn import_wallet(&self, mnemonic: String, passphrase: String) -> Result<FfiWallet, FfiError> {
+        let wallet = self.runtime.block_on(self.sdk.import_wallet(&mnemonic, &passphrase))?;
+        Ok(wallet.into())
+    }
+
+    /// Get the balance of an address, as a decimal string of base units
+    pub fn get_balance(&self, address: String) -> Result<String, FfiError> {
+        Ok(self.runtime.block_on(self.sdk.get_balance(&address))?.into_inner().to_string())
+    }
+
+    /// Send from the current wallet and return the transaction hash
+    ///
+    /// `amount` and `fee` are decimal strings of base units.
+    pub fn send_transaction(&self, to: String, amount: String, fee: Option<String>) -> Result<String, FfiError> {
+        let (amount, fee) = parse_send_amounts(&amount, fee.as_deref())?;
+        Ok(self.runtime.block_on(self.sdk.send_transaction(&to, amount, fee))?)
+    }
+
+    /// Get a page of transaction history for an address
+    pub fn get_transaction_history(
+        &self,
+        address: String,
+        page: u32,
+        per_page: u32,
+    ) -> Result<FfiTransactionPage, FfiError> {
+        let pagination = PaginationOptions::new(page, per_page);
+        let history = self.runtime.block_on(self.sdk.get_transaction_history(&address, &pagination))?.into_inner();
+
+        Ok(FfiTransactionPage {
+            items: history.items.into_iter().map(FfiTransaction::from).collect(),
+            total: history.total,
+            page: history.page,
+            total_pages: history.total_pages,
+        })
+    }
+
+    /// Register a listener for blockchain events
+    pub fn add_event_listener(&self, listener: Box<dyn FfiEventListener>) {
+        self.sdk.on_event(move |event| listener.on_event(event.into()));
+    }
+
+    /// Create a wallet without blocking the calling thread
+    pub fn create_wallet_async(&self, passphrase: String, callback: Box<dyn FfiWalletCallback>) {
+        let sdk = self.sdk.clone();
+        self.runtime.spawn(async move {
+            match sdk.create_wallet(&passphrase).await {
+                Ok(wallet) => callback.on_success(wallet.into()),
+                Err(e) => callback.on_error(e.into()),
+            }
+        });
+    }
+
+    /// Import a wallet without blocking the calling thread
+    pub fn import_wallet_async(&self, mnemonic: String, passphrase: String, callback: Box<dyn FfiWalletCallback>) {
+        let sdk = self.sdk.clone();
+        self.runtime.spawn(async move {
+            match sdk.import_wallet(&mnemonic, &passphrase).await {
+                Ok(wallet) => callback.on_success(wallet.into()),
+                Err(e) => callback.on_error(e.into()),
+            }
+        });
+    }
+
+    /// Query a balance without blocking the calling thread
+    pub fn get_balance_async(&self, address: String, callback: Box<dyn FfiBalanceCallback>) {
+        let sdk = self.sdk.clone();
+        self.runtime.spawn(async move {
+            match sdk.get_balance(&address).await {
+                Ok(balance) => callback.on_success(balance.into_inner().to_string()),
+                Err(e) => callback.on_error(e.into()),
+            }
+        });
+    }
+
+    /// Send a transaction without blocking the calling thread
+    pub fn send_transaction_async(&self, to: String, amount: String, fee: Option<String>, callback: Box<dyn FfiSendCallback>) {
+        let (amount, fee) = match parse_send_amounts(&amount, fee.as_deref()) {
+            Ok(amounts) => amounts,
+            Err(e) => return callback.on_error(e.into()),
+        };
+        let sdk = self.sdk.clone();
+        self.runtime.spawn(async move {
+            match sdk.send_transaction(&to, amount, fee).await {
+                Ok(hash) => callback.on_success(hash),
+                Err(e) => callback.on_error(e.into()),
+            }
+        });
+    }
+}
+
+/// Amount and optional fee of a send, from their decimal strings
+fn parse_send_amounts(amount: &str, fee: Option<&str>) -> SDKResult<(Amount, Option<Amount>)> {
+    Ok((parse_amount(amount)?, fee.map(parse_amount).transpose()?))
+}
+
+impl FfiSdk {
+    fn build(config: FfiConfig, keystore: Option<Arc<dyn KeystoreBackend>>) -> Result<Arc<Self>, FfiError> {
+        if config.node_urls.is_empty() {
+            return Err(FfiError::InvalidArgument("At least one node URL is required".to_string()));
+        }
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| FfiError::Internal(e.to_string()))?;
+        let platform_paths = config.platform_paths.clone()
+            .map(|paths| Arc::new(StaticPlatformPaths::from(paths)) as Arc<dyn PlatformPaths>);
+        let sdk = QuantumDAGSDK::build_with(config.into(), keystore, platform_paths)?;
+
+        Ok(Arc::new(Self {
+            sdk: Arc::new(sdk),
+            runtime,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> FfiConfig {
+        FfiConfig {
+            node_urls: vec!["https://testnet.quantum-dag.com".to_string()],
+            ws_urls: vec!["wss://testnet.quantum-dag.com/ws".to_string()],
+            network: "testnet".to_string(),
+            timeout_secs: 10,
+            database_path: None,
+            platform_paths: None,
+        }
+    }
+
+    #[test]
+    fn test_config_conversion() {
+        let config: SDKConfig = test_config().into();
+        assert_eq!(config.network.network_type, NetworkType::Testnet);
+        assert_eq!(config.network.timeout_secs, 10);
+
+        let custom: SDKConfig = FfiConfig { network: "staging".to_string(), ..test_config() }.into();
+        assert_eq!(custom.network.network_type, NetworkType::Custom("staging".to_string()));
+    }
+
+    #[test]
+    fn test_error_mapping() {
+        assert!(matches!(FfiError::from(SDKError::Network("x".into())), FfiError::Network(_)));
+        assert!(matches!(FfiError::from(SDKError::Validation("x".into())), FfiError::InvalidArgument(_)));
+        assert!(matches!(FfiError::from(SDKError::Unknown("x".into())), FfiError::Internal(_)));
+    }
+
+    #[test]
+    fn test_sdk_requires_node_url() {
+        let config = FfiConfig { node_urls: vec![], ..test_config() };
+        assert!(matches!(FfiSdk::new(config), Err(FfiError::InvalidArgument(_))));
+    }
+}
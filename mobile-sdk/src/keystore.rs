@@ -0,0 +1,230 @@
+//! Platform keystore integration for wallet keys
+//!
+//! The Ed25519 half of a wallet's hybrid key can live inside Android Keystore
+//! or the iOS Secure Enclave, where it is generated and never exported. The
+//! Dilithium3 half is generated in software and stored encrypted by the
+//! wallet manager, since platform keystores do not support PQC algorithms yet.
+//! Hybrid signatures use the node's layout: the 64-byte Ed25519 signature
+//! followed by the Dilithium3 signature.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+use pqcrypto_dilithium::dilithium3;
+use pqcrypto_traits::sign::{DetachedSignature, PublicKey as _, SecretKey as _};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::{SDKError, SDKResult};
+
+/// Length of an Ed25519 public key
+pub const CLASSICAL_PUBLIC_KEY_LEN: usize = 32;
+/// Length of an Ed25519 signature
+pub const CLASSICAL_SIGNATURE_LEN: usize = 64;
+
+/// Platform key store holding the classical half of hybrid wallet keys
+///
+/// Implementations wrap Android Keystore, the iOS Secure Enclave, or the
+/// in-process `SoftwareKeystore` fallback.
+pub trait KeystoreBackend: Send + Sync {
+    /// Human-readable backend name
+    fn name(&self) -> String;
+
+    /// Whether keys are held in dedicated hardware
+    fn is_hardware_backed(&self) -> bool;
+
+    /// Generate an Ed25519 key under `alias` and return its public key
+    fn generate_ed25519_key(&self, alias: &str, require_user_auth: bool) -> SDKResult<Vec<u8>>;
+
+    /// Public key for `alias`, if the key exists
+    fn ed25519_public_key(&self, alias: &str) -> SDKResult<Option<Vec<u8>>>;
+
+    /// Sign `data` with the key under `alias`
+    fn sign_ed25519(&self, alias: &str, data: &[u8]) -> SDKResult<Vec<u8>>;
+
+    /// Delete the key under `alias`
+    fn delete_key(&self, alias: &str) -> SDKResult<()>;
+}
+
+/// In-memory keystore for platforms without a hardware-backed store and for tests
+#[derive(Default)]
+pub struct SoftwareKeystore {
+    keys: RwLock<HashMap<String, SigningKey>>,
+}
+
+impl SoftwareKeystore {
+    /// Create an empty software keystore
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeystoreBackend for SoftwareKeystore {
+    fn name(&self) -> String {
+        "software".to_string()
+    }
+
+    fn is_hardware_backed(&self) -> bool {
+        false
+    }
+
+    fn generate_ed25519_key(&self, alias: &str, _require_user_auth: bool) -> SDKResult<Vec<u8>> {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        let signing_key = SigningKey::from_bytes(&secret);
+        let public_key = signing_key.verifying_key().to_bytes().to_vec();
+
+        let mut keys = self.keys.write()
+            .map_err(|_| SDKError::Crypto("Keystore lock poisoned".to_string()))?;
+        if keys.contains_key(alias) {
+            return Err(SDKError::Crypto(format!("Key alias already exists: {}", alias)));
+        }
+        keys.insert(alias.to_string(), signing_key);
+        Ok(public_key)
+    }
+
+    fn ed25519_public_key(&self, alias: &str) -> SDKResult<Option<Vec<u8>>> {
+        let keys = self.keys.read()
+            .map_err(|_| SDKError::Crypto("Keystore lock poisoned".to_string()))?;
+        Ok(keys.get(alias).map(|k| k.verifying_key().to_bytes().to_vec()))
+    }
+
+    fn sign_ed25519(&self, alias: &str, data: &[u8]) -> SDKResult<Vec<u8>> {
+        let keys = self.keys.read()
+            .map_err(|_| SDKError::Crypto("Keystore lock poisoned".to_string()))?;
+        let key = keys.get(alias)
+            .ok_or_else(|| SDKError::Crypto(format!("Key not found: {}", alias)))?;
+        Ok(key.sign(data).to_bytes().to_vec())
+    }
+
+    fn delete_key(&self, alias: &str) -> SDKResult<()> {
+        let mut keys = self.keys.write()
+            .map_err(|_| SDKError::Crypto("Keystore lock poisoned".to_string()))?;
+        keys.remove(alias);
+        Ok(())
+    }
+}
+
+/// Key material produced when generating a hybrid wallet key
+pub struct HybridKeyMaterial {
+    pub alias: String,
+    pub classical_public_key: Vec<u8>,
+    pub pqc_public_key: Vec<u8>,
+    /// Dilithium3 secret key; the caller must encrypt it before storing
+    pub pqc_secret_key: Vec<u8>,
+}
+
+impl HybridKeyMaterial {
+    /// Combined public key in the node's hybrid layout
+    pub fn public_key(&self) -> Vec<u8> {
+        [self.classical_public_key.as_slice(), self.pqc_public_key.as_slice()].concat()
+    }
+}
+
+/// Signs with an enclave-held Ed25519 key and a software Dilithium3 key
+pub struct HybridSigner {
+    backend: Arc<dyn KeystoreBackend>,
+}
+
+impl HybridSigner {
+    /// Create a signer over a keystore backend
+    pub fn new(backend: Arc<dyn KeystoreBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Get the keystore backend
+    pub fn backend(&self) -> &Arc<dyn KeystoreBackend> {
+        &self.backend
+    }
+
+    /// Generate both halves of a hybrid key
+    pub fn generate(&self, alias: &str, require_user_auth: bool) -> SDKResult<HybridKeyMaterial> {
+        let classical_public_key = self.backend.generate_ed25519_key(alias, require_user_auth)?;
+        if classical_public_key.len() != CLASSICAL_PUBLIC_KEY_LEN {
+            self.backend.delete_key(alias)?;
+            return Err(SDKError::Crypto(format!(
+                "Keystore returned a {}-byte Ed25519 public key",
+                classical_public_key.len()
+            )));
+        }
+
+        let (pqc_public, pqc_secret) = dilithium3::keypair();
+        Ok(HybridKeyMaterial {
+            alias: alias.to_string(),
+            classical_public_key,
+            pqc_public_key: pqc_public.as_bytes().to_vec(),
+            pqc_secret_key: pqc_secret.as_bytes().to_vec(),
+        })
+    }
+
+    /// Produce a hybrid signature over `data`
+    pub fn sign(&self, alias: &str, pqc_secret_key: &[u8], data: &[u8]) -> SDKResult<Vec<u8>> {
+        let classical = self.backend.sign_ed25519(alias, data)?;
+        if classical.len() != CLASSICAL_SIGNATURE_LEN {
+            return Err(SDKError::Crypto("Keystore returned a malformed Ed25519 signature".to_string()));
+        }
+
+        let secret = dilithium3::SecretKey::from_bytes(pqc_secret_key)
+            .map_err(|e| SDKError::Crypto(e.to_string()))?;
+        let pqc = dilithium3::detached_sign(data, &secret);
+
+        Ok([classical.as_slice(), pqc.as_bytes()].concat())
+    }
+
+    /// Verify a hybrid signature against a combined public key
+    pub fn verify(data: &[u8], signature: &[u8], public_key: &[u8]) -> SDKResult<bool> {
+        if signature.len() <= CLASSICAL_SIGNATURE_LEN || public_key.len() <= CLASSICAL_PUBLIC_KEY_LEN {
+            return Ok(false);
+        }
+
+        let (classical_sig, pqc_sig) = signature.split_at(CLASSICAL_SIGNATURE_LEN);
+        let (classical_pk, pqc_pk) = public_key.split_at(CLASSICAL_PUBLIC_KEY_LEN);
+
+        let classical_pk: [u8; CLASSICAL_PUBLIC_KEY_LEN] = classical_pk.try_into()
+            .map_err(|_| SDKError::Crypto("Invalid Ed25519 public key".to_string()))?;
+        let verifying_key = VerifyingKey::from_bytes(&classical_pk)
+            .map_err(|e| SDKError::Crypto(e.to_string()))?;
+        let classical_sig = ed25519_dalek::Signature::from_slice(classical_sig)
+            .map_err(|e| SDKError::Crypto(e.to_string()))?;
+        if verifying_key.verify(data, &classical_sig).is_err() {
+            return Ok(false);
+        }
+
+        let pqc_pk = dilithium3::PublicKey::from_bytes(pqc_pk)
+            .map_err(|e| SDKError::Crypto(e.to_string()))?;
+        let pqc_sig = match dilithium3::DetachedSignature::from_bytes(pqc_sig) {
+            Ok(sig) => sig,
+            Err(_) => return Ok(false),
+        };
+        Ok(dilithium3::verify_detached_signature(&pqc_sig, data, &pqc_pk).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hybrid_sign_and_verify() {
+        let signer = HybridSigner::new(Arc::new(SoftwareKeystore::new()));
+        let key = signer.generate("wallet-1", false).unwrap();
+
+        let signature = signer.sign("wallet-1", &key.pqc_secret_key, b"payload").unwrap();
+        assert!(HybridSigner::verify(b"payload", &signature, &key.public_key()).unwrap());
+        assert!(!HybridSigner::verify(b"tampered", &signature, &key.public_key()).unwrap());
+    }
+
+    #[test]
+    fn test_classical_key_stays_in_backend() {
+        let backend = Arc::new(SoftwareKeystore::new());
+        let signer = HybridSigner::new(backend.clone());
+        let key = signer.generate("wallet-1", true).unwrap();
+
+        assert_eq!(backend.ed25519_public_key("wallet-1").unwrap(), Some(key.classical_public_key.clone()));
+        assert!(signer.generate("wallet-1", true).is_err());
+
+        backend.delete_key("wallet-1").unwrap();
+        assert!(signer.sign("wallet-1", &key.pqc_secret_key, b"payload").is_err());
+    }
+}
@@ -2,22 +2,59 @@
 //! 
 //! A comprehensive SDK for mobile applications to interact with the Quantum DAG Blockchain.
 
+pub mod amount;
+pub mod attestation;
 pub mod client;
 pub mod wallet;
 pub mod network;
+pub mod networked;
 pub mod storage;
+pub mod paths;
 pub mod crypto;
+pub mod keystore;
+pub mod compliance;
+pub mod congestion;
+pub mod dust;
+pub mod payments;
+pub mod policy;
+pub mod proof;
+pub mod statements;
+pub mod stealth;
+pub mod swaps;
+pub mod sync;
 pub mod types;
 pub mod utils;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 
+#[cfg(feature = "ffi")]
+uniffi::setup_scaffolding!();
+
+pub use amount::{parse_amount, Amount};
+pub use attestation::*;
 pub use client::*;
 pub use wallet::*;
 pub use network::*;
+pub use networked::*;
 pub use storage::*;
+pub use paths::*;
 pub use crypto::*;
+pub use keystore::*;
+pub use compliance::*;
+pub use congestion::*;
+pub use dust::*;
+pub use payments::*;
+pub use policy::*;
+pub use proof::*;
+pub use statements::*;
+pub use stealth::*;
+pub use swaps::*;
+pub use sync::*;
 pub use types::*;
 pub use utils::*;
 
+use std::sync::Arc;
+
 /// SDK Configuration
 #[derive(Debug, Clone)]
 pub struct SDKConfig {
@@ -73,7 +110,8 @@ impl Default for NetworkConfig {
 }
 
 /// Network types
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum NetworkType {
     Mainnet,
     Testnet,
@@ -81,6 +119,32 @@ pub enum NetworkType {
     Custom(String),
 }
 
+impl NetworkType {
+    /// Name of the network's storage namespace, also typed to confirm a switch to it
+    ///
+    /// Custom networks are prefixed so one named "mainnet" cannot share
+    /// mainnet's storage.
+    pub fn slug(&self) -> String {
+        match self {
+            NetworkType::Mainnet => "mainnet".to_string(),
+            NetworkType::Testnet => "testnet".to_string(),
+            NetworkType::Devnet => "devnet".to_string(),
+            NetworkType::Custom(name) => {
+                let name: String = name.to_lowercase().chars()
+                    .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+                    .collect();
+                format!("custom-{}", name)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for NetworkType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.slug())
+    }
+}
+
 /// Security configuration
 #[derive(Debug, Clone)]
 pub struct SecurityConfig {
@@ -124,7 +188,7 @@ pub struct StorageConfig {
     pub enable_cache: bool,
     /// Cache size in MB
     pub cache_size_mb: u64,
-    /// Database path
+    /// Data directory; relative paths are taken from the platform documents directory
     pub database_path: Option<String>,
     /// Enable backup
     pub enable_backup: bool,
@@ -237,6 +301,15 @@ impl From<std::io::Error> for SDKError {
 /// SDK Builder
 pub struct SDKBuilder {
     config: SDKConfig,
+    keystore: Option<Arc<dyn KeystoreBackend>>,
+    compliance: Option<Arc<dyn ComplianceProvider>>,
+    policy_key: Option<Vec<u8>>,
+    biometric: Option<Arc<dyn BiometricAuthenticator>>,
+    platform_paths: Option<Arc<dyn PlatformPaths>>,
+    prices: Option<Arc<dyn PriceProvider>>,
+    coin_decimals: u32,
+    congestion: CongestionPolicy,
+    dust: DustPolicy,
 }
 
 impl SDKBuilder {
@@ -244,6 +317,15 @@ impl SDKBuilder {
     pub fn new() -> Self {
         Self {
             config: SDKConfig::default(),
+            keystore: None,
+            compliance: None,
+            policy_key: None,
+            biometric: None,
+            platform_paths: None,
+            prices: None,
+            coin_decimals: 0,
+            congestion: CongestionPolicy::default(),
+            dust: DustPolicy::default(),
         }
     }
 
@@ -271,9 +353,81 @@ impl SDKBuilder {
         self
     }
 
+    /// Keep wallet signing keys in a platform keystore
+    pub fn keystore(mut self, keystore: Arc<dyn KeystoreBackend>) -> Self {
+        self.keystore = Some(keystore);
+        self
+    }
+
+    /// Screen outgoing transfers with a compliance provider
+    pub fn compliance(mut self, provider: Arc<dyn ComplianceProvider>) -> Self {
+        self.compliance = Some(provider);
+        self
+    }
+
+    /// Persist wallet spending policies encrypted under a 32-byte key
+    ///
+    /// Without a key, policies are kept in memory for the life of the SDK.
+    pub fn policy_key(mut self, key: Vec<u8>) -> Self {
+        self.policy_key = Some(key);
+        self
+    }
+
+    /// Confirm transfers above a policy's biometric threshold with a platform prompt
+    pub fn biometric_authenticator(mut self, authenticator: Arc<dyn BiometricAuthenticator>) -> Self {
+        self.biometric = Some(authenticator);
+        self
+    }
+
+    /// Keep storage inside the app sandbox directories reported by the platform
+    pub fn platform_paths(mut self, paths: Arc<dyn PlatformPaths>) -> Self {
+        self.platform_paths = Some(paths);
+        self
+    }
+
+    /// Value statement entries in fiat with historical prices
+    pub fn price_provider(mut self, prices: Arc<dyn PriceProvider>) -> Self {
+        self.prices = Some(prices);
+        self
+    }
+
+    /// Decimal places between base units and whole coins on statements
+    pub fn coin_decimals(mut self, decimals: u32) -> Self {
+        self.coin_decimals = decimals;
+        self
+    }
+
+    /// When gated sends warn, ask for a higher fee or wait for congestion to ease
+    pub fn congestion_policy(mut self, policy: CongestionPolicy) -> Self {
+        self.congestion = policy;
+        self
+    }
+
+    /// When background sync sweeps small balances into the current wallet
+    pub fn dust_policy(mut self, policy: DustPolicy) -> Self {
+        self.dust = policy;
+        self
+    }
+
     /// Build the SDK
     pub fn build(self) -> SDKResult<QuantumDAGSDK> {
-        QuantumDAGSDK::new(self.config)
+        let mut sdk = QuantumDAGSDK::build_with(self.config, self.keystore, self.platform_paths)?;
+        if let Some(provider) = self.compliance {
+            sdk.compliance = Arc::new(ComplianceScreener::new(provider));
+        }
+        if let Some(key) = self.policy_key {
+            // Shared by every network, since wallet IDs are unique across them
+            let path = sdk.storage.root_path().join("policies.enc");
+            sdk.policies = Arc::new(PolicyEngine::open(path, key, sdk.crypto.clone())?);
+        }
+        if let Some(authenticator) = self.biometric {
+            sdk.policies.set_authenticator(authenticator);
+        }
+        sdk.prices = self.prices;
+        sdk.coin_decimals = self.coin_decimals;
+        sdk.congestion = CongestionGate::new(self.congestion);
+        sdk.dust = DustConsolidator::new(self.dust);
+        Ok(sdk)
     }
 }
 
@@ -290,22 +444,41 @@ pub struct QuantumDAGSDK {
     wallet_manager: Arc<WalletManager>,
     storage: Arc<SecureStorage>,
     crypto: Arc<CryptoService>,
+    compliance: Arc<ComplianceScreener>,
+    payments: Arc<PaymentTracker>,
+    policies: Arc<PolicyEngine>,
+    prices: Option<Arc<dyn PriceProvider>>,
+    coin_decimals: u32,
+    congestion: CongestionGate,
+    dust: DustConsolidator,
+    keystore: Option<Arc<dyn KeystoreBackend>>,
+    platform_paths: Arc<dyn PlatformPaths>,
 }
 
 impl QuantumDAGSDK {
     /// Create new SDK instance
     pub fn new(config: SDKConfig) -> SDKResult<Self> {
-        // Initialize storage
-        let storage = Arc::new(SecureStorage::new(&config.storage)?);
-        
+        Self::build_with(config, None, None)
+    }
+
+    /// Create new SDK instance backed by a platform keystore
+    pub fn with_keystore(config: SDKConfig, keystore: Arc<dyn KeystoreBackend>) -> SDKResult<Self> {
+        Self::build_with(config, Some(keystore), None)
+    }
+
+    fn build_with(
+        config: SDKConfig,
+        keystore: Option<Arc<dyn KeystoreBackend>>,
+        platform_paths: Option<Arc<dyn PlatformPaths>>,
+    ) -> SDKResult<Self> {
+        let platform_paths = platform_paths.unwrap_or_else(|| Arc::new(DesktopPaths));
+
         // Initialize crypto service
         let crypto = Arc::new(CryptoService::new(&config.security)?);
-        
-        // Initialize network client
-        let client = Arc::new(MobileClient::new(&config.network, crypto.clone())?);
-        
-        // Initialize wallet manager
-        let wallet_manager = Arc::new(WalletManager::new(storage.clone(), crypto.clone())?);
+
+        // Initialize storage, network client and wallet manager for the configured network
+        let (storage, client, wallet_manager) =
+            Self::open_network(&config, &config.network, &crypto, keystore.as_ref(), platform_paths.as_ref())?;
         
         Ok(Self {
             config,
@@ -313,9 +486,80 @@ impl QuantumDAGSDK {
             wallet_manager,
             storage,
             crypto,
+            compliance: Arc::new(ComplianceScreener::default()),
+            payments: Arc::new(PaymentTracker::new()),
+            policies: Arc::new(PolicyEngine::in_memory(crypto.clone())),
+            prices: None,
+            coin_decimals: 0,
+            congestion: CongestionGate::default(),
+            dust: DustConsolidator::default(),
+            keystore,
+            platform_paths,
         })
     }
 
+    /// Open the storage namespace, client and wallets of `network`
+    fn open_network(
+        config: &SDKConfig,
+        network: &NetworkConfig,
+        crypto: &Arc<CryptoService>,
+        keystore: Option<&Arc<dyn KeystoreBackend>>,
+        platform_paths: &dyn PlatformPaths,
+    ) -> SDKResult<(Arc<SecureStorage>, Arc<MobileClient>, Arc<WalletManager>)> {
+        let storage = Arc::new(SecureStorage::for_network(&config.storage, platform_paths, &network.network_type)?);
+        let client = Arc::new(MobileClient::new(network, crypto.clone())?);
+        let wallet_manager = Arc::new(match keystore {
+            Some(keystore) => WalletManager::with_keystore(storage.clone(), crypto.clone(), keystore.clone())?,
+            None => WalletManager::new(storage.clone(), crypto.clone())?,
+        });
+        Ok((storage, client, wallet_manager))
+    }
+
+    /// Network the SDK is connected to
+    pub fn network(&self) -> &NetworkType {
+        &self.config.network.network_type
+    }
+
+    /// Switch to another network, confirmed by typing its slug
+    ///
+    /// `confirmation` must equal the target's `NetworkType::slug`, so a
+    /// switch, above all to mainnet, never happens by accident. The wallets,
+    /// history and cache of the current network stay in its namespace; the
+    /// target's are opened in their place. Payment requests, dust reports
+    /// and registered event and payment callbacks belong to the network they
+    /// were made on and are dropped. Spending policies are kept.
+    pub fn switch_network(&mut self, network: NetworkConfig, confirmation: &str) -> SDKResult<()> {
+        let target = network.network_type.slug();
+        if confirmation != target {
+            return Err(SDKError::Validation(format!(
+                "Type '{}' to confirm switching from {} to {}",
+                target, self.network(), target
+            )));
+        }
+
+        let (storage, client, wallet_manager) = Self::open_network(
+            &self.config,
+            &network,
+            &self.crypto,
+            self.keystore.as_ref(),
+            self.platform_paths.as_ref(),
+        )?;
+        log::info!("🔀 Switched network from {} to {}", self.network(), target);
+
+        self.storage = storage;
+        self.client = client;
+        self.wallet_manager = wallet_manager;
+        self.payments = Arc::new(PaymentTracker::new());
+        self.dust = DustConsolidator::new(self.dust.policy().clone());
+        self.config.network = network;
+        Ok(())
+    }
+
+    /// Label data read from the current network
+    fn label<T>(&self, data: T) -> Networked<T> {
+        Networked::new(self.network().clone(), data)
+    }
+
     /// Get SDK configuration
     pub fn config(&self) -> &SDKConfig {
         &self.config
@@ -342,58 +586,457 @@ impl QuantumDAGSDK {
     }
 
     /// Create new wallet
-    pub async fn create_wallet(&self, passphrase: &str) -> SDKResult<Wallet> {
-        self.wallet_manager.create_wallet(passphrase).await
+    pub async fn create_wallet(&self, passphrase: &str) -> SDKResult<Networked<Wallet>> {
+        Ok(self.label(self.wallet_manager.create_wallet(passphrase).await?))
     }
 
     /// Import wallet from mnemonic
-    pub async fn import_wallet(&self, mnemonic: &str, passphrase: &str) -> SDKResult<Wallet> {
-        self.wallet_manager.import_wallet(mnemonic, passphrase).await
+    pub async fn import_wallet(&self, mnemonic: &str, passphrase: &str) -> SDKResult<Networked<Wallet>> {
+        Ok(self.label(self.wallet_manager.import_wallet(mnemonic, passphrase).await?))
+    }
+
+    /// Create wallet with its classical key held in the platform keystore
+    pub async fn create_keystore_wallet(&self, passphrase: &str) -> SDKResult<Networked<Wallet>> {
+        let wallet = self.wallet_manager
+            .create_keystore_wallet(passphrase, self.config.security.enable_biometric)
+            .await?;
+        Ok(self.label(wallet))
     }
 
     /// Get current wallet
-    pub async fn get_current_wallet(&self) -> SDKResult<Option<Wallet>> {
-        self.wallet_manager.get_current_wallet().await
+    pub async fn get_current_wallet(&self) -> SDKResult<Option<Networked<Wallet>>> {
+        Ok(self.wallet_manager.get_current_wallet().await?.map(|wallet| self.label(wallet)))
+    }
+
+    /// List the wallets of the current network
+    pub async fn list_wallets(&self) -> SDKResult<Networked<Vec<Wallet>>> {
+        Ok(self.label(self.wallet_manager.list_wallets().await?))
     }
 
     /// Get wallet balance
-    pub async fn get_balance(&self, address: &str) -> SDKResult<u64> {
-        self.client.get_balance(address).await
+    pub async fn get_balance(&self, address: &str) -> SDKResult<Networked<Amount>> {
+        Ok(self.label(self.client.get_balance(address).await?))
     }
 
     /// Send transaction
     pub async fn send_transaction(
         &self,
         to: &str,
-        amount: u64,
-        fee: Option<u64>,
+        amount: Amount,
+        fee: Option<Amount>,
+    ) -> SDKResult<TransactionHash> {
+        self.send_with_metadata(to, amount, fee, None).await
+    }
+
+    /// Get the node's congestion report
+    pub async fn get_congestion_status(&self) -> SDKResult<CongestionStatus> {
+        self.client.get_congestion_status().await
+    }
+
+    /// Decide how a send of `fee` at `priority` should go under current congestion
+    pub async fn check_send(&self, priority: SendPriority, fee: Amount) -> SDKResult<SendDecision> {
+        let status = self.client.get_congestion_status().await?;
+        Ok(self.congestion.evaluate(&status, priority, fee))
+    }
+
+    /// Send a transaction, gated on network congestion
+    ///
+    /// Without a `fee`, the fee suggested for `priority` is paid. A fee below
+    /// the suggestion under congestion is returned as `FeeTooLow` without
+    /// sending. Low-priority sends wait while the network is congested,
+    /// rechecking until it eases or the policy's `max_defer_secs` runs out.
+    pub async fn send_with_priority(
+        &self,
+        to: &str,
+        amount: Amount,
+        fee: Option<Amount>,
+        priority: SendPriority,
+    ) -> SDKResult<GatedSend> {
+        let started = std::time::Instant::now();
+        loop {
+            let status = self.client.get_congestion_status().await?;
+            let fee = fee.unwrap_or_else(|| status.suggested_fee(priority));
+            match self.congestion.evaluate(&status, priority, fee) {
+                SendDecision::Defer { level, retry_after_secs } => {
+                    if started.elapsed().as_secs() + retry_after_secs > self.congestion.policy().max_defer_secs {
+                        return Ok(GatedSend::Deferred { level, retry_after_secs });
+                    }
+                    log::info!("Network congestion is {:?}, deferring low-priority send", level);
+                    tokio::time::sleep(self.congestion.recheck_interval()).await;
+                }
+                SendDecision::RaiseFee { offered_fee, suggested_fee, .. } => {
+                    return Ok(GatedSend::FeeTooLow { offered_fee, suggested_fee });
+                }
+                decision => {
+                    let warning = matches!(decision, SendDecision::Warn { .. }).then_some(decision);
+                    let hash = self.send_with_metadata(to, amount, Some(fee), None).await?;
+                    return Ok(GatedSend::Sent {
+                        hash,
+                        fee,
+                        warning,
+                        deferred_secs: started.elapsed().as_secs(),
+                    });
+                }
+            }
+        }
+    }
+
+    async fn send_with_metadata(
+        &self,
+        to: &str,
+        amount: Amount,
+        fee: Option<Amount>,
+        metadata: Option<std::collections::HashMap<String, serde_json::Value>>,
     ) -> SDKResult<TransactionHash> {
         let wallet = self.wallet_manager.get_current_wallet().await?
             .ok_or_else(|| SDKError::Wallet("No wallet loaded".to_string()))?;
+
+        // Screen addresses and enforce local policy before anything is signed
+        self.compliance.check(&wallet.address, to, amount)?;
         
-        let transaction = TransactionBuilder::new()
+        let mut builder = TransactionBuilder::new()
             .from_wallet(&wallet)
             .to(to)
             .amount(amount)
-            .fee(fee.unwrap_or(1000))
+            .fee(fee.unwrap_or(1000));
+        if let Some(metadata) = metadata {
+            builder = builder.metadata(metadata);
+        }
+        let transaction = builder.build()?;
+
+        let authorization = self.policies.authorize(&wallet.id, to, amount).await?;
+        let result = self.client.send_transaction(&transaction).await;
+        if result.is_err() {
+            self.policies.release(&authorization).await?;
+        }
+        result
+    }
+
+    /// Set the spending policy of the current wallet
+    pub async fn set_wallet_policy(&self, policy: WalletPolicy) -> SDKResult<()> {
+        let wallet = self.wallet_manager.get_current_wallet().await?
+            .ok_or_else(|| SDKError::Wallet("No wallet loaded".to_string()))?;
+
+        self.policies.set_policy(&wallet.id, policy).await
+    }
+
+    /// Get the spending policy of the current wallet
+    pub async fn get_wallet_policy(&self) -> SDKResult<Option<WalletPolicy>> {
+        let wallet = self.wallet_manager.get_current_wallet().await?
+            .ok_or_else(|| SDKError::Wallet("No wallet loaded".to_string()))?;
+
+        Ok(self.policies.policy(&wallet.id))
+    }
+
+    /// Amount the current wallet has sent today under its policy
+    pub async fn get_spent_today(&self) -> SDKResult<Amount> {
+        let wallet = self.wallet_manager.get_current_wallet().await?
+            .ok_or_else(|| SDKError::Wallet("No wallet loaded".to_string()))?;
+
+        Ok(self.policies.spent_today(&wallet.id))
+    }
+
+    /// Get compliance screening decisions for audit
+    pub fn compliance_records(&self) -> Vec<ComplianceRecord> {
+        self.compliance.records()
+    }
+
+    /// Create a payment request to the current wallet, returned with its shareable link
+    pub async fn create_payment_request(
+        &self,
+        amount: Amount,
+        expires_in_secs: i64,
+        memo: Option<String>,
+    ) -> SDKResult<PaymentRequest> {
+        let wallet = self.wallet_manager.get_current_wallet().await?
+            .ok_or_else(|| SDKError::Wallet("No wallet loaded".to_string()))?;
+
+        self.payments.create_request(&wallet.address, amount, chrono::Duration::seconds(expires_in_secs), memo)
+    }
+
+    /// Get a tracked payment request
+    pub fn get_payment_request(&self, id: &str) -> Option<PaymentRequest> {
+        self.payments.request(id)
+    }
+
+    /// Register a callback for payment request status changes
+    pub fn on_payment_status<F>(&self, callback: F)
+    where
+        F: Fn(&PaymentStatusChange) + Send + Sync + 'static,
+    {
+        self.payments.on_status_change(callback);
+    }
+
+    /// Match recent transactions against open payment requests and expire overdue ones
+    pub async fn refresh_payment_requests(&self) -> SDKResult<Vec<PaymentRequest>> {
+        let open = self.payments.open_requests();
+        let mut addresses: Vec<&str> = open.iter().map(|request| request.address.as_str()).collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+
+        for address in addresses {
+            let history = self.client.get_transaction_history(address, &PaginationOptions::new(1, 100)).await?;
+            for transaction in &history.items {
+                self.payments.record_transaction(transaction)?;
+            }
+        }
+        self.payments.expire_overdue();
+
+        Ok(open.iter().filter_map(|request| self.payments.request(&request.id)).collect())
+    }
+
+    /// Fetch a wallet's transactions from the node into its local history
+    pub async fn sync_wallet_history(&self, wallet_id: &str) -> SDKResult<usize> {
+        let wallet = self.wallet_manager.get_wallet(wallet_id).await?
+            .ok_or_else(|| SDKError::Wallet(format!("Wallet {} not found", wallet_id)))?;
+
+        let mut page = 1;
+        let mut fetched = 0;
+        loop {
+            let history = self.client.get_transaction_history(&wallet.address, &PaginationOptions::new(page, 100)).await?;
+            fetched += history.items.len();
+            self.storage.record_history(wallet_id, &history.items).await?;
+            if history.items.is_empty() || page >= history.total_pages {
+                break;
+            }
+            page += 1;
+        }
+
+        if self.dust.is_due(chrono::Utc::now().timestamp() as u64) {
+            if let Err(e) = self.consolidate_dust().await {
+                log::warn!("Dust consolidation failed: {}", e);
+            }
+        }
+
+        Ok(fetched)
+    }
+
+    /// Sweep dust from the other wallets into the current one where the fee is worth it
+    ///
+    /// Runs on its own schedule during `sync_wallet_history`; calling it
+    /// directly runs it now regardless of the policy's interval.
+    pub async fn consolidate_dust(&self) -> SDKResult<ConsolidationReport> {
+        let primary = self.wallet_manager.get_current_wallet().await?
+            .ok_or_else(|| SDKError::Wallet("No wallet loaded".to_string()))?;
+        let status = self.client.get_congestion_status().await?;
+        let mut report = ConsolidationReport::new(chrono::Utc::now().timestamp() as u64, &primary.address);
+
+        for wallet in self.wallet_manager.list_wallets().await? {
+            if wallet.id == primary.id || wallet.address == primary.address {
+                continue;
+            }
+            let candidate = DustCandidate {
+                balance: self.client.get_balance(&wallet.address).await?,
+                wallet_id: wallet.id.clone(),
+                address: wallet.address.clone(),
+            };
+            if !self.dust.is_dust(&candidate) {
+                continue;
+            }
+
+            let (amount, fee, standard_fee) = match self.dust.plan(&status, &candidate) {
+                DustAction::Sweep { amount, fee, standard_fee } => (amount, fee, standard_fee),
+                DustAction::Skip(reason) => {
+                    report.record_skip(&candidate, reason);
+                    continue;
+                }
+            };
+            match self.sweep_dust(&wallet, &primary.address, amount, fee).await {
+                Ok(hash) => report.record_sweep(SweptDust { address: candidate.address.clone(), amount, fee, hash }, standard_fee),
+                Err(e) => report.record_skip(&candidate, SkipReason::Failed { error: e.to_string() }),
+            }
+        }
+
+        if !report.swept.is_empty() {
+            log::info!(
+                "Consolidated {} dust balances: {} recovered, {} paid in fees, {} saved",
+                report.swept.len(), report.recovered, report.fees_paid, report.fees_saved
+            );
+        }
+        self.dust.record(report.clone());
+        Ok(report)
+    }
+
+    async fn sweep_dust(&self, wallet: &Wallet, to: &str, amount: Amount, fee: Amount) -> SDKResult<TransactionHash> {
+        let transaction = TransactionBuilder::new()
+            .from_wallet(wallet)
+            .to(to)
+            .amount(amount)
+            .fee(fee)
             .build()?;
-        
-        self.client.send_transaction(&transaction).await
+
+        let authorization = self.policies.authorize(&wallet.id, to, amount).await?;
+        let result = self.client.send_transaction(&transaction).await;
+        if result.is_err() {
+            self.policies.release(&authorization).await?;
+        }
+        result
+    }
+
+    /// Reports of recent dust consolidation runs, oldest first
+    pub fn dust_reports(&self) -> Vec<ConsolidationReport> {
+        self.dust.reports()
+    }
+
+    /// Start a swap with `counterparty`, locking the offered leg from the current wallet
+    ///
+    /// Returns the secret the swap is locked under; share its hashlock with
+    /// the counterparty and keep the preimage to claim once both legs are
+    /// locked.
+    pub async fn initiate_swap(
+        &self,
+        counterparty: &str,
+        terms: SwapTerms,
+        fee: Option<Amount>,
+    ) -> SDKResult<(SwapSecret, TransactionHash)> {
+        let secret = SwapSecret::generate();
+        let payload = SwapPayload::initiate(&secret.hashlock, terms);
+        let hash = self.send_with_metadata(counterparty, 0, fee, Some(payload.metadata()?)).await?;
+        Ok((secret, hash))
+    }
+
+    /// Lock the current wallet's leg of a swap it was named counterparty to
+    ///
+    /// Check the swap's terms with `get_swap` first; participating locks
+    /// exactly what the initiator asked for.
+    pub async fn participate_swap(&self, hashlock: &str, fee: Option<Amount>) -> SDKResult<TransactionHash> {
+        let swap = self.client.get_swap(hashlock).await?;
+        let payload = SwapPayload::SwapParticipate { hashlock: swap.hashlock.clone() };
+        self.send_with_metadata(&swap.initiator.party, 0, fee, Some(payload.metadata()?)).await
+    }
+
+    /// Settle both legs of a locked swap by revealing its secret
+    pub async fn claim_swap(&self, secret: &SwapSecret, fee: Option<Amount>) -> SDKResult<TransactionHash> {
+        let swap = self.client.get_swap(&secret.hashlock).await?;
+        let payload = SwapPayload::SwapClaim { hashlock: swap.hashlock.clone(), preimage: secret.preimage.clone() };
+        self.send_with_metadata(&swap.counterparty.party, 0, fee, Some(payload.metadata()?)).await
+    }
+
+    /// Return both legs of an expired swap to their owners
+    pub async fn refund_swap(&self, hashlock: &str, fee: Option<Amount>) -> SDKResult<TransactionHash> {
+        let swap = self.client.get_swap(hashlock).await?;
+        let payload = SwapPayload::SwapRefund { hashlock: swap.hashlock.clone() };
+        self.send_with_metadata(&swap.counterparty.party, 0, fee, Some(payload.metadata()?)).await
+    }
+
+    /// Get an atomic swap by its hashlock
+    pub async fn get_swap(&self, hashlock: &str) -> SDKResult<Networked<AtomicSwap>> {
+        Ok(self.label(self.client.get_swap(hashlock).await?))
+    }
+
+    /// Get the swaps the current wallet is party to, newest first
+    pub async fn get_swaps(&self) -> SDKResult<Networked<Vec<AtomicSwap>>> {
+        let wallet = self.wallet_manager.get_current_wallet().await?
+            .ok_or_else(|| SDKError::Wallet("No wallet loaded".to_string()))?;
+
+        Ok(self.label(self.client.get_swaps(&wallet.address).await?))
+    }
+
+    /// Wait for a swap to leave `status`, polling every `poll_interval`
+    ///
+    /// Returns the swap as soon as its status differs, or as it stands once
+    /// `timeout` has passed.
+    pub async fn monitor_swap(
+        &self,
+        hashlock: &str,
+        status: SwapStatus,
+        poll_interval: std::time::Duration,
+        timeout: std::time::Duration,
+    ) -> SDKResult<Networked<AtomicSwap>> {
+        let started = std::time::Instant::now();
+        loop {
+            let swap = self.client.get_swap(hashlock).await?;
+            if swap.status != status || started.elapsed() + poll_interval > timeout {
+                return Ok(self.label(swap));
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Statement of a wallet's confirmed transactions in `[from, to)` from its local history
+    pub async fn account_statement(
+        &self,
+        wallet_id: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> SDKResult<Networked<AccountStatement>> {
+        let wallet = self.wallet_manager.get_wallet(wallet_id).await?
+            .ok_or_else(|| SDKError::Wallet(format!("Wallet {} not found", wallet_id)))?;
+        let history = self.storage.get_history(wallet_id).await?;
+
+        let statement = AccountStatement::build(&wallet.address, &history, from, to, self.coin_decimals, self.prices.as_deref())?;
+        Ok(self.label(statement))
+    }
+
+    /// Export a wallet's statement for accounting software
+    pub async fn export_statement(
+        &self,
+        wallet_id: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+        format: StatementFormat,
+    ) -> SDKResult<String> {
+        Ok(self.account_statement(wallet_id, from, to).await?.data.render(format))
+    }
+
+    /// Pay a payment link, sending `amount` or else the full requested amount
+    pub async fn pay_payment_link(
+        &self,
+        link: &str,
+        amount: Option<Amount>,
+        fee: Option<Amount>,
+    ) -> SDKResult<TransactionHash> {
+        let link = PaymentLink::parse(link)?;
+        if link.is_expired() {
+            return Err(SDKError::Validation("Payment request has expired".to_string()));
+        }
+
+        self.send_with_metadata(&link.address, amount.unwrap_or(link.amount), fee, Some(link.metadata())).await
+    }
+
+    /// Request test funds for the current wallet from the node's faucet
+    ///
+    /// Only available when the SDK is configured for devnet or testnet.
+    pub async fn request_testnet_funds(&self, captcha_token: Option<&str>) -> SDKResult<FaucetGrant> {
+        if self.config.network.network_type == NetworkType::Mainnet {
+            return Err(SDKError::Config("Faucet is not available on mainnet".to_string()));
+        }
+        let wallet = self.wallet_manager.get_current_wallet().await?
+            .ok_or_else(|| SDKError::Wallet("No wallet loaded".to_string()))?;
+
+        self.client.request_faucet_funds(&wallet.address, captcha_token).await
     }
 
     /// Get transaction status
-    pub async fn get_transaction_status(&self, hash: &str) -> SDKResult<TransactionStatus> {
-        self.client.get_transaction_status(hash).await
+    pub async fn get_transaction_status(&self, hash: &str) -> SDKResult<Networked<TransactionStatus>> {
+        Ok(self.label(self.client.get_transaction_status(hash).await?))
+    }
+
+    /// Get transaction history for an address
+    pub async fn get_transaction_history(
+        &self,
+        address: &str,
+        pagination: &PaginationOptions,
+    ) -> SDKResult<Networked<PaginatedResponse<Transaction>>> {
+        Ok(self.label(self.client.get_transaction_history(address, pagination).await?))
+    }
+
+    /// Register a callback for blockchain events
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(&BlockchainEvent) + Send + Sync + 'static,
+    {
+        self.client.on_event(callback);
     }
 
     /// Get blockchain status
-    pub async fn get_blockchain_status(&self) -> SDKResult<BlockchainStatus> {
-        self.client.get_blockchain_status().await
+    pub async fn get_blockchain_status(&self) -> SDKResult<Networked<BlockchainStatus>> {
+        Ok(self.label(self.client.get_blockchain_status().await?))
     }
 
     /// Get network info
-    pub async fn get_network_info(&self) -> SDKResult<NetworkInfo> {
-        self.client.get_network_info().await
+    pub async fn get_network_info(&self) -> SDKResult<Networked<NetworkInfo>> {
+        Ok(self.label(self.client.get_network_info().await?))
     }
 
     /// Check node health
@@ -406,14 +1049,29 @@ impl QuantumDAGSDK {
         self.client.get_connected_peers().await
     }
 
+    /// Register a Bloom filter of watched addresses for filtered sync
+    pub async fn register_address_filter(&self, filter: &BloomFilter) -> SDKResult<String> {
+        self.client.register_address_filter(filter).await
+    }
+
+    /// Get transactions matching a registered filter
+    pub async fn get_filtered_transactions(&self, subscription_id: &str, from_segment: u64) -> SDKResult<FilteredSync> {
+        self.client.get_filtered_transactions(subscription_id, from_segment).await
+    }
+
+    /// Get the compact filter of a sealed segment
+    pub async fn get_filter_segment(&self, index: u64) -> SDKResult<FilterSegment> {
+        self.client.get_filter_segment(index).await
+    }
+
     /// Backup wallet
     pub async fn backup_wallet(&self, backup_path: &str) -> SDKResult<()> {
         self.wallet_manager.backup_wallet(backup_path).await
     }
 
     /// Restore wallet from backup
-    pub async fn restore_wallet(&self, backup_path: &str, passphrase: &str) -> SDKResult<Wallet> {
-        self.wallet_manager.restore_wallet(backup_path, passphrase).await
+    pub async fn restore_wallet(&self, backup_path: &str, passphrase: &str) -> SDKResult<Networked<Wallet>> {
+        Ok(self.label(self.wallet_manager.restore_wallet(backup_path, passphrase).await?))
     }
 
     /// Clear all data
@@ -447,6 +1105,33 @@ mod tests {
         assert!(sdk.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_switch_network_requires_confirmation() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let storage = StorageConfig {
+            database_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let testnet = NetworkConfig { network_type: NetworkType::Testnet, ..NetworkConfig::default() };
+        let mut sdk = SDKBuilder::new().network(testnet).storage(storage).build().unwrap();
+        sdk.create_wallet("passphrase").await.unwrap();
+
+        let mainnet = NetworkConfig::default();
+        assert!(sdk.switch_network(mainnet.clone(), "yes").is_err());
+        assert_eq!(sdk.network(), &NetworkType::Testnet);
+
+        sdk.switch_network(mainnet, "mainnet").unwrap();
+        assert_eq!(sdk.network(), &NetworkType::Mainnet);
+        assert!(sdk.get_current_wallet().await.unwrap().is_none());
+        assert!(sdk.list_wallets().await.unwrap().is_empty());
+
+        let testnet = NetworkConfig { network_type: NetworkType::Testnet, ..NetworkConfig::default() };
+        sdk.switch_network(testnet, "testnet").unwrap();
+        let wallet = sdk.get_current_wallet().await.unwrap().unwrap();
+        assert!(wallet.is_on(&NetworkType::Testnet));
+        assert_eq!(NetworkType::Custom("Staging Net".to_string()).slug(), "custom-staging_net");
+    }
+
     #[test]
     fn test_sdk_config_defaults() {
         let config = SDKConfig::default();
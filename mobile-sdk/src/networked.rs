@@ -0,0 +1,82 @@
+//! Network labels on returned data
+//!
+//! Wallets, balances and history read through the SDK are wrapped in
+//! `Networked`, naming the network they came from. An app that lets users
+//! switch between mainnet and a test network can check the label before
+//! showing or acting on data fetched before the switch.
+
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{NetworkType, SDKError, SDKResult};
+
+/// Data read from one network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Networked<T> {
+    pub network: NetworkType,
+    pub data: T,
+}
+
+impl<T> Networked<T> {
+    pub fn new(network: NetworkType, data: T) -> Self {
+        Self { network, data }
+    }
+
+    /// The data, without its label
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    /// Whether the data came from `network`
+    pub fn is_on(&self, network: &NetworkType) -> bool {
+        self.network == *network
+    }
+
+    /// The data, if it came from `network`
+    pub fn expect_on(&self, network: &NetworkType) -> SDKResult<&T> {
+        if !self.is_on(network) {
+            return Err(SDKError::Validation(format!(
+                "Data is from {}, not {}",
+                self.network, network
+            )));
+        }
+        Ok(&self.data)
+    }
+
+    /// Transform the data, keeping its label
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Networked<U> {
+        Networked { network: self.network, data: f(self.data) }
+    }
+}
+
+impl<T> Deref for Networked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Networked<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} on {}", self.data, self.network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_is_checked() {
+        let balance = Networked::new(NetworkType::Testnet, 42u64);
+        assert_eq!(*balance, 42);
+        assert_eq!(balance.expect_on(&NetworkType::Testnet).unwrap(), &42);
+        assert!(balance.expect_on(&NetworkType::Mainnet).is_err());
+        assert_eq!(balance.to_string(), "42 on testnet");
+
+        let json = serde_json::to_value(balance.map(|amount| amount * 2)).unwrap();
+        assert_eq!(json, serde_json::json!({"network": "testnet", "data": 84}));
+    }
+}
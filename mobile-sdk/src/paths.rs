@@ -0,0 +1,312 @@
+//! Platform-aware storage locations
+//!
+//! Mobile apps may only write inside their sandbox: the documents (or
+//! application support) directory for data that must survive, and the caches
+//! directory for data the OS is free to purge. The host app reports both
+//! through `PlatformPaths`. Data left at a location used by an earlier release
+//! of the app is moved into the resolved data directory on first open.
+//!
+//! Each network keeps its data under its own namespace, `networks/<slug>` in
+//! both directories, so wallets and caches of a testnet never mix with those
+//! of mainnet. Data written before namespacing is moved into the namespace of
+//! the first network opened.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::{StorageConfig, SDKResult, SDKError};
+
+/// Directory created under the platform directories for SDK data
+pub const SDK_DIR_NAME: &str = "quantum-dag-sdk";
+
+/// Directory under the data and cache directories holding per-network namespaces
+pub const NETWORKS_DIR_NAME: &str = "networks";
+
+/// Entries of the data directory that belong to a network
+const NETWORK_SCOPED_ENTRIES: &[&str] = &[
+    "wallets",
+    "history",
+    "backups",
+    "quarantine",
+    "current_wallet.txt",
+    crate::storage::MANIFEST_FILE,
+];
+
+/// Sandbox directories supplied by the host platform
+pub trait PlatformPaths: Send + Sync {
+    /// Human-readable platform name
+    fn name(&self) -> String;
+
+    /// Directory for data that must survive app updates and OS backups
+    fn documents_dir(&self) -> SDKResult<PathBuf>;
+
+    /// Directory for data the OS may purge under storage pressure
+    fn cache_dir(&self) -> SDKResult<PathBuf>;
+
+    /// Data directories used by earlier releases of the app, newest first
+    fn legacy_dirs(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Whether storage must stay inside the documents and cache directories
+    fn is_sandboxed(&self) -> bool {
+        true
+    }
+}
+
+/// User data and cache directories of desktop operating systems
+///
+/// Not sandboxed, so an explicit `database_path` may point anywhere.
+pub struct DesktopPaths;
+
+impl PlatformPaths for DesktopPaths {
+    fn name(&self) -> String {
+        "desktop".to_string()
+    }
+
+    fn documents_dir(&self) -> SDKResult<PathBuf> {
+        dirs::data_dir().ok_or_else(|| SDKError::Storage("Could not get data directory".to_string()))
+    }
+
+    fn cache_dir(&self) -> SDKResult<PathBuf> {
+        dirs::cache_dir().ok_or_else(|| SDKError::Storage("Could not get cache directory".to_string()))
+    }
+
+    fn is_sandboxed(&self) -> bool {
+        false
+    }
+}
+
+/// Sandbox directories reported once by the host app, e.g. over FFI
+#[derive(Debug, Clone)]
+pub struct StaticPlatformPaths {
+    pub documents_dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub legacy_dirs: Vec<PathBuf>,
+}
+
+impl PlatformPaths for StaticPlatformPaths {
+    fn name(&self) -> String {
+        "app sandbox".to_string()
+    }
+
+    fn documents_dir(&self) -> SDKResult<PathBuf> {
+        Ok(self.documents_dir.clone())
+    }
+
+    fn cache_dir(&self) -> SDKResult<PathBuf> {
+        Ok(self.cache_dir.clone())
+    }
+
+    fn legacy_dirs(&self) -> Vec<PathBuf> {
+        self.legacy_dirs.clone()
+    }
+}
+
+/// Resolved directories for SDK data and cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageLocation {
+    pub data_dir: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+/// Resolve where storage lives on `platform`
+///
+/// A relative `database_path` is taken from the documents directory. When a
+/// `database_path` is given the cache lives inside it, otherwise in the
+/// platform cache directory.
+pub fn resolve_storage_location(config: &StorageConfig, platform: &dyn PlatformPaths) -> SDKResult<StorageLocation> {
+    let documents_dir = platform.documents_dir()?;
+
+    let location = match config.database_path {
+        Some(ref path) => {
+            let path = Path::new(path);
+            if path.components().any(|c| c == Component::ParentDir) {
+                return Err(SDKError::Storage(format!("Storage path {} must not contain '..'", path.display())));
+            }
+            let data_dir = if path.is_relative() { documents_dir.join(path) } else { path.to_path_buf() };
+            StorageLocation { cache_dir: data_dir.join("cache"), data_dir }
+        }
+        None => StorageLocation {
+            data_dir: documents_dir.join(SDK_DIR_NAME),
+            cache_dir: platform.cache_dir()?.join(SDK_DIR_NAME),
+        },
+    };
+
+    if platform.is_sandboxed() {
+        let cache_root = platform.cache_dir()?;
+        if !location.data_dir.starts_with(&documents_dir) && !location.data_dir.starts_with(&cache_root) {
+            return Err(SDKError::Storage(format!(
+                "Storage path {} is outside the {} directories",
+                location.data_dir.display(),
+                platform.name()
+            )));
+        }
+    }
+
+    Ok(location)
+}
+
+impl StorageLocation {
+    /// Namespace of the network with `slug` inside this location
+    pub fn for_network(&self, slug: &str) -> StorageLocation {
+        StorageLocation {
+            data_dir: self.data_dir.join(NETWORKS_DIR_NAME).join(slug),
+            cache_dir: self.cache_dir.join(NETWORKS_DIR_NAME).join(slug),
+        }
+    }
+}
+
+/// Move data written before namespacing from `data_dir` into `network_dir`
+///
+/// Nothing moves once `network_dir` exists, so only the first network opened
+/// inherits the data. Returns whether anything moved.
+pub fn migrate_unscoped_data(data_dir: &Path, network_dir: &Path) -> SDKResult<bool> {
+    if network_dir.exists() || !has_sdk_data(data_dir) {
+        return Ok(false);
+    }
+
+    log::info!("📦 Moving SDK storage in {} into network namespace {}", data_dir.display(), network_dir.display());
+    std::fs::create_dir_all(network_dir)?;
+    for name in NETWORK_SCOPED_ENTRIES {
+        let from = data_dir.join(name);
+        if !from.exists() {
+            continue;
+        }
+        let to = network_dir.join(name);
+        if std::fs::rename(&from, &to).is_err() {
+            if from.is_dir() {
+                move_dir_contents(&from, &to)?;
+                std::fs::remove_dir(&from)?;
+            } else {
+                std::fs::copy(&from, &to)?;
+                std::fs::remove_file(&from)?;
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Move data from the first legacy directory holding any into `data_dir`
+///
+/// Nothing moves when `data_dir` already holds data. Returns the directory the
+/// data came from.
+pub fn migrate_legacy_data(data_dir: &Path, platform: &dyn PlatformPaths) -> SDKResult<Option<PathBuf>> {
+    if has_sdk_data(data_dir) {
+        return Ok(None);
+    }
+
+    for legacy_dir in platform.legacy_dirs() {
+        if legacy_dir == data_dir || !has_sdk_data(&legacy_dir) {
+            continue;
+        }
+
+        log::info!("📦 Migrating SDK storage from {} to {}", legacy_dir.display(), data_dir.display());
+        move_dir_contents(&legacy_dir, data_dir)?;
+        // Leaves the old directory behind if something else still lives there
+        let _ = std::fs::remove_dir(&legacy_dir);
+        return Ok(Some(legacy_dir));
+    }
+
+    Ok(None)
+}
+
+/// Whether `dir` holds SDK wallets or a storage manifest
+fn has_sdk_data(dir: &Path) -> bool {
+    dir.join("wallets").is_dir() || dir.join(crate::storage::MANIFEST_FILE).is_file()
+}
+
+/// Move every entry of `from` into `to`, copying when a rename crosses volumes
+fn move_dir_contents(from: &Path, to: &Path) -> SDKResult<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if std::fs::rename(entry.path(), &target).is_ok() {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            move_dir_contents(&entry.path(), &target)?;
+            std::fs::remove_dir(entry.path())?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sandbox(root: &Path) -> StaticPlatformPaths {
+        StaticPlatformPaths {
+            documents_dir: root.join("Documents"),
+            cache_dir: root.join("Library/Caches"),
+            legacy_dirs: vec![root.join("old-data")],
+        }
+    }
+
+    #[test]
+    fn test_resolve_inside_sandbox() {
+        let root = TempDir::new().unwrap();
+        let platform = sandbox(root.path());
+
+        let location = resolve_storage_location(&StorageConfig::default(), &platform).unwrap();
+        assert_eq!(location.data_dir, root.path().join("Documents").join(SDK_DIR_NAME));
+        assert_eq!(location.cache_dir, root.path().join("Library/Caches").join(SDK_DIR_NAME));
+
+        let relative = StorageConfig { database_path: Some("wallet-data".to_string()), ..Default::default() };
+        let location = resolve_storage_location(&relative, &platform).unwrap();
+        assert_eq!(location.data_dir, root.path().join("Documents/wallet-data"));
+
+        for path in ["/var/mobile/elsewhere", "../escape"] {
+            let outside = StorageConfig { database_path: Some(path.to_string()), ..Default::default() };
+            assert!(resolve_storage_location(&outside, &platform).is_err());
+        }
+    }
+
+    #[test]
+    fn test_migrate_legacy_data() {
+        let root = TempDir::new().unwrap();
+        let platform = sandbox(root.path());
+        let legacy = root.path().join("old-data");
+        std::fs::create_dir_all(legacy.join("wallets")).unwrap();
+        std::fs::write(legacy.join("wallets/w1.wallet"), b"wallet").unwrap();
+        std::fs::write(legacy.join("current_wallet.txt"), b"w1").unwrap();
+
+        let data_dir = root.path().join("Documents").join(SDK_DIR_NAME);
+        assert_eq!(migrate_legacy_data(&data_dir, &platform).unwrap(), Some(legacy.clone()));
+        assert_eq!(std::fs::read(data_dir.join("wallets/w1.wallet")).unwrap(), b"wallet");
+        assert!(!legacy.exists());
+
+        // Already migrated
+        assert_eq!(migrate_legacy_data(&data_dir, &platform).unwrap(), None);
+    }
+
+    #[test]
+    fn test_unscoped_data_moves_into_first_network() {
+        let root = TempDir::new().unwrap();
+        let data_dir = root.path().join(SDK_DIR_NAME);
+        std::fs::create_dir_all(data_dir.join("wallets")).unwrap();
+        std::fs::write(data_dir.join("wallets/w1.wallet"), b"wallet").unwrap();
+        std::fs::write(data_dir.join("current_wallet.txt"), b"w1").unwrap();
+        std::fs::write(data_dir.join("policies.enc"), b"policies").unwrap();
+
+        let location = StorageLocation { data_dir: data_dir.clone(), cache_dir: root.path().join("cache") };
+        let testnet = location.for_network("testnet");
+        assert_eq!(testnet.data_dir, data_dir.join("networks/testnet"));
+        assert!(migrate_unscoped_data(&data_dir, &testnet.data_dir).unwrap());
+        assert_eq!(std::fs::read(testnet.data_dir.join("wallets/w1.wallet")).unwrap(), b"wallet");
+        assert!(testnet.data_dir.join("current_wallet.txt").is_file());
+        // Shared files stay at the root
+        assert!(data_dir.join("policies.enc").is_file());
+
+        // Later networks start empty
+        let mainnet = location.for_network("mainnet");
+        assert!(!migrate_unscoped_data(&data_dir, &mainnet.data_dir).unwrap());
+        assert!(!mainnet.data_dir.exists());
+    }
+}
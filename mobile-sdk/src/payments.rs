@@ -0,0 +1,431 @@
+//! Merchant payment requests
+//!
+//! A merchant creates a `PaymentRequest` for an amount with an expiry and
+//! shares it as a `qdag:` payment link. Payers send to the merchant address
+//! with the request id in the transaction metadata. `PaymentTracker` matches
+//! incoming transactions to requests, adds up partial payments and moves each
+//! request from pending to partial to paid, or to expired if the deadline
+//! passes first. Status changes are delivered to registered callbacks.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::types::{Address, Transaction, TransactionStatus};
+use crate::utils::EventBus;
+use crate::{Amount, SDKError, SDKResult};
+
+/// URI scheme of payment links
+pub const PAYMENT_LINK_SCHEME: &str = "qdag";
+/// Transaction metadata key carrying the payment request id
+pub const PAYMENT_REQUEST_METADATA_KEY: &str = "payment_request";
+
+/// Status of a payment request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    /// Nothing received yet
+    Pending,
+    /// Some but not all of the amount received
+    Partial,
+    /// Full amount received before the deadline
+    Paid,
+    /// Deadline passed before the full amount arrived
+    Expired,
+}
+
+impl PaymentStatus {
+    /// Whether the request can no longer change status
+    pub fn is_final(&self) -> bool {
+        matches!(self, PaymentStatus::Paid | PaymentStatus::Expired)
+    }
+}
+
+/// Payment received against a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceivedPayment {
+    pub transaction_id: String,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Request for payment to a merchant address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentRequest {
+    pub id: String,
+    pub address: Address,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub status: PaymentStatus,
+    pub payments: Vec<ReceivedPayment>,
+}
+
+impl PaymentRequest {
+    /// Create a pending request that expires after `ttl`
+    pub fn new(address: &str, amount: Amount, ttl: Duration, memo: Option<String>) -> SDKResult<Self> {
+        if amount == 0 {
+            return Err(SDKError::Validation("Payment request amount must be positive".to_string()));
+        }
+        if ttl <= Duration::zero() {
+            return Err(SDKError::Validation("Payment request expiry must be in the future".to_string()));
+        }
+
+        let created_at = Utc::now();
+        Ok(Self {
+            id: Uuid::new_v4().to_string(),
+            address: address.to_string(),
+            amount,
+            memo,
+            created_at,
+            expires_at: created_at + ttl,
+            status: PaymentStatus::Pending,
+            payments: Vec::new(),
+        })
+    }
+
+    /// Total received, including late payments
+    pub fn received(&self) -> Amount {
+        self.payments.iter().map(|payment| payment.amount).sum()
+    }
+
+    /// Total received before the deadline
+    pub fn received_in_time(&self) -> Amount {
+        self.payments.iter()
+            .filter(|payment| payment.received_at <= self.expires_at)
+            .map(|payment| payment.amount)
+            .sum()
+    }
+
+    /// Amount still owed
+    pub fn outstanding(&self) -> Amount {
+        self.amount.saturating_sub(self.received_in_time())
+    }
+
+    /// Link to share with the payer
+    pub fn link(&self) -> String {
+        PaymentLink {
+            address: self.address.clone(),
+            amount: self.amount,
+            request_id: self.id.clone(),
+            expires_at: self.expires_at,
+            memo: self.memo.clone(),
+        }.to_string()
+    }
+
+    /// Status implied by the payments received so far at `now`
+    fn next_status(&self, now: DateTime<Utc>) -> PaymentStatus {
+        if self.status.is_final() {
+            return self.status;
+        }
+
+        let received = self.received_in_time();
+        if received >= self.amount {
+            PaymentStatus::Paid
+        } else if now > self.expires_at {
+            PaymentStatus::Expired
+        } else if received > 0 {
+            PaymentStatus::Partial
+        } else {
+            PaymentStatus::Pending
+        }
+    }
+}
+
+/// Parsed `qdag:` payment link
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentLink {
+    pub address: Address,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    pub request_id: String,
+    pub expires_at: DateTime<Utc>,
+    pub memo: Option<String>,
+}
+
+impl PaymentLink {
+    /// Parse a link of the form `qdag:<address>?amount=..&request=..&expires=..[&memo=..]`
+    pub fn parse(link: &str) -> SDKResult<Self> {
+        let invalid = |reason: &str| SDKError::Validation(format!("Invalid payment link: {}", reason));
+
+        let url = Url::parse(link).map_err(|e| invalid(&e.to_string()))?;
+        if url.scheme() != PAYMENT_LINK_SCHEME {
+            return Err(invalid("unknown scheme"));
+        }
+        let address = url.path().to_string();
+        if address.is_empty() {
+            return Err(invalid("missing address"));
+        }
+
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let amount = params.get("amount")
+            .and_then(|amount| amount.parse().ok())
+            .ok_or_else(|| invalid("missing or malformed amount"))?;
+        let request_id = params.get("request")
+            .cloned()
+            .ok_or_else(|| invalid("missing request id"))?;
+        let expires_at = params.get("expires")
+            .and_then(|expires| expires.parse().ok())
+            .and_then(|expires| Utc.timestamp_opt(expires, 0).single())
+            .ok_or_else(|| invalid("missing or malformed expiry"))?;
+
+        Ok(Self {
+            address,
+            amount,
+            request_id,
+            expires_at,
+            memo: params.get("memo").cloned(),
+        })
+    }
+
+    /// Whether the request behind the link has expired
+    pub fn is_expired(&self) -> bool {
+        Utc::now() > self.expires_at
+    }
+
+    /// Metadata a payer attaches so the merchant can match the payment
+    pub fn metadata(&self) -> HashMap<String, serde_json::Value> {
+        let mut metadata = HashMap::new();
+        metadata.insert(PAYMENT_REQUEST_METADATA_KEY.to_string(), serde_json::Value::String(self.request_id.clone()));
+        metadata
+    }
+}
+
+impl std::fmt::Display for PaymentLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut url = Url::parse(&format!("{}:{}", PAYMENT_LINK_SCHEME, self.address)).map_err(|_| std::fmt::Error)?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("amount", &self.amount.to_string());
+            query.append_pair("request", &self.request_id);
+            query.append_pair("expires", &self.expires_at.timestamp().to_string());
+            if let Some(memo) = &self.memo {
+                query.append_pair("memo", memo);
+            }
+        }
+        f.write_str(url.as_str())
+    }
+}
+
+/// Status transition delivered to payment callbacks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentStatusChange {
+    pub request_id: String,
+    pub previous: PaymentStatus,
+    pub status: PaymentStatus,
+    #[serde(with = "crate::amount")]
+    pub received: Amount,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+}
+
+/// Tracks payment requests and the payments made against them
+#[derive(Default)]
+pub struct PaymentTracker {
+    requests: RwLock<HashMap<String, PaymentRequest>>,
+    callbacks: RwLock<EventBus<PaymentStatusChange>>,
+}
+
+impl PaymentTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create and track a request for `amount` to `address`
+    pub fn create_request(&self, address: &str, amount: Amount, ttl: Duration, memo: Option<String>) -> SDKResult<PaymentRequest> {
+        let request = PaymentRequest::new(address, amount, ttl, memo)?;
+        self.requests.write()
+            .map_err(|_| SDKError::Unknown("Payment tracker lock poisoned".to_string()))?
+            .insert(request.id.clone(), request.clone());
+        Ok(request)
+    }
+
+    /// Get a tracked request
+    pub fn request(&self, id: &str) -> Option<PaymentRequest> {
+        self.requests.read().ok()?.get(id).cloned()
+    }
+
+    /// Requests that can still receive payments
+    pub fn open_requests(&self) -> Vec<PaymentRequest> {
+        self.requests.read()
+            .map(|requests| requests.values().filter(|r| !r.status.is_final()).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Register a callback for status changes
+    pub fn on_status_change<F>(&self, callback: F)
+    where
+        F: Fn(&PaymentStatusChange) + Send + Sync + 'static,
+    {
+        if let Ok(mut callbacks) = self.callbacks.write() {
+            callbacks.subscribe(callback);
+        }
+    }
+
+    /// Apply an incoming transaction, returning the status of the request it pays
+    ///
+    /// Transactions without a known request id, sent to another address,
+    /// failed or already recorded are ignored. Payments arriving after the
+    /// deadline are recorded so they can be refunded but do not count.
+    pub fn record_transaction(&self, transaction: &Transaction) -> SDKResult<Option<PaymentStatus>> {
+        if matches!(transaction.status, TransactionStatus::Failed | TransactionStatus::Rejected) {
+            return Ok(None);
+        }
+        let Some(request_id) = transaction.metadata.as_ref()
+            .and_then(|metadata| metadata.get(PAYMENT_REQUEST_METADATA_KEY))
+            .and_then(|value| value.as_str())
+        else {
+            return Ok(None);
+        };
+
+        let change = {
+            let mut requests = self.requests.write()
+                .map_err(|_| SDKError::Unknown("Payment tracker lock poisoned".to_string()))?;
+            let Some(request) = requests.get_mut(request_id) else {
+                return Ok(None);
+            };
+            if request.address != transaction.receiver {
+                return Ok(None);
+            }
+            if request.payments.iter().any(|payment| payment.transaction_id == transaction.id) {
+                return Ok(Some(request.status));
+            }
+
+            request.payments.push(ReceivedPayment {
+                transaction_id: transaction.id.clone(),
+                amount: transaction.amount,
+                received_at: Utc.timestamp_opt(transaction.timestamp as i64, 0).single().unwrap_or_else(Utc::now),
+            });
+            Self::advance(request, Utc::now())
+        };
+
+        let status = change.as_ref().map(|change| change.status);
+        if let Some(change) = change {
+            self.publish(change);
+        }
+        Ok(status.or_else(|| self.request(request_id).map(|request| request.status)))
+    }
+
+    /// Expire requests whose deadline has passed, returning their ids
+    pub fn expire_overdue(&self) -> Vec<String> {
+        let changes: Vec<PaymentStatusChange> = match self.requests.write() {
+            Ok(mut requests) => requests.values_mut()
+                .filter_map(|request| Self::advance(request, Utc::now()))
+                .collect(),
+            Err(_) => return Vec::new(),
+        };
+
+        let mut expired = Vec::new();
+        for change in changes {
+            if change.status == PaymentStatus::Expired {
+                expired.push(change.request_id.clone());
+            }
+            self.publish(change);
+        }
+        expired
+    }
+
+    fn advance(request: &mut PaymentRequest, now: DateTime<Utc>) -> Option<PaymentStatusChange> {
+        let status = request.next_status(now);
+        if status == request.status {
+            return None;
+        }
+
+        let previous = request.status;
+        request.status = status;
+        Some(PaymentStatusChange {
+            request_id: request.id.clone(),
+            previous,
+            status,
+            received: request.received_in_time(),
+            amount: request.amount,
+        })
+    }
+
+    fn publish(&self, change: PaymentStatusChange) {
+        log::info!("Payment request {} is now {:?}", change.request_id, change.status);
+        if let Ok(callbacks) = self.callbacks.read() {
+            callbacks.publish(change);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn payment(request: &PaymentRequest, id: &str, amount: Amount) -> Transaction {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "hash": id,
+            "sender": "qdag_payer",
+            "receiver": request.address,
+            "amount": amount,
+            "fee": 1000,
+            "nonce": 0,
+            "timestamp": Utc::now().timestamp(),
+            "signature": "",
+            "quantum_proof": { "prime_hash": [], "resistance_score": 0, "proof_timestamp": 0 },
+            "status": "Confirmed",
+            "block_hash": null,
+            "confirmations": 1,
+            "metadata": { PAYMENT_REQUEST_METADATA_KEY: request.id },
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_link_round_trip() {
+        let request = PaymentRequest::new("qdag_shop", 2500, Duration::minutes(15), Some("order #42 & co".to_string())).unwrap();
+        let link = PaymentLink::parse(&request.link()).unwrap();
+
+        assert_eq!(link.address, "qdag_shop");
+        assert_eq!(link.amount, 2500);
+        assert_eq!(link.request_id, request.id);
+        assert_eq!(link.expires_at.timestamp(), request.expires_at.timestamp());
+        assert_eq!(link.memo.as_deref(), Some("order #42 & co"));
+        assert!(PaymentLink::parse("bitcoin:abc?amount=1").is_err());
+    }
+
+    #[test]
+    fn test_partial_then_paid() {
+        let tracker = PaymentTracker::new();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let seen = changes.clone();
+        tracker.on_status_change(move |change| seen.lock().unwrap().push(change.status));
+
+        let request = tracker.create_request("qdag_shop", 1000, Duration::minutes(15), None).unwrap();
+        let first = payment(&request, "tx1", 400);
+
+        assert_eq!(tracker.record_transaction(&first).unwrap(), Some(PaymentStatus::Partial));
+        // Replaying the same transaction does not double count
+        assert_eq!(tracker.record_transaction(&first).unwrap(), Some(PaymentStatus::Partial));
+        assert_eq!(tracker.request(&request.id).unwrap().outstanding(), 600);
+
+        assert_eq!(tracker.record_transaction(&payment(&request, "tx2", 600)).unwrap(), Some(PaymentStatus::Paid));
+        assert_eq!(*changes.lock().unwrap(), vec![PaymentStatus::Partial, PaymentStatus::Paid]);
+        assert!(tracker.open_requests().is_empty());
+    }
+
+    #[test]
+    fn test_overdue_request_expires() {
+        let tracker = PaymentTracker::new();
+        let request = tracker.create_request("qdag_shop", 1000, Duration::seconds(1), None).unwrap();
+        tracker.requests.write().unwrap().get_mut(&request.id).unwrap().expires_at = Utc::now() - Duration::seconds(5);
+
+        assert_eq!(tracker.expire_overdue(), vec![request.id.clone()]);
+        assert_eq!(tracker.request(&request.id).unwrap().status, PaymentStatus::Expired);
+
+        // A late payment is recorded but does not revive the request
+        assert_eq!(tracker.record_transaction(&payment(&request, "late", 1000)).unwrap(), Some(PaymentStatus::Expired));
+        assert_eq!(tracker.request(&request.id).unwrap().received(), 1000);
+    }
+}
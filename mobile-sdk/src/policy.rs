@@ -0,0 +1,404 @@
+//! Local spending policies enforced before signing
+//!
+//! Each wallet can carry a `WalletPolicy`: a daily spending limit, a list of
+//! whitelisted destinations, a threshold above which the user must confirm
+//! with biometrics, and a cooling-off delay before a new payee can be paid.
+//! The SDK authorizes every outgoing transfer against the policy before the
+//! transaction is built and signed. Policies, known payees and daily spending
+//! are persisted encrypted and authenticated, so editing the file on disk
+//! makes it fail to load rather than loosening the limits.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{Amount, CryptoService, SDKError, SDKResult};
+
+/// Length of the HMAC-SHA256 tag appended to the encrypted policy file
+const POLICY_TAG_LEN: usize = 32;
+
+/// Spending policy for one wallet
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalletPolicy {
+    /// Most that may be sent per UTC day, fees excluded
+    pub daily_limit: Option<Amount>,
+    /// Destinations that are always known payees
+    pub whitelist: Vec<String>,
+    /// Refuse destinations that are not whitelisted
+    pub enforce_whitelist: bool,
+    /// Amounts above this require biometric confirmation
+    pub biometric_threshold: Option<Amount>,
+    /// Delay after a payee is first seen before it can be paid
+    pub payee_cooling_off_secs: u64,
+}
+
+impl WalletPolicy {
+    fn is_whitelisted(&self, address: &str) -> bool {
+        self.whitelist.iter().any(|entry| entry.eq_ignore_ascii_case(address))
+    }
+}
+
+/// Platform hook that asks the user to confirm with Face ID, Touch ID or fingerprint
+pub trait BiometricAuthenticator: Send + Sync {
+    /// Prompt the user; `Ok(false)` means the user cancelled or failed
+    fn authenticate(&self, reason: &str) -> SDKResult<bool>;
+}
+
+/// Authenticator that refuses every prompt, used until the app installs one
+#[derive(Debug, Default)]
+pub struct UnavailableBiometricAuthenticator;
+
+impl BiometricAuthenticator for UnavailableBiometricAuthenticator {
+    fn authenticate(&self, _reason: &str) -> SDKResult<bool> {
+        Ok(false)
+    }
+}
+
+/// Amount reserved against a wallet's daily limit by a successful authorization
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpendAuthorization {
+    pub wallet_id: String,
+    pub payee: String,
+    pub amount: Amount,
+    day: NaiveDate,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DailySpend {
+    day: Option<NaiveDate>,
+    spent: Amount,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PolicyState {
+    policies: HashMap<String, WalletPolicy>,
+    /// First time each payee was seen, per wallet
+    payees: HashMap<String, HashMap<String, DateTime<Utc>>>,
+    spending: HashMap<String, DailySpend>,
+}
+
+/// Evaluates and persists wallet policies
+pub struct PolicyEngine {
+    state: RwLock<PolicyState>,
+    authenticator: RwLock<Arc<dyn BiometricAuthenticator>>,
+    crypto: Arc<CryptoService>,
+    /// Encrypted policy file and its 32-byte key; `None` keeps policies in memory
+    store: Option<(PathBuf, Vec<u8>)>,
+}
+
+impl PolicyEngine {
+    /// Create an engine that keeps policies in memory only
+    pub fn in_memory(crypto: Arc<CryptoService>) -> Self {
+        Self {
+            state: RwLock::new(PolicyState::default()),
+            authenticator: RwLock::new(Arc::new(UnavailableBiometricAuthenticator)),
+            crypto,
+            store: None,
+        }
+    }
+
+    /// Open an engine persisted encrypted at `path` under a 32-byte `key`
+    pub fn open(path: PathBuf, key: Vec<u8>, crypto: Arc<CryptoService>) -> SDKResult<Self> {
+        if key.len() != 32 {
+            return Err(SDKError::Crypto("Policy key must be 32 bytes".to_string()));
+        }
+
+        let state = if path.exists() {
+            let sealed = std::fs::read(&path)?;
+            Self::unseal(&crypto, &key, &sealed)?
+        } else {
+            PolicyState::default()
+        };
+
+        Ok(Self {
+            state: RwLock::new(state),
+            authenticator: RwLock::new(Arc::new(UnavailableBiometricAuthenticator)),
+            crypto,
+            store: Some((path, key)),
+        })
+    }
+
+    /// Install the platform biometric prompt
+    pub fn set_authenticator(&self, authenticator: Arc<dyn BiometricAuthenticator>) {
+        *self.authenticator.write().unwrap_or_else(|e| e.into_inner()) = authenticator;
+    }
+
+    /// Policy for a wallet, if one is set
+    pub fn policy(&self, wallet_id: &str) -> Option<WalletPolicy> {
+        self.read_state().policies.get(wallet_id).cloned()
+    }
+
+    /// Set a wallet's policy; newly whitelisted addresses start their cooling-off now
+    pub async fn set_policy(&self, wallet_id: &str, policy: WalletPolicy) -> SDKResult<()> {
+        {
+            let mut state = self.write_state();
+            let now = Utc::now();
+            let payees = state.payees.entry(wallet_id.to_string()).or_default();
+            for address in &policy.whitelist {
+                payees.entry(address.to_lowercase()).or_insert(now);
+            }
+            state.policies.insert(wallet_id.to_string(), policy);
+        }
+        self.save().await
+    }
+
+    /// Remove a wallet's policy and its tracked payees and spending
+    pub async fn remove_policy(&self, wallet_id: &str) -> SDKResult<()> {
+        {
+            let mut state = self.write_state();
+            state.policies.remove(wallet_id);
+            state.payees.remove(wallet_id);
+            state.spending.remove(wallet_id);
+        }
+        self.save().await
+    }
+
+    /// Amount already sent or reserved today
+    pub fn spent_today(&self, wallet_id: &str) -> Amount {
+        let today = Utc::now().date_naive();
+        self.read_state()
+            .spending
+            .get(wallet_id)
+            .filter(|spend| spend.day == Some(today))
+            .map(|spend| spend.spent)
+            .unwrap_or(0)
+    }
+
+    /// Check a transfer against the wallet's policy and reserve it against the daily limit
+    ///
+    /// Wallets without a policy are always authorized. A successful
+    /// authorization must be followed by `release` if the transfer is not sent.
+    pub async fn authorize(&self, wallet_id: &str, to: &str, amount: Amount) -> SDKResult<SpendAuthorization> {
+        let now = Utc::now();
+        let today = now.date_naive();
+        let payee = to.to_lowercase();
+        let authorization = SpendAuthorization {
+            wallet_id: wallet_id.to_string(),
+            payee: payee.clone(),
+            amount,
+            day: today,
+        };
+
+        let Some(policy) = self.policy(wallet_id) else {
+            return Ok(authorization);
+        };
+
+        if policy.enforce_whitelist && !policy.is_whitelisted(to) {
+            return Err(SDKError::Validation(format!("Destination {} is not whitelisted", to)));
+        }
+
+        // Remember the payee on first sight so its cooling-off starts even if this transfer is refused
+        let (first_seen, new_payee) = {
+            let mut state = self.write_state();
+            let payees = state.payees.entry(wallet_id.to_string()).or_default();
+            let new_payee = !payees.contains_key(&payee);
+            (*payees.entry(payee.clone()).or_insert(now), new_payee)
+        };
+        if new_payee {
+            self.save().await?;
+        }
+
+        let cooling_off = chrono::Duration::seconds(policy.payee_cooling_off_secs as i64);
+        if now < first_seen + cooling_off {
+            let remaining = (first_seen + cooling_off - now).num_seconds().max(1);
+            return Err(SDKError::Validation(format!(
+                "Payee {} is in its cooling-off period for another {}s",
+                to, remaining
+            )));
+        }
+
+        if let Some(limit) = policy.daily_limit {
+            let spent = self.spent_today(wallet_id);
+            if spent.saturating_add(amount) > limit {
+                return Err(SDKError::Validation(format!(
+                    "Daily spending limit of {} exceeded ({} already spent today)",
+                    limit, spent
+                )));
+            }
+        }
+
+        if policy.biometric_threshold.is_some_and(|threshold| amount > threshold) {
+            let authenticator = self.authenticator.read().unwrap_or_else(|e| e.into_inner()).clone();
+            if !authenticator.authenticate(&format!("Confirm sending {} to {}", amount, to))? {
+                return Err(SDKError::Auth("Biometric confirmation required".to_string()));
+            }
+        }
+
+        {
+            let mut state = self.write_state();
+            // Re-check under the write lock so concurrent sends cannot both fit under the limit
+            let spend = state.spending.entry(wallet_id.to_string()).or_default();
+            if spend.day != Some(today) {
+                *spend = DailySpend { day: Some(today), spent: 0 };
+            }
+            if let Some(limit) = policy.daily_limit {
+                if spend.spent.saturating_add(amount) > limit {
+                    return Err(SDKError::Validation(format!(
+                        "Daily spending limit of {} exceeded ({} already spent today)",
+                        limit, spend.spent
+                    )));
+                }
+            }
+            spend.spent = spend.spent.saturating_add(amount);
+        }
+        self.save().await?;
+
+        Ok(authorization)
+    }
+
+    /// Return a reservation for a transfer that was not sent
+    pub async fn release(&self, authorization: &SpendAuthorization) -> SDKResult<()> {
+        {
+            let mut state = self.write_state();
+            if let Some(spend) = state.spending.get_mut(&authorization.wallet_id) {
+                if spend.day == Some(authorization.day) {
+                    spend.spent = spend.spent.saturating_sub(authorization.amount);
+                }
+            }
+        }
+        self.save().await
+    }
+
+    async fn save(&self) -> SDKResult<()> {
+        let Some((path, key)) = &self.store else {
+            return Ok(());
+        };
+
+        let plaintext = serde_json::to_vec(&*self.read_state())?;
+        let sealed = Self::seal(&self.crypto, key, &plaintext)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, sealed).await?;
+        Ok(())
+    }
+
+    /// Encrypt-then-MAC with subkeys derived from `key`
+    fn seal(crypto: &CryptoService, key: &[u8], plaintext: &[u8]) -> SDKResult<Vec<u8>> {
+        let (encryption_key, mac_key) = Self::subkeys(crypto, key)?;
+        let mut sealed = crypto.encrypt(plaintext, &encryption_key)?;
+        let tag = crypto.generate_hmac(&sealed, &mac_key)?;
+        sealed.extend(tag);
+        Ok(sealed)
+    }
+
+    fn unseal(crypto: &CryptoService, key: &[u8], sealed: &[u8]) -> SDKResult<PolicyState> {
+        if sealed.len() < POLICY_TAG_LEN {
+            return Err(SDKError::Storage("Policy file is truncated".to_string()));
+        }
+
+        let (encryption_key, mac_key) = Self::subkeys(crypto, key)?;
+        let (ciphertext, tag) = sealed.split_at(sealed.len() - POLICY_TAG_LEN);
+        if !crypto.verify_hmac(ciphertext, tag, &mac_key)? {
+            return Err(SDKError::Storage("Policy file failed authentication".to_string()));
+        }
+
+        let plaintext = crypto.decrypt(ciphertext, &encryption_key)?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn subkeys(crypto: &CryptoService, key: &[u8]) -> SDKResult<(Vec<u8>, Vec<u8>)> {
+        Ok((
+            crypto.generate_hmac(b"qdag-policy-encryption", key)?,
+            crypto.generate_hmac(b"qdag-policy-authentication", key)?,
+        ))
+    }
+
+    fn read_state(&self) -> std::sync::RwLockReadGuard<'_, PolicyState> {
+        self.state.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_state(&self) -> std::sync::RwLockWriteGuard<'_, PolicyState> {
+        self.state.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SecurityConfig;
+
+    struct Approve(bool);
+
+    impl BiometricAuthenticator for Approve {
+        fn authenticate(&self, _reason: &str) -> SDKResult<bool> {
+            Ok(self.0)
+        }
+    }
+
+    fn crypto() -> Arc<CryptoService> {
+        Arc::new(CryptoService::new(&SecurityConfig::default()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_daily_limit_and_biometric_threshold() {
+        let engine = PolicyEngine::in_memory(crypto());
+        engine.set_policy("w1", WalletPolicy {
+            daily_limit: Some(1_000),
+            biometric_threshold: Some(500),
+            ..WalletPolicy::default()
+        }).await.unwrap();
+
+        assert!(matches!(engine.authorize("w1", "qdag_a", 600).await, Err(SDKError::Auth(_))));
+        engine.set_authenticator(Arc::new(Approve(true)));
+        let first = engine.authorize("w1", "qdag_a", 600).await.unwrap();
+        assert_eq!(engine.spent_today("w1"), 600);
+
+        assert!(matches!(engine.authorize("w1", "qdag_a", 500).await, Err(SDKError::Validation(_))));
+        engine.release(&first).await.unwrap();
+        engine.authorize("w1", "qdag_a", 500).await.unwrap();
+
+        // Wallets without a policy are unrestricted
+        engine.authorize("w2", "qdag_a", u64::MAX).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_whitelist_and_cooling_off() {
+        let engine = PolicyEngine::in_memory(crypto());
+        engine.set_policy("w1", WalletPolicy {
+            whitelist: vec!["QDAG_FRIEND".to_string()],
+            enforce_whitelist: true,
+            payee_cooling_off_secs: 3_600,
+            ..WalletPolicy::default()
+        }).await.unwrap();
+
+        assert!(engine.authorize("w1", "qdag_stranger", 10).await.is_err());
+        let err = engine.authorize("w1", "qdag_friend", 10).await.unwrap_err();
+        assert!(err.to_string().contains("cooling-off"));
+
+        // Whitelisting again keeps the original first-seen time
+        engine.set_policy("w1", WalletPolicy {
+            whitelist: vec!["qdag_friend".to_string()],
+            payee_cooling_off_secs: 0,
+            ..WalletPolicy::default()
+        }).await.unwrap();
+        engine.authorize("w1", "qdag_friend", 10).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_policies_persist_encrypted() {
+        let path = std::env::temp_dir().join(format!("qdag-policies-{}.enc", uuid::Uuid::new_v4()));
+        let key = vec![7u8; 32];
+        let policy = WalletPolicy { daily_limit: Some(42), ..WalletPolicy::default() };
+
+        let engine = PolicyEngine::open(path.clone(), key.clone(), crypto()).unwrap();
+        engine.set_policy("w1", policy.clone()).await.unwrap();
+
+        let sealed = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("daily_limit"));
+
+        let reopened = PolicyEngine::open(path.clone(), key.clone(), crypto()).unwrap();
+        assert_eq!(reopened.policy("w1"), Some(policy));
+
+        assert!(PolicyEngine::open(path.clone(), vec![8u8; 32], crypto()).is_err());
+        let mut tampered = sealed;
+        tampered[20] ^= 1;
+        std::fs::write(&path, tampered).unwrap();
+        assert!(PolicyEngine::open(path.clone(), key, crypto()).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+//! Transaction inclusion proofs
+//!
+//! Lets the client check that a transaction is part of the DAG without
+//! downloading it. Transaction IDs hash the IDs of their parents, so a path
+//! of transactions from the checked one up to a finalized checkpoint, each
+//! approving the previous, proves the checkpoint descends from it. The
+//! checkpoint ID must come from a source the app trusts. The ID hashing must
+//! stay byte-compatible with the node's `Transaction::compute_id` and
+//! `core::proof` module.
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::{amount, Amount, SDKError, SDKResult};
+
+/// Domain separator for transaction content hashes
+pub const TRANSACTION_ID_DOMAIN: &[u8] = b"qdag-tx-v1";
+
+/// ID-bearing fields of a transaction on a proof path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofLink {
+    /// Hex sender public key
+    pub sender: String,
+    /// Hex receiver public key
+    pub receiver: String,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    pub nonce: u64,
+    pub timestamp: u64,
+    /// Parent transaction IDs
+    pub parents: Vec<String>,
+    /// Hex metadata
+    pub metadata: Option<String>,
+}
+
+impl ProofLink {
+    /// Transaction ID of the link, as the node computes it
+    pub fn compute_id(&self) -> SDKResult<String> {
+        fn field(hasher: &mut Sha3_256, bytes: &[u8]) {
+            hasher.update((bytes.len() as u64).to_le_bytes());
+            hasher.update(bytes);
+        }
+
+        let mut hasher = Sha3_256::new();
+        hasher.update(TRANSACTION_ID_DOMAIN);
+        field(&mut hasher, &decode_hex("sender", &self.sender)?);
+        field(&mut hasher, &decode_hex("receiver", &self.receiver)?);
+        hasher.update(amount::digest_bytes(self.amount));
+        hasher.update(self.nonce.to_le_bytes());
+        hasher.update(self.timestamp.to_le_bytes());
+        hasher.update((self.parents.len() as u64).to_le_bytes());
+        for parent in &self.parents {
+            field(&mut hasher, &id_bytes(parent)?);
+        }
+        match &self.metadata {
+            Some(metadata) => {
+                hasher.update([1]);
+                field(&mut hasher, &decode_hex("metadata", metadata)?);
+            }
+            None => hasher.update([0]),
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+/// Proof that a transaction is an ancestor of a finalized checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub transaction_id: String,
+    pub checkpoint_id: String,
+    /// Descendants from a child of the transaction up to the checkpoint
+    pub path: Vec<ProofLink>,
+}
+
+/// Check that `proof` links `transaction_id` to the trusted `checkpoint_id`
+pub fn verify_inclusion_proof(proof: &InclusionProof, transaction_id: &str, checkpoint_id: &str) -> SDKResult<()> {
+    if !same_id(&proof.transaction_id, transaction_id) {
+        return Err(SDKError::Validation("Inclusion proof is for another transaction".to_string()));
+    }
+    if !same_id(&proof.checkpoint_id, checkpoint_id) {
+        return Err(SDKError::Validation("Inclusion proof is anchored at another checkpoint".to_string()));
+    }
+
+    let mut current = transaction_id.to_string();
+    for (index, link) in proof.path.iter().enumerate() {
+        if !link.parents.iter().any(|parent| same_id(parent, &current)) {
+            return Err(SDKError::Validation(format!("Inclusion proof link {} does not approve {}", index, current)));
+        }
+        current = link.compute_id()?;
+    }
+    if !same_id(&current, checkpoint_id) {
+        return Err(SDKError::Validation("Inclusion proof path does not end at the checkpoint".to_string()));
+    }
+    Ok(())
+}
+
+fn same_id(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+fn decode_hex(field: &str, value: &str) -> SDKResult<Vec<u8>> {
+    hex::decode(value).map_err(|_| SDKError::Validation(format!("Inclusion proof {} is not hex", field)))
+}
+
+/// Bytes hashed for a transaction ID: a 32-byte content hash, or the 16 bytes
+/// of a UUID assigned before content addressing
+fn id_bytes(id: &str) -> SDKResult<Vec<u8>> {
+    if id.len() == 64 {
+        if let Ok(bytes) = hex::decode(id) {
+            return Ok(bytes);
+        }
+    }
+    uuid::Uuid::parse_str(id)
+        .map(|uuid| uuid.as_bytes().to_vec())
+        .map_err(|_| SDKError::Validation(format!("Invalid transaction ID in inclusion proof: {}", id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(parents: Vec<String>, nonce: u64) -> ProofLink {
+        ProofLink {
+            sender: "0101".to_string(),
+            receiver: "0202".to_string(),
+            amount: 5,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents,
+            metadata: Some("ff".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_link_id_vector() {
+        // Shared with the node's core::proof tests
+        assert_eq!(
+            link(vec![hex::encode([3u8; 32])], 1).compute_id().unwrap(),
+            "a0ab21e27db69e323b7048d378dc579b1e99925916600d224d149617fb5ad9f6"
+        );
+    }
+
+    #[test]
+    fn test_verify_follows_path_to_checkpoint() {
+        let target = hex::encode([3u8; 32]);
+        let first = link(vec![target.clone()], 1);
+        let second = link(vec![first.compute_id().unwrap()], 2);
+        let checkpoint = second.compute_id().unwrap();
+        let proof = InclusionProof {
+            transaction_id: target.clone(),
+            checkpoint_id: checkpoint.clone(),
+            path: vec![first, second],
+        };
+        verify_inclusion_proof(&proof, &target, &checkpoint).unwrap();
+        assert!(verify_inclusion_proof(&proof, &target, &target).is_err());
+
+        let mut altered = proof.clone();
+        altered.path[0].amount = 6;
+        assert!(matches!(verify_inclusion_proof(&altered, &target, &checkpoint), Err(SDKError::Validation(_))));
+    }
+}
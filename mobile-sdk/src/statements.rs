@@ -0,0 +1,379 @@
+//! Account statements from local history
+//!
+//! Builds a statement of a wallet's confirmed transactions over a date range
+//! and renders it as CSV, OFX or QIF for accounting software. The running
+//! balance starts from the confirmed transactions before the range, so it is
+//! only as complete as the wallet's local history. Amounts are kept in base
+//! units and written with `decimals` places. With a price provider, every
+//! entry also carries its fiat value at the time of the transaction.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Transaction, TransactionStatus};
+use crate::{Amount, SDKError, SDKResult};
+
+/// Bank ID written to OFX statements
+const OFX_BANK_ID: &str = "QDAG";
+
+/// Longest account ID OFX allows
+const OFX_MAX_ACCOUNT_ID: usize = 22;
+
+/// Longest payee name OFX allows
+const OFX_MAX_NAME: usize = 32;
+
+/// Source of historical fiat prices
+pub trait PriceProvider: Send + Sync {
+    /// ISO 4217 code of the prices, e.g. "USD"
+    fn currency(&self) -> String;
+
+    /// Price of one whole coin at `timestamp`, if known
+    fn price_at(&self, timestamp: u64) -> SDKResult<Option<f64>>;
+}
+
+/// Statement file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatementFormat {
+    Csv,
+    Ofx,
+    Qif,
+}
+
+/// One transaction on a statement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatementEntry {
+    pub transaction_id: String,
+    pub timestamp: u64,
+    /// Other party of the transfer; the wallet itself for self-transfers
+    pub counterparty: String,
+    /// Amount received, or sent as a negative number, excluding the fee
+    pub amount: i128,
+    /// Fee paid by the wallet; 0 for incoming transfers
+    pub fee: Amount,
+    /// Balance after the transaction
+    pub balance: i128,
+    /// Fiat value of `amount - fee` at the time of the transaction
+    pub fiat_value: Option<f64>,
+}
+
+impl StatementEntry {
+    /// Change in balance: the amount less the fee
+    pub fn net(&self) -> i128 {
+        self.amount - self.fee as i128
+    }
+}
+
+/// Statement of one wallet over `[from, to)`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountStatement {
+    pub address: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Decimal places between base units and whole coins
+    pub decimals: u32,
+    pub opening_balance: i128,
+    pub closing_balance: i128,
+    /// Currency of the entries' fiat values, with a price provider
+    pub fiat_currency: Option<String>,
+    pub entries: Vec<StatementEntry>,
+}
+
+impl AccountStatement {
+    /// Build a statement for `address` from its local history
+    ///
+    /// Only confirmed transactions count; each ID is counted once.
+    pub fn build(
+        address: &str,
+        history: &[Transaction],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        decimals: u32,
+        prices: Option<&dyn PriceProvider>,
+    ) -> SDKResult<Self> {
+        if from >= to {
+            return Err(SDKError::Validation("Statement range is empty".to_string()));
+        }
+
+        let mut confirmed: Vec<&Transaction> = history.iter()
+            .filter(|transaction| transaction.status == TransactionStatus::Confirmed)
+            .filter(|transaction| transaction.sender == address || transaction.receiver == address)
+            .collect();
+        confirmed.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+        confirmed.dedup_by(|a, b| a.id == b.id);
+
+        let (from_secs, to_secs) = (from.timestamp().max(0) as u64, to.timestamp().max(0) as u64);
+        let mut balance = 0i128;
+        let mut opening_balance = 0i128;
+        let mut entries = Vec::new();
+        for transaction in confirmed {
+            if transaction.timestamp >= to_secs {
+                break;
+            }
+            let (amount, fee, counterparty) = effect(address, transaction);
+            balance += amount - fee as i128;
+            if transaction.timestamp < from_secs {
+                opening_balance = balance;
+                continue;
+            }
+
+            let fiat_value = match prices {
+                Some(prices) => prices.price_at(transaction.timestamp)?
+                    .map(|price| (amount - fee as i128) as f64 / 10f64.powi(decimals as i32) * price),
+                None => None,
+            };
+            entries.push(StatementEntry {
+                transaction_id: transaction.id.clone(),
+                timestamp: transaction.timestamp,
+                counterparty,
+                amount,
+                fee,
+                balance,
+                fiat_value,
+            });
+        }
+
+        Ok(Self {
+            address: address.to_string(),
+            from,
+            to,
+            decimals,
+            opening_balance,
+            closing_balance: balance,
+            fiat_currency: prices.map(|prices| prices.currency()),
+            entries,
+        })
+    }
+
+    /// Render the statement in `format`
+    pub fn render(&self, format: StatementFormat) -> String {
+        match format {
+            StatementFormat::Csv => self.to_csv(),
+            StatementFormat::Ofx => self.to_ofx(),
+            StatementFormat::Qif => self.to_qif(),
+        }
+    }
+
+    /// One row per transaction, with amount, fee, net change and running balance
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,transaction_id,type,counterparty,amount,fee,net,balance,fiat_value,fiat_currency\r\n");
+        for entry in &self.entries {
+            let fiat_value = entry.fiat_value.map(|value| format!("{:.2}", value)).unwrap_or_default();
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{}\r\n",
+                datetime(entry.timestamp).to_rfc3339(),
+                entry.transaction_id,
+                if entry.amount > 0 { "credit" } else { "debit" },
+                entry.counterparty,
+                self.units(entry.amount),
+                self.units(entry.fee as i128),
+                self.units(entry.net()),
+                self.units(entry.balance),
+                fiat_value,
+                self.fiat_currency.as_deref().unwrap_or_default(),
+            ));
+        }
+        csv
+    }
+
+    /// OFX 1.0.2 bank statement; fees are separate `FEE` transactions
+    pub fn to_ofx(&self) -> String {
+        let mut transactions = String::new();
+        for entry in &self.entries {
+            let posted = ofx_date(entry.timestamp);
+            let memo = self.memo(entry);
+            if entry.amount != 0 {
+                transactions.push_str(&format!(
+                    "<STMTTRN><TRNTYPE>{}<DTPOSTED>{}<TRNAMT>{}<FITID>{}<NAME>{}<MEMO>{}</STMTTRN>\n",
+                    if entry.amount > 0 { "CREDIT" } else { "DEBIT" },
+                    posted,
+                    self.units(entry.amount),
+                    entry.transaction_id,
+                    truncate(&entry.counterparty, OFX_MAX_NAME),
+                    memo,
+                ));
+            }
+            if entry.fee > 0 {
+                transactions.push_str(&format!(
+                    "<STMTTRN><TRNTYPE>FEE<DTPOSTED>{}<TRNAMT>{}<FITID>{}-fee<NAME>Network fee<MEMO>{}</STMTTRN>\n",
+                    posted,
+                    self.units(-(entry.fee as i128)),
+                    entry.transaction_id,
+                    memo,
+                ));
+            }
+        }
+
+        let now = ofx_date(Utc::now().timestamp().max(0) as u64);
+        let status = "<STATUS><CODE>0<SEVERITY>INFO</STATUS>";
+        format!(
+            "OFXHEADER:100\nDATA:OFXSGML\nVERSION:102\nSECURITY:NONE\nENCODING:USASCII\nCHARSET:1252\nCOMPRESSION:NONE\nOLDFILEUID:NONE\nNEWFILEUID:NONE\n\n\
+             <OFX>\n<SIGNONMSGSRSV1><SONRS>{status}<DTSERVER>{now}<LANGUAGE>ENG</SONRS></SIGNONMSGSRSV1>\n\
+             <BANKMSGSRSV1><STMTTRNRS><TRNUID>0{status}<STMTRS>\n\
+             <CURDEF>{currency}\n\
+             <BANKACCTFROM><BANKID>{bank}<ACCTID>{account}<ACCTTYPE>CHECKING</BANKACCTFROM>\n\
+             <BANKTRANLIST><DTSTART>{start}<DTEND>{end}\n{transactions}</BANKTRANLIST>\n\
+             <LEDGERBAL><BALAMT>{balance}<DTASOF>{end}</LEDGERBAL>\n\
+             </STMTRS></STMTTRNRS></BANKMSGSRSV1>\n</OFX>\n",
+            // XXX is the ISO 4217 code for "no currency"
+            currency = self.fiat_currency.as_deref().unwrap_or("XXX"),
+            bank = OFX_BANK_ID,
+            account = truncate(&self.address, OFX_MAX_ACCOUNT_ID),
+            start = ofx_date(self.from.timestamp().max(0) as u64),
+            end = ofx_date(self.to.timestamp().max(0) as u64),
+            balance = self.units(self.closing_balance),
+        )
+    }
+
+    /// QIF bank register; outgoing fees are split out under a `Fees` category
+    pub fn to_qif(&self) -> String {
+        let mut qif = String::from("!Type:Bank\n");
+        for entry in &self.entries {
+            qif.push_str(&format!("D{}\n", datetime(entry.timestamp).format("%m/%d/%Y")));
+            qif.push_str(&format!("T{}\n", self.units(entry.net())));
+            qif.push_str(&format!("P{}\n", entry.counterparty));
+            qif.push_str(&format!("M{}\n", self.memo(entry)));
+            if entry.fee > 0 {
+                qif.push_str(&format!("STransfers\n${}\n", self.units(entry.amount)));
+                qif.push_str(&format!("SFees\n${}\n", self.units(-(entry.fee as i128))));
+            }
+            qif.push_str("^\n");
+        }
+        qif
+    }
+
+    /// Base units written as whole coins with `decimals` places
+    fn units(&self, value: i128) -> String {
+        format_units(value, self.decimals)
+    }
+
+    fn memo(&self, entry: &StatementEntry) -> String {
+        match (entry.fiat_value, &self.fiat_currency) {
+            (Some(value), Some(currency)) => format!("{} ({:.2} {})", entry.transaction_id, value, currency),
+            _ => entry.transaction_id.clone(),
+        }
+    }
+}
+
+/// Signed amount, fee paid and counterparty of `transaction` for `address`
+fn effect(address: &str, transaction: &Transaction) -> (i128, Amount, String) {
+    let amount = transaction.amount as i128;
+    match (transaction.sender == address, transaction.receiver == address) {
+        (true, true) => (0, transaction.fee, address.to_string()),
+        (true, false) => (-amount, transaction.fee, transaction.receiver.clone()),
+        _ => (amount, 0, transaction.sender.clone()),
+    }
+}
+
+/// Format base units as a decimal with `decimals` places
+pub fn format_units(value: i128, decimals: u32) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let magnitude = value.unsigned_abs();
+    if decimals == 0 {
+        return format!("{}{}", sign, magnitude);
+    }
+    let scale = 10u128.pow(decimals);
+    format!("{}{}.{:0width$}", sign, magnitude / scale, magnitude % scale, width = decimals as usize)
+}
+
+fn datetime(timestamp: u64) -> DateTime<Utc> {
+    Utc.timestamp_opt(timestamp as i64, 0).single().unwrap_or_default()
+}
+
+fn ofx_date(timestamp: u64) -> String {
+    datetime(timestamp).format("%Y%m%d%H%M%S").to_string()
+}
+
+fn truncate(value: &str, max: usize) -> &str {
+    value.char_indices().nth(max).map(|(index, _)| &value[..index]).unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::QuantumProof;
+
+    struct FixedPrice;
+
+    impl PriceProvider for FixedPrice {
+        fn currency(&self) -> String {
+            "USD".to_string()
+        }
+
+        fn price_at(&self, _timestamp: u64) -> SDKResult<Option<f64>> {
+            Ok(Some(2.0))
+        }
+    }
+
+    fn transaction(id: &str, sender: &str, receiver: &str, amount: Amount, fee: Amount, timestamp: u64, status: TransactionStatus) -> Transaction {
+        Transaction {
+            id: id.to_string(),
+            hash: id.to_string(),
+            sender: sender.to_string(),
+            receiver: receiver.to_string(),
+            amount,
+            fee,
+            nonce: 0,
+            timestamp,
+            signature: String::new(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 0, proof_timestamp: 0 },
+            status,
+            block_hash: None,
+            confirmations: 1,
+            metadata: None,
+        }
+    }
+
+    fn history() -> Vec<Transaction> {
+        vec![
+            transaction("t1", "bob", "me", 1_000, 10, 100, TransactionStatus::Confirmed),
+            transaction("t2", "me", "carol", 300, 20, 200, TransactionStatus::Confirmed),
+            transaction("t3", "me", "carol", 500, 20, 250, TransactionStatus::Failed),
+            transaction("t4", "dave", "me", 50, 5, 300, TransactionStatus::Confirmed),
+            transaction("t5", "me", "erin", 10, 1, 400, TransactionStatus::Confirmed),
+        ]
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_running_balance_over_range() {
+        let statement = AccountStatement::build("me", &history(), at(150), at(400), 2, Some(&FixedPrice)).unwrap();
+        assert_eq!(statement.opening_balance, 1_000);
+        // t2 and t4 fall in the range; the failed t3 and the later t5 do not
+        let balances: Vec<i128> = statement.entries.iter().map(|entry| entry.balance).collect();
+        assert_eq!(balances, vec![680, 730]);
+        assert_eq!(statement.closing_balance, 730);
+        assert_eq!(statement.entries[0].net(), -320);
+        // 3.20 coins sent at 2 USD each
+        assert_eq!(statement.entries[0].fiat_value, Some(-6.4));
+
+        assert!(AccountStatement::build("me", &history(), at(400), at(400), 2, None).is_err());
+    }
+
+    #[test]
+    fn test_renders_fee_breakdown() {
+        let statement = AccountStatement::build("me", &history(), at(150), at(400), 2, Some(&FixedPrice)).unwrap();
+
+        let csv = statement.render(StatementFormat::Csv);
+        assert!(csv.contains(",t2,debit,carol,-3.00,0.20,-3.20,6.80,-6.40,USD\r\n"));
+
+        let ofx = statement.render(StatementFormat::Ofx);
+        assert!(ofx.contains("<TRNTYPE>DEBIT<DTPOSTED>19700101000320<TRNAMT>-3.00<FITID>t2"));
+        assert!(ofx.contains("<TRNTYPE>FEE<DTPOSTED>19700101000320<TRNAMT>-0.20<FITID>t2-fee"));
+        assert!(ofx.contains("<CURDEF>USD"));
+        assert!(ofx.contains("<BALAMT>7.30"));
+
+        let qif = statement.render(StatementFormat::Qif);
+        assert!(qif.starts_with("!Type:Bank\nD01/01/1970\nT-3.20\nPcarol\n"));
+        assert!(qif.contains("STransfers\n$-3.00\nSFees\n$-0.20\n^\n"));
+    }
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(-320, 2), "-3.20");
+        assert_eq!(format_units(5, 3), "0.005");
+        assert_eq!(format_units(42, 0), "42");
+    }
+}
@@ -0,0 +1,273 @@
+//! Stealth (one-time) receiving addresses
+//!
+//! A receiver shares a stealth address made of a scan public key and a spend
+//! public key. Senders derive a fresh one-time address for every payment and
+//! put the ephemeral key and a derivation proof in the transaction metadata,
+//! so payments to the same receiver cannot be linked on the ledger.
+//! `StealthKeys::scan` recognises payments with the scan key, and
+//! `one_time_secret` gives the key controlling a received payment.
+//!
+//! The derivation and proof must stay byte-compatible with the node's
+//! `core::stealth` module, which verifies the proof before accepting the
+//! transaction.
+
+use std::collections::HashMap;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_512};
+
+use crate::types::{Address, Transaction, TransactionStatus};
+use crate::{Amount, SDKError, SDKResult};
+
+/// Transaction metadata key carrying the stealth announcement
+pub const STEALTH_METADATA_KEY: &str = "stealth";
+
+const SHARED_SECRET_DOMAIN: &[u8] = b"qdag-stealth-shared-v1";
+const PROOF_DOMAIN: &[u8] = b"qdag-stealth-proof-v1";
+const SCAN_KEY_DOMAIN: &[u8] = b"qdag-stealth-scan-key-v1";
+const SPEND_KEY_DOMAIN: &[u8] = b"qdag-stealth-spend-key-v1";
+
+/// Published scan and spend public keys of a receiver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StealthAddress {
+    pub scan_public: [u8; 32],
+    pub spend_public: [u8; 32],
+}
+
+impl StealthAddress {
+    /// Parse the hex form: scan key followed by spend key
+    pub fn parse(encoded: &str) -> SDKResult<Self> {
+        let bytes = hex::decode(encoded.trim())
+            .map_err(|_| SDKError::Validation(format!("Invalid stealth address: {}", encoded)))?;
+        if bytes.len() != 64 {
+            return Err(SDKError::Validation(format!("Invalid stealth address: {}", encoded)));
+        }
+        let address = Self {
+            scan_public: bytes[..32].try_into().expect("checked length"),
+            spend_public: bytes[32..].try_into().expect("checked length"),
+        };
+        decompress(&address.scan_public)?;
+        decompress(&address.spend_public)?;
+        Ok(address)
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}{}", hex::encode(self.scan_public), hex::encode(self.spend_public))
+    }
+
+    /// Derive a one-time receiver address and the metadata announcing it
+    ///
+    /// Send to the returned address with the returned metadata merged into
+    /// the transaction metadata.
+    pub fn derive_payment(&self) -> SDKResult<(Address, HashMap<String, serde_json::Value>)> {
+        let r = random_scalar();
+        let ephemeral = RISTRETTO_BASEPOINT_POINT * r;
+        let shared = shared_scalar(&(decompress(&self.scan_public)? * r));
+        let one_time = (RISTRETTO_BASEPOINT_POINT * shared + decompress(&self.spend_public)?).compress().to_bytes();
+
+        let k = random_scalar();
+        let challenge = proof_challenge(&ephemeral, &one_time, &(RISTRETTO_BASEPOINT_POINT * k));
+        let response = k - challenge * r;
+        let mut proof = challenge.to_bytes().to_vec();
+        proof.extend_from_slice(response.as_bytes());
+
+        let announcement = StealthAnnouncement {
+            ephemeral: hex::encode(ephemeral.compress().as_bytes()),
+            proof: hex::encode(proof),
+        };
+        let mut metadata = HashMap::new();
+        metadata.insert(STEALTH_METADATA_KEY.to_string(), serde_json::to_value(&announcement)?);
+        Ok((hex::encode(one_time), metadata))
+    }
+}
+
+/// Ephemeral key and derivation proof published with a stealth payment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthAnnouncement {
+    /// Hex ephemeral public key
+    pub ephemeral: String,
+    /// Hex proof that the sender derived the receiver address
+    pub proof: String,
+}
+
+impl StealthAnnouncement {
+    /// Announcement carried by a transaction, if it is a stealth payment
+    pub fn from_transaction(transaction: &Transaction) -> Option<Self> {
+        let value = transaction.metadata.as_ref()?.get(STEALTH_METADATA_KEY)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+}
+
+/// Stealth payment found by scanning
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthPayment {
+    pub transaction_id: String,
+    /// One-time address the payment was sent to
+    pub address: Address,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+}
+
+/// Scan and spend secrets behind a stealth address
+#[derive(Clone)]
+pub struct StealthKeys {
+    scan_secret: Scalar,
+    spend_secret: Scalar,
+}
+
+impl std::fmt::Debug for StealthKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StealthKeys").field("address", &self.address().encode()).finish_non_exhaustive()
+    }
+}
+
+impl StealthKeys {
+    /// Fresh random keys
+    pub fn generate() -> Self {
+        Self {
+            scan_secret: random_scalar(),
+            spend_secret: random_scalar(),
+        }
+    }
+
+    /// Keys derived from a wallet seed, so the mnemonic recovers them
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self {
+            scan_secret: hash_to_scalar(&[SCAN_KEY_DOMAIN, seed]),
+            spend_secret: hash_to_scalar(&[SPEND_KEY_DOMAIN, seed]),
+        }
+    }
+
+    /// Stealth address to share with senders
+    pub fn address(&self) -> StealthAddress {
+        StealthAddress {
+            scan_public: (RISTRETTO_BASEPOINT_POINT * self.scan_secret).compress().to_bytes(),
+            spend_public: (RISTRETTO_BASEPOINT_POINT * self.spend_secret).compress().to_bytes(),
+        }
+    }
+
+    /// Payment to this receiver carried by `transaction`, if any
+    ///
+    /// Failed and rejected transactions are skipped.
+    pub fn scan(&self, transaction: &Transaction) -> Option<StealthPayment> {
+        if matches!(transaction.status, TransactionStatus::Failed | TransactionStatus::Rejected) {
+            return None;
+        }
+        let shared = self.shared_secret(transaction)?;
+        let expected = (RISTRETTO_BASEPOINT_POINT * shared + RISTRETTO_BASEPOINT_POINT * self.spend_secret).compress();
+        if hex::decode(&transaction.receiver).ok()? != expected.as_bytes() {
+            return None;
+        }
+        Some(StealthPayment {
+            transaction_id: transaction.id.clone(),
+            address: transaction.receiver.clone(),
+            amount: transaction.amount,
+        })
+    }
+
+    /// All payments to this receiver among `transactions`
+    pub fn scan_all<'a>(&self, transactions: impl IntoIterator<Item = &'a Transaction>) -> Vec<StealthPayment> {
+        transactions.into_iter().filter_map(|transaction| self.scan(transaction)).collect()
+    }
+
+    /// Secret key controlling the one-time address of a received payment
+    pub fn one_time_secret(&self, transaction: &Transaction) -> SDKResult<[u8; 32]> {
+        self.scan(transaction)
+            .ok_or_else(|| SDKError::Validation("Transaction is not a stealth payment to this wallet".to_string()))?;
+        let shared = self.shared_secret(transaction)
+            .ok_or_else(|| SDKError::Crypto("Missing stealth announcement".to_string()))?;
+        Ok((shared + self.spend_secret).to_bytes())
+    }
+
+    fn shared_secret(&self, transaction: &Transaction) -> Option<Scalar> {
+        let announcement = StealthAnnouncement::from_transaction(transaction)?;
+        let ephemeral: [u8; 32] = hex::decode(&announcement.ephemeral).ok()?.try_into().ok()?;
+        let ephemeral = decompress(&ephemeral).ok()?;
+        Some(shared_scalar(&(ephemeral * self.scan_secret)))
+    }
+}
+
+fn proof_challenge(ephemeral: &RistrettoPoint, receiver: &[u8], commitment: &RistrettoPoint) -> Scalar {
+    hash_to_scalar(&[PROOF_DOMAIN, ephemeral.compress().as_bytes(), receiver, commitment.compress().as_bytes()])
+}
+
+fn shared_scalar(shared_point: &RistrettoPoint) -> Scalar {
+    hash_to_scalar(&[SHARED_SECRET_DOMAIN, shared_point.compress().as_bytes()])
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha3_512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&hasher.finalize());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+fn random_scalar() -> Scalar {
+    let mut bytes = [0u8; 64];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+fn decompress(bytes: &[u8; 32]) -> SDKResult<RistrettoPoint> {
+    CompressedRistretto(*bytes)
+        .decompress()
+        .ok_or_else(|| SDKError::Crypto(format!("{} is not a valid point", hex::encode(bytes))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{QuantumProof, TransactionHash};
+
+    fn transaction(receiver: Address, metadata: HashMap<String, serde_json::Value>) -> Transaction {
+        Transaction {
+            id: "tx-1".to_string(),
+            hash: TransactionHash::default(),
+            sender: "aa".repeat(32),
+            receiver,
+            amount: 25,
+            fee: 1,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            signature: String::new(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            status: TransactionStatus::Confirmed,
+            block_hash: None,
+            confirmations: 1,
+            metadata: Some(metadata),
+        }
+    }
+
+    #[test]
+    fn test_receiver_finds_payment_and_controls_address() {
+        let keys = StealthKeys::from_seed(b"wallet seed");
+        let address = StealthAddress::parse(&keys.address().encode()).unwrap();
+        let (receiver, metadata) = address.derive_payment().unwrap();
+        let payment = transaction(receiver.clone(), metadata);
+
+        let found = keys.scan(&payment).unwrap();
+        assert_eq!(found.address, receiver);
+        assert_eq!(found.amount, 25);
+
+        let secret = Scalar::from_canonical_bytes(keys.one_time_secret(&payment).unwrap()).unwrap();
+        assert_eq!(hex::encode((RISTRETTO_BASEPOINT_POINT * secret).compress().as_bytes()), receiver);
+    }
+
+    #[test]
+    fn test_other_wallets_do_not_match() {
+        let keys = StealthKeys::generate();
+        let (receiver, metadata) = keys.address().derive_payment().unwrap();
+        let payments = vec![transaction(receiver, metadata), transaction("bb".repeat(32), HashMap::new())];
+
+        assert_eq!(keys.scan_all(&payments).len(), 1);
+        assert!(StealthKeys::generate().scan_all(&payments).is_empty());
+        assert!(StealthKeys::generate().one_time_secret(&payments[0]).is_err());
+    }
+}
@@ -9,40 +9,140 @@ use tokio::fs;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 
+use crate::types::Transaction;
 use crate::wallet::WalletData;
-use crate::{StorageConfig, SDKResult, SDKError};
+use crate::paths::{
+    migrate_legacy_data, migrate_unscoped_data, resolve_storage_location, DesktopPaths, PlatformPaths, StorageLocation,
+};
+use crate::{NetworkType, StorageConfig, SDKResult, SDKError};
+
+/// Manifest recording where storage lives and checksums of wallet files
+pub const MANIFEST_FILE: &str = "storage_manifest.json";
 
 /// Secure storage implementation
 pub struct SecureStorage {
     config: StorageConfig,
+    root_path: PathBuf,
     base_path: PathBuf,
+    cache_path: PathBuf,
+    network: Option<NetworkType>,
     encryption_key: Option<Vec<u8>>,
+    manifest: tokio::sync::Mutex<StorageManifest>,
+    integrity: StorageIntegrityReport,
 }
 
 impl SecureStorage {
-    /// Create new secure storage
+    /// Create new secure storage in the desktop data directory
     pub fn new(config: &StorageConfig) -> SDKResult<Self> {
-        let base_path = if let Some(ref path) = config.database_path {
-            PathBuf::from(path)
-        } else {
-            // Use default path
-            let mut path = dirs::data_dir()
-                .ok_or_else(|| SDKError::Storage("Could not get data directory".to_string()))?;
-            path.push("quantum-dag-sdk");
-            path
-        };
+        Self::with_platform(config, &DesktopPaths)
+    }
+
+    /// Create new secure storage in the sandbox directories of `platform`
+    ///
+    /// Moves data left at a legacy location, notices when the OS has
+    /// relocated the app container, and checks wallet files against the
+    /// checksums recorded before the move or an OS backup restore.
+    pub fn with_platform(config: &StorageConfig, platform: &dyn PlatformPaths) -> SDKResult<Self> {
+        let location = resolve_storage_location(config, platform)?;
+        let migrated_from = migrate_legacy_data(&location.data_dir, platform)?;
+        Self::open(config, location.data_dir.clone(), location, None, migrated_from)
+    }
+
+    /// Create storage namespaced to `network` in the sandbox directories of `platform`
+    ///
+    /// Wallets, history and cache entries of each network live apart, so a
+    /// wallet list or cached balance never crosses networks. Data written
+    /// before namespacing is moved into the first network opened.
+    pub fn for_network(config: &StorageConfig, platform: &dyn PlatformPaths, network: &NetworkType) -> SDKResult<Self> {
+        let location = resolve_storage_location(config, platform)?;
+        let mut migrated_from = migrate_legacy_data(&location.data_dir, platform)?;
+        let scoped = location.for_network(&network.slug());
+        if migrate_unscoped_data(&location.data_dir, &scoped.data_dir)? {
+            migrated_from = Some(location.data_dir.clone());
+        }
+        Self::open(config, location.data_dir, scoped, Some(network.clone()), migrated_from)
+    }
+
+    fn open(
+        config: &StorageConfig,
+        root_path: PathBuf,
+        location: StorageLocation,
+        network: Option<NetworkType>,
+        migrated_from: Option<PathBuf>,
+    ) -> SDKResult<Self> {
+        let migrated_from = migrated_from.map(|path| path.to_string_lossy().to_string());
         
         // Create directory if it doesn't exist
-        std::fs::create_dir_all(&base_path)
+        std::fs::create_dir_all(&location.data_dir)
             .map_err(|e| SDKError::Storage(e.to_string()))?;
+
+        let mut manifest = StorageManifest::load(&location.data_dir)?;
+        let current_dir = location.data_dir.to_string_lossy().to_string();
+        // Data that was migrated here records where it came from, which is not a relocation
+        let relocated_from = manifest.data_dir.take()
+            .filter(|previous| *previous != current_dir && Some(previous) != migrated_from.as_ref());
+        if let Some(ref previous) = relocated_from {
+            log::info!("📦 Storage container moved from {} to {}", previous, current_dir);
+        }
+        manifest.data_dir = Some(current_dir);
+
+        let mut integrity = check_integrity(&location.data_dir, &mut manifest)?;
+        integrity.migrated_from = migrated_from;
+        integrity.relocated_from = relocated_from;
+        if !integrity.is_clean() {
+            log::warn!("⚠️ Storage integrity check: {} missing, {} corrupted file(s)",
+                integrity.missing.len(), integrity.corrupted.len());
+        }
+        std::fs::write(location.data_dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?)?;
         
         Ok(Self {
             config: config.clone(),
-            base_path,
+            root_path,
+            base_path: location.data_dir,
+            cache_path: location.cache_dir,
+            network,
             encryption_key: None,
+            manifest: tokio::sync::Mutex::new(manifest),
+            integrity,
         })
     }
 
+    /// Directory holding the SDK's files, inside the network namespace if any
+    pub fn base_path(&self) -> &std::path::Path {
+        &self.base_path
+    }
+
+    /// Directory holding files shared by every network
+    pub fn root_path(&self) -> &std::path::Path {
+        &self.root_path
+    }
+
+    /// Network this storage is namespaced to
+    pub fn network(&self) -> Option<&NetworkType> {
+        self.network.as_ref()
+    }
+
+    /// Directory holding purgeable cache entries
+    pub fn cache_path(&self) -> &std::path::Path {
+        &self.cache_path
+    }
+
+    /// Result of the integrity check run when storage was opened
+    pub fn integrity_report(&self) -> &StorageIntegrityReport {
+        &self.integrity
+    }
+
+    /// Check wallet files against their recorded checksums again
+    ///
+    /// Call this when the platform reports that it restored app data from a
+    /// backup while the SDK was running.
+    pub async fn verify_integrity(&self) -> SDKResult<StorageIntegrityReport> {
+        let mut manifest = self.manifest.lock().await;
+        let report = check_integrity(&self.base_path, &mut manifest)?;
+        fs::write(self.base_path.join(MANIFEST_FILE), serde_json::to_vec_pretty(&*manifest)?).await?;
+        Ok(report)
+    }
+
     /// Initialize storage with encryption key
     pub fn with_encryption_key(mut self, key: Vec<u8>) -> Self {
         self.encryption_key = Some(key);
@@ -100,6 +200,13 @@ impl SecureStorage {
             fs::remove_file(&wallet_path).await
                 .map_err(|e| SDKError::Storage(e.to_string()))?;
         }
+        self.forget_file(&wallet_path).await?;
+
+        let history_path = self.get_history_path(wallet_id);
+        if self.file_exists(&history_path).await? {
+            fs::remove_file(&history_path).await
+                .map_err(|e| SDKError::Storage(e.to_string()))?;
+        }
         
         Ok(())
     }
@@ -169,6 +276,7 @@ impl SecureStorage {
             fs::remove_file(&current_wallet_path).await
                 .map_err(|e| SDKError::Storage(e.to_string()))?;
         }
+        self.forget_file(&current_wallet_path).await?;
         
         Ok(())
     }
@@ -181,6 +289,7 @@ impl SecureStorage {
             fs::remove_dir_all(&wallets_dir).await
                 .map_err(|e| SDKError::Storage(e.to_string()))?;
         }
+        self.manifest.lock().await.checksums.retain(|name, _| !name.starts_with("wallets/"));
         
         // Remove current wallet ID
         self.remove_current_wallet_id().await?;
@@ -188,13 +297,55 @@ impl SecureStorage {
         Ok(())
     }
 
+    /// Merge transactions into a wallet's local history
+    ///
+    /// A transaction already in the history is replaced, so later copies carry
+    /// status updates.
+    pub async fn record_history(&self, wallet_id: &str, transactions: &[Transaction]) -> SDKResult<()> {
+        let mut history = self.get_history(wallet_id).await?;
+        for transaction in transactions {
+            match history.iter_mut().find(|known| known.id == transaction.id) {
+                Some(known) => *known = transaction.clone(),
+                None => history.push(transaction.clone()),
+            }
+        }
+        history.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+
+        let history_json = serde_json::to_string(&history)?;
+        let data_to_store = if let Some(ref key) = self.encryption_key {
+            self.encrypt_data(&history_json, key)?
+        } else {
+            history_json.into_bytes()
+        };
+        self.write_file(&self.get_history_path(wallet_id), &data_to_store).await
+    }
+
+    /// Local history of a wallet, oldest first
+    pub async fn get_history(&self, wallet_id: &str) -> SDKResult<Vec<Transaction>> {
+        let history_path = self.get_history_path(wallet_id);
+
+        if !self.file_exists(&history_path).await? {
+            return Ok(Vec::new());
+        }
+
+        let data = self.read_file(&history_path).await?;
+        let history_json = if let Some(ref key) = self.encryption_key {
+            self.decrypt_data(&data, key)?
+        } else {
+            String::from_utf8(data).map_err(|e| SDKError::Storage(e.to_string()))?
+        };
+
+        serde_json::from_str(&history_json)
+            .map_err(|e| SDKError::Serialization(e.to_string()))
+    }
+
     /// Store cache data
     pub async fn store_cache(&self, key: &str, data: &[u8], ttl_seconds: u64) -> SDKResult<()> {
         if !self.config.enable_cache {
             return Ok(());
         }
         
-        let cache_dir = self.base_path.join("cache");
+        let cache_dir = self.cache_path.clone();
         if !self.dir_exists(&cache_dir).await? {
             fs::create_dir_all(&cache_dir).await
                 .map_err(|e| SDKError::Storage(e.to_string()))?;
@@ -222,7 +373,7 @@ impl SecureStorage {
             return Ok(None);
         }
         
-        let cache_path = self.base_path.join("cache").join(format!("{}.cache", key));
+        let cache_path = self.cache_path.join(format!("{}.cache", key));
         
         if !self.file_exists(&cache_path).await? {
             return Ok(None);
@@ -249,7 +400,7 @@ impl SecureStorage {
             return Ok(());
         }
         
-        let cache_dir = self.base_path.join("cache");
+        let cache_dir = self.cache_path.clone();
         
         if !self.dir_exists(&cache_dir).await? {
             return Ok(());
@@ -283,7 +434,7 @@ impl SecureStorage {
 
     /// Clear all cache
     pub async fn clear_all_cache(&self) -> SDKResult<()> {
-        let cache_dir = self.base_path.join("cache");
+        let cache_dir = self.cache_path.clone();
         
         if self.dir_exists(&cache_dir).await? {
             fs::remove_dir_all(&cache_dir).await
@@ -358,6 +509,12 @@ impl SecureStorage {
                 tokio::io::copy(&mut file, &mut out_file).await?;
             }
         }
+
+        // Restored files replace the ones the manifest knew about
+        let mut manifest = self.manifest.lock().await;
+        manifest.checksums.clear();
+        check_integrity(&self.base_path, &mut manifest)?;
+        fs::write(self.base_path.join(MANIFEST_FILE), serde_json::to_vec_pretty(&*manifest)?).await?;
         
         Ok(())
     }
@@ -382,7 +539,7 @@ impl SecureStorage {
         }
         
         // Count cache entries
-        let cache_dir = self.base_path.join("cache");
+        let cache_dir = self.cache_path.clone();
         if self.dir_exists(&cache_dir).await? {
             let mut entries = fs::read_dir(&cache_dir).await?;
             while let Some(entry) = entries.next_entry().await? {
@@ -438,6 +595,10 @@ impl SecureStorage {
         wallets_dir.join(format!("{}.wallet", wallet_id))
     }
 
+    fn get_history_path(&self, wallet_id: &str) -> PathBuf {
+        self.base_path.join("history").join(format!("{}.history", wallet_id))
+    }
+
     async fn file_exists(&self, path: &PathBuf) -> SDKResult<bool> {
         Ok(path.exists())
     }
@@ -465,6 +626,22 @@ impl SecureStorage {
             .map_err(|e| SDKError::Storage(e.to_string()))?;
         file.write_all(data).await
             .map_err(|e| SDKError::Storage(e.to_string()))?;
+
+        if let Some(name) = tracked_name(&self.base_path, path) {
+            let mut manifest = self.manifest.lock().await;
+            manifest.checksums.insert(name, checksum(data));
+            fs::write(self.base_path.join(MANIFEST_FILE), serde_json::to_vec_pretty(&*manifest)?).await?;
+        }
+        Ok(())
+    }
+
+    async fn forget_file(&self, path: &PathBuf) -> SDKResult<()> {
+        if let Some(name) = tracked_name(&self.base_path, path) {
+            let mut manifest = self.manifest.lock().await;
+            if manifest.checksums.remove(&name).is_some() {
+                fs::write(self.base_path.join(MANIFEST_FILE), serde_json::to_vec_pretty(&*manifest)?).await?;
+            }
+        }
         Ok(())
     }
 
@@ -536,6 +713,114 @@ pub struct StorageStats {
     pub base_path: String,
 }
 
+/// Storage location and wallet file checksums, kept in the data directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageManifest {
+    /// Data directory the manifest was last written from
+    pub data_dir: Option<String>,
+    /// SHA-256 of each tracked file, keyed by path relative to the data directory
+    pub checksums: std::collections::BTreeMap<String, String>,
+}
+
+impl StorageManifest {
+    fn load(data_dir: &std::path::Path) -> SDKResult<Self> {
+        match std::fs::read(data_dir.join(MANIFEST_FILE)) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Outcome of checking wallet files against the manifest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageIntegrityReport {
+    /// Files whose checksum matched
+    pub verified: u32,
+    /// Files found without a recorded checksum and adopted as they are
+    pub adopted: u32,
+    /// Recorded files that no longer exist
+    pub missing: Vec<String>,
+    /// Files whose contents changed, moved to `quarantine/`
+    pub corrupted: Vec<String>,
+    /// Legacy directory the data was migrated from
+    pub migrated_from: Option<String>,
+    /// Data directory before the OS relocated the app container
+    pub relocated_from: Option<String>,
+}
+
+impl StorageIntegrityReport {
+    /// Whether every recorded file was found intact
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.corrupted.is_empty()
+    }
+}
+
+/// Name under which `path` is tracked in the manifest, if it is a wallet file
+fn tracked_name(data_dir: &std::path::Path, path: &std::path::Path) -> Option<String> {
+    let name = path.strip_prefix(data_dir).ok()?.to_string_lossy().replace('\\', "/");
+    (name.starts_with("wallets/") || name == "current_wallet.txt").then_some(name)
+}
+
+fn checksum(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(data))
+}
+
+/// Compare tracked files in `data_dir` with the checksums in `manifest`
+///
+/// Corrupted files are moved aside so they are not loaded as wallets, and a
+/// current wallet pointer to a wallet that is gone is dropped.
+fn check_integrity(data_dir: &std::path::Path, manifest: &mut StorageManifest) -> SDKResult<StorageIntegrityReport> {
+    let mut report = StorageIntegrityReport::default();
+
+    for (name, expected) in std::mem::take(&mut manifest.checksums) {
+        let path = data_dir.join(&name);
+        match std::fs::read(&path) {
+            Ok(data) if checksum(&data) == expected => {
+                report.verified += 1;
+                manifest.checksums.insert(name, expected);
+            }
+            Ok(_) => {
+                let quarantine = data_dir.join("quarantine");
+                std::fs::create_dir_all(&quarantine)?;
+                let target = quarantine.join(format!("{}.{}", name.replace('/', "_"), Utc::now().timestamp()));
+                std::fs::rename(&path, target)?;
+                report.corrupted.push(name);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => report.missing.push(name),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    // Files written before checksums were recorded
+    let mut on_disk = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(data_dir.join("wallets")) {
+        for entry in entries {
+            on_disk.push(entry?.path());
+        }
+    }
+    on_disk.push(data_dir.join("current_wallet.txt"));
+    for path in on_disk {
+        let Some(name) = tracked_name(data_dir, &path) else { continue };
+        if manifest.checksums.contains_key(&name) || !path.is_file() {
+            continue;
+        }
+        manifest.checksums.insert(name, checksum(&std::fs::read(&path)?));
+        report.adopted += 1;
+    }
+
+    let current_wallet_path = data_dir.join("current_wallet.txt");
+    if let Ok(wallet_id) = std::fs::read_to_string(&current_wallet_path) {
+        if !data_dir.join("wallets").join(format!("{}.wallet", wallet_id.trim())).is_file() {
+            std::fs::remove_file(&current_wallet_path)?;
+            manifest.checksums.remove("current_wallet.txt");
+        }
+    }
+
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -619,4 +904,77 @@ mod tests {
         let retrieved_data = storage.get_cache("test_key").await.unwrap();
         assert!(retrieved_data.is_none());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_integrity_check_after_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = StorageConfig {
+            database_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let storage = SecureStorage::new(&config).unwrap();
+        for id in ["w1", "w2"] {
+            let wallet_data = WalletData {
+                id: id.to_string(),
+                name: id.to_string(),
+                address: "test_address".to_string(),
+                public_key: "test_public_key".to_string(),
+                encrypted_private_key: vec![1, 2, 3, 4],
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                is_active: true,
+                metadata: HashMap::new(),
+            };
+            storage.store_wallet(&wallet_data).await.unwrap();
+        }
+        storage.set_current_wallet_id("w2").await.unwrap();
+        drop(storage);
+
+        // A partial OS restore brings back a truncated w1 and loses w2
+        std::fs::write(temp_dir.path().join("wallets/w1.wallet"), b"{").unwrap();
+        std::fs::remove_file(temp_dir.path().join("wallets/w2.wallet")).unwrap();
+
+        let storage = SecureStorage::new(&config).unwrap();
+        let report = storage.integrity_report();
+        assert_eq!(report.corrupted, vec!["wallets/w1.wallet".to_string()]);
+        assert_eq!(report.missing, vec!["wallets/w2.wallet".to_string()]);
+        assert!(storage.list_wallets().await.unwrap().is_empty());
+        assert_eq!(storage.get_current_wallet_id().await.unwrap(), None);
+
+        assert!(storage.verify_integrity().await.unwrap().is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_networks_are_isolated() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = StorageConfig {
+            database_path: Some(temp_dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let testnet = SecureStorage::for_network(&config, &DesktopPaths, &NetworkType::Testnet).unwrap();
+        let wallet_data = WalletData {
+            id: "test_wallet".to_string(),
+            name: "Test Wallet".to_string(),
+            address: "test_address".to_string(),
+            public_key: "test_public_key".to_string(),
+            encrypted_private_key: vec![1, 2, 3, 4],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            is_active: true,
+            metadata: HashMap::new(),
+        };
+        testnet.store_wallet(&wallet_data).await.unwrap();
+        testnet.store_cache("balance", b"100", 3600).await.unwrap();
+
+        let mainnet = SecureStorage::for_network(&config, &DesktopPaths, &NetworkType::Mainnet).unwrap();
+        assert_eq!(mainnet.network(), Some(&NetworkType::Mainnet));
+        assert_eq!(mainnet.root_path(), testnet.root_path());
+        assert!(mainnet.list_wallets().await.unwrap().is_empty());
+        assert!(mainnet.get_cache("balance").await.unwrap().is_none());
+
+        assert_eq!(testnet.list_wallets().await.unwrap().len(), 1);
+        assert_eq!(testnet.get_cache("balance").await.unwrap().unwrap(), b"100");
+    }
+}
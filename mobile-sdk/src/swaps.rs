@@ -0,0 +1,181 @@
+//! Atomic swaps
+//!
+//! Mirrors the node's `core::swaps` types and builds the transaction
+//! payloads that drive a swap. The initiator generates a `SwapSecret`, locks
+//! its leg under the secret's hashlock and shares the hashlock with the
+//! counterparty, who locks the agreed asset in return. Once both legs are
+//! locked either party can claim with the secret, which settles both legs;
+//! after expiry either can refund them. Swap transactions move no amount
+//! themselves, the node takes the locked assets from the parties' balances.
+//!
+//! The hashlock and payload encodings must stay compatible with the node's
+//! `core::payload` module.
+
+use std::collections::HashMap;
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::{Amount, SDKError, SDKResult};
+
+/// Transaction metadata key carrying the payload
+pub const PAYLOAD_METADATA_KEY: &str = "payload";
+
+/// An asset held in balances and escrow
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Asset {
+    /// The chain's own coin
+    Native,
+    /// A token, identified by the transaction that issued it
+    Token(String),
+}
+
+/// Where a swap stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapStatus {
+    Initiated,
+    Locked,
+    Completed,
+    Refunded,
+}
+
+impl SwapStatus {
+    /// Whether the swap can no longer change
+    pub fn is_settled(&self) -> bool {
+        matches!(self, SwapStatus::Completed | SwapStatus::Refunded)
+    }
+}
+
+/// What one party puts into a swap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapLeg {
+    pub party: String,
+    pub asset: Asset,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+}
+
+/// A swap as reported by the node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtomicSwap {
+    pub hashlock: String,
+    pub initiator: SwapLeg,
+    pub counterparty: SwapLeg,
+    pub expires_at: u64,
+    pub status: SwapStatus,
+    pub preimage: Option<String>,
+    pub initiated_by: String,
+    pub updated_at: u64,
+}
+
+/// Secret a swap is locked under; keep it until the swap is claimed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapSecret {
+    /// Hex secret
+    pub preimage: String,
+    /// Hex SHA3-256 of the secret
+    pub hashlock: String,
+}
+
+impl SwapSecret {
+    pub fn generate() -> Self {
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        Self {
+            preimage: hex::encode(secret),
+            hashlock: swap_hashlock(&secret),
+        }
+    }
+}
+
+/// Hashlock of a swap secret: its hex SHA3-256
+pub fn swap_hashlock(preimage: &[u8]) -> String {
+    hex::encode(Sha3_256::digest(preimage))
+}
+
+/// What the initiator offers and asks for in a swap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapTerms {
+    pub asset: Asset,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    pub counter_asset: Asset,
+    #[serde(with = "crate::amount")]
+    pub counter_amount: Amount,
+    /// Unix time from which the swap can only be refunded
+    pub expires_at: u64,
+}
+
+/// A swap step, in the node's payload encoding
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SwapPayload {
+    SwapInitiate {
+        hashlock: String,
+        asset: Asset,
+        #[serde(with = "crate::amount")]
+        amount: Amount,
+        counter_asset: Asset,
+        #[serde(with = "crate::amount")]
+        counter_amount: Amount,
+        expires_at: u64,
+    },
+    SwapParticipate { hashlock: String },
+    SwapClaim { hashlock: String, preimage: String },
+    SwapRefund { hashlock: String },
+}
+
+impl SwapPayload {
+    pub fn initiate(hashlock: &str, terms: SwapTerms) -> Self {
+        Self::SwapInitiate {
+            hashlock: hashlock.to_string(),
+            asset: terms.asset,
+            amount: terms.amount,
+            counter_asset: terms.counter_asset,
+            counter_amount: terms.counter_amount,
+            expires_at: terms.expires_at,
+        }
+    }
+
+    /// Transaction metadata carrying this payload
+    pub fn metadata(&self) -> SDKResult<HashMap<String, serde_json::Value>> {
+        let value = serde_json::to_value(self).map_err(|e| SDKError::Serialization(e.to_string()))?;
+        Ok(HashMap::from([(PAYLOAD_METADATA_KEY.to_string(), value)]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_matches_its_hashlock() {
+        let secret = SwapSecret::generate();
+        assert_eq!(swap_hashlock(&hex::decode(&secret.preimage).unwrap()), secret.hashlock);
+        assert_ne!(SwapSecret::generate(), secret);
+    }
+
+    #[test]
+    fn test_payload_uses_node_encoding() {
+        let payload = SwapPayload::initiate("ab", SwapTerms {
+            asset: Asset::Native,
+            amount: 100,
+            counter_asset: Asset::Token("cd".to_string()),
+            counter_amount: 5,
+            expires_at: 1_700_000_000,
+        });
+        let metadata = payload.metadata().unwrap();
+        assert_eq!(metadata[PAYLOAD_METADATA_KEY], serde_json::json!({
+            "kind": "swap_initiate",
+            "hashlock": "ab",
+            "asset": "native",
+            "amount": 100,
+            "counter_asset": {"token": "cd"},
+            "counter_amount": 5,
+            "expires_at": 1_700_000_000u64,
+        }));
+    }
+}
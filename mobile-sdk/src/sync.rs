@@ -0,0 +1,324 @@
+//! Bloom-filtered transaction sync
+//!
+//! Instead of downloading every transaction, the client registers a Bloom
+//! filter of its watched addresses with the node and receives only matching
+//! transactions. A higher false-positive rate costs bandwidth but hides which
+//! of the matched addresses are really ours.
+//!
+//! The node also publishes a chain of filter headers, each committing to a
+//! Golomb-coded filter of every address in a segment of transactions. Checking
+//! those filters locally reveals segments where the node served nothing even
+//! though one of our addresses is present. The hashing here must stay
+//! byte-compatible with the node's `core::filters` module.
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::{Amount, SDKError, SDKResult};
+
+/// Largest Bloom filter the node accepts, in bytes
+pub const MAX_BLOOM_FILTER_BYTES: usize = 36_000;
+/// Largest number of hash functions the node accepts
+pub const MAX_BLOOM_HASH_FUNCS: u32 = 50;
+/// Golomb-Rice parameter of segment filters
+pub const COMPACT_FILTER_P: u8 = 19;
+/// Inverse false-positive rate of segment filters
+pub const COMPACT_FILTER_M: u64 = 784_931;
+
+/// Bloom filter over watched addresses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    pub bits: Vec<u8>,
+    pub hash_funcs: u32,
+    pub tweak: u32,
+}
+
+impl BloomFilter {
+    /// Size a filter for `expected_items` at the given false-positive rate
+    pub fn new(expected_items: usize, false_positive_rate: f64, tweak: u32) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+
+        let num_bits = (-n * p.ln() / (ln2 * ln2)).ceil() as usize;
+        let num_bytes = num_bits.div_ceil(8).clamp(1, MAX_BLOOM_FILTER_BYTES);
+        let hash_funcs = ((num_bytes * 8) as f64 / n * ln2).round() as u32;
+
+        Self {
+            bits: vec![0u8; num_bytes],
+            hash_funcs: hash_funcs.clamp(1, MAX_BLOOM_HASH_FUNCS),
+            tweak,
+        }
+    }
+
+    /// Build a filter over `addresses` with a random tweak
+    pub fn for_addresses(addresses: &[String], false_positive_rate: f64) -> Self {
+        let mut filter = Self::new(addresses.len(), false_positive_rate, rand::random());
+        for address in addresses {
+            filter.insert(&address_bytes(address));
+        }
+        filter
+    }
+
+    /// Add an item to the filter
+    pub fn insert(&mut self, item: &[u8]) {
+        let indices: Vec<usize> = self.bit_indices(item).collect();
+        for index in indices {
+            self.bits[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    /// Whether the filter may contain an item
+    pub fn contains(&self, item: &[u8]) -> bool {
+        self.bit_indices(item).all(|index| self.bits[index / 8] & (1 << (index % 8)) != 0)
+    }
+
+    fn bit_indices<'a>(&'a self, item: &[u8]) -> impl Iterator<Item = usize> + 'a {
+        let mut hasher = Sha3_256::new();
+        hasher.update(self.tweak.to_le_bytes());
+        hasher.update(item);
+        let digest = hasher.finalize();
+
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap());
+        let num_bits = (self.bits.len() * 8) as u64;
+
+        (0..self.hash_funcs as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+}
+
+/// Golomb-coded set of the addresses in a segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactFilter {
+    pub item_count: u64,
+    pub data: Vec<u8>,
+}
+
+impl CompactFilter {
+    /// Whether any of `items` may be in the filter
+    pub fn match_any(&self, key: &[u8; 16], items: &[Vec<u8>]) -> bool {
+        if self.item_count == 0 || items.is_empty() {
+            return false;
+        }
+
+        let range = self.item_count * COMPACT_FILTER_M;
+        let mut queries: Vec<u64> = items.iter().map(|item| hash_to_range(key, item, range)).collect();
+        queries.sort_unstable();
+
+        let mut position = 0;
+        let mut value = 0u64;
+        let mut query_index = 0;
+        for _ in 0..self.item_count {
+            let Some(delta) = read_golomb(&self.data, &mut position) else {
+                return false;
+            };
+            value += delta;
+
+            while query_index < queries.len() && queries[query_index] < value {
+                query_index += 1;
+            }
+            if query_index == queries.len() {
+                return false;
+            }
+            if queries[query_index] == value {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Hash committed to by the filter header chain
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(self.item_count.to_le_bytes());
+        hasher.update(&self.data);
+        hasher.finalize().into()
+    }
+}
+
+/// Header committing to a segment filter and all earlier ones
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterHeader {
+    pub segment: u64,
+    pub filter_hash: [u8; 32],
+    pub previous_header: [u8; 32],
+    pub header: [u8; 32],
+}
+
+impl FilterHeader {
+    /// Verify that `headers` form an unbroken chain starting after `previous`
+    pub fn verify_chain(headers: &[FilterHeader], mut previous: [u8; 32]) -> bool {
+        headers.iter().all(|header| {
+            let mut hasher = Sha3_256::new();
+            hasher.update(header.filter_hash);
+            hasher.update(header.previous_header);
+            let expected: [u8; 32] = hasher.finalize().into();
+
+            let linked = header.previous_header == previous && header.header == expected;
+            previous = header.header;
+            linked
+        })
+    }
+}
+
+/// Sealed segment with its compact filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterSegment {
+    pub index: u64,
+    pub transaction_ids: Vec<String>,
+    pub filter: CompactFilter,
+    pub header: FilterHeader,
+}
+
+impl FilterSegment {
+    /// Key the node used to hash addresses into this segment's filter
+    pub fn filter_key(&self) -> [u8; 16] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(self.index.to_le_bytes());
+        hasher.update(self.header.previous_header);
+        let digest = hasher.finalize();
+        digest[..16].try_into().unwrap()
+    }
+}
+
+/// Transaction as served by the node's filtered sync endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedTransaction {
+    pub id: String,
+    pub sender: Vec<u8>,
+    pub receiver: Vec<u8>,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+/// Transactions matching a registered filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilteredSync {
+    pub transactions: Vec<SyncedTransaction>,
+    pub headers: Vec<FilterHeader>,
+    /// Segment to request next
+    pub next_segment: u64,
+}
+
+impl FilteredSync {
+    /// Check a sync response against segment filters
+    ///
+    /// `previous_header` is the last header already trusted (all zeroes before
+    /// segment 0) and `segments` must hold the segments named in `headers`.
+    /// Returns the indices of segments whose filter matches a watched address
+    /// but from which the node served no transaction.
+    pub fn verify(
+        &self,
+        previous_header: [u8; 32],
+        segments: &[FilterSegment],
+        watched: &[String],
+    ) -> SDKResult<Vec<u64>> {
+        if !FilterHeader::verify_chain(&self.headers, previous_header) {
+            return Err(SDKError::Validation("Filter header chain is broken".to_string()));
+        }
+
+        let watched: Vec<Vec<u8>> = watched.iter().map(|address| address_bytes(address)).collect();
+        let mut suspicious = Vec::new();
+        for header in &self.headers {
+            let segment = segments.iter()
+                .find(|segment| segment.index == header.segment)
+                .ok_or_else(|| SDKError::Validation(format!("Missing filter for segment {}", header.segment)))?;
+            if segment.filter.hash() != header.filter_hash {
+                return Err(SDKError::Validation(format!("Filter for segment {} does not match its header", header.segment)));
+            }
+
+            if segment.filter.match_any(&segment.filter_key(), &watched) {
+                let served = self.transactions.iter().any(|tx| segment.transaction_ids.contains(&tx.id));
+                if !served {
+                    suspicious.push(segment.index);
+                }
+            }
+        }
+        Ok(suspicious)
+    }
+}
+
+/// Bytes of an address as the node stores them
+pub fn address_bytes(address: &str) -> Vec<u8> {
+    hex::decode(address).unwrap_or_else(|_| address.as_bytes().to_vec())
+}
+
+fn hash_to_range(key: &[u8; 16], item: &[u8], range: u64) -> u64 {
+    let mut hasher = Sha3_256::new();
+    hasher.update(key);
+    hasher.update(item);
+    let digest = hasher.finalize();
+    let hash = u64::from_le_bytes(digest[..8].try_into().unwrap());
+    ((hash as u128 * range as u128) >> 64) as u64
+}
+
+fn read_bit(data: &[u8], position: &mut usize) -> Option<bool> {
+    let byte = data.get(*position / 8)?;
+    let bit = byte & (0x80 >> (*position % 8)) != 0;
+    *position += 1;
+    Some(bit)
+}
+
+fn read_golomb(data: &[u8], position: &mut usize) -> Option<u64> {
+    let mut quotient = 0u64;
+    while read_bit(data, position)? {
+        quotient += 1;
+    }
+    let mut remainder = 0u64;
+    for _ in 0..COMPACT_FILTER_P {
+        remainder = (remainder << 1) | read_bit(data, position)? as u64;
+    }
+    Some((quotient << COMPACT_FILTER_P) | remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Segment produced by the node for transfers aa..aa -> bb..bb and cc..cc -> dd..dd
+    const NODE_SEGMENT: &str = r#"{"index":0,"transaction_ids":["42ca1dc1-4d94-404a-ad14-736ff9d7d7a1","25044cd5-37f1-4893-aae0-3023e99a7136"],"filter":{"item_count":4,"data":[180,108,105,229,105,234,115,32,243,100,224]},"header":{"segment":0,"filter_hash":[155,102,126,203,166,27,55,137,209,60,85,176,22,147,233,203,78,109,162,224,153,32,29,24,27,208,249,16,80,188,106,217],"previous_header":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0],"header":[124,236,228,233,140,191,129,4,96,192,232,254,252,245,228,131,216,231,160,57,23,117,30,209,42,157,154,53,3,156,217,62]}}"#;
+
+    fn synced(id: &str) -> SyncedTransaction {
+        SyncedTransaction {
+            id: id.to_string(),
+            sender: vec![0xaa; 32],
+            receiver: vec![0xbb; 32],
+            amount: 1,
+            nonce: 0,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_for_addresses() {
+        let addresses: Vec<String> = (0..50u8).map(|i| hex::encode([i; 32])).collect();
+        let filter = BloomFilter::for_addresses(&addresses, 0.01);
+
+        assert!((0..50u8).all(|i| filter.contains(&[i; 32])));
+
+        let false_positives = (0..1000u32).filter(|i| filter.contains(&i.to_be_bytes())).count();
+        assert!(false_positives < 50);
+    }
+
+    #[test]
+    fn test_verify_detects_withheld_segment() {
+        let segment: FilterSegment = serde_json::from_str(NODE_SEGMENT).unwrap();
+        let watched = vec!["aa".repeat(32)];
+
+        let withheld = FilteredSync { transactions: vec![], headers: vec![segment.header.clone()], next_segment: 1 };
+        assert_eq!(withheld.verify([0u8; 32], &[segment.clone()], &watched).unwrap(), vec![0]);
+        assert!(withheld.verify([0u8; 32], &[segment.clone()], &["ee".repeat(32)]).unwrap().is_empty());
+
+        let served = FilteredSync {
+            transactions: vec![synced(&segment.transaction_ids[0])],
+            headers: vec![segment.header.clone()],
+            next_segment: 1,
+        };
+        assert!(served.verify([0u8; 32], &[segment.clone()], &watched).unwrap().is_empty());
+
+        // A header that does not chain from the trusted one is rejected
+        assert!(served.verify([1u8; 32], &[segment], &watched).is_err());
+    }
+}
@@ -3,6 +3,7 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
+use crate::Amount;
 
 /// Transaction hash
 pub type TransactionHash = String;
@@ -33,8 +34,10 @@ pub struct Transaction {
     pub hash: TransactionHash,
     pub sender: Address,
     pub receiver: Address,
-    pub amount: u64,
-    pub fee: u64,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    #[serde(with = "crate::amount")]
+    pub fee: Amount,
     pub nonce: u64,
     pub timestamp: u64,
     pub signature: String,
@@ -143,8 +146,10 @@ pub struct Peer {
 pub struct UnsignedTransaction {
     pub sender: Address,
     pub receiver: Address,
-    pub amount: u64,
-    pub fee: u64,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    #[serde(with = "crate::amount")]
+    pub fee: Amount,
     pub nonce: u64,
     pub timestamp: u64,
     pub metadata: Option<HashMap<String, serde_json::Value>>,
@@ -155,8 +160,8 @@ pub struct UnsignedTransaction {
 pub struct TransactionBuilder {
     sender: Option<Address>,
     receiver: Option<Address>,
-    amount: Option<u64>,
-    fee: u64,
+    amount: Option<Amount>,
+    fee: Amount,
     nonce: Option<u64>,
     timestamp: Option<u64>,
     metadata: Option<HashMap<String, serde_json::Value>>,
@@ -195,13 +200,13 @@ impl TransactionBuilder {
     }
 
     /// Set amount
-    pub fn amount(mut self, amount: u64) -> Self {
+    pub fn amount(mut self, amount: Amount) -> Self {
         self.amount = Some(amount);
         self
     }
 
     /// Set fee
-    pub fn fee(mut self, fee: u64) -> Self {
+    pub fn fee(mut self, fee: Amount) -> Self {
         self.fee = fee;
         self
     }
@@ -260,11 +265,22 @@ pub struct TransactionReceipt {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Test funds paid by a devnet or testnet faucet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetGrant {
+    pub address: Address,
+    #[serde(with = "crate::amount")]
+    pub amount: Amount,
+    pub transaction_id: String,
+    pub granted_at: u64,
+}
+
 /// Account info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountInfo {
     pub address: Address,
-    pub balance: u64,
+    #[serde(with = "crate::amount")]
+    pub balance: Amount,
     pub nonce: u64,
     pub transaction_count: u64,
     pub created_at: DateTime<Utc>,
@@ -351,8 +367,10 @@ pub struct TransactionFilter {
     pub sender: Option<Address>,
     pub receiver: Option<Address>,
     pub status: Option<TransactionStatus>,
-    pub min_amount: Option<u64>,
-    pub max_amount: Option<u64>,
+    #[serde(default, with = "crate::amount::option")]
+    pub min_amount: Option<Amount>,
+    #[serde(default, with = "crate::amount::option")]
+    pub max_amount: Option<Amount>,
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
     pub block_hash: Option<BlockHash>,
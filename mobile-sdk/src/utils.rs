@@ -5,7 +5,7 @@ use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::{SDKResult, SDKError};
+use crate::{Amount, SDKResult, SDKError};
 
 /// Retry utility with exponential backoff
 pub async fn retry<T, F, Fut>(
@@ -420,8 +420,8 @@ impl Validator {
     }
 
     /// Validate amount
-    pub fn validate_amount(amount: u64) -> bool {
-        amount > 0 && amount <= u64::MAX
+    pub fn validate_amount(amount: Amount) -> bool {
+        amount > 0
     }
 
     /// Validate mnemonic phrase
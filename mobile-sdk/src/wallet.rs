@@ -8,6 +8,7 @@ use uuid::Uuid;
 
 use crate::types::*;
 use crate::crypto::{CryptoService, KeyPair, EncryptedData, SessionToken};
+use crate::keystore::{HybridSigner, KeystoreBackend};
 use crate::storage::SecureStorage;
 use crate::{SDKResult, SDKError};
 
@@ -16,8 +17,14 @@ pub struct WalletManager {
     storage: Arc<SecureStorage>,
     crypto: Arc<CryptoService>,
     current_wallet_id: Option<String>,
+    keystore: Option<HybridSigner>,
 }
 
+/// Wallet metadata key naming the keystore backend holding the classical key
+const KEY_STORAGE_METADATA: &str = "key_storage";
+/// Wallet metadata key holding the keystore alias of the classical key
+const KEYSTORE_ALIAS_METADATA: &str = "keystore_alias";
+
 impl WalletManager {
     /// Create new wallet manager
     pub fn new(storage: Arc<SecureStorage>, crypto: Arc<CryptoService>) -> SDKResult<Self> {
@@ -25,9 +32,83 @@ impl WalletManager {
             storage,
             crypto,
             current_wallet_id: None,
+            keystore: None,
         })
     }
 
+    /// Create a wallet manager that keeps classical keys in a platform keystore
+    pub fn with_keystore(
+        storage: Arc<SecureStorage>,
+        crypto: Arc<CryptoService>,
+        keystore: Arc<dyn KeystoreBackend>,
+    ) -> SDKResult<Self> {
+        let mut manager = Self::new(storage, crypto)?;
+        manager.keystore = Some(HybridSigner::new(keystore));
+        Ok(manager)
+    }
+
+    /// Whether a platform keystore is configured
+    pub fn has_keystore(&self) -> bool {
+        self.keystore.is_some()
+    }
+
+    /// Create a wallet whose Ed25519 key is generated inside the platform keystore
+    ///
+    /// The Dilithium3 key is generated in software and stored encrypted with
+    /// the passphrase. Such wallets have no mnemonic, because the keystore
+    /// never releases the classical key.
+    pub async fn create_keystore_wallet(&self, passphrase: &str, require_user_auth: bool) -> SDKResult<Wallet> {
+        let signer = self.keystore.as_ref()
+            .ok_or_else(|| SDKError::Wallet("No keystore backend configured".to_string()))?;
+
+        let wallet_id = Uuid::new_v4().to_string();
+        let alias = format!("quantum-dag-wallet-{}", wallet_id);
+        let key = signer.generate(&alias, require_user_auth)?;
+
+        let mut metadata = HashMap::new();
+        metadata.insert(KEY_STORAGE_METADATA.to_string(), serde_json::Value::String(signer.backend().name()));
+        metadata.insert(KEYSTORE_ALIAS_METADATA.to_string(), serde_json::Value::String(alias.clone()));
+
+        let wallet = Wallet {
+            id: wallet_id,
+            name: "My Wallet".to_string(),
+            address: self.crypto.public_key_to_address(&key.classical_public_key),
+            public_key: hex::encode(key.public_key()),
+            mnemonic: String::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            is_active: true,
+            metadata,
+        };
+
+        // Only the PQC half is held by the SDK
+        let encryption_key = self.crypto.derive_encryption_key(passphrase, b"salt")?;
+        let encrypted_private_key = self.crypto.encrypt(&key.pqc_secret_key, &encryption_key)?;
+
+        let wallet_data = WalletData {
+            id: wallet.id.clone(),
+            name: wallet.name.clone(),
+            address: wallet.address.clone(),
+            public_key: wallet.public_key.clone(),
+            encrypted_private_key,
+            created_at: wallet.created_at,
+            updated_at: wallet.updated_at,
+            is_active: wallet.is_active,
+            metadata: wallet.metadata.clone(),
+        };
+
+        if let Err(e) = self.storage.store_wallet(&wallet_data).await {
+            signer.backend().delete_key(&alias)?;
+            return Err(e);
+        }
+
+        self.current_wallet_id = Some(wallet.id.clone());
+        self.storage.set_current_wallet_id(&wallet.id).await?;
+
+        log::info!("🔐 Created wallet {} with {} keystore", wallet.id, signer.backend().name());
+        Ok(wallet)
+    }
+
     /// Create new wallet
     pub async fn create_wallet(&self, passphrase: &str) -> SDKResult<Wallet> {
         // Generate mnemonic
@@ -231,6 +312,13 @@ impl WalletManager {
         // Hash transaction
         let tx_hash = self.crypto.hash_transaction(transaction)?;
         
+        // Keystore wallets sign with the enclave key plus the decrypted PQC key
+        if let Some(alias) = wallet_data.metadata.get(KEYSTORE_ALIAS_METADATA).and_then(|v| v.as_str()) {
+            let signer = self.keystore.as_ref()
+                .ok_or_else(|| SDKError::Wallet("Wallet requires a keystore backend".to_string()))?;
+            return signer.sign(alias, &private_key, &tx_hash);
+        }
+        
         // Sign transaction
         let signature = self.crypto.sign(&tx_hash, &private_key)?;
         
@@ -260,6 +348,10 @@ impl WalletManager {
         let signature = hex::decode(&transaction.signature)
             .map_err(|e| SDKError::Crypto(e.to_string()))?;
         
+        if signature.len() > 64 {
+            return HybridSigner::verify(&tx_hash, &signature, &public_key);
+        }
+        
         self.crypto.verify_signature(&tx_hash, &signature, &public_key)
     }
 
@@ -518,4 +610,30 @@ mod tests {
         assert_eq!(stats.total_wallets, 1);
         assert_eq!(stats.active_wallets, 1);
     }
+
+    #[tokio::test]
+    async fn test_keystore_wallet_signs_hybrid() {
+        let storage = Arc::new(SecureStorage::new(&StorageConfig::default()).unwrap());
+        let crypto = Arc::new(CryptoService::new(&SecurityConfig::default()).unwrap());
+        let keystore = Arc::new(crate::keystore::SoftwareKeystore::new());
+        let wallet_manager = WalletManager::with_keystore(storage, crypto.clone(), keystore).unwrap();
+
+        let wallet = wallet_manager.create_keystore_wallet("test_passphrase", true).await.unwrap();
+        assert!(wallet.mnemonic.is_empty());
+        assert_eq!(wallet.metadata.get("key_storage").and_then(|v| v.as_str()), Some("software"));
+
+        let transaction = UnsignedTransaction {
+            sender: wallet.address.clone(),
+            receiver: "receiver".to_string(),
+            amount: 100,
+            fee: 1,
+            nonce: 1,
+            timestamp: 0,
+            metadata: None,
+        };
+        let signature = wallet_manager.sign_transaction(&transaction, "test_passphrase").await.unwrap();
+        let tx_hash = crypto.hash_transaction(&transaction).unwrap();
+        let public_key = hex::decode(&wallet.public_key).unwrap();
+        assert!(HybridSigner::verify(&tx_hash, &signature, &public_key).unwrap());
+    }
 }
\ No newline at end of file
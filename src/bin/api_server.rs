@@ -1,6 +1,10 @@
 //! HTTP API server for the Quantum-Proof DAG Blockchain
 
-use crate::{Blockchain, BlockchainError, Transaction, TransactionId};
+use crate::{
+    cpu_profile, heap_stats, spawn_instrumented, task_stats, AccountBalance, Amount, ArchiveBundle, ArchiveRecord, AtomicSwap, Blockchain, BlockchainError, BloomFilter, BootstrapInfo, Capabilities, CapabilityDistribution, CorruptedRow, ContractId, CoreError, Deadline,
+    DatabaseStats, EventFilter, EventLog, ExecutionTrace, IdentityInfo, InclusionProof, IngestionStatus, IngestionTicket, JournalEntry, MaintenanceJob, MaintenanceRun, NodeAttestation, NodeSettings, ParticipationCredential, OperatorContact, OperatorMessage, PruneReport, ReindexConfig, ReindexStatus, ReloadReport,
+    ResumeAuthorization, SafeModeStatus, SafeModeTransition, SignatureType, StateSnapshot, Staleness, Subsystem, Token, Transaction, TransactionId, TransactionPayload, TransactionReceipt, DEFAULT_DIFF_SAMPLES, DEFAULT_PROFILE_FREQUENCY,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -23,8 +27,10 @@ pub struct BlockchainStatusResponse {
     pub transactions_per_second: f64,
     pub block_time: f64,
     pub active_validators: u32,
-    pub total_stake: u64,
+    #[serde(with = "crate::core::amount")]
+    pub total_stake: Amount,
     pub network_status: String,
+    pub safe_mode: SafeModeStatus,
     pub last_updated: String,
     pub version: String,
 }
@@ -35,13 +41,18 @@ pub struct TransactionResponse {
     pub id: String,
     pub sender: String,
     pub receiver: String,
-    pub amount: u64,
+    #[serde(with = "crate::core::amount")]
+    pub amount: Amount,
     pub timestamp: u64,
     pub status: String,
-    pub fee: u64,
+    #[serde(with = "crate::core::amount")]
+    pub fee: Amount,
     pub quantum_resistance_score: u32,
     pub parents: Vec<String>,
     pub confidence: f64,
+    /// Confidence the transaction must exceed to confirm, given its amount
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_confidence: Option<f64>,
 }
 
 /// DAG node response
@@ -56,14 +67,55 @@ pub struct DagNodeResponse {
     pub quantum_score: u32,
 }
 
+/// Validator row for explorer views
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidatorResponse {
+    pub id: String,
+    pub public_key: String,
+    #[serde(with = "crate::core::amount")]
+    pub stake_amount: Amount,
+    pub reputation_score: f64,
+    pub quantum_resistance_score: u32,
+    pub total_validations: u64,
+    pub successful_validations: u64,
+    pub is_active: bool,
+}
+
 /// Create transaction request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTransactionRequest {
     pub sender: String,
     pub receiver: String,
-    pub amount: u64,
-    pub fee: Option<u64>,
+    #[serde(with = "crate::core::amount")]
+    pub amount: Amount,
+    #[serde(default, with = "crate::core::amount::option")]
+    pub fee: Option<Amount>,
     pub metadata: Option<String>,
+    /// Contract, stake or governance action; plain transfer when absent
+    #[serde(default)]
+    pub payload: Option<TransactionPayload>,
+    /// Time budget in milliseconds, capped by the node's `submit_timeout_ms`
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Scheme to sign with instead of the value band's, e.g. `Falcon1024`
+    #[serde(default)]
+    pub signature_scheme: Option<SignatureType>,
+}
+
+/// One transaction in a bundle request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleMemberRequest {
+    #[serde(flatten)]
+    pub transaction: CreateTransactionRequest,
+    /// Indexes of earlier members this one approves
+    #[serde(default)]
+    pub approves: Vec<usize>,
+}
+
+/// Submit bundle request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateBundleRequest {
+    pub transactions: Vec<BundleMemberRequest>,
 }
 
 /// Create backup request
@@ -87,6 +139,180 @@ pub struct BackupQuery {
     pub limit: Option<usize>,
 }
 
+/// Filtered sync query parameters
+#[derive(Debug, Deserialize)]
+pub struct FilterSyncQuery {
+    pub from_segment: Option<u64>,
+    pub max_headers: Option<usize>,
+}
+
+/// Filter header query parameters
+#[derive(Debug, Deserialize)]
+pub struct FilterHeaderQuery {
+    pub from: Option<u64>,
+    pub count: Option<usize>,
+}
+
+/// CPU profile query parameters
+#[derive(Debug, Deserialize)]
+pub struct CpuProfileQuery {
+    pub seconds: Option<u64>,
+    pub frequency: Option<i32>,
+}
+
+/// Explorer response with the staleness of the data it was read from
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExplorerResponse<T> {
+    pub results: T,
+    pub staleness: Staleness,
+}
+
+/// Operator message query parameters
+#[derive(Debug, Deserialize)]
+pub struct OperatorMessageQuery {
+    pub peer: Option<String>,
+}
+
+/// Faucet funding request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FaucetFundingRequest {
+    pub address: String,
+    pub captcha_token: Option<String>,
+}
+
+/// Faucet top-up request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FaucetTopUpRequest {
+    #[serde(with = "crate::core::amount")]
+    pub amount: Amount,
+}
+
+/// Safe mode halt request
+#[derive(Debug, Deserialize)]
+pub struct SafeModeHaltRequest {
+    /// Operator name recorded in the audit trail
+    pub operator: Option<String>,
+    pub reason: String,
+}
+
+/// Safe mode state with its audit trail
+#[derive(Debug, Serialize)]
+pub struct SafeModeResponse {
+    pub status: SafeModeStatus,
+    pub transitions: Vec<SafeModeTransition>,
+}
+
+/// Snapshot diff query parameters
+#[derive(Debug, Deserialize)]
+pub struct SnapshotDiffQuery {
+    /// Divergent items listed per category
+    pub samples: Option<usize>,
+}
+
+/// Quarantined row listing query parameters
+#[derive(Debug, Deserialize)]
+pub struct CorruptedRowsQuery {
+    pub limit: Option<usize>,
+}
+
+/// Address transaction listing query parameters
+#[derive(Debug, Deserialize)]
+pub struct AddressTransactionsQuery {
+    /// Page number, from zero
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// Contract receipt listing query parameters
+#[derive(Debug, Deserialize)]
+pub struct ContractReceiptsQuery {
+    pub limit: Option<usize>,
+}
+
+/// Metadata search query parameters
+#[derive(Debug, Deserialize)]
+pub struct MetadataSearchQuery {
+    /// Terms `value` or `path=value`, all of which must match
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+/// SLO incident listing query parameters
+#[derive(Debug, Deserialize)]
+pub struct SloIncidentsQuery {
+    pub limit: Option<usize>,
+}
+
+/// Event journal query parameters
+#[derive(Debug, Deserialize)]
+pub struct JournalQuery {
+    /// Event type to list; all journaled types when absent
+    pub kind: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Event stream query parameters
+#[derive(Debug, Deserialize)]
+pub struct EventStreamQuery {
+    /// Comma-separated event types to stream; all when absent
+    pub types: Option<String>,
+}
+
+/// Selective disclosure query parameters
+#[derive(Debug, Deserialize)]
+pub struct DisclosureQuery {
+    /// Comma-separated attribute names to disclose
+    pub attributes: String,
+}
+
+/// Node attestation query parameters
+#[derive(Debug, Deserialize)]
+pub struct AttestationQuery {
+    /// Hex challenge to sign, chosen fresh by the client
+    pub challenge: String,
+}
+
+/// Traced contract call request
+#[derive(Debug, Deserialize)]
+pub struct DebugCallRequest {
+    pub contract_id: String,
+    pub function: String,
+    /// Hex call input
+    #[serde(default)]
+    pub input: String,
+    /// Hex caller address
+    pub caller: String,
+    #[serde(default, with = "crate::core::amount")]
+    pub value: Amount,
+    pub gas_limit: u64,
+}
+
+/// Inclusion proof query parameters
+#[derive(Debug, Deserialize)]
+pub struct InclusionProofQuery {
+    /// Finalized transaction to anchor at; the nearest one by default
+    pub checkpoint: Option<String>,
+}
+
+/// Send operator message request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SendOperatorMessageRequest {
+    pub recipient: String,
+    pub body: String,
+}
+
+/// Filter registration response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FilterRegistrationResponse {
+    pub subscription_id: String,
+}
+
+/// Bootstrap snapshot export or import request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BootstrapSnapshotRequest {
+    pub path: String,
+}
+
 /// Export database request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportDatabaseRequest {
@@ -94,6 +320,13 @@ pub struct ExportDatabaseRequest {
     pub format: Option<String>,
 }
 
+/// Asynchronous submission acknowledgement
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestionTicketResponse {
+    pub ticket: String,
+    pub status: IngestionStatus,
+}
+
 /// API response wrapper
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
@@ -119,6 +352,16 @@ impl ApiServer {
         // Clone blockchain for the routes
         let blockchain = self.blockchain.clone();
 
+        // Drain the intent log in the background
+        let ingestion_blockchain = self.blockchain.clone();
+        spawn_instrumented(Subsystem::Api, "ingestion", async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_millis(100));
+            loop {
+                interval.tick().await;
+                ingestion_blockchain.read().await.process_ingestion_queue(256).await;
+            }
+        });
+
         // CORS configuration
         let cors = warp::cors()
             .allow_any_origin()
@@ -156,11 +399,118 @@ impl ApiServer {
             .and(with_blockchain(blockchain.clone()))
             .and_then(create_transaction);
 
+        // Asynchronous submission routes
+        let transactions_async_post = warp::path!("transactions" / "async")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(create_transaction_async);
+
+        let transactions_bundle_post = warp::path!("transactions" / "bundle")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(create_bundle);
+
+        let ingestion_ticket_route = warp::path!("transactions" / "tickets" / String)
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_ingestion_ticket);
+
+        // Transactions by metadata, when the metadata index is on
+        let transaction_search_route = warp::path!("transactions" / "search")
+            .and(warp::get())
+            .and(warp::query::<MetadataSearchQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(search_transactions);
+
         let transaction_by_id = warp::path!("transactions" / String)
             .and(warp::get())
             .and(with_blockchain(blockchain.clone()))
             .and_then(get_transaction_by_id);
 
+        // Inclusion proof for light clients
+        let inclusion_proof_route = warp::path!("transactions" / String / "proof")
+            .and(warp::get())
+            .and(warp::query::<InclusionProofQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_inclusion_proof);
+
+        // Outcome of a finalized transaction
+        let receipt_route = warp::path!("transactions" / String / "receipt")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_transaction_receipt);
+
+        // Step-level contract traces, run without keeping state changes
+        let debug_transaction_route = warp::path!("transactions" / String / "debug_call")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(debug_transaction_call);
+
+        let debug_call_route = warp::path!("contracts" / "debug_call")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(debug_contract_call);
+
+        // Receipts of a contract's deploy and calls
+        let contract_receipts_route = warp::path!("contracts" / String / "receipts")
+            .and(warp::get())
+            .and(warp::query::<ContractReceiptsQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_contract_receipts);
+
+        // Contract events by contract, topic and finalized height
+        let events_route = warp::path!("events")
+            .and(warp::get())
+            .and(warp::query::<EventFilter>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_events);
+
+        // Account balance
+        let account_balance_route = warp::path!("accounts" / String / "balance")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_account_balance);
+
+        // Atomic swaps and issued tokens
+        let swap_route = warp::path!("swaps" / String)
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_swap);
+
+        let account_swaps_route = warp::path!("accounts" / String / "swaps")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_account_swaps);
+
+        let account_transactions_route = warp::path!("accounts" / String / "transactions")
+            .and(warp::get())
+            .and(warp::query::<AddressTransactionsQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_account_transactions);
+
+        let token_route = warp::path!("tokens" / String)
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_token);
+
+        let token_balance_route = warp::path!("tokens" / String / "balances" / String)
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_token_balance);
+
+        let archives_route = warp::path!("archives")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_archives);
+
+        let archive_route = warp::path!("archives" / String)
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(retrieve_archive);
+
         // DAG routes
         let dag_nodes = warp::path("dag")
             .and(warp::get())
@@ -184,9 +534,41 @@ impl ApiServer {
             .and(with_blockchain(blockchain.clone()))
             .and_then(get_identity_info);
 
+        // Selective disclosure endpoints
+        let disclosure_proof_route = warp::path!("identity" / "disclosure")
+            .and(warp::get())
+            .and(warp::query::<DisclosureQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_disclosure_proof);
+
+        let verify_disclosure_route = warp::path!("identity" / "disclosure" / "verify")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(verify_disclosure_proof);
+
+        // Signed statement of version, genesis and validator set
+        let attestation_route = warp::path("attestation")
+            .and(warp::get())
+            .and(warp::query::<AttestationQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_attestation);
+
+        // Governance participation credentials
+        let credential_route = warp::path!("governance" / "credentials" / String)
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_participation_credential);
+
+        let voter_credentials_route = warp::path!("governance" / "voters" / String / "credentials")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_voter_credentials);
+
         // Identity rotation endpoint
         let rotate_identity_route = warp::path("rotate-identity")
             .and(warp::post())
+            .and(with_admin_token())
             .and(with_blockchain(blockchain.clone()))
             .and_then(rotate_identity);
 
@@ -215,88 +597,506 @@ impl ApiServer {
             .and(with_blockchain(blockchain.clone()))
             .and_then(export_database);
 
-        // Metrics endpoint
-        let metrics_route = warp::path("metrics")
+        // Light client sync routes
+        let register_filter_route = warp::path!("sync" / "filters")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(register_address_filter);
+
+        let filtered_sync_route = warp::path!("sync" / "filters" / String)
             .and(warp::get())
+            .and(warp::query::<FilterSyncQuery>())
             .and(with_blockchain(blockchain.clone()))
-            .and_then(get_metrics);
+            .and_then(get_filtered_transactions);
 
-        // Combine all routes
-        let routes = health
-            .or(status_route)
-            .or(transactions_get)
-            .or(transactions_post)
-            .or(transaction_by_id)
-            .or(dag_nodes)
-            .or(dag_node_by_id)
-            .or(dag_tips)
-            .or(identity_route)
-            .or(rotate_identity_route)
-            .or(create_backup_route)
-            .or(restore_backup_route)
-            .or(list_backups_route)
-            .or(export_database_route)
-            .or(metrics_route)
-            .with(cors)
-            .with(warp::log("api"));
+        let filter_headers_route = warp::path!("sync" / "headers")
+            .and(warp::get())
+            .and(warp::query::<FilterHeaderQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_filter_headers);
 
-        // Start the server
-        warp::serve(routes)
-            .run(([0, 0, 0, 0], self.port))
-            .await;
+        let filter_segment_route = warp::path!("sync" / "segments" / u64)
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_filter_segment);
 
-        Ok(())
-    }
-}
+        // Explorer endpoints, served from the read replica when one is attached
+        let explorer_transactions_route = warp::path!("explorer" / "transactions")
+            .and(warp::get())
+            .and(warp::query::<TransactionQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_explorer_transactions);
 
-/// Helper function to inject blockchain into routes
-fn with_blockchain(
-    blockchain: Arc<RwLock<Blockchain>>,
-) -> impl Filter<Extract = (Arc<RwLock<Blockchain>>,), Error = Infallible> + Clone {
-    warp::any().map(move || blockchain.clone())
-}
+        let explorer_stats_route = warp::path!("explorer" / "stats")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_explorer_stats);
 
-/// Transaction query parameters
-#[derive(Debug, Deserialize)]
-struct TransactionQuery {
-    limit: Option<usize>,
-    offset: Option<usize>,
-    status: Option<String>,
-}
+        let explorer_dag_route = warp::path!("explorer" / "dag")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_explorer_dag);
 
-/// Get blockchain status
-async fn get_blockchain_status(
-    blockchain: Arc<RwLock<Blockchain>>,
-) -> Result<impl warp::Reply, warp::Rejection> {
-    let status = blockchain.read().await.get_status().await;
-    
-    let response = BlockchainStatusResponse {
-        total_transactions: status.total_transactions,
-        network_peers: status.network_peers,
-        consensus_height: status.consensus_height,
-        quantum_resistance_score: status.quantum_resistance_score,
-        transactions_per_second: 1250.0, // Mock TPS calculation
-        block_time: 3.2, // Mock block time
-        active_validators: 3,
-        total_stake: 15000,
-        network_status: "online".to_string(),
-        last_updated: chrono::Utc::now().to_rfc3339(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-    };
+        let explorer_validators_route = warp::path!("explorer" / "validators")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_explorer_validators);
 
-    Ok(warp::reply::json(&ApiResponse {
-        success: true,
-        data: Some(response),
-        error: None,
-        timestamp: chrono::Utc::now().to_rfc3339(),
-    }))
-}
+        let proposals_route = warp::path!("governance" / "proposals")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_governance_proposals);
 
-/// Get transactions
-async fn get_transactions(
-    query: TransactionQuery,
-    blockchain: Arc<RwLock<Blockchain>>,
-) -> Result<impl warp::Reply, warp::Rejection> {
+        // Embedded explorer UI, served when QDAG_EXPLORER_UI is set
+        let explorer_ui_route = warp::path!("explorer")
+            .and(warp::get())
+            .and(with_explorer_ui())
+            .map(|| warp::reply::html(EXPLORER_INDEX_HTML));
+
+        let explorer_assets_route = warp::path!("explorer" / "assets" / String)
+            .and(warp::get())
+            .and(with_explorer_ui())
+            .and_then(get_explorer_asset);
+
+        // Peer scoring endpoint
+        let network_peers_route = warp::path!("network" / "peers")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_network_peers);
+
+        let spam_filter_route = warp::path!("network" / "spam-filter")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_spam_filter_stats);
+
+        let peer_capabilities_route = warp::path!("network" / "capabilities")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_peer_capabilities);
+
+        let congestion_route = warp::path!("network" / "congestion")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_congestion);
+
+        let orphans_route = warp::path!("network" / "orphans")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_orphan_stats);
+
+        let slo_route = warp::path!("consensus" / "slo")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_slo);
+
+        let slo_incidents_route = warp::path!("consensus" / "slo" / "incidents")
+            .and(warp::get())
+            .and(warp::query::<SloIncidentsQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_slo_incidents);
+
+        // Admin profiling endpoints
+        let cpu_profile_route = warp::path!("admin" / "profile" / "cpu")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(warp::query::<CpuProfileQuery>())
+            .and_then(get_cpu_profile);
+
+        let heap_profile_route = warp::path!("admin" / "profile" / "heap")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and_then(get_heap_stats);
+
+        let tasks_route = warp::path!("admin" / "tasks")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and_then(get_task_stats);
+
+        // Admin settings endpoints
+        let get_settings_route = warp::path!("admin" / "config")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_settings);
+
+        let reload_settings_route = warp::path!("admin" / "config")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(reload_settings);
+
+        // Admin operator messaging endpoints
+        let list_contacts_route = warp::path!("admin" / "contacts")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_operator_contacts);
+
+        let add_contact_route = warp::path!("admin" / "contacts")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(add_operator_contact);
+
+        let list_messages_route = warp::path!("admin" / "messages")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(warp::query::<OperatorMessageQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_operator_messages);
+
+        let send_message_route = warp::path!("admin" / "messages")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(send_operator_message);
+
+        let read_message_route = warp::path!("admin" / "messages" / String / "read")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(mark_operator_message_read);
+
+        // Faucet endpoints (devnet and testnet only)
+        let faucet_route = warp::path!("faucet")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(warp::addr::remote())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(request_faucet_funds);
+
+        let faucet_stats_route = warp::path!("faucet" / "stats")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_faucet_stats);
+
+        let faucet_top_up_route = warp::path!("admin" / "faucet" / "top-up")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(top_up_faucet);
+
+        // State snapshots for upgrade validation
+        let snapshot_route = warp::path!("admin" / "snapshot")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_state_snapshot);
+
+        let snapshot_diff_route = warp::path!("admin" / "snapshot" / "diff")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::query::<SnapshotDiffQuery>())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(diff_state_snapshot);
+
+        // Bootstrap snapshots for new nodes
+        let export_bootstrap_route = warp::path!("admin" / "bootstrap" / "export")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(export_bootstrap_snapshot);
+
+        let import_bootstrap_route = warp::path!("admin" / "bootstrap" / "import")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(import_bootstrap_snapshot);
+
+        // Rows quarantined after failing checksum verification
+        let corrupted_rows_route = warp::path!("admin" / "storage" / "corrupted")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(warp::query::<CorruptedRowsQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_corrupted_rows);
+
+        let journal_route = warp::path!("admin" / "journal")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(warp::query::<JournalQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_event_journal);
+
+        // Safe mode: reads stay available while transaction acceptance is halted
+        let safe_mode_route = warp::path!("safe-mode")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_safe_mode);
+
+        let halt_route = warp::path!("admin" / "safe-mode" / "halt")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(halt_node);
+
+        let resume_route = warp::path!("admin" / "safe-mode" / "resume")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(resume_node);
+
+        // Rebuild of derived tables, run online in the background
+        let reindex_status_route = warp::path!("admin" / "reindex")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_reindex_status);
+
+        let reindex_route = warp::path!("admin" / "reindex")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(warp::body::json())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(start_reindex);
+
+        // Pruning of old finalized transactions
+        let prune_route = warp::path!("admin" / "prune")
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(prune_dag);
+
+        // Vacuum, analyze and WAL checkpoint jobs
+        let maintenance_route = warp::path!("admin" / "maintenance")
+            .and(warp::get())
+            .and(with_admin_token())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_maintenance_runs);
+
+        let run_maintenance_route = warp::path!("admin" / "maintenance" / String)
+            .and(warp::post())
+            .and(with_admin_token())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(run_maintenance);
+
+        let checkpoint_roots_route = warp::path!("checkpoints" / "roots")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_checkpoint_roots);
+
+        let finality_route = warp::path!("consensus" / "finality")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_finality_status);
+
+        let sampling_route = warp::path!("consensus" / "sampling")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_sampling_status);
+
+        // Live node events over WebSocket
+        let events_ws_route = warp::path!("events" / "ws")
+            .and(warp::ws())
+            .and(warp::query::<EventStreamQuery>())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(stream_events);
+
+        // Metrics endpoint
+        let metrics_route = warp::path("metrics")
+            .and(warp::get())
+            .and(with_blockchain(blockchain.clone()))
+            .and_then(get_metrics);
+
+        // Combine all routes
+        let routes = health
+            .or(status_route)
+            .or(transactions_async_post)
+            .or(transactions_bundle_post)
+            .or(ingestion_ticket_route)
+            .or(transactions_get)
+            .or(transactions_post)
+            .or(transaction_search_route)
+            .or(transaction_by_id)
+            .or(inclusion_proof_route)
+            .or(receipt_route)
+            .or(debug_transaction_route)
+            .or(debug_call_route)
+            .or(contract_receipts_route)
+            .or(events_route)
+            .or(account_balance_route)
+            .or(swap_route)
+            .or(account_swaps_route)
+            .or(account_transactions_route)
+            .or(token_route)
+            .or(token_balance_route)
+            .or(archives_route)
+            .or(archive_route)
+            .or(dag_nodes)
+            .or(dag_node_by_id)
+            .or(dag_tips)
+            .or(disclosure_proof_route)
+            .or(verify_disclosure_route)
+            .or(identity_route)
+            .or(attestation_route)
+            .or(credential_route)
+            .or(voter_credentials_route)
+            .or(rotate_identity_route)
+            .or(create_backup_route)
+            .or(restore_backup_route)
+            .or(list_backups_route)
+            .or(export_database_route)
+            .or(register_filter_route)
+            .or(filtered_sync_route)
+            .or(filter_headers_route)
+            .or(filter_segment_route)
+            .or(explorer_transactions_route)
+            .or(explorer_stats_route)
+            .or(explorer_dag_route)
+            .or(explorer_validators_route)
+            .or(proposals_route)
+            .or(explorer_ui_route)
+            .or(explorer_assets_route)
+            .or(network_peers_route)
+            .or(spam_filter_route)
+            .or(peer_capabilities_route)
+            .or(congestion_route)
+            .or(orphans_route)
+            .or(slo_route)
+            .or(slo_incidents_route)
+            .or(cpu_profile_route)
+            .or(heap_profile_route)
+            .or(tasks_route)
+            .or(get_settings_route)
+            .or(reload_settings_route)
+            .or(list_contacts_route)
+            .or(add_contact_route)
+            .or(list_messages_route)
+            .or(send_message_route)
+            .or(read_message_route)
+            .or(faucet_route)
+            .or(faucet_stats_route)
+            .or(faucet_top_up_route)
+            .or(snapshot_route)
+            .or(snapshot_diff_route)
+            .or(export_bootstrap_route)
+            .or(import_bootstrap_route)
+            .or(corrupted_rows_route)
+            .or(journal_route)
+            .or(safe_mode_route)
+            .or(halt_route)
+            .or(resume_route)
+            .or(reindex_status_route)
+            .or(reindex_route)
+            .or(prune_route)
+            .or(maintenance_route)
+            .or(run_maintenance_route)
+            .or(checkpoint_roots_route)
+            .or(finality_route)
+            .or(sampling_route)
+            .or(events_ws_route)
+            .or(metrics_route)
+            .with(cors)
+            .with(warp::log("api"));
+
+        // Start the server
+        warp::serve(routes)
+            .run(([0, 0, 0, 0], self.port))
+            .await;
+
+        Ok(())
+    }
+}
+
+/// Helper function to inject blockchain into routes
+fn with_blockchain(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> impl Filter<Extract = (Arc<RwLock<Blockchain>>,), Error = Infallible> + Clone {
+    warp::any().map(move || blockchain.clone())
+}
+
+/// Only let requests through when they carry the `QDAG_ADMIN_TOKEN` value in
+/// `x-admin-token`; admin routes are hidden when no token is configured
+fn with_admin_token() -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("x-admin-token")
+        .and_then(|token: Option<String>| async move {
+            match std::env::var("QDAG_ADMIN_TOKEN") {
+                Ok(expected) if !expected.is_empty() && token.as_deref() == Some(expected.as_str()) => Ok(()),
+                _ => Err(warp::reject::not_found()),
+            }
+        })
+        .untuple_one()
+}
+
+/// Bundled explorer UI assets
+const EXPLORER_INDEX_HTML: &str = include_str!("explorer/index.html");
+const EXPLORER_JS: &str = include_str!("explorer/explorer.js");
+const EXPLORER_CSS: &str = include_str!("explorer/explorer.css");
+
+/// Only pass when `QDAG_EXPLORER_UI` is set; the UI is hidden otherwise
+fn with_explorer_ui() -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::any()
+        .and_then(|| async move {
+            match std::env::var("QDAG_EXPLORER_UI") {
+                Ok(value) if !value.is_empty() && value != "0" => Ok(()),
+                _ => Err(warp::reject::not_found()),
+            }
+        })
+        .untuple_one()
+}
+
+/// Serve a bundled explorer asset
+async fn get_explorer_asset(name: String) -> Result<impl warp::Reply, warp::Rejection> {
+    let (body, content_type) = match name.as_str() {
+        "explorer.js" => (EXPLORER_JS, "application/javascript; charset=utf-8"),
+        "explorer.css" => (EXPLORER_CSS, "text/css; charset=utf-8"),
+        _ => return Err(warp::reject::not_found()),
+    };
+    Ok(warp::reply::with_header(body, "content-type", content_type))
+}
+
+/// Transaction query parameters
+#[derive(Debug, Deserialize)]
+struct TransactionQuery {
+    limit: Option<usize>,
+    offset: Option<usize>,
+    status: Option<String>,
+}
+
+/// Get blockchain status
+async fn get_blockchain_status(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let status = blockchain.read().await.get_status().await;
+    
+    let response = BlockchainStatusResponse {
+        total_transactions: status.total_transactions,
+        network_peers: status.network_peers,
+        consensus_height: status.consensus_height,
+        quantum_resistance_score: status.quantum_resistance_score,
+        transactions_per_second: 1250.0, // Mock TPS calculation
+        block_time: 3.2, // Mock block time
+        active_validators: 3,
+        total_stake: 15000,
+        network_status: if status.safe_mode.active { "safe_mode" } else { "online" }.to_string(),
+        safe_mode: status.safe_mode,
+        last_updated: chrono::Utc::now().to_rfc3339(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(response),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get transactions
+async fn get_transactions(
+    query: TransactionQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
     let limit = query.limit.unwrap_or(10);
     let offset = query.offset.unwrap_or(0);
     
@@ -311,260 +1111,1797 @@ async fn get_transactions(
     }))
 }
 
-/// Create transaction
-async fn create_transaction(
-    request: CreateTransactionRequest,
+/// Create transaction
+async fn create_transaction(
+    request: CreateTransactionRequest,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let deadline = request.timeout_ms
+        .map(|ms| Deadline::after(std::time::Duration::from_millis(ms)))
+        .unwrap_or_else(Deadline::never);
+    let scheme = request.signature_scheme.clone();
+    let transaction = match build_transaction(request, &*blockchain.read().await) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            return Ok(warp::reply::json(&ApiResponse::<String> {
+                success: false,
+                data: None,
+                error: Some(format!("Failed to create transaction: {}", e)),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+    };
+    
+    // Submit to blockchain
+    let blockchain = blockchain.write().await;
+    let submitted = match scheme {
+        Some(scheme) => blockchain.submit_transaction_with_scheme(transaction, scheme, deadline).await,
+        None => blockchain.submit_transaction_with_deadline(transaction, deadline).await,
+    };
+    match submitted {
+        Ok(tx_id) => {
+            Ok(warp::reply::json(&ApiResponse {
+                success: true,
+                data: Some(tx_id.as_string()),
+                error: None,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+        Err(e) => {
+            Ok(warp::reply::json(&ApiResponse::<String> {
+                success: false,
+                data: None,
+                error: Some(format!("Failed to create transaction: {}", e)),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+    }
+}
+
+/// Accept a transaction into the intent log and return a ticket
+async fn create_transaction_async(
+    request: CreateTransactionRequest,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<Box<dyn warp::Reply>, warp::Rejection> {
+    let blockchain = blockchain.read().await;
+    let transaction = match build_transaction(request, &blockchain) {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            return Ok(Box::new(warp::reply::json(&ApiResponse::<IngestionTicketResponse> {
+                success: false,
+                data: None,
+                error: Some(format!("Failed to create transaction: {}", e)),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            })))
+        }
+    };
+
+    match blockchain.submit_transaction_async(transaction).await {
+        Ok(ticket) => {
+            let reply = warp::reply::json(&ApiResponse {
+                success: true,
+                data: Some(IngestionTicketResponse {
+                    ticket: ticket.as_string(),
+                    status: IngestionStatus::Queued,
+                }),
+                error: None,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+            Ok(Box::new(warp::reply::with_status(reply, warp::http::StatusCode::ACCEPTED)))
+        }
+        Err(BlockchainError::Core(CoreError::Backpressure(retry_after))) => {
+            let reply = warp::reply::json(&ApiResponse::<IngestionTicketResponse> {
+                success: false,
+                data: None,
+                error: Some("Ingestion queue is full".to_string()),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+            let reply = warp::reply::with_header(reply, "Retry-After", retry_after.to_string());
+            Ok(Box::new(warp::reply::with_status(reply, warp::http::StatusCode::SERVICE_UNAVAILABLE)))
+        }
+        Err(e) => {
+            Ok(Box::new(warp::reply::json(&ApiResponse::<IngestionTicketResponse> {
+                success: false,
+                data: None,
+                error: Some(format!("Failed to accept transaction: {}", e)),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            })))
+        }
+    }
+}
+
+/// Poll the status of an asynchronous submission
+async fn get_ingestion_ticket(
+    ticket: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let status = match IngestionTicket::parse(&ticket) {
+        Some(parsed) => blockchain.read().await.get_ingestion_status(&parsed).await,
+        None => None,
+    };
+
+    match status {
+        Some(status) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(IngestionTicketResponse { ticket, status }),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        None => Ok(warp::reply::json(&ApiResponse::<IngestionTicketResponse> {
+            success: false,
+            data: None,
+            error: Some("Ticket not found or expired".to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Register a light client address filter
+async fn register_address_filter(
+    filter: BloomFilter,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.register_address_filter(filter).await {
+        Ok(id) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(FilterRegistrationResponse { subscription_id: id.to_string() }),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<FilterRegistrationResponse> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get transactions matching a registered filter
+async fn get_filtered_transactions(
+    subscription_id: String,
+    query: FilterSyncQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = match uuid::Uuid::parse_str(&subscription_id) {
+        Ok(id) => {
+            blockchain.read().await.get_filtered_transactions(
+                &id,
+                query.from_segment.unwrap_or(0),
+                query.max_headers.unwrap_or(100).min(1000),
+            ).await
+        }
+        Err(_) => Err(BlockchainError::Core(CoreError::InvalidFilter("malformed subscription id".to_string()))),
+    };
+
+    match result {
+        Ok(sync) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(sync),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<crate::FilteredSync> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get filter headers
+async fn get_filter_headers(
+    query: FilterHeaderQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let headers = blockchain.read().await
+        .get_filter_headers(query.from.unwrap_or(0), query.count.unwrap_or(100).min(2000))
+        .await;
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(headers),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get the compact filter of a sealed segment
+async fn get_filter_segment(
+    index: u64,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_filter_segment(index).await {
+        Some(segment) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(segment),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        None => Ok(warp::reply::json(&ApiResponse::<crate::FilterSegment> {
+            success: false,
+            data: None,
+            error: Some("Segment not found or not sealed yet".to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// List stored transactions for the explorer
+async fn get_explorer_transactions(
+    query: TransactionQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = query.limit.unwrap_or(10).min(1000);
+    let result = blockchain
+        .read()
+        .await
+        .explore_transactions(Some(limit), query.offset, query.status.as_deref())
+        .await;
+
+    match result {
+        Ok((transactions, staleness)) => {
+            let results: Vec<TransactionResponse> = transactions
+                .iter()
+                .map(|tx| TransactionResponse {
+                    id: tx.id.as_string(),
+                    sender: hex::encode(&tx.sender),
+                    receiver: hex::encode(&tx.receiver),
+                    amount: tx.amount,
+                    timestamp: tx.timestamp,
+                    status: query.status.clone().unwrap_or_else(|| "unknown".to_string()),
+                    fee: tx.fee,
+                    quantum_resistance_score: tx.quantum_proof.resistance_score,
+                    parents: tx.parents.iter().map(|p| p.as_string()).collect(),
+                    confidence: 0.0,
+                    required_confidence: None,
+                })
+                .collect();
+
+            Ok(warp::reply::json(&ApiResponse {
+                success: true,
+                data: Some(ExplorerResponse { results, staleness }),
+                error: None,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<ExplorerResponse<Vec<TransactionResponse>>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Database statistics for the explorer
+async fn get_explorer_stats(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.explore_stats().await {
+        Ok((results, staleness)) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(ExplorerResponse { results, staleness }),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<ExplorerResponse<DatabaseStats>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get statistics of the in-memory DAG
+async fn get_explorer_dag(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let stats = blockchain.read().await.get_dag_stats().await;
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(stats),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get the consensus validator set
+async fn get_explorer_validators(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let validators: Vec<ValidatorResponse> = blockchain
+        .read()
+        .await
+        .get_validators()
+        .into_iter()
+        .map(|v| ValidatorResponse {
+            id: v.id,
+            public_key: hex::encode(&v.public_key),
+            stake_amount: v.stake_amount,
+            reputation_score: v.reputation_score,
+            quantum_resistance_score: v.quantum_resistance_score,
+            total_validations: v.total_validations,
+            successful_validations: v.successful_validations,
+            is_active: v.is_active,
+        })
+        .collect();
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(validators),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// List governance proposals
+async fn get_governance_proposals(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.list_proposals().await {
+        Ok(proposals) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(proposals),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<crate::governance::proposals::Proposal>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get peers with their misbehavior scores
+async fn get_network_peers(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let scores = blockchain.read().await.get_peer_scores().await;
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(scores),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Local protocol capabilities and what connected peers negotiated
+#[derive(Debug, Serialize)]
+struct PeerCapabilitiesResponse {
+    local: Capabilities,
+    peers: CapabilityDistribution,
+}
+
+async fn get_peer_capabilities(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (local, peers) = blockchain.read().await.get_peer_capabilities().await;
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(PeerCapabilitiesResponse { local, peers }),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get gossip spam filter effectiveness counters
+async fn get_spam_filter_stats(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let stats = blockchain.read().await.get_spam_filter_stats();
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(stats),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get mempool depth, confirmation latency percentiles and fee rates
+async fn get_orphan_stats(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let stats = blockchain.read().await.get_orphan_stats().await;
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(stats),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+async fn get_congestion(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let report = blockchain.read().await.congestion_report().await;
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(report),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get compliance of recent consensus rounds with the configured SLOs
+async fn get_slo(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let report = blockchain.read().await.slo_report();
+
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(report),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// List recent SLO breaches and recoveries
+async fn get_slo_incidents(
+    query: SloIncidentsQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_slo_incidents(query.limit.unwrap_or(100)).await {
+        Ok(incidents) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(incidents),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<JournalEntry>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Receipts of transactions that deployed or called a contract, newest first
+async fn get_contract_receipts(
+    contract_id: String,
+    query: ContractReceiptsQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_receipts_by_contract(&contract_id, query.limit.unwrap_or(100)).await {
+        Ok(receipts) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(receipts),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<TransactionReceipt>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Contract events matching the query, oldest first
+async fn get_events(
+    filter: EventFilter,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_events(&filter).await {
+        Ok(events) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(events),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<EventLog>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Submit a bundle of transactions, accepted all or none
+async fn create_bundle(
+    request: CreateBundleRequest,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let failed = |e: String| {
+        Ok::<_, warp::Rejection>(warp::reply::json(&ApiResponse::<Vec<String>> {
+            success: false,
+            data: None,
+            error: Some(format!("Failed to submit bundle: {}", e)),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }))
+    };
+
+    let mut transactions: Vec<Transaction> = Vec::with_capacity(request.transactions.len());
+    {
+        let blockchain = blockchain.read().await;
+        for (index, member) in request.transactions.into_iter().enumerate() {
+            let fee = member.transaction.fee;
+            let mut transaction = match build_transaction(member.transaction, &blockchain) {
+                Ok(transaction) => transaction,
+                Err(e) => return failed(e.to_string()),
+            };
+            for approved in member.approves {
+                if approved >= index {
+                    return failed(format!("member {} can only approve earlier members, not {}", index, approved));
+                }
+                transaction.parents.push(transactions[approved].id.clone());
+            }
+            // Parents add to the size the fee is estimated on
+            if fee.is_none() {
+                transaction.fee = blockchain.estimate_fee(&transaction).minimum;
+            }
+            transaction.id = transaction.compute_id();
+            transactions.push(transaction);
+        }
+    }
+
+    match blockchain.write().await.submit_bundle(transactions).await {
+        Ok(tx_ids) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(tx_ids.iter().map(|tx_id| tx_id.as_string()).collect::<Vec<_>>()),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => failed(e.to_string()),
+    }
+}
+
+/// Build an unsigned transaction from an API request
+///
+/// Without a requested fee the transaction pays the node's minimum. A
+/// payload is added to the metadata, which must then be a JSON object.
+fn build_transaction(request: CreateTransactionRequest, blockchain: &Blockchain) -> Result<Transaction, CoreError> {
+    // Convert hex strings to bytes
+    let sender = hex::decode(&request.sender).unwrap_or_default();
+    let receiver = hex::decode(&request.receiver).unwrap_or_default();
+    let fee = request.fee;
+
+    let mut transaction = Transaction {
+        id: TransactionId::new(),
+        sender,
+        receiver,
+        amount: request.amount,
+        fee: 0,
+        nonce: rand::random(),
+        timestamp: chrono::Utc::now().timestamp() as u64,
+        parents: vec![], // Will be filled by blockchain
+        signature: vec![0u8; 64], // Placeholder
+        signature_scheme: Default::default(),
+        quantum_proof: crate::core::QuantumProof {
+            prime_hash: vec![0u8; 32],
+            resistance_score: 80,
+            proof_timestamp: chrono::Utc::now().timestamp() as u64,
+        },
+        metadata: request.metadata.map(|s| s.into_bytes()),
+    };
+    if let Some(payload) = &request.payload {
+        payload.attach(&mut transaction)?;
+    }
+    transaction.fee = fee.unwrap_or_else(|| blockchain.estimate_fee(&transaction).minimum);
+    Ok(transaction)
+}
+
+/// Get transaction by ID
+async fn get_transaction_by_id(
+    tx_id: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let tx_id = match TransactionId::from_bytes(&hex::decode(&tx_id).unwrap_or_default()) {
+        Ok(id) => id,
+        Err(_) => {
+            return Ok(warp::reply::json(&ApiResponse::<TransactionResponse> {
+                success: false,
+                data: None,
+                error: Some("Invalid transaction ID".to_string()),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+    };
+    
+    let blockchain = blockchain.read().await;
+    match blockchain.get_transaction(&tx_id).await {
+        Ok(Some(tx)) => {
+            let confirmation = blockchain.get_confirmation_status(&tx_id).await.ok().flatten();
+            let response = TransactionResponse {
+                id: tx.id.as_string(),
+                sender: hex::encode(&tx.sender),
+                receiver: hex::encode(&tx.receiver),
+                amount: tx.amount,
+                timestamp: tx.timestamp,
+                status: confirmation.as_ref().map_or_else(|| "pending".to_string(), |c| format!("{:?}", c.status)),
+                fee: tx.fee,
+                quantum_resistance_score: tx.quantum_proof.resistance_score,
+                parents: tx.parents.iter().map(|p| p.as_string()).collect(),
+                confidence: confirmation.as_ref().map_or(0.0, |c| c.confidence),
+                required_confidence: confirmation.map(|c| c.required_confidence),
+            };
+            
+            Ok(warp::reply::json(&ApiResponse {
+                success: true,
+                data: Some(response),
+                error: None,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+        Ok(None) => {
+            Ok(warp::reply::json(&ApiResponse::<TransactionResponse> {
+                success: false,
+                data: None,
+                error: Some("Transaction not found".to_string()),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+        Err(e) => {
+            Ok(warp::reply::json(&ApiResponse::<TransactionResponse> {
+                success: false,
+                data: None,
+                error: Some(format!("Failed to get transaction: {}", e)),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+    }
+}
+
+/// Get DAG nodes
+async fn get_dag_nodes(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let dag = blockchain.read().await;
+    let nodes = generate_mock_dag_nodes(10);
+    
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(nodes),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get DAG node by ID
+async fn get_dag_node_by_id(
+    node_id: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // For now, return mock data
+    let node = DagNodeResponse {
+        id: node_id,
+        transaction: TransactionResponse {
+            id: node_id,
+            sender: "0x1234".to_string(),
+            receiver: "0x5678".to_string(),
+            amount: 100,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            status: "confirmed".to_string(),
+            fee: 1,
+            quantum_resistance_score: 85,
+            parents: vec![],
+            confidence: 0.95,
+            required_confidence: None,
+        },
+        children: vec!["child1".to_string(), "child2".to_string()],
+        weight: 100,
+        confidence: 0.95,
+        status: "confirmed".to_string(),
+        quantum_score: 85,
+    };
+    
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(node),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get DAG tips
+async fn get_dag_tips(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let dag = blockchain.read().await;
+    let tips = dag.get_tips();
+    
+    let tip_responses: Vec<DagNodeResponse> = tips.iter().take(5).map(|node| {
+        DagNodeResponse {
+            id: node.transaction.id.as_string(),
+            transaction: TransactionResponse {
+                id: node.transaction.id.as_string(),
+                sender: hex::encode(&node.transaction.sender),
+                receiver: hex::encode(&node.transaction.receiver),
+                amount: node.transaction.amount,
+                timestamp: node.transaction.timestamp,
+                status: format!("{:?}", node.status),
+                fee: node.transaction.fee,
+                quantum_resistance_score: node.transaction.quantum_proof.resistance_score,
+                parents: node.transaction.parents.iter().map(|p| p.as_string()).collect(),
+                confidence: node.confidence,
+                required_confidence: None,
+            },
+            children: node.children.iter().map(|c| c.as_string()).collect(),
+            weight: node.weight,
+            confidence: node.confidence,
+            status: format!("{:?}", node.status),
+            quantum_score: node.quantum_score,
+        }
+    }).collect();
+    
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(tip_responses),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get node identity information
+async fn get_identity_info(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_identity_info().await {
+        Ok(identity_info) => {
+            Ok(warp::reply::json(&ApiResponse {
+                success: true,
+                data: Some(identity_info),
+                error: None,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+        Err(e) => {
+            Ok(warp::reply::json(&ApiResponse::<IdentityInfo> {
+                success: false,
+                data: None,
+                error: Some(format!("Failed to get identity info: {}", e)),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+    }
+}
+
+/// Pay test funds to an address
+async fn request_faucet_funds(
+    request: FaucetFundingRequest,
+    remote: Option<std::net::SocketAddr>,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<Box<dyn warp::Reply>, warp::Rejection> {
+    let request = FaucetRequest {
+        address: request.address,
+        ip: remote.map(|addr| addr.ip()),
+        captcha_token: request.captcha_token,
+    };
+
+    match blockchain.read().await.request_faucet_funds(request).await {
+        Ok(grant) => Ok(Box::new(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(grant),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }))),
+        Err(BlockchainError::Faucet(FaucetError::RateLimited(retry_after))) => {
+            let reply = warp::reply::json(&ApiResponse::<FaucetGrant> {
+                success: false,
+                data: None,
+                error: Some(FaucetError::RateLimited(retry_after).to_string()),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+            let reply = warp::reply::with_header(reply, "Retry-After", retry_after.to_string());
+            Ok(Box::new(warp::reply::with_status(reply, warp::http::StatusCode::TOO_MANY_REQUESTS)))
+        }
+        Err(e) => Ok(Box::new(warp::reply::json(&ApiResponse::<FaucetGrant> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }))),
+    }
+}
+
+/// Get faucet statistics
+async fn get_faucet_stats(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_faucet_stats().await {
+        Ok(stats) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(stats),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<FaucetStats> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Add funds to the faucet balance
+async fn top_up_faucet(
+    request: FaucetTopUpRequest,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.top_up_faucet(request.amount).await {
+        Ok(balance) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(balance.to_string()),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<String> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Snapshot the node's finalized state
+async fn get_state_snapshot(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let snapshot = blockchain.read().await.capture_state_snapshot().await;
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(snapshot),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Compare the node's current state against a snapshot taken earlier
+async fn diff_state_snapshot(
+    query: SnapshotDiffQuery,
+    before: StateSnapshot,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let samples = query.samples.unwrap_or(DEFAULT_DIFF_SAMPLES);
+    let diff = blockchain.read().await.diff_state_snapshot(&before, samples).await;
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(diff),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Write a bootstrap snapshot of the finalized state
+async fn export_bootstrap_snapshot(
+    request: BootstrapSnapshotRequest,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    bootstrap_reply(blockchain.read().await.export_snapshot(&request.path).await)
+}
+
+/// Bootstrap this node from a snapshot
+async fn import_bootstrap_snapshot(
+    request: BootstrapSnapshotRequest,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    bootstrap_reply(blockchain.read().await.import_snapshot(&request.path).await)
+}
+
+fn bootstrap_reply(result: Result<BootstrapInfo, BlockchainError>) -> Result<warp::reply::Json, warp::Rejection> {
+    match result {
+        Ok(info) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(info),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<BootstrapInfo> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// List rows quarantined after failing checksum verification
+async fn get_corrupted_rows(
+    query: CorruptedRowsQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_corrupted_rows(query.limit.unwrap_or(100)).await {
+        Ok(rows) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(rows),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<CorruptedRow>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// List journaled events for postmortems
+async fn get_event_journal(
+    query: JournalQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = query.limit.unwrap_or(100);
+    match blockchain.read().await.get_event_journal(query.kind.as_deref(), limit).await {
+        Ok(entries) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(entries),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<JournalEntry>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get the safe mode state and its transitions
+async fn get_safe_mode(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let blockchain = blockchain.read().await;
+    let response = SafeModeResponse {
+        status: blockchain.get_status().await.safe_mode,
+        transitions: blockchain.get_safe_mode_transitions().await,
+    };
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(response),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Halt transaction acceptance
+async fn halt_node(
+    request: SafeModeHaltRequest,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let operator = request.operator.unwrap_or_else(|| "admin".to_string());
+    safe_mode_reply(blockchain.read().await.halt(&operator, &request.reason).await)
+}
+
+/// Resume transaction acceptance with a signed authorization
+async fn resume_node(
+    authorization: ResumeAuthorization,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    safe_mode_reply(blockchain.read().await.resume(&authorization).await)
+}
+
+/// Get the progress of the running reindex or the outcome of the last one
+async fn get_reindex_status(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let status = blockchain.read().await.get_reindex_status();
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(status),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Prune old finalized transactions now
+async fn prune_dag(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.prune_dag().await {
+        Ok(report) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(report),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<PruneReport> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Last run of each database maintenance job
+async fn get_maintenance_runs(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.maintenance_runs().await {
+        Ok(runs) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(runs),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<MaintenanceRun>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Run a database maintenance job now
+async fn run_maintenance(
+    job: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = match job.parse::<MaintenanceJob>() {
+        Ok(job) => blockchain.read().await.run_maintenance(job).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(run) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(run),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<MaintenanceRun> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Finalized transactions left at the pruned boundary
+async fn get_checkpoint_roots(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let roots = blockchain.read().await.get_checkpoint_roots().await;
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(roots.iter().map(TransactionId::as_string).collect::<Vec<_>>()),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+async fn get_finality_status(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let status = blockchain.read().await.get_finality_status();
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(status),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+async fn get_sampling_status(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let status = blockchain.read().await.get_sampling_status();
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(status),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Start rebuilding derived tables in the background
+async fn start_reindex(
+    config: ReindexConfig,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let blockchain = blockchain.read().await;
+    match blockchain.start_reindex(config) {
+        Ok(()) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(blockchain.get_reindex_status()),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<ReindexStatus> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+fn safe_mode_reply(result: Result<SafeModeTransition, BlockchainError>) -> Result<warp::reply::Json, warp::Rejection> {
+    match result {
+        Ok(transition) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(transition),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<SafeModeTransition> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Stream node events to a WebSocket client as JSON messages
+async fn stream_events(
+    ws: warp::ws::Ws,
+    query: EventStreamQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut events = blockchain.read().await.subscribe_events();
+    let types: Vec<String> = query.types.unwrap_or_default()
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    Ok(ws.on_upgrade(move |socket| async move {
+        use futures::{SinkExt, StreamExt};
+        use tokio::sync::broadcast::error::RecvError;
+
+        let (mut sender, mut receiver) = socket.split();
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        if !types.is_empty() && !types.iter().any(|t| t.eq_ignore_ascii_case(event.kind())) {
+                            continue;
+                        }
+                        let Ok(json) = serde_json::to_string(&event) else { continue };
+                        if sender.send(warp::ws::Message::text(json)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        log::warn!("📣 Event stream client fell behind and missed {} event(s)", missed);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                message = receiver.next() => match message {
+                    Some(Ok(message)) if !message.is_close() => {}
+                    _ => break,
+                },
+            }
+        }
+    }))
+}
+
+/// Prove selected identity metadata attributes
+async fn get_disclosure_proof(
+    query: DisclosureQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let names: Vec<&str> = query.attributes.split(',').map(str::trim).filter(|name| !name.is_empty()).collect();
+
+    match blockchain.read().await.prove_identity_attributes(&names).await {
+        Ok(proof) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(proof),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<DisclosureProof> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get an account's finalized balance and the amount held by pending transfers
+async fn get_account_balance(
+    address: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_account_balance(&address).await {
+        Ok(balance) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(balance),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<AccountBalance> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get an atomic swap by its hashlock
+async fn get_swap(
+    hashlock: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_swap(&hashlock).await {
+        Ok(swap) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(swap),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<AtomicSwap> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get the atomic swaps an account is party to
+async fn get_account_swaps(
+    address: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_swaps(&address).await {
+        Ok(swaps) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(swaps),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<AtomicSwap>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get transactions an address sent or received, newest first
+async fn get_account_transactions(
+    address: String,
+    query: AddressTransactionsQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = query.limit.unwrap_or(50).min(1000);
+    match blockchain.read().await.get_transactions_by_address(&address, query.page.unwrap_or(0), limit).await {
+        Ok(transactions) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(transactions),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<Transaction>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Transactions whose metadata matches the query, newest first
+async fn search_transactions(
+    query: MetadataSearchQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.search_transactions(&query.q, query.limit.unwrap_or(50)).await {
+        Ok(transactions) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(transactions),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<Transaction>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get an issued token
+async fn get_token(
+    token_id: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_token(&token_id).await {
+        Ok(token) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(token),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Token> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get an account's finalized balance of an issued token
+async fn get_token_balance(
+    token_id: String,
+    address: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_token_balance(&token_id, &address).await {
+        Ok(balance) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(balance.to_string()),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<String> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// List the archive bundles this node uploaded
+async fn get_archives(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_archives().await {
+        Ok(archives) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(archives),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<ArchiveRecord>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Fetch an archive bundle from cold storage, verified against its anchor
+async fn retrieve_archive(
+    content_id: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.retrieve_archive(&content_id).await {
+        Ok(bundle) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(bundle),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<ArchiveBundle> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Prove a transaction is an ancestor of a finalized checkpoint
+async fn get_inclusion_proof(
+    tx_id: String,
+    query: InclusionProofQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let ids = TransactionId::from_string(&tx_id).and_then(|tx_id| {
+        let checkpoint = query.checkpoint.as_deref().map(TransactionId::from_string).transpose()?;
+        Ok((tx_id, checkpoint))
+    });
+    let result = match ids {
+        Ok((tx_id, checkpoint)) => blockchain.read().await.get_inclusion_proof(&tx_id, checkpoint.as_ref()).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(proof) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(proof),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<InclusionProof> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get the receipt of a finalized transaction
+async fn get_transaction_receipt(
+    tx_id: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = match TransactionId::from_string(&tx_id) {
+        Ok(tx_id) => blockchain.read().await.get_receipt(&tx_id).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(Some(receipt)) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(receipt),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Ok(None) => Ok(warp::reply::json(&ApiResponse::<TransactionReceipt> {
+            success: false,
+            data: None,
+            error: Some("No receipt; the transaction is unknown or not finalized".to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<TransactionReceipt> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Replay a contract call transaction with tracing
+async fn debug_transaction_call(
+    tx_id: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let result = match TransactionId::from_string(&tx_id) {
+        Ok(tx_id) => blockchain.read().await.debug_transaction(&tx_id).await,
+        Err(e) => Err(e),
+    };
+    Ok(trace_reply(result))
+}
+
+/// Trace a contract call against current state
+async fn debug_contract_call(
+    request: DebugCallRequest,
     blockchain: Arc<RwLock<Blockchain>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // Convert hex strings to bytes
-    let sender = hex::decode(&request.sender).unwrap_or_default();
-    let receiver = hex::decode(&request.receiver).unwrap_or_default();
-    
-    // Create transaction
-    let transaction = Transaction {
-        id: TransactionId::new(),
-        sender,
-        receiver,
-        amount: request.amount,
-        nonce: rand::random(),
-        timestamp: chrono::Utc::now().timestamp() as u64,
-        parents: vec![], // Will be filled by blockchain
-        signature: vec![0u8; 64], // Placeholder
-        quantum_proof: crate::core::QuantumProof {
-            prime_hash: vec![0u8; 32],
-            resistance_score: 80,
-            proof_timestamp: chrono::Utc::now().timestamp() as u64,
-        },
-        metadata: request.metadata.map(|s| s.into_bytes()),
+    let (Ok(input), Ok(caller)) = (hex::decode(&request.input), hex::decode(&request.caller)) else {
+        return Ok(trace_reply(Err(BlockchainError::Other("Input and caller must be hex".to_string()))));
     };
-    
-    // Submit to blockchain
-    match blockchain.write().await.submit_transaction(transaction).await {
-        Ok(tx_id) => {
-            Ok(warp::reply::json(&ApiResponse {
-                success: true,
-                data: Some(tx_id.as_string()),
-                error: None,
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }))
-        }
-        Err(e) => {
-            Ok(warp::reply::json(&ApiResponse::<String> {
-                success: false,
-                data: None,
-                error: Some(format!("Failed to create transaction: {}", e)),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }))
-        }
+    let result = blockchain.read().await.debug_call(
+        &ContractId::new(request.contract_id),
+        &request.function,
+        input,
+        caller,
+        request.value,
+        request.gas_limit,
+    ).await;
+    Ok(trace_reply(result))
+}
+
+fn trace_reply(result: Result<ExecutionTrace, BlockchainError>) -> warp::reply::Json {
+    match result {
+        Ok(trace) => warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(trace),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }),
+        Err(e) => warp::reply::json(&ApiResponse::<ExecutionTrace> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }),
     }
 }
 
-/// Get transaction by ID
-async fn get_transaction_by_id(
-    tx_id: String,
+/// Get a participation credential by ID
+async fn get_participation_credential(
+    credential_id: String,
     blockchain: Arc<RwLock<Blockchain>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let tx_id = match TransactionId::from_bytes(&hex::decode(&tx_id).unwrap_or_default()) {
-        Ok(id) => id,
-        Err(_) => {
-            return Ok(warp::reply::json(&ApiResponse::<TransactionResponse> {
-                success: false,
-                data: None,
-                error: Some("Invalid transaction ID".to_string()),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }))
-        }
-    };
-    
-    match blockchain.read().await.get_transaction(&tx_id).await {
-        Ok(Some(tx)) => {
-            let response = TransactionResponse {
-                id: tx.id.as_string(),
-                sender: hex::encode(&tx.sender),
-                receiver: hex::encode(&tx.receiver),
-                amount: tx.amount,
-                timestamp: tx.timestamp,
-                status: "pending".to_string(), // Would get from DAG node
-                fee: 0, // Would calculate
-                quantum_resistance_score: tx.quantum_proof.resistance_score,
-                parents: tx.parents.iter().map(|p| p.as_string()).collect(),
-                confidence: 0.0, // Would get from DAG node
-            };
-            
-            Ok(warp::reply::json(&ApiResponse {
-                success: true,
-                data: Some(response),
-                error: None,
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }))
-        }
-        Ok(None) => {
-            Ok(warp::reply::json(&ApiResponse::<TransactionResponse> {
-                success: false,
-                data: None,
-                error: Some("Transaction not found".to_string()),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }))
+    let result = blockchain.read().await.get_participation_credential(&credential_id).await
+        .and_then(|credential| credential.ok_or_else(|| BlockchainError::Other("Credential not found".to_string())));
+    match result {
+        Ok(credential) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(credential),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<ParticipationCredential> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get the participation credentials issued to a voter
+async fn get_voter_credentials(
+    voter: String,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_voter_credentials(&voter).await {
+        Ok(credentials) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(credentials),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<Vec<ParticipationCredential>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Attest to the node's software and chain over the client's challenge
+async fn get_attestation(
+    query: AttestationQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.attest(&query.challenge).await {
+        Ok(attestation) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(attestation),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<NodeAttestation> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Verify a selective disclosure proof and return the disclosed attributes
+async fn verify_disclosure_proof(
+    proof: DisclosureProof,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.verify_disclosure_proof(&proof).await {
+        Ok(attributes) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(attributes),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<std::collections::BTreeMap<String, String>> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get Prometheus metrics
+async fn get_metrics(
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.get_metrics().await {
+        Ok(metrics_text) => {
+            Ok(warp::reply::with_header(metrics_text, "content-type", "text/plain; version=0.0.4"))
         }
         Err(e) => {
-            Ok(warp::reply::json(&ApiResponse::<TransactionResponse> {
-                success: false,
-                data: None,
-                error: Some(format!("Failed to get transaction: {}", e)),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }))
+            let error_response = format!("# Error fetching metrics\n{}", e);
+            Ok(warp::reply::with_header(error_response, "content-type", "text/plain; version=0.0.4"))
         }
     }
 }
 
-/// Get DAG nodes
-async fn get_dag_nodes(
+/// Capture a pprof CPU profile
+async fn get_cpu_profile(
+    query: CpuProfileQuery,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    use warp::Reply;
+
+    let duration = std::time::Duration::from_secs(query.seconds.unwrap_or(30));
+    match cpu_profile(duration, query.frequency.unwrap_or(DEFAULT_PROFILE_FREQUENCY)).await {
+        Ok(profile) => Ok(warp::reply::with_header(
+            warp::reply::with_header(profile, "content-type", "application/octet-stream"),
+            "content-disposition",
+            "attachment; filename=\"cpu.pb\"",
+        ).into_response()),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<()> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }).into_response()),
+    }
+}
+
+/// Get heap statistics
+async fn get_heap_stats() -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(heap_stats()),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get background task counts per subsystem
+async fn get_task_stats() -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&ApiResponse {
+        success: true,
+        data: Some(task_stats()),
+        error: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Get the running node settings
+async fn get_settings(
     blockchain: Arc<RwLock<Blockchain>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let dag = blockchain.read().await;
-    let nodes = generate_mock_dag_nodes(10);
-    
     Ok(warp::reply::json(&ApiResponse {
         success: true,
-        data: Some(nodes),
+        data: Some(blockchain.read().await.get_settings().await),
         error: None,
         timestamp: chrono::Utc::now().to_rfc3339(),
     }))
 }
 
-/// Get DAG node by ID
-async fn get_dag_node_by_id(
-    node_id: String,
+/// Apply the hot-reloadable fields of a settings document
+async fn reload_settings(
+    settings: NodeSettings,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match blockchain.read().await.reload_settings(settings).await {
+        Ok(report) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(report),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<ReloadReport> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get operator messaging contacts
+async fn get_operator_contacts(
     blockchain: Arc<RwLock<Blockchain>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // For now, return mock data
-    let node = DagNodeResponse {
-        id: node_id,
-        transaction: TransactionResponse {
-            id: node_id,
-            sender: "0x1234".to_string(),
-            receiver: "0x5678".to_string(),
-            amount: 100,
-            timestamp: chrono::Utc::now().timestamp() as u64,
-            status: "confirmed".to_string(),
-            fee: 1,
-            quantum_resistance_score: 85,
-            parents: vec![],
-            confidence: 0.95,
-        },
-        children: vec!["child1".to_string(), "child2".to_string()],
-        weight: 100,
-        confidence: 0.95,
-        status: "confirmed".to_string(),
-        quantum_score: 85,
-    };
-    
     Ok(warp::reply::json(&ApiResponse {
         success: true,
-        data: Some(node),
+        data: Some(blockchain.read().await.get_operator_contacts().await),
         error: None,
         timestamp: chrono::Utc::now().to_rfc3339(),
     }))
 }
 
-/// Get DAG tips
-async fn get_dag_tips(
+/// Add an operator contact from the identity info their node publishes
+async fn add_operator_contact(
+    info: IdentityInfo,
     blockchain: Arc<RwLock<Blockchain>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let dag = blockchain.read().await;
-    let tips = dag.get_tips();
-    
-    let tip_responses: Vec<DagNodeResponse> = tips.iter().take(5).map(|node| {
-        DagNodeResponse {
-            id: node.transaction.id.as_string(),
-            transaction: TransactionResponse {
-                id: node.transaction.id.as_string(),
-                sender: hex::encode(&node.transaction.sender),
-                receiver: hex::encode(&node.transaction.receiver),
-                amount: node.transaction.amount,
-                timestamp: node.transaction.timestamp,
-                status: format!("{:?}", node.status),
-                fee: 0,
-                quantum_resistance_score: node.transaction.quantum_proof.resistance_score,
-                parents: node.transaction.parents.iter().map(|p| p.as_string()).collect(),
-                confidence: node.confidence,
-            },
-            children: node.children.iter().map(|c| c.as_string()).collect(),
-            weight: node.weight,
-            confidence: node.confidence,
-            status: format!("{:?}", node.status),
-            quantum_score: node.quantum_score,
-        }
-    }).collect();
-    
+    match blockchain.read().await.add_operator_contact(&info).await {
+        Ok(contact) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(contact),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<OperatorContact> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+/// Get stored operator messages
+async fn get_operator_messages(
+    query: OperatorMessageQuery,
+    blockchain: Arc<RwLock<Blockchain>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let messages = blockchain.read().await.get_operator_messages(query.peer.as_deref()).await;
+
     Ok(warp::reply::json(&ApiResponse {
         success: true,
-        data: Some(tip_responses),
+        data: Some(messages),
         error: None,
         timestamp: chrono::Utc::now().to_rfc3339(),
     }))
 }
 
-/// Get node identity information
-async fn get_identity_info(
+/// Encrypt and send a message to another operator
+async fn send_operator_message(
+    request: SendOperatorMessageRequest,
     blockchain: Arc<RwLock<Blockchain>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    match blockchain.read().await.get_identity_info().await {
-        Ok(identity_info) => {
-            Ok(warp::reply::json(&ApiResponse {
-                success: true,
-                data: Some(identity_info),
-                error: None,
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }))
-        }
-        Err(e) => {
-            Ok(warp::reply::json(&ApiResponse::<IdentityInfo> {
-                success: false,
-                data: None,
-                error: Some(format!("Failed to get identity info: {}", e)),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            }))
-        }
+    match blockchain.read().await.send_operator_message(&request.recipient, &request.body).await {
+        Ok(message) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(message),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<OperatorMessage> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
     }
 }
 
-/// Get Prometheus metrics
-async fn get_metrics(
+/// Mark a received operator message as read and send a receipt
+async fn mark_operator_message_read(
+    message_id: String,
     blockchain: Arc<RwLock<Blockchain>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    match blockchain.read().await.get_metrics().await {
-        Ok(metrics_text) => {
-            Ok(warp::reply::with_header(metrics_text, "content-type", "text/plain; version=0.0.4"))
-        }
-        Err(e) => {
-            let error_response = format!("# Error fetching metrics\n{}", e);
-            Ok(warp::reply::with_header(error_response, "content-type", "text/plain; version=0.0.4"))
-        }
+    match blockchain.read().await.mark_operator_message_read(&message_id).await {
+        Ok(()) => Ok(warp::reply::json(&ApiResponse {
+            success: true,
+            data: Some(message_id),
+            error: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<String> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
     }
 }
 
 /// Rotate node identity
+///
+/// Returns the announcement sent to peers and, once only, the shares of the
+/// previous identity's backup key as BIP-39 phrases.
 async fn rotate_identity(
     blockchain: Arc<RwLock<Blockchain>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // Note: This is a simplified implementation
-    // In a real implementation, you'd need to handle the mutable reference properly
-    // For now, we'll return a success response with a message
-    
-    let response = serde_json::json!({
-        "success": true,
-        "message": "Identity rotation initiated. Check logs for details.",
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "note": "In a production implementation, this would trigger actual key rotation"
-    });
-    
-    Ok(warp::reply::json(&ApiResponse {
-        success: true,
-        data: Some(response),
-        error: None,
-        timestamp: chrono::Utc::now().to_rfc3339(),
-    }))
+    match blockchain.read().await.rotate_identity().await {
+        Ok(rotation) => {
+            let response = serde_json::json!({
+                "announcement": rotation.announcement(),
+                "backup_shares": rotation.backup_shares.iter().map(|share| share.to_mnemonic()).collect::<Vec<_>>(),
+            });
+            Ok(warp::reply::json(&ApiResponse {
+                success: true,
+                data: Some(response),
+                error: None,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            }))
+        }
+        Err(e) => Ok(warp::reply::json(&ApiResponse::<serde_json::Value> {
+            success: false,
+            data: None,
+            error: Some(e.to_string()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })),
+    }
 }
 
 /// Create database backup
@@ -702,6 +3039,7 @@ fn generate_mock_transactions(count: usize) -> Vec<TransactionResponse> {
             quantum_resistance_score: 80 + (i % 20),
             parents: vec![],
             confidence: 0.5 + (i as f64 * 0.1),
+            required_confidence: None,
         });
     }
     
@@ -725,6 +3063,7 @@ fn generate_mock_dag_nodes(count: usize) -> Vec<DagNodeResponse> {
                 quantum_resistance_score: 80 + (i % 20),
                 parents: vec![],
                 confidence: 0.5 + (i as f64 * 0.1),
+                required_confidence: None,
             },
             children: vec![format!("node_{}", i + 1)],
             weight: (i + 1) * 10,
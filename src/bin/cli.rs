@@ -43,9 +43,9 @@ enum Commands {
         #[arg(short, long)]
         receiver: String,
         
-        /// Amount to transfer
-        #[arg(short, long)]
-        amount: u64,
+        /// Amount to transfer, in base units
+        #[arg(short, long, value_parser = parse_amount)]
+        amount: Amount,
         
         /// Node RPC address
         #[arg(short, long, default_value = "http://127.0.0.1:8999")]
@@ -81,6 +81,189 @@ enum Commands {
         public_key: String,
     },
     
+    /// Save a snapshot of a node's finalized state
+    Snapshot {
+        /// Output file
+        #[arg(short, long, default_value = "snapshot.json")]
+        output: String,
+
+        /// Node API address
+        #[arg(short, long, default_value = "http://127.0.0.1:8000")]
+        node: String,
+    },
+
+    /// Compare two state snapshots; exits non-zero when the state diverged
+    SnapshotDiff {
+        /// Snapshot taken before the upgrade
+        before: String,
+
+        /// Snapshot taken after the upgrade
+        after: String,
+
+        /// Divergent items listed per category
+        #[arg(short, long, default_value_t = DEFAULT_DIFF_SAMPLES)]
+        samples: usize,
+
+        /// Accept transactions finalized after the first snapshot
+        #[arg(long)]
+        allow_progress: bool,
+    },
+    
+    /// Put a node into safe mode, halting transaction acceptance
+    Halt {
+        /// Reason recorded in the audit trail
+        reason: String,
+
+        /// Operator name recorded in the audit trail
+        #[arg(long, default_value = "cli")]
+        operator: String,
+
+        /// Node API address
+        #[arg(short, long, default_value = "http://127.0.0.1:8000")]
+        node: String,
+    },
+
+    /// Sign a resume authorization and take a node out of safe mode
+    Resume {
+        /// Reason recorded in the audit trail
+        reason: String,
+
+        /// Key file holding the hex Ed25519 `private_key` of a resume key
+        #[arg(short, long)]
+        key: String,
+
+        /// Node API address
+        #[arg(short, long, default_value = "http://127.0.0.1:8000")]
+        node: String,
+    },
+
+    /// Rebuild derived tables (DAG nodes, parent links, indexes) from the transactions table
+    Reindex {
+        /// Path to blockchain data of a stopped node
+        #[arg(short, long, default_value = "./blockchain_data")]
+        path: String,
+
+        /// Node API address; reindexes the running node instead of `path`
+        #[arg(short, long)]
+        node: Option<String>,
+
+        /// Transactions per batch
+        #[arg(short, long, default_value_t = 1000)]
+        batch_size: usize,
+
+        /// Pause between batches in milliseconds
+        #[arg(short, long, default_value_t = 0)]
+        throttle_ms: u64,
+    },
+    
+    /// Import historical transactions into a stopped node's database
+    ///
+    /// Running it again for the same file resumes an interrupted import.
+    BulkImport {
+        /// File to import
+        file: String,
+
+        /// Path to blockchain data
+        #[arg(short, long, default_value = "./blockchain_data")]
+        path: String,
+
+        /// File format: jsonl, csv or export
+        #[arg(short, long, default_value = "jsonl")]
+        format: String,
+
+        /// Transactions per batch
+        #[arg(short, long, default_value_t = 10_000)]
+        batch_size: usize,
+
+        /// Skip lines that are not a valid transaction instead of stopping
+        #[arg(long)]
+        skip_invalid: bool,
+    },
+
+    /// Check every transaction, DAG node and parent link of a stopped node's database
+    VerifyIntegrity {
+        /// Path to blockchain data
+        #[arg(short, long, default_value = "./blockchain_data")]
+        path: String,
+
+        /// Delete orphans, restore parent links and re-derive broken DAG nodes
+        #[arg(long)]
+        repair: bool,
+    },
+
+    /// Apply pending schema migrations to the database of a stopped node
+    Migrate {
+        /// Path to blockchain data
+        #[arg(short, long, default_value = "./blockchain_data")]
+        path: String,
+
+        /// Apply the migrations in a transaction that is rolled back
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Change the SQLCipher key of a stopped node's database
+    ///
+    /// The current key comes from `QDAG_DATABASE_KEY`, the new one from
+    /// `QDAG_DATABASE_NEW_KEY`; a plaintext database is encrypted.
+    Rekey {
+        /// Path to blockchain data
+        #[arg(short, long, default_value = "./blockchain_data")]
+        path: String,
+
+        /// Decrypt the database instead of setting a new key
+        #[arg(long)]
+        decrypt: bool,
+    },
+
+    /// Encrypt the plaintext identity file of a stopped node
+    ///
+    /// The passphrase comes from `QDAG_IDENTITY_PASSPHRASE`, or from the OS
+    /// keyring with `--keyring`.
+    EncryptIdentity {
+        /// Path to blockchain data
+        #[arg(short, long, default_value = "./blockchain_data")]
+        path: String,
+
+        /// Keep a generated passphrase in the OS keyring
+        #[arg(long)]
+        keyring: bool,
+    },
+
+    /// Write an encrypted identity backup and print the shares of its key
+    BackupIdentity {
+        /// Path to blockchain data
+        #[arg(short, long, default_value = "./blockchain_data")]
+        path: String,
+
+        /// Shares needed to recover the identity
+        #[arg(short, long, default_value_t = 3)]
+        threshold: u8,
+
+        /// Shares to create
+        #[arg(short, long, default_value_t = 5)]
+        shares: u8,
+
+        /// Print QR payloads instead of mnemonic phrases
+        #[arg(long)]
+        qr: bool,
+    },
+
+    /// Restore the identity of a stopped node from backup shares
+    RecoverIdentity {
+        /// Path to blockchain data
+        #[arg(short, long, default_value = "./blockchain_data")]
+        path: String,
+
+        /// Shares as mnemonic phrases or QR payloads, one argument each
+        #[arg(required = true)]
+        shares: Vec<String>,
+
+        /// Replace an existing identity
+        #[arg(long)]
+        force: bool,
+    },
+    
     /// Test blockchain performance
     Benchmark {
         /// Number of transactions to test
@@ -118,6 +301,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::GenerateKeys { private_key, public_key } => {
             generate_key_pair(&private_key, &public_key).await?;
         }
+        Commands::Snapshot { output, node } => {
+            save_snapshot(&output, &node).await?;
+        }
+        Commands::SnapshotDiff { before, after, samples, allow_progress } => {
+            if !diff_snapshots(&before, &after, samples, allow_progress).await? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Halt { reason, operator, node } => {
+            halt_node(&reason, &operator, &node).await?;
+        }
+        Commands::Resume { reason, key, node } => {
+            resume_node(&reason, &key, &node).await?;
+        }
+        Commands::Reindex { path, node, batch_size, throttle_ms } => {
+            let config = ReindexConfig { batch_size, throttle_ms };
+            match node {
+                Some(node) => reindex_node(config, &node).await?,
+                None => reindex_data(config, &path).await?,
+            }
+        }
+        Commands::BulkImport { file, path, format, batch_size, skip_invalid } => {
+            let config = BulkImportConfig { batch_size, skip_invalid, ..BulkImportConfig::default() };
+            bulk_import_data(&file, format.parse()?, config, &path).await?;
+        }
+        Commands::VerifyIntegrity { path, repair } => {
+            verify_data(&path, repair).await?;
+        }
+        Commands::Migrate { path, dry_run } => {
+            migrate_data(&path, dry_run).await?;
+        }
+        Commands::Rekey { path, decrypt } => {
+            rekey_data(&path, decrypt).await?;
+        }
+        Commands::EncryptIdentity { path, keyring } => {
+            encrypt_identity(&path, keyring).await?;
+        }
+        Commands::BackupIdentity { path, threshold, shares, qr } => {
+            backup_identity(&path, ShareScheme::new(threshold, shares)?, qr).await?;
+        }
+        Commands::RecoverIdentity { path, shares, force } => {
+            recover_identity(&path, &shares, force).await?;
+        }
         Commands::Benchmark { count, node } => {
             run_benchmark(count, &node).await?;
         }
@@ -135,6 +361,7 @@ async fn init_blockchain(path: &str) -> Result<(), Box<dyn std::error::Error>> {
     // Create default configuration
     let config = BlockchainConfig {
         network: NetworkConfig {
+            network_type: std::env::var("QDAG_NETWORK").ok().and_then(|network| network.parse().ok()).unwrap_or_default(),
             listen_addr: "/ip4/127.0.0.1/tcp/8999".to_string(),
             bootstrap_nodes: vec![],
             max_peers: 10,
@@ -148,10 +375,17 @@ async fn init_blockchain(path: &str) -> Result<(), Box<dyn std::error::Error>> {
             quantum_resistance_level: 128,
             signature_scheme: "dilithium".to_string(),
             key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
         },
         database: DatabaseConfig {
             path: format!("{}/data", path),
             cache_size_mb: 1024,
+            backend: Default::default(),
+            remote_backup: None,
+            pragmas: Default::default(),
+            mode: Default::default(),
+            metadata_index: false,
         },
     };
     
@@ -202,7 +436,7 @@ async fn start_node(path: &str, listen: &str) -> Result<(), Box<dyn std::error::
 async fn create_transaction(
     sender: &str,
     receiver: &str,
-    amount: u64,
+    amount: Amount,
     node: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("Creating transaction...");
@@ -220,10 +454,12 @@ async fn create_transaction(
         sender: sender_key,
         receiver: receiver_key,
         amount,
+        fee: 0,
         nonce: rand::random(),
         timestamp: chrono::Utc::now().timestamp() as u64,
         parents: vec![], // Will be filled by the node
         signature: vec![0u8; 64], // Placeholder
+        signature_scheme: Default::default(),
         quantum_proof: QuantumProof {
             prime_hash: vec![0u8; 32], // Will be calculated by node
             resistance_score: 80,
@@ -278,6 +514,326 @@ async fn get_status(node: &str) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+async fn save_snapshot(output: &str, node: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Taking state snapshot from: {}", node);
+
+    let response: serde_json::Value = admin_request(reqwest::Method::GET, node, "admin/snapshot")
+        .send().await?.error_for_status()?.json().await?;
+    let snapshot: StateSnapshot = serde_json::from_value(response["data"].clone())?;
+    snapshot.save(output).await?;
+
+    println!("Snapshot saved to: {}", output);
+    println!("  Consensus Height: {}", snapshot.consensus_height);
+    println!("  Finalized Transactions: {}", snapshot.finalized.len());
+    println!("  State Root: {}", snapshot.state_root);
+    Ok(())
+}
+
+fn admin_request(method: reqwest::Method, node: &str, path: &str) -> reqwest::RequestBuilder {
+    let mut request = reqwest::Client::new().request(method, format!("{}/{}", node.trim_end_matches('/'), path));
+    if let Ok(token) = std::env::var("QDAG_ADMIN_TOKEN") {
+        request = request.header("x-admin-token", token);
+    }
+    request
+}
+
+async fn reindex_data(config: ReindexConfig, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let config_json = tokio::fs::read_to_string(format!("{}/config.json", path)).await?;
+    let node_config: BlockchainConfig = serde_json::from_str(&config_json)?;
+    let database = DatabaseManager::new(storage::DatabaseConfig {
+        path: node_config.database.path,
+        backend: node_config.database.backend,
+        encryption_key: storage::resolve_encryption_key(node_config.security.database_key.as_deref()),
+        ..storage::DatabaseConfig::default()
+    }).await?;
+
+    println!("🔧 Reindexing {}", path);
+    let report = database.reindex(&config, |progress| {
+        println!("  {:?}: {}/{}", progress.phase, progress.processed, progress.total);
+    }).await?;
+    print_reindex_report(&report);
+    Ok(())
+}
+
+async fn bulk_import_data(
+    file: &str,
+    format: BulkImportFormat,
+    config: BulkImportConfig,
+    path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config_json = tokio::fs::read_to_string(format!("{}/config.json", path)).await?;
+    let node_config: BlockchainConfig = serde_json::from_str(&config_json)?;
+    let database = DatabaseManager::new(storage::DatabaseConfig {
+        path: node_config.database.path,
+        backend: node_config.database.backend,
+        encryption_key: storage::resolve_encryption_key(node_config.security.database_key.as_deref()),
+        ..storage::DatabaseConfig::default()
+    }).await?;
+
+    println!("📥 Importing {} into {}", file, path);
+    let report = database.bulk_import(file, format, &config, |progress| match progress.phase {
+        BulkImportPhase::Rows => println!(
+            "  Rows: {} line(s), {} transaction(s), {:.0} tx/s",
+            progress.lines, progress.imported, progress.rate
+        ),
+        BulkImportPhase::Indexes => println!("  Indexes: rebuilt"),
+        BulkImportPhase::DerivedTables => println!("  Derived Tables: {} transaction(s)", progress.derived),
+    }).await?;
+
+    println!("Import complete:");
+    println!("  Lines: {} ({} resumed from an earlier run)", report.lines, report.resumed_from);
+    println!("  Transactions: {}", report.imported);
+    println!("  Skipped: {} ({} invalid)", report.skipped + report.invalid, report.invalid);
+    println!("  Indexes Rebuilt: {}", report.indexes_rebuilt.len());
+    Ok(())
+}
+
+async fn verify_data(path: &str, repair: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config_json = tokio::fs::read_to_string(format!("{}/config.json", path)).await?;
+    let node_config: BlockchainConfig = serde_json::from_str(&config_json)?;
+    let database = DatabaseManager::new(storage::DatabaseConfig {
+        path: node_config.database.path,
+        backend: node_config.database.backend,
+        encryption_key: storage::resolve_encryption_key(node_config.security.database_key.as_deref()),
+        ..storage::DatabaseConfig::default()
+    }).await?;
+
+    println!("🔍 Verifying {}", path);
+    let report = database.verify_integrity(repair).await?;
+    println!("Checked {} transaction(s), {} DAG node(s), {} parent link(s)",
+        report.transactions_checked, report.dag_nodes_checked, report.parent_links_checked);
+    for issue in &report.issues {
+        match &issue.detail {
+            Some(detail) => println!("  {:?} in {}: {} ({})", issue.kind, issue.table, issue.row_id, detail),
+            None => println!("  {:?} in {}: {}", issue.kind, issue.table, issue.row_id),
+        }
+    }
+    if repair {
+        println!("Repaired: {} DAG node(s) re-derived, {} parent link(s) restored, {} orphan(s) removed",
+            report.dag_nodes_rederived, report.parent_links_restored, report.orphans_removed);
+    }
+
+    let left = if repair { report.unrepairable().count() } else { report.issues.len() };
+    if left > 0 {
+        return Err(format!("{} integrity issue(s) left", left).into());
+    }
+    println!("✅ Database is consistent");
+    Ok(())
+}
+
+async fn migrate_data(path: &str, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config_json = tokio::fs::read_to_string(format!("{}/config.json", path)).await?;
+    let node_config: BlockchainConfig = serde_json::from_str(&config_json)?;
+    let database = DatabaseManager::new(storage::DatabaseConfig {
+        path: node_config.database.path,
+        backend: node_config.database.backend,
+        encryption_key: storage::resolve_encryption_key(node_config.security.database_key.as_deref()),
+        auto_migrate: false,
+        ..storage::DatabaseConfig::default()
+    }).await?;
+
+    let report = database.migrate(dry_run).await?;
+    if report.applied.is_empty() {
+        println!("✅ Schema is up to date at version {}", report.from_version);
+        return Ok(());
+    }
+    for (version, name) in &report.applied {
+        println!("  {} {}", version, name);
+    }
+    if report.dry_run {
+        println!("🔍 Dry run: {} migration(s) would take the schema from version {} to {}", report.applied.len(), report.from_version, report.to_version);
+    } else {
+        println!("✅ Migrated the schema from version {} to {}", report.from_version, report.to_version);
+    }
+    Ok(())
+}
+
+async fn rekey_data(path: &str, decrypt: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config_json = tokio::fs::read_to_string(format!("{}/config.json", path)).await?;
+    let node_config: BlockchainConfig = serde_json::from_str(&config_json)?;
+    let current = storage::resolve_encryption_key(node_config.security.database_key.as_deref());
+    let new = if decrypt {
+        None
+    } else {
+        match std::env::var("QDAG_DATABASE_NEW_KEY") {
+            Ok(key) if !key.is_empty() => Some(key),
+            _ => return Err("Set QDAG_DATABASE_NEW_KEY to the new key, or pass --decrypt".into()),
+        }
+    };
+
+    DatabaseManager::rekey_database(&node_config.database.path, current.as_deref(), new.as_deref()).await?;
+    match new {
+        Some(_) => println!("🔐 Database at {} is encrypted with the new key; set QDAG_DATABASE_KEY to it before starting the node", node_config.database.path),
+        None => println!("🔓 Database at {} is decrypted; unset QDAG_DATABASE_KEY before starting the node", node_config.database.path),
+    }
+    Ok(())
+}
+
+/// Identity storage directory of the node at `path`
+async fn identity_path(path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let config_json = tokio::fs::read_to_string(format!("{}/config.json", path)).await?;
+    let node_config: BlockchainConfig = serde_json::from_str(&config_json)?;
+    Ok(node_config.database.path)
+}
+
+async fn encrypt_identity(path: &str, keyring: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let storage_path = identity_path(path).await?;
+    if !Path::new(&format!("{}/identity.json", storage_path)).exists() {
+        return Err(format!("No node identity in {}", storage_path).into());
+    }
+    let source = if keyring { IdentityKeySource::Keyring } else { IdentityKeySource::default().resolve() };
+    if source == IdentityKeySource::Plaintext {
+        return Err(format!("Set {} to the passphrase, or pass --keyring", IDENTITY_PASSPHRASE_ENV).into());
+    }
+
+    // Loading with a key source rewrites a plaintext file encrypted
+    let mut manager = IdentityManager::new(storage_path.clone());
+    manager.set_key_source(source);
+    let identity = manager.initialize_identity().await?;
+    println!("🔐 Identity {} in {} is encrypted", identity.node_id, storage_path);
+    Ok(())
+}
+
+async fn backup_identity(path: &str, scheme: ShareScheme, qr: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let storage_path = identity_path(path).await?;
+    if !Path::new(&format!("{}/identity.json", storage_path)).exists() {
+        return Err(format!("No node identity in {}", storage_path).into());
+    }
+    let mut manager = IdentityManager::new(storage_path);
+    manager.set_key_source(IdentityKeySource::default().resolve());
+    manager.set_backup_scheme(scheme);
+    let identity = manager.initialize_identity().await?;
+    let shares = manager.export_backup().await?;
+
+    println!("🔐 Backed up identity {}", identity.node_id);
+    println!("Any {} of these {} shares recover it. Store each one separately:", scheme.threshold, scheme.shares);
+    for share in &shares {
+        println!();
+        println!("{}", if qr { share.to_qr_payload() } else { share.to_mnemonic() });
+    }
+    Ok(())
+}
+
+async fn recover_identity(path: &str, blobs: &[String], force: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let storage_path = identity_path(path).await?;
+    if !force && Path::new(&format!("{}/identity.json", storage_path)).exists() {
+        return Err("The node already has an identity; pass --force to replace it".into());
+    }
+    let shares = blobs.iter().map(|blob| BackupShare::parse(blob)).collect::<Result<Vec<_>, _>>()?;
+
+    let mut manager = IdentityManager::new(storage_path);
+    manager.set_key_source(IdentityKeySource::default().resolve());
+    let identity = manager.recover_identity(&shares).await?;
+    println!("✅ Recovered identity {}", identity.node_id);
+    Ok(())
+}
+
+async fn reindex_node(config: ReindexConfig, node: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let response: serde_json::Value = admin_request(reqwest::Method::POST, node, "admin/reindex")
+        .json(&config)
+        .send().await?.error_for_status()?.json().await?;
+    if response["success"] != true {
+        return Err(format!("Reindex failed to start: {}", response["error"]).into());
+    }
+
+    println!("🔧 Reindexing {}", node);
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let response: serde_json::Value = admin_request(reqwest::Method::GET, node, "admin/reindex")
+            .send().await?.error_for_status()?.json().await?;
+        let status: ReindexStatus = serde_json::from_value(response["data"].clone())?;
+        if let Some(progress) = &status.progress {
+            println!("  {:?}: {}/{}", progress.phase, progress.processed, progress.total);
+        }
+        if status.running {
+            continue;
+        }
+        if let Some(error) = status.last_error {
+            return Err(format!("Reindex failed: {}", error).into());
+        }
+        if let Some(report) = &status.last_report {
+            print_reindex_report(report);
+        }
+        return Ok(());
+    }
+}
+
+fn print_reindex_report(report: &ReindexReport) {
+    println!("Reindex complete:");
+    println!("  Transactions: {}", report.transactions);
+    println!("  Parent Links: {} ({} missing parents)", report.parent_links, report.missing_parents);
+    println!("  DAG Nodes: {} rebuilt, {} recreated", report.dag_nodes_rebuilt, report.dag_nodes_created);
+    println!("  Orphans Removed: {}", report.orphans_removed);
+}
+
+async fn halt_node(reason: &str, operator: &str, node: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let response: serde_json::Value = admin_request(reqwest::Method::POST, node, "admin/safe-mode/halt")
+        .json(&serde_json::json!({ "operator": operator, "reason": reason }))
+        .send().await?.error_for_status()?.json().await?;
+    if response["success"] != true {
+        return Err(format!("Halt failed: {}", response["error"]).into());
+    }
+
+    println!("🛑 Node {} is in safe mode", node);
+    println!("  Halt Sequence: {}", response["data"]["sequence"]);
+    Ok(())
+}
+
+async fn resume_node(reason: &str, key_path: &str, node: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let key_file: serde_json::Value = serde_json::from_str(&tokio::fs::read_to_string(key_path).await?)?;
+    let secret_hex = key_file["private_key"].as_str().ok_or("Key file has no private_key")?;
+    let secret = ed25519_dalek::SecretKey::from_bytes(&hex::decode(secret_hex)?)?;
+    let public = ed25519_dalek::PublicKey::from(&secret);
+    let keypair = ed25519_dalek::Keypair { secret, public };
+
+    let state: serde_json::Value = admin_request(reqwest::Method::GET, node, "safe-mode")
+        .send().await?.error_for_status()?.json().await?;
+    let halt_sequence = state["data"]["status"]["halt_sequence"].as_u64().ok_or("Node is not in safe mode")?;
+
+    let authorization = ResumeAuthorization::sign(&keypair, halt_sequence, reason);
+    let response: serde_json::Value = admin_request(reqwest::Method::POST, node, "admin/safe-mode/resume")
+        .json(&authorization)
+        .send().await?.error_for_status()?.json().await?;
+    if response["success"] != true {
+        return Err(format!("Resume failed: {}", response["error"]).into());
+    }
+
+    println!("▶️ Node {} resumed transaction acceptance", node);
+    Ok(())
+}
+
+/// Print the differences between two snapshots and return whether they agree
+async fn diff_snapshots(
+    before_path: &str,
+    after_path: &str,
+    samples: usize,
+    allow_progress: bool,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let before = StateSnapshot::load(before_path).await?;
+    let after = StateSnapshot::load(after_path).await?;
+    let diff = before.diff(&after, samples);
+
+    println!("Comparing {} ({}) with {} ({})", before_path, before.node_version, after_path, after.node_version);
+    println!("  Consensus Height: {} -> {}", diff.before_height, diff.after_height);
+    println!("  State Root: {}", if diff.state_root_matches { "match" } else { "differs" });
+    println!("  Diverging Balances: {}", diff.balances.count);
+    for divergence in &diff.balances.samples {
+        println!("    {}: {:?} -> {:?}", divergence.account, divergence.before, divergence.after);
+    }
+    println!("  Finalized Transactions Missing: {}", diff.missing_finalized.count);
+    for id in &diff.missing_finalized.samples {
+        println!("    {}", id);
+    }
+    println!("  Finalized Transactions Added: {}", diff.added_finalized.count);
+    for id in &diff.added_finalized.samples {
+        println!("    {}", id);
+    }
+
+    let agrees = if allow_progress { diff.is_consistent_extension() } else { diff.is_identical() };
+    println!("{}", if agrees { "State parity confirmed." } else { "State diverged!" });
+    Ok(agrees)
+}
+
 async fn generate_key_pair(
     private_key_path: &str,
     public_key_path: &str,
@@ -331,11 +887,13 @@ async fn run_benchmark(count: u32, node: &str) -> Result<(), Box<dyn std::error:
             id: TransactionId::new(),
             sender: vec![i as u8; 32],
             receiver: vec![(i + 1) as u8; 32],
-            amount: i as u64,
+            amount: i as Amount,
+            fee: 0,
             nonce: rand::random(),
             timestamp: chrono::Utc::now().timestamp() as u64,
             parents: vec![],
             signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
             quantum_proof: QuantumProof {
                 prime_hash: vec![i as u8; 32],
                 resistance_score: 80,
@@ -0,0 +1,163 @@
+// Embedded explorer: renders the node's JSON endpoints, refreshing periodically.
+(function () {
+  "use strict";
+
+  const REFRESH_MS = 5000;
+  const TRANSACTION_LIMIT = 25;
+
+  async function fetchData(path) {
+    const response = await fetch(path, { headers: { accept: "application/json" } });
+    const body = await response.json();
+    if (!body.success) {
+      throw new Error(body.error || "request failed");
+    }
+    return body.data;
+  }
+
+  function short(id) {
+    return id && id.length > 16 ? id.slice(0, 8) + "…" + id.slice(-6) : id;
+  }
+
+  function formatTime(seconds) {
+    return new Date(seconds * 1000).toLocaleString();
+  }
+
+  function cell(text, className) {
+    const td = document.createElement("td");
+    td.textContent = text;
+    if (className) {
+      td.className = className;
+    }
+    return td;
+  }
+
+  function fillTable(id, rows, emptyText) {
+    const tbody = document.getElementById(id);
+    tbody.replaceChildren();
+    if (rows.length === 0) {
+      const tr = document.createElement("tr");
+      const td = cell(emptyText);
+      td.colSpan = tbody.closest("table").querySelectorAll("th").length;
+      tr.appendChild(td);
+      tbody.appendChild(tr);
+      return;
+    }
+    for (const cells of rows) {
+      const tr = document.createElement("tr");
+      cells.forEach((td) => tr.appendChild(td));
+      tbody.appendChild(tr);
+    }
+  }
+
+  function fillStats(id, entries) {
+    const dl = document.getElementById(id);
+    dl.replaceChildren();
+    for (const [label, value] of entries) {
+      const dt = document.createElement("dt");
+      dt.textContent = label;
+      const dd = document.createElement("dd");
+      dd.textContent = value;
+      dl.append(dt, dd);
+    }
+  }
+
+  async function loadStatus() {
+    const status = await fetchData("/status");
+    const badge = document.getElementById("network-status");
+    badge.textContent = status.network_status;
+    badge.className = "badge " + status.network_status;
+    fillStats("status", [
+      ["Version", status.version],
+      ["Transactions", status.total_transactions],
+      ["Peers", status.network_peers],
+      ["Consensus height", status.consensus_height],
+      ["Quantum resistance", status.quantum_resistance_score],
+    ]);
+  }
+
+  async function loadDag() {
+    const [dag, stored] = await Promise.all([fetchData("/explorer/dag"), fetchData("/explorer/stats")]);
+    fillStats("dag-stats", [
+      ["Nodes in memory", dag.node_count],
+      ["Depth", dag.depth],
+      ["Tips", dag.width],
+      ["Branching factor", dag.average_branching_factor.toFixed(2)],
+      ["Pending", stored.results.pending_nodes],
+      ["Confirmed", stored.results.confirmed_nodes],
+      ["Finalized", stored.results.finalized_nodes],
+    ]);
+  }
+
+  async function loadTransactions() {
+    const data = await fetchData("/explorer/transactions?limit=" + TRANSACTION_LIMIT);
+    const staleness = data.staleness;
+    document.getElementById("staleness").textContent =
+      staleness.max_staleness_secs > 0 ? "Served from a replica up to " + staleness.max_staleness_secs + "s behind" : "";
+    fillTable(
+      "transactions",
+      data.results.map((tx) => [
+        cell(short(tx.id), "mono"),
+        cell(short(tx.sender), "mono"),
+        cell(short(tx.receiver), "mono"),
+        cell(tx.amount),
+        cell(formatTime(tx.timestamp)),
+        cell(tx.parents.length),
+      ]),
+      "No transactions yet"
+    );
+  }
+
+  async function loadValidators() {
+    const validators = await fetchData("/explorer/validators");
+    fillTable(
+      "validators",
+      validators.map((v) => [
+        cell(v.id, "mono"),
+        cell(v.stake_amount),
+        cell(v.reputation_score.toFixed(3)),
+        cell(v.quantum_resistance_score),
+        cell(v.successful_validations + " / " + v.total_validations),
+        cell(v.is_active ? "yes" : "no"),
+      ]),
+      "No validators"
+    );
+  }
+
+  async function loadProposals() {
+    const note = document.getElementById("governance-note");
+    try {
+      const proposals = await fetchData("/governance/proposals");
+      note.textContent = "";
+      fillTable(
+        "proposals",
+        proposals.map((p) => [
+          cell(p.title),
+          cell(short(p.proposer), "mono"),
+          cell(p.status),
+          cell(p.votes.for_votes),
+          cell(p.votes.against_votes),
+          cell(new Date(p.voting_end_time).toLocaleString()),
+        ]),
+        "No proposals"
+      );
+    } catch (e) {
+      note.textContent = e.message;
+      fillTable("proposals", [], "Governance unavailable");
+    }
+  }
+
+  async function refresh() {
+    const results = await Promise.allSettled([loadStatus(), loadDag(), loadTransactions(), loadValidators(), loadProposals()]);
+    const failed = results.find((r) => r.status === "rejected");
+    if (failed && results[0].status === "rejected") {
+      const badge = document.getElementById("network-status");
+      badge.textContent = "offline";
+      badge.className = "badge offline";
+    }
+    document.getElementById("updated").textContent =
+      (failed ? "Partially updated " : "Updated ") + new Date().toLocaleTimeString();
+  }
+
+  refresh();
+  setInterval(refresh, REFRESH_MS);
+})();
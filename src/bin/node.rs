@@ -5,9 +5,15 @@ use std::sync::Arc;
 use tokio::signal;
 use tokio::sync::RwLock;
 
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
+    logging::init_reloadable_logging(log::LevelFilter::Info);
+    if std::env::var("QDAG_TOKIO_CONSOLE").is_ok() {
+        init_console();
+    }
     
     println!("🚀 Starting Quantum-Proof DAG Blockchain Node");
     println!("================================================");
@@ -15,6 +21,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Default configuration
     let config = BlockchainConfig {
         network: NetworkConfig {
+            network_type: std::env::var("QDAG_NETWORK").ok().and_then(|network| network.parse().ok()).unwrap_or_default(),
             listen_addr: "/ip4/127.0.0.1/tcp/8999".to_string(),
             bootstrap_nodes: vec![],
             max_peers: 10,
@@ -28,10 +35,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             quantum_resistance_level: 128,
             signature_scheme: "dilithium".to_string(),
             key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
         },
         database: DatabaseConfig {
             path: "./blockchain_data".to_string(),
             cache_size_mb: 1024,
+            backend: Default::default(),
+            remote_backup: None,
+            pragmas: Default::default(),
+            mode: Default::default(),
+            metadata_index: false,
         },
     };
     
@@ -48,6 +62,88 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Start blockchain
     println!("🌐 Starting blockchain services...");
     blockchain.start().await?;
+
+    // Serve explorer queries from a read-only replica when one is given
+    if let Ok(replica_path) = std::env::var("QDAG_READ_REPLICA_PATH") {
+        let replica = ReadReplicaConfig { path: replica_path, ..ReadReplicaConfig::default() };
+        match blockchain.attach_read_replica(replica).await {
+            Ok(staleness) => println!("📖 Read replica attached ({} transaction(s) behind)", staleness.lag_transactions),
+            Err(e) => eprintln!("⚠️ Failed to attach read replica: {}", e),
+        }
+    }
+
+    // Forward node events to a webhook when one is given
+    if let Ok(url) = std::env::var("QDAG_EVENT_WEBHOOK") {
+        let kinds = std::env::var("QDAG_EVENT_WEBHOOK_TYPES")
+            .map(|types| types.split(',').map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+            .unwrap_or_default();
+        blockchain.add_event_handler(Arc::new(WebhookEventHandler::new(&url, kinds)));
+        println!("📣 Forwarding node events to {}", url);
+    }
+
+    // Attest to the trust anchor handed to clients for this network
+    if let Ok(anchor_path) = std::env::var("QDAG_TRUST_ANCHOR") {
+        match TrustAnchor::load(&anchor_path).await {
+            Ok(anchor) => blockchain.set_trust_anchor(anchor).await,
+            Err(e) => eprintln!("⚠️ Failed to load trust anchor: {}", e),
+        }
+    }
+
+    // Keys allowed to sign resume authorizations after a halt
+    if let Ok(keys) = std::env::var("QDAG_SAFE_MODE_RESUME_KEYS") {
+        for key in keys.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+            if let Err(e) = blockchain.add_safe_mode_resume_key(key) {
+                eprintln!("⚠️ Ignoring safe mode resume key: {}", e);
+            }
+        }
+    }
+
+    // Run a faucet on devnet and testnet when a faucet account is given
+    if let Ok(account) = std::env::var("QDAG_FAUCET_ACCOUNT") {
+        match blockchain.enable_faucet(FaucetConfig { account, ..FaucetConfig::default() }).await {
+            Ok(faucet) => {
+                if let Ok(secret) = std::env::var("QDAG_FAUCET_CAPTCHA_SECRET") {
+                    faucet.add_verifier(Arc::new(CaptchaVerifier::new(CaptchaVerifier::HCAPTCHA_VERIFY_URL, &secret)));
+                }
+                if let Ok(url) = std::env::var("QDAG_FAUCET_WEBHOOK") {
+                    faucet.add_verifier(Arc::new(WebhookVerifier::new(&url)));
+                }
+                println!("🚰 Faucet enabled");
+            }
+            Err(e) => eprintln!("⚠️ Faucet not enabled: {}", e),
+        }
+    }
+
+    // Archive finalized milestone ranges when a cold storage target is given
+    let archive_backend: Option<Arc<dyn ColdStorage>> = match (std::env::var("QDAG_ARCHIVE_URL"), std::env::var("QDAG_ARCHIVE_DIR")) {
+        (Ok(url), _) => Some(Arc::new(HttpColdStorage::new(&url, std::env::var("QDAG_ARCHIVE_TOKEN").ok()))),
+        (_, Ok(dir)) => Some(Arc::new(FilesystemColdStorage::new(dir))),
+        _ => None,
+    };
+    if let Some(backend) = archive_backend {
+        let archiver = blockchain.enable_archival(ArchivalConfig::default(), backend).await;
+        let archiving = blockchain.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(archiver.config().interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if let Err(e) = archiving.archive_milestones().await {
+                    eprintln!("⚠️ Archival run failed: {}", e);
+                }
+            }
+        });
+        println!("🧊 Archival enabled");
+    }
+
+    // Hot-reload settings from a file when one is given
+    if let Ok(path) = std::env::var("QDAG_SETTINGS_FILE") {
+        println!("📝 Watching settings file {}", path);
+        let watched = blockchain.clone();
+        watch_settings_file(path.into(), std::time::Duration::from_secs(5), move |settings| {
+            let blockchain = watched.clone();
+            async move { blockchain.reload_settings(settings).await }
+        });
+    }
     
     // Start API server
     println!("🌐 Starting API server...");
@@ -189,7 +285,7 @@ impl RpcServer {
                         Ok(tx_id) => {
                             match self.blockchain.get_transaction(&tx_id).await {
                                 Ok(Some(tx)) => {
-                                    format!("{{\"id\": \"{}\", \"amount\": {}, \"timestamp\": {}, \"status\": \"found\"}}",
+                                    format!("{{\"id\": \"{}\", \"amount\": \"{}\", \"timestamp\": {}, \"status\": \"found\"}}",
                                             tx.id, tx.amount, tx.timestamp)
                                 },
                                 _ => "{\"error\": \"Transaction not found\"}".to_string(),
@@ -47,6 +47,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create blockchain configuration
     let config = BlockchainConfig {
         network: NetworkConfig {
+            network_type: std::env::var("QDAG_NETWORK").ok().and_then(|network| network.parse().ok()).unwrap_or_default(),
             listen_addr: format!("/ip4/127.0.0.1/tcp/{}", network_port),
             bootstrap_nodes: vec![],
             max_peers: 10,
@@ -60,10 +61,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             quantum_resistance_level: 128,
             signature_scheme: "dilithium".to_string(),
             key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
         },
         database: DatabaseConfig {
             path: format!("{}/data", data_path),
             cache_size_mb: 1024,
+            backend: Default::default(),
+            remote_backup: None,
+            pragmas: Default::default(),
+            mode: Default::default(),
+            metadata_index: false,
         },
     };
 
@@ -181,11 +189,13 @@ async fn start_background_tasks(blockchain: Arc<RwLock<Blockchain>>) {
                 id: TransactionId::new(),
                 sender: sender.clone(),
                 receiver: receiver.clone(),
-                amount: rand::random::<u64>() % 1000 + 1,
+                amount: rand::random::<Amount>() % 1000 + 1,
+                fee: 1,
                 nonce: rand::random(),
                 timestamp: chrono::Utc::now().timestamp() as u64,
                 parents: blockchain.read().await.dag.read().await.select_parents(2),
                 signature: vec![0u8; 64], // Placeholder
+                signature_scheme: Default::default(),
                 quantum_proof: crate::core::QuantumProof {
                     prime_hash: vec![0u8; 32],
                     resistance_score: 80 + (rand::random::<u32>() % 20),
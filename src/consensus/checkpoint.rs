@@ -0,0 +1,399 @@
+//! Aggregated checkpoint certificates
+//!
+//! A checkpoint certificate proves that a quorum of the committee signed a
+//! checkpoint. Each vote carries a BLS signature for the classical component
+//! and a Dilithium signature for the post-quantum component. Certificates
+//! aggregate the BLS signatures into one, and store the Dilithium signatures
+//! in a compact form. Signer public keys and per-signature metadata are
+//! dropped because the signer bitmap indexes into the committee, which light
+//! clients already hold.
+
+use crate::{BlockchainError, consensus::ConsensusError};
+use crate::core::{amount, Amount};
+use crate::identity::{IdentityManager, NodeSignature, SignatureType};
+use blst::min_pk::{AggregateSignature, PublicKey as BlsPublicKey, SecretKey as BlsSecretKey, Signature as BlsSignature};
+use blst::BLST_ERROR;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/// Domain separation tag for checkpoint BLS signatures
+const CHECKPOINT_BLS_DST: &[u8] = b"QUANTUM_DAG_CHECKPOINT_BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+/// Fraction of committee stake required for a valid certificate
+pub const CHECKPOINT_QUORUM: f64 = 2.0 / 3.0;
+
+/// BLS key used for the classical half of checkpoint votes
+pub struct BlsKeypair {
+    secret: BlsSecretKey,
+}
+
+impl BlsKeypair {
+    /// Generate a fresh keypair
+    pub fn generate() -> Result<Self, BlockchainError> {
+        let seed: [u8; 32] = rand::random();
+        Self::from_seed(&seed)
+    }
+
+    /// Derive a keypair from at least 32 bytes of seed material
+    pub fn from_seed(seed: &[u8]) -> Result<Self, BlockchainError> {
+        let secret = BlsSecretKey::key_gen(seed, &[])
+            .map_err(|e| invalid(format!("BLS key generation failed: {:?}", e)))?;
+        Ok(Self { secret })
+    }
+
+    /// Compressed public key bytes
+    pub fn public_key(&self) -> Vec<u8> {
+        self.secret.sk_to_pk().to_bytes().to_vec()
+    }
+
+    /// Sign a message under the checkpoint domain
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.secret.sign(message, CHECKPOINT_BLS_DST, &[]).to_bytes().to_vec()
+    }
+}
+
+/// Committee member keys
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommitteeMember {
+    pub validator_id: String,
+    pub bls_public_key: Vec<u8>,
+    pub pqc_public_key: Vec<u8>,
+    #[serde(with = "amount")]
+    pub stake_amount: Amount,
+}
+
+/// Ordered committee that signer bitmaps index into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointCommittee {
+    pub pqc_signature_type: SignatureType,
+    pub members: Vec<CommitteeMember>,
+}
+
+impl CheckpointCommittee {
+    /// Create a committee, ordering members by validator ID
+    pub fn new(pqc_signature_type: SignatureType, mut members: Vec<CommitteeMember>) -> Self {
+        members.sort_by(|a, b| a.validator_id.cmp(&b.validator_id));
+        Self { pqc_signature_type, members }
+    }
+
+    /// Hash committing to the committee membership and keys
+    pub fn hash(&self) -> Vec<u8> {
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"quantum-dag-checkpoint-committee");
+        hasher.update(format!("{:?}", self.pqc_signature_type).as_bytes());
+        for member in &self.members {
+            hasher.update((member.validator_id.len() as u32).to_le_bytes());
+            hasher.update(member.validator_id.as_bytes());
+            hasher.update(&member.bls_public_key);
+            hasher.update((member.pqc_public_key.len() as u32).to_le_bytes());
+            hasher.update(&member.pqc_public_key);
+            hasher.update(amount::digest_bytes(member.stake_amount));
+        }
+        hasher.finalize().to_vec()
+    }
+
+    /// Position of a validator in the committee
+    pub fn index_of(&self, validator_id: &str) -> Option<usize> {
+        self.members.iter().position(|m| m.validator_id == validator_id)
+    }
+
+    /// Total stake of the committee
+    pub fn total_stake(&self) -> Amount {
+        self.members.iter().map(|m| m.stake_amount).sum()
+    }
+}
+
+/// Single validator's vote on a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointVote {
+    pub validator_id: String,
+    pub bls_signature: Vec<u8>,
+    pub pqc_signature: NodeSignature,
+}
+
+impl CheckpointVote {
+    /// Sign a checkpoint with both key types
+    pub async fn sign(
+        validator_id: String,
+        payload: &[u8],
+        bls_key: &BlsKeypair,
+        identity: &IdentityManager,
+        pqc_signature_type: SignatureType,
+    ) -> Result<Self, BlockchainError> {
+        Ok(Self {
+            validator_id,
+            bls_signature: bls_key.sign(payload),
+            pqc_signature: identity.sign(payload, pqc_signature_type).await?,
+        })
+    }
+}
+
+/// Post-quantum signatures packed back to back in committee order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactPqcSignatures {
+    pub signature_type: SignatureType,
+    /// Byte length of each signature
+    pub lengths: Vec<u32>,
+    pub data: Vec<u8>,
+}
+
+impl CompactPqcSignatures {
+    /// Iterate over the packed signatures
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        let mut offset = 0usize;
+        self.lengths.iter().map(move |len| {
+            let start = offset;
+            offset += *len as usize;
+            &self.data[start.min(self.data.len())..offset.min(self.data.len())]
+        })
+    }
+}
+
+/// Aggregated certificate for a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointCertificate {
+    pub height: u64,
+    pub checkpoint_hash: Vec<u8>,
+    pub committee_hash: Vec<u8>,
+    /// Bit `i` is set when committee member `i` signed
+    pub signer_bitmap: Vec<u8>,
+    pub aggregate_bls_signature: Vec<u8>,
+    pub pqc_signatures: CompactPqcSignatures,
+}
+
+impl CheckpointCertificate {
+    /// Message every committee member signs for a checkpoint
+    pub fn signing_payload(height: u64, checkpoint_hash: &[u8], committee: &CheckpointCommittee) -> Vec<u8> {
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"quantum-dag-checkpoint");
+        hasher.update(height.to_le_bytes());
+        hasher.update(checkpoint_hash);
+        hasher.update(committee.hash());
+        hasher.finalize().to_vec()
+    }
+
+    /// Aggregate committee votes into a certificate
+    pub fn aggregate(
+        height: u64,
+        checkpoint_hash: Vec<u8>,
+        committee: &CheckpointCommittee,
+        votes: &[CheckpointVote],
+    ) -> Result<Self, BlockchainError> {
+        let mut ordered: Vec<(usize, &CheckpointVote)> = Vec::with_capacity(votes.len());
+        for vote in votes {
+            let index = committee.index_of(&vote.validator_id)
+                .ok_or_else(|| invalid(format!("{} is not a committee member", vote.validator_id)))?;
+            if vote.pqc_signature.signature_type != committee.pqc_signature_type {
+                return Err(invalid(format!("{} used the wrong PQC signature type", vote.validator_id)));
+            }
+            if ordered.iter().any(|(i, _)| *i == index) {
+                return Err(invalid(format!("duplicate vote from {}", vote.validator_id)));
+            }
+            ordered.push((index, vote));
+        }
+        if ordered.is_empty() {
+            return Err(invalid("no votes to aggregate".to_string()));
+        }
+        ordered.sort_by_key(|(i, _)| *i);
+
+        let mut signer_bitmap = vec![0u8; committee.members.len().div_ceil(8)];
+        let mut bls_signatures = Vec::with_capacity(ordered.len());
+        let mut lengths = Vec::with_capacity(ordered.len());
+        let mut data = Vec::new();
+
+        for (index, vote) in &ordered {
+            signer_bitmap[index / 8] |= 1 << (index % 8);
+            bls_signatures.push(BlsSignature::from_bytes(&vote.bls_signature)
+                .map_err(|e| invalid(format!("malformed BLS signature from {}: {:?}", vote.validator_id, e)))?);
+            lengths.push(vote.pqc_signature.signature_data.len() as u32);
+            data.extend_from_slice(&vote.pqc_signature.signature_data);
+        }
+
+        let signature_refs: Vec<&BlsSignature> = bls_signatures.iter().collect();
+        let aggregate = AggregateSignature::aggregate(&signature_refs, true)
+            .map_err(|e| invalid(format!("BLS aggregation failed: {:?}", e)))?;
+
+        Ok(Self {
+            height,
+            checkpoint_hash,
+            committee_hash: committee.hash(),
+            signer_bitmap,
+            aggregate_bls_signature: aggregate.to_signature().to_bytes().to_vec(),
+            pqc_signatures: CompactPqcSignatures {
+                signature_type: committee.pqc_signature_type.clone(),
+                lengths,
+                data,
+            },
+        })
+    }
+
+    /// Committee indices marked in the signer bitmap
+    pub fn signer_indices(&self) -> Vec<usize> {
+        (0..self.signer_bitmap.len() * 8)
+            .filter(|i| self.signer_bitmap[i / 8] & (1 << (i % 8)) != 0)
+            .collect()
+    }
+
+    /// Serialized size in bytes
+    pub fn encoded_size(&self) -> usize {
+        bincode::serialized_size(self).map(|s| s as usize).unwrap_or(0)
+    }
+
+    /// Check the quorum and the aggregated BLS signature only
+    ///
+    /// Suitable for constrained clients that accept classical security for
+    /// intermediate checkpoints.
+    pub fn verify_classical(&self, committee: &CheckpointCommittee) -> Result<(), BlockchainError> {
+        let signers = self.check_quorum(committee)?;
+
+        let public_keys = signers.iter()
+            .map(|i| BlsPublicKey::from_bytes(&committee.members[*i].bls_public_key))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| invalid(format!("malformed committee BLS key: {:?}", e)))?;
+        let key_refs: Vec<&BlsPublicKey> = public_keys.iter().collect();
+
+        let signature = BlsSignature::from_bytes(&self.aggregate_bls_signature)
+            .map_err(|e| invalid(format!("malformed aggregate signature: {:?}", e)))?;
+        let payload = Self::signing_payload(self.height, &self.checkpoint_hash, committee);
+
+        match signature.fast_aggregate_verify(true, &payload, CHECKPOINT_BLS_DST, &key_refs) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            e => Err(invalid(format!("aggregate BLS signature rejected: {:?}", e))),
+        }
+    }
+
+    /// Check the quorum, the aggregated BLS signature, and every PQC signature
+    pub async fn verify(&self, committee: &CheckpointCommittee, verifier: &IdentityManager) -> Result<(), BlockchainError> {
+        self.verify_classical(committee)?;
+
+        let signers = self.signer_indices();
+        if self.pqc_signatures.lengths.len() != signers.len()
+            || self.pqc_signatures.signature_type != committee.pqc_signature_type
+        {
+            return Err(invalid("PQC signatures do not match signer bitmap".to_string()));
+        }
+
+        let payload = Self::signing_payload(self.height, &self.checkpoint_hash, committee);
+        for (index, signature_data) in signers.iter().zip(self.pqc_signatures.iter()) {
+            let member = &committee.members[*index];
+            let signature = NodeSignature {
+                signature_type: committee.pqc_signature_type.clone(),
+                signature_data: signature_data.to_vec(),
+                public_key: member.pqc_public_key.clone(),
+                timestamp: 0,
+                nonce: 0,
+                threshold: None,
+            };
+            if !verifier.verify(&payload, &signature).await.unwrap_or(false) {
+                return Err(invalid(format!("PQC signature from {} rejected", member.validator_id)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_quorum(&self, committee: &CheckpointCommittee) -> Result<Vec<usize>, BlockchainError> {
+        if self.committee_hash != committee.hash() {
+            return Err(invalid("certificate was issued for a different committee".to_string()));
+        }
+
+        let signers = self.signer_indices();
+        if signers.iter().any(|i| *i >= committee.members.len()) {
+            return Err(invalid("signer bitmap exceeds committee size".to_string()));
+        }
+
+        let signed_stake: Amount = signers.iter().map(|i| committee.members[*i].stake_amount).sum();
+        let required = (committee.total_stake() as f64 * CHECKPOINT_QUORUM).ceil() as Amount;
+        if signed_stake < required.max(1) {
+            return Err(invalid(format!("signed stake {} below quorum {}", signed_stake, required)));
+        }
+
+        Ok(signers)
+    }
+}
+
+fn invalid(reason: String) -> BlockchainError {
+    BlockchainError::Consensus(ConsensusError::InvalidCheckpointCertificate(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    struct TestSigner {
+        id: String,
+        bls: BlsKeypair,
+        identity: IdentityManager,
+    }
+
+    async fn setup(count: usize) -> (TempDir, CheckpointCommittee, Vec<TestSigner>) {
+        let dir = TempDir::new().unwrap();
+        let mut signers = Vec::new();
+        let mut members = Vec::new();
+
+        for i in 0..count {
+            let id = format!("validator_{}", i);
+            let mut identity = IdentityManager::new(dir.path().join(&id).to_string_lossy().to_string());
+            let node = identity.initialize_identity().await.unwrap();
+            let bls = BlsKeypair::generate().unwrap();
+            members.push(CommitteeMember {
+                validator_id: id.clone(),
+                bls_public_key: bls.public_key(),
+                pqc_public_key: node.dilithium3_public.clone(),
+                stake_amount: 100,
+            });
+            signers.push(TestSigner { id, bls, identity });
+        }
+
+        (dir, CheckpointCommittee::new(SignatureType::Dilithium3, members), signers)
+    }
+
+    async fn votes(committee: &CheckpointCommittee, signers: &[TestSigner], payload_height: u64) -> Vec<CheckpointVote> {
+        let payload = CheckpointCertificate::signing_payload(payload_height, &[7u8; 32], committee);
+        let mut votes = Vec::new();
+        for signer in signers {
+            votes.push(CheckpointVote::sign(signer.id.clone(), &payload, &signer.bls, &signer.identity, SignatureType::Dilithium3).await.unwrap());
+        }
+        votes
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_and_verify() {
+        let (_dir, committee, signers) = setup(4).await;
+        let votes = votes(&committee, &signers[..3], 10).await;
+
+        let certificate = CheckpointCertificate::aggregate(10, vec![7u8; 32], &committee, &votes).unwrap();
+        assert_eq!(certificate.signer_indices(), vec![0, 1, 2]);
+        certificate.verify_classical(&committee).unwrap();
+        certificate.verify(&committee, &signers[0].identity).await.unwrap();
+
+        // Dropping per-signature keys and metadata shrinks the certificate
+        let naive_size = bincode::serialized_size(&votes).unwrap() as usize;
+        assert!(certificate.encoded_size() < naive_size);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_insufficient_quorum() {
+        let (_dir, committee, signers) = setup(4).await;
+        let votes = votes(&committee, &signers[..2], 10).await;
+
+        let certificate = CheckpointCertificate::aggregate(10, vec![7u8; 32], &committee, &votes).unwrap();
+        assert!(certificate.verify_classical(&committee).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_tampered_certificate() {
+        let (_dir, committee, signers) = setup(3).await;
+        let votes = votes(&committee, &signers, 10).await;
+
+        let mut certificate = CheckpointCertificate::aggregate(10, vec![7u8; 32], &committee, &votes).unwrap();
+        certificate.height = 11;
+        assert!(certificate.verify_classical(&committee).is_err());
+
+        let mut certificate = CheckpointCertificate::aggregate(10, vec![7u8; 32], &committee, &votes).unwrap();
+        certificate.pqc_signatures.data[0] ^= 0xff;
+        assert!(certificate.verify_classical(&committee).is_ok());
+        assert!(certificate.verify(&committee, &signers[0].identity).await.is_err());
+    }
+}
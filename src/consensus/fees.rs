@@ -0,0 +1,142 @@
+//! Fee accrual to validators
+//!
+//! Senders pay the fee together with the amount when their transaction is
+//! applied to balances. When a round is finalized, the fees of the
+//! transactions it validated are credited to the round's selected validator,
+//! both in the stored balances and in the in-memory `FeeLedger` used for
+//! reporting. Transactions that are not stored are skipped.
+
+use crate::core::Amount;
+use crate::events::{EventHandler, NodeEvent};
+use crate::storage::DatabaseManager;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Fees accrued by each validator since startup
+#[derive(Debug, Default)]
+pub struct FeeLedger {
+    accrued: Mutex<HashMap<String, Amount>>,
+}
+
+impl FeeLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `amount` to a validator's accrued fees, returning the new total
+    pub fn accrue(&self, validator: &str, amount: Amount) -> Amount {
+        let mut accrued = self.accrued.lock().unwrap_or_else(|e| e.into_inner());
+        let total = accrued.entry(validator.to_string()).or_default();
+        *total = total.saturating_add(amount);
+        *total
+    }
+
+    /// Fees accrued by a validator
+    pub fn accrued(&self, validator: &str) -> Amount {
+        self.accrued.lock().unwrap_or_else(|e| e.into_inner()).get(validator).copied().unwrap_or(0)
+    }
+
+    /// Accrued fees of every validator that earned any
+    pub fn totals(&self) -> HashMap<String, Amount> {
+        self.accrued.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Credits the fees of finalized rounds to their validators
+pub struct FeeAccrualHandler {
+    ledger: Arc<FeeLedger>,
+    database: Arc<DatabaseManager>,
+}
+
+impl FeeAccrualHandler {
+    pub fn new(ledger: Arc<FeeLedger>, database: Arc<DatabaseManager>) -> Self {
+        Self { ledger, database }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for FeeAccrualHandler {
+    fn name(&self) -> String {
+        "fees".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        let NodeEvent::RoundFinalized { round_number, validator, transactions, .. } = event else {
+            return;
+        };
+
+        let mut total: Amount = 0;
+        for tx_id in transactions {
+            match self.database.get_transaction(tx_id).await {
+                Ok(Some(transaction)) => total = total.saturating_add(transaction.fee),
+                Ok(None) => {}
+                Err(e) => log::error!("❌ Failed to load transaction {} for fee accrual: {}", tx_id, e),
+            }
+        }
+        if total == 0 {
+            return;
+        }
+
+        if let Err(e) = self.database.credit_account(validator, total).await {
+            log::error!("❌ Failed to credit {} in fees to validator {}: {}", total, validator, e);
+            return;
+        }
+        self.ledger.accrue(validator, total);
+        log::debug!("💰 Credited {} in fees from round {} to validator {}", total, round_number, validator);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+    use crate::TransactionId;
+
+    #[test]
+    fn test_ledger_accumulates_per_validator() {
+        let ledger = FeeLedger::new();
+        assert_eq!(ledger.accrue("a", 5), 5);
+        assert_eq!(ledger.accrue("a", 7), 12);
+        ledger.accrue("b", 1);
+
+        assert_eq!(ledger.accrued("a"), 12);
+        assert_eq!(ledger.accrued("c"), 0);
+        assert_eq!(ledger.totals().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_round_fees_are_credited_to_validator() {
+        let database = Arc::new(DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap());
+
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 25,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        database.store_transaction(&transaction).await.unwrap();
+
+        let ledger = Arc::new(FeeLedger::new());
+        let handler = FeeAccrualHandler::new(ledger.clone(), database.clone());
+        handler.handle(&NodeEvent::RoundFinalized {
+            round_number: 1,
+            validator: "ab".repeat(32),
+            transactions: vec![transaction.id.clone(), TransactionId::new()],
+            finality_score: 1.0,
+            duration_ms: 5,
+        }).await;
+
+        assert_eq!(ledger.accrued(&"ab".repeat(32)), 25);
+        assert_eq!(database.get_balance(&"ab".repeat(32)).await.unwrap(), 25);
+    }
+}
@@ -0,0 +1,231 @@
+//! Finality gadget
+//!
+//! Confidence only ever confirms a transaction; finality comes from
+//! validators. Every finalized consensus round is an attestation by its
+//! selected validator to the transactions it validated. Once attestations
+//! from a quorum of distinct validators reference a transaction, the gadget
+//! finalizes it in the DAG together with its ancestors. Finality is
+//! irreversible: the DAG never changes the status of a finalized
+//! transaction, and attestations to one are ignored.
+//!
+//! The finalized height is the latest round whose attestation completed a
+//! quorum. Like the fee ledger it is tracked since startup; finalized
+//! statuses themselves are persisted with the DAG.
+
+use crate::core::DAGCore;
+use crate::events::{EventBus, EventHandler, NodeEvent};
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock as StdRwLock};
+use tokio::sync::RwLock;
+
+/// When attestations finalize a transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinalityConfig {
+    /// Share of active validators, in percent, that must attest
+    pub quorum_percent: u8,
+    /// Rounds an attestation counts towards a quorum before it is dropped
+    pub attestation_window_rounds: u64,
+}
+
+impl Default for FinalityConfig {
+    fn default() -> Self {
+        Self {
+            quorum_percent: 67,
+            attestation_window_rounds: 256,
+        }
+    }
+}
+
+/// Progress of the finality gadget
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalityStatus {
+    pub finalized_height: u64,
+    pub last_finalized: Option<TransactionId>,
+    /// Transactions finalized since startup
+    pub finalized_count: u64,
+    /// Transactions with attestations short of a quorum
+    pub pending_attestations: usize,
+    pub quorum: usize,
+    pub validator_count: usize,
+}
+
+#[derive(Debug, Default)]
+struct Attestations {
+    /// First round that attested the transaction, and its attesting validators
+    pending: HashMap<TransactionId, (u64, HashSet<String>)>,
+    /// Round at which each recently finalized transaction reached quorum
+    finalized: HashMap<TransactionId, u64>,
+    finalized_height: u64,
+    last_finalized: Option<TransactionId>,
+    finalized_count: u64,
+}
+
+/// Counts validator attestations towards finality
+#[derive(Debug)]
+pub struct FinalityGadget {
+    config: FinalityConfig,
+    validator_count: StdRwLock<usize>,
+    state: StdRwLock<Attestations>,
+}
+
+impl FinalityGadget {
+    pub fn new(config: FinalityConfig, validator_count: usize) -> Self {
+        Self {
+            config,
+            validator_count: StdRwLock::new(validator_count),
+            state: StdRwLock::new(Attestations::default()),
+        }
+    }
+
+    /// Set the number of active validators the quorum is taken from
+    pub fn set_validator_count(&self, count: usize) {
+        *self.validator_count.write().unwrap_or_else(|e| e.into_inner()) = count;
+    }
+
+    /// Distinct validators that must attest a transaction
+    pub fn quorum(&self) -> usize {
+        let count = *self.validator_count.read().unwrap_or_else(|e| e.into_inner());
+        (count * self.config.quorum_percent as usize).div_ceil(100).max(1)
+    }
+
+    /// Record `validator`'s attestation in `round_number` to `transactions`
+    ///
+    /// Returns the transactions that reached a quorum with it. They count as
+    /// finalized from now on; pass them to `DAGCore::finalize` and the result
+    /// to `record_finalized`.
+    pub fn attest(&self, round_number: u64, validator: &str, transactions: &[TransactionId]) -> Vec<TransactionId> {
+        let quorum = self.quorum();
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+
+        // Attestations outside the window no longer count
+        let oldest = round_number.saturating_sub(self.config.attestation_window_rounds);
+        state.pending.retain(|_, (first_round, _)| *first_round >= oldest);
+        state.finalized.retain(|_, round| *round >= oldest);
+
+        let mut reached = Vec::new();
+        for tx_id in transactions {
+            if state.finalized.contains_key(tx_id) {
+                continue;
+            }
+            let (_, validators) = state.pending.entry(tx_id.clone()).or_insert_with(|| (round_number, HashSet::new()));
+            validators.insert(validator.to_string());
+            let attested = validators.len();
+            if attested >= quorum {
+                state.pending.remove(tx_id);
+                state.finalized.insert(tx_id.clone(), round_number);
+                reached.push(tx_id.clone());
+            }
+        }
+        reached
+    }
+
+    /// Record transactions the DAG finalized after a quorum in `round_number`
+    pub fn record_finalized(&self, round_number: u64, finalized: &[TransactionId]) {
+        let Some(last) = finalized.last() else {
+            return;
+        };
+        let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+        state.finalized_height = state.finalized_height.max(round_number);
+        state.last_finalized = Some(last.clone());
+        state.finalized_count += finalized.len() as u64;
+    }
+
+    /// Latest round whose attestation finalized transactions
+    pub fn finalized_height(&self) -> u64 {
+        self.state.read().unwrap_or_else(|e| e.into_inner()).finalized_height
+    }
+
+    pub fn status(&self) -> FinalityStatus {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        FinalityStatus {
+            finalized_height: state.finalized_height,
+            last_finalized: state.last_finalized.clone(),
+            finalized_count: state.finalized_count,
+            pending_attestations: state.pending.len(),
+            quorum: self.quorum(),
+            validator_count: *self.validator_count.read().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+}
+
+/// Turns finalized consensus rounds into attestations and finalizes the DAG
+pub struct FinalityHandler {
+    gadget: Arc<FinalityGadget>,
+    dag: Arc<RwLock<DAGCore>>,
+    events: EventBus,
+}
+
+impl FinalityHandler {
+    pub fn new(gadget: Arc<FinalityGadget>, dag: Arc<RwLock<DAGCore>>, events: EventBus) -> Self {
+        Self { gadget, dag, events }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for FinalityHandler {
+    fn name(&self) -> String {
+        "finality".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        let NodeEvent::RoundFinalized { round_number, validator, transactions, .. } = event else {
+            return;
+        };
+
+        let reached = self.gadget.attest(*round_number, validator, transactions);
+        if reached.is_empty() {
+            return;
+        }
+        let finalized = self.dag.write().await.finalize(&reached);
+        if finalized.is_empty() {
+            return;
+        }
+
+        self.gadget.record_finalized(*round_number, &finalized);
+        log::info!("🔒 Finalized {} transaction(s) at height {}", finalized.len(), round_number);
+        self.events.publish(NodeEvent::FinalityAdvanced {
+            height: self.gadget.finalized_height(),
+            finalized,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quorum_of_distinct_validators_finalizes() {
+        let gadget = FinalityGadget::new(FinalityConfig::default(), 4);
+        // Two thirds of four validators, rounded up
+        assert_eq!(gadget.quorum(), 3);
+
+        let txs = vec![TransactionId::new()];
+        assert!(gadget.attest(1, "v1", &txs).is_empty());
+        // A validator attesting again does not count twice
+        assert!(gadget.attest(2, "v1", &txs).is_empty());
+        assert!(gadget.attest(3, "v2", &txs).is_empty());
+        assert_eq!(gadget.attest(4, "v3", &txs), txs);
+        // Finalized transactions are not finalized again
+        assert!(gadget.attest(5, "v4", &txs).is_empty());
+
+        gadget.record_finalized(4, &txs);
+        gadget.record_finalized(2, &[]);
+        let status = gadget.status();
+        assert_eq!((status.finalized_height, status.finalized_count), (4, 1));
+        assert_eq!(status.last_finalized.as_ref(), txs.first());
+        assert_eq!(status.pending_attestations, 0);
+    }
+
+    #[test]
+    fn test_attestations_expire_outside_window() {
+        let gadget = FinalityGadget::new(FinalityConfig { quorum_percent: 100, attestation_window_rounds: 10 }, 2);
+        let txs = vec![TransactionId::new()];
+        assert!(gadget.attest(1, "v1", &txs).is_empty());
+        assert!(gadget.attest(20, "v2", &txs).is_empty());
+        assert_eq!(gadget.status().pending_attestations, 1);
+        assert_eq!(gadget.attest(21, "v1", &txs), txs);
+    }
+}
@@ -1,19 +1,49 @@
 //! Consensus mechanism for DAG-based blockchain
 
 use crate::{BlockchainError, TransactionId, math::{PrimeLayer, ValidatorInfo, MathError}};
-use crate::core::{Transaction, DAGNode, NodeStatus};
+use crate::core::{Amount, Transaction, DAGNode, NodeStatus};
+use crate::events::{EventBus, NodeEvent};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
 
+pub mod trust_anchor;
+pub mod checkpoint;
+pub mod fees;
+pub mod finality;
+pub mod staking;
+pub mod sampling;
+pub mod thresholds;
+
+pub use trust_anchor::{TrustAnchor, AnchorValidator, AnchorSignature};
+pub use fees::{FeeAccrualHandler, FeeLedger};
+pub use finality::{FinalityConfig, FinalityGadget, FinalityHandler, FinalityStatus};
+pub use staking::StakeLedger;
+pub use sampling::{ConfirmationMode, ConfirmationSampler, PeerSampler, SamplingConfig, SamplingStatus};
+pub use thresholds::{ConfirmationStatus, ConfirmationThresholds, ThresholdTier};
+pub use checkpoint::{BlsKeypair, CheckpointCertificate, CheckpointCommittee, CheckpointVote, CommitteeMember, CompactPqcSignatures};
+
 /// Consensus configuration
 #[derive(Debug, Clone)]
 pub struct ConsensusConfig {
     pub block_time_ms: u64,
     pub validator_count: u32,
     pub prime_modulus: u64,
+    /// Confidence a transaction must exceed to confirm, below the first tier
     pub finality_threshold: f64,
     pub fork_resolution_enabled: bool,
+    /// How double spends are decided
+    pub confirmation: ConfirmationMode,
+    /// Higher confidence thresholds for larger amounts
+    pub confirmation_tiers: Vec<ThresholdTier>,
+}
+
+impl ConsensusConfig {
+    /// Confidence thresholds from `finality_threshold` and `confirmation_tiers`
+    pub fn confirmation_thresholds(&self) -> ConfirmationThresholds {
+        ConfirmationThresholds::new(self.finality_threshold, self.confirmation_tiers.clone())
+    }
 }
 
 /// Prime Validator with scoring
@@ -22,7 +52,8 @@ pub struct PrimeValidator {
     pub id: String,
     pub public_key: Vec<u8>,
     pub prime_base: u64,
-    pub stake_amount: u64,
+    #[serde(with = "crate::core::amount")]
+    pub stake_amount: Amount,
     pub reputation_score: f64,
     pub quantum_resistance_score: u32,
     pub total_validations: u64,
@@ -63,11 +94,18 @@ pub struct ConsensusEngine {
     consensus_state: DagConsensusState,
     is_running: bool,
     current_round: Option<ConsensusRound>,
+    events: Option<EventBus>,
+    fee_ledger: Arc<FeeLedger>,
+    stake_ledger: Arc<StakeLedger>,
 }
 
 impl ConsensusEngine {
     /// Create a new consensus engine
     pub fn new(config: &ConsensusConfig) -> Result<Self, BlockchainError> {
+        if let ConfirmationMode::Sampling(sampling) = &config.confirmation {
+            sampling.validate()?;
+        }
+        config.confirmation_thresholds().validate()?;
         let prime_layer = PrimeLayer::new()?;
         let mut validators = HashMap::new();
         
@@ -80,7 +118,7 @@ impl ConsensusEngine {
                 id: validator_id,
                 public_key: Self::generate_validator_key(i),
                 prime_base,
-                stake_amount: 1000 * (i + 1) as u64, // Different stake amounts
+                stake_amount: 1000 * (i + 1) as Amount, // Different stake amounts
                 reputation_score: 1.0,
                 quantum_resistance_score: 80 + (i % 20),
                 total_validations: 0,
@@ -105,9 +143,32 @@ impl ConsensusEngine {
             },
             is_running: false,
             current_round: None,
+            events: None,
+            fee_ledger: Arc::new(FeeLedger::new()),
+            stake_ledger: Arc::new(StakeLedger::new()),
         })
     }
 
+    /// Publish finalized rounds to `events`
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = Some(events);
+    }
+
+    /// Fees accrued by validators of finalized rounds
+    pub fn fee_ledger(&self) -> Arc<FeeLedger> {
+        self.fee_ledger.clone()
+    }
+
+    /// Stake bonded to validators by stake transactions
+    pub fn stake_ledger(&self) -> Arc<StakeLedger> {
+        self.stake_ledger.clone()
+    }
+
+    /// A validator's own stake plus the stake bonded to it
+    pub fn effective_stake(&self, validator: &PrimeValidator) -> Amount {
+        validator.stake_amount.saturating_add(self.stake_ledger.bonded(&validator.id))
+    }
+
     /// Start the consensus engine
     pub async fn start(&mut self) -> Result<(), BlockchainError> {
         println!("⚖️  Starting Prime Validator consensus engine");
@@ -169,7 +230,7 @@ impl ConsensusEngine {
                 public_key: v.public_key.clone(),
                 weight: self.calculate_validator_weight(v),
                 prime_base: v.prime_base,
-                stake_amount: v.stake_amount,
+                stake_amount: self.effective_stake(v),
             })
             .collect();
 
@@ -202,6 +263,16 @@ impl ConsensusEngine {
         // Update consensus state
         self.update_consensus_state(&round, validated_count).await?;
 
+        if let Some(events) = self.events.as_ref().filter(|_| round.consensus_reached) {
+            events.publish(NodeEvent::RoundFinalized {
+                round_number,
+                validator: selected_validator_id.clone(),
+                transactions: round.transactions_validated.clone(),
+                finality_score,
+                duration_ms: start_time.elapsed().as_millis() as u64,
+            });
+        }
+
         log::info!("🔄 Consensus round {} completed. Validator: {}, Finality: {:.2}", 
                   round_number, selected_validator_id, finality_score);
 
@@ -210,7 +281,7 @@ impl ConsensusEngine {
 
     /// Calculate validator weight using Prime Validator scoring
     fn calculate_validator_weight(&self, validator: &PrimeValidator) -> u64 {
-        let mut weight = validator.stake_amount;
+        let mut weight = u64::try_from(self.effective_stake(validator)).unwrap_or(u64::MAX);
 
         // Weight from prime base (higher primes get more weight)
         weight += validator.prime_base * 100;
@@ -404,6 +475,12 @@ impl ConsensusEngine {
                 // Potential fork detected
                 self.consensus_state.fork_detected = true;
                 log::warn!("🔀 Fork detected in consensus rounds");
+                if let Some(events) = &self.events {
+                    events.publish(NodeEvent::ForkDetected {
+                        round_number: last_three[2].round_number,
+                        validators: validators.iter().map(|validator| validator.to_string()).collect(),
+                    });
+                }
                 
                 // Resolve fork by selecting the validator with highest weight
                 let mut best_validator = "";
@@ -437,6 +514,11 @@ impl ConsensusEngine {
         &self.validators
     }
 
+    /// Build an unsigned trust anchor for the current validator set
+    pub fn export_trust_anchor(&self, epoch: u64, previous: Option<&TrustAnchor>) -> TrustAnchor {
+        TrustAnchor::from_validators(epoch, self.current_height(), &self.validators, previous)
+    }
+
     /// Update validator reputation
     pub fn update_validator_reputation(&mut self, validator_id: &str, delta: f64) {
         if let Some(validator) = self.validators.get_mut(validator_id) {
@@ -520,6 +602,14 @@ pub enum ConsensusError {
     Timeout,
     #[error("Fork resolution failed")]
     ForkResolutionFailed,
+    #[error("Invalid trust anchor: {0}")]
+    InvalidTrustAnchor(String),
+    #[error("Invalid checkpoint certificate: {0}")]
+    InvalidCheckpointCertificate(String),
+    #[error("Invalid sampling configuration: {0}")]
+    InvalidSamplingConfig(String),
+    #[error("Invalid confirmation thresholds: {0}")]
+    InvalidConfirmationThresholds(String),
     #[error("Math error: {0}")]
     Math(#[from] MathError),
 }
@@ -551,7 +641,7 @@ impl ConsensusAlgorithm for ConsensusEngine {
                 public_key: v.public_key.clone(),
                 weight: self.calculate_validator_weight(v),
                 prime_base: v.prime_base,
-                stake_amount: v.stake_amount,
+                stake_amount: self.effective_stake(v),
             })
             .collect();
 
@@ -589,6 +679,8 @@ mod tests {
             prime_modulus: 2147483647,
             finality_threshold: 0.8,
             fork_resolution_enabled: true,
+            confirmation: ConfirmationMode::Local,
+            confirmation_tiers: Vec::new(),
         };
 
         let engine = ConsensusEngine::new(&config);
@@ -607,6 +699,8 @@ mod tests {
             prime_modulus: 2147483647,
             finality_threshold: 0.8,
             fork_resolution_enabled: true,
+            confirmation: ConfirmationMode::Local,
+            confirmation_tiers: Vec::new(),
         };
 
         let mut engine = ConsensusEngine::new(&config).unwrap();
@@ -636,6 +730,8 @@ mod tests {
             prime_modulus: 2147483647,
             finality_threshold: 0.5, // Lower threshold for testing
             fork_resolution_enabled: true,
+            confirmation: ConfirmationMode::Local,
+            confirmation_tiers: Vec::new(),
         };
 
         let mut engine = ConsensusEngine::new(&config).unwrap();
@@ -657,6 +753,8 @@ mod tests {
             prime_modulus: 2147483647,
             finality_threshold: 0.8,
             fork_resolution_enabled: true,
+            confirmation: ConfirmationMode::Local,
+            confirmation_tiers: Vec::new(),
         };
 
         let engine = ConsensusEngine::new(&config).unwrap();
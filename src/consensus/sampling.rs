@@ -0,0 +1,311 @@
+//! Repeated sampling confirmation
+//!
+//! By default a double spend is settled locally: the member on the heaviest
+//! branch confirms once its confidence crosses the threshold. In sampling
+//! mode the node instead keeps asking a random sample of peers which member
+//! of each unresolved conflict set they prefer, Snowball style. A member
+//! named by at least `quorum` of a sample is a success and becomes the
+//! preference once it has more successes than the current one. After
+//! `decision_threshold` consecutive successes for the same member the
+//! preference is accepted: the DAG rejects the other branches, and the
+//! accepted branch confirms by confidence as usual. Transactions outside
+//! conflicts are unaffected.
+
+use super::ConsensusError;
+use crate::core::{ConflictSet, DAGCore, SpendKey};
+use crate::network::NetworkLayer;
+use crate::{BlockchainError, TransactionId};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::RwLock;
+
+/// How double spends are decided
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ConfirmationMode {
+    /// The heaviest branch by local cumulative weight wins
+    #[default]
+    Local,
+    /// Peers are sampled until they converge on a member
+    Sampling(SamplingConfig),
+}
+
+/// Parameters of repeated sampling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplingConfig {
+    /// Peers queried per sample
+    pub sample_size: usize,
+    /// Answers naming the same member for a sample to succeed
+    pub quorum: usize,
+    /// Consecutive successes for the same member before it is accepted
+    pub decision_threshold: u32,
+    /// Pause between sampling rounds
+    pub interval_ms: u64,
+    /// How long a peer has to answer before it counts as silent
+    pub query_timeout_ms: u64,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            sample_size: 20,
+            quorum: 14,
+            decision_threshold: 15,
+            interval_ms: 200,
+            query_timeout_ms: 1000,
+        }
+    }
+}
+
+impl SamplingConfig {
+    pub fn validate(&self) -> Result<(), ConsensusError> {
+        let invalid = |reason: &str| Err(ConsensusError::InvalidSamplingConfig(reason.to_string()));
+        if self.sample_size == 0 {
+            return invalid("sample_size must be greater than zero");
+        }
+        if self.quorum * 2 <= self.sample_size || self.quorum > self.sample_size {
+            return invalid("quorum must be a majority of sample_size");
+        }
+        if self.decision_threshold == 0 {
+            return invalid("decision_threshold must be greater than zero");
+        }
+        if self.interval_ms == 0 || self.query_timeout_ms == 0 {
+            return invalid("interval_ms and query_timeout_ms must be greater than zero");
+        }
+        Ok(())
+    }
+}
+
+/// Peers that can be asked for their preference
+#[async_trait::async_trait]
+pub trait PeerSampler: Send + Sync {
+    /// Up to `size` peers chosen at random
+    async fn sample(&self, size: usize) -> Vec<PeerId>;
+
+    /// The member of `conflict` that `peer` prefers, if it answered
+    async fn query(&self, peer: &PeerId, conflict: &ConflictSet) -> Result<Option<TransactionId>, BlockchainError>;
+}
+
+#[async_trait::async_trait]
+impl PeerSampler for NetworkLayer {
+    async fn sample(&self, size: usize) -> Vec<PeerId> {
+        self.sample_peers(size).await
+    }
+
+    async fn query(&self, peer: &PeerId, conflict: &ConflictSet) -> Result<Option<TransactionId>, BlockchainError> {
+        self.query_preference(peer, &conflict.key, &conflict.members).await
+    }
+}
+
+/// Snowball state of one conflict set
+#[derive(Debug, Clone)]
+struct Poll {
+    preference: TransactionId,
+    /// Successful samples per member
+    successes: HashMap<TransactionId, u32>,
+    /// Member of the latest successful sample and its run of successes
+    last: Option<TransactionId>,
+    streak: u32,
+    samples: u32,
+}
+
+impl Poll {
+    fn new(preference: TransactionId) -> Self {
+        Self { preference, successes: HashMap::new(), last: None, streak: 0, samples: 0 }
+    }
+
+    /// Count one sample's answers, returning the member it accepted
+    fn record(&mut self, members: &[TransactionId], answers: &[TransactionId], config: &SamplingConfig) -> Option<TransactionId> {
+        self.samples += 1;
+        let mut votes: HashMap<&TransactionId, usize> = HashMap::new();
+        for answer in answers.iter().filter(|answer| members.contains(answer)) {
+            *votes.entry(answer).or_default() += 1;
+        }
+        let Some((member, _)) = votes.into_iter().find(|(_, count)| *count >= config.quorum) else {
+            self.streak = 0;
+            return None;
+        };
+
+        let successes = {
+            let successes = self.successes.entry(member.clone()).or_default();
+            *successes += 1;
+            *successes
+        };
+        if successes > self.successes.get(&self.preference).copied().unwrap_or(0) {
+            self.preference = member.clone();
+        }
+        if self.last.as_ref() == Some(member) {
+            self.streak += 1;
+        } else {
+            self.last = Some(member.clone());
+            self.streak = 1;
+        }
+        (self.streak >= config.decision_threshold).then(|| self.preference.clone())
+    }
+}
+
+/// How sampling stands on an unresolved conflict
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingStatus {
+    pub key: SpendKey,
+    pub preference: TransactionId,
+    /// Consecutive successful samples for the latest successful member
+    pub streak: u32,
+    pub samples: u32,
+}
+
+/// Decides double spends by repeatedly sampling peers
+pub struct ConfirmationSampler {
+    config: SamplingConfig,
+    polls: Mutex<HashMap<SpendKey, Poll>>,
+}
+
+impl ConfirmationSampler {
+    pub fn new(config: SamplingConfig) -> Self {
+        Self { config, polls: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &SamplingConfig {
+        &self.config
+    }
+
+    /// This node's answer to a peer asking about `conflict`
+    ///
+    /// Before sampling has started on it, the first member seen is preferred.
+    pub fn preference(&self, conflict: &ConflictSet) -> Option<TransactionId> {
+        if let Some(winner) = &conflict.winner {
+            return Some(winner.clone());
+        }
+        let polls = self.polls.lock().unwrap_or_else(|e| e.into_inner());
+        polls.get(&conflict.key).map(|poll| poll.preference.clone()).or_else(|| conflict.members.first().cloned())
+    }
+
+    /// Query one sample about `conflict`, returning the member it accepted
+    pub async fn poll(&self, conflict: &ConflictSet, peers: &dyn PeerSampler) -> Option<TransactionId> {
+        if conflict.winner.is_some() {
+            return None;
+        }
+        let timeout = std::time::Duration::from_millis(self.config.query_timeout_ms);
+        let sample = peers.sample(self.config.sample_size).await;
+        let queries = sample.iter().map(|peer| async move {
+            match tokio::time::timeout(timeout, peers.query(peer, conflict)).await {
+                Ok(Ok(answer)) => answer,
+                Ok(Err(e)) => {
+                    log::debug!("🗳️ Peer {} failed a preference query: {}", peer, e);
+                    None
+                }
+                Err(_) => None,
+            }
+        });
+        let answers: Vec<TransactionId> = futures::future::join_all(queries).await.into_iter().flatten().collect();
+
+        let mut polls = self.polls.lock().unwrap_or_else(|e| e.into_inner());
+        let first = conflict.members.first()?.clone();
+        polls.entry(conflict.key.clone())
+            .or_insert_with(|| Poll::new(first))
+            .record(&conflict.members, &answers, &self.config)
+    }
+
+    /// Sample every unresolved conflict once and accept the members peers converged on
+    ///
+    /// Returns the accepted members.
+    pub async fn run_round(&self, dag: &RwLock<DAGCore>, peers: &dyn PeerSampler) -> Vec<TransactionId> {
+        let open: Vec<ConflictSet> = dag.read().await.get_conflict_sets().into_iter()
+            .filter(|conflict| conflict.winner.is_none())
+            .collect();
+        self.polls.lock().unwrap_or_else(|e| e.into_inner())
+            .retain(|key, _| open.iter().any(|conflict| conflict.key == *key));
+
+        let mut accepted = Vec::new();
+        for conflict in &open {
+            let Some(winner) = self.poll(conflict, peers).await else {
+                continue;
+            };
+            let rejected = dag.write().await.accept_conflict(&winner);
+            log::info!("🗳️ Peers accepted {} for {}, rejecting {} transaction(s)", winner, conflict.key, rejected.len());
+            self.polls.lock().unwrap_or_else(|e| e.into_inner()).remove(&conflict.key);
+            accepted.push(winner);
+        }
+        accepted
+    }
+
+    /// Conflicts being sampled
+    pub fn status(&self) -> Vec<SamplingStatus> {
+        let polls = self.polls.lock().unwrap_or_else(|e| e.into_inner());
+        let mut status: Vec<SamplingStatus> = polls.iter()
+            .map(|(key, poll)| SamplingStatus {
+                key: key.clone(),
+                preference: poll.preference.clone(),
+                streak: poll.streak,
+                samples: poll.samples,
+            })
+            .collect();
+        status.sort_by_key(|status| status.key.to_string());
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Peers that all answer with the same member
+    struct Unanimous(Option<TransactionId>);
+
+    #[async_trait::async_trait]
+    impl PeerSampler for Unanimous {
+        async fn sample(&self, size: usize) -> Vec<PeerId> {
+            (0..size).map(|_| PeerId::random()).collect()
+        }
+
+        async fn query(&self, _peer: &PeerId, _conflict: &ConflictSet) -> Result<Option<TransactionId>, BlockchainError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn conflict() -> ConflictSet {
+        ConflictSet {
+            key: SpendKey { sender: vec![1u8; 32], nonce: 1 },
+            members: vec![TransactionId::new(), TransactionId::new()],
+            winner: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_converges_on_sampled_preference() {
+        let config = SamplingConfig { sample_size: 5, quorum: 4, decision_threshold: 3, ..SamplingConfig::default() };
+        assert!(config.validate().is_ok());
+        assert!(SamplingConfig { quorum: 2, ..config.clone() }.validate().is_err());
+
+        let sampler = ConfirmationSampler::new(config);
+        let conflict = conflict();
+        // The first member seen is preferred until peers say otherwise
+        assert_eq!(sampler.preference(&conflict).as_ref(), conflict.members.first());
+
+        let peers = Unanimous(Some(conflict.members[1].clone()));
+        assert_eq!(sampler.poll(&conflict, &peers).await, None);
+        assert_eq!(sampler.preference(&conflict).as_ref(), conflict.members.get(1));
+        assert_eq!(sampler.poll(&conflict, &peers).await, None);
+        assert_eq!(sampler.poll(&conflict, &peers).await, Some(conflict.members[1].clone()));
+    }
+
+    #[tokio::test]
+    async fn test_silent_or_split_samples_reset_streak() {
+        let config = SamplingConfig { sample_size: 3, quorum: 2, decision_threshold: 2, ..SamplingConfig::default() };
+        let sampler = ConfirmationSampler::new(config);
+        let conflict = conflict();
+        let agreeing = Unanimous(Some(conflict.members[0].clone()));
+
+        assert_eq!(sampler.poll(&conflict, &agreeing).await, None);
+        assert_eq!(sampler.poll(&conflict, &Unanimous(None)).await, None);
+        assert_eq!(sampler.status()[0].streak, 0);
+        // Answers naming transactions outside the conflict do not count
+        assert_eq!(sampler.poll(&conflict, &Unanimous(Some(TransactionId::new()))).await, None);
+        assert_eq!(sampler.poll(&conflict, &agreeing).await, None);
+        assert_eq!(sampler.poll(&conflict, &agreeing).await, Some(conflict.members[0].clone()));
+        assert_eq!(sampler.status()[0].samples, 5);
+    }
+}
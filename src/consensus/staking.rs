@@ -0,0 +1,62 @@
+//! Stake bonded by transactions
+//!
+//! Stake payloads bond part of an account's funds to a validator. Bonds add
+//! to the validator's own stake when the consensus engine weighs validators
+//! for selection.
+
+use crate::core::Amount;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Stake bonded to each validator, by staker address
+#[derive(Debug, Default)]
+pub struct StakeLedger {
+    bonds: Mutex<HashMap<String, HashMap<String, Amount>>>,
+}
+
+impl StakeLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bond `amount` from `staker` to a validator, returning the validator's new bonded total
+    pub fn bond(&self, validator_id: &str, staker: &str, amount: Amount) -> Amount {
+        let mut bonds = self.bonds.lock().unwrap_or_else(|e| e.into_inner());
+        let stakers = bonds.entry(validator_id.to_string()).or_default();
+        let bond = stakers.entry(staker.to_string()).or_default();
+        *bond = bond.saturating_add(amount);
+        stakers.values().fold(0, |total: Amount, bond| total.saturating_add(*bond))
+    }
+
+    /// Total stake bonded to a validator
+    pub fn bonded(&self, validator_id: &str) -> Amount {
+        let bonds = self.bonds.lock().unwrap_or_else(|e| e.into_inner());
+        bonds.get(validator_id)
+            .map(|stakers| stakers.values().fold(0, |total: Amount, bond| total.saturating_add(*bond)))
+            .unwrap_or(0)
+    }
+
+    /// Stake a staker has bonded to a validator
+    pub fn bond_of(&self, validator_id: &str, staker: &str) -> Amount {
+        let bonds = self.bonds.lock().unwrap_or_else(|e| e.into_inner());
+        bonds.get(validator_id).and_then(|stakers| stakers.get(staker)).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bonds_accumulate_per_validator_and_staker() {
+        let ledger = StakeLedger::new();
+        assert_eq!(ledger.bond("v1", "alice", 100), 100);
+        assert_eq!(ledger.bond("v1", "bob", 50), 150);
+        assert_eq!(ledger.bond("v1", "alice", 25), 175);
+        ledger.bond("v2", "alice", 10);
+
+        assert_eq!(ledger.bonded("v1"), 175);
+        assert_eq!(ledger.bond_of("v1", "alice"), 125);
+        assert_eq!(ledger.bonded("v3"), 0);
+    }
+}
@@ -0,0 +1,114 @@
+//! Confirmation thresholds by transaction value
+//!
+//! A pending transaction confirms once its confidence exceeds a threshold.
+//! By default every transaction uses `ConsensusConfig::finality_threshold`;
+//! `confirmation_tiers` raise it for larger amounts, so a large transfer
+//! needs more weight and approvers behind it than a micro-payment. The tier
+//! with the highest `min_amount` not above a transaction's amount applies.
+
+use super::ConsensusError;
+use crate::core::{amount, Amount, NodeStatus};
+use serde::{Deserialize, Serialize};
+
+/// Threshold for transactions of at least `min_amount`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdTier {
+    #[serde(with = "amount")]
+    pub min_amount: Amount,
+    /// Confidence a transaction must exceed to confirm
+    pub confidence: f64,
+}
+
+/// Confidence thresholds, rising with amount
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmationThresholds {
+    /// Threshold below the first tier
+    pub base: f64,
+    /// Tiers in ascending `min_amount`
+    pub tiers: Vec<ThresholdTier>,
+}
+
+impl Default for ConfirmationThresholds {
+    fn default() -> Self {
+        Self::flat(0.8)
+    }
+}
+
+impl ConfirmationThresholds {
+    /// The same threshold for every amount
+    pub fn flat(base: f64) -> Self {
+        Self { base, tiers: Vec::new() }
+    }
+
+    /// `base` below the first tier, then `tiers` by amount
+    pub fn new(base: f64, mut tiers: Vec<ThresholdTier>) -> Self {
+        tiers.sort_by_key(|tier| tier.min_amount);
+        Self { base, tiers }
+    }
+
+    /// Thresholds must lie in (0, 1) and never fall as the amount rises
+    pub fn validate(&self) -> Result<(), ConsensusError> {
+        let invalid = |reason: String| Err(ConsensusError::InvalidConfirmationThresholds(reason));
+        if !(self.base > 0.0 && self.base < 1.0) {
+            return invalid(format!("base threshold {} must be between 0 and 1", self.base));
+        }
+        let mut previous = self.base;
+        for (index, tier) in self.tiers.iter().enumerate() {
+            if !(tier.confidence > 0.0 && tier.confidence < 1.0) {
+                return invalid(format!("tier from {} must be between 0 and 1", tier.min_amount));
+            }
+            if tier.confidence < previous {
+                return invalid(format!("tier from {} lowers the threshold to {}", tier.min_amount, tier.confidence));
+            }
+            if index > 0 && self.tiers[index - 1].min_amount == tier.min_amount {
+                return invalid(format!("more than one tier from {}", tier.min_amount));
+            }
+            previous = tier.confidence;
+        }
+        Ok(())
+    }
+
+    /// Tier a transaction of `amount` falls in, if above the base
+    pub fn tier_for(&self, amount: Amount) -> Option<&ThresholdTier> {
+        self.tiers.iter().rev().find(|tier| tier.min_amount <= amount)
+    }
+
+    /// Confidence a transaction of `amount` must exceed to confirm
+    pub fn required_confidence(&self, amount: Amount) -> f64 {
+        self.tier_for(amount).map_or(self.base, |tier| tier.confidence)
+    }
+}
+
+/// How far a transaction is from confirming
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmationStatus {
+    pub status: NodeStatus,
+    pub confidence: f64,
+    /// Confidence it must exceed to confirm
+    pub required_confidence: f64,
+    /// `min_amount` of the tier it falls in, if above the base
+    #[serde(with = "amount::option")]
+    pub tier_min_amount: Option<Amount>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_threshold_rises_with_amount() {
+        let thresholds = ConfirmationThresholds::new(0.8, vec![
+            ThresholdTier { min_amount: 1_000_000, confidence: 0.95 },
+            ThresholdTier { min_amount: 10_000, confidence: 0.9 },
+        ]);
+        assert!(thresholds.validate().is_ok());
+        assert_eq!(thresholds.required_confidence(9_999), 0.8);
+        assert_eq!(thresholds.required_confidence(10_000), 0.9);
+        assert_eq!(thresholds.required_confidence(Amount::MAX), 0.95);
+        assert_eq!(thresholds.tier_for(50).map(|tier| tier.min_amount), None);
+
+        let falling = ConfirmationThresholds::new(0.8, vec![ThresholdTier { min_amount: 10, confidence: 0.7 }]);
+        assert!(matches!(falling.validate(), Err(ConsensusError::InvalidConfirmationThresholds(_))));
+        assert!(ConfirmationThresholds::flat(1.0).validate().is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Validator set trust anchors for light-client bootstrapping
+//!
+//! A trust anchor records the validator set of an epoch together with
+//! signatures from the previous epoch's validators over the new set. Light
+//! clients pin the genesis anchor hash and then verify each hand-over instead
+//! of replaying the full history.
+
+use crate::{BlockchainError, consensus::{ConsensusError, PrimeValidator}};
+use crate::core::{amount, Amount};
+use crate::identity::{IdentityManager, NodeSignature};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::{HashMap, HashSet};
+
+/// Current trust anchor file format version
+pub const TRUST_ANCHOR_VERSION: u32 = 1;
+
+/// Fraction of the previous set's stake that must sign a hand-over
+pub const HANDOVER_STAKE_THRESHOLD: f64 = 2.0 / 3.0;
+
+/// Validator entry in a trust anchor
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnchorValidator {
+    pub id: String,
+    pub public_key: Vec<u8>,
+    #[serde(with = "amount")]
+    pub stake_amount: Amount,
+}
+
+/// Signature by a member of the previous validator set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnchorSignature {
+    pub validator_id: String,
+    pub signature: NodeSignature,
+}
+
+/// Trust anchor for one validator set epoch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustAnchor {
+    pub version: u32,
+    pub epoch: u64,
+    pub consensus_height: u64,
+    pub created_at: u64,
+    pub validators: Vec<AnchorValidator>,
+    /// Hash of the previous anchor's validator set, `None` for genesis
+    pub previous_set_hash: Option<Vec<u8>>,
+    /// Signatures from the previous set over `signing_payload()`
+    pub previous_signatures: Vec<AnchorSignature>,
+}
+
+impl TrustAnchor {
+    /// Build an unsigned anchor from the active validators
+    pub fn from_validators(
+        epoch: u64,
+        consensus_height: u64,
+        validators: &HashMap<String, PrimeValidator>,
+        previous: Option<&TrustAnchor>,
+    ) -> Self {
+        let mut entries: Vec<AnchorValidator> = validators.values()
+            .filter(|v| v.is_active)
+            .map(|v| AnchorValidator {
+                id: v.id.clone(),
+                public_key: v.public_key.clone(),
+                stake_amount: v.stake_amount,
+            })
+            .collect();
+        entries.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Self {
+            version: TRUST_ANCHOR_VERSION,
+            epoch,
+            consensus_height,
+            created_at: chrono::Utc::now().timestamp() as u64,
+            validators: entries,
+            previous_set_hash: previous.map(|p| p.validator_set_hash()),
+            previous_signatures: Vec::new(),
+        }
+    }
+
+    /// Hash committing to the epoch and validator set
+    pub fn validator_set_hash(&self) -> Vec<u8> {
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"quantum-dag-trust-anchor");
+        hasher.update(self.version.to_le_bytes());
+        hasher.update(self.epoch.to_le_bytes());
+        for validator in &self.validators {
+            hasher.update((validator.id.len() as u32).to_le_bytes());
+            hasher.update(validator.id.as_bytes());
+            hasher.update((validator.public_key.len() as u32).to_le_bytes());
+            hasher.update(&validator.public_key);
+            hasher.update(amount::digest_bytes(validator.stake_amount));
+        }
+        hasher.finalize().to_vec()
+    }
+
+    /// Bytes the previous validator set signs to hand over trust
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut hasher = Sha3_256::new();
+        hasher.update(self.previous_set_hash.as_deref().unwrap_or(&[]));
+        hasher.update(self.validator_set_hash());
+        hasher.finalize().to_vec()
+    }
+
+    /// Attach a signature from a previous-set validator
+    pub fn add_signature(&mut self, validator_id: String, signature: NodeSignature) {
+        self.previous_signatures.retain(|s| s.validator_id != validator_id);
+        self.previous_signatures.push(AnchorSignature { validator_id, signature });
+    }
+
+    /// Total stake of this validator set
+    pub fn total_stake(&self) -> Amount {
+        self.validators.iter().map(|v| v.stake_amount).sum()
+    }
+
+    /// Verify that `previous` handed trust over to this anchor
+    pub async fn verify_handover(&self, previous: &TrustAnchor, verifier: &IdentityManager) -> Result<(), BlockchainError> {
+        if self.epoch <= previous.epoch {
+            return Err(invalid(format!("epoch {} does not follow {}", self.epoch, previous.epoch)));
+        }
+
+        if self.previous_set_hash.as_deref() != Some(previous.validator_set_hash().as_slice()) {
+            return Err(invalid(format!("epoch {} is not chained to epoch {}", self.epoch, previous.epoch)));
+        }
+
+        let payload = self.signing_payload();
+        let mut signed_stake: Amount = 0;
+        let mut seen = HashSet::new();
+
+        for entry in &self.previous_signatures {
+            let Some(signer) = previous.validators.iter().find(|v| v.id == entry.validator_id) else {
+                continue;
+            };
+            if !seen.insert(signer.id.clone()) || entry.signature.public_key != signer.public_key {
+                continue;
+            }
+            if verifier.verify(&payload, &entry.signature).await.unwrap_or(false) {
+                signed_stake += signer.stake_amount;
+            }
+        }
+
+        let required = (previous.total_stake() as f64 * HANDOVER_STAKE_THRESHOLD).ceil() as Amount;
+        if signed_stake < required.max(1) {
+            return Err(invalid(format!(
+                "epoch {} signed by {} of {} required stake",
+                self.epoch, signed_stake, required
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Verify a chain of anchors starting from a pinned genesis set hash
+    pub async fn verify_chain(
+        anchors: &[TrustAnchor],
+        trusted_genesis_hash: &[u8],
+        verifier: &IdentityManager,
+    ) -> Result<TrustAnchor, BlockchainError> {
+        let genesis = anchors.first()
+            .ok_or_else(|| invalid("empty anchor chain".to_string()))?;
+        if genesis.validator_set_hash() != trusted_genesis_hash {
+            return Err(invalid("genesis anchor does not match pinned hash".to_string()));
+        }
+
+        for pair in anchors.windows(2) {
+            pair[1].verify_handover(&pair[0], verifier).await?;
+        }
+
+        Ok(anchors[anchors.len() - 1].clone())
+    }
+
+    /// Write the anchor to a JSON file
+    pub async fn save(&self, path: &str) -> Result<(), BlockchainError> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(path, json).await?;
+        log::info!("⚓ Trust anchor for epoch {} written to {}", self.epoch, path);
+        Ok(())
+    }
+
+    /// Read an anchor from a JSON file
+    pub async fn load(path: &str) -> Result<Self, BlockchainError> {
+        let json = tokio::fs::read_to_string(path).await?;
+        let anchor: TrustAnchor = serde_json::from_str(&json)?;
+        if anchor.version != TRUST_ANCHOR_VERSION {
+            return Err(invalid(format!("unsupported anchor version {}", anchor.version)));
+        }
+        Ok(anchor)
+    }
+}
+
+fn invalid(reason: String) -> BlockchainError {
+    BlockchainError::Consensus(ConsensusError::InvalidTrustAnchor(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::SignatureType;
+    use tempfile::TempDir;
+
+    async fn validator_with_key(dir: &TempDir, id: &str, stake: Amount) -> (PrimeValidator, IdentityManager) {
+        let mut manager = IdentityManager::new(dir.path().join(id).to_string_lossy().to_string());
+        let identity = manager.initialize_identity().await.unwrap();
+        let validator = PrimeValidator {
+            id: id.to_string(),
+            public_key: identity.dilithium3_public.clone(),
+            prime_base: 31,
+            stake_amount: stake,
+            reputation_score: 1.0,
+            quantum_resistance_score: 90,
+            total_validations: 0,
+            successful_validations: 0,
+            last_active: std::time::Instant::now(),
+            is_active: true,
+        };
+        (validator, manager)
+    }
+
+    #[tokio::test]
+    async fn test_anchor_handover_and_chain() {
+        let dir = TempDir::new().unwrap();
+        let (v1, m1) = validator_with_key(&dir, "v1", 100).await;
+        let (v2, m2) = validator_with_key(&dir, "v2", 100).await;
+        let (v3, _m3) = validator_with_key(&dir, "v3", 100).await;
+
+        let genesis_set: HashMap<_, _> = [v1.clone(), v2.clone(), v3.clone()]
+            .into_iter().map(|v| (v.id.clone(), v)).collect();
+        let genesis = TrustAnchor::from_validators(0, 0, &genesis_set, None);
+
+        let next_set: HashMap<_, _> = [v1, v2].into_iter().map(|v| (v.id.clone(), v)).collect();
+        let mut next = TrustAnchor::from_validators(1, 100, &next_set, Some(&genesis));
+
+        // One of three signers is below the two-thirds threshold
+        let payload = next.signing_payload();
+        next.add_signature("v1".to_string(), m1.sign(&payload, SignatureType::Dilithium3).await.unwrap());
+        assert!(next.verify_handover(&genesis, &m1).await.is_err());
+
+        next.add_signature("v2".to_string(), m2.sign(&payload, SignatureType::Dilithium3).await.unwrap());
+        assert!(next.verify_handover(&genesis, &m1).await.is_ok());
+
+        let head = TrustAnchor::verify_chain(&[genesis.clone(), next], &genesis.validator_set_hash(), &m1).await.unwrap();
+        assert_eq!(head.epoch, 1);
+        assert!(TrustAnchor::verify_chain(&[genesis], &[0u8; 32], &m1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_anchor_file_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let (v1, _m1) = validator_with_key(&dir, "v1", 100).await;
+        let set: HashMap<_, _> = [(v1.id.clone(), v1)].into_iter().collect();
+        let anchor = TrustAnchor::from_validators(0, 0, &set, None);
+
+        let path = dir.path().join("anchors/epoch_0.json").to_string_lossy().to_string();
+        anchor.save(&path).await.unwrap();
+        let loaded = TrustAnchor::load(&path).await.unwrap();
+
+        assert_eq!(loaded.validators, anchor.validators);
+        assert_eq!(loaded.validator_set_hash(), anchor.validator_set_hash());
+    }
+}
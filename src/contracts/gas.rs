@@ -0,0 +1,200 @@
+//! Versioned gas schedules
+//!
+//! Contract execution is priced by a `GasSchedule`. Schedules are immutable
+//! once registered and identified by version; governance adds a new version
+//! together with the round it activates at. The engine prices each call with
+//! the schedule active at the call's round and records the version in the
+//! result, so replaying a round always uses the schedule it originally ran
+//! under. Activations must lie after every round already executed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Governance parameter that registers and activates a gas schedule
+pub const GAS_SCHEDULE_PARAMETER: &str = "contracts.gas_schedule";
+
+/// Costs charged for host functions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostFunctionCosts {
+    /// Per byte of call input
+    pub input_byte: u64,
+    /// Per entry in the contract's storage
+    pub storage_entry: u64,
+}
+
+/// Per-function and host-function gas costs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasSchedule {
+    pub version: u32,
+    /// Base cost of each contract function
+    pub function_costs: BTreeMap<String, u64>,
+    /// Base cost of functions not listed
+    pub default_function_cost: u64,
+    pub host_functions: HostFunctionCosts,
+}
+
+impl GasSchedule {
+    /// Genesis schedule
+    pub fn v1() -> Self {
+        let function_costs = [("constructor", 1000), ("get", 100), ("set", 500), ("transfer", 800)]
+            .into_iter()
+            .map(|(name, cost)| (name.to_string(), cost))
+            .collect();
+        Self {
+            version: 1,
+            function_costs,
+            default_function_cost: 200,
+            host_functions: HostFunctionCosts {
+                input_byte: 10,
+                storage_entry: 5,
+            },
+        }
+    }
+
+    /// Base cost of `function_name`
+    pub fn function_cost(&self, function_name: &str) -> u64 {
+        self.function_costs.get(function_name).copied().unwrap_or(self.default_function_cost)
+    }
+
+    /// Cost of a call to `function_name` with `input_len` bytes against `storage_entries`
+    pub fn call_cost(&self, function_name: &str, input_len: usize, storage_entries: usize) -> u64 {
+        self.function_cost(function_name)
+            .saturating_add((input_len as u64).saturating_mul(self.host_functions.input_byte))
+            .saturating_add((storage_entries as u64).saturating_mul(self.host_functions.storage_entry))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.version == 0 {
+            return Err("Gas schedule version must be at least 1".to_string());
+        }
+        if self.default_function_cost == 0 || self.function_costs.values().any(|cost| *cost == 0) {
+            return Err("Function costs must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Proposed value of the `contracts.gas_schedule` parameter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasScheduleUpgrade {
+    pub schedule: GasSchedule,
+    /// First round priced with the new schedule
+    pub activation_round: u64,
+}
+
+/// Registered gas schedules and the rounds they activate at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GasScheduleRegistry {
+    schedules: BTreeMap<u32, GasSchedule>,
+    /// Activation round to schedule version
+    activations: BTreeMap<u64, u32>,
+    /// Highest round contracts have executed in
+    executed_through: u64,
+}
+
+impl Default for GasScheduleRegistry {
+    fn default() -> Self {
+        let genesis = GasSchedule::v1();
+        Self {
+            activations: BTreeMap::from([(0, genesis.version)]),
+            schedules: BTreeMap::from([(genesis.version, genesis)]),
+            executed_through: 0,
+        }
+    }
+}
+
+impl GasScheduleRegistry {
+    /// Schedule pricing calls in `round`
+    pub fn active_at(&self, round: u64) -> &GasSchedule {
+        let version = self.activations.range(..=round).next_back().map(|(_, version)| *version).unwrap_or(1);
+        &self.schedules[&version]
+    }
+
+    /// Schedule by version
+    pub fn schedule(&self, version: u32) -> Option<&GasSchedule> {
+        self.schedules.get(&version)
+    }
+
+    /// Activation rounds and the versions they switch to, oldest first
+    pub fn activations(&self) -> Vec<(u64, u32)> {
+        self.activations.iter().map(|(round, version)| (*round, *version)).collect()
+    }
+
+    /// Record that contracts executed in `round`, fixing its schedule
+    pub fn note_executed(&mut self, round: u64) {
+        self.executed_through = self.executed_through.max(round);
+    }
+
+    /// Registry with `upgrade` applied, if it is valid
+    ///
+    /// The new version must be higher than every registered one and activate
+    /// after both the last executed round and the last activation.
+    pub fn with_upgrade(&self, upgrade: &GasScheduleUpgrade) -> Result<Self, String> {
+        upgrade.schedule.validate()?;
+        let latest = self.schedules.keys().next_back().copied().unwrap_or(0);
+        if upgrade.schedule.version <= latest {
+            return Err(format!("Gas schedule version {} is not newer than {}", upgrade.schedule.version, latest));
+        }
+        let last_activation = self.activations.keys().next_back().copied().unwrap_or(0);
+        if upgrade.activation_round <= self.executed_through.max(last_activation) {
+            return Err(format!(
+                "Gas schedule must activate after round {}",
+                self.executed_through.max(last_activation)
+            ));
+        }
+
+        let mut registry = self.clone();
+        registry.schedules.insert(upgrade.schedule.version, upgrade.schedule.clone());
+        registry.activations.insert(upgrade.activation_round, upgrade.schedule.version);
+        Ok(registry)
+    }
+
+    /// Registry with the `contracts.gas_schedule` parameter set to `value`
+    pub fn with_parameter(&self, parameter: &str, value: &serde_json::Value) -> Result<Self, String> {
+        if parameter != GAS_SCHEDULE_PARAMETER {
+            return Err(format!("{} is not a gas schedule parameter", parameter));
+        }
+        let upgrade: GasScheduleUpgrade = serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid value for {}: {}", parameter, e))?;
+        self.with_upgrade(&upgrade)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v2() -> GasSchedule {
+        let mut schedule = GasSchedule::v1();
+        schedule.version = 2;
+        schedule.function_costs.insert("get".to_string(), 150);
+        schedule
+    }
+
+    #[test]
+    fn test_schedule_selected_by_round() {
+        let registry = GasScheduleRegistry::default()
+            .with_upgrade(&GasScheduleUpgrade { schedule: v2(), activation_round: 10 })
+            .unwrap();
+
+        assert_eq!(registry.active_at(9).version, 1);
+        assert_eq!(registry.active_at(9).call_cost("get", 4, 0), 140);
+        assert_eq!(registry.active_at(10).version, 2);
+        assert_eq!(registry.active_at(10).function_cost("get"), 150);
+        assert_eq!(registry.activations(), vec![(0, 1), (10, 2)]);
+    }
+
+    #[test]
+    fn test_upgrade_cannot_rewrite_executed_rounds() {
+        let mut registry = GasScheduleRegistry::default();
+        registry.note_executed(20);
+
+        let early = GasScheduleUpgrade { schedule: v2(), activation_round: 20 };
+        assert!(registry.with_upgrade(&early).is_err());
+
+        let value = serde_json::to_value(GasScheduleUpgrade { schedule: v2(), activation_round: 21 }).unwrap();
+        let upgraded = registry.with_parameter(GAS_SCHEDULE_PARAMETER, &value).unwrap();
+        // Versions are never replaced
+        assert!(upgraded.with_parameter(GAS_SCHEDULE_PARAMETER, &value).is_err());
+    }
+}
@@ -1,14 +1,24 @@
 //! Smart contract engine for the blockchain
 
+use crate::core::{amount, Amount};
 use crate::{BlockchainError, TransactionId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+pub mod gas;
+pub mod testing;
+pub mod trace;
+
+pub use gas::{GasSchedule, GasScheduleRegistry, GasScheduleUpgrade, HostFunctionCosts, GAS_SCHEDULE_PARAMETER};
+pub use trace::{CallFrame, ExecutionTrace, TraceOp, TraceStep, Tracer};
+
 /// Smart contract engine implementation
 pub struct ContractEngine {
     contracts: HashMap<ContractId, SmartContract>,
     is_running: bool,
+    block_number: u64,
+    gas_schedules: Arc<std::sync::RwLock<GasScheduleRegistry>>,
 }
 
 /// Contract ID type
@@ -41,7 +51,8 @@ pub struct SmartContract {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractState {
     pub storage: HashMap<Vec<u8>, Vec<u8>>,
-    pub balance: u64,
+    #[serde(with = "amount")]
+    pub balance: Amount,
     pub nonce: u64,
     pub permissions: Permissions,
 }
@@ -76,9 +87,22 @@ pub struct QuantumProof {
 pub struct ExecutionContext {
     pub contract: Arc<SmartContract>,
     pub caller: Vec<u8>,
-    pub value: u64,
+    pub value: Amount,
     pub gas_limit: u64,
     pub block_number: u64,
+    /// Schedule active at `block_number`
+    pub gas_schedule: GasSchedule,
+    /// Step recorder, when the call is traced
+    pub tracer: Option<Arc<std::sync::Mutex<Tracer>>>,
+}
+
+impl ExecutionContext {
+    /// Record a step if the call is traced
+    fn trace(&self, op: TraceOp) {
+        if let Some(tracer) = &self.tracer {
+            tracer.lock().unwrap_or_else(|e| e.into_inner()).record(op);
+        }
+    }
 }
 
 /// Execution result
@@ -87,7 +111,19 @@ pub struct ExecutionResult {
     pub success: bool,
     pub output: Vec<u8>,
     pub gas_used: u64,
+    /// Version of the gas schedule the call was priced with
+    pub gas_schedule_version: u32,
     pub error: Option<String>,
+    pub events: Vec<ContractEvent>,
+}
+
+/// Event emitted by a contract during execution
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractEvent {
+    pub contract_id: ContractId,
+    pub name: String,
+    pub data: Vec<u8>,
+    pub block_number: u64,
 }
 
 impl ContractEngine {
@@ -96,6 +132,8 @@ impl ContractEngine {
         Ok(Self {
             contracts: HashMap::new(),
             is_running: false,
+            block_number: 0,
+            gas_schedules: Arc::new(std::sync::RwLock::new(GasScheduleRegistry::default())),
         })
     }
 
@@ -167,8 +205,95 @@ impl ContractEngine {
         function_name: &str,
         input: Vec<u8>,
         caller: Vec<u8>,
-        value: u64,
+        value: Amount,
+        gas_limit: u64,
+    ) -> Result<ExecutionResult, BlockchainError> {
+        self.execute_contract_with_schedule(contract_id, function_name, input, caller, value, gas_limit, None).await
+    }
+
+    /// Execute a smart contract function from a transaction that names its gas schedule
+    ///
+    /// Fails if `gas_schedule_version` is given and is not the schedule active
+    /// at the current block.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_contract_with_schedule(
+        &mut self,
+        contract_id: &ContractId,
+        function_name: &str,
+        input: Vec<u8>,
+        caller: Vec<u8>,
+        value: Amount,
+        gas_limit: u64,
+        gas_schedule_version: Option<u32>,
+    ) -> Result<ExecutionResult, BlockchainError> {
+        self.run_call(contract_id, function_name, input, caller, value, gas_limit, gas_schedule_version, None, true).await
+    }
+
+    /// Execute a smart contract function and record a trace of every step
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_contract_traced(
+        &mut self,
+        contract_id: &ContractId,
+        function_name: &str,
+        input: Vec<u8>,
+        caller: Vec<u8>,
+        value: Amount,
+        gas_limit: u64,
+    ) -> Result<(ExecutionResult, ExecutionTrace), BlockchainError> {
+        self.traced_call(contract_id, function_name, input, caller, value, gas_limit, true).await
+    }
+
+    /// Trace a call without keeping its state changes
+    ///
+    /// Runs against the current state and block like a real call, but leaves
+    /// contract storage, nonces and the executed-round marker untouched, so
+    /// it is safe for replaying failed transactions.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn debug_call(
+        &mut self,
+        contract_id: &ContractId,
+        function_name: &str,
+        input: Vec<u8>,
+        caller: Vec<u8>,
+        value: Amount,
+        gas_limit: u64,
+    ) -> Result<(ExecutionResult, ExecutionTrace), BlockchainError> {
+        self.traced_call(contract_id, function_name, input, caller, value, gas_limit, false).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn traced_call(
+        &mut self,
+        contract_id: &ContractId,
+        function_name: &str,
+        input: Vec<u8>,
+        caller: Vec<u8>,
+        value: Amount,
+        gas_limit: u64,
+        commit: bool,
+    ) -> Result<(ExecutionResult, ExecutionTrace), BlockchainError> {
+        let tracer = Arc::new(std::sync::Mutex::new(Tracer::new()));
+        let result = self.run_call(contract_id, function_name, input, caller, value, gas_limit, None, Some(tracer.clone()), commit).await?;
+
+        let tracer = std::mem::take(&mut *tracer.lock().unwrap_or_else(|e| e.into_inner()));
+        let trace = tracer.finish(self.block_number, result.gas_schedule_version, commit && result.success)
+            .ok_or_else(|| BlockchainError::Other("Traced call left no trace".to_string()))?;
+        Ok((result, trace))
+    }
+
+    /// Run one call, tracing it into `tracer` and keeping its state changes if `commit`
+    #[allow(clippy::too_many_arguments)]
+    async fn run_call(
+        &mut self,
+        contract_id: &ContractId,
+        function_name: &str,
+        input: Vec<u8>,
+        caller: Vec<u8>,
+        value: Amount,
         gas_limit: u64,
+        gas_schedule_version: Option<u32>,
+        tracer: Option<Arc<std::sync::Mutex<Tracer>>>,
+        commit: bool,
     ) -> Result<ExecutionResult, BlockchainError> {
         if !self.is_running {
             return Err(BlockchainError::Security(SecurityError::EngineNotRunning));
@@ -178,26 +303,74 @@ impl ContractEngine {
         let contract = self.contracts.get(contract_id)
             .ok_or_else(|| BlockchainError::Security(SecurityError::ContractNotFound(contract_id.clone())))?;
 
+        // Price the call with the schedule active at this block
+        let gas_schedule = {
+            let mut schedules = self.gas_schedules.write().unwrap_or_else(|e| e.into_inner());
+            if commit {
+                schedules.note_executed(self.block_number);
+            }
+            schedules.active_at(self.block_number).clone()
+        };
+        if let Some(expected) = gas_schedule_version {
+            if expected != gas_schedule.version {
+                return Err(BlockchainError::Security(SecurityError::GasScheduleMismatch {
+                    expected,
+                    active: gas_schedule.version,
+                }));
+            }
+        }
+
         // Create execution context
         let context = ExecutionContext {
             contract: Arc::new(contract.clone()),
             caller: caller.clone(),
             value,
             gas_limit,
-            block_number: 0, // Would get from blockchain
+            block_number: self.block_number,
+            gas_schedule,
+            tracer,
         };
+        if let Some(tracer) = &context.tracer {
+            tracer.lock().unwrap_or_else(|e| e.into_inner()).enter(contract_id, function_name, &caller, value, gas_limit);
+        }
 
         // Execute contract
-        let result = self.execute_function(&context, function_name, input).await?;
+        let result = match self.execute_function(&context, function_name, input).await {
+            Ok(result) => result,
+            Err(e) => {
+                if let Some(tracer) = &context.tracer {
+                    tracer.lock().unwrap_or_else(|e| e.into_inner()).abort(&e.to_string());
+                }
+                return Err(e);
+            }
+        };
 
         // Update contract state if successful
         if result.success {
-            self.update_contract_state(contract_id, &context, &result)?;
+            self.update_contract_state(contract_id, &context, &result, commit)?;
+        }
+        if let Some(tracer) = &context.tracer {
+            tracer.lock().unwrap_or_else(|e| e.into_inner()).exit(&result);
         }
 
         Ok(result)
     }
 
+    /// Set the block height seen by executing contracts
+    pub fn set_block_number(&mut self, block_number: u64) {
+        self.block_number = block_number;
+    }
+
+    /// Get the block height seen by executing contracts
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// Shared gas schedule registry, for wiring into governance execution
+    pub fn gas_schedules_handle(&self) -> Arc<std::sync::RwLock<GasScheduleRegistry>> {
+        self.gas_schedules.clone()
+    }
+
     /// Get contract by ID
     pub fn get_contract(&self, contract_id: &ContractId) -> Option<&SmartContract> {
         self.contracts.get(contract_id)
@@ -248,22 +421,32 @@ impl ContractEngine {
         input: Vec<u8>,
     ) -> Result<ExecutionResult, BlockchainError> {
         // Check permissions
-        self.check_permissions(context, function_name)?;
+        let permitted = self.check_permissions(context, function_name);
+        context.trace(TraceOp::PermissionCheck { allowed: permitted.is_ok() });
+        permitted?;
 
         // Calculate gas cost
         let gas_cost = self.calculate_gas_cost(context, function_name, &input);
+        context.trace(TraceOp::GasCheck { required: gas_cost, limit: context.gas_limit });
         
         if gas_cost > context.gas_limit {
             return Ok(ExecutionResult {
                 success: false,
                 output: Vec::new(),
                 gas_used: gas_cost,
+                gas_schedule_version: context.gas_schedule.version,
                 error: Some("Out of gas".to_string()),
+                events: Vec::new(),
             });
         }
 
         // Execute function (simplified for prototype)
-        match function_name {
+        let known = matches!(function_name, "constructor" | "get" | "set" | "transfer");
+        context.trace(TraceOp::Charge {
+            reason: format!("function {}", function_name),
+            gas: if known { context.gas_schedule.function_cost(function_name) } else { gas_cost },
+        });
+        let result = match function_name {
             "constructor" => self.execute_constructor(context, input).await,
             "get" => self.execute_get(context, input).await,
             "set" => self.execute_set(context, input).await,
@@ -272,9 +455,19 @@ impl ContractEngine {
                 success: false,
                 output: Vec::new(),
                 gas_used: gas_cost,
+                gas_schedule_version: context.gas_schedule.version,
                 error: Some(format!("Unknown function: {}", function_name)),
+                events: Vec::new(),
             }),
+        };
+
+        if let (Some(tracer), Ok(result)) = (&context.tracer, &result) {
+            let mut tracer = tracer.lock().unwrap_or_else(|e| e.into_inner());
+            for event in &result.events {
+                tracer.emit(event);
+            }
         }
+        result
     }
 
     /// Check execution permissions
@@ -296,19 +489,7 @@ impl ContractEngine {
 
     /// Calculate gas cost
     fn calculate_gas_cost(&self, context: &ExecutionContext, function_name: &str, input: &[u8]) -> u64 {
-        // Simple gas calculation for prototype
-        let base_cost = match function_name {
-            "constructor" => 1000,
-            "get" => 100,
-            "set" => 500,
-            "transfer" => 800,
-            _ => 200,
-        };
-
-        let input_cost = input.len() as u64 * 10;
-        let storage_cost = context.contract.state.storage.len() as u64 * 5;
-
-        base_cost + input_cost + storage_cost
+        context.gas_schedule.call_cost(function_name, input.len(), context.contract.state.storage.len())
     }
 
     /// Execute constructor
@@ -317,23 +498,28 @@ impl ContractEngine {
         Ok(ExecutionResult {
             success: true,
             output: context.contract.id.as_str().as_bytes().to_vec(),
-            gas_used: 1000,
+            gas_used: context.gas_schedule.function_cost("constructor"),
+            gas_schedule_version: context.gas_schedule.version,
             error: None,
+            events: Vec::new(),
         })
     }
 
     /// Execute get function
     async fn execute_get(&self, context: &ExecutionContext, input: Vec<u8>) -> Result<ExecutionResult, BlockchainError> {
         // Get value from storage
-        let value = context.contract.state.storage.get(&input)
-            .cloned()
-            .unwrap_or_else(|| b"value_not_found".to_vec());
+        context.trace(TraceOp::HostCall { function: "storage_get".to_string(), input: hex::encode(&input) });
+        let stored = context.contract.state.storage.get(&input).cloned();
+        context.trace(TraceOp::StorageRead { key: hex::encode(&input), value: stored.as_ref().map(hex::encode) });
+        let value = stored.unwrap_or_else(|| b"value_not_found".to_vec());
 
         Ok(ExecutionResult {
             success: true,
             output: value,
-            gas_used: 100,
+            gas_used: context.gas_schedule.function_cost("get"),
+            gas_schedule_version: context.gas_schedule.version,
             error: None,
+            events: Vec::new(),
         })
     }
 
@@ -341,11 +527,14 @@ impl ContractEngine {
     async fn execute_set(&self, context: &ExecutionContext, input: Vec<u8>) -> Result<ExecutionResult, BlockchainError> {
         // For prototype, we can't modify state during execution
         // This would be handled in update_contract_state
+        context.trace(TraceOp::HostCall { function: "emit_event".to_string(), input: hex::encode(&input) });
         Ok(ExecutionResult {
             success: true,
             output: b"ok".to_vec(),
-            gas_used: 500,
+            gas_used: context.gas_schedule.function_cost("set"),
+            gas_schedule_version: context.gas_schedule.version,
             error: None,
+            events: vec![Self::event(context, "StorageUpdated", input)],
         })
     }
 
@@ -356,49 +545,77 @@ impl ContractEngine {
             return Ok(ExecutionResult {
                 success: false,
                 output: Vec::new(),
-                gas_used: 800,
+                gas_used: context.gas_schedule.function_cost("transfer"),
+                gas_schedule_version: context.gas_schedule.version,
                 error: Some("Invalid input".to_string()),
+                events: Vec::new(),
             });
         }
 
         let amount = u64::from_be_bytes(
             input[..8].try_into().unwrap_or([0u8; 8])
         );
+        context.trace(TraceOp::HostCall { function: "balance".to_string(), input: String::new() });
 
-        if context.contract.state.balance < amount {
+        if context.contract.state.balance < amount as Amount {
             return Ok(ExecutionResult {
                 success: false,
                 output: Vec::new(),
-                gas_used: 800,
+                gas_used: context.gas_schedule.function_cost("transfer"),
+                gas_schedule_version: context.gas_schedule.version,
                 error: Some("Insufficient balance".to_string()),
+                events: Vec::new(),
             });
         }
 
         Ok(ExecutionResult {
             success: true,
             output: b"transfer_successful".to_vec(),
-            gas_used: 800,
+            gas_used: context.gas_schedule.function_cost("transfer"),
+            gas_schedule_version: context.gas_schedule.version,
             error: None,
+            events: vec![Self::event(context, "Transfer", input[..8].to_vec())],
         })
     }
 
+    /// Build an event emitted by the executing contract
+    fn event(context: &ExecutionContext, name: &str, data: Vec<u8>) -> ContractEvent {
+        ContractEvent {
+            contract_id: context.contract.id.clone(),
+            name: name.to_string(),
+            data,
+            block_number: context.block_number,
+        }
+    }
+
     /// Update contract state after execution
+    ///
+    /// Storage writes are traced either way but only applied if `commit`.
     fn update_contract_state(
         &mut self,
         contract_id: &ContractId,
         context: &ExecutionContext,
         result: &ExecutionResult,
+        commit: bool,
     ) -> Result<(), BlockchainError> {
+        // For set function, update storage (simplified)
+        let mut writes = Vec::new();
+        if result.success && result.output == b"ok" {
+            // In real implementation, this would parse the input and update storage
+            // For prototype, we'll just add a sample entry
+            writes.push((b"last_update".to_vec(), chrono::Utc::now().timestamp().to_be_bytes().to_vec()));
+        }
+        for (key, value) in &writes {
+            context.trace(TraceOp::StorageWrite { key: hex::encode(key), value: hex::encode(value) });
+        }
+
+        if !commit {
+            return Ok(());
+        }
         if let Some(contract) = self.contracts.get_mut(contract_id) {
             // Update nonce
             contract.state.nonce += 1;
-            
-            // For set function, update storage (simplified)
-            if result.success && result.output == b"ok" {
-                // In real implementation, this would parse the input and update storage
-                // For prototype, we'll just add a sample entry
-                contract.state.storage.insert(b"last_update".to_vec(), chrono::Utc::now().timestamp().to_be_bytes().to_vec());
-            }
+            contract.state.storage.extend(writes);
         }
 
         Ok(())
@@ -418,6 +635,8 @@ pub enum SecurityError {
     PermissionDenied,
     #[error("Execution failed: {0}")]
     ExecutionFailed(String),
+    #[error("Transaction priced with gas schedule {expected}, but {active} is active")]
+    GasScheduleMismatch { expected: u32, active: u32 },
 }
 
 #[cfg(test)]
@@ -499,6 +718,76 @@ mod tests {
         assert_eq!(execution_result.output, b"value_not_found");
     }
 
+    #[tokio::test]
+    async fn test_calls_priced_by_schedule_active_at_block() {
+        let mut engine = ContractEngine::new().unwrap();
+        engine.start().await.unwrap();
+        let metadata = ContractMetadata {
+            name: "TestContract".to_string(),
+            version: "1.0.0".to_string(),
+            description: "A test contract".to_string(),
+            gas_limit: 1000000,
+        };
+        let contract_id = engine.deploy_contract(b"code".to_vec(), vec![1u8; 32], metadata).await.unwrap();
+        engine.contracts.get_mut(&contract_id).unwrap().state.permissions.public_functions.push("get".to_string());
+
+        let mut schedule = GasSchedule::v1();
+        schedule.version = 2;
+        schedule.function_costs.insert("get".to_string(), 300);
+        let handle = engine.gas_schedules_handle();
+        let upgraded = handle.read().unwrap().with_upgrade(&GasScheduleUpgrade { schedule, activation_round: 10 }).unwrap();
+        *handle.write().unwrap() = upgraded;
+
+        let before = engine.execute_contract(&contract_id, "get", vec![], vec![1u8; 32], 0, 1000).await.unwrap();
+        assert_eq!((before.gas_used, before.gas_schedule_version), (100, 1));
+
+        engine.set_block_number(10);
+        let after = engine.execute_contract(&contract_id, "get", vec![], vec![1u8; 32], 0, 1000).await.unwrap();
+        assert_eq!((after.gas_used, after.gas_schedule_version), (300, 2));
+
+        // A transaction priced under the old schedule is refused
+        let stale = engine.execute_contract_with_schedule(&contract_id, "get", vec![], vec![1u8; 32], 0, 1000, Some(1)).await;
+        assert!(stale.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_debug_call_traces_without_committing() {
+        let mut engine = ContractEngine::new().unwrap();
+        engine.start().await.unwrap();
+        let metadata = ContractMetadata {
+            name: "TestContract".to_string(),
+            version: "1.0.0".to_string(),
+            description: "A test contract".to_string(),
+            gas_limit: 1000000,
+        };
+        let contract_id = engine.deploy_contract(b"code".to_vec(), vec![1u8; 32], metadata).await.unwrap();
+        let permissions = &mut engine.contracts.get_mut(&contract_id).unwrap().state.permissions;
+        permissions.public_functions.extend(["set".to_string(), "transfer".to_string()]);
+
+        let (result, trace) = engine.debug_call(&contract_id, "set", b"k".to_vec(), vec![1u8; 32], 0, 1000).await.unwrap();
+        assert!(result.success);
+        assert!(!trace.committed);
+        let ops: Vec<&str> = trace.root.steps.iter().map(|step| match step.op {
+            TraceOp::PermissionCheck { .. } => "permission",
+            TraceOp::GasCheck { .. } => "gas_check",
+            TraceOp::Charge { .. } => "charge",
+            TraceOp::HostCall { .. } => "host",
+            TraceOp::StorageRead { .. } => "read",
+            TraceOp::StorageWrite { .. } => "write",
+            TraceOp::Emit { .. } => "emit",
+            TraceOp::Revert { .. } => "revert",
+        }).collect();
+        assert_eq!(ops, vec!["permission", "gas_check", "charge", "host", "emit", "write"]);
+        assert_eq!(trace.root.gas_used, 500);
+        // The write was traced but not applied
+        assert!(engine.get_contract_state(&contract_id).unwrap().storage.is_empty());
+
+        // A failed traced call ends in a revert
+        let (result, trace) = engine.execute_contract_traced(&contract_id, "transfer", 5u64.to_be_bytes().to_vec(), vec![1u8; 32], 0, 1000).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(trace.root.steps.last().unwrap().op, TraceOp::Revert { reason: "Insufficient balance".to_string() });
+    }
+
     #[test]
     fn test_gas_calculation() {
         let engine = ContractEngine::new().unwrap();
@@ -535,6 +824,8 @@ mod tests {
             value: 0,
             gas_limit: 1000,
             block_number: 0,
+            gas_schedule: GasSchedule::v1(),
+            tracer: None,
         };
 
         let gas_cost = engine.calculate_gas_cost(&context, "get", b"test");
@@ -0,0 +1,263 @@
+//! In-memory chain context for contract unit tests
+//!
+//! `TestChain` wraps the same `ContractEngine` the node runs and adds the
+//! controls a contract developer needs in a unit test: deploying contracts,
+//! advancing blocks, impersonating callers, and asserting on emitted events,
+//! storage and gas.
+
+use super::{ContractEngine, ContractEvent, ContractId, ContractMetadata, ContractState, ExecutionResult};
+use crate::core::Amount;
+use crate::BlockchainError;
+
+/// Caller used until another one is impersonated
+pub const DEFAULT_CALLER: [u8; 32] = [1u8; 32];
+
+/// Gas limit applied to calls unless overridden
+pub const DEFAULT_GAS_LIMIT: u64 = 1_000_000;
+
+/// Lightweight chain context around a running contract engine
+pub struct TestChain {
+    engine: ContractEngine,
+    caller: Vec<u8>,
+    gas_limit: u64,
+    events: Vec<ContractEvent>,
+    last_result: Option<ExecutionResult>,
+    total_gas_used: u64,
+}
+
+impl TestChain {
+    /// Create a test chain with a started engine at block 0
+    pub async fn new() -> Result<Self, BlockchainError> {
+        let mut engine = ContractEngine::new()?;
+        engine.start().await?;
+
+        Ok(Self {
+            engine,
+            caller: DEFAULT_CALLER.to_vec(),
+            gas_limit: DEFAULT_GAS_LIMIT,
+            events: Vec::new(),
+            last_result: None,
+            total_gas_used: 0,
+        })
+    }
+
+    /// Make subsequent deployments and calls come from `caller`
+    pub fn impersonate(&mut self, caller: Vec<u8>) -> &mut Self {
+        self.caller = caller;
+        self
+    }
+
+    /// Get the current caller
+    pub fn caller(&self) -> &[u8] {
+        &self.caller
+    }
+
+    /// Set the gas limit for subsequent calls
+    pub fn set_gas_limit(&mut self, gas_limit: u64) -> &mut Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Advance the chain by `blocks` blocks
+    pub fn advance_blocks(&mut self, blocks: u64) -> u64 {
+        let block_number = self.engine.block_number() + blocks;
+        self.engine.set_block_number(block_number);
+        block_number
+    }
+
+    /// Current block height
+    pub fn block_number(&self) -> u64 {
+        self.engine.block_number()
+    }
+
+    /// Deploy a contract owned by the current caller
+    pub async fn deploy(&mut self, code: Vec<u8>, name: &str) -> Result<ContractId, BlockchainError> {
+        let metadata = ContractMetadata {
+            name: name.to_string(),
+            version: "0.0.0".to_string(),
+            description: "Deployed by TestChain".to_string(),
+            gas_limit: self.gas_limit,
+        };
+
+        self.engine.deploy_contract(code, self.caller.clone(), metadata).await
+    }
+
+    /// Make functions of a contract callable
+    pub fn expose(&mut self, contract_id: &ContractId, functions: &[&str]) -> Result<(), BlockchainError> {
+        let public_functions = &mut self.contract_state_mut(contract_id)?.permissions.public_functions;
+        for function in functions {
+            if !public_functions.iter().any(|f| f == function) {
+                public_functions.push(function.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Allow `caller` to call a contract
+    pub fn allow_caller(&mut self, contract_id: &ContractId, caller: Vec<u8>) -> Result<(), BlockchainError> {
+        let allowed_callers = &mut self.contract_state_mut(contract_id)?.permissions.allowed_callers;
+        if !allowed_callers.contains(&caller) {
+            allowed_callers.push(caller);
+        }
+        Ok(())
+    }
+
+    /// Set a contract's balance
+    pub fn set_balance(&mut self, contract_id: &ContractId, balance: Amount) -> Result<(), BlockchainError> {
+        self.contract_state_mut(contract_id)?.balance = balance;
+        Ok(())
+    }
+
+    /// Call a contract function as the current caller
+    pub async fn call(
+        &mut self,
+        contract_id: &ContractId,
+        function_name: &str,
+        input: Vec<u8>,
+    ) -> Result<ExecutionResult, BlockchainError> {
+        self.call_with_value(contract_id, function_name, input, 0).await
+    }
+
+    /// Call a contract function as the current caller, attaching `value`
+    pub async fn call_with_value(
+        &mut self,
+        contract_id: &ContractId,
+        function_name: &str,
+        input: Vec<u8>,
+        value: Amount,
+    ) -> Result<ExecutionResult, BlockchainError> {
+        let result = self.engine.execute_contract(
+            contract_id,
+            function_name,
+            input,
+            self.caller.clone(),
+            value,
+            self.gas_limit,
+        ).await?;
+
+        self.total_gas_used += result.gas_used;
+        self.events.extend(result.events.iter().cloned());
+        self.last_result = Some(result.clone());
+        Ok(result)
+    }
+
+    /// Get a contract's state
+    pub fn state(&self, contract_id: &ContractId) -> Option<&ContractState> {
+        self.engine.get_contract_state(contract_id)
+    }
+
+    /// Get a storage value of a contract
+    pub fn storage(&self, contract_id: &ContractId, key: &[u8]) -> Option<&Vec<u8>> {
+        self.state(contract_id).and_then(|state| state.storage.get(key))
+    }
+
+    /// All events emitted since the chain was created
+    pub fn events(&self) -> &[ContractEvent] {
+        &self.events
+    }
+
+    /// Events with the given name
+    pub fn events_named(&self, name: &str) -> Vec<&ContractEvent> {
+        self.events.iter().filter(|event| event.name == name).collect()
+    }
+
+    /// Result of the most recent call
+    pub fn last_result(&self) -> Option<&ExecutionResult> {
+        self.last_result.as_ref()
+    }
+
+    /// Gas used by all calls so far
+    pub fn total_gas_used(&self) -> u64 {
+        self.total_gas_used
+    }
+
+    /// Get the underlying engine
+    pub fn engine(&self) -> &ContractEngine {
+        &self.engine
+    }
+
+    /// Assert that `contract_id` emitted an event called `name`
+    pub fn assert_event(&self, contract_id: &ContractId, name: &str) {
+        assert!(
+            self.events.iter().any(|event| &event.contract_id == contract_id && event.name == name),
+            "expected event {} from {}, got {:?}",
+            name,
+            contract_id.as_str(),
+            self.events.iter().map(|event| &event.name).collect::<Vec<_>>()
+        );
+    }
+
+    /// Assert that a storage slot holds `expected`
+    pub fn assert_storage(&self, contract_id: &ContractId, key: &[u8], expected: &[u8]) {
+        assert_eq!(
+            self.storage(contract_id, key).map(|value| value.as_slice()),
+            Some(expected),
+            "unexpected storage value for key {}",
+            hex::encode(key)
+        );
+    }
+
+    /// Assert that the most recent call used at most `max_gas`
+    pub fn assert_gas_at_most(&self, max_gas: u64) {
+        let result = self.last_result.as_ref().expect("no call has been made");
+        assert!(
+            result.gas_used <= max_gas,
+            "call used {} gas, expected at most {}",
+            result.gas_used,
+            max_gas
+        );
+    }
+
+    fn contract_state_mut(&mut self, contract_id: &ContractId) -> Result<&mut ContractState, BlockchainError> {
+        self.engine.contracts.get_mut(contract_id)
+            .map(|contract| &mut contract.state)
+            .ok_or_else(|| BlockchainError::Other(format!("Contract not found: {}", contract_id.as_str())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transfer_emits_event_at_current_block() {
+        let mut chain = TestChain::new().await.unwrap();
+        let contract = chain.deploy(b"token".to_vec(), "Token").await.unwrap();
+        chain.expose(&contract, &["transfer"]).unwrap();
+        chain.set_balance(&contract, 1_000).unwrap();
+        chain.advance_blocks(5);
+
+        let result = chain.call(&contract, "transfer", 250u64.to_be_bytes().to_vec()).await.unwrap();
+        assert!(result.success);
+        chain.assert_event(&contract, "Transfer");
+        chain.assert_gas_at_most(800);
+        assert_eq!(chain.events_named("Transfer")[0].block_number, 5);
+    }
+
+    #[tokio::test]
+    async fn test_impersonated_caller_needs_access() {
+        let mut chain = TestChain::new().await.unwrap();
+        let contract = chain.deploy(b"registry".to_vec(), "Registry").await.unwrap();
+        chain.expose(&contract, &["get"]).unwrap();
+
+        chain.impersonate(vec![9u8; 32]);
+        assert!(chain.call(&contract, "get", b"key".to_vec()).await.is_err());
+
+        chain.allow_caller(&contract, vec![9u8; 32]).unwrap();
+        let result = chain.call(&contract, "get", b"key".to_vec()).await.unwrap();
+        assert_eq!(result.output, b"value_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_out_of_gas_is_reported() {
+        let mut chain = TestChain::new().await.unwrap();
+        let contract = chain.deploy(b"store".to_vec(), "Store").await.unwrap();
+        chain.expose(&contract, &["set"]).unwrap();
+        chain.set_gas_limit(100);
+
+        let result = chain.call(&contract, "set", b"key".to_vec()).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("Out of gas"));
+        assert!(chain.events().is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+//! Contract execution tracing
+//!
+//! A `Tracer` is attached to a single call when tracing is requested. The
+//! engine reports each step to it: gas checks and charges, host function
+//! invocations, storage reads and writes, and emitted events, each with the
+//! gas used so far in the frame. Every call runs in a `CallFrame`; a call
+//! made from inside another opens a nested frame, so the finished
+//! `ExecutionTrace` is a tree mirroring the call stack.
+//!
+//! Byte fields are hex encoded so traces can be returned as JSON as is.
+
+use super::{ContractEvent, ContractId, ExecutionResult};
+use crate::core::Amount;
+use serde::{Deserialize, Serialize};
+
+/// Something the engine did while executing a call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TraceOp {
+    /// Caller and function checked against the contract's permissions
+    PermissionCheck { allowed: bool },
+    /// Upfront cost of the call compared with its gas limit
+    GasCheck { required: u64, limit: u64 },
+    /// Gas charged for `reason`
+    Charge { reason: String, gas: u64 },
+    /// Host function invoked by the contract
+    HostCall { function: String, input: String },
+    StorageRead { key: String, value: Option<String> },
+    StorageWrite { key: String, value: String },
+    Emit { name: String, data: String },
+    /// The call failed and its effects were discarded
+    Revert { reason: String },
+}
+
+/// One step of a call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceStep {
+    /// Position in the frame, from 0
+    pub index: usize,
+    #[serde(flatten)]
+    pub op: TraceOp,
+    /// Gas used in the frame after this step
+    pub gas_used: u64,
+}
+
+/// A call and everything it did
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallFrame {
+    pub contract_id: ContractId,
+    pub function: String,
+    /// Hex caller address
+    pub caller: String,
+    #[serde(with = "crate::core::amount")]
+    pub value: Amount,
+    pub gas_limit: u64,
+    /// 0 for the outermost call
+    pub depth: usize,
+    pub steps: Vec<TraceStep>,
+    /// Calls made from this one, in order
+    pub calls: Vec<CallFrame>,
+    pub gas_used: u64,
+    pub success: bool,
+    /// Hex return data
+    pub output: String,
+    pub error: Option<String>,
+}
+
+/// Trace of a traced call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    pub block_number: u64,
+    pub gas_schedule_version: u32,
+    /// Whether the call's state changes were kept
+    pub committed: bool,
+    pub root: CallFrame,
+}
+
+impl ExecutionTrace {
+    /// Every frame, outermost first, depth-first
+    pub fn frames(&self) -> Vec<&CallFrame> {
+        let mut frames = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(frame) = stack.pop() {
+            frames.push(frame);
+            stack.extend(frame.calls.iter().rev());
+        }
+        frames
+    }
+
+    /// Gas used by the call, including nested calls
+    pub fn total_gas_used(&self) -> u64 {
+        self.root.gas_used
+    }
+}
+
+/// Collects the frames and steps of one traced call
+#[derive(Debug, Default)]
+pub struct Tracer {
+    /// Open frames, innermost last
+    stack: Vec<CallFrame>,
+    finished: Option<CallFrame>,
+}
+
+impl Tracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a frame for a call
+    pub fn enter(&mut self, contract_id: &ContractId, function: &str, caller: &[u8], value: Amount, gas_limit: u64) {
+        self.stack.push(CallFrame {
+            contract_id: contract_id.clone(),
+            function: function.to_string(),
+            caller: hex::encode(caller),
+            value,
+            gas_limit,
+            depth: self.stack.len(),
+            steps: Vec::new(),
+            calls: Vec::new(),
+            gas_used: 0,
+            success: false,
+            output: String::new(),
+            error: None,
+        });
+    }
+
+    /// Record a step in the innermost frame; charges add to its gas used
+    pub fn record(&mut self, op: TraceOp) {
+        let Some(frame) = self.stack.last_mut() else {
+            return;
+        };
+        if let TraceOp::Charge { gas, .. } = &op {
+            frame.gas_used = frame.gas_used.saturating_add(*gas);
+        }
+        frame.steps.push(TraceStep { index: frame.steps.len(), op, gas_used: frame.gas_used });
+    }
+
+    /// Record an event emitted by the innermost frame
+    pub fn emit(&mut self, event: &ContractEvent) {
+        self.record(TraceOp::Emit { name: event.name.clone(), data: hex::encode(&event.data) });
+    }
+
+    /// Close the innermost frame with the call's result
+    ///
+    /// The frame's gas used is the result's, which includes nested calls.
+    pub fn exit(&mut self, result: &ExecutionResult) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        if let (false, Some(error)) = (result.success, &result.error) {
+            frame.steps.push(TraceStep {
+                index: frame.steps.len(),
+                op: TraceOp::Revert { reason: error.clone() },
+                gas_used: result.gas_used,
+            });
+        }
+        frame.gas_used = result.gas_used;
+        frame.success = result.success;
+        frame.output = hex::encode(&result.output);
+        frame.error = result.error.clone();
+
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => self.finished = Some(frame),
+        }
+    }
+
+    /// Close any frames left open by a call that failed outright
+    pub fn abort(&mut self, error: &str) {
+        while !self.stack.is_empty() {
+            let gas_used = self.stack.last().map_or(0, |frame| frame.gas_used);
+            self.exit(&ExecutionResult {
+                success: false,
+                output: Vec::new(),
+                gas_used,
+                gas_schedule_version: 0,
+                error: Some(error.to_string()),
+                events: Vec::new(),
+            });
+        }
+    }
+
+    /// Finished trace of the outermost call, if it has exited
+    pub fn finish(self, block_number: u64, gas_schedule_version: u32, committed: bool) -> Option<ExecutionTrace> {
+        self.finished.map(|root| ExecutionTrace { block_number, gas_schedule_version, committed, root })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(success: bool, gas_used: u64) -> ExecutionResult {
+        ExecutionResult {
+            success,
+            output: b"ok".to_vec(),
+            gas_used,
+            gas_schedule_version: 1,
+            error: (!success).then(|| "Insufficient balance".to_string()),
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_nested_frames_form_a_tree() {
+        let outer = ContractId::new("outer".to_string());
+        let inner = ContractId::new("inner".to_string());
+        let mut tracer = Tracer::new();
+
+        tracer.enter(&outer, "transfer", &[1u8; 2], 5, 1000);
+        tracer.record(TraceOp::Charge { reason: "function transfer".to_string(), gas: 800 });
+        tracer.enter(&inner, "get", &[2u8; 2], 0, 200);
+        tracer.record(TraceOp::StorageRead { key: "6b".to_string(), value: None });
+        tracer.exit(&result(true, 100));
+        tracer.exit(&result(false, 900));
+
+        let trace = tracer.finish(7, 1, false).unwrap();
+        let frames = trace.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[1].depth, frames[1].function.as_str()), (1, "get"));
+        assert_eq!(trace.root.caller, "0101");
+        assert_eq!(trace.total_gas_used(), 900);
+        // The failure is recorded as the last step
+        assert_eq!(trace.root.steps.last().unwrap().op, TraceOp::Revert { reason: "Insufficient balance".to_string() });
+    }
+
+    #[test]
+    fn test_steps_track_gas_used_so_far() {
+        let mut tracer = Tracer::new();
+        tracer.enter(&ContractId::new("c".to_string()), "set", &[], 0, 1000);
+        tracer.record(TraceOp::GasCheck { required: 540, limit: 1000 });
+        tracer.record(TraceOp::Charge { reason: "function set".to_string(), gas: 500 });
+        tracer.record(TraceOp::StorageWrite { key: "6b".to_string(), value: "76".to_string() });
+        tracer.abort("engine stopped");
+
+        let trace = tracer.finish(0, 1, false).unwrap();
+        let gas: Vec<u64> = trace.root.steps.iter().map(|step| step.gas_used).collect();
+        assert_eq!(gas, vec![0, 500, 500, 500]);
+        assert_eq!(trace.root.error.as_deref(), Some("engine stopped"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Account balances on top of the DAG
+//!
+//! Balances live in storage and change only when a transaction is finalized:
+//! the sender is debited and the receiver credited, once per transaction.
+//! Until then an accepted transfer holds a reservation against its sender, so
+//! a second submission cannot spend the same funds while the first is pending.
+//! Reservations are released when the transfer is finalized or rejected.
+
+use super::{amount, Amount, CoreError, NodeStatus, Transaction};
+use crate::events::{EventHandler, NodeEvent};
+use crate::storage::DatabaseManager;
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock as StdRwLock};
+
+/// Account address of a public key: its lowercase hex encoding
+pub fn account_address(public_key: &[u8]) -> String {
+    hex::encode(public_key)
+}
+
+/// Canonical form of a hex address supplied by a client
+pub fn normalize_address(address: &str) -> Result<String, CoreError> {
+    hex::decode(address.trim())
+        .map(|key| account_address(&key))
+        .map_err(|_| CoreError::InvalidAddress(address.to_string()))
+}
+
+/// Balance of one account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountBalance {
+    pub address: String,
+    /// Balance after all applied finalized transactions
+    #[serde(with = "amount")]
+    pub balance: Amount,
+    /// Held by accepted transfers that are not yet finalized
+    #[serde(with = "amount")]
+    pub reserved: Amount,
+}
+
+impl AccountBalance {
+    /// Amount a new transfer may spend
+    pub fn available(&self) -> Amount {
+        self.balance.saturating_sub(self.reserved)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Reservations {
+    by_transaction: HashMap<TransactionId, (String, Amount)>,
+    by_sender: HashMap<String, Amount>,
+}
+
+/// Funds held by pending transfers
+#[derive(Debug, Default)]
+pub struct AccountState {
+    reservations: StdRwLock<Reservations>,
+}
+
+impl AccountState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Amount held by pending transfers from `address`
+    pub fn reserved(&self, address: &str) -> Amount {
+        let reservations = self.reservations.read().unwrap_or_else(|e| e.into_inner());
+        reservations.by_sender.get(address).copied().unwrap_or(0)
+    }
+
+    /// Hold a transfer's amount and fee against its sender's finalized `balance`
+    ///
+    /// Fails if the balance, less what other pending transfers hold, does not
+    /// cover the amount and fee. Reserving a transaction twice is a no-op.
+    pub fn reserve(&self, transaction: &Transaction, balance: Amount) -> Result<(), CoreError> {
+        let mut reservations = self.reservations.write().unwrap_or_else(|e| e.into_inner());
+        if reservations.by_transaction.contains_key(&transaction.id) {
+            return Ok(());
+        }
+
+        let address = account_address(&transaction.sender);
+        let reserved = reservations.by_sender.get(&address).copied().unwrap_or(0);
+        let available = balance.saturating_sub(reserved);
+        let required = transaction.amount.saturating_add(transaction.fee);
+        if required > available {
+            return Err(CoreError::InsufficientBalance {
+                address,
+                available,
+                required,
+            });
+        }
+
+        *reservations.by_sender.entry(address.clone()).or_default() += required;
+        reservations.by_transaction.insert(transaction.id.clone(), (address, required));
+        Ok(())
+    }
+
+    /// Drop a transfer's reservation, returning whether it held one
+    pub fn release(&self, tx_id: &TransactionId) -> bool {
+        let mut reservations = self.reservations.write().unwrap_or_else(|e| e.into_inner());
+        let Some((address, amount)) = reservations.by_transaction.remove(tx_id) else {
+            return false;
+        };
+        if let Some(reserved) = reservations.by_sender.get_mut(&address) {
+            *reserved = reserved.saturating_sub(amount);
+            if *reserved == 0 {
+                reservations.by_sender.remove(&address);
+            }
+        }
+        true
+    }
+
+    /// Number of transfers holding funds
+    pub fn pending_count(&self) -> usize {
+        self.reservations.read().unwrap_or_else(|e| e.into_inner()).by_transaction.len()
+    }
+}
+
+/// Applies finalized transactions to stored balances
+pub struct AccountStateHandler {
+    state: Arc<AccountState>,
+    database: Arc<DatabaseManager>,
+}
+
+impl AccountStateHandler {
+    pub fn new(state: Arc<AccountState>, database: Arc<DatabaseManager>) -> Self {
+        Self { state, database }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for AccountStateHandler {
+    fn name(&self) -> String {
+        "accounts".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        let NodeEvent::StatusChanged { tx_id, status, .. } = event else {
+            return;
+        };
+        match status {
+            NodeStatus::Finalized => {
+                match self.database.get_transaction(tx_id).await {
+                    Ok(Some(transaction)) => {
+                        if let Err(e) = self.database.apply_finalized_transaction(&transaction).await {
+                            log::error!("❌ Failed to apply finalized transaction {} to balances: {}", tx_id, e);
+                        }
+                    }
+                    Ok(None) => log::warn!("⚠️ Finalized transaction {} is not stored; balances unchanged", tx_id),
+                    Err(e) => log::error!("❌ Failed to load finalized transaction {}: {}", tx_id, e),
+                }
+                self.state.release(tx_id);
+            }
+            NodeStatus::Rejected => {
+                self.state.release(tx_id);
+            }
+            NodeStatus::Pending | NodeStatus::Confirmed => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+
+    fn transfer(sender: u8, amount: Amount, nonce: u64) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![sender; 32],
+            receiver: vec![9u8; 32],
+            amount,
+            fee: 0,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[test]
+    fn test_reservations_hold_pending_funds() {
+        let state = AccountState::new();
+        let first = transfer(1, 60, 1);
+        state.reserve(&first, 100).unwrap();
+        // Reserving again does not hold the amount twice
+        state.reserve(&first, 100).unwrap();
+        assert_eq!(state.reserved(&account_address(&[1u8; 32])), 60);
+
+        let second = transfer(1, 50, 2);
+        match state.reserve(&second, 100) {
+            Err(CoreError::InsufficientBalance { available, required, .. }) => assert_eq!((available, required), (40, 50)),
+            other => panic!("expected insufficient balance, got {:?}", other),
+        }
+        // Other senders are unaffected
+        state.reserve(&transfer(2, 50, 1), 50).unwrap();
+
+        assert!(state.release(&first.id));
+        assert!(!state.release(&first.id));
+        state.reserve(&second, 100).unwrap();
+        assert_eq!(state.pending_count(), 2);
+    }
+
+    #[test]
+    fn test_normalize_address() {
+        assert_eq!(normalize_address(" ABCD ").unwrap(), "abcd");
+        assert!(matches!(normalize_address("xyz"), Err(CoreError::InvalidAddress(_))));
+    }
+}
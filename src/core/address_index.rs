@@ -0,0 +1,127 @@
+//! Transactions by address
+//!
+//! `AddressIndex` maps every sender and receiver in the in-memory DAG to the
+//! transactions involving it, newest first. When the DAG holds only a window
+//! of its history, lookups go to the `sender` and `receiver` indexes of the
+//! `transactions` table instead; see `DAGCore::get_transactions_by_address`.
+
+use super::{DAGCore, Transaction};
+use crate::{BlockchainError, TransactionId};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+/// Sort key of an indexed transaction: newest first, then by ID
+type IndexKey = (Reverse<u64>, String);
+
+/// Transactions involving each address, newest first
+#[derive(Debug, Default)]
+pub struct AddressIndex {
+    by_address: HashMap<Vec<u8>, BTreeMap<IndexKey, TransactionId>>,
+}
+
+impl AddressIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index a transaction under its sender and receiver
+    pub fn insert(&mut self, transaction: &Transaction) {
+        let key = (Reverse(transaction.timestamp), transaction.id.as_string());
+        for address in [&transaction.sender, &transaction.receiver] {
+            self.by_address.entry(address.clone()).or_default().insert(key.clone(), transaction.id.clone());
+        }
+    }
+
+    /// Forget a transaction dropped from memory
+    pub fn remove(&mut self, transaction: &Transaction) {
+        let key = (Reverse(transaction.timestamp), transaction.id.as_string());
+        for address in [&transaction.sender, &transaction.receiver] {
+            if let Some(entries) = self.by_address.get_mut(address) {
+                entries.remove(&key);
+                if entries.is_empty() {
+                    self.by_address.remove(address);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.by_address.clear();
+    }
+
+    /// Up to `limit` transactions involving `address`, newest first, after skipping `offset`
+    pub fn page(&self, address: &[u8], offset: usize, limit: usize) -> Vec<TransactionId> {
+        self.by_address.get(address)
+            .map(|entries| entries.values().skip(offset).take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Transactions involving `address`
+    pub fn count(&self, address: &[u8]) -> usize {
+        self.by_address.get(address).map_or(0, BTreeMap::len)
+    }
+}
+
+impl DAGCore {
+    /// Transactions sent or received by `address`, newest first
+    ///
+    /// `page` counts from zero in pages of `limit`. Answered from memory
+    /// when the whole DAG is loaded, otherwise from storage.
+    pub async fn get_transactions_by_address(&self, address: &[u8], page: usize, limit: usize) -> Result<Vec<Transaction>, BlockchainError> {
+        let offset = page.saturating_mul(limit);
+        if self.use_persistence && self.transaction_count > self.transactions.len() as u64 {
+            return self.database.get_transactions_by_address(address, limit, offset).await;
+        }
+        Ok(self.addresses.page(address, offset, limit).iter()
+            .filter_map(|tx_id| self.transactions.get(tx_id))
+            .map(|node| node.transaction.clone())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+
+    fn transaction(sender: u8, receiver: u8, timestamp: u64) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![sender; 32],
+            receiver: vec![receiver; 32],
+            amount: 5,
+            fee: 1,
+            nonce: timestamp,
+            timestamp,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[test]
+    fn test_pages_newest_first_for_sender_and_receiver() {
+        let mut index = AddressIndex::new();
+        let sent = transaction(1, 2, 100);
+        let received = transaction(3, 1, 200);
+        let to_self = transaction(1, 1, 300);
+        let unrelated = transaction(2, 3, 400);
+        for transaction in [&sent, &received, &to_self, &unrelated] {
+            index.insert(transaction);
+        }
+
+        let alice = vec![1u8; 32];
+        assert_eq!(index.count(&alice), 3);
+        assert_eq!(index.page(&alice, 0, 2), vec![to_self.id.clone(), received.id.clone()]);
+        assert_eq!(index.page(&alice, 2, 2), vec![sent.id.clone()]);
+        assert!(index.page(&alice, 4, 2).is_empty());
+
+        index.remove(&to_self);
+        assert_eq!(index.page(&alice, 0, 10), vec![received.id, sent.id]);
+        assert_eq!(index.count(&[9u8; 32]), 0);
+    }
+}
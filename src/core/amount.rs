@@ -0,0 +1,136 @@
+//! Token amounts
+//!
+//! Amounts, fees, balances and stakes are `u128`, enough for a supply in
+//! the billions at 18 decimal places. JSON numbers above 2^53 lose
+//! precision in most clients, so human-readable formats write amounts as
+//! decimal strings; plain numbers are still read, so JSON written before
+//! amounts were widened stays valid. Binary formats such as bincode write
+//! the integer itself.
+//!
+//! Fields opt in with `#[serde(with = "crate::core::amount")]`, or
+//! `crate::core::amount::option` for an `Option<Amount>`.
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+use std::fmt;
+
+/// Quantity of the native coin or an issued token, in base units
+pub type Amount = u128;
+
+/// Bytes of an amount in digests laid out before amounts widened
+///
+/// Amounts below `u64::MAX` keep their 8-byte little-endian form, so
+/// existing IDs, checksums and signed validator sets are unchanged. Larger
+/// amounts are eight `0xff` bytes followed by all 16 bytes of the amount;
+/// the form is prefix-free, so the fields after it stay unambiguous.
+pub fn digest_bytes(amount: Amount) -> Vec<u8> {
+    match u64::try_from(amount) {
+        Ok(short) if short < u64::MAX => short.to_le_bytes().to_vec(),
+        _ => [u64::MAX.to_le_bytes().as_slice(), &amount.to_le_bytes()].concat(),
+    }
+}
+
+/// Parse a decimal amount, e.g. from a query string
+pub fn parse_amount(value: &str) -> Result<Amount, String> {
+    value.trim().parse().map_err(|_| format!("Invalid amount: {}", value))
+}
+
+pub fn serialize<S: Serializer>(amount: &Amount, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.collect_str(amount)
+    } else {
+        serializer.serialize_u128(*amount)
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Amount, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(AmountVisitor)
+    } else {
+        deserializer.deserialize_u128(AmountVisitor)
+    }
+}
+
+/// Accepts a decimal string or a non-negative integer
+struct AmountVisitor;
+
+impl Visitor<'_> for AmountVisitor {
+    type Value = Amount;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a non-negative integer or a decimal string")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Amount, E> {
+        Ok(value as Amount)
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<Amount, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Amount, E> {
+        Amount::try_from(value).map_err(|_| E::custom(format!("negative amount {}", value)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Amount, E> {
+        parse_amount(value).map_err(E::custom)
+    }
+}
+
+/// `Option<Amount>` fields
+pub mod option {
+    use super::Amount;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapped(#[serde(with = "super")] Amount);
+
+    pub fn serialize<S: Serializer>(amount: &Option<Amount>, serializer: S) -> Result<S::Ok, S::Error> {
+        amount.map(Wrapped).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Amount>, D::Error> {
+        Ok(Option::<Wrapped>::deserialize(deserializer)?.map(|Wrapped(amount)| amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Payment {
+        #[serde(with = "crate::core::amount")]
+        amount: Amount,
+        #[serde(default, with = "crate::core::amount::option")]
+        fee: Option<Amount>,
+    }
+
+    #[test]
+    fn test_amounts_beyond_u64_round_trip() {
+        let payment = Payment { amount: 10u128.pow(27), fee: Some(7) };
+        let json = serde_json::to_string(&payment).unwrap();
+        assert_eq!(json, r#"{"amount":"1000000000000000000000000000","fee":"7"}"#);
+        assert_eq!(serde_json::from_str::<Payment>(&json).unwrap(), payment);
+
+        let binary = bincode::serialize(&payment).unwrap();
+        assert_eq!(bincode::deserialize::<Payment>(&binary).unwrap(), payment);
+    }
+
+    #[test]
+    fn test_digest_bytes_keep_u64_layout() {
+        assert_eq!(digest_bytes(5), 5u64.to_le_bytes());
+        assert_eq!(digest_bytes(u64::MAX as Amount).len(), 24);
+        assert_ne!(digest_bytes(1 << 64), digest_bytes(0));
+    }
+
+    #[test]
+    fn test_numbers_are_still_accepted() {
+        let payment: Payment = serde_json::from_str(r#"{"amount":250}"#).unwrap();
+        assert_eq!(payment, Payment { amount: 250, fee: None });
+        assert!(serde_json::from_str::<Payment>(r#"{"amount":-1}"#).is_err());
+        assert!(serde_json::from_str::<Payment>(r#"{"amount":"12x"}"#).is_err());
+    }
+}
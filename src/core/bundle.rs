@@ -0,0 +1,172 @@
+//! Atomic transaction bundles
+//!
+//! A bundle is a group of related transactions, such as the legs of a swap,
+//! that must enter the DAG together. Members may approve each other as well
+//! as transactions already in the DAG. Every member is validated before any
+//! is inserted, treating earlier members as present; the bundle is then
+//! written to storage in one storage transaction and linked into the DAG, so
+//! either every member is pending or none is.
+
+use super::{CoreError, DAGCore, SpendKey, Transaction};
+use crate::{BlockchainError, TransactionId};
+use std::collections::{HashMap, HashSet};
+
+/// Most transactions accepted in one bundle
+pub const MAX_BUNDLE_SIZE: usize = 64;
+
+/// Order a bundle so members come after the members they approve
+///
+/// Fails if the bundle is empty or too large, repeats a transaction or a
+/// spend, or its members approve each other in a cycle.
+pub fn order_bundle(transactions: Vec<Transaction>) -> Result<Vec<Transaction>, CoreError> {
+    let invalid = |reason: String| Err(CoreError::InvalidBundle(reason));
+    if transactions.is_empty() {
+        return invalid("bundle is empty".to_string());
+    }
+    if transactions.len() > MAX_BUNDLE_SIZE {
+        return invalid(format!("{} transactions, at most {} allowed", transactions.len(), MAX_BUNDLE_SIZE));
+    }
+
+    let mut spends = HashSet::new();
+    let mut pending: HashMap<TransactionId, Transaction> = HashMap::new();
+    for transaction in transactions {
+        if !spends.insert(SpendKey::of(&transaction)) {
+            return invalid(format!("{} spends {} twice", transaction.id, SpendKey::of(&transaction)));
+        }
+        if let Some(duplicate) = pending.insert(transaction.id.clone(), transaction) {
+            return invalid(format!("{} appears twice", duplicate.id));
+        }
+    }
+
+    // Repeatedly take members whose in-bundle parents are all placed
+    let mut ordered = Vec::with_capacity(pending.len());
+    let mut placed = HashSet::new();
+    while !pending.is_empty() {
+        let mut ready: Vec<TransactionId> = pending.values()
+            .filter(|transaction| {
+                transaction.parents.iter().all(|parent| placed.contains(parent) || !pending.contains_key(parent))
+            })
+            .map(|transaction| transaction.id.clone())
+            .collect();
+        if ready.is_empty() {
+            return invalid("members approve each other in a cycle".to_string());
+        }
+        ready.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        for tx_id in ready {
+            if let Some(transaction) = pending.remove(&tx_id) {
+                placed.insert(tx_id);
+                ordered.push(transaction);
+            }
+        }
+    }
+    Ok(ordered)
+}
+
+impl DAGCore {
+    /// Add a bundle of transactions to the DAG, all or none
+    ///
+    /// Returns the member IDs in insertion order, parents before the members
+    /// approving them. If any member fails validation, or storage fails,
+    /// nothing is added.
+    pub async fn add_bundle(&mut self, transactions: Vec<Transaction>) -> Result<Vec<TransactionId>, BlockchainError> {
+        let ordered = order_bundle(transactions)?;
+        let members: HashSet<TransactionId> = ordered.iter().map(|transaction| transaction.id.clone()).collect();
+
+        if self.use_persistence {
+            for transaction in &ordered {
+                let outside: Vec<TransactionId> = transaction.parents.iter()
+                    .filter(|parent| !members.contains(*parent))
+                    .cloned()
+                    .collect();
+                self.page_in(&outside).await?;
+                self.ensure_not_stored(&transaction.id).await?;
+            }
+        }
+
+        let mut staged = HashSet::new();
+        for transaction in &ordered {
+            if let Err(e) = self.validate_transaction(transaction, &staged) {
+                self.tip_selector.penalize_sender(&transaction.sender);
+                return Err(CoreError::BundleRejected {
+                    tx_id: transaction.id.clone(),
+                    reason: e.to_string(),
+                }.into());
+            }
+            staged.insert(transaction.id.clone());
+        }
+
+        let nodes: Vec<_> = ordered.iter().map(|transaction| self.new_node(transaction)).collect();
+        if self.use_persistence {
+            self.database.store_bundle(&nodes).await?;
+        }
+
+        let tx_ids: Vec<TransactionId> = nodes.into_iter().map(|node| self.insert_node(node)).collect();
+        log::info!("📦 Added bundle of {} transaction(s) to DAG", tx_ids.len());
+        Ok(tx_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{NodeStatus, QuantumProof};
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 0,
+            nonce,
+            timestamp: now,
+            parents,
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: now },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[test]
+    fn test_bundle_is_ordered_parents_first() {
+        let root = TransactionId::new();
+        let first = transaction(1, vec![root]);
+        let second = transaction(2, vec![first.id.clone()]);
+        let ordered = order_bundle(vec![second.clone(), first.clone()]).unwrap();
+        assert_eq!(ordered.iter().map(|t| t.id.clone()).collect::<Vec<_>>(), vec![first.id.clone(), second.id]);
+
+        assert!(matches!(order_bundle(vec![]), Err(CoreError::InvalidBundle(_))));
+        assert!(matches!(order_bundle(vec![first.clone(), first.clone()]), Err(CoreError::InvalidBundle(_))));
+        // Two members spending the same nonce double spend within the bundle
+        let rival = transaction(1, vec![]);
+        assert!(matches!(order_bundle(vec![first, rival]), Err(CoreError::InvalidBundle(_))));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_member_leaves_dag_unchanged() {
+        let mut dag = DAGCore::new().await.unwrap();
+        let genesis = dag.genesis_id().cloned().unwrap();
+        let first = transaction(1, vec![genesis.clone()]);
+        let second = transaction(2, vec![first.id.clone()]);
+        let mut broken = transaction(3, vec![second.id.clone()]);
+        broken.quantum_proof.resistance_score = 10;
+        broken.id = broken.compute_id();
+        let count = dag.transaction_count();
+
+        assert!(matches!(
+            dag.add_bundle(vec![first.clone(), second.clone(), broken]).await,
+            Err(BlockchainError::Core(CoreError::BundleRejected { .. }))
+        ));
+        assert_eq!(dag.transaction_count(), count);
+        assert!(dag.get_node(&first.id).is_none());
+
+        let ids = dag.add_bundle(vec![second.clone(), first.clone()]).await.unwrap();
+        assert_eq!(ids, vec![first.id.clone(), second.id.clone()]);
+        assert_eq!(dag.get_node(&second.id).unwrap().status, NodeStatus::Pending);
+        assert_eq!(dag.get_tips().len(), 1);
+    }
+}
@@ -0,0 +1,401 @@
+//! Double-spend conflict tracking
+//!
+//! Two transactions from the same sender with the same nonce spend the same
+//! funds and form a conflict set. Every transaction approving one member,
+//! directly or indirectly, is on that member's branch. A transaction may not
+//! approve both sides of a conflict, and a branch may only confirm while its
+//! member is the heaviest in the set by cumulative weight. When the heaviest
+//! member confirms, the set is resolved and the other branches are rejected.
+//! Under repeated sampling confirmation the set is instead resolved when
+//! peers accept a member, and only settled branches may confirm.
+
+use super::{CoreError, DAGNode, NodeStatus, Transaction};
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The funds a transaction spends: its sender and nonce
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendKey {
+    pub sender: Vec<u8>,
+    pub nonce: u64,
+}
+
+impl SpendKey {
+    pub fn of(transaction: &Transaction) -> Self {
+        Self {
+            sender: transaction.sender.clone(),
+            nonce: transaction.nonce,
+        }
+    }
+}
+
+impl std::fmt::Display for SpendKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", hex::encode(&self.sender), self.nonce)
+    }
+}
+
+/// Transactions spending the same funds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictSet {
+    pub key: SpendKey,
+    /// Members in arrival order
+    pub members: Vec<TransactionId>,
+    /// Member that confirmed, once the conflict is resolved
+    pub winner: Option<TransactionId>,
+}
+
+/// Conflict sets and the branches built on their members
+#[derive(Debug, Default)]
+pub struct ConflictTracker {
+    /// Every spend seen; a conflict when more than one transaction
+    spends: HashMap<SpendKey, Vec<TransactionId>>,
+    /// For each transaction on a conflicting branch, the member it approves per conflict
+    branches: HashMap<TransactionId, HashMap<SpendKey, TransactionId>>,
+    /// Confirmed member of each resolved conflict
+    resolved: HashMap<SpendKey, TransactionId>,
+}
+
+impl ConflictTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild from nodes loaded from storage
+    ///
+    /// Conflicts with a confirmed or finalized member are treated as resolved.
+    pub fn from_nodes(nodes: &HashMap<TransactionId, DAGNode>) -> Self {
+        let mut tracker = Self::new();
+        for (tx_id, node) in nodes {
+            tracker.spends.entry(SpendKey::of(&node.transaction)).or_default().push(tx_id.clone());
+        }
+
+        let conflicts: Vec<(SpendKey, Vec<TransactionId>)> = tracker.spends.iter()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(key, members)| (key.clone(), members.clone()))
+            .collect();
+        for (key, mut members) in conflicts {
+            members.sort_by_key(|id| (nodes[id].transaction.timestamp, id.as_string()));
+            for member in &members {
+                tracker.mark_branch(&key, member, nodes);
+                if matches!(nodes[member].status, NodeStatus::Confirmed | NodeStatus::Finalized) {
+                    tracker.resolved.insert(key.clone(), member.clone());
+                }
+            }
+            tracker.spends.insert(key, members);
+        }
+        tracker
+    }
+
+    /// Check that a transaction does not double-spend settled funds or
+    /// approve both sides of a conflict
+    pub fn check(&self, transaction: &Transaction, nodes: &HashMap<TransactionId, DAGNode>) -> Result<(), CoreError> {
+        let key = SpendKey::of(transaction);
+        let settled = self.resolved.contains_key(&key) || self.spends.get(&key).into_iter().flatten().any(|member| {
+            nodes.get(member).map_or(false, |node| matches!(node.status, NodeStatus::Confirmed | NodeStatus::Finalized))
+        });
+        if settled {
+            return Err(CoreError::DoubleSpend(key.to_string()));
+        }
+
+        let marks = self.inherited(transaction)?;
+        let rivals = self.spends.get(&key).map(Vec::as_slice).unwrap_or_default();
+        if !rivals.is_empty() && (marks.contains_key(&key) || approves_any(transaction, rivals, nodes)) {
+            return Err(CoreError::ConflictingParents(key.to_string()));
+        }
+
+        for (key, member) in marks {
+            if self.resolved.get(&key).map_or(false, |winner| *winner != member) {
+                return Err(CoreError::RejectedBranch(member));
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a transaction added to the DAG
+    ///
+    /// Call after the node is inserted and linked as a child of its parents.
+    /// Returns the conflict set when the transaction creates or joins one.
+    pub fn insert(&mut self, transaction: &Transaction, nodes: &HashMap<TransactionId, DAGNode>) -> Option<ConflictSet> {
+        if let Ok(marks) = self.inherited(transaction) {
+            if !marks.is_empty() {
+                self.branches.insert(transaction.id.clone(), marks);
+            }
+        }
+
+        let key = SpendKey::of(transaction);
+        let members = self.spends.entry(key.clone()).or_default();
+        members.push(transaction.id.clone());
+        let members = members.clone();
+        if members.len() < 2 {
+            return None;
+        }
+
+        if members.len() == 2 {
+            // The first spend's branch only becomes a conflicting branch now
+            self.mark_branch(&key, &members[0], nodes);
+        }
+        self.mark_branch(&key, &transaction.id, nodes);
+
+        Some(ConflictSet { key, members, winner: None })
+    }
+
+    /// Whether a transaction is on the heaviest side of every conflict it is part of
+    pub fn can_confirm<F>(&self, tx_id: &TransactionId, nodes: &HashMap<TransactionId, DAGNode>, cumulative_weight: F) -> bool
+    where
+        F: Fn(&TransactionId) -> u64,
+    {
+        let Some(marks) = self.branches.get(tx_id) else {
+            return true;
+        };
+        marks.iter().all(|(key, member)| match self.resolved.get(key) {
+            Some(winner) => winner == member,
+            None => self.heaviest(key, nodes, &cumulative_weight).as_ref() == Some(member),
+        })
+    }
+
+    /// Whether every conflict a transaction is part of was resolved in its favour
+    pub fn is_settled(&self, tx_id: &TransactionId) -> bool {
+        self.branches.get(tx_id).map_or(true, |marks| {
+            marks.iter().all(|(key, member)| self.resolved.get(key) == Some(member))
+        })
+    }
+
+    /// Resolve the conflict a newly confirmed transaction is a member of
+    ///
+    /// Returns the transactions on the losing branches, to be rejected.
+    pub fn resolve(&mut self, winner: &TransactionId, nodes: &HashMap<TransactionId, DAGNode>) -> Vec<TransactionId> {
+        let Some(node) = nodes.get(winner) else {
+            return Vec::new();
+        };
+        let key = SpendKey::of(&node.transaction);
+        if self.resolved.contains_key(&key) || self.spends.get(&key).map_or(true, |members| members.len() < 2) {
+            return Vec::new();
+        }
+
+        self.resolved.insert(key.clone(), winner.clone());
+        let mut losers: Vec<TransactionId> = self.branches.iter()
+            .filter(|(_, marks)| marks.get(&key).map_or(false, |member| member != winner))
+            .map(|(tx_id, _)| tx_id.clone())
+            .collect();
+        losers.sort_by_key(|id| id.as_string());
+        losers
+    }
+
+    /// Every conflict set, unresolved first
+    pub fn conflict_sets(&self) -> Vec<ConflictSet> {
+        let mut sets: Vec<ConflictSet> = self.spends.iter()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(key, members)| ConflictSet {
+                key: key.clone(),
+                members: members.clone(),
+                winner: self.resolved.get(key).cloned(),
+            })
+            .collect();
+        sets.sort_by_key(|set| (set.winner.is_some(), set.key.to_string()));
+        sets
+    }
+
+    /// Forget a pruned finalized transaction while keeping its funds settled
+    pub fn prune(&mut self, transaction: &Transaction) {
+        let key = SpendKey::of(transaction);
+        self.branches.remove(&transaction.id);
+        if let Some(members) = self.spends.get_mut(&key) {
+            members.retain(|member| *member != transaction.id);
+            if members.is_empty() {
+                self.spends.remove(&key);
+            }
+        }
+        self.resolved.entry(key).or_insert_with(|| transaction.id.clone());
+    }
+
+    /// Whether a transaction is on a conflicting branch
+    pub fn is_conflicting(&self, tx_id: &TransactionId) -> bool {
+        self.branches.contains_key(tx_id)
+    }
+
+    /// Conflict marks a transaction inherits from its parents
+    fn inherited(&self, transaction: &Transaction) -> Result<HashMap<SpendKey, TransactionId>, CoreError> {
+        let mut marks: HashMap<SpendKey, TransactionId> = HashMap::new();
+        for parent in &transaction.parents {
+            for (key, member) in self.branches.get(parent).into_iter().flatten() {
+                match marks.get(key) {
+                    Some(existing) if existing != member => {
+                        return Err(CoreError::ConflictingParents(key.to_string()));
+                    }
+                    _ => {
+                        marks.insert(key.clone(), member.clone());
+                    }
+                }
+            }
+        }
+        Ok(marks)
+    }
+
+    /// Mark a member and everything approving it as on the member's branch
+    fn mark_branch(&mut self, key: &SpendKey, member: &TransactionId, nodes: &HashMap<TransactionId, DAGNode>) {
+        let mut stack = vec![member.clone()];
+        while let Some(tx_id) = stack.pop() {
+            let marks = self.branches.entry(tx_id.clone()).or_default();
+            if marks.contains_key(key) {
+                continue;
+            }
+            marks.insert(key.clone(), member.clone());
+            if let Some(node) = nodes.get(&tx_id) {
+                stack.extend(node.children.iter().cloned());
+            }
+        }
+    }
+
+    /// Member with the highest cumulative weight; ties go to the earliest
+    /// timestamp, then the lowest ID, so every node picks the same member
+    fn heaviest<F>(&self, key: &SpendKey, nodes: &HashMap<TransactionId, DAGNode>, cumulative_weight: &F) -> Option<TransactionId>
+    where
+        F: Fn(&TransactionId) -> u64,
+    {
+        self.spends.get(key)?.iter()
+            .filter(|member| nodes.get(*member).map_or(false, |node| node.status != NodeStatus::Rejected))
+            .max_by(|a, b| {
+                cumulative_weight(a).cmp(&cumulative_weight(b))
+                    .then_with(|| nodes[*b].transaction.timestamp.cmp(&nodes[*a].transaction.timestamp))
+                    .then_with(|| b.as_string().cmp(&a.as_string()))
+            })
+            .cloned()
+    }
+}
+
+/// Whether a transaction approves any of `targets`, directly or indirectly
+fn approves_any(transaction: &Transaction, targets: &[TransactionId], nodes: &HashMap<TransactionId, DAGNode>) -> bool {
+    let mut visited = std::collections::HashSet::new();
+    let mut stack = transaction.parents.clone();
+    while let Some(tx_id) = stack.pop() {
+        if targets.contains(&tx_id) {
+            return true;
+        }
+        if visited.insert(tx_id.clone()) {
+            if let Some(node) = nodes.get(&tx_id) {
+                stack.extend(node.transaction.parents.iter().cloned());
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+
+    fn add(
+        tracker: &mut ConflictTracker,
+        nodes: &mut HashMap<TransactionId, DAGNode>,
+        sender: u8,
+        nonce: u64,
+        weight: u64,
+        parents: &[TransactionId],
+    ) -> Result<TransactionId, CoreError> {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![sender; 32],
+            receiver: vec![9u8; 32],
+            amount: 10,
+            fee: 0,
+            nonce,
+            timestamp: 1_700_000_000 + weight,
+            parents: parents.to_vec(),
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: Some((nodes.len() as u64).to_le_bytes().to_vec()),
+        };
+        transaction.id = transaction.compute_id();
+        tracker.check(&transaction, nodes)?;
+
+        let id = transaction.id.clone();
+        for parent in parents {
+            nodes.get_mut(parent).unwrap().children.push(id.clone());
+        }
+        nodes.insert(id.clone(), DAGNode {
+            transaction: transaction.clone(),
+            children: Vec::new(),
+            weight,
+            confidence: 0.0,
+            status: NodeStatus::Pending,
+            quantum_score: 80,
+        });
+        tracker.insert(&transaction, nodes);
+        Ok(id)
+    }
+
+    fn total_weight(nodes: &HashMap<TransactionId, DAGNode>) -> impl Fn(&TransactionId) -> u64 + '_ {
+        |id| {
+            let mut stack = vec![id.clone()];
+            let mut total = 0;
+            while let Some(id) = stack.pop() {
+                total += nodes[&id].weight;
+                stack.extend(nodes[&id].children.iter().cloned());
+            }
+            total
+        }
+    }
+
+    #[test]
+    fn test_conflict_marks_existing_branch() {
+        let (mut tracker, mut nodes) = (ConflictTracker::new(), HashMap::new());
+        let root = add(&mut tracker, &mut nodes, 0, 0, 1, &[]).unwrap();
+        let first = add(&mut tracker, &mut nodes, 1, 5, 1, &[root.clone()]).unwrap();
+        let approver = add(&mut tracker, &mut nodes, 2, 0, 1, &[first.clone()]).unwrap();
+        assert!(!tracker.is_conflicting(&approver));
+
+        let second = add(&mut tracker, &mut nodes, 1, 5, 2, &[root.clone()]).unwrap();
+        assert!(tracker.is_conflicting(&approver));
+        assert!(!tracker.is_conflicting(&root));
+        assert_eq!(tracker.conflict_sets()[0].members, vec![first.clone(), second.clone()]);
+
+        // Approving both sides is invalid, as is a spend approving its rival
+        assert!(matches!(
+            add(&mut tracker, &mut nodes, 3, 0, 1, &[approver.clone(), second]),
+            Err(CoreError::ConflictingParents(_))
+        ));
+        assert!(matches!(
+            add(&mut tracker, &mut nodes, 1, 5, 3, &[approver]),
+            Err(CoreError::ConflictingParents(_))
+        ));
+    }
+
+    #[test]
+    fn test_only_heaviest_side_confirms() {
+        let (mut tracker, mut nodes) = (ConflictTracker::new(), HashMap::new());
+        let root = add(&mut tracker, &mut nodes, 0, 0, 1, &[]).unwrap();
+        let first = add(&mut tracker, &mut nodes, 1, 5, 1, &[root.clone()]).unwrap();
+        let second = add(&mut tracker, &mut nodes, 1, 5, 1, &[root.clone()]).unwrap();
+        let heavy = add(&mut tracker, &mut nodes, 2, 0, 10, &[second.clone()]).unwrap();
+
+        let weight = total_weight(&nodes);
+        assert!(!tracker.can_confirm(&first, &nodes, &weight));
+        assert!(tracker.can_confirm(&second, &nodes, &weight));
+        assert!(tracker.can_confirm(&heavy, &nodes, &weight));
+        assert!(tracker.can_confirm(&root, &nodes, &weight));
+    }
+
+    #[test]
+    fn test_resolution_rejects_losing_branch() {
+        let (mut tracker, mut nodes) = (ConflictTracker::new(), HashMap::new());
+        let root = add(&mut tracker, &mut nodes, 0, 0, 1, &[]).unwrap();
+        let first = add(&mut tracker, &mut nodes, 1, 5, 1, &[root.clone()]).unwrap();
+        let loser_child = add(&mut tracker, &mut nodes, 2, 0, 1, &[first.clone()]).unwrap();
+        let second = add(&mut tracker, &mut nodes, 1, 5, 5, &[root.clone()]).unwrap();
+
+        nodes.get_mut(&second).unwrap().status = NodeStatus::Confirmed;
+        let losers = tracker.resolve(&second, &nodes);
+        let mut expected = vec![first.clone(), loser_child.clone()];
+        expected.sort_by_key(|id| id.as_string());
+        assert_eq!(losers, expected);
+        assert_eq!(tracker.conflict_sets()[0].winner, Some(second));
+
+        // Late spends and approvals of the losing side are refused
+        assert!(matches!(add(&mut tracker, &mut nodes, 1, 5, 7, &[root]), Err(CoreError::DoubleSpend(_))));
+        assert!(matches!(add(&mut tracker, &mut nodes, 3, 0, 1, &[loser_child]), Err(CoreError::RejectedBranch(_))));
+    }
+}
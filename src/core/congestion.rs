@@ -0,0 +1,208 @@
+//! Network congestion reporting
+//!
+//! `CongestionMonitor` keeps the confirmation latencies of recently confirmed
+//! transactions, measured from the transaction timestamp to the moment it
+//! was confirmed. A `CongestionReport` combines their percentiles with the
+//! depth of the intent log and recent fee rates, and classifies the result
+//! into a `CongestionLevel` clients can use to decide whether to send now,
+//! pay more, or wait.
+
+use super::{FeeRates, NodeStatus};
+use crate::events::{EventHandler, NodeEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Congestion thresholds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CongestionConfig {
+    /// Confirmation latency considered normal, in seconds
+    pub target_confirmation_secs: u64,
+    /// Confirmed transactions the latency percentiles are taken over
+    pub latency_window: usize,
+}
+
+impl Default for CongestionConfig {
+    fn default() -> Self {
+        Self {
+            target_confirmation_secs: 10,
+            latency_window: 500,
+        }
+    }
+}
+
+/// How congested the node is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CongestionLevel {
+    Normal,
+    Elevated,
+    High,
+    Severe,
+}
+
+impl CongestionLevel {
+    /// Level for an intent log `utilization` in `[0, 1]` and a p90 latency
+    /// `latency_ratio` relative to the target
+    pub fn classify(utilization: f64, latency_ratio: f64) -> Self {
+        if utilization >= 0.9 || latency_ratio >= 4.0 {
+            CongestionLevel::Severe
+        } else if utilization >= 0.7 || latency_ratio >= 2.0 {
+            CongestionLevel::High
+        } else if utilization >= 0.4 || latency_ratio > 1.0 {
+            CongestionLevel::Elevated
+        } else {
+            CongestionLevel::Normal
+        }
+    }
+}
+
+/// Confirmation latency percentiles in seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    /// Confirmations the percentiles are taken over
+    pub samples: usize,
+    pub p50_secs: u64,
+    pub p90_secs: u64,
+    pub p99_secs: u64,
+}
+
+/// Congestion as seen by this node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CongestionReport {
+    pub level: CongestionLevel,
+    /// Intents waiting for validation
+    pub mempool_depth: usize,
+    pub mempool_capacity: usize,
+    pub confirmation_latency: LatencyPercentiles,
+    /// Latency considered normal, in seconds
+    pub target_confirmation_secs: u64,
+    /// Recent fee rates per KiB
+    pub fee_rates: FeeRates,
+    pub timestamp: u64,
+}
+
+impl CongestionReport {
+    /// Report for the given intent log depth, latencies and fee rates
+    pub fn new(
+        config: &CongestionConfig,
+        mempool_depth: usize,
+        mempool_capacity: usize,
+        confirmation_latency: LatencyPercentiles,
+        fee_rates: FeeRates,
+    ) -> Self {
+        let utilization = mempool_depth as f64 / mempool_capacity.max(1) as f64;
+        let latency_ratio = confirmation_latency.p90_secs as f64 / config.target_confirmation_secs.max(1) as f64;
+        Self {
+            level: CongestionLevel::classify(utilization, latency_ratio),
+            mempool_depth,
+            mempool_capacity,
+            confirmation_latency,
+            target_confirmation_secs: config.target_confirmation_secs,
+            fee_rates,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+}
+
+/// Recent confirmation latencies, fed from node events
+#[derive(Debug)]
+pub struct CongestionMonitor {
+    config: CongestionConfig,
+    latencies: Mutex<VecDeque<u64>>,
+}
+
+impl Default for CongestionMonitor {
+    fn default() -> Self {
+        Self::new(CongestionConfig::default())
+    }
+}
+
+impl CongestionMonitor {
+    pub fn new(config: CongestionConfig) -> Self {
+        Self {
+            config,
+            latencies: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn config(&self) -> &CongestionConfig {
+        &self.config
+    }
+
+    /// Record the latency of a confirmed transaction
+    pub fn record_confirmation(&self, latency_secs: u64) {
+        let mut latencies = self.latencies.lock().unwrap_or_else(|e| e.into_inner());
+        if latencies.len() >= self.config.latency_window.max(1) {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency_secs);
+    }
+
+    /// Percentiles over the recent confirmations
+    pub fn latency(&self) -> LatencyPercentiles {
+        let mut latencies: Vec<u64> = self.latencies.lock().unwrap_or_else(|e| e.into_inner()).iter().copied().collect();
+        if latencies.is_empty() {
+            return LatencyPercentiles::default();
+        }
+        latencies.sort_unstable();
+        let at_percentile = |percentile: usize| latencies[(latencies.len() - 1) * percentile / 100];
+        LatencyPercentiles {
+            samples: latencies.len(),
+            p50_secs: at_percentile(50),
+            p90_secs: at_percentile(90),
+            p99_secs: at_percentile(99),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for CongestionMonitor {
+    fn name(&self) -> String {
+        "congestion".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        if let NodeEvent::StatusChanged { status: NodeStatus::Confirmed, submitted_at, .. } = event {
+            let now = chrono::Utc::now().timestamp() as u64;
+            self.record_confirmation(now.saturating_sub(*submitted_at));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates() -> FeeRates {
+        FeeRates { economy: 1, standard: 2, priority: 5 }
+    }
+
+    #[test]
+    fn test_latency_percentiles_over_window() {
+        let monitor = CongestionMonitor::new(CongestionConfig { target_confirmation_secs: 10, latency_window: 100 });
+        assert_eq!(monitor.latency().samples, 0);
+
+        for latency in 1..=150 {
+            monitor.record_confirmation(latency);
+        }
+        let latency = monitor.latency();
+        assert_eq!(latency.samples, 100);
+        assert_eq!(latency.p50_secs, 100);
+        assert_eq!(latency.p90_secs, 140);
+        assert_eq!(latency.p99_secs, 149);
+    }
+
+    #[test]
+    fn test_level_follows_depth_and_latency() {
+        let config = CongestionConfig::default();
+        let fast = LatencyPercentiles { samples: 10, p50_secs: 2, p90_secs: 5, p99_secs: 8 };
+        let slow = LatencyPercentiles { samples: 10, p50_secs: 20, p90_secs: 45, p99_secs: 60 };
+
+        assert_eq!(CongestionReport::new(&config, 10, 100, fast, rates()).level, CongestionLevel::Normal);
+        assert_eq!(CongestionReport::new(&config, 75, 100, fast, rates()).level, CongestionLevel::High);
+        assert_eq!(CongestionReport::new(&config, 10, 100, slow, rates()).level, CongestionLevel::Severe);
+        assert!(CongestionLevel::Elevated < CongestionLevel::High);
+    }
+}
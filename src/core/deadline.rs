@@ -0,0 +1,120 @@
+//! Request deadlines
+//!
+//! A submission carries a `Deadline` through signing, validation, DAG insert
+//! and propagation. Each stage runs under what is left of the budget and is
+//! dropped once it runs out, so a slow stage cannot hold a request, or the
+//! locks it waits on, past its deadline. Stages that must not be interrupted
+//! midway, such as writing a transaction into the DAG, check the deadline
+//! before they start rather than being cancelled.
+
+use super::CoreError;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Stage of a submission a deadline can expire in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmitStage {
+    Signing,
+    Validation,
+    Insert,
+    Propagation,
+}
+
+impl SubmitStage {
+    /// Metric label for the stage
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubmitStage::Signing => "signing",
+            SubmitStage::Validation => "validation",
+            SubmitStage::Insert => "insert",
+            SubmitStage::Propagation => "propagation",
+        }
+    }
+}
+
+impl std::fmt::Display for SubmitStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Point in time a request must finish by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline {
+    expires_at: Option<Instant>,
+}
+
+impl Deadline {
+    /// Deadline `budget` from now
+    pub fn after(budget: Duration) -> Self {
+        Self { expires_at: Some(Instant::now() + budget) }
+    }
+
+    /// No deadline; stages run to completion
+    pub fn never() -> Self {
+        Self { expires_at: None }
+    }
+
+    /// The earlier of two deadlines
+    pub fn earliest(self, other: Deadline) -> Self {
+        match (self.expires_at, other.expires_at) {
+            (Some(a), Some(b)) => Self { expires_at: Some(a.min(b)) },
+            (a, b) => Self { expires_at: a.or(b) },
+        }
+    }
+
+    /// Time left, or `None` without a deadline
+    pub fn remaining(&self) -> Option<Duration> {
+        self.expires_at.map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+
+    /// Fail if the deadline passed before `stage` starts
+    pub fn check(&self, stage: SubmitStage) -> Result<(), CoreError> {
+        if self.is_expired() {
+            return Err(CoreError::DeadlineExceeded(stage));
+        }
+        Ok(())
+    }
+
+    /// Run `stage`, dropping it if the deadline passes first
+    pub async fn run<F: Future>(&self, stage: SubmitStage, future: F) -> Result<F::Output, CoreError> {
+        self.check(stage)?;
+        match self.expires_at {
+            Some(at) => tokio::time::timeout_at(at, future).await.map_err(|_| CoreError::DeadlineExceeded(stage)),
+            None => Ok(future.await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_abandons_stage_past_deadline() {
+        let deadline = Deadline::after(Duration::from_millis(20));
+        assert_eq!(deadline.run(SubmitStage::Validation, async { 7 }).await.unwrap(), 7);
+
+        let slow = deadline.run(SubmitStage::Insert, tokio::time::sleep(Duration::from_secs(5))).await;
+        assert!(matches!(slow, Err(CoreError::DeadlineExceeded(SubmitStage::Insert))));
+        assert!(deadline.is_expired());
+        // Later stages fail without starting
+        assert!(matches!(deadline.check(SubmitStage::Propagation), Err(CoreError::DeadlineExceeded(SubmitStage::Propagation))));
+    }
+
+    #[test]
+    fn test_earliest_deadline_wins() {
+        let near = Deadline::after(Duration::from_secs(1));
+        let far = Deadline::after(Duration::from_secs(60));
+        assert_eq!(near.earliest(far), near);
+        assert_eq!(Deadline::never().earliest(far), far);
+        assert_eq!(Deadline::never().remaining(), None);
+    }
+}
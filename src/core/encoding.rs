@@ -0,0 +1,453 @@
+//! Canonical encoding of consensus objects
+//!
+//! serde_json and bincode output depends on field order, serde attributes
+//! and crate versions, so two nodes can encode the same transaction to
+//! different bytes. Everything that is hashed, signed or sent to peers uses
+//! this encoding instead:
+//!
+//! - integers are fixed-width little-endian
+//! - byte strings and sequences are prefixed with their length as a `u64`
+//! - options and enums start with a one-byte tag
+//! - floats are their IEEE 754 bits, with every NaN encoded the same way
+//! - fields are written in declaration order, with no field names
+//!
+//! Decoding is strict: unknown tags, lengths beyond the input and trailing
+//! bytes are errors, so every value has exactly one valid encoding.
+
+use super::{CoreError, DAGNode, NodeStatus, QuantumProof, Transaction};
+use crate::identity::SignatureType;
+use crate::TransactionId;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Domain separator for transaction signatures
+const TRANSACTION_SIGNING_DOMAIN: &[u8] = b"qdag-tx-sig-v1";
+
+/// Canonical decoding failures
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EncodingError {
+    #[error("Unexpected end of input: {needed} more byte(s) needed, {remaining} left")]
+    UnexpectedEnd { needed: u64, remaining: usize },
+    #[error("Invalid {what} tag {tag}")]
+    InvalidTag { what: &'static str, tag: u8 },
+    #[error("{0} trailing byte(s) after the encoded value")]
+    TrailingBytes(usize),
+}
+
+impl From<EncodingError> for CoreError {
+    fn from(error: EncodingError) -> Self {
+        CoreError::Serialization(error.to_string())
+    }
+}
+
+/// Builds a canonical encoding
+#[derive(Debug, Default)]
+pub struct CanonicalEncoder {
+    buf: Vec<u8>,
+}
+
+impl CanonicalEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes written so far, without a length prefix, e.g. a domain separator
+    pub fn raw(&mut self, bytes: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn u8(&mut self, value: u8) -> &mut Self {
+        self.buf.push(value);
+        self
+    }
+
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.raw(&value.to_le_bytes())
+    }
+
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.raw(&value.to_le_bytes())
+    }
+
+    pub fn u128(&mut self, value: u128) -> &mut Self {
+        self.raw(&value.to_le_bytes())
+    }
+
+    pub fn f64(&mut self, value: f64) -> &mut Self {
+        let value = if value.is_nan() { f64::NAN } else { value };
+        self.u64(value.to_bits())
+    }
+
+    /// Length-prefixed byte string
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.u64(bytes.len() as u64).raw(bytes)
+    }
+
+    pub fn value<T: Canonical>(&mut self, value: &T) -> &mut Self {
+        value.encode(self);
+        self
+    }
+
+    /// Length-prefixed sequence
+    pub fn seq<T: Canonical>(&mut self, values: &[T]) -> &mut Self {
+        self.u64(values.len() as u64);
+        for value in values {
+            value.encode(self);
+        }
+        self
+    }
+
+    pub fn option<T: Canonical>(&mut self, value: Option<&T>) -> &mut Self {
+        match value {
+            Some(value) => self.u8(1).value(value),
+            None => self.u8(0),
+        }
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Reads a canonical encoding
+#[derive(Debug)]
+pub struct CanonicalDecoder<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> CanonicalDecoder<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn raw(&mut self, len: u64) -> Result<&'a [u8], EncodingError> {
+        if len > self.data.len() as u64 {
+            return Err(EncodingError::UnexpectedEnd { needed: len, remaining: self.data.len() });
+        }
+        let (head, rest) = self.data.split_at(len as usize);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], EncodingError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.raw(N as u64)?);
+        Ok(array)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, EncodingError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, EncodingError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, EncodingError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub fn u128(&mut self) -> Result<u128, EncodingError> {
+        Ok(u128::from_le_bytes(self.array()?))
+    }
+
+    pub fn f64(&mut self) -> Result<f64, EncodingError> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    pub fn bytes(&mut self) -> Result<Vec<u8>, EncodingError> {
+        let len = self.u64()?;
+        Ok(self.raw(len)?.to_vec())
+    }
+
+    pub fn value<T: Canonical>(&mut self) -> Result<T, EncodingError> {
+        T::decode(self)
+    }
+
+    pub fn seq<T: Canonical>(&mut self) -> Result<Vec<T>, EncodingError> {
+        let len = self.u64()?;
+        // Every element takes at least one byte, which bounds the allocation
+        if len > self.data.len() as u64 {
+            return Err(EncodingError::UnexpectedEnd { needed: len, remaining: self.data.len() });
+        }
+        (0..len).map(|_| T::decode(self)).collect()
+    }
+
+    pub fn option<T: Canonical>(&mut self) -> Result<Option<T>, EncodingError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(self)?)),
+            tag => Err(EncodingError::InvalidTag { what: "option", tag }),
+        }
+    }
+
+    /// Fail unless the whole input was read
+    pub fn finish(self) -> Result<(), EncodingError> {
+        match self.data.len() {
+            0 => Ok(()),
+            remaining => Err(EncodingError::TrailingBytes(remaining)),
+        }
+    }
+}
+
+/// Types with exactly one byte encoding
+pub trait Canonical: Sized {
+    fn encode(&self, encoder: &mut CanonicalEncoder);
+    fn decode(decoder: &mut CanonicalDecoder<'_>) -> Result<Self, EncodingError>;
+}
+
+/// Canonical encoding of `value`
+pub fn to_canonical_bytes<T: Canonical>(value: &T) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new();
+    value.encode(&mut encoder);
+    encoder.finish()
+}
+
+/// Decode `data`, which must hold exactly one canonically encoded value
+pub fn from_canonical_bytes<T: Canonical>(data: &[u8]) -> Result<T, EncodingError> {
+    let mut decoder = CanonicalDecoder::new(data);
+    let value = T::decode(&mut decoder)?;
+    decoder.finish()?;
+    Ok(value)
+}
+
+impl Canonical for Vec<u8> {
+    fn encode(&self, encoder: &mut CanonicalEncoder) {
+        encoder.bytes(self);
+    }
+
+    fn decode(decoder: &mut CanonicalDecoder<'_>) -> Result<Self, EncodingError> {
+        decoder.bytes()
+    }
+}
+
+impl Canonical for TransactionId {
+    fn encode(&self, encoder: &mut CanonicalEncoder) {
+        match self {
+            TransactionId::Hash(hash) => encoder.u8(0).raw(hash),
+            TransactionId::Legacy(uuid) => encoder.u8(1).raw(uuid.as_bytes()),
+        };
+    }
+
+    fn decode(decoder: &mut CanonicalDecoder<'_>) -> Result<Self, EncodingError> {
+        match decoder.u8()? {
+            0 => Ok(TransactionId::Hash(decoder.array()?)),
+            1 => Ok(TransactionId::Legacy(Uuid::from_bytes(decoder.array()?))),
+            tag => Err(EncodingError::InvalidTag { what: "transaction ID", tag }),
+        }
+    }
+}
+
+impl Canonical for SignatureType {
+    fn encode(&self, encoder: &mut CanonicalEncoder) {
+        encoder.u8(match self {
+            SignatureType::Ed25519 => 0,
+            SignatureType::Dilithium3 => 1,
+            SignatureType::Dilithium5 => 2,
+            SignatureType::Hybrid => 3,
+            SignatureType::Falcon512 => 4,
+            SignatureType::Falcon1024 => 5,
+        });
+    }
+
+    fn decode(decoder: &mut CanonicalDecoder<'_>) -> Result<Self, EncodingError> {
+        match decoder.u8()? {
+            0 => Ok(SignatureType::Ed25519),
+            1 => Ok(SignatureType::Dilithium3),
+            2 => Ok(SignatureType::Dilithium5),
+            3 => Ok(SignatureType::Hybrid),
+            4 => Ok(SignatureType::Falcon512),
+            5 => Ok(SignatureType::Falcon1024),
+            tag => Err(EncodingError::InvalidTag { what: "signature scheme", tag }),
+        }
+    }
+}
+
+impl Canonical for QuantumProof {
+    fn encode(&self, encoder: &mut CanonicalEncoder) {
+        encoder.bytes(&self.prime_hash).u32(self.resistance_score).u64(self.proof_timestamp);
+    }
+
+    fn decode(decoder: &mut CanonicalDecoder<'_>) -> Result<Self, EncodingError> {
+        Ok(QuantumProof {
+            prime_hash: decoder.bytes()?,
+            resistance_score: decoder.u32()?,
+            proof_timestamp: decoder.u64()?,
+        })
+    }
+}
+
+impl Canonical for Transaction {
+    fn encode(&self, encoder: &mut CanonicalEncoder) {
+        encoder.value(&self.id);
+        self.encode_content(encoder);
+        encoder.bytes(&self.signature).value(&self.quantum_proof);
+    }
+
+    fn decode(decoder: &mut CanonicalDecoder<'_>) -> Result<Self, EncodingError> {
+        Ok(Transaction {
+            id: decoder.value()?,
+            sender: decoder.bytes()?,
+            receiver: decoder.bytes()?,
+            amount: decoder.u128()?,
+            fee: decoder.u128()?,
+            nonce: decoder.u64()?,
+            timestamp: decoder.u64()?,
+            parents: decoder.seq()?,
+            metadata: decoder.option()?,
+            signature_scheme: decoder.value()?,
+            signature: decoder.bytes()?,
+            quantum_proof: decoder.value()?,
+        })
+    }
+}
+
+impl Canonical for NodeStatus {
+    fn encode(&self, encoder: &mut CanonicalEncoder) {
+        encoder.u8(match self {
+            NodeStatus::Pending => 0,
+            NodeStatus::Confirmed => 1,
+            NodeStatus::Finalized => 2,
+            NodeStatus::Rejected => 3,
+        });
+    }
+
+    fn decode(decoder: &mut CanonicalDecoder<'_>) -> Result<Self, EncodingError> {
+        match decoder.u8()? {
+            0 => Ok(NodeStatus::Pending),
+            1 => Ok(NodeStatus::Confirmed),
+            2 => Ok(NodeStatus::Finalized),
+            3 => Ok(NodeStatus::Rejected),
+            tag => Err(EncodingError::InvalidTag { what: "node status", tag }),
+        }
+    }
+}
+
+impl Canonical for DAGNode {
+    fn encode(&self, encoder: &mut CanonicalEncoder) {
+        encoder.value(&self.transaction)
+            .seq(&self.children)
+            .u64(self.weight)
+            .f64(self.confidence)
+            .value(&self.status)
+            .u32(self.quantum_score);
+    }
+
+    fn decode(decoder: &mut CanonicalDecoder<'_>) -> Result<Self, EncodingError> {
+        Ok(DAGNode {
+            transaction: decoder.value()?,
+            children: decoder.seq()?,
+            weight: decoder.u64()?,
+            confidence: decoder.f64()?,
+            status: decoder.value()?,
+            quantum_score: decoder.u32()?,
+        })
+    }
+}
+
+impl Transaction {
+    /// Everything the sender commits to: all fields but the ID, signature and proof
+    fn encode_content(&self, encoder: &mut CanonicalEncoder) {
+        encoder.bytes(&self.sender)
+            .bytes(&self.receiver)
+            .u128(self.amount)
+            .u128(self.fee)
+            .u64(self.nonce)
+            .u64(self.timestamp)
+            .seq(&self.parents)
+            .option(self.metadata.as_ref())
+            .value(&self.signature_scheme);
+    }
+
+    /// Canonical encoding, as sent to peers
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        to_canonical_bytes(self)
+    }
+
+    /// Decode a transaction received from a peer
+    pub fn from_canonical_bytes(data: &[u8]) -> Result<Self, EncodingError> {
+        from_canonical_bytes(data)
+    }
+
+    /// Bytes the sender signs: the ID followed by the content it was derived from
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new();
+        encoder.raw(TRANSACTION_SIGNING_DOMAIN).value(&self.id);
+        self.encode_content(&mut encoder);
+        encoder.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction() -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10u128.pow(24),
+            fee: 1,
+            nonce: 7,
+            timestamp: 1_700_000_000,
+            parents: vec![TransactionId::new(), TransactionId::Legacy(Uuid::new_v4())],
+            signature: vec![9u8; 64],
+            signature_scheme: SignatureType::Dilithium3,
+            quantum_proof: QuantumProof { prime_hash: vec![3u8; 16], resistance_score: 80, proof_timestamp: 1_700_000_001 },
+            metadata: Some(b"memo".to_vec()),
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[test]
+    fn test_round_trips_byte_for_byte() {
+        let transaction = transaction();
+        let bytes = transaction.to_canonical_bytes();
+        let decoded = Transaction::from_canonical_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_canonical_bytes(), bytes);
+        assert!(decoded.has_valid_id());
+
+        let node = DAGNode {
+            transaction,
+            children: vec![TransactionId::new()],
+            weight: 3,
+            confidence: f64::NAN,
+            status: NodeStatus::Confirmed,
+            quantum_score: 80,
+        };
+        let bytes = to_canonical_bytes(&node);
+        assert_eq!(to_canonical_bytes(&from_canonical_bytes::<DAGNode>(&bytes).unwrap()), bytes);
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let bytes = transaction().to_canonical_bytes();
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(Transaction::from_canonical_bytes(&trailing).unwrap_err(), EncodingError::TrailingBytes(1));
+        assert!(matches!(Transaction::from_canonical_bytes(&bytes[..bytes.len() - 1]), Err(EncodingError::UnexpectedEnd { .. })));
+
+        let mut bad_tag = bytes;
+        bad_tag[0] = 7;
+        assert_eq!(Transaction::from_canonical_bytes(&bad_tag).unwrap_err(), EncodingError::InvalidTag { what: "transaction ID", tag: 7 });
+        // A huge length prefix fails before anything is allocated
+        assert!(from_canonical_bytes::<Vec<u8>>(&u64::MAX.to_le_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_signing_bytes_cover_content() {
+        let transaction = transaction();
+        let mut higher_fee = transaction.clone();
+        higher_fee.fee += 1;
+        assert_ne!(transaction.signing_bytes(), higher_fee.signing_bytes());
+
+        // The signature and proof are produced over the signing bytes, so they are not part of them
+        let mut resigned = transaction.clone();
+        resigned.signature = vec![0u8; 64];
+        resigned.quantum_proof.resistance_score = 95;
+        assert_eq!(transaction.signing_bytes(), resigned.signing_bytes());
+    }
+}
@@ -0,0 +1,214 @@
+//! Pending transaction expiry
+//!
+//! A transaction can carry a time to live, in seconds from its timestamp,
+//! under the `ttl` key of its JSON metadata. A pending tip that nothing has
+//! approved by then is evicted: it is rejected, leaves the tip set, and a
+//! `TransactionEvicted` event is published. Transactions without a TTL are
+//! evicted once they reach the node's `max_pending_age_secs`, if one is set.
+//! Only unapproved tips are evicted; a transaction something approves is no
+//! longer lingering, and evicting it would strand its approvers.
+//!
+//! Since the TTL is in the metadata it is covered by the transaction ID, so
+//! relays cannot change it.
+
+use super::{CoreError, DAGCore, NodeStatus, Transaction};
+use crate::events::NodeEvent;
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key holding a transaction's time to live
+pub const TTL_METADATA_KEY: &str = "ttl";
+
+/// When pending transactions are evicted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpiryConfig {
+    pub enabled: bool,
+    /// Age at which unapproved transactions without a TTL are evicted;
+    /// `None` keeps them until approved
+    pub max_pending_age_secs: Option<u64>,
+    /// Seconds between eviction runs
+    pub interval_secs: u64,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_pending_age_secs: Some(24 * 3600),
+            interval_secs: 30,
+        }
+    }
+}
+
+/// Why a transaction was evicted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionReason {
+    /// Its own TTL ran out
+    Ttl,
+    /// It reached the node's maximum pending age
+    MaxAge,
+}
+
+impl EvictionReason {
+    /// Metric label for the reason
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvictionReason::Ttl => "ttl",
+            EvictionReason::MaxAge => "max_age",
+        }
+    }
+}
+
+/// Time to live in a transaction's JSON metadata
+///
+/// Metadata that is not a JSON object, or has no TTL key, has none. A TTL
+/// that is not a positive integer is an error.
+pub fn transaction_ttl(transaction: &Transaction) -> Result<Option<u64>, CoreError> {
+    let Some(metadata) = &transaction.metadata else {
+        return Ok(None);
+    };
+    let Ok(serde_json::Value::Object(fields)) = serde_json::from_slice::<serde_json::Value>(metadata) else {
+        return Ok(None);
+    };
+    match fields.get(TTL_METADATA_KEY) {
+        None => Ok(None),
+        Some(value) => match value.as_u64() {
+            Some(ttl) if ttl > 0 => Ok(Some(ttl)),
+            _ => Err(CoreError::InvalidTtl(value.to_string())),
+        },
+    }
+}
+
+/// When a pending transaction is evicted under `config`, and why
+pub fn expires_at(transaction: &Transaction, config: &ExpiryConfig) -> Option<(u64, EvictionReason)> {
+    match transaction_ttl(transaction) {
+        Ok(Some(ttl)) => Some((transaction.timestamp.saturating_add(ttl), EvictionReason::Ttl)),
+        _ => config.max_pending_age_secs
+            .map(|age| (transaction.timestamp.saturating_add(age), EvictionReason::MaxAge)),
+    }
+}
+
+/// Check a transaction's TTL is well formed and has not run out at `now`
+pub fn validate_ttl(transaction: &Transaction, now: u64) -> Result<(), CoreError> {
+    if let Some(ttl) = transaction_ttl(transaction)? {
+        if transaction.timestamp.saturating_add(ttl) <= now {
+            return Err(CoreError::TransactionExpired(transaction.id.clone()));
+        }
+    }
+    Ok(())
+}
+
+impl DAGCore {
+    /// Evict pending tips whose TTL or maximum age has passed at `now`
+    ///
+    /// Evicted transactions are rejected and leave the tip set. Pending
+    /// parents they were the only live approver of become tips again.
+    /// Returns the evicted transactions and why each was evicted.
+    pub fn evict_expired(&mut self, now: u64, config: &ExpiryConfig) -> Vec<(TransactionId, EvictionReason)> {
+        let expired: Vec<(TransactionId, u64, EvictionReason)> = self.tips.iter()
+            .filter_map(|tx_id| self.transactions.get(tx_id))
+            .filter(|node| node.status == NodeStatus::Pending)
+            .filter_map(|node| {
+                let (at, reason) = expires_at(&node.transaction, config)?;
+                (at <= now).then(|| (node.transaction.id.clone(), at, reason))
+            })
+            .collect();
+        if expired.is_empty() {
+            return Vec::new();
+        }
+
+        let mut changed = Vec::new();
+        let mut parents = Vec::new();
+        for (tx_id, _, _) in &expired {
+            if let Some(node) = self.transactions.get_mut(tx_id) {
+                changed.push((tx_id.clone(), node.status.clone()));
+                node.status = NodeStatus::Rejected;
+                parents.extend(node.transaction.parents.iter().cloned());
+                self.tips.remove(tx_id);
+            }
+        }
+        self.publish_status_changes(changed);
+        self.retip_parents(parents);
+
+        for (tx_id, expired_at, reason) in &expired {
+            log::info!("⌛ Evicted {}: pending past its {} at {}", tx_id, reason.as_str(), expired_at);
+            if let Some(events) = &self.events {
+                events.publish(NodeEvent::TransactionEvicted {
+                    tx_id: tx_id.clone(),
+                    reason: *reason,
+                    expired_at: *expired_at,
+                });
+            }
+        }
+        expired.into_iter().map(|(tx_id, _, reason)| (tx_id, reason)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+
+    fn transaction(metadata: Option<&[u8]>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 1,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: metadata.map(|metadata| metadata.to_vec()),
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[test]
+    fn test_ttl_and_max_age_set_expiry() {
+        let config = ExpiryConfig::default();
+        let with_ttl = transaction(Some(br#"{"ttl":600,"memo":"hi"}"#));
+        assert_eq!(expires_at(&with_ttl, &config), Some((1_700_000_600, EvictionReason::Ttl)));
+        let without = transaction(None);
+        assert_eq!(expires_at(&without, &config), Some((1_700_086_400, EvictionReason::MaxAge)));
+        assert_eq!(expires_at(&without, &ExpiryConfig { max_pending_age_secs: None, ..config }), None);
+
+        assert!(validate_ttl(&with_ttl, 1_700_000_599).is_ok());
+        assert!(matches!(validate_ttl(&with_ttl, 1_700_000_600), Err(CoreError::TransactionExpired(_))));
+        assert!(matches!(validate_ttl(&transaction(Some(br#"{"ttl":0}"#)), 0), Err(CoreError::InvalidTtl(_))));
+        assert!(matches!(validate_ttl(&transaction(Some(br#"{"ttl":"soon"}"#)), 0), Err(CoreError::InvalidTtl(_))));
+    }
+
+    #[tokio::test]
+    async fn test_expired_tip_is_evicted_and_parent_retipped() {
+        let mut dag = DAGCore::new().await.unwrap();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut parent = dag.genesis_id().cloned().unwrap();
+        let mut ids = Vec::new();
+        for (nonce, metadata) in [(1, None), (2, Some(br#"{"ttl":60}"#.as_slice()))] {
+            let mut tx = transaction(metadata);
+            tx.nonce = nonce;
+            tx.timestamp = now;
+            tx.parents = vec![parent.clone()];
+            tx.id = tx.compute_id();
+            parent = dag.add_transaction(tx).await.unwrap();
+            ids.push(parent.clone());
+        }
+
+        let config = ExpiryConfig::default();
+        assert!(dag.evict_expired(now + 59, &config).is_empty());
+        assert_eq!(dag.evict_expired(now + 60, &config), vec![(ids[1].clone(), EvictionReason::Ttl)]);
+        assert_eq!(dag.get_node(&ids[1]).unwrap().status, NodeStatus::Rejected);
+        // Its parent is unapproved again, until it too ages out
+        assert_eq!(dag.get_tips().len(), 1);
+        assert_eq!(dag.get_tips()[0].transaction.id, ids[0]);
+        assert_eq!(dag.evict_expired(now + 86_400, &config), vec![(ids[0].clone(), EvictionReason::MaxAge)]);
+        assert!(dag.get_tips().is_empty());
+    }
+}
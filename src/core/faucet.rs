@@ -0,0 +1,499 @@
+//! Test-network faucet
+//!
+//! Devnet and testnet nodes can hand out tokens from a configured faucet
+//! account. Each request is first passed to the registered verifiers (a
+//! captcha check, an external webhook), then limited per address and per IP
+//! over a sliding window, and finally paid from the faucet's remaining
+//! balance as an ordinary transfer. The faucet refuses to start on mainnet.
+
+use crate::config::NetworkType;
+use crate::core::{amount, Amount, QuantumProof, Transaction};
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Faucet configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaucetConfig {
+    /// Hex public key of the account grants are paid from
+    pub account: String,
+    /// Amount paid per grant
+    #[serde(with = "amount")]
+    pub grant_amount: Amount,
+    /// Balance available for grants until topped up
+    #[serde(with = "amount")]
+    pub initial_balance: Amount,
+    /// Grants allowed per address within the window
+    pub max_grants_per_address: u32,
+    /// Grants allowed per IP within the window
+    pub max_grants_per_ip: u32,
+    pub window_secs: u64,
+}
+
+impl Default for FaucetConfig {
+    fn default() -> Self {
+        Self {
+            account: String::new(),
+            grant_amount: 1_000_000,
+            initial_balance: 1_000_000_000,
+            max_grants_per_address: 1,
+            max_grants_per_ip: 5,
+            window_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Request for test funds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetRequest {
+    /// Hex public key or wallet address to fund
+    pub address: String,
+    #[serde(default)]
+    pub ip: Option<IpAddr>,
+    #[serde(default)]
+    pub captcha_token: Option<String>,
+}
+
+/// Check run on every request before it is rate limited and paid
+#[async_trait::async_trait]
+pub trait FaucetVerifier: Send + Sync {
+    /// Verifier name reported on rejection
+    fn name(&self) -> String;
+
+    /// Accept or reject a request
+    async fn verify(&self, request: &FaucetRequest) -> Result<(), FaucetError>;
+}
+
+/// Verifies captcha tokens with an hCaptcha or reCAPTCHA compatible `siteverify` endpoint
+pub struct CaptchaVerifier {
+    verify_url: String,
+    secret: String,
+    client: reqwest::Client,
+}
+
+impl CaptchaVerifier {
+    /// hCaptcha verification endpoint
+    pub const HCAPTCHA_VERIFY_URL: &'static str = "https://api.hcaptcha.com/siteverify";
+
+    /// Create a verifier for `verify_url` with the site secret
+    pub fn new(verify_url: &str, secret: &str) -> Self {
+        Self {
+            verify_url: verify_url.to_string(),
+            secret: secret.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CaptchaResponse {
+    success: bool,
+}
+
+#[async_trait::async_trait]
+impl FaucetVerifier for CaptchaVerifier {
+    fn name(&self) -> String {
+        "captcha".to_string()
+    }
+
+    async fn verify(&self, request: &FaucetRequest) -> Result<(), FaucetError> {
+        let token = request.captcha_token.as_deref()
+            .ok_or_else(|| FaucetError::Rejected(self.name(), "missing captcha token".to_string()))?;
+
+        let mut form = vec![("secret", self.secret.clone()), ("response", token.to_string())];
+        if let Some(ip) = request.ip {
+            form.push(("remoteip", ip.to_string()));
+        }
+        let response: CaptchaResponse = self.client.post(&self.verify_url)
+            .form(&form)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| FaucetError::Verifier(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| FaucetError::Verifier(e.to_string()))?;
+
+        if response.success {
+            Ok(())
+        } else {
+            Err(FaucetError::Rejected(self.name(), "captcha not solved".to_string()))
+        }
+    }
+}
+
+/// Forwards requests to an external service that approves them with a 2xx response
+pub struct WebhookVerifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookVerifier {
+    /// Create a verifier posting requests to `url`
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl FaucetVerifier for WebhookVerifier {
+    fn name(&self) -> String {
+        "webhook".to_string()
+    }
+
+    async fn verify(&self, request: &FaucetRequest) -> Result<(), FaucetError> {
+        let response = self.client.post(&self.url)
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| FaucetError::Verifier(e.to_string()))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(FaucetError::Rejected(self.name(), format!("status {}", response.status())))
+        }
+    }
+}
+
+/// Grant held against the faucet's balance and rate limits until paid or released
+#[derive(Debug, Clone)]
+pub struct FaucetReservation {
+    pub address: String,
+    pub ip: Option<IpAddr>,
+    pub amount: Amount,
+    reserved_at: u64,
+}
+
+/// Paid faucet grant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetGrant {
+    pub address: String,
+    #[serde(with = "amount")]
+    pub amount: Amount,
+    pub transaction_id: TransactionId,
+    pub granted_at: u64,
+}
+
+/// Faucet statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FaucetStats {
+    #[serde(with = "amount")]
+    pub balance: Amount,
+    #[serde(with = "amount")]
+    pub grant_amount: Amount,
+    pub total_grants: u64,
+    #[serde(with = "amount")]
+    pub total_distributed: Amount,
+    pub unique_addresses: usize,
+    pub rate_limited: u64,
+    pub rejected: u64,
+    pub last_grant_at: Option<u64>,
+}
+
+#[derive(Default)]
+struct FaucetState {
+    balance: Amount,
+    by_address: HashMap<String, VecDeque<u64>>,
+    by_ip: HashMap<IpAddr, VecDeque<u64>>,
+    funded: std::collections::HashSet<String>,
+    stats: FaucetStats,
+}
+
+/// Rate-limited faucet paying grants from a configured account
+pub struct Faucet {
+    config: FaucetConfig,
+    account: Vec<u8>,
+    verifiers: RwLock<Vec<Arc<dyn FaucetVerifier>>>,
+    state: Mutex<FaucetState>,
+}
+
+impl Faucet {
+    /// Create a faucet, refusing networks other than devnet and testnet
+    pub fn new(config: FaucetConfig, network: NetworkType) -> Result<Self, FaucetError> {
+        if !network.is_test_network() {
+            return Err(FaucetError::Disabled(network));
+        }
+        let account = decode_address(&config.account)?;
+
+        Ok(Self {
+            state: Mutex::new(FaucetState { balance: config.initial_balance, ..FaucetState::default() }),
+            config,
+            account,
+            verifiers: RwLock::new(Vec::new()),
+        })
+    }
+
+    /// Faucet configuration
+    pub fn config(&self) -> &FaucetConfig {
+        &self.config
+    }
+
+    /// Public key grants are paid from
+    pub fn account(&self) -> &[u8] {
+        &self.account
+    }
+
+    /// Run `verifier` on every request
+    pub fn add_verifier(&self, verifier: Arc<dyn FaucetVerifier>) {
+        self.verifiers.write().unwrap_or_else(|e| e.into_inner()).push(verifier);
+    }
+
+    /// Verify a request and hold a grant for it against the limits and balance
+    pub async fn reserve(&self, request: &FaucetRequest) -> Result<FaucetReservation, FaucetError> {
+        decode_address(&request.address)?;
+
+        let verifiers = self.verifiers.read().unwrap_or_else(|e| e.into_inner()).clone();
+        for verifier in verifiers {
+            if let Err(e) = verifier.verify(request).await {
+                self.lock_state().stats.rejected += 1;
+                log::info!("🚰 Faucet request for {} rejected: {}", request.address, e);
+                return Err(e);
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        let address = request.address.to_lowercase();
+        let mut state = self.lock_state();
+
+        let window_start = now.saturating_sub(self.config.window_secs);
+        let address_grants = state.by_address.entry(address.clone()).or_default();
+        let address_retry = retry_after(address_grants, window_start, self.config.max_grants_per_address, self.config.window_secs);
+        let ip_retry = match request.ip {
+            Some(ip) => {
+                let ip_grants = state.by_ip.entry(ip).or_default();
+                retry_after(ip_grants, window_start, self.config.max_grants_per_ip, self.config.window_secs)
+            }
+            None => None,
+        };
+        if let Some(retry) = address_retry.max(ip_retry) {
+            state.stats.rate_limited += 1;
+            return Err(FaucetError::RateLimited(retry.saturating_sub(now).max(1)));
+        }
+
+        if state.balance < self.config.grant_amount {
+            return Err(FaucetError::Exhausted);
+        }
+        state.balance -= self.config.grant_amount;
+        state.by_address.entry(address.clone()).or_default().push_back(now);
+        if let Some(ip) = request.ip {
+            state.by_ip.entry(ip).or_default().push_back(now);
+        }
+
+        Ok(FaucetReservation {
+            address,
+            ip: request.ip,
+            amount: self.config.grant_amount,
+            reserved_at: now,
+        })
+    }
+
+    /// Transfer paying a reservation from the faucet account
+    pub fn grant_transaction(&self, reservation: &FaucetReservation) -> Result<Transaction, FaucetError> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        Ok(Transaction {
+            id: TransactionId::new(),
+            sender: self.account.clone(),
+            receiver: decode_address(&reservation.address)?,
+            amount: reservation.amount,
+            fee: 0,
+            nonce: rand::random(),
+            timestamp: now,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![0u8; 32],
+                resistance_score: 80,
+                proof_timestamp: now,
+            },
+            metadata: Some(b"faucet".to_vec()),
+        })
+    }
+
+    /// Record a reservation as paid by `transaction_id`
+    pub fn complete(&self, reservation: FaucetReservation, transaction_id: TransactionId) -> FaucetGrant {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut state = self.lock_state();
+        state.funded.insert(reservation.address.clone());
+        state.stats.total_grants += 1;
+        state.stats.total_distributed += reservation.amount;
+        state.stats.last_grant_at = Some(now);
+
+        log::info!("🚰 Faucet granted {} to {}", reservation.amount, reservation.address);
+        FaucetGrant {
+            address: reservation.address,
+            amount: reservation.amount,
+            transaction_id,
+            granted_at: now,
+        }
+    }
+
+    /// Return a reservation that could not be paid
+    pub fn release(&self, reservation: &FaucetReservation) {
+        let mut state = self.lock_state();
+        state.balance += reservation.amount;
+        if let Some(grants) = state.by_address.get_mut(&reservation.address) {
+            remove_grant(grants, reservation.reserved_at);
+        }
+        if let Some(grants) = reservation.ip.and_then(|ip| state.by_ip.get_mut(&ip)) {
+            remove_grant(grants, reservation.reserved_at);
+        }
+    }
+
+    /// Add funds to the faucet balance, returning the new balance
+    pub fn top_up(&self, amount: Amount) -> Amount {
+        let mut state = self.lock_state();
+        state.balance = state.balance.saturating_add(amount);
+        state.balance
+    }
+
+    /// Current statistics
+    pub fn stats(&self) -> FaucetStats {
+        let state = self.lock_state();
+        FaucetStats {
+            balance: state.balance,
+            grant_amount: self.config.grant_amount,
+            unique_addresses: state.funded.len(),
+            ..state.stats.clone()
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, FaucetState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Drop grants older than the window; if `limit` are still inside it, when the oldest expires
+fn retry_after(grants: &mut VecDeque<u64>, window_start: u64, limit: u32, window_secs: u64) -> Option<u64> {
+    while grants.front().is_some_and(|&at| at <= window_start) {
+        grants.pop_front();
+    }
+    if grants.len() >= limit as usize {
+        grants.front().map(|&oldest| oldest + window_secs)
+    } else {
+        None
+    }
+}
+
+fn remove_grant(grants: &mut VecDeque<u64>, at: u64) {
+    if let Some(position) = grants.iter().rposition(|&granted| granted == at) {
+        grants.remove(position);
+    }
+}
+
+/// Addresses are hex public keys (32 bytes) or SDK wallet addresses (20 bytes)
+fn decode_address(address: &str) -> Result<Vec<u8>, FaucetError> {
+    match hex::decode(address) {
+        Ok(bytes) if bytes.len() == 20 || bytes.len() == 32 => Ok(bytes),
+        _ => Err(FaucetError::InvalidAddress(address.to_string())),
+    }
+}
+
+/// Faucet errors
+#[derive(Debug, thiserror::Error)]
+pub enum FaucetError {
+    #[error("Faucet is only available on devnet and testnet, not {0}")]
+    Disabled(NetworkType),
+    #[error("Faucet is not enabled on this node")]
+    NotEnabled,
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Rate limit reached, retry after {0}s")]
+    RateLimited(u64),
+    #[error("Faucet balance is exhausted")]
+    Exhausted,
+    #[error("Request rejected by {0}: {1}")]
+    Rejected(String, String),
+    #[error("Verification failed: {0}")]
+    Verifier(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(byte: u8) -> String {
+        hex::encode([byte; 32])
+    }
+
+    fn faucet() -> Faucet {
+        Faucet::new(FaucetConfig {
+            account: address(0xfa),
+            grant_amount: 100,
+            initial_balance: 250,
+            max_grants_per_address: 1,
+            max_grants_per_ip: 2,
+            ..FaucetConfig::default()
+        }, NetworkType::Devnet).unwrap()
+    }
+
+    fn request(byte: u8, ip: &str) -> FaucetRequest {
+        FaucetRequest { address: address(byte), ip: Some(ip.parse().unwrap()), captcha_token: None }
+    }
+
+    struct RejectAll;
+
+    #[async_trait::async_trait]
+    impl FaucetVerifier for RejectAll {
+        fn name(&self) -> String {
+            "reject".to_string()
+        }
+
+        async fn verify(&self, _request: &FaucetRequest) -> Result<(), FaucetError> {
+            Err(FaucetError::Rejected(self.name(), "no".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_faucet_disabled_on_mainnet() {
+        let config = FaucetConfig { account: address(0xfa), ..FaucetConfig::default() };
+        assert!(matches!(Faucet::new(config.clone(), NetworkType::Mainnet), Err(FaucetError::Disabled(_))));
+        assert!(Faucet::new(config, NetworkType::Testnet).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits_and_balance() {
+        let faucet = faucet();
+
+        let first = faucet.reserve(&request(1, "10.0.0.1")).await.unwrap();
+        let transaction = faucet.grant_transaction(&first).unwrap();
+        assert_eq!(transaction.sender, vec![0xfa; 32]);
+        faucet.complete(first, transaction.id);
+
+        // Same address, then a third grant from the same IP
+        assert!(matches!(faucet.reserve(&request(1, "10.0.0.2")).await, Err(FaucetError::RateLimited(_))));
+        faucet.reserve(&request(2, "10.0.0.1")).await.unwrap();
+        assert!(matches!(faucet.reserve(&request(3, "10.0.0.1")).await, Err(FaucetError::RateLimited(_))));
+
+        // 50 left after two grants of 100
+        assert!(matches!(faucet.reserve(&request(4, "10.0.0.3")).await, Err(FaucetError::Exhausted)));
+        assert_eq!(faucet.top_up(50), 100);
+        faucet.reserve(&request(4, "10.0.0.3")).await.unwrap();
+
+        let stats = faucet.stats();
+        assert_eq!(stats.total_grants, 1);
+        assert_eq!(stats.rate_limited, 2);
+        assert_eq!(stats.balance, 0);
+    }
+
+    #[tokio::test]
+    async fn test_release_and_verifier_rejection() {
+        let faucet = faucet();
+
+        let reservation = faucet.reserve(&request(1, "10.0.0.1")).await.unwrap();
+        faucet.release(&reservation);
+        assert_eq!(faucet.stats().balance, 250);
+        faucet.reserve(&request(1, "10.0.0.1")).await.unwrap();
+
+        faucet.add_verifier(Arc::new(RejectAll));
+        assert!(matches!(faucet.reserve(&request(2, "10.0.0.2")).await, Err(FaucetError::Rejected(..))));
+        assert_eq!(faucet.stats().rejected, 1);
+    }
+}
@@ -0,0 +1,224 @@
+//! Transaction fees
+//!
+//! Every transaction pays a `fee` that is credited to the validator of the
+//! round that finalizes it. Fees are priced by size: the fee rate is the fee
+//! per KiB of payload, where the payload is the fields covered by the
+//! transaction ID. The signature and quantum proof are left out so a sender
+//! can settle the fee before signing, and so the rate does not depend on the
+//! signature scheme.
+//!
+//! `FeePolicy` sets the floor a transaction must pay to be accepted. The
+//! ingestion queue orders pending transactions by fee rate, and `FeeMarket`
+//! keeps the rates of recently accepted transactions to suggest fees.
+
+use super::{amount, Amount, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Fixed-size fields in the payload: amount, fee, nonce and timestamp
+const FIXED_PAYLOAD_BYTES: usize = 4 * 8;
+
+/// Minimum fees a transaction must pay to be accepted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeePolicy {
+    /// Flat minimum fee
+    #[serde(with = "amount")]
+    pub min_fee: Amount,
+    /// Minimum fee per KiB of payload
+    #[serde(with = "amount")]
+    pub min_fee_rate: Amount,
+}
+
+impl Default for FeePolicy {
+    fn default() -> Self {
+        Self {
+            min_fee: 1,
+            min_fee_rate: 1,
+        }
+    }
+}
+
+impl FeePolicy {
+    /// Policy accepting transactions without fees
+    pub fn free() -> Self {
+        Self { min_fee: 0, min_fee_rate: 0 }
+    }
+
+    /// Lowest fee accepted for a payload of `size` bytes
+    pub fn required_fee(&self, size: usize) -> Amount {
+        self.min_fee.max(fee_for_rate(self.min_fee_rate, size))
+    }
+}
+
+/// Payload bytes a transaction is charged for
+pub fn payload_size(transaction: &Transaction) -> usize {
+    transaction.sender.len()
+        + transaction.receiver.len()
+        + FIXED_PAYLOAD_BYTES
+        + transaction.parents.iter().map(|parent| parent.as_bytes().len()).sum::<usize>()
+        + transaction.metadata.as_ref().map_or(0, |metadata| metadata.len())
+}
+
+/// Fee per KiB of payload
+pub fn fee_rate(transaction: &Transaction) -> Amount {
+    let size = payload_size(transaction).max(1) as Amount;
+    transaction.fee.saturating_mul(1024) / size
+}
+
+/// Fee paying `rate` per KiB for `size` bytes, rounded up
+fn fee_for_rate(rate: Amount, size: usize) -> Amount {
+    rate.saturating_mul(size as Amount).div_ceil(1024)
+}
+
+/// Fee rates per KiB at the economy, standard and priority percentiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeRates {
+    /// 25th percentile of recent fee rates
+    #[serde(with = "amount")]
+    pub economy: Amount,
+    /// Median recent fee rate
+    #[serde(with = "amount")]
+    pub standard: Amount,
+    /// 90th percentile of recent fee rates
+    #[serde(with = "amount")]
+    pub priority: Amount,
+}
+
+/// Suggested fees for a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Payload bytes the fee is charged on
+    pub size_bytes: usize,
+    /// Lowest fee the policy accepts
+    #[serde(with = "amount")]
+    pub minimum: Amount,
+    /// Fee at the 25th percentile of recent fee rates
+    #[serde(with = "amount")]
+    pub economy: Amount,
+    /// Fee at the median recent fee rate
+    #[serde(with = "amount")]
+    pub standard: Amount,
+    /// Fee at the 90th percentile of recent fee rates
+    #[serde(with = "amount")]
+    pub priority: Amount,
+}
+
+/// Fee rates of recently accepted transactions
+#[derive(Debug)]
+pub struct FeeMarket {
+    window: usize,
+    rates: Mutex<VecDeque<Amount>>,
+}
+
+impl Default for FeeMarket {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl FeeMarket {
+    /// Market remembering the last `window` accepted transactions
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            rates: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record the fee rate of an accepted transaction
+    pub fn record(&self, transaction: &Transaction) {
+        let mut rates = self.rates.lock().unwrap_or_else(|e| e.into_inner());
+        if rates.len() == self.window {
+            rates.pop_front();
+        }
+        rates.push_back(fee_rate(transaction));
+    }
+
+    /// Recent fee rates, never below the policy's minimum rate
+    pub fn rates(&self, policy: &FeePolicy) -> FeeRates {
+        let mut rates: Vec<Amount> = self.rates.lock().unwrap_or_else(|e| e.into_inner()).iter().copied().collect();
+        rates.sort_unstable();
+
+        let at_percentile = |percentile: usize| {
+            let rate = if rates.is_empty() { 0 } else { rates[(rates.len() - 1) * percentile / 100] };
+            rate.max(policy.min_fee_rate)
+        };
+        FeeRates {
+            economy: at_percentile(25),
+            standard: at_percentile(50),
+            priority: at_percentile(90),
+        }
+    }
+
+    /// Suggested fees for `transaction` under `policy`
+    ///
+    /// With no recent transactions every suggestion is the policy minimum.
+    pub fn estimate(&self, policy: &FeePolicy, transaction: &Transaction) -> FeeEstimate {
+        let size = payload_size(transaction);
+        let minimum = policy.required_fee(size);
+        let rates = self.rates(policy);
+        FeeEstimate {
+            size_bytes: size,
+            minimum,
+            economy: fee_for_rate(rates.economy, size).max(minimum),
+            standard: fee_for_rate(rates.standard, size).max(minimum),
+            priority: fee_for_rate(rates.priority, size).max(minimum),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::TransactionId;
+
+    fn transaction(fee: Amount) -> Transaction {
+        Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee,
+            nonce: 1,
+            timestamp: 1_000,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: Some(vec![0u8; 928]),
+        }
+    }
+
+    #[test]
+    fn test_rate_is_charged_per_kib_of_payload() {
+        let tx = transaction(10);
+        assert_eq!(payload_size(&tx), 1024);
+        assert_eq!(fee_rate(&tx), 10);
+
+        let policy = FeePolicy { min_fee: 1, min_fee_rate: 4 };
+        assert_eq!(policy.required_fee(1024), 4);
+        assert_eq!(policy.required_fee(1025), 5);
+        assert_eq!(policy.required_fee(0), 1);
+    }
+
+    #[test]
+    fn test_estimate_follows_recent_rates() {
+        let market = FeeMarket::new(10);
+        let policy = FeePolicy::default();
+        let tx = transaction(0);
+        assert_eq!(market.estimate(&policy, &tx).standard, 1);
+
+        for fee in 1..=20 {
+            market.record(&transaction(fee * 10));
+        }
+        // Only the last 10 rates are kept
+        let estimate = market.estimate(&policy, &tx);
+        assert_eq!(estimate.minimum, 1);
+        assert_eq!(estimate.economy, 130);
+        assert_eq!(estimate.standard, 150);
+        assert_eq!(estimate.priority, 190);
+    }
+}
@@ -0,0 +1,510 @@
+//! Address filters for bandwidth-efficient light client sync
+//!
+//! Light clients register a Bloom filter of the addresses they watch and
+//! receive only transactions whose sender or receiver matches it. The filter's
+//! false-positive rate is chosen by the client: a higher rate returns more
+//! unrelated transactions but reveals less about which addresses it owns.
+//!
+//! To let clients detect a node that withholds matches, transactions are
+//! grouped into fixed-size segments in insertion order. Each sealed segment
+//! gets a Golomb-coded compact filter of every address it touches, and the
+//! filter hashes are chained into headers. A client that trusts the header
+//! chain can fetch a segment's compact filter, test its own addresses, and
+//! check that every matching segment was actually served.
+
+use crate::{BlockchainError, TransactionId, core::{CoreError, Transaction}};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Largest Bloom filter accepted from a client, in bytes
+pub const MAX_BLOOM_FILTER_BYTES: usize = 36_000;
+/// Largest number of hash functions accepted in a Bloom filter
+pub const MAX_BLOOM_HASH_FUNCS: u32 = 50;
+/// Golomb-Rice parameter for compact filters
+pub const COMPACT_FILTER_P: u8 = 19;
+/// Inverse false-positive rate for compact filters
+pub const COMPACT_FILTER_M: u64 = 784_931;
+
+/// Bloom filter over watched addresses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    pub bits: Vec<u8>,
+    pub hash_funcs: u32,
+    pub tweak: u32,
+}
+
+impl BloomFilter {
+    /// Size a filter for `expected_items` at the given false-positive rate
+    pub fn new(expected_items: usize, false_positive_rate: f64, tweak: u32) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+
+        let num_bits = (-n * p.ln() / (ln2 * ln2)).ceil() as usize;
+        let num_bytes = num_bits.div_ceil(8).clamp(1, MAX_BLOOM_FILTER_BYTES);
+        let hash_funcs = ((num_bytes * 8) as f64 / n * ln2).round() as u32;
+
+        Self {
+            bits: vec![0u8; num_bytes],
+            hash_funcs: hash_funcs.clamp(1, MAX_BLOOM_HASH_FUNCS),
+            tweak,
+        }
+    }
+
+    /// Add an item to the filter
+    pub fn insert(&mut self, item: &[u8]) {
+        let indices: Vec<usize> = self.bit_indices(item).collect();
+        for index in indices {
+            self.bits[index / 8] |= 1 << (index % 8);
+        }
+    }
+
+    /// Whether the filter may contain an item
+    pub fn contains(&self, item: &[u8]) -> bool {
+        self.bit_indices(item).all(|index| self.bits[index / 8] & (1 << (index % 8)) != 0)
+    }
+
+    /// Reject filters that are malformed or too expensive to evaluate
+    pub fn validate(&self) -> Result<(), BlockchainError> {
+        if self.bits.is_empty() || self.bits.len() > MAX_BLOOM_FILTER_BYTES {
+            return Err(BlockchainError::Core(CoreError::InvalidFilter(format!(
+                "filter must be 1 to {} bytes",
+                MAX_BLOOM_FILTER_BYTES
+            ))));
+        }
+        if self.hash_funcs == 0 || self.hash_funcs > MAX_BLOOM_HASH_FUNCS {
+            return Err(BlockchainError::Core(CoreError::InvalidFilter(format!(
+                "filter must use 1 to {} hash functions",
+                MAX_BLOOM_HASH_FUNCS
+            ))));
+        }
+        Ok(())
+    }
+
+    /// Whether a transaction touches a watched address
+    pub fn matches_transaction(&self, transaction: &Transaction) -> bool {
+        self.contains(&transaction.sender) || self.contains(&transaction.receiver)
+    }
+
+    fn bit_indices<'a>(&'a self, item: &[u8]) -> impl Iterator<Item = usize> + 'a {
+        let mut hasher = Sha3_256::new();
+        hasher.update(self.tweak.to_le_bytes());
+        hasher.update(item);
+        let digest = hasher.finalize();
+
+        // Double hashing: index_i = h1 + i * h2
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap());
+        let num_bits = (self.bits.len() * 8) as u64;
+
+        (0..self.hash_funcs as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+}
+
+/// Golomb-coded set of the addresses touched by a segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompactFilter {
+    pub item_count: u64,
+    pub data: Vec<u8>,
+}
+
+impl CompactFilter {
+    /// Build a filter over `items`, keyed by the segment's key
+    pub fn build(key: &[u8; 16], items: &[Vec<u8>]) -> Self {
+        let mut unique: Vec<&Vec<u8>> = items.iter().collect();
+        unique.sort_unstable();
+        unique.dedup();
+
+        let range = unique.len() as u64 * COMPACT_FILTER_M;
+        let mut values: Vec<u64> = unique.iter().map(|item| hash_to_range(key, item, range)).collect();
+        values.sort_unstable();
+
+        let mut writer = BitWriter::default();
+        let mut last = 0;
+        for value in &values {
+            let delta = value - last;
+            last = *value;
+            for _ in 0..(delta >> COMPACT_FILTER_P) {
+                writer.write_bit(true);
+            }
+            writer.write_bit(false);
+            writer.write_bits(delta, COMPACT_FILTER_P);
+        }
+
+        Self {
+            item_count: values.len() as u64,
+            data: writer.finish(),
+        }
+    }
+
+    /// Whether any of `items` may be in the filter
+    pub fn match_any(&self, key: &[u8; 16], items: &[Vec<u8>]) -> bool {
+        if self.item_count == 0 || items.is_empty() {
+            return false;
+        }
+
+        let range = self.item_count * COMPACT_FILTER_M;
+        let mut queries: Vec<u64> = items.iter().map(|item| hash_to_range(key, item, range)).collect();
+        queries.sort_unstable();
+
+        let mut reader = BitReader::new(&self.data);
+        let mut value = 0u64;
+        let mut query_index = 0;
+        for _ in 0..self.item_count {
+            let Some(delta) = reader.read_golomb(COMPACT_FILTER_P) else {
+                return false;
+            };
+            value += delta;
+
+            while query_index < queries.len() && queries[query_index] < value {
+                query_index += 1;
+            }
+            if query_index == queries.len() {
+                return false;
+            }
+            if queries[query_index] == value {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Hash committed to by the filter header chain
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(self.item_count.to_le_bytes());
+        hasher.update(&self.data);
+        hasher.finalize().into()
+    }
+}
+
+/// Header committing to a segment's compact filter and all earlier ones
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterHeader {
+    pub segment: u64,
+    pub filter_hash: [u8; 32],
+    pub previous_header: [u8; 32],
+    pub header: [u8; 32],
+}
+
+impl FilterHeader {
+    fn new(segment: u64, filter_hash: [u8; 32], previous_header: [u8; 32]) -> Self {
+        Self {
+            segment,
+            filter_hash,
+            previous_header,
+            header: chain_header(&filter_hash, &previous_header),
+        }
+    }
+
+    /// Verify that `headers` form an unbroken chain starting after `previous`
+    pub fn verify_chain(headers: &[FilterHeader], mut previous: [u8; 32]) -> bool {
+        headers.iter().all(|header| {
+            let linked = header.previous_header == previous
+                && header.header == chain_header(&header.filter_hash, &header.previous_header);
+            previous = header.header;
+            linked
+        })
+    }
+}
+
+/// Sealed group of transactions with its compact filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterSegment {
+    pub index: u64,
+    pub transaction_ids: Vec<TransactionId>,
+    pub filter: CompactFilter,
+    pub header: FilterHeader,
+}
+
+impl FilterSegment {
+    /// Key used to hash addresses into this segment's filter
+    pub fn filter_key(&self) -> [u8; 16] {
+        segment_key(self.index, &self.header.previous_header)
+    }
+}
+
+/// Identifier of a registered client filter
+pub type FilterSubscriptionId = Uuid;
+
+/// Transactions matching a client filter, with the headers needed to check them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilteredSync {
+    pub transactions: Vec<Transaction>,
+    pub headers: Vec<FilterHeader>,
+    /// Segment to request next
+    pub next_segment: u64,
+}
+
+/// Segment index and client filter registry
+pub struct FilterIndex {
+    segment_size: usize,
+    open: Vec<(TransactionId, Vec<Vec<u8>>)>,
+    segments: Vec<FilterSegment>,
+    subscriptions: HashMap<FilterSubscriptionId, BloomFilter>,
+    max_subscriptions: usize,
+}
+
+impl FilterIndex {
+    /// Create an index that seals a segment every `segment_size` transactions
+    pub fn new(segment_size: usize) -> Self {
+        Self {
+            segment_size: segment_size.max(1),
+            open: Vec::new(),
+            segments: Vec::new(),
+            subscriptions: HashMap::new(),
+            max_subscriptions: 10_000,
+        }
+    }
+
+    /// Record a transaction added to the DAG
+    pub fn add_transaction(&mut self, transaction: &Transaction) {
+        self.open.push((
+            transaction.id.clone(),
+            vec![transaction.sender.clone(), transaction.receiver.clone()],
+        ));
+        if self.open.len() >= self.segment_size {
+            self.seal();
+        }
+    }
+
+    /// Seal the open segment, if it has any transactions
+    pub fn seal(&mut self) {
+        if self.open.is_empty() {
+            return;
+        }
+
+        let index = self.segments.len() as u64;
+        let previous_header = self.segments.last().map(|s| s.header.header).unwrap_or([0u8; 32]);
+        let key = segment_key(index, &previous_header);
+
+        let (transaction_ids, addresses): (Vec<_>, Vec<_>) = std::mem::take(&mut self.open).into_iter().unzip();
+        let addresses: Vec<Vec<u8>> = addresses.into_iter().flatten().collect();
+        let filter = CompactFilter::build(&key, &addresses);
+        let header = FilterHeader::new(index, filter.hash(), previous_header);
+
+        self.segments.push(FilterSegment {
+            index,
+            transaction_ids,
+            filter,
+            header,
+        });
+    }
+
+    /// Register a client filter
+    pub fn register(&mut self, filter: BloomFilter) -> Result<FilterSubscriptionId, BlockchainError> {
+        filter.validate()?;
+        if self.subscriptions.len() >= self.max_subscriptions {
+            return Err(BlockchainError::Core(CoreError::InvalidFilter("too many registered filters".to_string())));
+        }
+
+        let id = Uuid::new_v4();
+        self.subscriptions.insert(id, filter);
+        Ok(id)
+    }
+
+    /// Maximum number of registered client filters
+    pub fn max_subscriptions(&self) -> usize {
+        self.max_subscriptions
+    }
+
+    /// Change the subscription limit; existing registrations are kept
+    pub fn set_max_subscriptions(&mut self, max_subscriptions: usize) {
+        self.max_subscriptions = max_subscriptions;
+    }
+
+    /// Remove a client filter
+    pub fn unregister(&mut self, id: &FilterSubscriptionId) -> bool {
+        self.subscriptions.remove(id).is_some()
+    }
+
+    /// Get a registered client filter
+    pub fn subscription(&self, id: &FilterSubscriptionId) -> Option<&BloomFilter> {
+        self.subscriptions.get(id)
+    }
+
+    /// Transaction IDs in the open segment, which has no header yet
+    pub fn open_transaction_ids(&self) -> Vec<TransactionId> {
+        self.open.iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    /// Headers of sealed segments from `from_segment` onwards
+    pub fn headers_from(&self, from_segment: u64, count: usize) -> Vec<FilterHeader> {
+        self.segments.iter()
+            .skip(from_segment as usize)
+            .take(count)
+            .map(|segment| segment.header.clone())
+            .collect()
+    }
+
+    /// Get a sealed segment
+    pub fn segment(&self, index: u64) -> Option<&FilterSegment> {
+        self.segments.get(index as usize)
+    }
+
+    /// Number of sealed segments
+    pub fn sealed_segments(&self) -> u64 {
+        self.segments.len() as u64
+    }
+}
+
+impl Default for FilterIndex {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+fn chain_header(filter_hash: &[u8; 32], previous_header: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(filter_hash);
+    hasher.update(previous_header);
+    hasher.finalize().into()
+}
+
+fn segment_key(index: u64, previous_header: &[u8; 32]) -> [u8; 16] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(index.to_le_bytes());
+    hasher.update(previous_header);
+    let digest = hasher.finalize();
+    digest[..16].try_into().unwrap()
+}
+
+fn hash_to_range(key: &[u8; 16], item: &[u8], range: u64) -> u64 {
+    let mut hasher = Sha3_256::new();
+    hasher.update(key);
+    hasher.update(item);
+    let digest = hasher.finalize();
+    let hash = u64::from_le_bytes(digest[..8].try_into().unwrap());
+    ((hash as u128 * range as u128) >> 64) as u64
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_len: usize,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.bit_len % 8 == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= 0x80 >> (self.bit_len % 8);
+        }
+        self.bit_len += 1;
+    }
+
+    fn write_bits(&mut self, value: u64, count: u8) {
+        for shift in (0..count).rev() {
+            self.write_bit((value >> shift) & 1 == 1);
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.position / 8)?;
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_golomb(&mut self, p: u8) -> Option<u64> {
+        let mut quotient = 0u64;
+        while self.read_bit()? {
+            quotient += 1;
+        }
+        let mut remainder = 0u64;
+        for _ in 0..p {
+            remainder = (remainder << 1) | self.read_bit()? as u64;
+        }
+        Some((quotient << p) | remainder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+
+    fn transaction(sender: u8, receiver: u8) -> Transaction {
+        Transaction {
+            id: TransactionId::new(),
+            sender: vec![sender; 32],
+            receiver: vec![receiver; 32],
+            amount: 1,
+            fee: 0,
+            nonce: 0,
+            timestamp: 0,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![],
+                resistance_score: 0,
+                proof_timestamp: 0,
+            },
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_bloom_filter_membership() {
+        let mut filter = BloomFilter::new(10, 0.01, 7);
+        filter.insert(&[1u8; 32]);
+
+        assert!(filter.contains(&[1u8; 32]));
+        assert!(filter.matches_transaction(&transaction(9, 1)));
+        assert!(filter.validate().is_ok());
+
+        let false_positives = (0..1000u32)
+            .filter(|i| filter.contains(&i.to_be_bytes()))
+            .count();
+        assert!(false_positives < 50);
+    }
+
+    #[test]
+    fn test_compact_filter_matches_segment_addresses() {
+        let key = [3u8; 16];
+        let items = vec![vec![1u8; 32], vec![2u8; 32], vec![5u8; 32]];
+        let filter = CompactFilter::build(&key, &items);
+
+        assert!(filter.match_any(&key, &[vec![2u8; 32]]));
+        assert!(filter.match_any(&key, &[vec![7u8; 32], vec![5u8; 32]]));
+        assert!(!filter.match_any(&key, &[vec![7u8; 32]]));
+    }
+
+    #[test]
+    fn test_segments_chain_headers() {
+        let mut index = FilterIndex::new(2);
+        for i in 0..5 {
+            index.add_transaction(&transaction(i, 100));
+        }
+
+        assert_eq!(index.sealed_segments(), 2);
+        assert_eq!(index.open_transaction_ids().len(), 1);
+
+        let headers = index.headers_from(0, 10);
+        assert!(FilterHeader::verify_chain(&headers, [0u8; 32]));
+
+        let segment = index.segment(1).unwrap();
+        assert!(segment.filter.match_any(&segment.filter_key(), &[vec![3u8; 32]]));
+    }
+}
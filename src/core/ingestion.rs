@@ -0,0 +1,378 @@
+//! Asynchronous transaction ingestion with backpressure
+//!
+//! Transactions submitted through the async path are appended to an intent
+//! log and acknowledged with a ticket straight away. A worker drains the log
+//! through the regular validation pipeline and publishes the outcome, which
+//! clients can poll or subscribe to. When the log is full, submissions are
+//! refused with a retry hint instead of queueing without bound.
+//!
+//! The worker takes intents highest fee rate first, oldest first among equal
+//! rates. A full log makes room for a transaction paying a strictly higher
+//! rate than its cheapest intent by evicting that intent.
+
+use crate::{BlockchainError, TransactionId, core::{fee_rate, Amount, CoreError, Transaction}};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::{broadcast, Mutex};
+use uuid::Uuid;
+
+/// Ingestion configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestionConfig {
+    /// Maximum number of intents waiting for validation
+    pub max_pending: usize,
+    /// Retry hint returned when the intent log is full
+    pub retry_after_secs: u64,
+    /// How long completed tickets remain queryable
+    pub ticket_ttl_secs: u64,
+}
+
+impl Default for IngestionConfig {
+    fn default() -> Self {
+        Self {
+            max_pending: 10_000,
+            retry_after_secs: 5,
+            ticket_ttl_secs: 3600,
+        }
+    }
+}
+
+/// Ticket acknowledging an accepted intent
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct IngestionTicket(Uuid);
+
+impl IngestionTicket {
+    fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    /// Parse a ticket from its string form
+    pub fn parse(value: &str) -> Option<Self> {
+        Uuid::parse_str(value).ok().map(Self)
+    }
+
+    /// Get the ticket as a string
+    pub fn as_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Processing state of an intent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum IngestionStatus {
+    Queued,
+    Validating,
+    Inserted { transaction_id: TransactionId },
+    Rejected { reason: String },
+}
+
+impl IngestionStatus {
+    /// Whether the intent has reached a final state
+    pub fn is_final(&self) -> bool {
+        matches!(self, IngestionStatus::Inserted { .. } | IngestionStatus::Rejected { .. })
+    }
+}
+
+/// Status change published to subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestionUpdate {
+    pub ticket: IngestionTicket,
+    pub status: IngestionStatus,
+    pub timestamp: u64,
+}
+
+/// Snapshot of the intent log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestionStats {
+    pub pending: usize,
+    pub capacity: usize,
+    pub tracked_tickets: usize,
+    pub rejected_for_backpressure: u64,
+}
+
+#[derive(Debug, Clone)]
+struct TicketRecord {
+    status: IngestionStatus,
+    updated_at: u64,
+}
+
+/// Position of a pending intent: highest fee rate first, then arrival order
+type IntentKey = (Reverse<Amount>, u64);
+
+#[derive(Debug, Default)]
+struct IntentLogState {
+    pending: BTreeMap<IntentKey, (IngestionTicket, Transaction)>,
+    next_sequence: u64,
+    tickets: HashMap<IngestionTicket, TicketRecord>,
+    rejected_for_backpressure: u64,
+}
+
+/// Bounded intent log feeding the validation pipeline
+pub struct IngestionQueue {
+    config: std::sync::RwLock<IngestionConfig>,
+    state: Mutex<IntentLogState>,
+    updates: broadcast::Sender<IngestionUpdate>,
+}
+
+impl IngestionQueue {
+    /// Create a new ingestion queue
+    pub fn new(config: IngestionConfig) -> Self {
+        let (updates, _) = broadcast::channel(1024);
+        Self {
+            config: std::sync::RwLock::new(config),
+            state: Mutex::new(IntentLogState::default()),
+            updates,
+        }
+    }
+
+    /// Append a transaction to the intent log and return its ticket
+    ///
+    /// When the log is full the cheapest intent is evicted if `transaction`
+    /// pays a higher fee rate; otherwise the submission is refused.
+    pub async fn enqueue(&self, transaction: Transaction) -> Result<IngestionTicket, BlockchainError> {
+        let config = self.config();
+        let rate = fee_rate(&transaction);
+        let mut state = self.state.lock().await;
+
+        let mut evicted = None;
+        if state.pending.len() >= config.max_pending {
+            let cheapest = state.pending.keys().next_back().copied();
+            match cheapest {
+                Some(key @ (Reverse(cheapest_rate), _)) if rate > cheapest_rate => {
+                    let (ticket, _) = state.pending.remove(&key).expect("key taken from the map");
+                    let status = IngestionStatus::Rejected { reason: "Evicted by a higher fee rate".to_string() };
+                    state.tickets.insert(ticket, TicketRecord {
+                        status: status.clone(),
+                        updated_at: now(),
+                    });
+                    evicted = Some((ticket, status));
+                }
+                _ => {
+                    state.rejected_for_backpressure += 1;
+                    log::warn!("🚦 Intent log full ({} pending), signalling backpressure", state.pending.len());
+                    return Err(BlockchainError::Core(CoreError::Backpressure(config.retry_after_secs)));
+                }
+            }
+        }
+
+        let ticket = IngestionTicket::new();
+        let sequence = state.next_sequence;
+        state.next_sequence += 1;
+        state.pending.insert((Reverse(rate), sequence), (ticket, transaction));
+        state.tickets.insert(ticket, TicketRecord {
+            status: IngestionStatus::Queued,
+            updated_at: now(),
+        });
+        drop(state);
+
+        if let Some((evicted, status)) = evicted {
+            log::debug!("Evicted intent {} for one paying {} per KiB", evicted.as_string(), rate);
+            self.publish(evicted, status);
+        }
+        self.publish(ticket, IngestionStatus::Queued);
+        Ok(ticket)
+    }
+
+    /// Take the highest fee rate intent for validation
+    pub async fn next_intent(&self) -> Option<(IngestionTicket, Transaction)> {
+        let mut state = self.state.lock().await;
+        let (_, (ticket, transaction)) = state.pending.pop_first()?;
+        state.tickets.insert(ticket, TicketRecord {
+            status: IngestionStatus::Validating,
+            updated_at: now(),
+        });
+        drop(state);
+
+        self.publish(ticket, IngestionStatus::Validating);
+        Some((ticket, transaction))
+    }
+
+    /// Record the outcome of validation and DAG insertion
+    pub async fn complete(&self, ticket: IngestionTicket, result: &Result<TransactionId, BlockchainError>) {
+        let status = match result {
+            Ok(tx_id) => IngestionStatus::Inserted { transaction_id: tx_id.clone() },
+            Err(e) => IngestionStatus::Rejected { reason: e.to_string() },
+        };
+
+        let mut state = self.state.lock().await;
+        state.tickets.insert(ticket, TicketRecord {
+            status: status.clone(),
+            updated_at: now(),
+        });
+        drop(state);
+
+        self.publish(ticket, status);
+    }
+
+    /// Current status of a ticket
+    pub async fn status(&self, ticket: &IngestionTicket) -> Option<IngestionStatus> {
+        let state = self.state.lock().await;
+        state.tickets.get(ticket).map(|record| record.status.clone())
+    }
+
+    /// Subscribe to status updates for all tickets
+    pub fn subscribe(&self) -> broadcast::Receiver<IngestionUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Retry hint for clients, in seconds
+    pub fn retry_after_secs(&self) -> u64 {
+        self.config().retry_after_secs
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> IngestionConfig {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the configuration; intents already queued are kept even if
+    /// the new limit is lower
+    pub fn set_config(&self, config: IngestionConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    /// Drop completed tickets older than the configured TTL
+    pub async fn prune_expired(&self) -> usize {
+        let cutoff = now().saturating_sub(self.config().ticket_ttl_secs);
+        let mut state = self.state.lock().await;
+        let before = state.tickets.len();
+        state.tickets.retain(|_, record| !record.status.is_final() || record.updated_at >= cutoff);
+        before - state.tickets.len()
+    }
+
+    /// Get intent log statistics
+    pub async fn stats(&self) -> IngestionStats {
+        let state = self.state.lock().await;
+        IngestionStats {
+            pending: state.pending.len(),
+            capacity: self.config().max_pending,
+            tracked_tickets: state.tickets.len(),
+            rejected_for_backpressure: state.rejected_for_backpressure,
+        }
+    }
+
+    fn publish(&self, ticket: IngestionTicket, status: IngestionStatus) {
+        // No subscribers is not an error
+        let _ = self.updates.send(IngestionUpdate {
+            ticket,
+            status,
+            timestamp: now(),
+        });
+    }
+}
+
+fn now() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+
+    fn test_transaction() -> Transaction {
+        paying(0)
+    }
+
+    fn paying(fee: Amount) -> Transaction {
+        Transaction {
+            id: TransactionId::new(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 100,
+            fee,
+            nonce: 1,
+            timestamp: now(),
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![0u8; 32],
+                resistance_score: 80,
+                proof_timestamp: now(),
+            },
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ticket_lifecycle() {
+        let queue = IngestionQueue::new(IngestionConfig::default());
+        let mut updates = queue.subscribe();
+
+        let ticket = queue.enqueue(test_transaction()).await.unwrap();
+        assert_eq!(queue.status(&ticket).await, Some(IngestionStatus::Queued));
+
+        let (taken, tx) = queue.next_intent().await.unwrap();
+        assert_eq!(taken, ticket);
+        assert_eq!(queue.status(&ticket).await, Some(IngestionStatus::Validating));
+
+        queue.complete(ticket, &Ok(tx.id.clone())).await;
+        assert_eq!(
+            queue.status(&ticket).await,
+            Some(IngestionStatus::Inserted { transaction_id: tx.id })
+        );
+
+        assert_eq!(updates.recv().await.unwrap().status, IngestionStatus::Queued);
+        assert_eq!(updates.recv().await.unwrap().status, IngestionStatus::Validating);
+        assert!(updates.recv().await.unwrap().status.is_final());
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_when_full() {
+        let queue = IngestionQueue::new(IngestionConfig {
+            max_pending: 1,
+            retry_after_secs: 7,
+            ..IngestionConfig::default()
+        });
+
+        queue.enqueue(test_transaction()).await.unwrap();
+        let result = queue.enqueue(test_transaction()).await;
+        assert!(matches!(result, Err(BlockchainError::Core(CoreError::Backpressure(7)))));
+        assert_eq!(queue.stats().await.rejected_for_backpressure, 1);
+
+        // Draining frees capacity again
+        queue.next_intent().await.unwrap();
+        assert!(queue.enqueue(test_transaction()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_higher_fee_rate_goes_first_and_evicts_cheapest() {
+        let queue = IngestionQueue::new(IngestionConfig {
+            max_pending: 2,
+            ..IngestionConfig::default()
+        });
+
+        let cheap = queue.enqueue(paying(1)).await.unwrap();
+        let first_mid = queue.enqueue(paying(5)).await.unwrap();
+        // Same rate as the cheapest intent is not enough to evict it
+        assert!(queue.enqueue(paying(1)).await.is_err());
+
+        let rich = queue.enqueue(paying(50)).await.unwrap();
+        assert!(matches!(queue.status(&cheap).await, Some(IngestionStatus::Rejected { .. })));
+
+        assert_eq!(queue.next_intent().await.unwrap().0, rich);
+        assert_eq!(queue.next_intent().await.unwrap().0, first_mid);
+        assert!(queue.next_intent().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_unfinished_tickets() {
+        let queue = IngestionQueue::new(IngestionConfig {
+            ticket_ttl_secs: 0,
+            ..IngestionConfig::default()
+        });
+
+        let pending = queue.enqueue(test_transaction()).await.unwrap();
+        let done = queue.enqueue(test_transaction()).await.unwrap();
+        queue.complete(done, &Err(BlockchainError::Other("bad".to_string()))).await;
+
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        assert_eq!(queue.prune_expired().await, 1);
+        assert!(queue.status(&pending).await.is_some());
+        assert!(queue.status(&done).await.is_none());
+    }
+}
@@ -0,0 +1,104 @@
+//! Structural limits on transactions
+//!
+//! Metadata, parents and signatures are variable length, so without bounds a
+//! single transaction could hold megabytes in memory, in every peer's DAG and
+//! in the database. `TransactionLimits` caps each of them and the encoded
+//! size of the whole transaction. The limits are checked first in core
+//! validation, before anything is hashed or looked up.
+
+use super::{CoreError, Transaction};
+use serde::{Deserialize, Serialize};
+
+/// Largest transaction the node accepts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionLimits {
+    /// Bytes of metadata, which must fit a hex-encoded contract deploy
+    pub max_metadata_bytes: usize,
+    /// Transactions a single transaction may approve
+    pub max_parents: usize,
+    /// Bytes of signature, enough for a Dilithium5 signature
+    pub max_signature_bytes: usize,
+    /// Bytes of the whole transaction, bincode-encoded
+    pub max_encoded_bytes: usize,
+}
+
+impl Default for TransactionLimits {
+    fn default() -> Self {
+        Self {
+            max_metadata_bytes: 192 * 1024,
+            max_parents: 16,
+            max_signature_bytes: 8 * 1024,
+            max_encoded_bytes: 256 * 1024,
+        }
+    }
+}
+
+impl TransactionLimits {
+    /// Check `transaction` against every limit
+    pub fn check(&self, transaction: &Transaction) -> Result<(), CoreError> {
+        let metadata = transaction.metadata.as_ref().map_or(0, |metadata| metadata.len());
+        if metadata > self.max_metadata_bytes {
+            return Err(CoreError::MetadataTooLarge { size: metadata, max: self.max_metadata_bytes });
+        }
+        if transaction.parents.len() > self.max_parents {
+            return Err(CoreError::TooManyParents { count: transaction.parents.len(), max: self.max_parents });
+        }
+        if transaction.signature.len() > self.max_signature_bytes {
+            return Err(CoreError::SignatureTooLarge { size: transaction.signature.len(), max: self.max_signature_bytes });
+        }
+        let encoded = encoded_size(transaction);
+        if encoded > self.max_encoded_bytes {
+            return Err(CoreError::TransactionTooLarge { size: encoded, max: self.max_encoded_bytes });
+        }
+        Ok(())
+    }
+}
+
+/// Bytes of `transaction` in its canonical encoding, as it is sent to peers
+pub fn encoded_size(transaction: &Transaction) -> usize {
+    transaction.to_canonical_bytes().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::TransactionId;
+
+    fn transaction() -> Transaction {
+        Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 1,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![TransactionId::new(); 2],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 0 },
+            metadata: Some(vec![b'x'; 100]),
+        }
+    }
+
+    #[test]
+    fn test_each_limit_has_its_own_error() {
+        let limits = TransactionLimits::default();
+        assert!(limits.check(&transaction()).is_ok());
+
+        let tight = TransactionLimits { max_metadata_bytes: 99, ..limits.clone() };
+        assert!(matches!(tight.check(&transaction()), Err(CoreError::MetadataTooLarge { size: 100, max: 99 })));
+
+        let tight = TransactionLimits { max_parents: 1, ..limits.clone() };
+        assert!(matches!(tight.check(&transaction()), Err(CoreError::TooManyParents { count: 2, max: 1 })));
+
+        let tight = TransactionLimits { max_signature_bytes: 63, ..limits.clone() };
+        assert!(matches!(tight.check(&transaction()), Err(CoreError::SignatureTooLarge { size: 64, .. })));
+
+        let size = encoded_size(&transaction());
+        let tight = TransactionLimits { max_encoded_bytes: size - 1, ..limits };
+        assert!(matches!(tight.check(&transaction()), Err(CoreError::TransactionTooLarge { .. })));
+    }
+}
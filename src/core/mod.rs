@@ -1,12 +1,70 @@
 //! Core DAG blockchain components
 
-use crate::{BlockchainError, TransactionId, storage::DatabaseManager};
+use crate::{BlockchainError, TransactionId, identity::SignatureType, storage::DatabaseManager};
+use crate::consensus::{ConfirmationStatus, ConfirmationThresholds};
+use crate::events::{EventBus, NodeEvent};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+pub mod accounts;
+pub mod address_index;
+pub mod amount;
+pub mod bundle;
+pub mod ingestion;
+pub mod conflicts;
+pub mod congestion;
+pub mod deadline;
+pub mod encoding;
+pub mod expiry;
+pub mod filters;
+pub mod limits;
+pub mod orphans;
+pub mod paging;
+pub mod payload;
+pub mod tips;
+pub mod traversal;
+pub mod faucet;
+pub mod fees;
+pub mod proof;
+pub mod pruning;
+pub mod reorg;
+pub mod safe_mode;
+pub mod stealth;
+pub mod swaps;
+pub mod validation;
+pub mod weights;
+
+pub use accounts::{account_address, normalize_address, AccountBalance, AccountState, AccountStateHandler};
+pub use address_index::AddressIndex;
+pub use amount::{parse_amount, Amount};
+pub use bundle::{order_bundle, MAX_BUNDLE_SIZE};
+pub use congestion::{CongestionConfig, CongestionLevel, CongestionMonitor, CongestionReport, LatencyPercentiles};
+pub use conflicts::{ConflictSet, ConflictTracker, SpendKey};
+pub use deadline::{Deadline, SubmitStage};
+pub use encoding::{from_canonical_bytes, to_canonical_bytes, Canonical, CanonicalDecoder, CanonicalEncoder, EncodingError};
+pub use expiry::{expires_at, transaction_ttl, validate_ttl, EvictionReason, ExpiryConfig, TTL_METADATA_KEY};
+pub use ingestion::{IngestionConfig, IngestionQueue, IngestionStats, IngestionStatus, IngestionTicket, IngestionUpdate};
+pub use filters::{BloomFilter, CompactFilter, FilterHeader, FilterIndex, FilterSegment, FilterSubscriptionId, FilteredSync};
+pub use fees::{fee_rate, payload_size, FeeEstimate, FeeMarket, FeePolicy, FeeRates};
+pub use faucet::{CaptchaVerifier, Faucet, FaucetConfig, FaucetError, FaucetGrant, FaucetRequest, FaucetReservation, FaucetStats, FaucetVerifier, WebhookVerifier};
+pub use limits::{encoded_size, TransactionLimits};
+pub use orphans::{OrphanConfig, OrphanPool, OrphanStats};
+pub use paging::{NodeCache, PagingConfig};
+pub use payload::{validate_payload, PayloadRouter, TransactionPayload, MAX_CONTRACT_CODE_BYTES, PAYLOAD_METADATA_KEY};
+pub use proof::{build_inclusion_proof, verify_inclusion_proof, InclusionProof, ProofLink};
+pub use pruning::{prune_dag, PruneReport, PruningConfig};
+pub use reorg::Reorg;
+pub use safe_mode::{HaltSource, ResumeAuthorization, SafeMode, SafeModeAction, SafeModeConfig, SafeModeError, SafeModeStatus, SafeModeTransition};
+pub use stealth::{validate_stealth_payment, StealthAddress, StealthAnnouncement, STEALTH_METADATA_KEY};
+pub use swaps::{swap_hashlock, Asset, AtomicSwap, SwapLeg, SwapStatus};
+pub use tips::{ReputationTipScorer, SenderReputation, TipCandidate, TipScorer, TipSelectionParams, TipSelector};
+pub use traversal::{ancestors, descendants, Traversal};
+pub use validation::{ValidationConfig, ValidationPipeline};
+pub use weights::WeightCache;
+
 /// Transaction structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -17,7 +75,11 @@ pub struct Transaction {
     /// Receiver's public key
     pub receiver: Vec<u8>,
     /// Transaction amount
-    pub amount: u64,
+    #[serde(with = "amount")]
+    pub amount: Amount,
+    /// Fee paid to the validator that finalizes the transaction
+    #[serde(default, with = "amount")]
+    pub fee: Amount,
     /// Nonce for replay protection
     pub nonce: u64,
     /// Timestamp
@@ -26,12 +88,57 @@ pub struct Transaction {
     pub parents: Vec<TransactionId>,
     /// Digital signature
     pub signature: Vec<u8>,
+    /// Scheme `signature` was made with; transactions from before schemes
+    /// were recorded are hybrid-signed
+    #[serde(default)]
+    pub signature_scheme: SignatureType,
     /// Quantum resistance proof
     pub quantum_proof: QuantumProof,
     /// Optional metadata
     pub metadata: Option<Vec<u8>>,
 }
 
+/// Domain separator for transaction content hashes
+const TRANSACTION_ID_DOMAIN: &[u8] = b"qdag-tx-v1";
+
+impl Transaction {
+    /// Derive the content-addressed ID of this transaction
+    ///
+    /// SHA3-256 over the sender, receiver, amount, nonce, timestamp, parents
+    /// and metadata, with variable-length fields length-prefixed. A nonzero
+    /// fee is appended last so fee-less transactions keep their IDs. The
+    /// signature and quantum proof are left out because they are produced
+    /// over the ID. The layout predates `encoding` and is kept so existing
+    /// IDs stay valid; parents are hashed as bare length-prefixed bytes and
+    /// amounts as `amount::digest_bytes`.
+    pub fn compute_id(&self) -> TransactionId {
+        use sha3::{Digest, Sha3_256};
+
+        let mut encoder = CanonicalEncoder::new();
+        encoder.raw(TRANSACTION_ID_DOMAIN)
+            .bytes(&self.sender)
+            .bytes(&self.receiver)
+            .raw(&amount::digest_bytes(self.amount))
+            .u64(self.nonce)
+            .u64(self.timestamp)
+            .u64(self.parents.len() as u64);
+        for parent in &self.parents {
+            encoder.bytes(parent.as_bytes());
+        }
+        encoder.option(self.metadata.as_ref());
+        if self.fee > 0 {
+            encoder.raw(b"fee").raw(&amount::digest_bytes(self.fee));
+        }
+
+        TransactionId::Hash(Sha3_256::digest(encoder.finish()).into())
+    }
+
+    /// Whether `id` is the content hash of this transaction
+    pub fn has_valid_id(&self) -> bool {
+        self.id == self.compute_id()
+    }
+}
+
 /// Quantum resistance proof
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuantumProof {
@@ -87,19 +194,44 @@ pub struct DAGCore {
     database: Arc<DatabaseManager>,
     /// Whether to use database persistence
     use_persistence: bool,
+    /// Parent selection scoring
+    tip_selector: TipSelector,
+    /// Bus receiving status changes
+    events: Option<EventBus>,
+    /// Memoized cumulative weights and depths
+    weights: WeightCache,
+    /// Double spends and the branches approving them
+    conflicts: ConflictTracker,
+    /// Older transactions fetched from storage on demand
+    cold: NodeCache,
+    /// Largest transaction accepted
+    limits: TransactionLimits,
+    /// Whether double spends wait for peers to accept a member
+    conflict_sampling: bool,
+    /// Relayed transactions waiting for their parents
+    orphans: OrphanPool,
+    /// In-memory transactions by sender and receiver
+    addresses: AddressIndex,
+    /// Confidence needed to confirm, by amount
+    thresholds: ConfirmationThresholds,
 }
 
 impl DAGCore {
-    /// Create a new DAG core
-    pub fn new() -> Result<Self, BlockchainError> {
-        // Create a dummy database manager for in-memory operation
-        let db_config = crate::storage::DatabaseConfig::default();
-        let database = Arc::new(DatabaseManager::new(db_config).await?);
+    /// Create a new DAG core kept entirely in memory
+    pub async fn new() -> Result<Self, BlockchainError> {
+        let database = Arc::new(DatabaseManager::new(crate::storage::DatabaseConfig::in_memory()).await?);
         Self::new_with_database(database).await
     }
 
     /// Create a new DAG core with database persistence
     pub async fn new_with_database(database: Arc<DatabaseManager>) -> Result<Self, BlockchainError> {
+        Self::new_with_paging(database, &PagingConfig::default()).await
+    }
+
+    /// Create a new DAG core, loading only recent transactions from the database
+    ///
+    /// See the `paging` module.
+    pub async fn new_with_paging(database: Arc<DatabaseManager>, paging: &PagingConfig) -> Result<Self, BlockchainError> {
         let use_persistence = true;
         let mut dag = Self {
             transactions: HashMap::new(),
@@ -108,15 +240,24 @@ impl DAGCore {
             transaction_count: 0,
             database: database.clone(),
             use_persistence,
+            tip_selector: TipSelector::default(),
+            events: None,
+            weights: WeightCache::new(),
+            conflicts: ConflictTracker::new(),
+            cold: NodeCache::with_megabytes(paging.cache_size_mb),
+            limits: TransactionLimits::default(),
+            conflict_sampling: false,
+            orphans: OrphanPool::default(),
+            addresses: AddressIndex::new(),
+            thresholds: ConfirmationThresholds::default(),
         };
 
         // Try to load existing data from database
         if use_persistence {
             if let Ok(existing_count) = database.get_transaction_count().await {
                 if existing_count > 0 {
-                    // Load existing transactions from database
-                    dag.load_from_database().await?;
-                    log::info!("Loaded {} transactions from database", existing_count);
+                    // Load recent transactions; older ones are fetched on demand
+                    dag.load_recent(paging).await?;
                     return Ok(dag);
                 }
             }
@@ -136,6 +277,7 @@ impl DAGCore {
         };
 
         dag.genesis = Some(genesis_id.clone());
+        dag.addresses.insert(&genesis_node.transaction);
         dag.transactions.insert(genesis_id.clone(), genesis_node);
 
         // Store genesis transaction if using persistence
@@ -149,99 +291,90 @@ impl DAGCore {
         Ok(dag)
     }
 
-    /// Load existing data from database
-    async fn load_from_database(&mut self) -> Result<(), BlockchainError> {
-        if !self.use_persistence {
-            return Ok(());
-        }
-
-        // Load all transactions from database
-        let transactions = self.database.get_transactions(None, None, None).await?;
-        
-        for transaction in transactions {
-            let tx_id = transaction.id.clone();
-            
-            // Try to load corresponding DAG node
-            let dag_node = if let Some(node) = self.database.get_dag_node(&tx_id).await? {
-                node
-            } else {
-                // Create DAG node if it doesn't exist
-                DAGNode {
-                    transaction: transaction.clone(),
-                    children: Vec::new(),
-                    weight: self.calculate_initial_weight(&transaction),
-                    confidence: 0.0,
-                    status: NodeStatus::Pending,
-                    quantum_score: transaction.quantum_proof.resistance_score,
-                }
-            };
-
-            self.transactions.insert(tx_id, dag_node);
-        }
-
-        // Update transaction count
-        self.transaction_count = self.transactions.len() as u64;
-
-        // Rebuild tips set
-        self.tips.clear();
-        for (tx_id, node) in &self.transactions {
-            if node.status == NodeStatus::Pending {
-                self.tips.insert(tx_id.clone());
-            }
-        }
-
-        // Find genesis transaction
-        for (tx_id, node) in &self.transactions {
-            if node.transaction.parents.is_empty() {
-                self.genesis = Some(tx_id.clone());
-                break;
-            }
-        }
-
-        Ok(())
-    }
-
     /// Create genesis transaction
     fn create_genesis_transaction(&self) -> Result<Transaction, BlockchainError> {
-        let id = TransactionId::new();
         let timestamp = chrono::Utc::now().timestamp() as u64;
 
-        Ok(Transaction {
-            id: id.clone(),
+        let mut genesis = Transaction {
+            id: TransactionId::default(),
             sender: vec![0u8; 32], // Genesis sender
             receiver: vec![0u8; 32], // Genesis receiver
             amount: 0,
+            fee: 0,
             nonce: 0,
             timestamp,
             parents: Vec::new(), // Genesis has no parents
             signature: vec![0u8; 64], // Empty signature
+            signature_scheme: Default::default(),
             quantum_proof: QuantumProof {
                 prime_hash: vec![0u8; 32],
                 resistance_score: 100,
                 proof_timestamp: timestamp,
             },
             metadata: Some(b"genesis".to_vec()),
-        })
+        };
+        genesis.id = genesis.compute_id();
+        Ok(genesis)
     }
 
     /// Add a transaction to the DAG
     pub async fn add_transaction(&mut self, transaction: Transaction) -> Result<TransactionId, BlockchainError> {
+        // Parents older than the loaded window are paged in from storage
+        if self.use_persistence {
+            self.page_in(&transaction.parents).await?;
+            self.ensure_not_stored(&transaction.id).await?;
+        }
+
         // Validate transaction
-        self.validate_transaction(&transaction)?;
+        if let Err(e) = self.validate_transaction(&transaction, &HashSet::new()) {
+            self.tip_selector.penalize_sender(&transaction.sender);
+            return Err(e);
+        }
+
+        let node = self.new_node(&transaction);
+        let tx_id = self.insert_node(node.clone());
+
+        // Store in database if persistence is enabled
+        if self.use_persistence {
+            self.database.store_transaction(&transaction).await?;
+            self.database.store_dag_node(&node).await?;
+        }
+
+        log::info!("Added transaction {} to DAG", tx_id);
+        Ok(tx_id)
+    }
+
+    /// Fail if a transaction beyond the loaded window is already stored
+    async fn ensure_not_stored(&self, tx_id: &TransactionId) -> Result<(), BlockchainError> {
+        if self.transaction_count > self.transactions.len() as u64
+            && !self.transactions.contains_key(tx_id)
+            && self.database.get_transaction(tx_id).await?.is_some()
+        {
+            return Err(BlockchainError::Core(CoreError::TransactionExists(tx_id.clone())));
+        }
+        Ok(())
+    }
 
-        // Create DAG node
-        let node = DAGNode {
+    /// Pending DAG node for a validated transaction
+    fn new_node(&self, transaction: &Transaction) -> DAGNode {
+        DAGNode {
             transaction: transaction.clone(),
             children: Vec::new(),
-            weight: self.calculate_initial_weight(&transaction),
+            weight: self.calculate_initial_weight(transaction),
             confidence: 0.0,
             status: NodeStatus::Pending,
             quantum_score: transaction.quantum_proof.resistance_score,
-        };
+        }
+    }
+
+    /// Link a validated node into the in-memory DAG and tip set
+    fn insert_node(&mut self, node: DAGNode) -> TransactionId {
+        let transaction = node.transaction.clone();
 
         // Add to DAG
         let tx_id = transaction.id.clone();
-        self.transactions.insert(tx_id.clone(), node.clone());
+        self.addresses.insert(&transaction);
+        self.transactions.insert(tx_id.clone(), node);
 
         // Update parent-child relationships
         for parent_id in &transaction.parents {
@@ -249,39 +382,32 @@ impl DAGCore {
                 parent_node.children.push(tx_id.clone());
             }
         }
+        self.weights.invalidate_ancestors(&tx_id, &self.transactions);
+        if let Some(conflict) = self.conflicts.insert(&transaction, &self.transactions) {
+            log::warn!("⚠️ Double spend of {} by {} transaction(s)", conflict.key, conflict.members.len());
+        }
 
         // Update tips
         self.tips.remove(&tx_id);
+        let mut approved_tips = Vec::new();
         for parent_id in &transaction.parents {
-            self.tips.remove(parent_id);
+            if self.tips.remove(parent_id) {
+                approved_tips.push(parent_id.clone());
+            }
+            self.tip_selector.forget(parent_id);
         }
         self.tips.insert(tx_id.clone());
+        self.publish_tip_change(vec![tx_id.clone()], approved_tips);
+        self.tip_selector.reward_sender(&transaction.sender);
 
         self.transaction_count += 1;
-
-        // Store in database if persistence is enabled
-        if self.use_persistence {
-            self.database.store_transaction(&transaction).await?;
-            self.database.store_dag_node(&node).await?;
-        }
-
-        log::info!("Added transaction {} to DAG", tx_id);
-        Ok(tx_id)
+        tx_id
     }
 
     /// Get transaction by ID
     pub async fn get_transaction(&self, tx_id: &TransactionId) -> Result<Option<Transaction>, BlockchainError> {
-        // First check in-memory cache
-        if let Some(node) = self.transactions.get(tx_id) {
-            return Ok(Some(node.transaction.clone()));
-        }
-
-        // If not in memory and persistence is enabled, check database
-        if self.use_persistence {
-            return self.database.get_transaction(tx_id).await;
-        }
-
-        Ok(None)
+        // Memory first, then the on-demand cache and the database
+        Ok(self.fetch_node(tx_id).await?.map(|node| node.transaction))
     }
 
     /// Get DAG node by ID
@@ -306,6 +432,8 @@ impl DAGCore {
     }
 
     /// Select parent transactions for a new transaction
+    ///
+    /// Tips are drawn without replacement, weighted by the active tip scorer.
     pub fn select_parents(&self, count: usize) -> Vec<TransactionId> {
         let tips = self.get_tips();
         
@@ -313,41 +441,104 @@ impl DAGCore {
             return vec![self.genesis.clone().unwrap()];
         }
 
-        // Simple weighted random selection based on node weight
-        let mut rng = rand::thread_rng();
-        let mut selected = Vec::new();
-        
-        for _ in 0..count.min(tips.len()) {
-            let total_weight: u64 = tips.iter().map(|node| node.weight).sum();
-            let target: u64 = rng.gen_range(0..total_weight);
-            
-            let mut current_weight = 0;
-            for node in &tips {
-                current_weight += node.weight;
-                if current_weight >= target {
-                    selected.push(node.transaction.id.clone());
-                    break;
-                }
-            }
+        self.tip_selector.select(&tips, count, |tx_id| self.calculate_cumulative_weight(tx_id))
+    }
+
+    /// Publish status changes to `events`
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = Some(events);
+    }
+
+    /// Replace the size limits new transactions are checked against
+    pub fn set_limits(&mut self, limits: TransactionLimits) {
+        self.limits = limits;
+    }
+
+    /// Size limits new transactions are checked against
+    pub fn limits(&self) -> &TransactionLimits {
+        &self.limits
+    }
+
+    /// Leave double spends to repeated sampling instead of branch weight
+    ///
+    /// While enabled, a transaction on a conflicting branch only confirms
+    /// once `accept_conflict` resolved every conflict it is part of in its
+    /// favour.
+    pub fn set_conflict_sampling(&mut self, enabled: bool) {
+        self.conflict_sampling = enabled;
+    }
+
+    /// Replace the confidence transactions need to confirm
+    pub fn set_confirmation_thresholds(&mut self, thresholds: ConfirmationThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Confidence needed to confirm, by amount
+    pub fn confirmation_thresholds(&self) -> &ConfirmationThresholds {
+        &self.thresholds
+    }
+
+    /// Status and confidence of a transaction against the threshold for its amount
+    pub async fn get_confirmation_status(&self, tx_id: &TransactionId) -> Result<Option<ConfirmationStatus>, BlockchainError> {
+        let Some(node) = self.fetch_node(tx_id).await? else {
+            return Ok(None);
+        };
+        let amount = node.transaction.amount;
+        Ok(Some(ConfirmationStatus {
+            status: node.status,
+            confidence: node.confidence,
+            required_confidence: self.thresholds.required_confidence(amount),
+            tier_min_amount: self.thresholds.tier_for(amount).map(|tier| tier.min_amount),
+        }))
+    }
+
+    /// Resolve a double spend in favour of the member peers accepted
+    ///
+    /// The branches of its rivals are rejected; the winner itself confirms
+    /// once its confidence allows. Returns the rejected transactions.
+    pub fn accept_conflict(&mut self, winner: &TransactionId) -> Vec<TransactionId> {
+        let Some(node) = self.transactions.get(winner) else {
+            return Vec::new();
+        };
+        if node.status == NodeStatus::Rejected {
+            log::warn!("⚠️ Not accepting {}: it was already rejected", winner);
+            return Vec::new();
         }
 
-        selected
+        let mut changed = Vec::new();
+        let reorg = self.reject_conflict_losers(winner, &mut changed);
+        let rejected = changed.iter().map(|(tx_id, _)| tx_id.clone()).collect();
+        self.publish_status_changes(changed);
+        self.publish_reorgs(reorg.into_iter().collect());
+        rejected
+    }
+
+    /// Replace the scoring function used for parent selection
+    pub fn set_tip_scorer(&mut self, scorer: Arc<dyn TipScorer>) {
+        self.tip_selector.set_scorer(scorer);
+    }
+
+    /// Tip selection state
+    pub fn tip_selector(&self) -> &TipSelector {
+        &self.tip_selector
+    }
+
+    /// Record the fee paid by a transaction for fee-density scoring
+    pub fn record_fee(&mut self, transaction: &Transaction) {
+        self.tip_selector.record_fee(&transaction.id, transaction.fee, payload_size(transaction));
+    }
+
+    /// Lower a sender's reputation after a rejected transaction
+    pub fn penalize_sender(&mut self, sender: &[u8]) {
+        self.tip_selector.penalize_sender(sender);
     }
 
     /// Calculate cumulative weight for a node
+    ///
+    /// Own weight plus the weights of all approvers (children), memoized
+    /// until a new descendant is added.
     pub fn calculate_cumulative_weight(&self, node_id: &TransactionId) -> u64 {
-        if let Some(node) = self.transactions.get(node_id) {
-            let mut weight = node.weight;
-            
-            // Add weights of all approvers (children)
-            for child_id in &node.children {
-                weight += self.calculate_cumulative_weight(child_id);
-            }
-            
-            weight
-        } else {
-            0
-        }
+        self.weights.cumulative_weight(node_id, &self.transactions)
     }
 
     /// Get transaction count
@@ -356,7 +547,13 @@ impl DAGCore {
     }
 
     /// Validate transaction structure
-    fn validate_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
+    ///
+    /// Parents may also be in `staged`, transactions validated alongside
+    /// this one but not yet inserted.
+    fn validate_transaction(&self, transaction: &Transaction, staged: &HashSet<TransactionId>) -> Result<(), BlockchainError> {
+        // Reject oversized transactions before hashing or looking anything up
+        self.limits.check(transaction)?;
+
         // Check if transaction already exists
         if self.transactions.contains_key(&transaction.id) {
             return Err(BlockchainError::Core(CoreError::TransactionExists(
@@ -364,21 +561,40 @@ impl DAGCore {
             )));
         }
 
+        // Validate the ID is the content hash
+        if !transaction.has_valid_id() {
+            return Err(BlockchainError::Core(CoreError::IdMismatch(
+                transaction.id.clone()
+            )));
+        }
+
         // Validate parents exist
         for parent_id in &transaction.parents {
-            if !self.transactions.contains_key(parent_id) {
+            if !self.transactions.contains_key(parent_id) && !staged.contains(parent_id) {
                 return Err(BlockchainError::Core(CoreError::ParentNotFound(
                     parent_id.clone()
                 )));
             }
         }
 
+        // Validate the one-time address derivation of stealth payments
+        validate_stealth_payment(transaction)?;
+
+        // Validate the payload against the transaction carrying it
+        validate_payload(transaction)?;
+
+        // Validate against settled spends and conflicting branches
+        self.conflicts.check(transaction, &self.transactions)?;
+
         // Validate timestamp
         let current_time = chrono::Utc::now().timestamp() as u64;
         if transaction.timestamp > current_time + 300 { // 5 minutes in the future
             return Err(BlockchainError::Core(CoreError::InvalidTimestamp));
         }
 
+        // Validate the TTL has not already run out
+        validate_ttl(transaction, current_time)?;
+
         // Validate quantum proof
         if transaction.quantum_proof.resistance_score < 50 {
             return Err(BlockchainError::Core(CoreError::InsufficientQuantumResistance));
@@ -408,37 +624,173 @@ impl DAGCore {
         for (tx_id, node) in &self.transactions {
             if node.status == NodeStatus::Pending {
                 let confidence = self.calculate_confidence(tx_id);
-                updates.insert(tx_id.clone(), confidence);
+                let required = self.thresholds.required_confidence(node.transaction.amount);
+                // Only the heaviest, or under sampling the accepted, side of a double spend may confirm
+                let confirmable = confidence > required && if self.conflict_sampling {
+                    self.conflicts.is_settled(tx_id)
+                } else {
+                    self.conflicts.can_confirm(tx_id, &self.transactions, |id| self.calculate_cumulative_weight(id))
+                };
+                updates.insert(tx_id.clone(), (confidence, confirmable));
             }
         }
         
-        for (tx_id, confidence) in updates {
+        let mut changed = Vec::new();
+        let mut confirmed = Vec::new();
+        for (tx_id, (confidence, confirmable)) in updates {
             if let Some(node) = self.transactions.get_mut(&tx_id) {
                 let old_status = node.status.clone();
                 node.confidence = confidence;
                 
                 // Auto-confirm transactions with high confidence
-                if confidence > 0.8 {
+                if confirmable {
                     node.status = NodeStatus::Confirmed;
                     self.tips.remove(&tx_id);
+                    confirmed.push(tx_id.clone());
                 }
 
-                // Update database if persistence is enabled
-                if self.use_persistence && old_status != node.status {
-                    let db = self.database.clone();
-                    let tx_id_clone = tx_id.clone();
-                    let status_clone = node.status.clone();
-                    let confidence_clone = confidence;
-                    
-                    // Spawn async task to update database
-                    tokio::spawn(async move {
-                        if let Err(e) = db.update_node_status(&tx_id_clone, status_clone, confidence_clone).await {
-                            log::error!("Failed to update node status in database: {}", e);
-                        }
-                    });
+                if old_status != node.status {
+                    changed.push((tx_id, old_status));
                 }
             }
         }
+
+        // A confirmed double spend rejects the branches of its rivals
+        let reorgs: Vec<Reorg> = confirmed.iter()
+            .filter_map(|winner| self.reject_conflict_losers(winner, &mut changed))
+            .collect();
+
+        self.publish_status_changes(changed);
+        self.publish_reorgs(reorgs);
+    }
+
+    /// Finalize transactions attested by a validator quorum, with their ancestors
+    ///
+    /// Finality does not wait for confidence: pending and confirmed
+    /// transactions alike become final, parents before children. It is
+    /// irreversible, so finalized transactions are skipped, and so are
+    /// rejected ones, which can no longer be finalized. Returns the newly
+    /// finalized transactions in the order they were finalized.
+    pub fn finalize(&mut self, tx_ids: &[TransactionId]) -> Vec<TransactionId> {
+        let mut targets = HashSet::new();
+        for tx_id in tx_ids {
+            let Some(node) = self.transactions.get(tx_id) else {
+                log::warn!("⚠️ Cannot finalize {}: not in the DAG", tx_id);
+                continue;
+            };
+            if node.status == NodeStatus::Rejected {
+                log::error!("❌ Cannot finalize {}: it was rejected", tx_id);
+                continue;
+            }
+            targets.insert(tx_id.clone());
+            if let Ok(ancestors) = self.get_ancestors(tx_id, usize::MAX) {
+                targets.extend(ancestors.into_iter().map(|node| node.transaction.id.clone()));
+            }
+        }
+
+        let order: Vec<TransactionId> = self.traverse()
+            .filter(|node| targets.contains(&node.transaction.id))
+            .filter(|node| matches!(node.status, NodeStatus::Pending | NodeStatus::Confirmed))
+            .map(|node| node.transaction.id.clone())
+            .collect();
+
+        let mut changed = Vec::new();
+        let mut reorgs = Vec::new();
+        for tx_id in &order {
+            if let Some(node) = self.transactions.get_mut(tx_id) {
+                changed.push((tx_id.clone(), node.status.clone()));
+                node.status = NodeStatus::Finalized;
+                self.tips.remove(tx_id);
+            }
+            reorgs.extend(self.reject_conflict_losers(tx_id, &mut changed));
+        }
+
+        self.publish_status_changes(changed);
+        self.publish_reorgs(reorgs);
+        order
+    }
+
+    /// Reject the branches of a double spend that lost to `winner`
+    ///
+    /// See the `reorg` module.
+    fn reject_conflict_losers(&mut self, winner: &TransactionId, changed: &mut Vec<(TransactionId, NodeStatus)>) -> Option<Reorg> {
+        let losers = self.conflicts.resolve(winner, &self.transactions);
+        self.roll_back(winner, losers, changed)
+    }
+
+    /// Publish status changes, and the tips they removed, given each node's old status
+    fn publish_status_changes(&self, changed: Vec<(TransactionId, NodeStatus)>) {
+        let left_tips: Vec<_> = changed.iter()
+            .filter(|(_, old_status)| *old_status == NodeStatus::Pending)
+            .map(|(tx_id, _)| tx_id.clone())
+            .collect();
+        if !left_tips.is_empty() {
+            self.publish_tip_change(Vec::new(), left_tips);
+        }
+
+        for (tx_id, old_status) in changed {
+            self.publish_status_change(&tx_id, old_status);
+        }
+    }
+
+    /// Publish a change to the tip set
+    fn publish_tip_change(&self, added: Vec<TransactionId>, removed: Vec<TransactionId>) {
+        if let Some(events) = &self.events {
+            events.publish(NodeEvent::TipSetChanged {
+                added,
+                removed,
+                tip_count: self.tips.len(),
+            });
+        }
+    }
+
+    /// Publish and persist a node's status change
+    fn publish_status_change(&self, tx_id: &TransactionId, old_status: NodeStatus) {
+        let Some(node) = self.transactions.get(tx_id) else {
+            return;
+        };
+
+        if let Some(events) = &self.events {
+            events.publish(NodeEvent::StatusChanged {
+                tx_id: tx_id.clone(),
+                previous: old_status,
+                status: node.status.clone(),
+                confidence: node.confidence,
+                submitted_at: node.transaction.timestamp,
+            });
+        }
+
+        // Update database if persistence is enabled
+        if self.use_persistence {
+            let db = self.database.clone();
+            let tx_id_clone = tx_id.clone();
+            let status_clone = node.status.clone();
+            let confidence_clone = node.confidence;
+            
+            // Spawn async task to update database
+            crate::spawn_instrumented(crate::Subsystem::Storage, "node_status_update", async move {
+                if let Err(e) = db.update_node_status(&tx_id_clone, status_clone, confidence_clone).await {
+                    log::error!("Failed to update node status in database: {}", e);
+                }
+            });
+        }
+    }
+
+    /// ID of the genesis transaction, once the DAG is initialized
+    pub fn genesis_id(&self) -> Option<&TransactionId> {
+        self.genesis.as_ref()
+    }
+
+    /// Proof that `tx_id` is an ancestor of a finalized checkpoint
+    ///
+    /// See `build_inclusion_proof`.
+    pub fn inclusion_proof(&self, tx_id: &TransactionId, checkpoint: Option<&TransactionId>) -> Result<InclusionProof, CoreError> {
+        build_inclusion_proof(&self.transactions, tx_id, checkpoint)
+    }
+
+    /// Double spends seen by this node, unresolved first
+    pub fn get_conflict_sets(&self) -> Vec<ConflictSet> {
+        self.conflicts.conflict_sets()
     }
 
     /// Calculate confidence score for a transaction
@@ -484,8 +836,8 @@ impl DAGCore {
         let mut total_children = 0;
 
         // Calculate maximum depth (longest path from genesis)
-        if let Some(genesis_id) = &self.genesis {
-            max_depth = self.calculate_depth(genesis_id);
+        for tx_id in self.transactions.keys() {
+            max_depth = max_depth.max(self.calculate_depth(tx_id));
         }
 
         // Count tips (pending transactions)
@@ -511,23 +863,7 @@ impl DAGCore {
 
     /// Calculate depth of a node (distance from genesis)
     fn calculate_depth(&self, node_id: &TransactionId) -> usize {
-        let Some(node) = self.transactions.get(node_id) else {
-            return 0;
-        };
-
-        if node.transaction.parents.is_empty() {
-            return 1; // Genesis node
-        }
-
-        let mut max_parent_depth = 0;
-        for parent_id in &node.transaction.parents {
-            let parent_depth = self.calculate_depth(parent_id);
-            if parent_depth > max_parent_depth {
-                max_parent_depth = parent_depth;
-            }
-        }
-
-        max_parent_depth + 1
+        self.weights.depth(node_id, &self.transactions)
     }
 
     /// Get storage size estimate
@@ -559,6 +895,8 @@ impl DAGCore {
 pub enum CoreError {
     #[error("Transaction already exists: {0}")]
     TransactionExists(TransactionId),
+    #[error("Transaction ID {0} does not match its content hash")]
+    IdMismatch(TransactionId),
     #[error("Parent transaction not found: {0}")]
     ParentNotFound(TransactionId),
     #[error("Invalid timestamp")]
@@ -569,39 +907,132 @@ pub enum CoreError {
     InvalidTransactionStructure,
     #[error("Serialization error: {0}")]
     Serialization(String),
+    #[error("Ingestion queue full, retry after {0}s")]
+    Backpressure(u64),
+    #[error("Invalid address filter: {0}")]
+    InvalidFilter(String),
+    #[error("Double spend of settled funds: {0}")]
+    DoubleSpend(String),
+    #[error("Transaction approves conflicting spends of {0}")]
+    ConflictingParents(String),
+    #[error("Transaction approves rejected branch of {0}")]
+    RejectedBranch(TransactionId),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Insufficient balance in {address}: {available} available, {required} required")]
+    InsufficientBalance { address: String, available: Amount, required: Amount },
+    #[error("Transaction of {amount} requires a {required:?} signature, not {used:?}")]
+    WeakSignatureScheme { amount: Amount, required: SignatureType, used: SignatureType },
+    #[error("Signature does not match recorded scheme {0:?}")]
+    SignatureSchemeMismatch(SignatureType),
+    #[error("Transaction not found: {0}")]
+    TransactionNotFound(TransactionId),
+    #[error("No finalized checkpoint descends from {0}")]
+    NoCheckpoint(TransactionId),
+    #[error("Invalid inclusion proof: {0}")]
+    InvalidInclusionProof(String),
+    #[error("Deadline exceeded during {0}")]
+    DeadlineExceeded(SubmitStage),
+    #[error("Invalid stealth payment: {0}")]
+    InvalidStealthPayment(String),
+    #[error("Invalid transaction payload: {0}")]
+    InvalidPayload(String),
+    #[error("Token not found: {0}")]
+    TokenNotFound(String),
+    #[error("Insufficient {token_id} in {address}: {available} available, {required} required")]
+    InsufficientTokenBalance { token_id: String, address: String, available: Amount, required: Amount },
+    #[error("Swap not found: {0}")]
+    SwapNotFound(String),
+    #[error("Swap {hashlock} rejected: {reason}")]
+    InvalidSwap { hashlock: String, reason: String },
+    #[error("Invalid TTL: {0}")]
+    InvalidTtl(String),
+    #[error("Transaction expired: {0}")]
+    TransactionExpired(TransactionId),
+    #[error("Invalid bundle: {0}")]
+    InvalidBundle(String),
+    #[error("Bundle rejected at {tx_id}: {reason}")]
+    BundleRejected { tx_id: TransactionId, reason: String },
+    #[error("Metadata of {size} bytes exceeds the {max} byte limit")]
+    MetadataTooLarge { size: usize, max: usize },
+    #[error("Transaction approves {count} parents, at most {max} allowed")]
+    TooManyParents { count: usize, max: usize },
+    #[error("Signature of {size} bytes exceeds the {max} byte limit")]
+    SignatureTooLarge { size: usize, max: usize },
+    #[error("Transaction of {size} bytes exceeds the {max} byte limit")]
+    TransactionTooLarge { size: usize, max: usize },
 }
 
 /// Transaction ID type
-#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
-pub struct TransactionId(Uuid);
+///
+/// Transactions are identified by a SHA3-256 hash over their canonical fields
+/// (see `Transaction::compute_id`), so every node derives the same ID for the
+/// same transaction. IDs stored before content addressing are random UUIDs;
+/// they are read back as `Legacy` until the database is migrated.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum TransactionId {
+    /// SHA3-256 content hash
+    Hash([u8; 32]),
+    /// Random UUID from before content addressing
+    Legacy(Uuid),
+}
 
 impl TransactionId {
-    /// Generate a new transaction ID
+    /// Generate a random placeholder ID
+    ///
+    /// Placeholders do not match any transaction's content hash; they are
+    /// replaced by `Transaction::compute_id` before a transaction is accepted.
     pub fn new() -> Self {
-        Self(Uuid::new_v4())
+        Self::Hash(rand::random())
     }
 
-    /// Create from bytes
+    /// Create from bytes: 32 bytes for a content hash, 16 for a legacy UUID
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlockchainError> {
+        if let Ok(hash) = <[u8; 32]>::try_from(bytes) {
+            return Ok(Self::Hash(hash));
+        }
         let uuid = Uuid::from_slice(bytes)
             .map_err(|e| BlockchainError::Core(CoreError::Serialization(e.to_string())))?;
-        Ok(Self(uuid))
+        Ok(Self::Legacy(uuid))
+    }
+
+    /// Parse from the string form produced by `as_string`
+    pub fn from_string(value: &str) -> Result<Self, BlockchainError> {
+        if value.len() == 64 {
+            if let Ok(bytes) = hex::decode(value) {
+                return Self::from_bytes(&bytes);
+            }
+        }
+        let uuid = Uuid::parse_str(value)
+            .map_err(|e| BlockchainError::Core(CoreError::Serialization(e.to_string())))?;
+        Ok(Self::Legacy(uuid))
     }
 
     /// Get as bytes
     pub fn as_bytes(&self) -> &[u8] {
-        self.0.as_bytes()
+        match self {
+            Self::Hash(hash) => hash,
+            Self::Legacy(uuid) => uuid.as_bytes(),
+        }
     }
 
-    /// Get as string
+    /// Get as string: hex for content hashes, hyphenated for legacy UUIDs
     pub fn as_string(&self) -> String {
-        self.0.to_string()
+        match self {
+            Self::Hash(hash) => hex::encode(hash),
+            Self::Legacy(uuid) => uuid.to_string(),
+        }
+    }
+
+    /// Whether this is a content hash rather than a legacy UUID
+    pub fn is_content_addressed(&self) -> bool {
+        matches!(self, Self::Hash(_))
     }
 }
 
 impl std::fmt::Display for TransactionId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.as_string())
     }
 }
 
@@ -611,6 +1042,21 @@ impl Default for TransactionId {
     }
 }
 
+// Serialized as the `as_string` form, which keeps UUIDs written by earlier
+// releases readable
+impl Serialize for TransactionId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for TransactionId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::from_string(&value).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -622,8 +1068,54 @@ mod tests {
     }
 
     #[test]
-    fn test_dag_core_creation() {
-        let dag = DAGCore::new();
+    fn test_content_addressed_id() {
+        let mut tx = Transaction {
+            id: TransactionId::new(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 100,
+            fee: 0,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![TransactionId::Legacy(Uuid::new_v4())],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![],
+                resistance_score: 80,
+                proof_timestamp: 0,
+            },
+            metadata: None,
+        };
+        assert!(!tx.has_valid_id());
+
+        tx.id = tx.compute_id();
+        let on_other_node = tx.clone();
+        tx.signature = vec![7u8; 64];
+        assert!(tx.has_valid_id());
+        assert_eq!(tx.id, on_other_node.compute_id());
+
+        tx.amount += 1;
+        assert!(!tx.has_valid_id());
+    }
+
+    #[test]
+    fn test_transaction_id_string_forms() {
+        let hash = TransactionId::Hash([0xab; 32]);
+        assert_eq!(TransactionId::from_string(&hash.as_string()).unwrap(), hash);
+        assert_eq!(TransactionId::from_bytes(hash.as_bytes()).unwrap(), hash);
+
+        // IDs serialized by earlier releases are bare UUID strings
+        let uuid = Uuid::new_v4();
+        let legacy: TransactionId = serde_json::from_str(&format!("\"{}\"", uuid)).unwrap();
+        assert_eq!(legacy, TransactionId::Legacy(uuid));
+        assert!(!legacy.is_content_addressed());
+        assert_eq!(serde_json::to_string(&legacy).unwrap(), format!("\"{}\"", uuid));
+    }
+
+    #[tokio::test]
+    async fn test_dag_core_creation() {
+        let dag = DAGCore::new().await;
         assert!(dag.is_ok());
         let dag = dag.unwrap();
         assert_eq!(dag.transaction_count(), 1); // Genesis transaction
@@ -632,17 +1124,19 @@ mod tests {
 
     #[tokio::test]
     async fn test_add_transaction() {
-        let mut dag = DAGCore::new().unwrap();
+        let mut dag = DAGCore::new().await.unwrap();
         
-        let tx = Transaction {
+        let mut tx = Transaction {
             id: TransactionId::new(),
             sender: vec![1u8; 32],
             receiver: vec![2u8; 32],
             amount: 100,
+            fee: 0,
             nonce: 1,
             timestamp: chrono::Utc::now().timestamp() as u64,
             parents: vec![dag.genesis.clone().unwrap()],
             signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
             quantum_proof: QuantumProof {
                 prime_hash: vec![1u8; 32],
                 resistance_score: 80,
@@ -650,9 +1144,98 @@ mod tests {
             },
             metadata: None,
         };
+        tx.id = tx.compute_id();
 
         let result = dag.add_transaction(tx).await;
         assert!(result.is_ok());
         assert_eq!(dag.transaction_count(), 2);
     }
-}
\ No newline at end of file
+    #[tokio::test]
+    async fn test_tip_set_changes_are_published() {
+        let mut dag = DAGCore::new().await.unwrap();
+        let events = EventBus::default();
+        let mut receiver = events.subscribe();
+        dag.set_event_bus(events);
+
+        let mut parent = dag.genesis.clone().unwrap();
+        let mut added_ids = Vec::new();
+        for nonce in 1..=2 {
+            let mut tx = Transaction {
+                id: TransactionId::new(),
+                sender: vec![1u8; 32],
+                receiver: vec![2u8; 32],
+                amount: 100,
+                fee: 0,
+                nonce,
+                timestamp: chrono::Utc::now().timestamp() as u64,
+                parents: vec![parent.clone()],
+                signature: vec![0u8; 64],
+                signature_scheme: Default::default(),
+                quantum_proof: QuantumProof {
+                    prime_hash: vec![1u8; 32],
+                    resistance_score: 80,
+                    proof_timestamp: chrono::Utc::now().timestamp() as u64,
+                },
+                metadata: None,
+            };
+            tx.id = tx.compute_id();
+            parent = dag.add_transaction(tx).await.unwrap();
+            added_ids.push(parent.clone());
+        }
+
+        // Genesis is finalized, so the first transaction approves no tip
+        match receiver.try_recv().unwrap() {
+            NodeEvent::TipSetChanged { added, removed, tip_count } => {
+                assert_eq!(added, vec![added_ids[0].clone()]);
+                assert!(removed.is_empty());
+                assert_eq!(tip_count, 1);
+            }
+            other => panic!("unexpected event {}", other.kind()),
+        }
+        match receiver.try_recv().unwrap() {
+            NodeEvent::TipSetChanged { added, removed, tip_count } => {
+                assert_eq!(added, vec![added_ids[1].clone()]);
+                assert_eq!(removed, vec![added_ids[0].clone()]);
+                assert_eq!(tip_count, 1);
+            }
+            other => panic!("unexpected event {}", other.kind()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_finalize_takes_ancestors_and_is_irreversible() {
+        let mut dag = DAGCore::new().await.unwrap();
+        let mut parent = dag.genesis.clone().unwrap();
+        let mut ids = Vec::new();
+        for nonce in 1..=3 {
+            let mut tx = Transaction {
+                id: TransactionId::new(),
+                sender: vec![1u8; 32],
+                receiver: vec![2u8; 32],
+                amount: 100,
+                fee: 0,
+                nonce,
+                timestamp: chrono::Utc::now().timestamp() as u64,
+                parents: vec![parent.clone()],
+                signature: vec![0u8; 64],
+                signature_scheme: Default::default(),
+                quantum_proof: QuantumProof {
+                    prime_hash: vec![1u8; 32],
+                    resistance_score: 80,
+                    proof_timestamp: chrono::Utc::now().timestamp() as u64,
+                },
+                metadata: None,
+            };
+            tx.id = tx.compute_id();
+            parent = dag.add_transaction(tx).await.unwrap();
+            ids.push(parent.clone());
+        }
+        dag.transactions.get_mut(&ids[2]).unwrap().status = NodeStatus::Rejected;
+
+        // Pending ancestors are finalized first; the finalized genesis is skipped
+        assert_eq!(dag.finalize(&ids[1..2]), ids[..2]);
+        assert_eq!(dag.transactions[&ids[1]].status, NodeStatus::Finalized);
+        assert!(dag.finalize(&ids[1..]).is_empty());
+        assert_eq!(dag.transactions[&ids[2]].status, NodeStatus::Rejected);
+    }
+}
@@ -0,0 +1,316 @@
+//! Orphan transactions
+//!
+//! Peers relay transactions in whatever order they receive them, so a
+//! transaction can arrive before one of its parents. Instead of failing it
+//! with `ParentNotFound`, `DAGCore::add_or_hold` keeps it in the orphan pool
+//! until every missing parent has been added, then inserts it with full
+//! validation. Orphans whose parents never turn up are dropped after
+//! `ttl_secs`, and when the pool is full the oldest orphan makes room.
+//! Orphans are held in memory only; after a restart peers relay them again.
+
+use super::{CoreError, DAGCore, Transaction};
+use crate::{BlockchainError, TransactionId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// How many orphans are held and for how long
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrphanConfig {
+    pub max_orphans: usize,
+    /// Seconds an orphan waits for its parents before it is dropped
+    pub ttl_secs: u64,
+}
+
+impl Default for OrphanConfig {
+    fn default() -> Self {
+        Self {
+            max_orphans: 1024,
+            ttl_secs: 300,
+        }
+    }
+}
+
+/// Orphan pool counters
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanStats {
+    pub orphans: usize,
+    /// Distinct parents the orphans are waiting on
+    pub missing_parents: usize,
+    /// Orphans released since startup because their parents arrived
+    pub released: u64,
+    /// Orphans dropped since startup because they timed out or the pool was full
+    pub dropped: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Orphan {
+    transaction: Transaction,
+    missing: HashSet<TransactionId>,
+    received_at: u64,
+}
+
+/// Transactions waiting for their parents
+#[derive(Debug, Default)]
+pub struct OrphanPool {
+    config: OrphanConfig,
+    orphans: HashMap<TransactionId, Orphan>,
+    /// Orphans waiting on each missing parent
+    waiting: HashMap<TransactionId, HashSet<TransactionId>>,
+    released: u64,
+    dropped: u64,
+}
+
+impl OrphanPool {
+    pub fn new(config: OrphanConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    pub fn config(&self) -> &OrphanConfig {
+        &self.config
+    }
+
+    /// Replace the limits, dropping the oldest orphans beyond the new maximum
+    pub fn set_config(&mut self, config: OrphanConfig) {
+        self.config = config;
+        while self.orphans.len() > self.config.max_orphans {
+            self.drop_oldest();
+        }
+    }
+
+    pub fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.orphans.contains_key(tx_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.orphans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orphans.is_empty()
+    }
+
+    /// Hold `transaction` until its `missing` parents are added
+    ///
+    /// Returns false if it is already held or the pool holds nothing.
+    pub fn hold(&mut self, transaction: Transaction, missing: HashSet<TransactionId>, now: u64) -> bool {
+        if self.config.max_orphans == 0 || self.contains(&transaction.id) {
+            return false;
+        }
+        while self.orphans.len() >= self.config.max_orphans {
+            self.drop_oldest();
+        }
+
+        let tx_id = transaction.id.clone();
+        for parent in &missing {
+            self.waiting.entry(parent.clone()).or_default().insert(tx_id.clone());
+        }
+        self.orphans.insert(tx_id, Orphan { transaction, missing, received_at: now });
+        true
+    }
+
+    /// Record that `parent` was added to the DAG
+    ///
+    /// Returns the orphans no longer missing any parent, oldest first.
+    pub fn parent_added(&mut self, parent: &TransactionId) -> Vec<Transaction> {
+        let Some(waiting) = self.waiting.remove(parent) else {
+            return Vec::new();
+        };
+        let mut ready: Vec<Orphan> = Vec::new();
+        for tx_id in waiting {
+            let complete = self.orphans.get_mut(&tx_id).map_or(false, |orphan| {
+                orphan.missing.remove(parent);
+                orphan.missing.is_empty()
+            });
+            if complete {
+                ready.extend(self.orphans.remove(&tx_id));
+            }
+        }
+        ready.sort_by_key(|orphan| (orphan.received_at, orphan.transaction.id.as_string()));
+        self.released += ready.len() as u64;
+        ready.into_iter().map(|orphan| orphan.transaction).collect()
+    }
+
+    /// Drop orphans that have waited `ttl_secs` at `now`
+    pub fn expire(&mut self, now: u64) -> Vec<TransactionId> {
+        let ttl = self.config.ttl_secs;
+        let mut expired: Vec<TransactionId> = self.orphans.iter()
+            .filter(|(_, orphan)| orphan.received_at.saturating_add(ttl) <= now)
+            .map(|(tx_id, _)| tx_id.clone())
+            .collect();
+        expired.sort_by_key(|tx_id| tx_id.as_string());
+        for tx_id in &expired {
+            self.remove(tx_id);
+        }
+        expired
+    }
+
+    pub fn stats(&self) -> OrphanStats {
+        OrphanStats {
+            orphans: self.orphans.len(),
+            missing_parents: self.waiting.len(),
+            released: self.released,
+            dropped: self.dropped,
+        }
+    }
+
+    fn drop_oldest(&mut self) {
+        let oldest = self.orphans.iter()
+            .min_by_key(|(tx_id, orphan)| (orphan.received_at, tx_id.as_string()))
+            .map(|(tx_id, _)| tx_id.clone());
+        if let Some(tx_id) = oldest {
+            log::debug!("👶 Orphan pool full, dropping {}", tx_id);
+            self.remove(&tx_id);
+        }
+    }
+
+    fn remove(&mut self, tx_id: &TransactionId) {
+        let Some(orphan) = self.orphans.remove(tx_id) else {
+            return;
+        };
+        for parent in &orphan.missing {
+            if let Some(waiting) = self.waiting.get_mut(parent) {
+                waiting.remove(tx_id);
+                if waiting.is_empty() {
+                    self.waiting.remove(parent);
+                }
+            }
+        }
+        self.dropped += 1;
+    }
+}
+
+impl DAGCore {
+    /// Add a relayed transaction, holding it as an orphan while parents are missing
+    ///
+    /// Returns the transactions added to the DAG, parents first: the
+    /// transaction itself followed by any orphans that were waiting on it.
+    /// Nothing is returned while it is held.
+    pub async fn add_or_hold(&mut self, transaction: Transaction, now: u64) -> Result<Vec<Transaction>, BlockchainError> {
+        self.expire_orphans(now);
+        if self.orphans.contains(&transaction.id) {
+            return Err(BlockchainError::Core(CoreError::TransactionExists(transaction.id.clone())));
+        }
+        if self.use_persistence {
+            self.page_in(&transaction.parents).await?;
+        }
+
+        let missing: HashSet<TransactionId> = transaction.parents.iter()
+            .filter(|parent| !self.transactions.contains_key(*parent))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            // Checks that do not need the parents run now, so junk is not held
+            self.limits.check(&transaction)?;
+            if !transaction.has_valid_id() {
+                return Err(BlockchainError::Core(CoreError::IdMismatch(transaction.id.clone())));
+            }
+            log::debug!("👶 Holding orphan {} until {} missing parent(s) arrive", transaction.id, missing.len());
+            self.orphans.hold(transaction, missing, now);
+            return Ok(Vec::new());
+        }
+
+        let tx_id = self.add_transaction(transaction.clone()).await?;
+        let mut added = vec![transaction];
+        added.extend(self.adopt_orphans(&[tx_id]).await);
+        Ok(added)
+    }
+
+    /// Add the orphans waiting on `parents`, and the orphans waiting on those
+    ///
+    /// Call after adding transactions by any other means. An orphan that
+    /// fails validation once its parents are present is dropped. Returns the
+    /// orphans added, parents first.
+    pub async fn adopt_orphans(&mut self, parents: &[TransactionId]) -> Vec<Transaction> {
+        let mut queue: VecDeque<TransactionId> = parents.iter().cloned().collect();
+        let mut adopted = Vec::new();
+        while let Some(parent) = queue.pop_front() {
+            for orphan in self.orphans.parent_added(&parent) {
+                match self.add_transaction(orphan.clone()).await {
+                    Ok(tx_id) => {
+                        log::debug!("👶 Adopted orphan {} after parent {} arrived", tx_id, parent);
+                        queue.push_back(tx_id);
+                        adopted.push(orphan);
+                    }
+                    Err(e) => log::warn!("👶 Dropped orphan {}: {}", orphan.id, e),
+                }
+            }
+        }
+        adopted
+    }
+
+    /// Drop orphans whose parents did not arrive in time
+    pub fn expire_orphans(&mut self, now: u64) -> Vec<TransactionId> {
+        let expired = self.orphans.expire(now);
+        for tx_id in &expired {
+            log::info!("⌛ Dropped orphan {}: parents did not arrive within {}s", tx_id, self.orphans.config().ttl_secs);
+        }
+        expired
+    }
+
+    /// Replace how many orphans are held and for how long
+    pub fn set_orphan_config(&mut self, config: OrphanConfig) {
+        self.orphans.set_config(config);
+    }
+
+    pub fn orphan_stats(&self) -> OrphanStats {
+        self.orphans.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents,
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[test]
+    fn test_orphans_wait_for_every_parent() {
+        let mut pool = OrphanPool::new(OrphanConfig::default());
+        let (a, b) = (TransactionId::new(), TransactionId::new());
+        let orphan = transaction(1, vec![a.clone(), b.clone()]);
+        assert!(pool.hold(orphan.clone(), HashSet::from([a.clone(), b.clone()]), 100));
+        assert!(!pool.hold(orphan.clone(), HashSet::from([a.clone()]), 100));
+        assert_eq!(pool.stats().missing_parents, 2);
+
+        assert!(pool.parent_added(&a).is_empty());
+        assert_eq!(pool.parent_added(&b), vec![orphan]);
+        assert!(pool.is_empty());
+        assert_eq!(pool.stats(), OrphanStats { orphans: 0, missing_parents: 0, released: 1, dropped: 0 });
+    }
+
+    #[test]
+    fn test_orphans_expire_and_make_room() {
+        let mut pool = OrphanPool::new(OrphanConfig { max_orphans: 2, ttl_secs: 60 });
+        let parent = TransactionId::new();
+        let (first, second, third) = (transaction(1, vec![parent.clone()]), transaction(2, vec![parent.clone()]), transaction(3, vec![parent.clone()]));
+        pool.hold(first.clone(), HashSet::from([parent.clone()]), 100);
+        pool.hold(second.clone(), HashSet::from([parent.clone()]), 110);
+        // A full pool drops its oldest orphan
+        pool.hold(third.clone(), HashSet::from([parent.clone()]), 120);
+        assert!(!pool.contains(&first.id));
+
+        assert_eq!(pool.expire(169), Vec::<TransactionId>::new());
+        assert_eq!(pool.expire(170), vec![second.id.clone()]);
+        assert_eq!(pool.parent_added(&parent), vec![third]);
+        assert_eq!(pool.stats().dropped, 2);
+    }
+}
@@ -0,0 +1,343 @@
+//! Paged DAG loading
+//!
+//! Startup loads only the genesis transaction, the current tips and the most
+//! recent `recent_window` transactions into memory. Older transactions stay
+//! in storage and are fetched when something asks for them: lookups go
+//! through a least-recently-used cache bounded by `cache_size_mb`, and a new
+//! transaction approving an old parent pages that parent into memory so the
+//! parent-child links stay complete.
+//!
+//! Weights, depths and inclusion proofs are computed over the in-memory part
+//! of the DAG only. Double spends are checked against the loaded window, so a
+//! replay of a spend older than the window is caught by account balances
+//! rather than the conflict tracker.
+
+use super::{DAGCore, DAGNode, NodeStatus};
+use crate::{BlockchainError, TransactionId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex as StdMutex;
+
+/// Fixed per-node overhead added to the serialized size when accounting cache usage
+const NODE_OVERHEAD_BYTES: usize = 128;
+
+/// What to load at startup and how much to cache afterwards
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PagingConfig {
+    /// Most recent transactions loaded at startup, besides tips and genesis
+    pub recent_window: usize,
+    /// Memory budget for transactions fetched on demand
+    pub cache_size_mb: u64,
+}
+
+impl Default for PagingConfig {
+    fn default() -> Self {
+        Self {
+            recent_window: 10_000,
+            cache_size_mb: 64,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<TransactionId, (DAGNode, usize, u64)>,
+    /// Access tick to ID, oldest first
+    order: BTreeMap<u64, TransactionId>,
+    used_bytes: usize,
+    tick: u64,
+}
+
+/// Least-recently-used cache of nodes fetched from storage, bounded in bytes
+#[derive(Debug)]
+pub struct NodeCache {
+    capacity_bytes: usize,
+    state: StdMutex<CacheState>,
+}
+
+impl NodeCache {
+    pub fn new(capacity_bytes: usize) -> Self {
+        Self {
+            capacity_bytes,
+            state: StdMutex::new(CacheState::default()),
+        }
+    }
+
+    /// Cache holding up to `cache_size_mb` megabytes
+    pub fn with_megabytes(cache_size_mb: u64) -> Self {
+        Self::new((cache_size_mb as usize).saturating_mul(1024 * 1024))
+    }
+
+    /// Cached node, marking it most recently used
+    pub fn get(&self, tx_id: &TransactionId) -> Option<DAGNode> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tick += 1;
+        let tick = state.tick;
+        let (node, _, last_used) = state.entries.get_mut(tx_id)?;
+        let node = node.clone();
+        let previous = std::mem::replace(last_used, tick);
+        state.order.remove(&previous);
+        state.order.insert(tick, tx_id.clone());
+        Some(node)
+    }
+
+    /// Cache `node`, evicting the least recently used nodes past capacity
+    ///
+    /// A node larger than the whole cache is not kept.
+    pub fn insert(&self, node: DAGNode) {
+        let size = estimated_size(&node);
+        if size > self.capacity_bytes {
+            return;
+        }
+        let tx_id = node.transaction.id.clone();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Self::remove_locked(&mut state, &tx_id);
+        while state.used_bytes + size > self.capacity_bytes {
+            let Some((_, oldest)) = state.order.pop_first() else {
+                break;
+            };
+            if let Some((_, evicted, _)) = state.entries.remove(&oldest) {
+                state.used_bytes -= evicted;
+            }
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.order.insert(tick, tx_id.clone());
+        state.entries.insert(tx_id, (node, size, tick));
+        state.used_bytes += size;
+    }
+
+    /// Drop a node, returning it if it was cached
+    pub fn remove(&self, tx_id: &TransactionId) -> Option<DAGNode> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Self::remove_locked(&mut state, tx_id)
+    }
+
+    fn remove_locked(state: &mut CacheState, tx_id: &TransactionId) -> Option<DAGNode> {
+        let (node, size, tick) = state.entries.remove(tx_id)?;
+        state.order.remove(&tick);
+        state.used_bytes -= size;
+        Some(node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes currently accounted to cached nodes
+    pub fn used_bytes(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).used_bytes
+    }
+}
+
+/// Approximate memory held by a node
+fn estimated_size(node: &DAGNode) -> usize {
+    let transaction = bincode::serialized_size(&node.transaction).map(|s| s as usize).unwrap_or(0);
+    transaction + node.children.len() * std::mem::size_of::<TransactionId>() + NODE_OVERHEAD_BYTES
+}
+
+impl DAGCore {
+    /// Load genesis, tips and the most recent transactions from storage
+    pub(crate) async fn load_recent(&mut self, config: &PagingConfig) -> Result<(), BlockchainError> {
+        let mut loaded = HashMap::new();
+
+        for node in self.database.get_dag_tips().await? {
+            loaded.insert(node.transaction.id.clone(), node);
+        }
+        for transaction in self.database.get_transactions(Some(config.recent_window), None, None).await? {
+            if !loaded.contains_key(&transaction.id) {
+                let node = self.stored_node(transaction).await?;
+                loaded.insert(node.transaction.id.clone(), node);
+            }
+        }
+        if let Some(genesis) = self.database.get_genesis_transaction().await? {
+            self.genesis = Some(genesis.id.clone());
+            if !loaded.contains_key(&genesis.id) {
+                let node = self.stored_node(genesis).await?;
+                loaded.insert(node.transaction.id.clone(), node);
+            }
+        }
+
+        self.transactions = loaded;
+        self.transaction_count = self.database.get_transaction_count().await?;
+        self.addresses.clear();
+        for node in self.transactions.values() {
+            self.addresses.insert(&node.transaction);
+        }
+        self.weights.clear();
+        self.conflicts = super::ConflictTracker::from_nodes(&self.transactions);
+        self.tips = self.transactions.iter()
+            .filter(|(_, node)| node.status == NodeStatus::Pending)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        log::info!(
+            "📦 Loaded {} of {} transaction(s) into memory, older ones load on demand",
+            self.transactions.len(),
+            self.transaction_count,
+        );
+        Ok(())
+    }
+
+    /// Nodes currently held in memory: genesis, the recent window and tips
+    pub fn loaded_nodes(&self) -> impl Iterator<Item = &DAGNode> {
+        self.transactions.values()
+    }
+
+    /// Stored DAG node for `transaction`, or a pending node if none was stored
+    async fn stored_node(&self, transaction: super::Transaction) -> Result<DAGNode, BlockchainError> {
+        if let Some(node) = self.database.get_dag_node(&transaction.id).await? {
+            return Ok(node);
+        }
+        Ok(DAGNode {
+            children: Vec::new(),
+            weight: self.calculate_initial_weight(&transaction),
+            confidence: 0.0,
+            status: NodeStatus::Pending,
+            quantum_score: transaction.quantum_proof.resistance_score,
+            transaction,
+        })
+    }
+
+    /// Node by ID from memory, the cache, or storage
+    ///
+    /// Nodes read from storage are cached but not added to the in-memory DAG.
+    pub async fn fetch_node(&self, tx_id: &TransactionId) -> Result<Option<DAGNode>, BlockchainError> {
+        if let Some(node) = self.transactions.get(tx_id) {
+            return Ok(Some(node.clone()));
+        }
+        if let Some(node) = self.cold.get(tx_id) {
+            return Ok(Some(node));
+        }
+        if !self.use_persistence {
+            return Ok(None);
+        }
+        let Some(transaction) = self.database.get_transaction(tx_id).await? else {
+            return Ok(None);
+        };
+        let node = self.stored_node(transaction).await?;
+        self.cold.insert(node.clone());
+        Ok(Some(node))
+    }
+
+    /// Move stored nodes for `ids` into the in-memory DAG, skipping unknown IDs
+    pub(crate) async fn page_in(&mut self, ids: &[TransactionId]) -> Result<(), BlockchainError> {
+        for tx_id in ids {
+            if self.transactions.contains_key(tx_id) {
+                continue;
+            }
+            let node = self.fetch_node(tx_id).await?;
+            self.cold.remove(tx_id);
+            if let Some(node) = node {
+                log::debug!("Paged in transaction {} from storage", tx_id);
+                self.addresses.insert(&node.transaction);
+                self.transactions.insert(tx_id.clone(), node);
+            }
+        }
+        Ok(())
+    }
+
+    /// Transactions fetched from storage and held outside the in-memory DAG
+    pub fn node_cache(&self) -> &NodeCache {
+        &self.cold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{QuantumProof, Transaction};
+    use crate::storage::{DatabaseConfig, DatabaseManager};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn node(nonce: u64, parents: Vec<TransactionId>) -> DAGNode {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 0,
+            nonce,
+            timestamp: 1_000 + nonce,
+            parents,
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        DAGNode {
+            transaction,
+            children: vec![],
+            weight: 1,
+            confidence: 1.0,
+            status: NodeStatus::Finalized,
+            quantum_score: 80,
+        }
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let a = node(1, vec![]);
+        let b = node(2, vec![]);
+        let c = node(3, vec![]);
+        let cache = NodeCache::new(estimated_size(&a) * 2);
+
+        cache.insert(a.clone());
+        cache.insert(b.clone());
+        assert!(cache.get(&a.transaction.id).is_some());
+        cache.insert(c.clone());
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&b.transaction.id).is_none());
+        assert!(cache.get(&a.transaction.id).is_some());
+        assert!(cache.get(&c.transaction.id).is_some());
+        assert!(cache.used_bytes() <= estimated_size(&a) * 2);
+    }
+
+    #[tokio::test]
+    async fn test_startup_loads_recent_window_and_pages_in_older() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            max_connections: 5,
+            retention: Default::default(),
+        }).await.unwrap());
+        let genesis = DAGCore::new_with_database(database.clone()).await.unwrap().genesis_id().unwrap().clone();
+
+        let mut parent = genesis.clone();
+        let mut chain = Vec::new();
+        for nonce in 1..=5 {
+            let mut stored = node(nonce, vec![parent.clone()]);
+            stored.status = if nonce == 5 { NodeStatus::Pending } else { NodeStatus::Finalized };
+            database.store_transaction(&stored.transaction).await.unwrap();
+            database.store_dag_node(&stored).await.unwrap();
+            parent = stored.transaction.id.clone();
+            chain.push(parent.clone());
+        }
+
+        let config = PagingConfig { recent_window: 2, cache_size_mb: 1 };
+        let mut dag = DAGCore::new_with_paging(database, &config).await.unwrap();
+        assert_eq!(dag.genesis_id(), Some(&genesis));
+        assert!(dag.get_node(&genesis).is_some());
+        assert!(dag.get_node(&chain[4]).is_some());
+        assert!(dag.get_node(&chain[0]).is_none());
+        assert_eq!(dag.transaction_count(), 6);
+
+        // Older transactions are served from storage through the cache
+        let old = dag.fetch_node(&chain[0]).await.unwrap().unwrap();
+        assert_eq!(old.transaction.id, chain[0]);
+        assert_eq!(dag.node_cache().len(), 1);
+        assert!(dag.get_node(&chain[0]).is_none());
+
+        dag.page_in(&[chain[0].clone()]).await.unwrap();
+        assert!(dag.get_node(&chain[0]).is_some());
+        assert!(dag.node_cache().is_empty());
+    }
+}
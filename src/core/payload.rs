@@ -0,0 +1,757 @@
+//! Typed transaction payloads
+//!
+//! A transaction moves `amount` from sender to receiver. It can also carry a
+//! `TransactionPayload` under the `payload` key of its JSON metadata, which
+//! says what else happens when it finalizes: deploying or calling a
+//! contract, bonding stake to a validator, voting on a governance proposal,
+//! issuing or moving a token, taking a step in an atomic swap, or anchoring
+//! an archive bundle.
+//! Transactions without one are plain transfers, so existing transactions
+//! and IDs are unchanged.
+//!
+//! Payloads are validated with the rest of the transaction when it is added
+//! to the DAG. `PayloadRouter` hands finalized payloads to the contract
+//! engine, the stake ledger, the governance service and the token, swap
+//! and archive ledgers in storage. Token and swap payloads carry no amount; the assets
+//! they move are taken from storage as they execute. Balances are applied
+//! separately by `AccountStateHandler`, so a payload that fails to execute
+//! does not undo the transfer or the fee.
+
+use super::{account_address, amount, swap_hashlock, Amount, Asset, AtomicSwap, CoreError, NodeStatus, SwapLeg, SwapStatus, Transaction};
+use crate::consensus::StakeLedger;
+use crate::contracts::{ContractEngine, ContractEvent, ContractId, ContractMetadata};
+use crate::events::{EventHandler, NodeEvent};
+use crate::governance::proposals::{ProposalId, VoteType};
+use crate::governance::GovernanceService;
+use crate::storage::{ArchiveAnchor, DatabaseManager, ReceiptStatus, TransactionReceipt};
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+
+/// Metadata key holding a transaction's payload
+pub const PAYLOAD_METADATA_KEY: &str = "payload";
+
+/// Largest contract accepted in a deploy payload
+pub const MAX_CONTRACT_CODE_BYTES: usize = 64 * 1024;
+
+/// What a transaction does besides moving its amount
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TransactionPayload {
+    /// Plain value transfer
+    #[default]
+    Transfer,
+    /// Deploy a contract owned by the sender
+    ContractDeploy {
+        /// Hex contract code
+        code: String,
+        name: String,
+        version: String,
+        gas_limit: u64,
+    },
+    /// Call a contract function; the amount is passed as the call value
+    ContractCall {
+        contract_id: String,
+        function: String,
+        /// Hex call input
+        #[serde(default)]
+        input: String,
+        gas_limit: u64,
+    },
+    /// Bond the amount to a validator
+    Stake { validator_id: String },
+    /// Vote on a governance proposal as the sender's address
+    GovernanceVote {
+        proposal_id: ProposalId,
+        vote: VoteType,
+        #[serde(default)]
+        justification: Option<String>,
+    },
+    /// Issue a token whose whole supply goes to the sender; the token takes
+    /// this transaction's ID
+    TokenIssue {
+        symbol: String,
+        #[serde(with = "amount")]
+        supply: Amount,
+    },
+    /// Move tokens to the receiver
+    TokenTransfer {
+        token_id: String,
+        #[serde(with = "amount")]
+        amount: Amount,
+    },
+    /// Lock `amount` of `asset` in a swap with the receiver, who must lock
+    /// `counter_amount` of `counter_asset` in return
+    SwapInitiate {
+        /// Hex SHA3-256 of the secret
+        hashlock: String,
+        asset: Asset,
+        #[serde(with = "amount")]
+        amount: Amount,
+        counter_asset: Asset,
+        #[serde(with = "amount")]
+        counter_amount: Amount,
+        /// Unix time from which the swap can only be refunded
+        expires_at: u64,
+    },
+    /// Lock the sender's leg of a swap it is the counterparty to
+    SwapParticipate { hashlock: String },
+    /// Reveal a swap's secret, settling both legs
+    SwapClaim {
+        hashlock: String,
+        /// Hex secret
+        preimage: String,
+    },
+    /// Refund an expired swap
+    SwapRefund { hashlock: String },
+    /// Anchor an archive bundle of finalized heights in cold storage
+    ArchiveAnchor {
+        /// Hex SHA3-256 of the bundle
+        content_id: String,
+        from_height: u64,
+        to_height: u64,
+        manifest_root: String,
+    },
+}
+
+impl TransactionPayload {
+    /// Payload in a transaction's JSON metadata
+    ///
+    /// Metadata that is not a JSON object, or has no payload key, is a plain
+    /// transfer. A payload key that does not parse is an error.
+    pub fn from_transaction(transaction: &Transaction) -> Result<Self, CoreError> {
+        let Some(metadata) = &transaction.metadata else {
+            return Ok(Self::Transfer);
+        };
+        let Ok(serde_json::Value::Object(mut fields)) = serde_json::from_slice::<serde_json::Value>(metadata) else {
+            return Ok(Self::Transfer);
+        };
+        match fields.remove(PAYLOAD_METADATA_KEY) {
+            Some(value) => serde_json::from_value(value).map_err(|e| CoreError::InvalidPayload(e.to_string())),
+            None => Ok(Self::Transfer),
+        }
+    }
+
+    /// Store this payload in a transaction's metadata
+    ///
+    /// Other metadata keys are kept. Fails if the existing metadata is not a
+    /// JSON object. The transaction ID must be recomputed afterwards.
+    pub fn attach(&self, transaction: &mut Transaction) -> Result<(), CoreError> {
+        if matches!(self, Self::Transfer) {
+            return Ok(());
+        }
+        let mut fields = match &transaction.metadata {
+            None => serde_json::Map::new(),
+            Some(metadata) => match serde_json::from_slice::<serde_json::Value>(metadata) {
+                Ok(serde_json::Value::Object(fields)) => fields,
+                _ => return Err(CoreError::InvalidPayload("metadata is not a JSON object".to_string())),
+            },
+        };
+        let value = serde_json::to_value(self).map_err(|e| CoreError::Serialization(e.to_string()))?;
+        fields.insert(PAYLOAD_METADATA_KEY.to_string(), value);
+        transaction.metadata = Some(serde_json::Value::Object(fields).to_string().into_bytes());
+        Ok(())
+    }
+
+    /// Short name of the payload kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Transfer => "transfer",
+            Self::ContractDeploy { .. } => "contract_deploy",
+            Self::ContractCall { .. } => "contract_call",
+            Self::Stake { .. } => "stake",
+            Self::GovernanceVote { .. } => "governance_vote",
+            Self::TokenIssue { .. } => "token_issue",
+            Self::TokenTransfer { .. } => "token_transfer",
+            Self::SwapInitiate { .. } => "swap_initiate",
+            Self::SwapParticipate { .. } => "swap_participate",
+            Self::SwapClaim { .. } => "swap_claim",
+            Self::SwapRefund { .. } => "swap_refund",
+            Self::ArchiveAnchor { .. } => "archive_anchor",
+        }
+    }
+
+    /// Check the payload against the transaction carrying it
+    pub fn validate(&self, transaction: &Transaction) -> Result<(), CoreError> {
+        let invalid = |reason: &str| Err(CoreError::InvalidPayload(format!("{}: {}", self.kind(), reason)));
+        let is_hash = |hashlock: &str| hashlock.len() == 64 && hex::decode(hashlock).is_ok();
+        let moves_assets = matches!(
+            self,
+            Self::TokenIssue { .. } | Self::TokenTransfer { .. } | Self::SwapInitiate { .. }
+                | Self::SwapParticipate { .. } | Self::SwapClaim { .. } | Self::SwapRefund { .. }
+        );
+        if moves_assets && transaction.amount != 0 {
+            return invalid("assets are moved by the payload, not the amount");
+        }
+        match self {
+            Self::Transfer => Ok(()),
+            Self::ContractDeploy { code, name, gas_limit, .. } => {
+                let Ok(code) = hex::decode(code) else {
+                    return invalid("code is not hex");
+                };
+                if code.is_empty() || code.len() > MAX_CONTRACT_CODE_BYTES {
+                    return invalid("code must be 1 to 65536 bytes");
+                }
+                if name.trim().is_empty() {
+                    return invalid("contract name is empty");
+                }
+                if *gas_limit == 0 {
+                    return invalid("gas limit is zero");
+                }
+                Ok(())
+            }
+            Self::ContractCall { contract_id, function, input, gas_limit } => {
+                if contract_id.is_empty() || function.is_empty() {
+                    return invalid("contract and function are required");
+                }
+                if hex::decode(input).is_err() {
+                    return invalid("input is not hex");
+                }
+                if *gas_limit == 0 {
+                    return invalid("gas limit is zero");
+                }
+                Ok(())
+            }
+            Self::Stake { validator_id } => {
+                if validator_id.is_empty() {
+                    return invalid("validator is required");
+                }
+                if transaction.amount == 0 {
+                    return invalid("nothing to bond");
+                }
+                Ok(())
+            }
+            Self::GovernanceVote { proposal_id, .. } => {
+                if proposal_id.is_empty() {
+                    return invalid("proposal is required");
+                }
+                if transaction.amount != 0 {
+                    return invalid("votes carry no amount");
+                }
+                Ok(())
+            }
+            Self::TokenIssue { symbol, supply } => {
+                if symbol.trim().is_empty() || symbol.len() > 12 {
+                    return invalid("symbol must be 1 to 12 characters");
+                }
+                if *supply == 0 {
+                    return invalid("supply is zero");
+                }
+                Ok(())
+            }
+            Self::TokenTransfer { token_id, amount } => {
+                if token_id.is_empty() {
+                    return invalid("token is required");
+                }
+                if *amount == 0 {
+                    return invalid("nothing to transfer");
+                }
+                Ok(())
+            }
+            Self::SwapInitiate { hashlock, asset, amount, counter_asset, counter_amount, expires_at } => {
+                if !is_hash(hashlock) {
+                    return invalid("hashlock must be a hex SHA3-256 hash");
+                }
+                if asset == counter_asset {
+                    return invalid("both legs trade the same asset");
+                }
+                if *amount == 0 || *counter_amount == 0 {
+                    return invalid("both legs need an amount");
+                }
+                if *expires_at <= transaction.timestamp {
+                    return invalid("swap expires before it starts");
+                }
+                if transaction.sender == transaction.receiver {
+                    return invalid("cannot swap with yourself");
+                }
+                Ok(())
+            }
+            Self::SwapParticipate { hashlock } | Self::SwapRefund { hashlock } => {
+                if !is_hash(hashlock) {
+                    return invalid("hashlock must be a hex SHA3-256 hash");
+                }
+                Ok(())
+            }
+            Self::SwapClaim { hashlock, preimage } => {
+                let Ok(secret) = hex::decode(preimage) else {
+                    return invalid("secret is not hex");
+                };
+                if swap_hashlock(&secret) != *hashlock {
+                    return invalid("secret does not match the hashlock");
+                }
+                Ok(())
+            }
+            Self::ArchiveAnchor { content_id, from_height, to_height, manifest_root } => {
+                if !is_hash(content_id) || !is_hash(manifest_root) {
+                    return invalid("content ID and manifest root must be hex SHA3-256 hashes");
+                }
+                if from_height > to_height {
+                    return invalid("height range is empty");
+                }
+                if transaction.amount != 0 {
+                    return invalid("anchors carry no amount");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Parse and validate a transaction's payload
+pub fn validate_payload(transaction: &Transaction) -> Result<(), CoreError> {
+    TransactionPayload::from_transaction(transaction)?.validate(transaction)
+}
+
+/// Routes the payloads of finalized transactions to the engines that execute them
+pub struct PayloadRouter {
+    database: Arc<DatabaseManager>,
+    contracts: Arc<RwLock<ContractEngine>>,
+    stakes: Arc<StakeLedger>,
+    governance: Arc<RwLock<Option<Arc<GovernanceService>>>>,
+    /// Transactions already routed, so a replayed finalization runs nothing twice
+    routed: Mutex<HashSet<TransactionId>>,
+    /// Outcomes waiting for the `FinalityAdvanced` event that gives their height
+    executed: Mutex<HashMap<TransactionId, PayloadOutcome>>,
+}
+
+impl PayloadRouter {
+    pub fn new(
+        database: Arc<DatabaseManager>,
+        contracts: Arc<RwLock<ContractEngine>>,
+        stakes: Arc<StakeLedger>,
+        governance: Arc<RwLock<Option<Arc<GovernanceService>>>>,
+    ) -> Self {
+        Self {
+            database,
+            contracts,
+            stakes,
+            governance,
+            routed: Mutex::new(HashSet::new()),
+            executed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Execute a finalized transaction's payload
+    ///
+    /// Returns `None` if it was already routed.
+    pub async fn route(&self, transaction: &Transaction) -> Option<PayloadOutcome> {
+        if !self.routed.lock().unwrap_or_else(|e| e.into_inner()).insert(transaction.id.clone()) {
+            return None;
+        }
+        let payload = match TransactionPayload::from_transaction(transaction) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::error!("❌ Finalized transaction {} has an invalid payload: {}", transaction.id, e);
+                return Some(PayloadOutcome::failed("invalid", e));
+            }
+        };
+        let kind = payload.kind();
+        let sender = account_address(&transaction.sender);
+
+        let outcome = match payload {
+            TransactionPayload::Transfer => PayloadOutcome::succeeded(kind),
+            TransactionPayload::ContractDeploy { code, name, version, gas_limit } => {
+                let metadata = ContractMetadata {
+                    name,
+                    version,
+                    description: format!("Deployed by transaction {}", transaction.id),
+                    gas_limit,
+                };
+                let code = hex::decode(code).unwrap_or_default();
+                match self.contracts.write().await.deploy_contract(code, transaction.sender.clone(), metadata).await {
+                    Ok(contract_id) => {
+                        log::info!("📝 Transaction {} deployed contract {}", transaction.id, contract_id.as_str());
+                        PayloadOutcome {
+                            output: contract_id.as_str().as_bytes().to_vec(),
+                            contract_id: Some(contract_id.as_str().to_string()),
+                            ..PayloadOutcome::succeeded(kind)
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("❌ Contract deploy in transaction {} failed: {}", transaction.id, e);
+                        PayloadOutcome::failed(kind, e)
+                    }
+                }
+            }
+            TransactionPayload::ContractCall { contract_id, function, input, gas_limit } => {
+                let input = hex::decode(input).unwrap_or_default();
+                let result = self.contracts.write().await.execute_contract(
+                    &ContractId::new(contract_id.clone()),
+                    &function,
+                    input,
+                    transaction.sender.clone(),
+                    transaction.amount,
+                    gas_limit,
+                ).await;
+                match result {
+                    Ok(result) => {
+                        if result.success {
+                            log::debug!("📜 Transaction {} called {}::{} using {} gas", transaction.id, contract_id, function, result.gas_used);
+                        } else {
+                            log::warn!(
+                                "⚠️ Call to {}::{} in transaction {} failed: {}",
+                                contract_id, function, transaction.id, result.error.clone().unwrap_or_default()
+                            );
+                        }
+                        PayloadOutcome {
+                            kind,
+                            contract_id: Some(contract_id),
+                            success: result.success,
+                            gas_used: result.gas_used,
+                            output: result.output,
+                            events: result.events,
+                            error: result.error,
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("❌ Contract call in transaction {} failed: {}", transaction.id, e);
+                        PayloadOutcome { contract_id: Some(contract_id), ..PayloadOutcome::failed(kind, e) }
+                    }
+                }
+            }
+            TransactionPayload::Stake { validator_id } => {
+                let bonded = self.stakes.bond(&validator_id, &sender, transaction.amount);
+                log::info!("🔒 {} bonded {} to {} ({} bonded in total)", sender, transaction.amount, validator_id, bonded);
+                PayloadOutcome::succeeded(kind)
+            }
+            TransactionPayload::GovernanceVote { proposal_id, vote, justification } => {
+                let Some(governance) = self.governance.read().await.clone() else {
+                    log::warn!("⚠️ Governance vote in transaction {} ignored; no governance service is running", transaction.id);
+                    return Some(PayloadOutcome::failed(kind, "no governance service is running"));
+                };
+                let result = governance.cast_vote(&proposal_id, sender, vote, justification).await;
+                PayloadOutcome::settle(kind, result, "Governance vote", &transaction.id)
+            }
+            TransactionPayload::TokenIssue { symbol, supply } => {
+                let result = self.database.issue_token(&transaction.id, &sender, &symbol, supply).await;
+                PayloadOutcome::settle(kind, result, "Token issue", &transaction.id)
+            }
+            TransactionPayload::TokenTransfer { token_id, amount } => {
+                let receiver = account_address(&transaction.receiver);
+                let result = self.database.transfer_token(&token_id, &sender, &receiver, amount).await;
+                PayloadOutcome::settle(kind, result, "Token transfer", &transaction.id)
+            }
+            TransactionPayload::SwapInitiate { hashlock, asset, amount, counter_asset, counter_amount, expires_at } => {
+                let swap = AtomicSwap {
+                    hashlock,
+                    initiator: SwapLeg { party: sender, asset, amount },
+                    counterparty: SwapLeg {
+                        party: account_address(&transaction.receiver),
+                        asset: counter_asset,
+                        amount: counter_amount,
+                    },
+                    expires_at,
+                    status: SwapStatus::Initiated,
+                    preimage: None,
+                    initiated_by: transaction.id.clone(),
+                    updated_at: transaction.timestamp,
+                };
+                let result = self.database.initiate_swap(&swap).await;
+                PayloadOutcome::settle(kind, result, "Swap initiation", &transaction.id)
+            }
+            TransactionPayload::SwapParticipate { hashlock } => {
+                let result = self.database.participate_swap(&hashlock, &sender, transaction.timestamp).await;
+                PayloadOutcome::settle(kind, result, "Swap participation", &transaction.id)
+            }
+            TransactionPayload::SwapClaim { hashlock, preimage } => {
+                let result = self.database.claim_swap(&hashlock, &preimage, &sender, transaction.timestamp).await;
+                PayloadOutcome::settle(kind, result, "Swap claim", &transaction.id)
+            }
+            TransactionPayload::SwapRefund { hashlock } => {
+                let result = self.database.refund_swap(&hashlock, &sender, transaction.timestamp).await;
+                PayloadOutcome::settle(kind, result, "Swap refund", &transaction.id)
+            }
+            TransactionPayload::ArchiveAnchor { content_id, from_height, to_height, manifest_root } => {
+                let anchor = ArchiveAnchor {
+                    content_id,
+                    from_height,
+                    to_height,
+                    manifest_root,
+                    anchored_by: transaction.id.clone(),
+                    anchored_at: transaction.timestamp,
+                };
+                match self.database.record_archive_anchor(&anchor).await {
+                    Ok(()) => {
+                        log::info!("⚓ Transaction {} anchored archive {}", transaction.id, anchor.content_id);
+                        PayloadOutcome::succeeded(kind)
+                    }
+                    Err(e) => {
+                        log::error!("❌ Archive anchor in transaction {} failed: {}", transaction.id, e);
+                        PayloadOutcome::failed(kind, e)
+                    }
+                }
+            }
+        };
+        Some(outcome)
+    }
+
+    /// Execute a finalized transaction loaded from storage
+    async fn route_stored(&self, tx_id: &TransactionId) -> Option<PayloadOutcome> {
+        match self.database.get_transaction(tx_id).await {
+            Ok(Some(transaction)) => self.route(&transaction).await,
+            Ok(None) => {
+                log::warn!("⚠️ Finalized transaction {} is not stored; payload not executed", tx_id);
+                None
+            }
+            Err(e) => {
+                log::error!("❌ Failed to load finalized transaction {}: {}", tx_id, e);
+                None
+            }
+        }
+    }
+
+    /// Write receipts for transactions finalized at `height`, in finalization order
+    async fn write_receipts(&self, height: u64, finalized: &[TransactionId]) {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut receipts = Vec::with_capacity(finalized.len());
+        for tx_id in finalized {
+            let executed = self.executed.lock().unwrap_or_else(|e| e.into_inner()).remove(tx_id);
+            // Finalizations this handler missed are executed now
+            let outcome = match executed {
+                Some(outcome) => outcome,
+                None => match self.route_stored(tx_id).await {
+                    Some(outcome) => outcome,
+                    None => continue,
+                },
+            };
+            receipts.push(outcome.into_receipt(tx_id.clone(), height, now));
+        }
+        if let Err(e) = self.database.store_receipts(&receipts).await {
+            log::error!("❌ Failed to store {} receipt(s) at height {}: {}", receipts.len(), height, e);
+        }
+    }
+}
+
+/// What executing a payload did, before it is recorded as a receipt
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadOutcome {
+    pub kind: &'static str,
+    /// Contract deployed or called
+    pub contract_id: Option<String>,
+    pub success: bool,
+    pub gas_used: u64,
+    pub output: Vec<u8>,
+    pub events: Vec<ContractEvent>,
+    pub error: Option<String>,
+}
+
+impl PayloadOutcome {
+    fn succeeded(kind: &'static str) -> Self {
+        Self { kind, contract_id: None, success: true, gas_used: 0, output: Vec::new(), events: Vec::new(), error: None }
+    }
+
+    fn failed(kind: &'static str, error: impl std::fmt::Display) -> Self {
+        Self { success: false, error: Some(error.to_string()), ..Self::succeeded(kind) }
+    }
+
+    /// Outcome of a payload that only succeeds or fails, logging failures
+    fn settle<T, E: std::fmt::Display>(kind: &'static str, result: Result<T, E>, what: &str, tx_id: &TransactionId) -> Self {
+        match result {
+            Ok(_) => Self::succeeded(kind),
+            Err(e) => {
+                log::error!("❌ {} in transaction {} failed: {}", what, tx_id, e);
+                Self::failed(kind, e)
+            }
+        }
+    }
+
+    pub fn into_receipt(self, tx_id: TransactionId, finalization_height: u64, finalized_at: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            tx_id,
+            status: if self.success { ReceiptStatus::Success } else { ReceiptStatus::Failed },
+            kind: self.kind.to_string(),
+            contract_id: self.contract_id,
+            gas_used: self.gas_used,
+            output: hex::encode(self.output),
+            events: self.events,
+            error: self.error,
+            finalization_height,
+            finalized_at,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for PayloadRouter {
+    fn name(&self) -> String {
+        "payloads".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        match event {
+            NodeEvent::StatusChanged { tx_id, status: NodeStatus::Finalized, .. } => {
+                // The receipt is written once the finalized height is known
+                if let Some(outcome) = self.route_stored(tx_id).await {
+                    self.executed.lock().unwrap_or_else(|e| e.into_inner()).insert(tx_id.clone(), outcome);
+                }
+            }
+            NodeEvent::FinalityAdvanced { height, finalized } => self.write_receipts(*height, finalized).await,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+
+    fn transaction(amount: Amount, payload: TransactionPayload) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount,
+            fee: 1,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: Some(br#"{"memo":"hi"}"#.to_vec()),
+        };
+        payload.attach(&mut transaction).unwrap();
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[test]
+    fn test_payload_round_trips_through_metadata() {
+        let stake = transaction(50, TransactionPayload::Stake { validator_id: "prime_validator_0".to_string() });
+        assert!(matches!(
+            TransactionPayload::from_transaction(&stake).unwrap(),
+            TransactionPayload::Stake { validator_id } if validator_id == "prime_validator_0"
+        ));
+        // Other metadata survives
+        let fields: serde_json::Value = serde_json::from_slice(stake.metadata.as_ref().unwrap()).unwrap();
+        assert_eq!(fields["memo"], "hi");
+
+        let plain = transaction(5, TransactionPayload::Transfer);
+        assert!(matches!(TransactionPayload::from_transaction(&plain).unwrap(), TransactionPayload::Transfer));
+
+        let mut garbled = plain.clone();
+        garbled.metadata = Some(br#"{"payload":{"kind":"stake"}}"#.to_vec());
+        assert!(matches!(TransactionPayload::from_transaction(&garbled), Err(CoreError::InvalidPayload(_))));
+    }
+
+    #[test]
+    fn test_payload_specific_validation() {
+        let deploy = |code: &str| TransactionPayload::ContractDeploy {
+            code: code.to_string(),
+            name: "token".to_string(),
+            version: "1".to_string(),
+            gas_limit: 100_000,
+        };
+        assert!(validate_payload(&transaction(0, deploy("0061736d"))).is_ok());
+        assert!(validate_payload(&transaction(0, deploy("not hex"))).is_err());
+        assert!(validate_payload(&transaction(0, deploy(""))).is_err());
+
+        let stake = TransactionPayload::Stake { validator_id: "prime_validator_0".to_string() };
+        assert!(validate_payload(&transaction(0, stake.clone())).is_err());
+        assert!(validate_payload(&transaction(10, stake)).is_ok());
+
+        let vote = TransactionPayload::GovernanceVote {
+            proposal_id: "proposal-1".to_string(),
+            vote: VoteType::For,
+            justification: None,
+        };
+        assert!(validate_payload(&transaction(0, vote.clone())).is_ok());
+        assert!(validate_payload(&transaction(10, vote)).is_err());
+
+        let initiate = |expires_at: u64| TransactionPayload::SwapInitiate {
+            hashlock: swap_hashlock(b"secret"),
+            asset: Asset::Native,
+            amount: 100,
+            counter_asset: Asset::Token("ab".to_string()),
+            counter_amount: 10,
+            expires_at,
+        };
+        assert!(validate_payload(&transaction(0, initiate(1_700_003_600))).is_ok());
+        assert!(validate_payload(&transaction(100, initiate(1_700_003_600))).is_err());
+        assert!(validate_payload(&transaction(0, initiate(1_699_999_999))).is_err());
+
+        let claim = |secret: &[u8]| TransactionPayload::SwapClaim {
+            hashlock: swap_hashlock(b"secret"),
+            preimage: hex::encode(secret),
+        };
+        assert!(validate_payload(&transaction(0, claim(b"secret"))).is_ok());
+        assert!(validate_payload(&transaction(0, claim(b"guess"))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_finalized_stake_is_bonded_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            max_connections: 5,
+            retention: Default::default(),
+        }).await.unwrap());
+        let stake = transaction(75, TransactionPayload::Stake { validator_id: "prime_validator_1".to_string() });
+        database.store_transaction(&stake).await.unwrap();
+
+        let stakes = Arc::new(StakeLedger::new());
+        let router = PayloadRouter::new(
+            database,
+            Arc::new(RwLock::new(ContractEngine::new().unwrap())),
+            stakes.clone(),
+            Arc::new(RwLock::new(None)),
+        );
+        let finalized = NodeEvent::StatusChanged {
+            tx_id: stake.id.clone(),
+            previous: NodeStatus::Confirmed,
+            status: NodeStatus::Finalized,
+            confidence: 1.0,
+            submitted_at: stake.timestamp,
+        };
+        router.handle(&finalized).await;
+        router.handle(&finalized).await;
+
+        assert_eq!(stakes.bond_of("prime_validator_1", &account_address(&[1u8; 32])), 75);
+    }
+
+    #[tokio::test]
+    async fn test_receipts_record_height_and_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap());
+        let transfer = transaction(10, TransactionPayload::Transfer);
+        let vote = transaction(0, TransactionPayload::GovernanceVote {
+            proposal_id: "proposal".to_string(),
+            vote: VoteType::For,
+            justification: None,
+        });
+        database.store_transaction(&transfer).await.unwrap();
+        database.store_transaction(&vote).await.unwrap();
+
+        let router = PayloadRouter::new(
+            database.clone(),
+            Arc::new(RwLock::new(ContractEngine::new().unwrap())),
+            Arc::new(StakeLedger::new()),
+            Arc::new(RwLock::new(None)),
+        );
+        router.handle(&NodeEvent::StatusChanged {
+            tx_id: transfer.id.clone(),
+            previous: NodeStatus::Pending,
+            status: NodeStatus::Finalized,
+            confidence: 1.0,
+            submitted_at: transfer.timestamp,
+        }).await;
+        // Nothing is written until the height is known
+        assert_eq!(database.get_receipt(&transfer.id).await.unwrap(), None);
+
+        // The vote's finalization was missed, so it executes with the height
+        router.handle(&NodeEvent::FinalityAdvanced { height: 4, finalized: vec![transfer.id.clone(), vote.id.clone()] }).await;
+        let receipt = database.get_receipt(&transfer.id).await.unwrap().unwrap();
+        assert_eq!((receipt.status, receipt.kind.as_str(), receipt.finalization_height), (ReceiptStatus::Success, "transfer", 4));
+        let receipt = database.get_receipt(&vote.id).await.unwrap().unwrap();
+        assert_eq!(receipt.status, ReceiptStatus::Failed);
+        assert_eq!(receipt.error.as_deref(), Some("no governance service is running"));
+    }
+}
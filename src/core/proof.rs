@@ -0,0 +1,259 @@
+//! Transaction inclusion proofs for light clients
+//!
+//! A transaction's ID hashes the IDs of its parents, so the DAG is a Merkle
+//! DAG: knowing a finalized checkpoint's ID, a client can check that a
+//! transaction is among its ancestors given the contents of every transaction
+//! on one path between them. Each link on the path names the previous ID as a
+//! parent, and hashing the last link must give the checkpoint. Signatures and
+//! quantum proofs are not part of the ID and are left out of the links.
+//!
+//! The link encoding and hashing must stay compatible with the mobile SDK's
+//! `proof` module.
+
+use super::{Amount, CoreError, DAGNode, NodeStatus, QuantumProof, Transaction};
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// ID-bearing fields of a transaction on a proof path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofLink {
+    /// Hex sender public key
+    pub sender: String,
+    /// Hex receiver public key
+    pub receiver: String,
+    #[serde(with = "crate::core::amount")]
+    pub amount: Amount,
+    pub nonce: u64,
+    pub timestamp: u64,
+    pub parents: Vec<TransactionId>,
+    /// Hex metadata
+    pub metadata: Option<String>,
+}
+
+impl ProofLink {
+    pub fn from_transaction(transaction: &Transaction) -> Self {
+        Self {
+            sender: hex::encode(&transaction.sender),
+            receiver: hex::encode(&transaction.receiver),
+            amount: transaction.amount,
+            nonce: transaction.nonce,
+            timestamp: transaction.timestamp,
+            parents: transaction.parents.clone(),
+            metadata: transaction.metadata.as_ref().map(hex::encode),
+        }
+    }
+
+    /// Content hash of the linked transaction
+    pub fn compute_id(&self) -> Result<TransactionId, CoreError> {
+        let decode = |field: &str, value: &str| {
+            hex::decode(value).map_err(|_| CoreError::InvalidInclusionProof(format!("{} is not hex", field)))
+        };
+        let transaction = Transaction {
+            id: TransactionId::default(),
+            sender: decode("sender", &self.sender)?,
+            receiver: decode("receiver", &self.receiver)?,
+            amount: self.amount,
+            fee: 0,
+            nonce: self.nonce,
+            timestamp: self.timestamp,
+            parents: self.parents.clone(),
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 0, proof_timestamp: 0 },
+            metadata: self.metadata.as_deref().map(|metadata| decode("metadata", metadata)).transpose()?,
+        };
+        Ok(transaction.compute_id())
+    }
+}
+
+/// Proof that a transaction is an ancestor of a finalized checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub transaction_id: TransactionId,
+    /// Finalized transaction the proof is anchored at
+    pub checkpoint_id: TransactionId,
+    /// Descendants from a child of the transaction up to the checkpoint
+    pub path: Vec<ProofLink>,
+}
+
+/// Build the shortest proof from `tx_id` to `checkpoint`
+///
+/// Without a checkpoint, the proof is anchored at the nearest finalized
+/// descendant. A finalized transaction is its own checkpoint, with an empty
+/// path.
+pub fn build_inclusion_proof(
+    nodes: &HashMap<TransactionId, DAGNode>,
+    tx_id: &TransactionId,
+    checkpoint: Option<&TransactionId>,
+) -> Result<InclusionProof, CoreError> {
+    if !nodes.contains_key(tx_id) {
+        return Err(CoreError::TransactionNotFound(tx_id.clone()));
+    }
+    if let Some(checkpoint) = checkpoint {
+        match nodes.get(checkpoint) {
+            Some(node) if node.status == NodeStatus::Finalized => {}
+            Some(_) => return Err(CoreError::InvalidInclusionProof(format!("{} is not finalized", checkpoint))),
+            None => return Err(CoreError::TransactionNotFound(checkpoint.clone())),
+        }
+    }
+    let is_checkpoint = |id: &TransactionId, node: &DAGNode| match checkpoint {
+        Some(checkpoint) => id == checkpoint,
+        None => node.status == NodeStatus::Finalized,
+    };
+
+    // Breadth-first over approvers, remembering how each was reached
+    let mut reached_from: HashMap<TransactionId, Option<TransactionId>> = HashMap::new();
+    reached_from.insert(tx_id.clone(), None);
+    let mut queue = VecDeque::from([tx_id.clone()]);
+    let mut found = None;
+    while let Some(id) = queue.pop_front() {
+        let Some(node) = nodes.get(&id) else {
+            continue;
+        };
+        if is_checkpoint(&id, node) {
+            found = Some(id);
+            break;
+        }
+        for child in &node.children {
+            if !reached_from.contains_key(child) {
+                reached_from.insert(child.clone(), Some(id.clone()));
+                queue.push_back(child.clone());
+            }
+        }
+    }
+    let checkpoint_id = found.ok_or_else(|| CoreError::NoCheckpoint(tx_id.clone()))?;
+
+    let mut path = Vec::new();
+    let mut current = checkpoint_id.clone();
+    while let Some(Some(previous)) = reached_from.get(&current) {
+        path.push(ProofLink::from_transaction(&nodes[&current].transaction));
+        current = previous.clone();
+    }
+    path.reverse();
+
+    Ok(InclusionProof { transaction_id: tx_id.clone(), checkpoint_id, path })
+}
+
+/// Check that `proof` links `transaction_id` to the trusted `checkpoint_id`
+pub fn verify_inclusion_proof(
+    proof: &InclusionProof,
+    transaction_id: &TransactionId,
+    checkpoint_id: &TransactionId,
+) -> Result<(), CoreError> {
+    if &proof.transaction_id != transaction_id {
+        return Err(CoreError::InvalidInclusionProof("proof is for another transaction".to_string()));
+    }
+    if &proof.checkpoint_id != checkpoint_id {
+        return Err(CoreError::InvalidInclusionProof("proof is anchored at another checkpoint".to_string()));
+    }
+
+    let mut current = transaction_id.clone();
+    for (index, link) in proof.path.iter().enumerate() {
+        if !link.parents.contains(&current) {
+            return Err(CoreError::InvalidInclusionProof(format!("link {} does not approve {}", index, current)));
+        }
+        current = link.compute_id()?;
+    }
+    if &current != checkpoint_id {
+        return Err(CoreError::InvalidInclusionProof("path does not end at the checkpoint".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(nodes: &mut HashMap<TransactionId, DAGNode>, nonce: u64, parents: Vec<TransactionId>, status: NodeStatus) -> TransactionId {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 0,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents: parents.clone(),
+            signature: vec![7u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        let id = transaction.id.clone();
+        for parent in &parents {
+            nodes.get_mut(parent).unwrap().children.push(id.clone());
+        }
+        nodes.insert(id.clone(), DAGNode {
+            transaction,
+            children: vec![],
+            weight: 1,
+            confidence: 0.0,
+            status,
+            quantum_score: 80,
+        });
+        id
+    }
+
+    #[test]
+    fn test_proof_reaches_nearest_checkpoint() {
+        let mut nodes = HashMap::new();
+        let genesis = add(&mut nodes, 0, vec![], NodeStatus::Finalized);
+        let target = add(&mut nodes, 1, vec![genesis.clone()], NodeStatus::Pending);
+        let middle = add(&mut nodes, 2, vec![target.clone()], NodeStatus::Confirmed);
+        let side = add(&mut nodes, 3, vec![genesis.clone()], NodeStatus::Pending);
+        let checkpoint = add(&mut nodes, 4, vec![middle.clone(), side], NodeStatus::Finalized);
+
+        let proof = build_inclusion_proof(&nodes, &target, None).unwrap();
+        assert_eq!(proof.checkpoint_id, checkpoint);
+        assert_eq!(proof.path.len(), 2);
+        verify_inclusion_proof(&proof, &target, &checkpoint).unwrap();
+
+        // A finalized transaction anchors itself
+        let own = build_inclusion_proof(&nodes, &genesis, None).unwrap();
+        assert!(own.path.is_empty());
+        verify_inclusion_proof(&own, &genesis, &genesis).unwrap();
+
+        assert!(matches!(build_inclusion_proof(&nodes, &target, Some(&middle)), Err(CoreError::InvalidInclusionProof(_))));
+        let tip = add(&mut nodes, 5, vec![checkpoint], NodeStatus::Pending);
+        assert!(matches!(build_inclusion_proof(&nodes, &tip, None), Err(CoreError::NoCheckpoint(_))));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_proofs() {
+        let mut nodes = HashMap::new();
+        let genesis = add(&mut nodes, 0, vec![], NodeStatus::Pending);
+        let target = add(&mut nodes, 1, vec![genesis.clone()], NodeStatus::Pending);
+        let checkpoint = add(&mut nodes, 2, vec![target.clone()], NodeStatus::Finalized);
+        let proof = build_inclusion_proof(&nodes, &target, None).unwrap();
+
+        let mut altered = proof.clone();
+        altered.path[0].amount += 1;
+        assert!(verify_inclusion_proof(&altered, &target, &checkpoint).is_err());
+
+        // The path does not approve a transaction it was not built for
+        let mut swapped = proof.clone();
+        swapped.transaction_id = genesis.clone();
+        assert!(verify_inclusion_proof(&swapped, &genesis, &checkpoint).is_err());
+        assert!(verify_inclusion_proof(&proof, &target, &genesis).is_err());
+    }
+
+    #[test]
+    fn test_link_id_vector() {
+        // Shared with the mobile SDK's proof tests
+        let link = ProofLink {
+            sender: "0101".to_string(),
+            receiver: "0202".to_string(),
+            amount: 5,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![TransactionId::Hash([3u8; 32])],
+            metadata: Some("ff".to_string()),
+        };
+        assert_eq!(
+            link.compute_id().unwrap().as_string(),
+            "a0ab21e27db69e323b7048d378dc579b1e99925916600d224d149617fb5ad9f6"
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! DAG pruning
+//!
+//! Finalized transactions older than the pruning horizon are dropped from the
+//! in-memory DAG. The boundary is kept: an old finalized transaction that a
+//! newer one approves, or that nothing approves yet, stays, so every remaining
+//! transaction's parents are still in memory or were finalized long ago. These boundary transactions are
+//! the checkpoint roots; inclusion proofs for newer transactions end at or
+//! above them. The genesis transaction is never pruned.
+//!
+//! With `delete_from_storage`, pruned transactions are also deleted from
+//! SQLite once they are written to a JSON-lines archive file, so the next
+//! startup does not load them again.
+
+use super::{DAGCore, DAGNode, NodeStatus};
+use crate::storage::DatabaseManager;
+use crate::{BlockchainError, TransactionId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio::sync::RwLock;
+
+const ARCHIVE_PREFIX: &str = "dag_pruned_";
+const ARCHIVE_SUFFIX: &str = ".jsonl";
+
+/// Pruning configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PruningConfig {
+    pub enabled: bool,
+    /// Age past which finalized transactions are pruned
+    pub horizon_secs: u64,
+    /// Seconds between pruning runs
+    pub interval_secs: u64,
+    /// Also delete pruned transactions from storage after archiving them
+    pub delete_from_storage: bool,
+    /// Directory for archive files
+    pub archive_dir: String,
+}
+
+impl Default for PruningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            horizon_secs: 7 * 24 * 3600,
+            interval_secs: 3600,
+            delete_from_storage: false,
+            archive_dir: "./archive".to_string(),
+        }
+    }
+}
+
+/// Outcome of a pruning run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneReport {
+    pub run_timestamp: i64,
+    /// Transactions dropped from memory
+    pub pruned: usize,
+    /// Finalized transactions left at the pruned boundary
+    pub checkpoint_roots: Vec<TransactionId>,
+    /// Archive written before deleting from storage
+    pub archive_file: Option<String>,
+    /// Transactions deleted from storage
+    pub deleted_from_storage: u64,
+}
+
+impl DAGCore {
+    /// Drop finalized transactions with a timestamp before `cutoff`, returning them
+    pub fn prune_finalized_before(&mut self, cutoff: u64) -> Vec<DAGNode> {
+        let eligible: HashSet<TransactionId> = self.transactions.iter()
+            .filter(|(id, node)| {
+                node.status == NodeStatus::Finalized
+                    && node.transaction.timestamp < cutoff
+                    && self.genesis.as_ref() != Some(*id)
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        // Transactions approved by something that stays, or by nothing yet,
+        // are kept as roots
+        let prunable: HashSet<TransactionId> = eligible.iter()
+            .filter(|id| {
+                let children = &self.transactions[*id].children;
+                !children.is_empty() && children.iter().all(|child| eligible.contains(child))
+            })
+            .cloned()
+            .collect();
+
+        let mut pruned = Vec::with_capacity(prunable.len());
+        for id in &prunable {
+            if let Some(node) = self.transactions.remove(id) {
+                self.conflicts.prune(&node.transaction);
+                self.addresses.remove(&node.transaction);
+                self.tip_selector.forget(id);
+                self.cold.remove(id);
+                pruned.push(node);
+            }
+        }
+        for node in self.transactions.values_mut() {
+            node.children.retain(|child| !prunable.contains(child));
+        }
+        if !pruned.is_empty() {
+            self.weights.clear();
+        }
+        pruned
+    }
+
+    /// Finalized transactions whose parents were pruned
+    pub fn checkpoint_roots(&self) -> Vec<TransactionId> {
+        let mut roots: Vec<TransactionId> = self.transactions.iter()
+            .filter(|(_, node)| {
+                node.status == NodeStatus::Finalized
+                    && node.transaction.parents.iter().any(|parent| !self.transactions.contains_key(parent))
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        roots.sort_by_key(|id| id.as_string());
+        roots
+    }
+}
+
+/// Prune `dag` according to `config`, archiving and deleting from `database` if configured
+pub async fn prune_dag(
+    dag: &RwLock<DAGCore>,
+    database: &DatabaseManager,
+    config: &PruningConfig,
+) -> Result<PruneReport, BlockchainError> {
+    let now = chrono::Utc::now().timestamp();
+    let cutoff = (now as u64).saturating_sub(config.horizon_secs);
+    let (pruned, checkpoint_roots) = {
+        let mut dag = dag.write().await;
+        let pruned = dag.prune_finalized_before(cutoff);
+        (pruned, dag.checkpoint_roots())
+    };
+
+    let mut report = PruneReport {
+        run_timestamp: now,
+        pruned: pruned.len(),
+        checkpoint_roots,
+        ..PruneReport::default()
+    };
+
+    if config.delete_from_storage && !pruned.is_empty() {
+        let mut lines = Vec::with_capacity(pruned.len());
+        for node in &pruned {
+            lines.push(serde_json::to_string(&node.transaction)?);
+        }
+        let path = format!("{}/{}{}{}", config.archive_dir, ARCHIVE_PREFIX, now, ARCHIVE_SUFFIX);
+        tokio::fs::create_dir_all(&config.archive_dir).await?;
+        tokio::fs::write(&path, lines.join("\n") + "\n").await?;
+
+        let ids: Vec<TransactionId> = pruned.iter().map(|node| node.transaction.id.clone()).collect();
+        report.deleted_from_storage = database.delete_transactions(&ids).await?;
+        report.archive_file = Some(path);
+    }
+
+    log::info!(
+        "✂️ Pruned {} finalized transaction(s), {} checkpoint root(s), {} deleted from storage",
+        report.pruned,
+        report.checkpoint_roots.len(),
+        report.deleted_from_storage,
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn link(dag: &mut DAGCore, nonce: u64, timestamp: u64, parents: Vec<TransactionId>, status: NodeStatus) -> TransactionId {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 0,
+            nonce,
+            timestamp,
+            parents: parents.clone(),
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        let id = transaction.id.clone();
+        for parent in &parents {
+            dag.transactions.get_mut(parent).unwrap().children.push(id.clone());
+        }
+        dag.transactions.insert(id.clone(), DAGNode {
+            transaction,
+            children: vec![],
+            weight: 1,
+            confidence: 1.0,
+            status,
+            quantum_score: 80,
+        });
+        id
+    }
+
+    async fn dag(temp_dir: &TempDir) -> DAGCore {
+        let database = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            max_connections: 5,
+            retention: Default::default(),
+        }).await.unwrap();
+        DAGCore::new_with_database(Arc::new(database)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prune_keeps_boundary_as_checkpoint_roots() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut dag = dag(&temp_dir).await;
+        let genesis = dag.genesis_id().unwrap().clone();
+
+        let old = link(&mut dag, 1, 100, vec![genesis.clone()], NodeStatus::Finalized);
+        let boundary = link(&mut dag, 2, 200, vec![old.clone()], NodeStatus::Finalized);
+        let recent = link(&mut dag, 3, 2_000, vec![boundary.clone()], NodeStatus::Finalized);
+        let tip = link(&mut dag, 4, 2_100, vec![recent.clone()], NodeStatus::Pending);
+
+        let pruned = dag.prune_finalized_before(1_000);
+        assert_eq!(pruned.iter().map(|node| node.transaction.id.clone()).collect::<Vec<_>>(), vec![old.clone()]);
+        assert!(dag.get_node(&old).is_none());
+        assert!(dag.get_node(&genesis).is_some());
+        assert_eq!(dag.checkpoint_roots(), vec![boundary.clone()]);
+
+        // Proofs for what remains still reach a checkpoint
+        assert_eq!(dag.inclusion_proof(&boundary, Some(&recent)).unwrap().path.len(), 1);
+        assert!(dag.inclusion_proof(&tip, None).is_err());
+
+        // The pruned spend stays settled
+        let mut replay = dag.get_node(&boundary).unwrap().transaction.clone();
+        replay.nonce = 1;
+        replay.parents = vec![recent.clone()];
+        replay.id = replay.compute_id();
+        assert!(dag.conflicts.check(&replay, &dag.transactions).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prune_dag_archives_before_deleting() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Arc::new(DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            max_connections: 5,
+            retention: Default::default(),
+        }).await.unwrap());
+        let mut core = DAGCore::new_with_database(database.clone()).await.unwrap();
+        let genesis = core.genesis_id().unwrap().clone();
+        let old = link(&mut core, 1, 100, vec![genesis.clone()], NodeStatus::Finalized);
+        link(&mut core, 2, 200, vec![old.clone()], NodeStatus::Finalized);
+        database.store_transaction(&core.get_node(&old).unwrap().transaction).await.unwrap();
+        let dag = RwLock::new(core);
+
+        let config = PruningConfig {
+            enabled: true,
+            horizon_secs: 60,
+            delete_from_storage: true,
+            archive_dir: temp_dir.path().join("archive").to_string_lossy().to_string(),
+            ..PruningConfig::default()
+        };
+        let report = prune_dag(&dag, &database, &config).await.unwrap();
+        assert_eq!(report.pruned, 1);
+        assert_eq!(report.deleted_from_storage, 1);
+        let archive = tokio::fs::read_to_string(report.archive_file.unwrap()).await.unwrap();
+        assert!(archive.contains(&old.as_string()));
+        assert!(database.get_transaction(&old).await.unwrap().is_none());
+    }
+}
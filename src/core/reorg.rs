@@ -0,0 +1,185 @@
+//! Rolling back rejected branches
+//!
+//! When a double spend is resolved, the losing members are rejected along
+//! with everything built on them, whatever their status: a pending or even
+//! confirmed transaction that approves a rejected one can never finalize.
+//! `roll_back` follows child links from each loser rather than relying on
+//! conflict marks alone, so descendants that were paged in after the
+//! conflict formed are caught too. Finalized transactions are never rolled
+//! back.
+//!
+//! Rolled-back transactions lose their confidence, their place in the tip
+//! set and their fee-density record, and pending parents they were the only
+//! live approvers of become tips again. Balances only change at finality,
+//! so the only other effect to undo is the funds reserved for pending
+//! spends, which `AccountStateHandler` releases on each `StatusChanged`.
+//! A `Reorganized` event follows the status changes of each rollback.
+
+use super::{DAGCore, NodeStatus};
+use crate::events::NodeEvent;
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+/// Transactions rolled back because a conflict was decided against them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reorg {
+    /// Member whose confirmation or acceptance decided the conflict
+    pub winner: TransactionId,
+    /// Every transaction rejected, in the order it was rejected
+    pub rejected: Vec<TransactionId>,
+    /// The rejected transactions that had already confirmed
+    pub demoted: Vec<TransactionId>,
+}
+
+impl DAGCore {
+    /// Reject `losers` and every transaction approving them
+    ///
+    /// Status changes are appended to `changed` for the caller to publish,
+    /// followed by the returned reorg with `publish_reorgs`.
+    pub(super) fn roll_back(
+        &mut self,
+        winner: &TransactionId,
+        losers: Vec<TransactionId>,
+        changed: &mut Vec<(TransactionId, NodeStatus)>,
+    ) -> Option<Reorg> {
+        let mut seen: HashSet<TransactionId> = losers.iter().cloned().collect();
+        let mut queue: VecDeque<TransactionId> = losers.into();
+        let mut rejected = Vec::new();
+        let mut demoted = Vec::new();
+        let mut parents = Vec::new();
+
+        while let Some(tx_id) = queue.pop_front() {
+            let Some(node) = self.transactions.get_mut(&tx_id) else {
+                continue;
+            };
+            match node.status {
+                NodeStatus::Rejected => continue,
+                NodeStatus::Finalized => {
+                    log::error!("❌ Not rejecting {}: it is finalized, but its branch lost a double spend to {}", tx_id, winner);
+                    continue;
+                }
+                NodeStatus::Confirmed => demoted.push(tx_id.clone()),
+                NodeStatus::Pending => {}
+            }
+            changed.push((tx_id.clone(), node.status.clone()));
+            node.status = NodeStatus::Rejected;
+            node.confidence = 0.0;
+            parents.extend(node.transaction.parents.iter().cloned());
+            for child in &node.children {
+                if seen.insert(child.clone()) {
+                    queue.push_back(child.clone());
+                }
+            }
+
+            self.tips.remove(&tx_id);
+            self.tip_selector.forget(&tx_id);
+            log::warn!("🚫 Rejected {}: approves a double spend that lost to {}", tx_id, winner);
+            rejected.push(tx_id);
+        }
+
+        if rejected.is_empty() {
+            return None;
+        }
+        if !demoted.is_empty() {
+            log::warn!("↩️ Demoted {} confirmed transaction(s) on branches that lost to {}", demoted.len(), winner);
+        }
+        let rejected_set: HashSet<&TransactionId> = rejected.iter().collect();
+        parents.retain(|parent| !rejected_set.contains(parent));
+        self.retip_parents(parents);
+        Some(Reorg { winner: winner.clone(), rejected, demoted })
+    }
+
+    /// Make pending `parents` left without a live approver tips again
+    pub(super) fn retip_parents(&mut self, parents: Vec<TransactionId>) {
+        let retipped: Vec<TransactionId> = parents.into_iter()
+            .filter(|parent_id| {
+                self.transactions.get(parent_id).is_some_and(|parent| {
+                    parent.status == NodeStatus::Pending
+                        && parent.children.iter().all(|child| {
+                            self.transactions.get(child).is_none_or(|child| child.status == NodeStatus::Rejected)
+                        })
+                })
+            })
+            .collect();
+        let retipped: Vec<TransactionId> = retipped.into_iter().filter(|parent_id| self.tips.insert(parent_id.clone())).collect();
+        if !retipped.is_empty() {
+            self.publish_tip_change(retipped, Vec::new());
+        }
+    }
+
+    /// Announce rollbacks, after the status changes they caused
+    pub(super) fn publish_reorgs(&self, reorgs: Vec<Reorg>) {
+        let Some(events) = &self.events else {
+            return;
+        };
+        for reorg in reorgs {
+            events.publish(NodeEvent::Reorganized {
+                winner: reorg.winner,
+                rejected: reorg.rejected,
+                demoted: reorg.demoted,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{QuantumProof, Transaction};
+    use crate::events::EventBus;
+
+    #[tokio::test]
+    async fn test_losing_branch_is_rolled_back() {
+        let mut dag = DAGCore::new().await.unwrap();
+        let events = EventBus::default();
+        dag.set_event_bus(events.clone());
+        let mut receiver = events.subscribe();
+        let genesis = dag.genesis_id().cloned().unwrap();
+
+        let add = |sender: u8, nonce: u64, to: u8, parent: TransactionId| {
+            let mut transaction = Transaction {
+                id: TransactionId::default(),
+                sender: vec![sender; 32],
+                receiver: vec![to; 32],
+                amount: 5,
+                fee: 1,
+                nonce,
+                timestamp: chrono::Utc::now().timestamp() as u64,
+                parents: vec![parent],
+                signature: vec![0u8; 64],
+                signature_scheme: Default::default(),
+                quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 0 },
+                metadata: None,
+            };
+            transaction.id = transaction.compute_id();
+            transaction
+        };
+        let parent = dag.add_transaction(add(3, 1, 4, genesis.clone())).await.unwrap();
+        let loser = dag.add_transaction(add(1, 1, 2, parent.clone())).await.unwrap();
+        let winner = dag.add_transaction(add(1, 1, 5, genesis)).await.unwrap();
+        let confirmed = dag.add_transaction(add(6, 1, 7, loser.clone())).await.unwrap();
+        let pending = dag.add_transaction(add(8, 1, 9, confirmed.clone())).await.unwrap();
+        dag.transactions.get_mut(&confirmed).unwrap().status = NodeStatus::Confirmed;
+
+        let sorted = |mut ids: Vec<TransactionId>| {
+            ids.sort_by_key(|id| id.as_string());
+            ids
+        };
+        let rolled_back = sorted(vec![loser, confirmed.clone(), pending]);
+        assert_eq!(sorted(dag.accept_conflict(&winner)), rolled_back);
+        assert_eq!(dag.get_node(&confirmed).unwrap().status, NodeStatus::Rejected);
+        assert_eq!(dag.get_node(&confirmed).unwrap().confidence, 0.0);
+        // The parent only the losing branch approved is a tip again
+        let tips = dag.get_tips().iter().map(|node| node.transaction.id.clone()).collect();
+        assert_eq!(sorted(tips), sorted(vec![parent, winner.clone()]));
+
+        let mut reorg = None;
+        while let Ok(event) = receiver.try_recv() {
+            if let NodeEvent::Reorganized { winner, rejected, demoted } = event {
+                reorg = Some(Reorg { winner, rejected: sorted(rejected), demoted });
+            }
+        }
+        assert_eq!(reorg, Some(Reorg { winner, rejected: rolled_back, demoted: vec![confirmed] }));
+    }
+}
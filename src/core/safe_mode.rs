@@ -0,0 +1,415 @@
+//! Chain halting and safe-mode operation
+//!
+//! In safe mode the node stops accepting new transactions, locally or from
+//! peers, while read APIs, sync and diagnostics keep running. Safe mode is
+//! entered by a local admin command or an approved governance emergency
+//! action. Leaving it always takes a resume authorization signed by one of
+//! the configured resume keys, bound to the halt it ends so it cannot be
+//! replayed later. Every transition is appended to a persisted audit trail,
+//! so a halted node stays halted across restarts.
+
+use crate::events::{EventBus, NodeEvent};
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock as StdRwLock;
+use tokio::sync::RwLock;
+
+/// Safe mode configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafeModeConfig {
+    /// Hex Ed25519 public keys allowed to sign resume authorizations
+    pub resume_keys: Vec<String>,
+    /// Oldest resume authorization accepted, in seconds
+    pub max_authorization_age_secs: u64,
+}
+
+impl Default for SafeModeConfig {
+    fn default() -> Self {
+        Self {
+            resume_keys: Vec::new(),
+            max_authorization_age_secs: 300,
+        }
+    }
+}
+
+/// Who put the node into safe mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HaltSource {
+    /// Local admin command
+    Admin { operator: String },
+    /// Approved governance emergency action
+    Governance { proposal_id: String },
+}
+
+/// Direction of a safe mode transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafeModeAction {
+    Halt,
+    Resume,
+}
+
+/// Audit record of one transition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SafeModeTransition {
+    pub sequence: u64,
+    pub action: SafeModeAction,
+    pub reason: String,
+    /// Halt source, or the hex key that signed a resume
+    pub actor: String,
+    pub at: u64,
+}
+
+/// Current safe mode state, as reported in node status
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SafeModeStatus {
+    pub active: bool,
+    pub reason: Option<String>,
+    pub source: Option<HaltSource>,
+    pub since: Option<u64>,
+    /// Sequence of the halt in effect, which a resume authorization must name
+    pub halt_sequence: Option<u64>,
+    pub transitions: u64,
+}
+
+/// Signed request to leave safe mode
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeAuthorization {
+    pub halt_sequence: u64,
+    pub reason: String,
+    pub issued_at: u64,
+    /// Hex Ed25519 public key
+    pub public_key: String,
+    /// Hex Ed25519 signature over `signing_payload`
+    pub signature: String,
+}
+
+impl ResumeAuthorization {
+    /// Bytes signed by a resume authorization
+    pub fn signing_payload(halt_sequence: u64, issued_at: u64, reason: &str) -> Vec<u8> {
+        format!("qdag-safe-mode-resume:{}:{}:{}", halt_sequence, issued_at, reason).into_bytes()
+    }
+
+    /// Sign an authorization to end the halt with `halt_sequence`
+    pub fn sign(keypair: &Keypair, halt_sequence: u64, reason: &str) -> Self {
+        let issued_at = chrono::Utc::now().timestamp() as u64;
+        let signature = keypair.sign(&Self::signing_payload(halt_sequence, issued_at, reason));
+        Self {
+            halt_sequence,
+            reason: reason.to_string(),
+            issued_at,
+            public_key: hex::encode(keypair.public.as_bytes()),
+            signature: hex::encode(signature.to_bytes()),
+        }
+    }
+
+    fn verify_signature(&self) -> Result<(), SafeModeError> {
+        let public_key = hex::decode(&self.public_key)
+            .ok()
+            .and_then(|bytes| PublicKey::from_bytes(&bytes).ok())
+            .ok_or(SafeModeError::InvalidSignature)?;
+        let signature = hex::decode(&self.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_bytes(&bytes).ok())
+            .ok_or(SafeModeError::InvalidSignature)?;
+        public_key
+            .verify(&Self::signing_payload(self.halt_sequence, self.issued_at, &self.reason), &signature)
+            .map_err(|_| SafeModeError::InvalidSignature)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SafeModeState {
+    halt: Option<(HaltSource, SafeModeTransition)>,
+    transitions: Vec<SafeModeTransition>,
+}
+
+/// Node safe mode switch with its audit trail
+pub struct SafeMode {
+    config: StdRwLock<SafeModeConfig>,
+    path: Option<PathBuf>,
+    halted: AtomicBool,
+    state: RwLock<SafeModeState>,
+    events: Option<EventBus>,
+}
+
+impl SafeMode {
+    /// Create an in-memory safe mode switch
+    pub fn new(config: SafeModeConfig) -> Self {
+        Self {
+            config: StdRwLock::new(config),
+            path: None,
+            halted: AtomicBool::new(false),
+            state: RwLock::new(SafeModeState::default()),
+            events: None,
+        }
+    }
+
+    /// Open the persisted state at `path`, resuming a halt that was in effect
+    pub async fn open(config: SafeModeConfig, path: impl Into<PathBuf>) -> Result<Self, SafeModeError> {
+        let path = path.into();
+        let state: SafeModeState = match tokio::fs::read_to_string(&path).await {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| SafeModeError::Storage(e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => SafeModeState::default(),
+            Err(e) => return Err(SafeModeError::Storage(e.to_string())),
+        };
+        if let Some((_, halt)) = &state.halt {
+            log::warn!("🛑 Node is in safe mode since {}: {}", halt.at, halt.reason);
+        }
+
+        Ok(Self {
+            config: StdRwLock::new(config),
+            path: Some(path),
+            halted: AtomicBool::new(state.halt.is_some()),
+            state: RwLock::new(state),
+            events: None,
+        })
+    }
+
+    /// Publish transitions to the node event bus
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = Some(events);
+    }
+
+    /// Add a key allowed to sign resume authorizations
+    pub fn add_resume_key(&self, public_key: &str) -> Result<(), SafeModeError> {
+        hex::decode(public_key)
+            .ok()
+            .and_then(|bytes| PublicKey::from_bytes(&bytes).ok())
+            .ok_or_else(|| SafeModeError::UnauthorizedKey(public_key.to_string()))?;
+        self.config.write().unwrap_or_else(|e| e.into_inner()).resume_keys.push(public_key.to_lowercase());
+        Ok(())
+    }
+
+    /// Whether new transactions are currently refused
+    pub fn is_halted(&self) -> bool {
+        self.halted.load(Ordering::SeqCst)
+    }
+
+    /// Fail with the halt reason while in safe mode
+    pub async fn ensure_accepting(&self) -> Result<(), SafeModeError> {
+        if !self.is_halted() {
+            return Ok(());
+        }
+        let reason = self.state.read().await.halt.as_ref()
+            .map(|(_, halt)| halt.reason.clone())
+            .unwrap_or_default();
+        Err(SafeModeError::Halted(reason))
+    }
+
+    /// Enter safe mode
+    pub async fn halt(&self, source: HaltSource, reason: &str) -> Result<SafeModeTransition, SafeModeError> {
+        let mut state = self.state.write().await;
+        if state.halt.is_some() {
+            return Err(SafeModeError::AlreadyHalted);
+        }
+
+        let transition = self.record(&mut state, SafeModeAction::Halt, reason, source_label(&source));
+        state.halt = Some((source, transition.clone()));
+        self.save(&state).await?;
+        self.halted.store(true, Ordering::SeqCst);
+
+        log::warn!("🛑 Entered safe mode ({}): {}", transition.actor, reason);
+        if self.config.read().unwrap_or_else(|e| e.into_inner()).resume_keys.is_empty() {
+            log::warn!("⚠️ No safe mode resume keys configured, the node cannot be resumed until one is added");
+        }
+        self.publish(&transition);
+        Ok(transition)
+    }
+
+    /// Leave safe mode with a signed authorization for the halt in effect
+    pub async fn resume(&self, authorization: &ResumeAuthorization) -> Result<SafeModeTransition, SafeModeError> {
+        let mut state = self.state.write().await;
+        let halt_sequence = state.halt.as_ref()
+            .map(|(_, halt)| halt.sequence)
+            .ok_or(SafeModeError::NotHalted)?;
+
+        if authorization.halt_sequence != halt_sequence {
+            return Err(SafeModeError::WrongHalt { expected: halt_sequence, got: authorization.halt_sequence });
+        }
+        let config = self.config.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if !config.resume_keys.iter().any(|key| key.eq_ignore_ascii_case(&authorization.public_key)) {
+            return Err(SafeModeError::UnauthorizedKey(authorization.public_key.clone()));
+        }
+        let now = chrono::Utc::now().timestamp() as u64;
+        if authorization.issued_at.abs_diff(now) > config.max_authorization_age_secs {
+            return Err(SafeModeError::StaleAuthorization);
+        }
+        authorization.verify_signature()?;
+
+        let transition = self.record(&mut state, SafeModeAction::Resume, &authorization.reason, authorization.public_key.clone());
+        state.halt = None;
+        self.save(&state).await?;
+        self.halted.store(false, Ordering::SeqCst);
+
+        log::info!("▶️ Left safe mode, authorized by {}: {}", authorization.public_key, authorization.reason);
+        self.publish(&transition);
+        Ok(transition)
+    }
+
+    /// Current safe mode state
+    pub async fn status(&self) -> SafeModeStatus {
+        let state = self.state.read().await;
+        match &state.halt {
+            Some((source, halt)) => SafeModeStatus {
+                active: true,
+                reason: Some(halt.reason.clone()),
+                source: Some(source.clone()),
+                since: Some(halt.at),
+                halt_sequence: Some(halt.sequence),
+                transitions: state.transitions.len() as u64,
+            },
+            None => SafeModeStatus {
+                transitions: state.transitions.len() as u64,
+                ..SafeModeStatus::default()
+            },
+        }
+    }
+
+    /// Every transition so far, oldest first
+    pub async fn transitions(&self) -> Vec<SafeModeTransition> {
+        self.state.read().await.transitions.clone()
+    }
+
+    fn record(&self, state: &mut SafeModeState, action: SafeModeAction, reason: &str, actor: String) -> SafeModeTransition {
+        let transition = SafeModeTransition {
+            sequence: state.transitions.last().map(|t| t.sequence + 1).unwrap_or(1),
+            action,
+            reason: reason.to_string(),
+            actor,
+            at: chrono::Utc::now().timestamp() as u64,
+        };
+        state.transitions.push(transition.clone());
+        transition
+    }
+
+    fn publish(&self, transition: &SafeModeTransition) {
+        if let Some(events) = &self.events {
+            events.publish(NodeEvent::SafeModeChanged { transition: transition.clone() });
+        }
+    }
+
+    async fn save(&self, state: &SafeModeState) -> Result<(), SafeModeError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| SafeModeError::Storage(e.to_string()))?;
+        }
+        let json = serde_json::to_string_pretty(state).map_err(|e| SafeModeError::Storage(e.to_string()))?;
+        tokio::fs::write(path, json).await.map_err(|e| SafeModeError::Storage(e.to_string()))
+    }
+}
+
+fn source_label(source: &HaltSource) -> String {
+    match source {
+        HaltSource::Admin { operator } => format!("admin:{}", operator),
+        HaltSource::Governance { proposal_id } => format!("governance:{}", proposal_id),
+    }
+}
+
+/// Safe mode error types
+#[derive(Debug, thiserror::Error)]
+pub enum SafeModeError {
+    #[error("Node is in safe mode: {0}")]
+    Halted(String),
+    #[error("Node is already in safe mode")]
+    AlreadyHalted,
+    #[error("Node is not in safe mode")]
+    NotHalted,
+    #[error("Key {0} may not resume the node")]
+    UnauthorizedKey(String),
+    #[error("Invalid resume signature")]
+    InvalidSignature,
+    #[error("Resume authorization has expired")]
+    StaleAuthorization,
+    #[error("Resume authorization is for halt {got}, but halt {expected} is in effect")]
+    WrongHalt { expected: u64, got: u64 },
+    #[error("Storage error: {0}")]
+    Storage(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair() -> Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&rand::random::<[u8; 32]>()).unwrap();
+        let public = PublicKey::from(&secret);
+        Keypair { secret, public }
+    }
+
+    fn safe_mode(keypair: &Keypair) -> SafeMode {
+        let safe_mode = SafeMode::new(SafeModeConfig::default());
+        safe_mode.add_resume_key(&hex::encode(keypair.public.as_bytes())).unwrap();
+        safe_mode
+    }
+
+    #[tokio::test]
+    async fn test_halt_and_signed_resume() {
+        let operator = keypair();
+        let safe_mode = safe_mode(&operator);
+        safe_mode.ensure_accepting().await.unwrap();
+
+        let halt = safe_mode.halt(HaltSource::Admin { operator: "ops".to_string() }, "investigating fork").await.unwrap();
+        assert!(safe_mode.is_halted());
+        assert!(matches!(safe_mode.ensure_accepting().await, Err(SafeModeError::Halted(reason)) if reason == "investigating fork"));
+        assert!(matches!(
+            safe_mode.halt(HaltSource::Governance { proposal_id: "p1".to_string() }, "again").await,
+            Err(SafeModeError::AlreadyHalted)
+        ));
+
+        let status = safe_mode.status().await;
+        assert!(status.active);
+        assert_eq!(status.halt_sequence, Some(halt.sequence));
+
+        safe_mode.resume(&ResumeAuthorization::sign(&operator, halt.sequence, "fork resolved")).await.unwrap();
+        assert!(!safe_mode.is_halted());
+        let transitions = safe_mode.transitions().await;
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[1].action, SafeModeAction::Resume);
+    }
+
+    #[tokio::test]
+    async fn test_resume_requires_valid_authorization() {
+        let operator = keypair();
+        let safe_mode = safe_mode(&operator);
+        assert!(matches!(
+            safe_mode.resume(&ResumeAuthorization::sign(&operator, 1, "nothing to resume")).await,
+            Err(SafeModeError::NotHalted)
+        ));
+
+        let halt = safe_mode.halt(HaltSource::Admin { operator: "ops".to_string() }, "maintenance").await.unwrap();
+
+        let stranger = ResumeAuthorization::sign(&keypair(), halt.sequence, "resume");
+        assert!(matches!(safe_mode.resume(&stranger).await, Err(SafeModeError::UnauthorizedKey(_))));
+
+        let replayed = ResumeAuthorization::sign(&operator, halt.sequence + 1, "resume");
+        assert!(matches!(safe_mode.resume(&replayed).await, Err(SafeModeError::WrongHalt { .. })));
+
+        let mut tampered = ResumeAuthorization::sign(&operator, halt.sequence, "resume");
+        tampered.reason = "something else".to_string();
+        assert!(matches!(safe_mode.resume(&tampered).await, Err(SafeModeError::InvalidSignature)));
+
+        let mut stale = ResumeAuthorization::sign(&operator, halt.sequence, "resume");
+        stale.issued_at -= 3600;
+        assert!(matches!(safe_mode.resume(&stale).await, Err(SafeModeError::StaleAuthorization)));
+        assert!(safe_mode.is_halted());
+    }
+
+    #[tokio::test]
+    async fn test_halt_survives_restart() {
+        let path = std::env::temp_dir().join(format!("safe-mode-{}.json", uuid::Uuid::new_v4()));
+        let safe_mode = SafeMode::open(SafeModeConfig::default(), &path).await.unwrap();
+        safe_mode.halt(HaltSource::Governance { proposal_id: "p7".to_string() }, "emergency pause").await.unwrap();
+
+        let reopened = SafeMode::open(SafeModeConfig::default(), &path).await.unwrap();
+        assert!(reopened.is_halted());
+        assert_eq!(reopened.status().await.source, Some(HaltSource::Governance { proposal_id: "p7".to_string() }));
+        let _ = std::fs::remove_file(&path);
+    }
+}
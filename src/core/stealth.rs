@@ -0,0 +1,247 @@
+//! Stealth (one-time) receiving addresses
+//!
+//! A receiver publishes a stealth address: a scan public key `A = a·G` and a
+//! spend public key `B = b·G` on the Ristretto group. For each payment the
+//! sender picks a random `r`, publishes `R = r·G` in the transaction metadata
+//! and pays to the one-time address `P = Hs(r·A)·G + B`. Only the holder of
+//! the scan secret `a` can recompute `Hs(a·R)` and recognise `P`, and only
+//! the holder of `b` can spend from it with `x = Hs(a·R) + b`.
+//!
+//! Validators cannot link `P` to the receiver. What they check is the
+//! derivation proof: a Schnorr proof that the sender knows `r` for the
+//! published `R`, bound to `P`. This rejects announcements copied from other
+//! payments and malformed points that no receiver could ever scan.
+
+use super::{CoreError, Transaction};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_512};
+
+/// Transaction metadata key carrying the stealth announcement
+pub const STEALTH_METADATA_KEY: &str = "stealth";
+
+const SHARED_SECRET_DOMAIN: &[u8] = b"qdag-stealth-shared-v1";
+const PROOF_DOMAIN: &[u8] = b"qdag-stealth-proof-v1";
+
+/// Published scan and spend public keys of a receiver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StealthAddress {
+    pub scan_public: [u8; 32],
+    pub spend_public: [u8; 32],
+}
+
+impl StealthAddress {
+    /// Parse the hex form: scan key followed by spend key
+    pub fn parse(encoded: &str) -> Result<Self, CoreError> {
+        let bytes = hex::decode(encoded.trim()).map_err(|_| CoreError::InvalidAddress(encoded.to_string()))?;
+        if bytes.len() != 64 {
+            return Err(CoreError::InvalidAddress(encoded.to_string()));
+        }
+        let address = Self {
+            scan_public: bytes[..32].try_into().expect("checked length"),
+            spend_public: bytes[32..].try_into().expect("checked length"),
+        };
+        decompress(&address.scan_public)?;
+        decompress(&address.spend_public)?;
+        Ok(address)
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}{}", hex::encode(self.scan_public), hex::encode(self.spend_public))
+    }
+
+    /// Derive a one-time address for a new payment
+    pub fn derive_payment(&self) -> Result<(Vec<u8>, StealthAnnouncement), CoreError> {
+        let r = random_scalar();
+        let ephemeral = RISTRETTO_BASEPOINT_POINT * r;
+        let shared = shared_scalar(&(decompress(&self.scan_public)? * r));
+        let one_time = RISTRETTO_BASEPOINT_POINT * shared + decompress(&self.spend_public)?;
+        let receiver = one_time.compress().to_bytes().to_vec();
+        let proof = prove(&r, &ephemeral, &receiver);
+        Ok((receiver, StealthAnnouncement {
+            ephemeral: hex::encode(ephemeral.compress().as_bytes()),
+            proof: hex::encode(proof),
+        }))
+    }
+}
+
+/// Ephemeral key and derivation proof published with a stealth payment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthAnnouncement {
+    /// Hex `R = r·G`
+    pub ephemeral: String,
+    /// Hex Schnorr proof of knowledge of `r`, challenge then response
+    pub proof: String,
+}
+
+impl StealthAnnouncement {
+    /// Announcement in a transaction's JSON metadata, if it carries one
+    ///
+    /// Metadata that is not a JSON object, or has no stealth key, is not a
+    /// stealth payment. A stealth key that does not parse is an error.
+    pub fn from_transaction(transaction: &Transaction) -> Result<Option<Self>, CoreError> {
+        let Some(metadata) = &transaction.metadata else {
+            return Ok(None);
+        };
+        let Ok(serde_json::Value::Object(mut fields)) = serde_json::from_slice::<serde_json::Value>(metadata) else {
+            return Ok(None);
+        };
+        match fields.remove(STEALTH_METADATA_KEY) {
+            Some(value) => serde_json::from_value(value)
+                .map(Some)
+                .map_err(|e| CoreError::InvalidStealthPayment(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Check the derivation proof against the one-time `receiver`
+    pub fn verify(&self, receiver: &[u8]) -> Result<(), CoreError> {
+        let invalid = |reason: &str| CoreError::InvalidStealthPayment(reason.to_string());
+        let receiver_point: [u8; 32] = receiver.try_into().map_err(|_| invalid("receiver is not a one-time address"))?;
+        if decompress(&receiver_point)? == RistrettoPoint::default() {
+            return Err(invalid("receiver is the identity point"));
+        }
+        let ephemeral = decompress(&decode32(&self.ephemeral, "ephemeral key")?)?;
+        let proof = hex::decode(&self.proof).map_err(|_| invalid("proof is not hex"))?;
+        if proof.len() != 64 {
+            return Err(invalid("proof must be 64 bytes"));
+        }
+        let challenge = canonical_scalar(&proof[..32])?;
+        let response = canonical_scalar(&proof[32..])?;
+
+        let commitment = RISTRETTO_BASEPOINT_POINT * response + ephemeral * challenge;
+        if challenge != proof_challenge(&ephemeral, receiver, &commitment) {
+            return Err(invalid("derivation proof does not verify"));
+        }
+        Ok(())
+    }
+}
+
+/// Reject transactions whose stealth announcement does not verify
+pub fn validate_stealth_payment(transaction: &Transaction) -> Result<(), CoreError> {
+    match StealthAnnouncement::from_transaction(transaction)? {
+        Some(announcement) => announcement.verify(&transaction.receiver),
+        None => Ok(()),
+    }
+}
+
+fn prove(r: &Scalar, ephemeral: &RistrettoPoint, receiver: &[u8]) -> [u8; 64] {
+    let k = random_scalar();
+    let commitment = RISTRETTO_BASEPOINT_POINT * k;
+    let challenge = proof_challenge(ephemeral, receiver, &commitment);
+    let response = k - challenge * r;
+    let mut proof = [0u8; 64];
+    proof[..32].copy_from_slice(challenge.as_bytes());
+    proof[32..].copy_from_slice(response.as_bytes());
+    proof
+}
+
+fn proof_challenge(ephemeral: &RistrettoPoint, receiver: &[u8], commitment: &RistrettoPoint) -> Scalar {
+    hash_to_scalar(&[PROOF_DOMAIN, ephemeral.compress().as_bytes(), receiver, commitment.compress().as_bytes()])
+}
+
+fn shared_scalar(shared_point: &RistrettoPoint) -> Scalar {
+    hash_to_scalar(&[SHARED_SECRET_DOMAIN, shared_point.compress().as_bytes()])
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha3_512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&hasher.finalize());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+fn random_scalar() -> Scalar {
+    let mut bytes = [0u8; 64];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+fn decompress(bytes: &[u8; 32]) -> Result<RistrettoPoint, CoreError> {
+    CompressedRistretto(*bytes)
+        .decompress()
+        .ok_or_else(|| CoreError::InvalidStealthPayment(format!("{} is not a valid point", hex::encode(bytes))))
+}
+
+fn decode32(value: &str, field: &str) -> Result<[u8; 32], CoreError> {
+    hex::decode(value)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| CoreError::InvalidStealthPayment(format!("{} must be 32 hex bytes", field)))
+}
+
+fn canonical_scalar(bytes: &[u8]) -> Result<Scalar, CoreError> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| CoreError::InvalidStealthPayment("scalar must be 32 bytes".to_string()))?;
+    Scalar::from_canonical_bytes(bytes)
+        .ok_or_else(|| CoreError::InvalidStealthPayment("non-canonical scalar in proof".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::TransactionId;
+
+    fn stealth_address() -> (Scalar, Scalar, StealthAddress) {
+        let (a, b) = (random_scalar(), random_scalar());
+        let address = StealthAddress {
+            scan_public: (RISTRETTO_BASEPOINT_POINT * a).compress().to_bytes(),
+            spend_public: (RISTRETTO_BASEPOINT_POINT * b).compress().to_bytes(),
+        };
+        (a, b, address)
+    }
+
+    fn payment(receiver: Vec<u8>, announcement: &StealthAnnouncement) -> Transaction {
+        let metadata = serde_json::json!({ STEALTH_METADATA_KEY: announcement }).to_string();
+        Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver,
+            amount: 10,
+            fee: 0,
+            nonce: 1,
+            timestamp: 1_000,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: Some(metadata.into_bytes()),
+        }
+    }
+
+    #[test]
+    fn test_receiver_recovers_one_time_address_and_key() {
+        let (a, b, address) = stealth_address();
+        assert_eq!(StealthAddress::parse(&address.encode()).unwrap(), address);
+
+        let (receiver, announcement) = address.derive_payment().unwrap();
+        let ephemeral = decompress(&decode32(&announcement.ephemeral, "ephemeral key").unwrap()).unwrap();
+        let shared = shared_scalar(&(ephemeral * a));
+        let one_time_secret = shared + b;
+        assert_eq!((RISTRETTO_BASEPOINT_POINT * one_time_secret).compress().as_bytes().to_vec(), receiver);
+    }
+
+    #[test]
+    fn test_validation_rejects_proof_moved_to_another_payment() {
+        let (_, _, address) = stealth_address();
+        let (receiver, announcement) = address.derive_payment().unwrap();
+        assert!(validate_stealth_payment(&payment(receiver, &announcement)).is_ok());
+
+        let (other_receiver, _) = address.derive_payment().unwrap();
+        assert!(matches!(
+            validate_stealth_payment(&payment(other_receiver, &announcement)),
+            Err(CoreError::InvalidStealthPayment(_))
+        ));
+
+        // Ordinary metadata is not a stealth payment
+        let mut plain = payment(vec![2u8; 32], &announcement);
+        plain.metadata = Some(b"genesis".to_vec());
+        assert!(validate_stealth_payment(&plain).is_ok());
+    }
+}
@@ -0,0 +1,145 @@
+//! Escrowed atomic swaps
+//!
+//! Two parties trade assets, the native coin or an issued token, through an
+//! escrow held by the ledger. The initiator picks a secret and locks its leg
+//! under the secret's hash (the hashlock) until an expiry time (the
+//! timelock), naming the counterparty and the asset it wants in return. The
+//! counterparty then locks exactly that. Revealing the secret before expiry
+//! settles both legs at once; after expiry either party can have both legs
+//! refunded. Since both legs live on this ledger, settlement and refund each
+//! move both legs in one storage transaction, so a swap never completes on
+//! one side only.
+//!
+//! Swaps are driven by transaction payloads and take effect when those
+//! transactions finalize; timelocks are judged against the finalizing
+//! transaction's timestamp, so every node reaches the same outcome.
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use super::{amount, Amount};
+use crate::TransactionId;
+
+/// An asset held in balances and escrow
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Asset {
+    /// The chain's own coin
+    Native,
+    /// A token, identified by the transaction that issued it
+    Token(String),
+}
+
+impl Asset {
+    /// Form stored in swap rows
+    pub fn as_column(&self) -> String {
+        match self {
+            Asset::Native => "native".to_string(),
+            Asset::Token(token_id) => format!("token:{}", token_id),
+        }
+    }
+
+    pub fn from_column(column: &str) -> Self {
+        match column.strip_prefix("token:") {
+            Some(token_id) => Asset::Token(token_id.to_string()),
+            None => Asset::Native,
+        }
+    }
+}
+
+/// Hashlock of a swap secret: its hex SHA3-256
+pub fn swap_hashlock(preimage: &[u8]) -> String {
+    hex::encode(Sha3_256::digest(preimage))
+}
+
+/// Where a swap stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapStatus {
+    /// The initiator's leg is locked; waiting for the counterparty
+    Initiated,
+    /// Both legs are locked; waiting for the secret
+    Locked,
+    /// The secret was revealed and both legs settled
+    Completed,
+    /// Expired and refunded
+    Refunded,
+}
+
+impl SwapStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SwapStatus::Initiated => "initiated",
+            SwapStatus::Locked => "locked",
+            SwapStatus::Completed => "completed",
+            SwapStatus::Refunded => "refunded",
+        }
+    }
+
+    pub fn from_str(status: &str) -> Option<Self> {
+        match status {
+            "initiated" => Some(SwapStatus::Initiated),
+            "locked" => Some(SwapStatus::Locked),
+            "completed" => Some(SwapStatus::Completed),
+            "refunded" => Some(SwapStatus::Refunded),
+            _ => None,
+        }
+    }
+
+    /// Whether the swap can no longer change
+    pub fn is_settled(&self) -> bool {
+        matches!(self, SwapStatus::Completed | SwapStatus::Refunded)
+    }
+}
+
+/// What one party puts into a swap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapLeg {
+    /// Hex address of the party locking this leg
+    pub party: String,
+    pub asset: Asset,
+    #[serde(with = "amount")]
+    pub amount: Amount,
+}
+
+/// A swap between two parties
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtomicSwap {
+    /// Hex SHA3-256 of the secret; also the swap's ID
+    pub hashlock: String,
+    pub initiator: SwapLeg,
+    /// Leg the counterparty must lock, whether or not it has yet
+    pub counterparty: SwapLeg,
+    /// Unix time from which the swap can only be refunded
+    pub expires_at: u64,
+    pub status: SwapStatus,
+    /// Hex secret, once revealed
+    pub preimage: Option<String>,
+    pub initiated_by: TransactionId,
+    pub updated_at: u64,
+}
+
+impl AtomicSwap {
+    /// Whether `address` is one of the two parties
+    pub fn is_party(&self, address: &str) -> bool {
+        self.initiator.party == address || self.counterparty.party == address
+    }
+
+    pub fn is_expired(&self, at: u64) -> bool {
+        at >= self.expires_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_columns_round_trip() {
+        for asset in [Asset::Native, Asset::Token("ab12".to_string())] {
+            assert_eq!(Asset::from_column(&asset.as_column()), asset);
+        }
+        assert_eq!(serde_json::to_value(Asset::Native).unwrap(), serde_json::json!("native"));
+        assert_eq!(serde_json::to_value(Asset::Token("ab".to_string())).unwrap(), serde_json::json!({"token": "ab"}));
+        assert_eq!(swap_hashlock(b"secret").len(), 64);
+    }
+}
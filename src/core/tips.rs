@@ -0,0 +1,425 @@
+//! Reputation-weighted tip selection
+//!
+//! Parent selection scores every tip instead of sampling by raw node weight.
+//! The default scorer combines the tip's cumulative weight with its sender's
+//! reputation and the fee it paid per kilobyte, and divides the score among
+//! tips from the same sender once that sender holds more than its share of
+//! the tip set. A spammer flooding the DAG therefore gets few approvals: its
+//! tips split one sender's score between them, and each rejected submission
+//! lowers that score further. The scorer is pluggable and its parameters can
+//! be changed through governance under the `tip_selection.` prefix.
+
+use crate::TransactionId;
+use crate::core::{Amount, DAGNode};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Prefix of governance parameters that tune tip selection
+pub const TIP_SELECTION_PARAMETER_PREFIX: &str = "tip_selection.";
+
+/// Reputation assigned to senders that have not been seen yet
+pub const NEUTRAL_REPUTATION: f64 = 0.5;
+
+/// Tip selection parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TipSelectionParams {
+    /// Exponent applied to the tip's log cumulative weight
+    pub weight_factor: f64,
+    /// Exponent applied to the sender's reputation
+    pub reputation_factor: f64,
+    /// Exponent applied to the tip's fee density
+    pub fee_density_factor: f64,
+    /// Tips from senders below this reputation are only picked when nothing else is available
+    pub min_reputation: f64,
+    /// Tips one sender may hold before their scores are shared
+    pub max_tips_per_sender: usize,
+    /// Reputation gained for each accepted transaction
+    pub reputation_reward: f64,
+    /// Reputation lost for each rejected transaction
+    pub reputation_penalty: f64,
+}
+
+impl Default for TipSelectionParams {
+    fn default() -> Self {
+        Self {
+            weight_factor: 1.0,
+            reputation_factor: 2.0,
+            fee_density_factor: 0.5,
+            min_reputation: 0.1,
+            max_tips_per_sender: 4,
+            reputation_reward: 0.01,
+            reputation_penalty: 0.1,
+        }
+    }
+}
+
+impl TipSelectionParams {
+    /// Check that every parameter is in range
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("weight_factor", self.weight_factor),
+            ("reputation_factor", self.reputation_factor),
+            ("fee_density_factor", self.fee_density_factor),
+        ] {
+            if !(0.0..=10.0).contains(&value) {
+                return Err(format!("{} must be between 0 and 10", name));
+            }
+        }
+        for (name, value) in [
+            ("min_reputation", self.min_reputation),
+            ("reputation_reward", self.reputation_reward),
+            ("reputation_penalty", self.reputation_penalty),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{} must be between 0 and 1", name));
+            }
+        }
+        if self.max_tips_per_sender == 0 {
+            return Err("max_tips_per_sender must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Copy with one governance parameter changed
+    ///
+    /// `parameter` is the full governance name, e.g. `tip_selection.weight_factor`.
+    pub fn with_parameter(&self, parameter: &str, value: &serde_json::Value) -> Result<Self, String> {
+        let field = parameter
+            .strip_prefix(TIP_SELECTION_PARAMETER_PREFIX)
+            .ok_or_else(|| format!("{} is not a tip selection parameter", parameter))?;
+
+        let mut params = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        match params.get_mut(field) {
+            Some(slot) => *slot = value.clone(),
+            None => return Err(format!("Unknown tip selection parameter: {}", field)),
+        }
+
+        let params: Self = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid value for {}: {}", parameter, e))?;
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+/// Tip being scored for parent selection
+#[derive(Debug, Clone)]
+pub struct TipCandidate<'a> {
+    pub node: &'a DAGNode,
+    /// Weight of the tip plus all of its approvers
+    pub cumulative_weight: u64,
+    /// Reputation of the tip's sender (0.0-1.0)
+    pub sender_reputation: f64,
+    /// Fee paid per kilobyte of serialized transaction
+    pub fee_density: f64,
+    /// Number of current tips from the same sender
+    pub sender_tips: usize,
+}
+
+/// Scoring function used for parent selection
+pub trait TipScorer: Send + Sync {
+    /// Scorer name for logs and status output
+    fn name(&self) -> &str;
+
+    /// Relative selection weight of a tip; zero or less excludes it
+    fn score(&self, candidate: &TipCandidate<'_>, params: &TipSelectionParams) -> f64;
+}
+
+/// Default scorer combining cumulative weight, reputation and fee density
+#[derive(Debug, Default, Clone)]
+pub struct ReputationTipScorer;
+
+impl TipScorer for ReputationTipScorer {
+    fn name(&self) -> &str {
+        "reputation_weighted"
+    }
+
+    fn score(&self, candidate: &TipCandidate<'_>, params: &TipSelectionParams) -> f64 {
+        let weight = (1.0 + (candidate.cumulative_weight as f64).ln_1p()).powf(params.weight_factor);
+        let reputation = candidate.sender_reputation.clamp(0.0, 1.0).powf(params.reputation_factor);
+        let fees = (1.0 + candidate.fee_density.max(0.0)).ln_1p().powf(params.fee_density_factor);
+
+        // Past its allowance a sender's tips share one sender's worth of score
+        let share = if candidate.sender_tips > params.max_tips_per_sender {
+            params.max_tips_per_sender as f64 / candidate.sender_tips as f64
+        } else {
+            1.0
+        };
+
+        weight * reputation * fees * share
+    }
+}
+
+/// Reputation of transaction senders as seen by this node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SenderReputation {
+    scores: HashMap<Vec<u8>, f64>,
+}
+
+impl SenderReputation {
+    /// Current reputation of a sender
+    pub fn get(&self, sender: &[u8]) -> f64 {
+        self.scores.get(sender).copied().unwrap_or(NEUTRAL_REPUTATION)
+    }
+
+    /// Adjust a sender's reputation, keeping it within 0.0-1.0
+    pub fn adjust(&mut self, sender: &[u8], delta: f64) -> f64 {
+        let score = (self.get(sender) + delta).clamp(0.0, 1.0);
+        self.scores.insert(sender.to_vec(), score);
+        score
+    }
+
+    /// Number of senders with a recorded reputation
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Whether no sender has a recorded reputation
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+}
+
+/// Tip selection state kept by the DAG
+pub struct TipSelector {
+    scorer: Arc<dyn TipScorer>,
+    params: Arc<RwLock<TipSelectionParams>>,
+    reputation: SenderReputation,
+    fee_densities: HashMap<TransactionId, f64>,
+}
+
+impl std::fmt::Debug for TipSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TipSelector")
+            .field("scorer", &self.scorer.name())
+            .field("params", &self.params())
+            .field("senders", &self.reputation.len())
+            .finish()
+    }
+}
+
+impl Default for TipSelector {
+    fn default() -> Self {
+        Self::new(TipSelectionParams::default())
+    }
+}
+
+impl TipSelector {
+    /// Create a selector using the default scorer
+    pub fn new(params: TipSelectionParams) -> Self {
+        Self {
+            scorer: Arc::new(ReputationTipScorer),
+            params: Arc::new(RwLock::new(params)),
+            reputation: SenderReputation::default(),
+            fee_densities: HashMap::new(),
+        }
+    }
+
+    /// Replace the scoring function
+    pub fn set_scorer(&mut self, scorer: Arc<dyn TipScorer>) {
+        log::info!("🎯 Tip scorer set to {}", scorer.name());
+        self.scorer = scorer;
+    }
+
+    /// Name of the active scorer
+    pub fn scorer_name(&self) -> &str {
+        self.scorer.name()
+    }
+
+    /// Current parameters
+    pub fn params(&self) -> TipSelectionParams {
+        self.params.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Shared handle to the parameters, used by governance to apply changes
+    pub fn params_handle(&self) -> Arc<RwLock<TipSelectionParams>> {
+        self.params.clone()
+    }
+
+    /// Sender reputations
+    pub fn reputation(&self) -> &SenderReputation {
+        &self.reputation
+    }
+
+    /// Record an accepted transaction from `sender`
+    pub fn reward_sender(&mut self, sender: &[u8]) {
+        let reward = self.params().reputation_reward;
+        self.reputation.adjust(sender, reward);
+    }
+
+    /// Record a rejected transaction from `sender`
+    pub fn penalize_sender(&mut self, sender: &[u8]) {
+        let penalty = self.params().reputation_penalty;
+        let score = self.reputation.adjust(sender, -penalty);
+        log::debug!("📉 Sender {} reputation lowered to {:.3}", hex::encode(sender), score);
+    }
+
+    /// Record the fee paid by a transaction of `size` serialized bytes
+    pub fn record_fee(&mut self, tx_id: &TransactionId, fee: Amount, size: usize) {
+        let density = fee as f64 * 1024.0 / size.max(1) as f64;
+        self.fee_densities.insert(tx_id.clone(), density);
+    }
+
+    /// Fee density of a transaction, zero if no fee was recorded
+    pub fn fee_density(&self, tx_id: &TransactionId) -> f64 {
+        self.fee_densities.get(tx_id).copied().unwrap_or(0.0)
+    }
+
+    /// Forget fee data for a transaction that is no longer a tip
+    pub fn forget(&mut self, tx_id: &TransactionId) {
+        self.fee_densities.remove(tx_id);
+    }
+
+    /// Pick up to `count` distinct tips, weighted by score
+    ///
+    /// `cumulative_weight` returns the cumulative weight of a tip. Tips from
+    /// senders below the minimum reputation are only used when every other
+    /// tip has been picked or scored zero.
+    pub fn select<F>(&self, tips: &[&DAGNode], count: usize, cumulative_weight: F) -> Vec<TransactionId>
+    where
+        F: Fn(&TransactionId) -> u64,
+    {
+        let params = self.params();
+
+        let mut sender_tips: HashMap<&[u8], usize> = HashMap::new();
+        for node in tips {
+            *sender_tips.entry(node.transaction.sender.as_slice()).or_insert(0) += 1;
+        }
+
+        let mut preferred = Vec::new();
+        let mut fallback = Vec::new();
+        for node in tips {
+            let candidate = TipCandidate {
+                node,
+                cumulative_weight: cumulative_weight(&node.transaction.id),
+                sender_reputation: self.reputation.get(&node.transaction.sender),
+                fee_density: self.fee_density(&node.transaction.id),
+                sender_tips: sender_tips[node.transaction.sender.as_slice()],
+            };
+            let score = self.scorer.score(&candidate, &params);
+            if candidate.sender_reputation >= params.min_reputation && score > 0.0 {
+                preferred.push((node.transaction.id.clone(), score));
+            } else {
+                fallback.push(node.transaction.id.clone());
+            }
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut selected = Vec::new();
+        while selected.len() < count && !preferred.is_empty() {
+            let total: f64 = preferred.iter().map(|(_, score)| score).sum();
+            let mut target = rng.gen_range(0.0..total);
+            let mut index = preferred.len() - 1;
+            for (i, (_, score)) in preferred.iter().enumerate() {
+                if target < *score {
+                    index = i;
+                    break;
+                }
+                target -= score;
+            }
+            selected.push(preferred.swap_remove(index).0);
+        }
+
+        for tx_id in fallback {
+            if selected.len() >= count {
+                break;
+            }
+            selected.push(tx_id);
+        }
+
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{NodeStatus, QuantumProof, Transaction};
+
+    fn tip(sender: u8) -> DAGNode {
+        DAGNode {
+            transaction: Transaction {
+                id: TransactionId::new(),
+                sender: vec![sender; 32],
+                receiver: vec![0u8; 32],
+                amount: 1,
+                fee: 0,
+                nonce: 0,
+                timestamp: 0,
+                parents: vec![],
+                signature: vec![],
+                signature_scheme: Default::default(),
+                quantum_proof: QuantumProof {
+                    prime_hash: vec![],
+                    resistance_score: 80,
+                    proof_timestamp: 0,
+                },
+                metadata: None,
+            },
+            children: vec![],
+            weight: 80,
+            confidence: 0.0,
+            status: NodeStatus::Pending,
+            quantum_score: 80,
+        }
+    }
+
+    #[test]
+    fn test_spam_tips_attract_fewer_approvals() {
+        let mut selector = TipSelector::default();
+        let honest: Vec<DAGNode> = (1..=4).map(tip).collect();
+        let spam: Vec<DAGNode> = (0..40).map(|_| tip(0xff)).collect();
+        for node in &honest {
+            selector.reward_sender(&node.transaction.sender);
+            selector.record_fee(&node.transaction.id, 10, 256);
+        }
+        for _ in 0..3 {
+            selector.penalize_sender(&[0xff; 32]);
+        }
+
+        let tips: Vec<&DAGNode> = honest.iter().chain(spam.iter()).collect();
+        let mut spam_picks = 0;
+        for _ in 0..200 {
+            let picked = selector.select(&tips, 2, |_| 80);
+            assert_eq!(picked.len(), 2);
+            assert_ne!(picked[0], picked[1]);
+            spam_picks += picked
+                .iter()
+                .filter(|id| spam.iter().any(|node| &node.transaction.id == *id))
+                .count();
+        }
+
+        // Spam is 90% of the tip set but should win only a small share of approvals
+        assert!(spam_picks < 80, "spam tips picked {} of 400 times", spam_picks);
+    }
+
+    #[test]
+    fn test_low_reputation_tips_are_fallback_only() {
+        let mut selector = TipSelector::default();
+        let good = tip(1);
+        let bad = tip(2);
+        for _ in 0..10 {
+            selector.penalize_sender(&bad.transaction.sender);
+        }
+
+        let tips = vec![&good, &bad];
+        assert_eq!(selector.select(&tips, 1, |_| 1), vec![good.transaction.id.clone()]);
+        // With nothing better left the low-reputation tip is still usable
+        assert_eq!(selector.select(&tips, 2, |_| 1).len(), 2);
+    }
+
+    #[test]
+    fn test_governed_parameter_changes() {
+        let params = TipSelectionParams::default();
+        let updated = params
+            .with_parameter("tip_selection.reputation_factor", &serde_json::json!(3.0))
+            .unwrap();
+        assert_eq!(updated.reputation_factor, 3.0);
+
+        assert!(params.with_parameter("tip_selection.min_reputation", &serde_json::json!(1.5)).is_err());
+        assert!(params.with_parameter("tip_selection.unknown", &serde_json::json!(1)).is_err());
+        assert!(params.with_parameter("block_size", &serde_json::json!(1)).is_err());
+    }
+}
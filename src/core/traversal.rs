@@ -0,0 +1,231 @@
+//! Walking the DAG
+//!
+//! Ancestors are reached through parent links and descendants through
+//! children, breadth first, so nearer transactions come first. `Traversal`
+//! visits every node in topological order: a transaction only after all of
+//! its parents, ties broken by timestamp and then ID so the order is the
+//! same on every run.
+//!
+//! Like weights and inclusion proofs, traversal covers the in-memory part of
+//! the DAG only; parents that were pruned or not paged in are skipped.
+
+use super::{CoreError, DAGCore, DAGNode};
+use crate::TransactionId;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+/// Nodes within `depth` parent links of `tx_id`, nearest first
+pub fn ancestors<'a>(
+    nodes: &'a HashMap<TransactionId, DAGNode>,
+    tx_id: &TransactionId,
+    depth: usize,
+) -> Result<Vec<&'a DAGNode>, CoreError> {
+    walk(nodes, tx_id, depth, |node| &node.transaction.parents)
+}
+
+/// Nodes within `depth` child links of `tx_id`, nearest first
+pub fn descendants<'a>(
+    nodes: &'a HashMap<TransactionId, DAGNode>,
+    tx_id: &TransactionId,
+    depth: usize,
+) -> Result<Vec<&'a DAGNode>, CoreError> {
+    walk(nodes, tx_id, depth, |node| &node.children)
+}
+
+fn walk<'a>(
+    nodes: &'a HashMap<TransactionId, DAGNode>,
+    tx_id: &TransactionId,
+    depth: usize,
+    next: impl Fn(&'a DAGNode) -> &'a Vec<TransactionId>,
+) -> Result<Vec<&'a DAGNode>, CoreError> {
+    let start = nodes.get(tx_id).ok_or_else(|| CoreError::TransactionNotFound(tx_id.clone()))?;
+
+    let mut seen = HashSet::from([tx_id]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    let mut found = Vec::new();
+    while let Some((node, distance)) = queue.pop_front() {
+        if distance == depth {
+            continue;
+        }
+        for id in next(node) {
+            let Some(neighbour) = nodes.get(id) else {
+                continue;
+            };
+            if seen.insert(id) {
+                found.push(neighbour);
+                queue.push_back((neighbour, distance + 1));
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Node ready to be visited, ordered by timestamp then ID
+struct Ready<'a>(&'a DAGNode);
+
+impl Ready<'_> {
+    fn key(&self) -> (u64, &[u8]) {
+        (self.0.transaction.timestamp, self.0.transaction.id.as_bytes())
+    }
+}
+
+impl PartialEq for Ready<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Ready<'_> {}
+
+impl PartialOrd for Ready<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ready<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Iterator over DAG nodes in topological order
+pub struct Traversal<'a> {
+    nodes: &'a HashMap<TransactionId, DAGNode>,
+    /// Parents not yet visited, per node still waiting on some
+    waiting: HashMap<&'a TransactionId, usize>,
+    ready: BinaryHeap<Reverse<Ready<'a>>>,
+}
+
+impl<'a> Traversal<'a> {
+    pub fn new(nodes: &'a HashMap<TransactionId, DAGNode>) -> Self {
+        let mut waiting = HashMap::new();
+        let mut ready = BinaryHeap::new();
+        for (id, node) in nodes {
+            let parents = node.transaction.parents.iter().filter(|parent| nodes.contains_key(*parent)).count();
+            if parents == 0 {
+                ready.push(Reverse(Ready(node)));
+            } else {
+                waiting.insert(id, parents);
+            }
+        }
+        Self { nodes, waiting, ready }
+    }
+}
+
+impl<'a> Iterator for Traversal<'a> {
+    type Item = &'a DAGNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse(Ready(node)) = self.ready.pop()?;
+        for child in &node.children {
+            let Some(remaining) = self.waiting.get_mut(child) else {
+                continue;
+            };
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.waiting.remove(child);
+                if let Some(child) = self.nodes.get(child) {
+                    self.ready.push(Reverse(Ready(child)));
+                }
+            }
+        }
+        Some(node)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.ready.len(), Some(self.ready.len() + self.waiting.len()))
+    }
+}
+
+impl DAGCore {
+    /// Transactions approved by `tx_id` directly or through up to `depth` links
+    pub fn get_ancestors(&self, tx_id: &TransactionId, depth: usize) -> Result<Vec<&DAGNode>, CoreError> {
+        ancestors(&self.transactions, tx_id, depth)
+    }
+
+    /// Transactions approving `tx_id` directly or through up to `depth` links
+    pub fn get_descendants(&self, tx_id: &TransactionId, depth: usize) -> Result<Vec<&DAGNode>, CoreError> {
+        descendants(&self.transactions, tx_id, depth)
+    }
+
+    /// Every in-memory node, parents before children
+    pub fn traverse(&self) -> Traversal<'_> {
+        Traversal::new(&self.transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{NodeStatus, QuantumProof, Transaction};
+
+    fn add(nodes: &mut HashMap<TransactionId, DAGNode>, timestamp: u64, parents: Vec<TransactionId>) -> TransactionId {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 0,
+            nonce: timestamp,
+            timestamp,
+            parents: parents.clone(),
+            signature: vec![7u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        let id = transaction.id.clone();
+        for parent in &parents {
+            if let Some(parent) = nodes.get_mut(parent) {
+                parent.children.push(id.clone());
+            }
+        }
+        nodes.insert(id.clone(), DAGNode {
+            transaction,
+            children: vec![],
+            weight: 1,
+            confidence: 0.0,
+            status: NodeStatus::Pending,
+            quantum_score: 80,
+        });
+        id
+    }
+
+    fn ids(nodes: Vec<&DAGNode>) -> Vec<TransactionId> {
+        nodes.into_iter().map(|node| node.transaction.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_ancestors_and_descendants_by_depth() {
+        let mut nodes = HashMap::new();
+        let genesis = add(&mut nodes, 0, vec![]);
+        let a = add(&mut nodes, 1, vec![genesis.clone()]);
+        let b = add(&mut nodes, 2, vec![genesis.clone()]);
+        let c = add(&mut nodes, 3, vec![a.clone(), b.clone()]);
+        let d = add(&mut nodes, 4, vec![c.clone()]);
+
+        assert_eq!(ids(ancestors(&nodes, &d, 1).unwrap()), vec![c.clone()]);
+        // The genesis is reached through both a and b but listed once
+        assert_eq!(ids(ancestors(&nodes, &d, 10).unwrap()), vec![c.clone(), a.clone(), b.clone(), genesis.clone()]);
+        assert_eq!(ids(descendants(&nodes, &genesis, 2).unwrap()), vec![a, b, c]);
+        assert!(descendants(&nodes, &d, 5).unwrap().is_empty());
+        assert!(ancestors(&nodes, &d, 0).unwrap().is_empty());
+        assert!(matches!(ancestors(&nodes, &TransactionId::new(), 1), Err(CoreError::TransactionNotFound(_))));
+    }
+
+    #[test]
+    fn test_traversal_visits_parents_first() {
+        let mut nodes = HashMap::new();
+        // A parent that is no longer in memory does not hold back its children
+        let pruned = TransactionId::new();
+        let root = add(&mut nodes, 10, vec![pruned]);
+        let late = add(&mut nodes, 30, vec![root.clone()]);
+        let early = add(&mut nodes, 20, vec![root.clone()]);
+        let join = add(&mut nodes, 15, vec![late.clone(), early.clone()]);
+
+        let order = ids(Traversal::new(&nodes).collect());
+        assert_eq!(order, vec![root, early, late, join]);
+    }
+}
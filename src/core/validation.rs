@@ -0,0 +1,165 @@
+//! Parallel transaction validation
+//!
+//! Signature, prime-layer and signature-policy checks need nothing but the
+//! transaction itself, so they can run for many transactions at once. The
+//! pipeline runs each check on a tokio task and bounds how many run at a
+//! time by the configured worker count. Results come back in submission
+//! order, and inserting into the DAG stays sequential, so a batch behaves as
+//! if it had been validated one by one.
+
+use super::Transaction;
+use crate::identity::SignaturePolicy;
+use crate::math::PrimeLayer;
+use crate::security::SecurityManager;
+use crate::BlockchainError;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Validation pipeline configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidationConfig {
+    /// Transactions validated concurrently
+    pub workers: usize,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            workers: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+        }
+    }
+}
+
+/// Validates transactions concurrently on the tokio runtime
+#[derive(Clone)]
+pub struct ValidationPipeline {
+    security: Arc<SecurityManager>,
+    prime_layer: Arc<PrimeLayer>,
+    workers: Arc<AtomicUsize>,
+}
+
+impl ValidationPipeline {
+    pub fn new(security: Arc<SecurityManager>, prime_layer: Arc<PrimeLayer>, config: &ValidationConfig) -> Self {
+        Self {
+            security,
+            prime_layer,
+            workers: Arc::new(AtomicUsize::new(config.workers.max(1))),
+        }
+    }
+
+    /// Change the worker count; batches already running keep theirs
+    pub fn set_config(&self, config: &ValidationConfig) {
+        self.workers.store(config.workers.max(1), Ordering::Relaxed);
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers.load(Ordering::Relaxed)
+    }
+
+    /// Run every check on one transaction
+    pub async fn validate(&self, transaction: &Transaction, policy: &SignaturePolicy) -> Result<(), BlockchainError> {
+        self.security.validate_transaction(transaction).await?;
+        self.prime_layer.validate_transaction(transaction).await?;
+        policy.check(transaction)?;
+        Ok(())
+    }
+
+    /// Validate a batch concurrently, returning one result per transaction in input order
+    pub async fn validate_batch(&self, transactions: &[Transaction], policy: &SignaturePolicy) -> Vec<Result<(), BlockchainError>> {
+        if transactions.len() <= 1 {
+            let mut results = Vec::with_capacity(transactions.len());
+            for transaction in transactions {
+                results.push(self.validate(transaction, policy).await);
+            }
+            return results;
+        }
+
+        let permits = Arc::new(Semaphore::new(self.workers()));
+        let mut tasks = JoinSet::new();
+        for (index, transaction) in transactions.iter().cloned().enumerate() {
+            let pipeline = self.clone();
+            let policy = policy.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                (index, pipeline.validate(&transaction, &policy).await)
+            });
+        }
+
+        let mut results: Vec<Result<(), BlockchainError>> = (0..transactions.len())
+            .map(|_| Err(BlockchainError::Other("Validation task failed".to_string())))
+            .collect();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = result,
+                // The transaction keeps the failure placeholder
+                Err(e) => log::error!("❌ Validation task failed: {}", e),
+            }
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::security::SecurityConfig;
+    use crate::TransactionId;
+
+    fn pipeline(workers: usize) -> ValidationPipeline {
+        let security = SecurityManager::new(&SecurityConfig {
+            quantum_resistance_level: 0,
+            signature_scheme: "dilithium".to_string(),
+            key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
+        })
+        .unwrap();
+        ValidationPipeline::new(Arc::new(security), Arc::new(PrimeLayer::new().unwrap()), &ValidationConfig { workers })
+    }
+
+    fn transaction(nonce: u64) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 0,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 0, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_batch_returns_a_result_per_transaction() {
+        let pipeline = pipeline(2);
+        let batch: Vec<Transaction> = (0..8).map(|nonce| transaction(nonce)).collect();
+
+        let results = pipeline.validate_batch(&batch, &SignaturePolicy::default()).await;
+        assert_eq!(results.len(), batch.len());
+        // Unsigned transactions fail the security check
+        assert!(results.iter().all(|result| matches!(result, Err(BlockchainError::Security(_)))));
+        assert!(pipeline.validate_batch(&[], &SignaturePolicy::default()).await.is_empty());
+    }
+
+    #[test]
+    fn test_worker_count_is_at_least_one() {
+        let pipeline = pipeline(0);
+        assert_eq!(pipeline.workers(), 1);
+        pipeline.set_config(&ValidationConfig { workers: 6 });
+        assert_eq!(pipeline.workers(), 6);
+        assert!(ValidationConfig::default().workers >= 1);
+    }
+}
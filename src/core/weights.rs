@@ -0,0 +1,233 @@
+//! Cumulative weight and depth computation
+//!
+//! Both values are computed with explicit stacks rather than recursion, so
+//! long chains cannot overflow the thread stack, and memoized per node.
+//! A node's depth depends only on its ancestors and never changes once it is
+//! in the DAG. Its cumulative weight depends on its descendants, so adding a
+//! transaction drops the cached weights of the new transaction's ancestors
+//! and nothing else.
+//!
+//! The cache keeps one invariant: if a node's weight is cached, so are the
+//! weights of all its descendants. Invalidation can therefore stop at the
+//! first ancestor that is not cached.
+
+use super::DAGNode;
+use crate::TransactionId;
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock as StdRwLock;
+
+/// Memoized cumulative weights and depths for `DAGCore`
+#[derive(Debug, Default)]
+pub struct WeightCache {
+    cumulative: StdRwLock<HashMap<TransactionId, u64>>,
+    depths: StdRwLock<HashMap<TransactionId, usize>>,
+}
+
+impl WeightCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Own weight plus the cumulative weight of every approver
+    ///
+    /// An approver reachable along several paths is counted once per path.
+    /// Unknown nodes weigh 0.
+    pub fn cumulative_weight(&self, node_id: &TransactionId, nodes: &HashMap<TransactionId, DAGNode>) -> u64 {
+        let mut cache = self.cumulative.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(weight) = cache.get(node_id) {
+            return *weight;
+        }
+
+        // Post-order walk over children; a node is summed once all of its
+        // children are cached. `visiting` breaks cycles in corrupt data.
+        let mut stack = vec![(node_id.clone(), false)];
+        let mut visiting = HashSet::new();
+        while let Some((id, expanded)) = stack.pop() {
+            if cache.contains_key(&id) {
+                continue;
+            }
+            // Unknown nodes count as 0 and are not cached, since they may arrive later
+            let Some(node) = nodes.get(&id) else {
+                continue;
+            };
+
+            if expanded {
+                let weight = node.children.iter()
+                    .filter_map(|child| cache.get(child))
+                    .fold(node.weight, |total, weight| total.saturating_add(*weight));
+                visiting.remove(&id);
+                cache.insert(id, weight);
+            } else {
+                visiting.insert(id.clone());
+                stack.push((id, true));
+                for child in &node.children {
+                    if !cache.contains_key(child) && !visiting.contains(child) {
+                        stack.push((child.clone(), false));
+                    }
+                }
+            }
+        }
+
+        cache.get(node_id).copied().unwrap_or(0)
+    }
+
+    /// Length of the longest parent chain ending at a node, counting the node
+    ///
+    /// Nodes without parents have depth 1. Unknown nodes have depth 0.
+    pub fn depth(&self, node_id: &TransactionId, nodes: &HashMap<TransactionId, DAGNode>) -> usize {
+        let mut cache = self.depths.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(depth) = cache.get(node_id) {
+            return *depth;
+        }
+
+        let mut stack = vec![(node_id.clone(), false)];
+        let mut visiting = HashSet::new();
+        while let Some((id, expanded)) = stack.pop() {
+            if cache.contains_key(&id) {
+                continue;
+            }
+            // Unknown nodes count as 0 and are not cached, since they may arrive later
+            let Some(node) = nodes.get(&id) else {
+                continue;
+            };
+
+            if expanded {
+                let depth = node.transaction.parents.iter()
+                    .filter_map(|parent| cache.get(parent))
+                    .max()
+                    .copied()
+                    .unwrap_or(0) + 1;
+                visiting.remove(&id);
+                cache.insert(id, depth);
+            } else {
+                visiting.insert(id.clone());
+                stack.push((id, true));
+                for parent in &node.transaction.parents {
+                    if !cache.contains_key(parent) && !visiting.contains(parent) {
+                        stack.push((parent.clone(), false));
+                    }
+                }
+            }
+        }
+
+        cache.get(node_id).copied().unwrap_or(0)
+    }
+
+    /// Drop cached weights that a newly added node changes
+    ///
+    /// Call after the node is inserted and linked as a child of its parents.
+    /// Returns the number of cached weights dropped.
+    pub fn invalidate_ancestors(&self, node_id: &TransactionId, nodes: &HashMap<TransactionId, DAGNode>) -> usize {
+        let mut cache = self.cumulative.write().unwrap_or_else(|e| e.into_inner());
+        let mut dropped = 0;
+        let mut stack: Vec<TransactionId> = nodes.get(node_id)
+            .map(|node| node.transaction.parents.clone())
+            .unwrap_or_default();
+        while let Some(id) = stack.pop() {
+            if cache.remove(&id).is_none() {
+                continue;
+            }
+            dropped += 1;
+            if let Some(node) = nodes.get(&id) {
+                stack.extend(node.transaction.parents.iter().cloned());
+            }
+        }
+        dropped
+    }
+
+    /// Forget everything, e.g. after the DAG is reloaded
+    pub fn clear(&self) {
+        self.cumulative.write().unwrap_or_else(|e| e.into_inner()).clear();
+        self.depths.write().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Number of cached cumulative weights
+    pub fn cached_weights(&self) -> usize {
+        self.cumulative.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{NodeStatus, QuantumProof, Transaction};
+
+    fn insert(nodes: &mut HashMap<TransactionId, DAGNode>, nonce: u64, weight: u64, parents: &[TransactionId]) -> TransactionId {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 1,
+            fee: 0,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents: parents.to_vec(),
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        let id = transaction.id.clone();
+        for parent in parents {
+            nodes.get_mut(parent).unwrap().children.push(id.clone());
+        }
+        nodes.insert(id.clone(), DAGNode {
+            transaction,
+            children: Vec::new(),
+            weight,
+            confidence: 0.0,
+            status: NodeStatus::Pending,
+            quantum_score: 80,
+        });
+        id
+    }
+
+    #[test]
+    fn test_diamond_counts_each_path() {
+        let mut nodes = HashMap::new();
+        let root = insert(&mut nodes, 0, 1, &[]);
+        let left = insert(&mut nodes, 1, 2, &[root.clone()]);
+        let right = insert(&mut nodes, 2, 3, &[root.clone()]);
+        let join = insert(&mut nodes, 3, 10, &[left.clone(), right.clone()]);
+
+        let cache = WeightCache::new();
+        // Same totals as summing children recursively
+        assert_eq!(cache.cumulative_weight(&root, &nodes), 1 + (2 + 10) + (3 + 10));
+        assert_eq!(cache.cumulative_weight(&left, &nodes), 12);
+        assert_eq!(cache.depth(&join, &nodes), 3);
+        assert_eq!(cache.cumulative_weight(&TransactionId::new(), &nodes), 0);
+    }
+
+    #[test]
+    fn test_long_chain_does_not_overflow() {
+        let mut nodes = HashMap::new();
+        let root = insert(&mut nodes, 0, 1, &[]);
+        let mut tip = root.clone();
+        for nonce in 1..100_000 {
+            tip = insert(&mut nodes, nonce, 1, &[tip]);
+        }
+
+        let cache = WeightCache::new();
+        assert_eq!(cache.cumulative_weight(&root, &nodes), 100_000);
+        assert_eq!(cache.depth(&tip, &nodes), 100_000);
+    }
+
+    #[test]
+    fn test_insert_invalidates_only_ancestors() {
+        let mut nodes = HashMap::new();
+        let root = insert(&mut nodes, 0, 1, &[]);
+        let left = insert(&mut nodes, 1, 1, &[root.clone()]);
+        let right = insert(&mut nodes, 2, 1, &[root.clone()]);
+
+        let cache = WeightCache::new();
+        assert_eq!(cache.cumulative_weight(&root, &nodes), 3);
+        assert_eq!(cache.cached_weights(), 3);
+
+        let child = insert(&mut nodes, 3, 5, &[left.clone()]);
+        assert_eq!(cache.invalidate_ancestors(&child, &nodes), 2);
+        assert_eq!(cache.cached_weights(), 1); // `right` is untouched
+        assert_eq!(cache.cumulative_weight(&root, &nodes), 8);
+        assert_eq!(cache.cumulative_weight(&right, &nodes), 1);
+    }
+}
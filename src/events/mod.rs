@@ -0,0 +1,313 @@
+//! Typed event bus shared by node subsystems
+//!
+//! Subsystems publish what happened (a transaction was accepted, changed
+//! status, the tip set moved, a consensus round finalized, the identity
+//! rotated) instead of
+//! calling whoever cares about it. Metrics, webhooks, indexers and the
+//! WebSocket API subscribe to the bus. Delivery is best effort: a subscriber
+//! that falls more than the channel capacity behind skips the oldest events
+//! and is told how many it missed.
+
+use crate::core::{EvictionReason, NodeStatus, SafeModeTransition, Transaction};
+use crate::storage::CorruptionResolution;
+use crate::metrics::{spawn_instrumented, SloObjective, Subsystem};
+use crate::TransactionId;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Events buffered per subscriber before the slowest ones start skipping
+pub const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+/// Something that happened in the node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum NodeEvent {
+    /// A transaction passed validation and was added to the DAG
+    TxAccepted {
+        transaction: Transaction,
+        /// Relayed by a peer rather than submitted locally
+        from_peer: bool,
+    },
+    /// A DAG node moved between statuses
+    StatusChanged {
+        tx_id: TransactionId,
+        previous: NodeStatus,
+        status: NodeStatus,
+        confidence: f64,
+        /// Timestamp of the transaction, for confirmation latency
+        submitted_at: u64,
+    },
+    /// Transactions joined or left the tip set
+    TipSetChanged {
+        added: Vec<TransactionId>,
+        removed: Vec<TransactionId>,
+        /// Tips after the change
+        tip_count: usize,
+    },
+    /// A consensus round reached finality
+    RoundFinalized {
+        round_number: u64,
+        validator: String,
+        transactions: Vec<TransactionId>,
+        finality_score: f64,
+        duration_ms: u64,
+    },
+    /// The node identity was rotated
+    IdentityRotated {
+        previous_node_id: Option<String>,
+        node_id: String,
+    },
+    /// The node entered or left safe mode
+    SafeModeChanged {
+        transition: SafeModeTransition,
+    },
+    /// A stored row failed checksum verification and was quarantined
+    StorageCorruption {
+        table: String,
+        row_id: TransactionId,
+        resolution: CorruptionResolution,
+    },
+    /// Validator attestations finalized transactions
+    FinalityAdvanced {
+        /// Consensus round whose attestation completed the quorum
+        height: u64,
+        finalized: Vec<TransactionId>,
+    },
+    /// A pending transaction expired unapproved and was evicted
+    TransactionEvicted {
+        tx_id: TransactionId,
+        reason: EvictionReason,
+        /// Unix time the transaction expired at
+        expired_at: u64,
+    },
+    /// Recent consensus rounds were finalized by conflicting validators
+    ForkDetected {
+        round_number: u64,
+        validators: Vec<String>,
+    },
+    /// A double spend was decided and the losing branches rolled back
+    Reorganized {
+        winner: TransactionId,
+        /// Transactions rejected, including ones that had confirmed
+        rejected: Vec<TransactionId>,
+        /// Rejected transactions that had confirmed
+        demoted: Vec<TransactionId>,
+    },
+    /// A consensus service level objective stopped being met
+    SloBreached {
+        slo: String,
+        objective: SloObjective,
+        measured: f64,
+        /// Finalized rounds the measurement covers
+        rounds: usize,
+    },
+    /// A breached service level objective is met again
+    SloRecovered {
+        slo: String,
+        measured: f64,
+        breached_for_secs: u64,
+    },
+}
+
+impl NodeEvent {
+    /// Variant name, as used in the serialized `type` field
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeEvent::TxAccepted { .. } => "TxAccepted",
+            NodeEvent::StatusChanged { .. } => "StatusChanged",
+            NodeEvent::TipSetChanged { .. } => "TipSetChanged",
+            NodeEvent::RoundFinalized { .. } => "RoundFinalized",
+            NodeEvent::IdentityRotated { .. } => "IdentityRotated",
+            NodeEvent::SafeModeChanged { .. } => "SafeModeChanged",
+            NodeEvent::StorageCorruption { .. } => "StorageCorruption",
+            NodeEvent::FinalityAdvanced { .. } => "FinalityAdvanced",
+            NodeEvent::TransactionEvicted { .. } => "TransactionEvicted",
+            NodeEvent::ForkDetected { .. } => "ForkDetected",
+            NodeEvent::Reorganized { .. } => "Reorganized",
+            NodeEvent::SloBreached { .. } => "SloBreached",
+            NodeEvent::SloRecovered { .. } => "SloRecovered",
+        }
+    }
+}
+
+/// Consumer of node events
+#[async_trait::async_trait]
+pub trait EventHandler: Send + Sync {
+    /// Handler name used in logs
+    fn name(&self) -> String;
+
+    /// Handle one event
+    async fn handle(&self, event: &NodeEvent);
+}
+
+/// Broadcast channel of node events
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<NodeEvent>,
+}
+
+impl EventBus {
+    /// Create a bus buffering `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish an event, returning how many subscribers will see it
+    pub fn publish(&self, event: NodeEvent) -> usize {
+        // No subscribers is not an error; the event is simply dropped
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    /// Subscribe to events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of live subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Feed every event to `handler` on a background task until the bus is dropped
+    pub fn spawn_handler(&self, handler: Arc<dyn EventHandler>) -> JoinHandle<()> {
+        let mut receiver = self.subscribe();
+        spawn_instrumented(Subsystem::Core, "event_handler", async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => handler.handle(&event).await,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        log::warn!("📣 Event handler {} fell behind and missed {} event(s)", handler.name(), missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+/// Posts events as JSON to an HTTP endpoint
+pub struct WebhookEventHandler {
+    url: String,
+    /// Event kinds to forward; empty forwards everything
+    kinds: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl WebhookEventHandler {
+    /// Forward events of the given kinds (all if empty) to `url`
+    pub fn new(url: &str, kinds: Vec<String>) -> Self {
+        Self {
+            url: url.to_string(),
+            kinds,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Whether events of `kind` are forwarded
+    pub fn accepts(&self, kind: &str) -> bool {
+        self.kinds.is_empty() || self.kinds.iter().any(|k| k.eq_ignore_ascii_case(kind))
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for WebhookEventHandler {
+    fn name(&self) -> String {
+        format!("webhook {}", self.url)
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        if !self.accepts(event.kind()) {
+            return;
+        }
+
+        let result = self.client.post(&self.url)
+            .json(event)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            log::warn!("📣 Event webhook {} failed: {}", self.url, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn rotated(node_id: &str) -> NodeEvent {
+        NodeEvent::IdentityRotated { previous_node_id: None, node_id: node_id.to_string() }
+    }
+
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait::async_trait]
+    impl EventHandler for Recorder {
+        fn name(&self) -> String {
+            "recorder".to_string()
+        }
+
+        async fn handle(&self, event: &NodeEvent) {
+            if let NodeEvent::IdentityRotated { node_id, .. } = event {
+                self.0.lock().unwrap().push(node_id.clone());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let bus = EventBus::default();
+        assert_eq!(bus.publish(rotated("qd_dropped")), 0);
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        assert_eq!(bus.publish(rotated("qd_new")), 2);
+
+        for receiver in [&mut first, &mut second] {
+            match receiver.recv().await.unwrap() {
+                NodeEvent::IdentityRotated { node_id, .. } => assert_eq!(node_id, "qd_new"),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawned_handler_survives_lag() {
+        let bus = EventBus::new(2);
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        let handle = bus.spawn_handler(recorder.clone());
+
+        for i in 0..5 {
+            bus.publish(rotated(&format!("qd_{}", i)));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        bus.publish(rotated("qd_last"));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let seen = recorder.0.lock().unwrap().clone();
+        assert_eq!(seen.last().map(String::as_str), Some("qd_last"));
+        drop(bus);
+        handle.await.unwrap();
+    }
+
+    #[test]
+    fn test_event_serializes_with_type_tag() {
+        let json = serde_json::to_value(rotated("qd_a")).unwrap();
+        assert_eq!(json["type"], "IdentityRotated");
+        assert_eq!(rotated("qd_a").kind(), "IdentityRotated");
+
+        let webhook = WebhookEventHandler::new("http://localhost/hook", vec!["txaccepted".to_string()]);
+        assert!(webhook.accepts("TxAccepted"));
+        assert!(!webhook.accepts("IdentityRotated"));
+    }
+}
@@ -7,7 +7,7 @@ use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::governance::proposals::{Proposal, ProposalStatus, Vote, ExecutionResult};
+use crate::governance::proposals::{Proposal, ProposalId, ProposalStatus, Vote, VoteType, ExecutionResult};
 
 /// Audit service for governance operations
 pub struct AuditService {
@@ -193,6 +193,54 @@ impl AuditService {
         Ok(filtered)
     }
 
+    /// Reconstruct a proposal's state as of `timestamp` by replaying the audit log
+    ///
+    /// Returns `None` if the proposal had not been created by then.
+    pub async fn reconstruct_proposal_at(
+        &self,
+        proposal_id: &ProposalId,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Option<ProposalSnapshot>, AuditError> {
+        let log = self.audit_log.read().await;
+        let mut entries: Vec<&AuditEntry> = log.iter()
+            .filter(|entry| entry.timestamp <= timestamp)
+            .filter(|entry| entry.details.get("proposal_id").and_then(|v| v.as_str()) == Some(proposal_id.as_str()))
+            .collect();
+        entries.sort_by_key(|entry| entry.timestamp);
+
+        let mut snapshot: Option<ProposalSnapshot> = None;
+        for entry in entries {
+            match entry.event_type.as_str() {
+                "proposal_created" => {
+                    snapshot = Some(ProposalSnapshot::new(proposal_id.clone(), timestamp, entry));
+                },
+                "proposal_status_changed" => {
+                    if let Some(snapshot) = snapshot.as_mut() {
+                        let new_status = entry.details.get("new_status").and_then(|v| v.as_str())
+                            .ok_or_else(|| AuditError::ValidationError(format!("Entry {} has no new_status", entry.id)))?;
+                        snapshot.status = parse_status(new_status)
+                            .ok_or_else(|| AuditError::ValidationError(format!("Unknown proposal status: {}", new_status)))?;
+                        snapshot.events_applied += 1;
+                    }
+                },
+                "vote_cast" => {
+                    if let Some(snapshot) = snapshot.as_mut() {
+                        snapshot.apply_vote(entry)?;
+                    }
+                },
+                "proposal_executed" => {
+                    if let Some(snapshot) = snapshot.as_mut() {
+                        snapshot.status = ProposalStatus::Executed;
+                        snapshot.events_applied += 1;
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        Ok(snapshot)
+    }
+
     /// Get audit statistics
     pub async fn get_audit_stats(&self) -> AuditStats {
         let log = self.audit_log.read().await;
@@ -452,6 +500,98 @@ pub struct AuditEntry {
     pub signature: Option<String>,
 }
 
+/// Proposal state reconstructed from the audit log at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposalSnapshot {
+    pub proposal_id: ProposalId,
+    pub as_of: DateTime<Utc>,
+    pub proposer: String,
+    pub proposal_type: String,
+    pub created_at: DateTime<Utc>,
+    pub status: ProposalStatus,
+    pub for_votes: f64,
+    pub against_votes: f64,
+    pub abstain_votes: f64,
+    pub veto_votes: f64,
+    pub total_power: f64,
+    /// Voting power each voter held when their vote was recorded
+    pub voting_power: HashMap<String, f64>,
+    pub votes_by_voter: HashMap<String, VoteType>,
+    /// Number of audit entries replayed to build the snapshot
+    pub events_applied: usize,
+}
+
+impl ProposalSnapshot {
+    fn new(proposal_id: ProposalId, as_of: DateTime<Utc>, created: &AuditEntry) -> Self {
+        let field = |name: &str| created.details.get(name)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        Self {
+            proposal_id,
+            as_of,
+            proposer: field("proposer"),
+            proposal_type: field("proposal_type"),
+            created_at: created.timestamp,
+            status: ProposalStatus::Discussion,
+            for_votes: 0.0,
+            against_votes: 0.0,
+            abstain_votes: 0.0,
+            veto_votes: 0.0,
+            total_power: 0.0,
+            voting_power: HashMap::new(),
+            votes_by_voter: HashMap::new(),
+            events_applied: 1,
+        }
+    }
+
+    fn apply_vote(&mut self, entry: &AuditEntry) -> Result<(), AuditError> {
+        let voter = entry.details.get("voter").and_then(|v| v.as_str())
+            .ok_or_else(|| AuditError::ValidationError(format!("Entry {} has no voter", entry.id)))?;
+        let power = entry.details.get("voting_power").and_then(|v| v.as_f64())
+            .ok_or_else(|| AuditError::ValidationError(format!("Entry {} has no voting_power", entry.id)))?;
+        let vote_type = match entry.details.get("vote_type").and_then(|v| v.as_str()) {
+            Some("For") => VoteType::For,
+            Some("Against") => VoteType::Against,
+            Some("Abstain") => VoteType::Abstain,
+            Some("Veto") => VoteType::Veto,
+            other => return Err(AuditError::ValidationError(format!("Unknown vote type: {:?}", other))),
+        };
+
+        // Voters cannot vote twice, so a repeat entry indicates a tampered log
+        if self.votes_by_voter.contains_key(voter) {
+            return Err(AuditError::ValidationError(format!("Duplicate vote from {} in entry {}", voter, entry.id)));
+        }
+
+        match vote_type {
+            VoteType::For => self.for_votes += power,
+            VoteType::Against => self.against_votes += power,
+            VoteType::Abstain => self.abstain_votes += power,
+            VoteType::Veto => self.veto_votes += power,
+        }
+        self.total_power += power;
+        self.voting_power.insert(voter.to_string(), power);
+        self.votes_by_voter.insert(voter.to_string(), vote_type);
+        self.events_applied += 1;
+        Ok(())
+    }
+}
+
+fn parse_status(status: &str) -> Option<ProposalStatus> {
+    match status {
+        "Draft" => Some(ProposalStatus::Draft),
+        "Discussion" => Some(ProposalStatus::Discussion),
+        "Voting" => Some(ProposalStatus::Voting),
+        "Approved" => Some(ProposalStatus::Approved),
+        "Rejected" => Some(ProposalStatus::Rejected),
+        "Executed" => Some(ProposalStatus::Executed),
+        "Cancelled" => Some(ProposalStatus::Cancelled),
+        "Expired" => Some(ProposalStatus::Expired),
+        _ => None,
+    }
+}
+
 /// Event severity levels
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum EventSeverity {
@@ -682,4 +822,73 @@ mod tests {
         assert_eq!(stats.event_type_counts.get("event1"), Some(&1));
         assert_eq!(stats.event_type_counts.get("event2"), Some(&1));
     }
+
+    fn test_proposal() -> Proposal {
+        Proposal::new(
+            crate::governance::proposals::ProposalType::ParameterChange(
+                crate::governance::proposals::ParameterChange {
+                    parameter: "block_size".to_string(),
+                    current_value: serde_json::json!(1),
+                    proposed_value: serde_json::json!(2),
+                    rationale: "Test".to_string(),
+                    impact_analysis: crate::governance::proposals::ImpactAnalysis {
+                        performance_impact: crate::governance::proposals::ImpactLevel::Low,
+                        security_impact: crate::governance::proposals::ImpactLevel::Low,
+                        compatibility_impact: crate::governance::proposals::ImpactLevel::Low,
+                        estimated_benefits: "Test".to_string(),
+                        potential_risks: vec![],
+                    },
+                }
+            ),
+            "Test".to_string(),
+            "Test proposal".to_string(),
+            "validator1".to_string(),
+            604800,
+            604800,
+            86400,
+        )
+    }
+
+    fn vote(proposal: &Proposal, voter: &str, vote_type: VoteType, power: f64) -> Vote {
+        Vote::new(proposal.id.clone(), voter.to_string(), vote_type, power, None)
+    }
+
+    #[tokio::test]
+    async fn test_reconstruct_proposal_at() {
+        let audit_service = AuditService::new();
+        let proposal = test_proposal();
+
+        audit_service.log_proposal_created(&proposal).await.unwrap();
+        audit_service.log_proposal_status_changed(&proposal, ProposalStatus::Discussion, ProposalStatus::Voting).await.unwrap();
+        audit_service.log_vote_cast(&vote(&proposal, "alice", VoteType::For, 10.0)).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let checkpoint = Utc::now();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        audit_service.log_vote_cast(&vote(&proposal, "bob", VoteType::Against, 4.0)).await.unwrap();
+        audit_service.log_proposal_status_changed(&proposal, ProposalStatus::Voting, ProposalStatus::Approved).await.unwrap();
+
+        let past = audit_service.reconstruct_proposal_at(&proposal.id, checkpoint).await.unwrap().unwrap();
+        assert_eq!(past.status, ProposalStatus::Voting);
+        assert_eq!(past.for_votes, 10.0);
+        assert_eq!(past.total_power, 10.0);
+        assert_eq!(past.voting_power.get("alice"), Some(&10.0));
+        assert!(!past.voting_power.contains_key("bob"));
+
+        let present = audit_service.reconstruct_proposal_at(&proposal.id, Utc::now()).await.unwrap().unwrap();
+        assert_eq!(present.status, ProposalStatus::Approved);
+        assert_eq!(present.against_votes, 4.0);
+        assert_eq!(present.events_applied, 5);
+    }
+
+    #[tokio::test]
+    async fn test_reconstruct_before_creation() {
+        let audit_service = AuditService::new();
+        let proposal = test_proposal();
+        let before = Utc::now() - chrono::Duration::seconds(1);
+
+        audit_service.log_proposal_created(&proposal).await.unwrap();
+
+        assert!(audit_service.reconstruct_proposal_at(&proposal.id, before).await.unwrap().is_none());
+        assert!(audit_service.reconstruct_proposal_at(&"unknown".to_string(), Utc::now()).await.unwrap().is_none());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,187 @@
+//! Verifiable participation credentials
+//!
+//! Every vote earns the voter a credential: a record linking the voter, the
+//! proposal and a commitment to the vote, signed with the node's Dilithium3
+//! key. The commitment is a salted hash of the vote direction, so a
+//! credential proves participation without revealing how the voter voted.
+//! Only the voter receives the salt and can later open the commitment, e.g.
+//! to a DAO that wants proof of direction.
+
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use sha3::{Digest, Sha3_256};
+use uuid::Uuid;
+use super::proposals::{ProposalId, Vote, VoteType};
+use crate::identity::{IdentityManager, NodeSignature, SignatureType};
+use crate::BlockchainError;
+
+/// Domain separator for vote commitments
+const VOTE_COMMITMENT_DOMAIN: &[u8] = b"qdag-vote-commitment-v1";
+
+/// Domain separator for credential signatures
+const CREDENTIAL_DOMAIN: &[u8] = b"qdag-participation-credential-v1";
+
+/// Signed proof that a voter took part in a proposal's vote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParticipationCredential {
+    pub id: String,
+    pub proposal_id: ProposalId,
+    pub voter: String,
+    /// Hex commitment to the vote direction
+    pub vote_commitment: String,
+    pub issued_at: DateTime<Utc>,
+    /// Issuing node's Dilithium3 signature over `signing_payload()`
+    pub signature: NodeSignature,
+}
+
+/// What a voter gets back for a vote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteReceipt {
+    pub vote: Vote,
+    pub credential: ParticipationCredential,
+    /// Hex salt opening the credential's commitment; not kept by the node
+    pub vote_salt: String,
+}
+
+impl ParticipationCredential {
+    /// Issue a credential for `vote`, returning it with the commitment's salt
+    pub async fn issue(identity: &IdentityManager, vote: &Vote) -> Result<(Self, String), BlockchainError> {
+        let salt: [u8; 32] = rand::random();
+        let vote_commitment = hex::encode(vote_commitment(&vote.proposal_id, &vote.voter, &vote.vote_type, &salt));
+        let id = Uuid::new_v4().to_string();
+        let issued_at = Utc::now();
+
+        let payload = signing_payload(&id, &vote.proposal_id, &vote.voter, &vote_commitment, issued_at);
+        let signature = identity.sign(&payload, SignatureType::Dilithium3).await?;
+
+        let credential = Self {
+            id,
+            proposal_id: vote.proposal_id.clone(),
+            voter: vote.voter.clone(),
+            vote_commitment,
+            issued_at,
+            signature,
+        };
+        Ok((credential, hex::encode(salt)))
+    }
+
+    /// Bytes covered by the signature
+    pub fn signing_payload(&self) -> Vec<u8> {
+        signing_payload(&self.id, &self.proposal_id, &self.voter, &self.vote_commitment, self.issued_at)
+    }
+
+    /// Check the issuer's signature
+    pub async fn verify_signature(&self, verifier: &IdentityManager) -> Result<bool, BlockchainError> {
+        if self.signature.signature_type != SignatureType::Dilithium3 {
+            return Ok(false);
+        }
+        verifier.verify(&self.signing_payload(), &self.signature).await
+    }
+
+    /// Whether `vote_type` and the hex `salt` open the vote commitment
+    pub fn opens_to(&self, vote_type: &VoteType, salt: &str) -> bool {
+        let Ok(salt) = hex::decode(salt) else {
+            return false;
+        };
+        hex::encode(vote_commitment(&self.proposal_id, &self.voter, vote_type, &salt)) == self.vote_commitment
+    }
+}
+
+/// SHA3-256 commitment to a vote direction
+pub fn vote_commitment(proposal_id: &str, voter: &str, vote_type: &VoteType, salt: &[u8]) -> Vec<u8> {
+    let direction: &[u8] = match vote_type {
+        VoteType::For => b"for",
+        VoteType::Against => b"against",
+        VoteType::Abstain => b"abstain",
+        VoteType::Veto => b"veto",
+    };
+    let mut hasher = Sha3_256::new();
+    hasher.update(VOTE_COMMITMENT_DOMAIN);
+    for field in [proposal_id.as_bytes(), voter.as_bytes(), direction, salt] {
+        hasher.update((field.len() as u32).to_le_bytes());
+        hasher.update(field);
+    }
+    hasher.finalize().to_vec()
+}
+
+fn signing_payload(id: &str, proposal_id: &str, voter: &str, vote_commitment: &str, issued_at: DateTime<Utc>) -> Vec<u8> {
+    let mut hasher = Sha3_256::new();
+    hasher.update(CREDENTIAL_DOMAIN);
+    for field in [id, proposal_id, voter, vote_commitment] {
+        hasher.update((field.len() as u32).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(issued_at.timestamp().to_le_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Credentials issued by this node
+#[derive(Debug, Default)]
+pub struct CredentialRegistry {
+    by_id: HashMap<String, ParticipationCredential>,
+    by_voter: HashMap<String, Vec<String>>,
+}
+
+impl CredentialRegistry {
+    pub fn insert(&mut self, credential: ParticipationCredential) {
+        self.by_voter.entry(credential.voter.clone()).or_default().push(credential.id.clone());
+        self.by_id.insert(credential.id.clone(), credential);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ParticipationCredential> {
+        self.by_id.get(id)
+    }
+
+    /// Credentials of a voter, oldest first
+    pub fn for_voter(&self, voter: &str) -> Vec<ParticipationCredential> {
+        self.by_voter.get(voter)
+            .map(|ids| ids.iter().filter_map(|id| self.by_id.get(id)).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_hides_and_binds_direction() {
+        let salt = [7u8; 32];
+        let for_vote = vote_commitment("p1", "alice", &VoteType::For, &salt);
+        assert_eq!(for_vote, vote_commitment("p1", "alice", &VoteType::For, &salt));
+        assert_ne!(for_vote, vote_commitment("p1", "alice", &VoteType::Against, &salt));
+        assert_ne!(for_vote, vote_commitment("p1", "alice", &VoteType::For, &[8u8; 32]));
+        assert_ne!(for_vote, vote_commitment("p2", "alice", &VoteType::For, &salt));
+    }
+
+    #[tokio::test]
+    async fn test_issued_credential_verifies_and_opens() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut identity = IdentityManager::new(temp_dir.path().to_string_lossy().to_string());
+        identity.initialize_identity().await.unwrap();
+        let vote = Vote::new("p1".to_string(), "alice".to_string(), VoteType::Against, 10.0, None);
+
+        let (credential, salt) = ParticipationCredential::issue(&identity, &vote).await.unwrap();
+        assert!(credential.verify_signature(&identity).await.unwrap());
+        assert!(credential.opens_to(&VoteType::Against, &salt));
+        assert!(!credential.opens_to(&VoteType::For, &salt));
+
+        let mut forged = credential.clone();
+        forged.voter = "mallory".to_string();
+        assert!(!forged.verify_signature(&identity).await.unwrap());
+
+        let mut registry = CredentialRegistry::default();
+        registry.insert(credential.clone());
+        assert_eq!(registry.for_voter("alice").len(), 1);
+        assert!(registry.get(&credential.id).is_some());
+    }
+}
@@ -10,7 +10,8 @@ use uuid::Uuid;
 use crate::governance::proposals::{Proposal, ProposalType, ExecutionResult};
 use crate::identity::IdentityManager;
 use crate::security::CryptoService;
-use crate::core::{Block, Transaction};
+use crate::core::{Block, Transaction, HaltSource, SafeMode, TipSelectionParams, tips::TIP_SELECTION_PARAMETER_PREFIX};
+use crate::contracts::{GasScheduleRegistry, GAS_SCHEDULE_PARAMETER};
 
 /// Execution engine for governance proposals
 pub struct ExecutionEngine {
@@ -18,6 +19,9 @@ pub struct ExecutionEngine {
     crypto_service: Arc<CryptoService>,
     execution_history: Arc<RwLock<HashMap<String, ExecutionRecord>>>,
     rollback_manager: RollbackManager,
+    tip_selection: Option<Arc<std::sync::RwLock<TipSelectionParams>>>,
+    gas_schedules: Option<Arc<std::sync::RwLock<GasScheduleRegistry>>>,
+    safe_mode: Option<Arc<SafeMode>>,
 }
 
 impl ExecutionEngine {
@@ -31,9 +35,30 @@ impl ExecutionEngine {
             crypto_service,
             execution_history: Arc::new(RwLock::new(HashMap::new())),
             rollback_manager: RollbackManager::new(),
+            tip_selection: None,
+            gas_schedules: None,
+            safe_mode: None,
         }
     }
 
+    /// Apply `tip_selection.*` parameter changes to these parameters
+    pub fn with_tip_selection(mut self, params: Arc<std::sync::RwLock<TipSelectionParams>>) -> Self {
+        self.tip_selection = Some(params);
+        self
+    }
+
+    /// Register and activate gas schedules from `contracts.gas_schedule` parameter changes
+    pub fn with_gas_schedules(mut self, schedules: Arc<std::sync::RwLock<GasScheduleRegistry>>) -> Self {
+        self.gas_schedules = Some(schedules);
+        self
+    }
+
+    /// Put the node into safe mode on `PauseNetwork` and `EnableMaintenance` actions
+    pub fn with_safe_mode(mut self, safe_mode: Arc<SafeMode>) -> Self {
+        self.safe_mode = Some(safe_mode);
+        self
+    }
+
     /// Execute a proposal
     pub async fn execute_proposal(&self, proposal: &Proposal) -> Result<ExecutionResult, ExecutionError> {
         let execution_id = Uuid::new_v4().to_string();
@@ -53,7 +78,7 @@ impl ExecutionEngine {
                 self.execute_parameter_change(change, execution_id.clone()).await
             },
             ProposalType::EmergencyAction(action) => {
-                self.execute_emergency_action(action, execution_id.clone()).await
+                self.execute_emergency_action(&proposal.id, action, execution_id.clone()).await
             },
             ProposalType::TreasuryManagement(treasury) => {
                 self.execute_treasury_management(treasury, execution_id.clone()).await
@@ -159,6 +184,7 @@ impl ExecutionEngine {
     /// Execute emergency action
     async fn execute_emergency_action(
         &self,
+        proposal_id: &str,
         action: &crate::governance::proposals::EmergencyAction,
         execution_id: String,
     ) -> Result<ExecutionResult, ExecutionError> {
@@ -168,7 +194,7 @@ impl ExecutionEngine {
         // Execute based on action type
         let result = match action.action_type {
             crate::governance::proposals::EmergencyActionType::PauseNetwork => {
-                self.pause_network().await?
+                self.pause_network(proposal_id, &action.reason).await?
             },
             crate::governance::proposals::EmergencyActionType::Rollback => {
                 self.rollback_manager.execute_rollback(action).await?
@@ -177,7 +203,7 @@ impl ExecutionEngine {
                 self.freeze_accounts(&action.affected_components).await?
             },
             crate::governance::proposals::EmergencyActionType::EnableMaintenance => {
-                self.enable_maintenance_mode().await?
+                self.enable_maintenance_mode(proposal_id, &action.reason).await?
             },
         };
 
@@ -224,7 +250,7 @@ impl ExecutionEngine {
             message: "Treasury action executed successfully".to_string(),
             details: serde_json::json!({
                 "action": format!("{:?}", treasury.action),
-                "amount": treasury.amount,
+                "amount": treasury.amount.to_string(),
                 "recipient": treasury.recipient,
                 "purpose": treasury.purpose,
                 "execution_id": execution_id,
@@ -340,6 +366,16 @@ impl ExecutionEngine {
                     return Err(ExecutionError::InvalidParameter("Max supply too small".to_string()));
                 }
             },
+            name if name.starts_with(TIP_SELECTION_PARAMETER_PREFIX) => {
+                self.current_tip_selection()
+                    .with_parameter(name, &change.proposed_value)
+                    .map_err(ExecutionError::InvalidParameter)?;
+            },
+            GAS_SCHEDULE_PARAMETER => {
+                self.current_gas_schedules()
+                    .with_parameter(GAS_SCHEDULE_PARAMETER, &change.proposed_value)
+                    .map_err(ExecutionError::InvalidParameter)?;
+            },
             _ => {
                 // Unknown parameter, allow for extensibility
             }
@@ -348,6 +384,20 @@ impl ExecutionEngine {
         Ok(())
     }
 
+    fn current_gas_schedules(&self) -> GasScheduleRegistry {
+        self.gas_schedules
+            .as_ref()
+            .map(|schedules| schedules.read().unwrap_or_else(|e| e.into_inner()).clone())
+            .unwrap_or_default()
+    }
+
+    fn current_tip_selection(&self) -> TipSelectionParams {
+        self.tip_selection
+            .as_ref()
+            .map(|params| params.read().unwrap_or_else(|e| e.into_inner()).clone())
+            .unwrap_or_default()
+    }
+
     /// Validate emergency action
     async fn validate_emergency_action(
         &self,
@@ -437,13 +487,35 @@ impl ExecutionEngine {
         &self,
         change: &crate::governance::proposals::ParameterChange,
     ) -> Result<(), ExecutionError> {
+        if change.parameter.starts_with(TIP_SELECTION_PARAMETER_PREFIX) {
+            if let Some(params) = &self.tip_selection {
+                let mut params = params.write().unwrap_or_else(|e| e.into_inner());
+                *params = params
+                    .with_parameter(&change.parameter, &change.proposed_value)
+                    .map_err(ExecutionError::InvalidParameter)?;
+                log::info!("🎯 Tip selection parameter {} set to {}", change.parameter, change.proposed_value);
+            }
+            return Ok(());
+        }
+
+        if change.parameter == GAS_SCHEDULE_PARAMETER {
+            if let Some(schedules) = &self.gas_schedules {
+                let mut schedules = schedules.write().unwrap_or_else(|e| e.into_inner());
+                *schedules = schedules
+                    .with_parameter(&change.parameter, &change.proposed_value)
+                    .map_err(ExecutionError::InvalidParameter)?;
+                log::info!("⛽ Gas schedule activations now {:?}", schedules.activations());
+            }
+            return Ok(());
+        }
+
         // Placeholder: Apply parameter change in the system
         Ok(())
     }
 
-    async fn pause_network(&self) -> Result<serde_json::Value, ExecutionError> {
-        // Placeholder: Pause network
-        Ok(serde_json::json!({"status": "paused"}))
+    async fn pause_network(&self, proposal_id: &str, reason: &str) -> Result<serde_json::Value, ExecutionError> {
+        let transition = self.enter_safe_mode(proposal_id, reason).await?;
+        Ok(serde_json::json!({"status": "paused", "safe_mode_transition": transition}))
     }
 
     async fn freeze_accounts(&self, accounts: &[String]) -> Result<serde_json::Value, ExecutionError> {
@@ -451,9 +523,23 @@ impl ExecutionEngine {
         Ok(serde_json::json!({"frozen_accounts": accounts}))
     }
 
-    async fn enable_maintenance_mode(&self) -> Result<serde_json::Value, ExecutionError> {
-        // Placeholder: Enable maintenance mode
-        Ok(serde_json::json!({"status": "maintenance_enabled"}))
+    async fn enable_maintenance_mode(&self, proposal_id: &str, reason: &str) -> Result<serde_json::Value, ExecutionError> {
+        let transition = self.enter_safe_mode(proposal_id, reason).await?;
+        Ok(serde_json::json!({"status": "maintenance_enabled", "safe_mode_transition": transition}))
+    }
+
+    /// Halt transaction acceptance; resuming needs a signed authorization, not a proposal
+    async fn enter_safe_mode(&self, proposal_id: &str, reason: &str) -> Result<Option<u64>, ExecutionError> {
+        let Some(safe_mode) = &self.safe_mode else {
+            return Ok(None);
+        };
+        let source = HaltSource::Governance { proposal_id: proposal_id.to_string() };
+        match safe_mode.halt(source, reason).await {
+            Ok(transition) => Ok(Some(transition.sequence)),
+            // Another action already halted the node
+            Err(crate::core::SafeModeError::AlreadyHalted) => Ok(safe_mode.status().await.halt_sequence),
+            Err(e) => Err(ExecutionError::ExecutionFailed(e.to_string())),
+        }
     }
 
     async fn execute_treasury_transfer(
@@ -488,7 +574,7 @@ impl ExecutionEngine {
         Ok(serde_json::json!({"burn_id": Uuid::new_v4().to_string()}))
     }
 
-    async fn get_treasury_balance(&self) -> Result<u64, ExecutionError> {
+    async fn get_treasury_balance(&self) -> Result<crate::core::Amount, ExecutionError> {
         // Placeholder: Get treasury balance
         Ok(1000000000) // 1 billion tokens
     }
@@ -693,4 +779,91 @@ mod tests {
         let result = engine.validate_parameter_change(&change).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_tip_selection_parameter_change() {
+        let identity_manager = Arc::new(IdentityManager::new().unwrap());
+        let crypto_service = Arc::new(CryptoService::new().unwrap());
+        let params = Arc::new(std::sync::RwLock::new(TipSelectionParams::default()));
+
+        let engine = ExecutionEngine::new(identity_manager, crypto_service)
+            .with_tip_selection(params.clone());
+
+        let mut change = crate::governance::proposals::ParameterChange {
+            parameter: "tip_selection.min_reputation".to_string(),
+            current_value: serde_json::json!(0.1),
+            proposed_value: serde_json::json!(2.0),
+            rationale: "Exclude more spam".to_string(),
+            impact_analysis: crate::governance::proposals::ImpactAnalysis {
+                performance_impact: crate::governance::proposals::ImpactLevel::Low,
+                security_impact: crate::governance::proposals::ImpactLevel::Medium,
+                compatibility_impact: crate::governance::proposals::ImpactLevel::Low,
+                estimated_benefits: "Fewer approvals for spam".to_string(),
+                potential_risks: vec!["Slower confirmation for new senders".to_string()],
+            },
+        };
+        assert!(engine.validate_parameter_change(&change).await.is_err());
+
+        change.proposed_value = serde_json::json!(0.3);
+        engine.validate_parameter_change(&change).await.unwrap();
+        engine.apply_parameter_change(&change).await.unwrap();
+        assert_eq!(params.read().unwrap().min_reputation, 0.3);
+    }
+
+    #[tokio::test]
+    async fn test_gas_schedule_parameter_change() {
+        let identity_manager = Arc::new(IdentityManager::new().unwrap());
+        let crypto_service = Arc::new(CryptoService::new().unwrap());
+        let schedules = Arc::new(std::sync::RwLock::new(GasScheduleRegistry::default()));
+        schedules.write().unwrap().note_executed(50);
+
+        let engine = ExecutionEngine::new(identity_manager, crypto_service)
+            .with_gas_schedules(schedules.clone());
+
+        let mut schedule = crate::contracts::GasSchedule::v1();
+        schedule.version = 2;
+        schedule.function_costs.insert("set".to_string(), 650);
+        let mut change = crate::governance::proposals::ParameterChange {
+            parameter: GAS_SCHEDULE_PARAMETER.to_string(),
+            current_value: serde_json::json!(1),
+            proposed_value: serde_json::json!({ "schedule": schedule, "activation_round": 50 }),
+            rationale: "Reprice storage writes".to_string(),
+            impact_analysis: crate::governance::proposals::ImpactAnalysis {
+                performance_impact: crate::governance::proposals::ImpactLevel::Low,
+                security_impact: crate::governance::proposals::ImpactLevel::Low,
+                compatibility_impact: crate::governance::proposals::ImpactLevel::Medium,
+                estimated_benefits: "Storage priced closer to its cost".to_string(),
+                potential_risks: vec!["Existing callers need higher gas limits".to_string()],
+            },
+        };
+        // Round 50 already executed under version 1
+        assert!(engine.validate_parameter_change(&change).await.is_err());
+
+        change.proposed_value["activation_round"] = serde_json::json!(60);
+        engine.validate_parameter_change(&change).await.unwrap();
+        engine.apply_parameter_change(&change).await.unwrap();
+        assert_eq!(schedules.read().unwrap().active_at(59).version, 1);
+        assert_eq!(schedules.read().unwrap().active_at(60).function_cost("set"), 650);
+    }
+
+    #[tokio::test]
+    async fn test_pause_network_enters_safe_mode() {
+        let identity_manager = Arc::new(IdentityManager::new().unwrap());
+        let crypto_service = Arc::new(CryptoService::new().unwrap());
+        let safe_mode = Arc::new(SafeMode::new(crate::core::SafeModeConfig::default()));
+
+        let engine = ExecutionEngine::new(identity_manager, crypto_service)
+            .with_safe_mode(safe_mode.clone());
+
+        engine.pause_network("proposal-1", "Critical bug in fee handling").await.unwrap();
+        assert!(safe_mode.is_halted());
+        assert_eq!(
+            safe_mode.status().await.source,
+            Some(HaltSource::Governance { proposal_id: "proposal-1".to_string() })
+        );
+
+        // A second emergency action while halted leaves the original halt in place
+        engine.enable_maintenance_mode("proposal-2", "Maintenance").await.unwrap();
+        assert_eq!(safe_mode.transitions().await.len(), 1);
+    }
 }
\ No newline at end of file
@@ -9,7 +9,7 @@ use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use crate::core::{Block, Transaction};
+use crate::core::{amount, Amount, Block, Transaction};
 use crate::identity::IdentityManager;
 use crate::security::CryptoService;
 
@@ -17,17 +17,22 @@ pub mod proposals;
 pub mod voting;
 pub mod execution;
 pub mod audit;
+pub mod rewards;
+pub mod credentials;
 
 use proposals::{Proposal, ProposalType, ProposalStatus, ProposalId};
 use voting::{Vote, VoteType, VotingPower, Votes};
 use execution::ExecutionEngine;
-use audit::{AuditEntry, AuditService};
+use audit::{AuditEntry, AuditService, ProposalSnapshot};
+use rewards::{RewardDistribution, RewardLedger, VoterStanding, VotingRewardConfig};
+pub use credentials::{CredentialRegistry, ParticipationCredential, VoteReceipt};
 
 /// Governance configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GovernanceConfig {
     /// Minimum stake required to create a proposal
-    pub min_proposal_stake: u64,
+    #[serde(with = "amount")]
+    pub min_proposal_stake: Amount,
     /// Discussion period duration in seconds
     pub discussion_period: u64,
     /// Voting period duration in seconds
@@ -43,7 +48,10 @@ pub struct GovernanceConfig {
     /// Maximum number of active proposals
     pub max_active_proposals: usize,
     /// Proposal fee
-    pub proposal_fee: u64,
+    #[serde(with = "amount")]
+    pub proposal_fee: Amount,
+    /// Rewards for voters on resolved proposals
+    pub voting_rewards: VotingRewardConfig,
 }
 
 impl Default for GovernanceConfig {
@@ -58,6 +66,7 @@ impl Default for GovernanceConfig {
             emergency_threshold: 0.80, // 80%
             max_active_proposals: 100,
             proposal_fee: 1000,
+            voting_rewards: VotingRewardConfig::default(),
         }
     }
 }
@@ -73,12 +82,18 @@ pub struct GovernanceStats {
     pub proposal_success_rate: f64,
     pub emergency_actions_count: u64,
     pub rollback_count: u64,
+    #[serde(with = "amount")]
+    pub voting_rewards_distributed: Amount,
+    pub rewarded_proposals: u64,
+    pub rewarded_identities: u64,
 }
 
 /// Main governance service
 pub struct GovernanceService {
     config: GovernanceConfig,
     proposals: Arc<RwLock<HashMap<ProposalId, Proposal>>>,
+    reward_ledger: Arc<RwLock<RewardLedger>>,
+    credentials: Arc<RwLock<CredentialRegistry>>,
     execution_engine: ExecutionEngine,
     audit_service: AuditService,
     identity_manager: Arc<IdentityManager>,
@@ -95,6 +110,8 @@ impl GovernanceService {
         Self {
             config,
             proposals: Arc::new(RwLock::new(HashMap::new())),
+            reward_ledger: Arc::new(RwLock::new(RewardLedger::default())),
+            credentials: Arc::new(RwLock::new(CredentialRegistry::default())),
             execution_engine: ExecutionEngine::new(
                 identity_manager.clone(),
                 crypto_service.clone(),
@@ -155,6 +172,16 @@ impl GovernanceService {
             .ok_or(GovernanceError::ProposalNotFound)
     }
 
+    /// Reconstruct a proposal as it stood at `timestamp`, for dispute resolution
+    pub async fn get_proposal_at(
+        &self,
+        proposal_id: &ProposalId,
+        timestamp: DateTime<Utc>,
+    ) -> Result<ProposalSnapshot, GovernanceError> {
+        self.audit_service.reconstruct_proposal_at(proposal_id, timestamp).await?
+            .ok_or(GovernanceError::ProposalNotFound)
+    }
+
     /// List all proposals
     pub async fn list_proposals(&self) -> Vec<Proposal> {
         let proposals = self.proposals.read().await;
@@ -169,6 +196,21 @@ impl GovernanceService {
         vote_type: VoteType,
         justification: Option<String>,
     ) -> Result<Vote, GovernanceError> {
+        let receipt = self.cast_vote_with_credential(proposal_id, voter, vote_type, justification).await?;
+        Ok(receipt.vote)
+    }
+
+    /// Cast a vote and issue the voter a participation credential
+    ///
+    /// The receipt holds the salt opening the credential's vote commitment.
+    /// It is returned only here; the node keeps just the credential.
+    pub async fn cast_vote_with_credential(
+        &self,
+        proposal_id: &ProposalId,
+        voter: String,
+        vote_type: VoteType,
+        justification: Option<String>,
+    ) -> Result<VoteReceipt, GovernanceError> {
         // Validate voter
         self.validate_voter(&voter).await?;
 
@@ -194,8 +236,13 @@ impl GovernanceService {
             justification,
         );
 
+        // Sign the credential first so a signing failure leaves no vote behind
+        let (credential, vote_salt) = ParticipationCredential::issue(&self.identity_manager, &vote).await
+            .map_err(|e| GovernanceError::CredentialError(e.to_string()))?;
+
         // Add vote to proposal
         proposal.add_vote(vote.clone())?;
+        self.credentials.write().await.insert(credential.clone());
 
         // Update proposal status if voting period ended
         self.update_proposal_status(proposal).await?;
@@ -203,7 +250,17 @@ impl GovernanceService {
         // Log audit entry
         self.audit_service.log_vote_cast(&vote).await?;
 
-        Ok(vote)
+        Ok(VoteReceipt { vote, credential, vote_salt })
+    }
+
+    /// Get a participation credential by ID
+    pub async fn get_credential(&self, credential_id: &str) -> Option<ParticipationCredential> {
+        self.credentials.read().await.get(credential_id).cloned()
+    }
+
+    /// Get the participation credentials issued to a voter, oldest first
+    pub async fn get_voter_credentials(&self, voter: &str) -> Vec<ParticipationCredential> {
+        self.credentials.read().await.for_voter(voter)
     }
 
     /// Execute a proposal
@@ -257,6 +314,8 @@ impl GovernanceService {
             0.0
         };
 
+        let ledger = self.reward_ledger.read().await;
+
         GovernanceStats {
             total_proposals,
             active_proposals,
@@ -266,6 +325,9 @@ impl GovernanceService {
             proposal_success_rate: success_rate,
             emergency_actions_count: 0, // TODO: Track emergency actions
             rollback_count: 0, // TODO: Track rollbacks
+            voting_rewards_distributed: ledger.total_distributed,
+            rewarded_proposals: ledger.distributions.len() as u64,
+            rewarded_identities: ledger.rewarded_identities(),
         }
     }
 
@@ -297,6 +359,8 @@ impl GovernanceService {
                         ProposalStatus::Voting,
                         proposal.status,
                     ).await?;
+
+                    self.distribute_voting_rewards(proposal).await?;
                 }
             },
             _ => {},
@@ -305,6 +369,54 @@ impl GovernanceService {
         Ok(())
     }
 
+    /// Get the voting rewards paid for a proposal
+    pub async fn get_voting_rewards(&self, proposal_id: &ProposalId) -> Option<RewardDistribution> {
+        self.reward_ledger.read().await.distributions.get(proposal_id).cloned()
+    }
+
+    /// Get the voting rewards accrued by an identity
+    pub async fn get_reward_balance(&self, identity: &str) -> Amount {
+        self.reward_ledger.read().await.balances.get(identity).copied().unwrap_or(0)
+    }
+
+    /// Pay the reward pool of a resolved proposal to its voters
+    async fn distribute_voting_rewards(&self, proposal: &Proposal) -> Result<(), GovernanceError> {
+        let config = &self.config.voting_rewards;
+        if !config.enabled || config.pool_per_proposal == 0 {
+            return Ok(());
+        }
+
+        let mut standings = HashMap::new();
+        for voter in proposal.votes.votes_by_voter.keys() {
+            if let Some(stake) = self.identity_manager.get_stake(voter).await {
+                // Voters registered under the same signing key share a single reward
+                let identity = self.identity_manager.get_peer_identity(voter).await
+                    .map(|identity| hex::encode(&identity.ed25519_public))
+                    .unwrap_or_else(|| voter.clone());
+                standings.insert(voter.clone(), VoterStanding { identity, stake });
+            }
+        }
+
+        let distribution = rewards::distribute_rewards(config, proposal, &standings);
+        let details = serde_json::json!({
+            "proposal_id": distribution.proposal_id,
+            "pool": distribution.pool.to_string(),
+            "distributed": distribution.distributed.to_string(),
+            "recipients": distribution.rewards.len(),
+            "excluded": distribution.excluded.len(),
+        });
+
+        if self.reward_ledger.write().await.credit(distribution) {
+            self.audit_service.log_custom_event(
+                "voting_rewards_distributed".to_string(),
+                "governance".to_string(),
+                details,
+            ).await?;
+        }
+
+        Ok(())
+    }
+
     /// Validate proposer
     async fn validate_proposer(&self, proposer: &str) -> Result<(), GovernanceError> {
         // Check if proposer has sufficient stake
@@ -385,6 +497,8 @@ pub enum GovernanceError {
     AuditError(String),
     #[error("Identity error: {0}")]
     IdentityError(String),
+    #[error("Credential error: {0}")]
+    CredentialError(String),
 }
 
 impl From<proposals::ProposalError> for GovernanceError {
@@ -4,6 +4,7 @@ use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use crate::core::{amount, Amount};
 
 /// Unique identifier for proposals
 pub type ProposalId = String;
@@ -104,7 +105,8 @@ pub enum TreasuryAction {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TreasuryManagement {
     pub action: TreasuryAction,
-    pub amount: u64,
+    #[serde(with = "amount")]
+    pub amount: Amount,
     pub recipient: String,
     pub purpose: String,
     pub budget_category: String,
@@ -454,7 +456,8 @@ pub struct VotingStats {
 /// Governance configuration (re-export for proposals)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GovernanceConfig {
-    pub min_proposal_stake: u64,
+    #[serde(with = "amount")]
+    pub min_proposal_stake: Amount,
     pub discussion_period: u64,
     pub voting_period: u64,
     pub execution_delay: u64,
@@ -462,7 +465,8 @@ pub struct GovernanceConfig {
     pub majority_threshold: f64,
     pub emergency_threshold: f64,
     pub max_active_proposals: usize,
-    pub proposal_fee: u64,
+    #[serde(with = "amount")]
+    pub proposal_fee: Amount,
 }
 
 /// Proposal error types
@@ -0,0 +1,297 @@
+//! Voting rewards for governance participation
+//!
+//! Each resolved proposal can pay a small reward pool to the voters who took
+//! part. A voter's share is proportional to its stake weighted by how early in
+//! the voting period the vote was cast. Voters below the minimum stake get
+//! nothing, and an identity controlling several voting keys is rewarded once.
+
+use std::collections::{HashMap, HashSet};
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use super::proposals::{Proposal, ProposalId};
+use crate::core::{amount, Amount};
+
+/// Voting reward configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VotingRewardConfig {
+    /// Whether rewards are paid at all
+    pub enabled: bool,
+    /// Reward pool distributed per resolved proposal
+    #[serde(with = "amount")]
+    pub pool_per_proposal: Amount,
+    /// Minimum stake a voter needs to be rewarded
+    #[serde(with = "amount")]
+    pub min_voter_stake: Amount,
+    /// Weight of a vote cast at the very end of the voting period (0.0 to 1.0)
+    pub min_timeliness_weight: f64,
+}
+
+impl Default for VotingRewardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pool_per_proposal: 10000,
+            min_voter_stake: 1000,
+            min_timeliness_weight: 0.5,
+        }
+    }
+}
+
+/// Stake and controlling identity of a voter at resolution time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoterStanding {
+    pub identity: String,
+    #[serde(with = "amount")]
+    pub stake: Amount,
+}
+
+/// Reward paid to one voter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VotingReward {
+    pub voter: String,
+    pub identity: String,
+    #[serde(with = "amount")]
+    pub stake: Amount,
+    pub timeliness: f64,
+    #[serde(with = "amount")]
+    pub amount: Amount,
+}
+
+/// Why a voter was left out of a distribution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RewardExclusion {
+    UnknownVoter,
+    BelowMinimumStake,
+    DuplicateIdentity,
+}
+
+/// Rewards paid out for one proposal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardDistribution {
+    pub proposal_id: ProposalId,
+    #[serde(with = "amount")]
+    pub pool: Amount,
+    #[serde(with = "amount")]
+    pub distributed: Amount,
+    pub rewards: Vec<VotingReward>,
+    pub excluded: HashMap<String, RewardExclusion>,
+    pub distributed_at: DateTime<Utc>,
+}
+
+/// Weight of a vote cast at `cast_at` within the voting period
+///
+/// Falls linearly from 1.0 at the start of voting to `min_weight` at its end.
+pub fn timeliness_weight(
+    cast_at: DateTime<Utc>,
+    voting_start: DateTime<Utc>,
+    voting_end: DateTime<Utc>,
+    min_weight: f64,
+) -> f64 {
+    let min_weight = min_weight.clamp(0.0, 1.0);
+    let period = (voting_end - voting_start).num_seconds();
+    if period <= 0 {
+        return 1.0;
+    }
+
+    let elapsed = (cast_at - voting_start).num_seconds().clamp(0, period) as f64 / period as f64;
+    1.0 - elapsed * (1.0 - min_weight)
+}
+
+/// Split the reward pool of a resolved proposal among its voters
+///
+/// When one identity voted through several keys only its earliest vote is
+/// rewarded. Integer remainders stay in the pool.
+pub fn distribute_rewards(
+    config: &VotingRewardConfig,
+    proposal: &Proposal,
+    standings: &HashMap<String, VoterStanding>,
+) -> RewardDistribution {
+    let mut votes: Vec<_> = proposal.votes.votes_by_voter.values().collect();
+    votes.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.voter.cmp(&b.voter)));
+
+    let mut excluded = HashMap::new();
+    let mut identities = HashSet::new();
+    let mut eligible = Vec::new();
+    for vote in votes {
+        let standing = match standings.get(&vote.voter) {
+            Some(standing) => standing,
+            None => {
+                excluded.insert(vote.voter.clone(), RewardExclusion::UnknownVoter);
+                continue;
+            }
+        };
+        if standing.stake < config.min_voter_stake {
+            excluded.insert(vote.voter.clone(), RewardExclusion::BelowMinimumStake);
+            continue;
+        }
+        if !identities.insert(standing.identity.clone()) {
+            excluded.insert(vote.voter.clone(), RewardExclusion::DuplicateIdentity);
+            continue;
+        }
+
+        let timeliness = timeliness_weight(
+            vote.timestamp,
+            proposal.voting_start_time,
+            proposal.voting_end_time,
+            config.min_timeliness_weight,
+        );
+        eligible.push((vote.voter.clone(), standing, timeliness));
+    }
+
+    let total_weight: f64 = eligible.iter()
+        .map(|(_, standing, timeliness)| standing.stake as f64 * timeliness)
+        .sum();
+
+    let mut rewards = Vec::new();
+    if total_weight > 0.0 {
+        for (voter, standing, timeliness) in eligible {
+            let share = standing.stake as f64 * timeliness / total_weight;
+            let amount = (config.pool_per_proposal as f64 * share).floor() as Amount;
+            rewards.push(VotingReward {
+                voter,
+                identity: standing.identity.clone(),
+                stake: standing.stake,
+                timeliness,
+                amount,
+            });
+        }
+    }
+
+    RewardDistribution {
+        proposal_id: proposal.id.clone(),
+        pool: config.pool_per_proposal,
+        distributed: rewards.iter().map(|r| r.amount).sum(),
+        rewards,
+        excluded,
+        distributed_at: Utc::now(),
+    }
+}
+
+/// Record of paid distributions and accrued rewards per identity
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RewardLedger {
+    pub distributions: HashMap<ProposalId, RewardDistribution>,
+    pub balances: HashMap<String, Amount>,
+    #[serde(with = "amount")]
+    pub total_distributed: Amount,
+}
+
+impl RewardLedger {
+    /// Credit a distribution, unless the proposal was already rewarded
+    pub fn credit(&mut self, distribution: RewardDistribution) -> bool {
+        if self.distributions.contains_key(&distribution.proposal_id) {
+            return false;
+        }
+
+        for reward in &distribution.rewards {
+            *self.balances.entry(reward.identity.clone()).or_default() += reward.amount;
+        }
+        self.total_distributed += distribution.distributed;
+        self.distributions.insert(distribution.proposal_id.clone(), distribution);
+        true
+    }
+
+    /// Number of distinct identities ever rewarded
+    pub fn rewarded_identities(&self) -> u64 {
+        self.balances.len() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::proposals::{ImpactAnalysis, ImpactLevel, ParameterChange, ProposalType, Vote, VoteType};
+    use chrono::Duration;
+
+    fn proposal() -> Proposal {
+        let mut proposal = Proposal::new(
+            ProposalType::ParameterChange(ParameterChange {
+                parameter: "block_size".to_string(),
+                current_value: serde_json::json!(1000000),
+                proposed_value: serde_json::json!(2000000),
+                rationale: "Increase block size".to_string(),
+                impact_analysis: ImpactAnalysis {
+                    performance_impact: ImpactLevel::Medium,
+                    security_impact: ImpactLevel::Low,
+                    compatibility_impact: ImpactLevel::Low,
+                    estimated_benefits: "Better throughput".to_string(),
+                    potential_risks: vec!["Increased storage requirements".to_string()],
+                },
+            }),
+            "Increase Block Size".to_string(),
+            "Proposal to increase block size".to_string(),
+            "validator1".to_string(),
+            0,
+            100,
+            0,
+        );
+        proposal.voting_start_time = Utc::now();
+        proposal.voting_end_time = proposal.voting_start_time + Duration::seconds(100);
+        proposal
+    }
+
+    fn cast(proposal: &mut Proposal, voter: &str, after_secs: i64) {
+        let mut vote = Vote::new(proposal.id.clone(), voter.to_string(), VoteType::For, 1.0, None);
+        vote.timestamp = proposal.voting_start_time + Duration::seconds(after_secs);
+        proposal.add_vote(vote).unwrap();
+    }
+
+    fn standing(identity: &str, stake: Amount) -> VoterStanding {
+        VoterStanding { identity: identity.to_string(), stake }
+    }
+
+    #[test]
+    fn test_timeliness_weight() {
+        let start = Utc::now();
+        let end = start + Duration::seconds(100);
+        assert_eq!(timeliness_weight(start, start, end, 0.5), 1.0);
+        assert_eq!(timeliness_weight(start + Duration::seconds(50), start, end, 0.5), 0.75);
+        assert_eq!(timeliness_weight(end + Duration::seconds(10), start, end, 0.5), 0.5);
+    }
+
+    #[test]
+    fn test_distribution_by_stake_and_timeliness() {
+        let config = VotingRewardConfig { enabled: true, pool_per_proposal: 9000, ..Default::default() };
+        let mut proposal = proposal();
+        cast(&mut proposal, "early", 0);
+        cast(&mut proposal, "late", 100);
+        cast(&mut proposal, "small", 0);
+
+        let standings = HashMap::from([
+            ("early".to_string(), standing("a", 2000)),
+            ("late".to_string(), standing("b", 2000)),
+            ("small".to_string(), standing("c", 10)),
+        ]);
+        let distribution = distribute_rewards(&config, &proposal, &standings);
+
+        let amount = |voter: &str| distribution.rewards.iter().find(|r| r.voter == voter).map(|r| r.amount);
+        assert_eq!(amount("early"), Some(6000));
+        assert_eq!(amount("late"), Some(3000));
+        assert_eq!(amount("small"), None);
+        assert_eq!(distribution.excluded["small"], RewardExclusion::BelowMinimumStake);
+        assert_eq!(distribution.distributed, 9000);
+    }
+
+    #[test]
+    fn test_one_reward_per_identity() {
+        let config = VotingRewardConfig { enabled: true, ..Default::default() };
+        let mut proposal = proposal();
+        cast(&mut proposal, "key1", 10);
+        cast(&mut proposal, "key2", 5);
+
+        let standings = HashMap::from([
+            ("key1".to_string(), standing("operator", 5000)),
+            ("key2".to_string(), standing("operator", 5000)),
+        ]);
+        let distribution = distribute_rewards(&config, &proposal, &standings);
+        assert_eq!(distribution.rewards.len(), 1);
+        assert_eq!(distribution.rewards[0].voter, "key2");
+        assert_eq!(distribution.excluded["key1"], RewardExclusion::DuplicateIdentity);
+
+        let mut ledger = RewardLedger::default();
+        assert!(ledger.credit(distribution.clone()));
+        assert!(!ledger.credit(distribution));
+        assert_eq!(ledger.balances["operator"], config.pool_per_proposal);
+        assert_eq!(ledger.rewarded_identities(), 1);
+    }
+}
@@ -3,6 +3,7 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
+use crate::core::{amount, Amount};
 
 /// Vote types (re-export from proposals)
 pub use crate::governance::proposals::{Vote, VoteType, Votes, VotingStats};
@@ -11,9 +12,11 @@ pub use crate::governance::proposals::{Vote, VoteType, Votes, VotingStats};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VotingPower {
     pub validator_id: String,
-    pub stake_amount: u64,
+    #[serde(with = "amount")]
+    pub stake_amount: Amount,
     pub voting_weight: f64,
-    pub delegation_power: u64,
+    #[serde(with = "amount")]
+    pub delegation_power: Amount,
     pub reputation_score: f64,
     pub total_power: f64,
 }
@@ -22,8 +25,8 @@ impl VotingPower {
     /// Create new voting power calculation
     pub fn new(
         validator_id: String,
-        stake_amount: u64,
-        delegation_power: u64,
+        stake_amount: Amount,
+        delegation_power: Amount,
         reputation_score: f64,
     ) -> Self {
         let base_power = stake_amount as f64;
@@ -77,8 +80,8 @@ impl VotingCalculator {
     pub fn calculate_voting_power(
         &self,
         validator_id: &str,
-        stake_amount: u64,
-        delegation_power: u64,
+        stake_amount: Amount,
+        delegation_power: Amount,
     ) -> f64 {
         let base_power = stake_amount as f64 * self.stake_multiplier;
         let delegation_power = (delegation_power as f64).sqrt() * self.delegation_multiplier;
@@ -123,7 +126,8 @@ impl VotingCalculator {
 pub struct Delegation {
     pub delegator: String,
     pub delegate: String,
-    pub amount: u64,
+    #[serde(with = "amount")]
+    pub amount: Amount,
     pub created_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
     pub is_active: bool,
@@ -134,7 +138,7 @@ impl Delegation {
     pub fn new(
         delegator: String,
         delegate: String,
-        amount: u64,
+        amount: Amount,
         expires_at: Option<DateTime<Utc>>,
     ) -> Self {
         Self {
@@ -201,7 +205,7 @@ impl DelegationManager {
     }
 
     /// Get total delegation power for a delegate
-    pub fn get_delegation_power(&self, delegate_id: &str) -> u64 {
+    pub fn get_delegation_power(&self, delegate_id: &str) -> Amount {
         self.delegations.get(delegate_id)
             .map(|delegations| {
                 delegations.iter()
@@ -260,7 +264,8 @@ impl DelegationManager {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DelegationStats {
     pub total_delegations: u64,
-    pub total_delegated_amount: u64,
+    #[serde(with = "amount")]
+    pub total_delegated_amount: Amount,
     pub active_delegations: u64,
     pub unique_delegators: usize,
     pub unique_delegates: usize,
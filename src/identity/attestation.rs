@@ -0,0 +1,122 @@
+//! Remote node attestation
+//!
+//! A node attests to the software it runs and the chain it follows: its
+//! version, genesis transaction and validator set hash, signed with its
+//! Dilithium3 identity key over a challenge chosen by the client. Mobile
+//! clients check the statement against values pinned from their trust-anchor
+//! file before trusting the node. The signing payload must stay
+//! byte-compatible with the mobile SDK's `attestation` module.
+
+use super::{IdentityManager, NodeSignature, SignatureType};
+use crate::BlockchainError;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/// Domain separator for attestation payloads
+pub const ATTESTATION_DOMAIN: &[u8] = b"qdag-node-attestation-v1";
+
+/// Largest client challenge accepted, in bytes
+pub const MAX_ATTESTATION_CHALLENGE: usize = 64;
+
+/// Signed statement of a node's software and chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeAttestation {
+    pub software_version: String,
+    /// Hex genesis transaction ID
+    pub genesis_hash: String,
+    /// Hex hash of the validator set, as in the node's trust anchor
+    pub validator_set_hash: String,
+    /// Hex challenge supplied by the client
+    pub challenge: String,
+    pub issued_at: u64,
+    /// Dilithium3 signature over `signing_payload()`
+    pub signature: NodeSignature,
+}
+
+impl NodeAttestation {
+    /// Sign an attestation with the node's Dilithium3 key
+    pub async fn sign(
+        identity: &IdentityManager,
+        software_version: &str,
+        genesis_hash: &str,
+        validator_set_hash: &str,
+        challenge: &str,
+    ) -> Result<Self, BlockchainError> {
+        let challenge_bytes = hex::decode(challenge)
+            .map_err(|e| BlockchainError::Other(format!("Invalid attestation challenge: {}", e)))?;
+        if challenge_bytes.is_empty() || challenge_bytes.len() > MAX_ATTESTATION_CHALLENGE {
+            return Err(BlockchainError::Other(format!(
+                "Attestation challenge must be 1 to {} bytes", MAX_ATTESTATION_CHALLENGE
+            )));
+        }
+
+        let issued_at = chrono::Utc::now().timestamp() as u64;
+        let payload = signing_payload(software_version, genesis_hash, validator_set_hash, challenge, issued_at);
+        let signature = identity.sign(&payload, SignatureType::Dilithium3).await?;
+
+        Ok(Self {
+            software_version: software_version.to_string(),
+            genesis_hash: genesis_hash.to_string(),
+            validator_set_hash: validator_set_hash.to_string(),
+            challenge: challenge.to_string(),
+            issued_at,
+            signature,
+        })
+    }
+
+    /// Bytes covered by the signature
+    pub fn signing_payload(&self) -> Vec<u8> {
+        signing_payload(&self.software_version, &self.genesis_hash, &self.validator_set_hash, &self.challenge, self.issued_at)
+    }
+
+    /// Check the signature, without judging the attested values
+    pub async fn verify_signature(&self, verifier: &IdentityManager) -> Result<bool, BlockchainError> {
+        if self.signature.signature_type != SignatureType::Dilithium3 {
+            return Ok(false);
+        }
+        verifier.verify(&self.signing_payload(), &self.signature).await
+    }
+}
+
+/// SHA3-256 over the domain and the length-prefixed attested fields
+pub fn signing_payload(
+    software_version: &str,
+    genesis_hash: &str,
+    validator_set_hash: &str,
+    challenge: &str,
+    issued_at: u64,
+) -> Vec<u8> {
+    let mut hasher = Sha3_256::new();
+    hasher.update(ATTESTATION_DOMAIN);
+    for field in [software_version, genesis_hash, validator_set_hash, challenge] {
+        hasher.update((field.len() as u32).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(issued_at.to_le_bytes());
+    hasher.finalize().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_commits_to_every_field() {
+        let base = signing_payload("1.0.0", "aa", "bb", "cc", 1);
+        assert_eq!(base, signing_payload("1.0.0", "aa", "bb", "cc", 1));
+        assert_ne!(base, signing_payload("1.0.1", "aa", "bb", "cc", 1));
+        assert_ne!(base, signing_payload("1.0.0", "aa", "bb", "cd", 1));
+        assert_ne!(base, signing_payload("1.0.0", "aa", "bb", "cc", 2));
+        // Length prefixes keep field boundaries unambiguous
+        assert_ne!(signing_payload("1.0.0", "aab", "b", "cc", 1), signing_payload("1.0.0", "aa", "bb", "cc", 1));
+    }
+
+    #[test]
+    fn test_payload_vector() {
+        // Shared with the mobile SDK's attestation tests
+        assert_eq!(
+            hex::encode(signing_payload("0.1.0", "00ff", "abcd", "0102", 1_700_000_000)),
+            "de77d09dde7d07d6c1a13f914def92876d76736bdd64b9c45f610bf9b7225ded"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Selective disclosure of signed identity attributes
+//!
+//! A node or wallet identity commits to each of its metadata attributes
+//! separately as `SHA3(domain || salt || name || 0x00 || value)` with a fresh
+//! random salt, and signs the set of commitments with its Ed25519 key. To prove
+//! a single attribute (say `region = EU`) it reveals that value and its salt;
+//! the verifier recomputes the commitment and checks the signature. Attribute
+//! names are visible in the signed set, but undisclosed values stay hidden
+//! behind their salted commitments. Fresh salts are drawn for every
+//! credential, so two proofs over the same metadata cannot be linked by their
+//! commitments.
+
+use crate::identity::{IdentityInfo, NodeIdentity};
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::{BTreeMap, HashMap};
+
+const COMMITMENT_DOMAIN: &[u8] = b"quantum-dag-attribute-commitment";
+const ATTRIBUTES_DOMAIN: &[u8] = b"quantum-dag-signed-attributes";
+
+/// Commitments to every attribute of a subject, signed by the subject's key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedAttributes {
+    /// Node ID or wallet address the attributes describe
+    pub subject: String,
+    pub ed25519_public: Vec<u8>,
+    pub issued_at: u64,
+    /// Attribute name to salted commitment
+    pub commitments: BTreeMap<String, Vec<u8>>,
+    pub signature: Vec<u8>,
+}
+
+impl SignedAttributes {
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut bytes = ATTRIBUTES_DOMAIN.to_vec();
+        for field in [self.subject.as_bytes(), &self.ed25519_public, &self.issued_at.to_le_bytes()] {
+            bytes.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+        for (name, commitment) in &self.commitments {
+            bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(commitment);
+        }
+        bytes
+    }
+
+    /// Check the subject's signature over the commitments
+    pub fn verify(&self) -> Result<(), DisclosureError> {
+        let public_key = PublicKey::from_bytes(&self.ed25519_public)
+            .map_err(|e| DisclosureError::InvalidKey(e.to_string()))?;
+        let signature = Signature::try_from(self.signature.as_slice())
+            .map_err(|_| DisclosureError::InvalidSignature)?;
+        public_key.verify(&self.signed_bytes(), &signature)
+            .map_err(|_| DisclosureError::InvalidSignature)
+    }
+}
+
+/// Value and salt revealed for one attribute
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisclosedAttribute {
+    pub value: String,
+    pub salt: Vec<u8>,
+}
+
+/// Signed commitments together with the attributes chosen for disclosure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisclosureProof {
+    pub attributes: SignedAttributes,
+    pub disclosed: BTreeMap<String, DisclosedAttribute>,
+}
+
+impl DisclosureProof {
+    /// Verify the proof and return the disclosed attributes
+    pub fn verify(&self) -> Result<BTreeMap<String, String>, DisclosureError> {
+        self.attributes.verify()?;
+
+        let mut revealed = BTreeMap::new();
+        for (name, attribute) in &self.disclosed {
+            let commitment = self.attributes.commitments.get(name)
+                .ok_or_else(|| DisclosureError::UnknownAttribute(name.clone()))?;
+            if commit(name, &attribute.value, &attribute.salt) != *commitment {
+                return Err(DisclosureError::CommitmentMismatch(name.clone()));
+            }
+            revealed.insert(name.clone(), attribute.value.clone());
+        }
+        Ok(revealed)
+    }
+
+    /// Verify the proof was issued by the node publishing `info`
+    pub fn verify_for(&self, info: &IdentityInfo) -> Result<BTreeMap<String, String>, DisclosureError> {
+        let public_key = hex::decode(&info.ed25519_public)
+            .map_err(|e| DisclosureError::InvalidKey(e.to_string()))?;
+        if self.attributes.subject != info.node_id || self.attributes.ed25519_public != public_key {
+            return Err(DisclosureError::SubjectMismatch(self.attributes.subject.clone()));
+        }
+        self.verify()
+    }
+}
+
+/// Attribute values and salts kept by the subject to produce proofs
+#[derive(Debug, Clone)]
+pub struct AttributeCredential {
+    signed: SignedAttributes,
+    openings: BTreeMap<String, DisclosedAttribute>,
+}
+
+impl AttributeCredential {
+    /// Commit to `attributes` and sign the commitments with `keypair`
+    pub fn issue(subject: &str, attributes: &HashMap<String, String>, keypair: &Keypair) -> Self {
+        let mut openings = BTreeMap::new();
+        let mut commitments = BTreeMap::new();
+        for (name, value) in attributes {
+            let salt = rand::random::<[u8; 32]>().to_vec();
+            commitments.insert(name.clone(), commit(name, value, &salt));
+            openings.insert(name.clone(), DisclosedAttribute { value: value.clone(), salt });
+        }
+
+        let mut signed = SignedAttributes {
+            subject: subject.to_string(),
+            ed25519_public: keypair.public.to_bytes().to_vec(),
+            issued_at: chrono::Utc::now().timestamp() as u64,
+            commitments,
+            signature: Vec::new(),
+        };
+        signed.signature = keypair.sign(&signed.signed_bytes()).to_bytes().to_vec();
+
+        Self { signed, openings }
+    }
+
+    /// Credential over a node identity's metadata
+    pub fn from_identity(identity: &NodeIdentity) -> Result<Self, DisclosureError> {
+        let keypair = Keypair::from_bytes(&identity.ed25519_keypair)
+            .map_err(|e| DisclosureError::InvalidKey(e.to_string()))?;
+        Ok(Self::issue(&identity.node_id, &identity.metadata, &keypair))
+    }
+
+    /// Signed commitments to all attributes
+    pub fn signed_attributes(&self) -> &SignedAttributes {
+        &self.signed
+    }
+
+    /// Prove the named attributes, revealing nothing about the rest
+    pub fn disclose(&self, names: &[&str]) -> Result<DisclosureProof, DisclosureError> {
+        let disclosed = names.iter()
+            .map(|name| {
+                self.openings.get(*name)
+                    .map(|opening| (name.to_string(), opening.clone()))
+                    .ok_or_else(|| DisclosureError::UnknownAttribute(name.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(DisclosureProof { attributes: self.signed.clone(), disclosed })
+    }
+}
+
+fn commit(name: &str, value: &str, salt: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha3_256::new();
+    hasher.update(COMMITMENT_DOMAIN);
+    hasher.update(salt);
+    hasher.update(name.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(value.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Selective disclosure errors
+#[derive(Debug, thiserror::Error)]
+pub enum DisclosureError {
+    #[error("Unknown attribute: {0}")]
+    UnknownAttribute(String),
+    #[error("Disclosed value of {0} does not match its commitment")]
+    CommitmentMismatch(String),
+    #[error("Proof subject {0} does not match the expected identity")]
+    SubjectMismatch(String),
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    #[error("Invalid signature")]
+    InvalidSignature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair() -> Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&rand::random::<[u8; 32]>()).unwrap();
+        Keypair { public: PublicKey::from(&secret), secret }
+    }
+
+    fn attributes() -> HashMap<String, String> {
+        HashMap::from([
+            ("region".to_string(), "EU".to_string()),
+            ("operator".to_string(), "Example GmbH".to_string()),
+            ("hosting".to_string(), "bare-metal".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_disclose_single_attribute() {
+        let credential = AttributeCredential::issue("qd_node", &attributes(), &keypair());
+        let proof = credential.disclose(&["region"]).unwrap();
+
+        let revealed = proof.verify().unwrap();
+        assert_eq!(revealed, BTreeMap::from([("region".to_string(), "EU".to_string())]));
+
+        // Other values are only present as commitments
+        let json = serde_json::to_string(&proof).unwrap();
+        assert!(!json.contains("Example GmbH"));
+        assert!(matches!(credential.disclose(&["missing"]), Err(DisclosureError::UnknownAttribute(_))));
+    }
+
+    #[test]
+    fn test_tampered_proof_is_rejected() {
+        let credential = AttributeCredential::issue("qd_node", &attributes(), &keypair());
+
+        let mut proof = credential.disclose(&["region"]).unwrap();
+        proof.disclosed.get_mut("region").unwrap().value = "US".to_string();
+        assert!(matches!(proof.verify(), Err(DisclosureError::CommitmentMismatch(_))));
+
+        let mut proof = credential.disclose(&["region"]).unwrap();
+        proof.attributes.commitments.remove("hosting");
+        assert!(matches!(proof.verify(), Err(DisclosureError::InvalidSignature)));
+
+        // A proof signed by another key does not pass for this subject
+        let mut proof = credential.disclose(&["region"]).unwrap();
+        let other = AttributeCredential::issue("qd_node", &attributes(), &keypair());
+        proof.attributes.ed25519_public = other.signed_attributes().ed25519_public.clone();
+        assert!(proof.verify().is_err());
+    }
+
+    #[test]
+    fn test_fresh_salts_per_credential() {
+        let keypair = keypair();
+        let first = AttributeCredential::issue("qd_node", &attributes(), &keypair);
+        let second = AttributeCredential::issue("qd_node", &attributes(), &keypair);
+
+        assert_ne!(
+            first.signed_attributes().commitments["region"],
+            second.signed_attributes().commitments["region"]
+        );
+    }
+}
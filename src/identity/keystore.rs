@@ -0,0 +1,267 @@
+//! Node identity file encryption at rest
+//!
+//! `identity.json` holds the node's secret keys. With a key source set, it
+//! is written as an `EncryptedIdentityFile`: the identity JSON encrypted with
+//! AES-256-GCM under a key derived from a passphrase with Argon2id. The
+//! passphrase is configured, taken from `QDAG_IDENTITY_PASSPHRASE`, or kept
+//! in the OS keyring, which generates one on first use in builds with the
+//! `os-keyring` feature.
+//!
+//! Loading detects the format, so plaintext files written before encryption
+//! was enabled are read as before and rewritten encrypted. An encrypted file
+//! is never read without its passphrase.
+
+use super::NodeIdentity;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+/// Format version of `EncryptedIdentityFile`
+pub const IDENTITY_FILE_VERSION: u32 = 1;
+
+/// Passphrase of the identity file when none is configured
+pub const IDENTITY_PASSPHRASE_ENV: &str = "QDAG_IDENTITY_PASSPHRASE";
+
+/// OS keyring service the generated passphrases are stored under
+pub const KEYRING_SERVICE: &str = "qdag-node-identity";
+
+const IDENTITY_AAD_DOMAIN: &[u8] = b"qdag-identity-file-v1";
+
+/// Where the passphrase of the identity file comes from
+#[derive(Clone, Default, PartialEq, Eq)]
+pub enum IdentityKeySource {
+    /// The identity is written unencrypted
+    #[default]
+    Plaintext,
+    Passphrase(String),
+    /// A passphrase in the OS keyring, one per identity directory
+    Keyring,
+}
+
+impl std::fmt::Debug for IdentityKeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Plaintext => f.write_str("Plaintext"),
+            Self::Passphrase(_) => f.write_str("Passphrase(..)"),
+            Self::Keyring => f.write_str("Keyring"),
+        }
+    }
+}
+
+impl IdentityKeySource {
+    /// This source, or the passphrase in `QDAG_IDENTITY_PASSPHRASE` in place of `Plaintext`
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Plaintext => match std::env::var(IDENTITY_PASSPHRASE_ENV) {
+                Ok(passphrase) if !passphrase.is_empty() => Self::Passphrase(passphrase),
+                _ => Self::Plaintext,
+            },
+            source => source,
+        }
+    }
+
+    /// Passphrase of the identity in `storage_path`; a missing keyring entry
+    /// is generated only if `create` is set
+    pub(crate) fn passphrase(&self, storage_path: &str, create: bool) -> Result<Option<String>, IdentityFileError> {
+        match self {
+            Self::Plaintext => Ok(None),
+            Self::Passphrase(passphrase) if passphrase.is_empty() => Err(IdentityFileError::EmptyPassphrase),
+            Self::Passphrase(passphrase) => Ok(Some(passphrase.clone())),
+            Self::Keyring => keyring_passphrase(storage_path, create).map(Some),
+        }
+    }
+}
+
+/// Whether this build can keep passphrases in the OS keyring
+pub fn keyring_supported() -> bool {
+    cfg!(feature = "os-keyring")
+}
+
+#[cfg(feature = "os-keyring")]
+fn keyring_passphrase(account: &str, create: bool) -> Result<String, IdentityFileError> {
+    let keyring_error = |e: keyring::Error| IdentityFileError::Keyring(e.to_string());
+    let entry = keyring::Entry::new(KEYRING_SERVICE, account).map_err(keyring_error)?;
+    match entry.get_password() {
+        Ok(passphrase) => Ok(passphrase),
+        Err(keyring::Error::NoEntry) if create => {
+            let mut secret = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut secret);
+            let passphrase = hex::encode(secret);
+            entry.set_password(&passphrase).map_err(keyring_error)?;
+            log::info!("🔑 Stored a new identity passphrase in the OS keyring for {}", account);
+            Ok(passphrase)
+        }
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+#[cfg(not(feature = "os-keyring"))]
+fn keyring_passphrase(_account: &str, _create: bool) -> Result<String, IdentityFileError> {
+    Err(IdentityFileError::Keyring("OS keyring support needs a node built with the `os-keyring` feature".to_string()))
+}
+
+/// Argon2id cost of deriving the file key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory in KiB
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// OWASP's recommended minimum for Argon2id
+    fn default() -> Self {
+        Self { memory_kib: 19 * 1024, iterations: 2, parallelism: 1 }
+    }
+}
+
+/// Node identity encrypted under a passphrase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedIdentityFile {
+    pub version: u32,
+    /// Readable without the passphrase
+    pub node_id: String,
+    pub kdf: KdfParams,
+    /// Hex Argon2id salt
+    salt: String,
+    /// Hex AES-GCM nonce
+    nonce: String,
+    /// Hex encrypted identity JSON
+    ciphertext: String,
+}
+
+/// Contents of `identity.json` in either format
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum StoredIdentity {
+    Encrypted(EncryptedIdentityFile),
+    Plaintext(NodeIdentity),
+}
+
+impl EncryptedIdentityFile {
+    /// Encrypt `identity` under `passphrase`
+    pub fn seal(identity: &NodeIdentity, passphrase: &str, kdf: KdfParams) -> Result<Self, IdentityFileError> {
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        let mut file = Self {
+            version: IDENTITY_FILE_VERSION,
+            node_id: identity.node_id.clone(),
+            kdf,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: String::new(),
+        };
+        let key = derive_key(passphrase, &salt, kdf)?;
+        let plaintext = serde_json::to_vec(identity).map_err(|e| IdentityFileError::Malformed(e.to_string()))?;
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &file.aad() })
+            .map_err(|_| IdentityFileError::Malformed("encryption failed".to_string()))?;
+        file.ciphertext = hex::encode(ciphertext);
+        Ok(file)
+    }
+
+    /// Decrypt the identity with `passphrase`
+    pub fn open(&self, passphrase: &str) -> Result<NodeIdentity, IdentityFileError> {
+        if self.version != IDENTITY_FILE_VERSION {
+            return Err(IdentityFileError::UnsupportedVersion(self.version));
+        }
+        let malformed = |field: &str| IdentityFileError::Malformed(format!("invalid {}", field));
+        let salt = hex::decode(&self.salt).map_err(|_| malformed("salt"))?;
+        let nonce = hex::decode(&self.nonce).map_err(|_| malformed("nonce"))?;
+        let ciphertext = hex::decode(&self.ciphertext).map_err(|_| malformed("ciphertext"))?;
+        if nonce.len() != 12 {
+            return Err(malformed("nonce"));
+        }
+
+        let key = derive_key(passphrase, &salt, self.kdf)?;
+        // A wrong passphrase derives a wrong key, which fails authentication
+        let plaintext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &self.aad() })
+            .map_err(|_| IdentityFileError::WrongPassphrase)?;
+        let identity: NodeIdentity = serde_json::from_slice(&plaintext).map_err(|e| IdentityFileError::Malformed(e.to_string()))?;
+        if identity.node_id != self.node_id {
+            return Err(IdentityFileError::Malformed(format!("file of {} contains identity {}", self.node_id, identity.node_id)));
+        }
+        Ok(identity)
+    }
+
+    fn aad(&self) -> Vec<u8> {
+        [IDENTITY_AAD_DOMAIN, &self.version.to_be_bytes(), self.node_id.as_bytes()].join(&b'|')
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], kdf: KdfParams) -> Result<[u8; 32], IdentityFileError> {
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| IdentityFileError::Malformed(format!("invalid KDF parameters: {}", e)))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| IdentityFileError::Malformed(format!("key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Identity file encryption errors
+#[derive(Debug, thiserror::Error)]
+pub enum IdentityFileError {
+    #[error("The identity file is encrypted; set {} or configure its key source", IDENTITY_PASSPHRASE_ENV)]
+    PassphraseRequired,
+    #[error("The identity passphrase is empty")]
+    EmptyPassphrase,
+    #[error("Wrong passphrase for the identity file")]
+    WrongPassphrase,
+    #[error("Unsupported identity file version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Malformed identity file: {0}")]
+    Malformed(String),
+    #[error("OS keyring error: {0}")]
+    Keyring(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityManager;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_seal_and_open() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = IdentityManager::new(temp_dir.path().to_string_lossy().to_string());
+        let identity = manager.initialize_identity().await.unwrap();
+
+        let file = EncryptedIdentityFile::seal(&identity, "correct horse", KdfParams::default()).unwrap();
+        assert_eq!(file.open("correct horse").unwrap().ed25519_keypair, identity.ed25519_keypair);
+        assert!(matches!(file.open("battery staple"), Err(IdentityFileError::WrongPassphrase)));
+
+        let json = serde_json::to_string(&file).unwrap();
+        assert!(!json.contains(&hex::encode(&identity.x25519_secret)));
+        assert!(matches!(serde_json::from_str(&json).unwrap(), StoredIdentity::Encrypted(_)));
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_file_is_encrypted_on_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage_path = temp_dir.path().to_string_lossy().to_string();
+        let identity = IdentityManager::new(storage_path.clone()).initialize_identity().await.unwrap();
+
+        let mut manager = IdentityManager::new(storage_path.clone());
+        manager.set_key_source(IdentityKeySource::Passphrase("correct horse".to_string()));
+        assert_eq!(manager.initialize_identity().await.unwrap().node_id, identity.node_id);
+
+        let json = std::fs::read_to_string(temp_dir.path().join("identity.json")).unwrap();
+        assert!(matches!(serde_json::from_str(&json).unwrap(), StoredIdentity::Encrypted(_)));
+
+        // Without the passphrase the file is not read, nor replaced by a new identity
+        assert!(IdentityManager::new(storage_path.clone()).initialize_identity().await.is_err());
+        let mut manager = IdentityManager::new(storage_path);
+        manager.set_key_source(IdentityKeySource::Passphrase("correct horse".to_string()));
+        assert_eq!(manager.initialize_identity().await.unwrap().node_id, identity.node_id);
+    }
+}
@@ -0,0 +1,609 @@
+//! Encrypted direct messages between node operators
+//!
+//! Operators exchange short messages (upgrade coordination, incident notes)
+//! addressed by node ID. Each message is encrypted under a key derived from
+//! both an ephemeral X25519 exchange and a Kyber768 encapsulation against the
+//! recipient's identity keys, so it stays confidential as long as either
+//! scheme holds, and is signed with the sender's Ed25519 identity key. Only
+//! senders added as contacts are accepted. Messages and read receipts are
+//! kept in a local mailbox file; nothing here touches the DAG or consensus.
+
+use crate::identity::{IdentityInfo, NodeIdentity};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
+use pqcrypto_kyber::kyber768;
+use pqcrypto_traits::kem::{Ciphertext as _, PublicKey as _, SecretKey as _, SharedSecret as _};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Longest message body accepted, in bytes
+pub const MAX_MESSAGE_BYTES: usize = 16 * 1024;
+
+const MESSAGE_DOMAIN: &[u8] = b"quantum-dag-operator-message";
+const RECEIPT_DOMAIN: &[u8] = b"quantum-dag-operator-receipt";
+
+/// Public keys of another node operator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperatorContact {
+    pub node_id: String,
+    pub ed25519_public: Vec<u8>,
+    pub x25519_public: Vec<u8>,
+    pub kyber_public: Vec<u8>,
+}
+
+impl OperatorContact {
+    /// Contact entry for a local identity
+    pub fn from_identity(identity: &NodeIdentity) -> Self {
+        Self {
+            node_id: identity.node_id.clone(),
+            ed25519_public: identity.ed25519_public.clone(),
+            x25519_public: identity.x25519_public.clone(),
+            kyber_public: identity.kyber_public.clone(),
+        }
+    }
+
+    /// Contact entry from the identity info another node publishes
+    pub fn from_identity_info(info: &IdentityInfo) -> Result<Self, MessagingError> {
+        let decode = |name: &str, value: &str| {
+            hex::decode(value).map_err(|e| MessagingError::InvalidKey(format!("{}: {}", name, e)))
+        };
+        let contact = Self {
+            node_id: info.node_id.clone(),
+            ed25519_public: decode("ed25519_public", &info.ed25519_public)?,
+            x25519_public: decode("x25519_public", &info.x25519_public)?,
+            kyber_public: decode("kyber_public", &info.kyber_public)?,
+        };
+        contact.validate()?;
+        Ok(contact)
+    }
+
+    fn validate(&self) -> Result<(), MessagingError> {
+        PublicKey::from_bytes(&self.ed25519_public)
+            .map_err(|e| MessagingError::InvalidKey(format!("ed25519_public: {}", e)))?;
+        x25519_key(&self.x25519_public)?;
+        kyber768::PublicKey::from_bytes(&self.kyber_public)
+            .map_err(|e| MessagingError::InvalidKey(format!("kyber_public: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Encrypted and signed operator message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedMessage {
+    pub id: String,
+    pub sender: String,
+    pub recipient: String,
+    pub sent_at: u64,
+    /// Sender's ephemeral X25519 public key
+    pub ephemeral_public: Vec<u8>,
+    /// Kyber768 encapsulation to the recipient
+    pub kem_ciphertext: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    /// Ed25519 signature over all other fields
+    pub signature: Vec<u8>,
+}
+
+impl SealedMessage {
+    fn header(&self) -> Vec<u8> {
+        let mut header = Vec::new();
+        for field in [self.id.as_bytes(), self.sender.as_bytes(), self.recipient.as_bytes()] {
+            header.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            header.extend_from_slice(field);
+        }
+        header.extend_from_slice(&self.sent_at.to_le_bytes());
+        header
+    }
+
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut data = MESSAGE_DOMAIN.to_vec();
+        data.extend_from_slice(&self.header());
+        for field in [&self.ephemeral_public, &self.kem_ciphertext, &self.nonce, &self.ciphertext] {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field);
+        }
+        data
+    }
+}
+
+/// Signed acknowledgement that a message was read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadReceipt {
+    pub message_id: String,
+    /// Node ID of the reader, i.e. the original recipient
+    pub reader: String,
+    /// Node ID of the original sender
+    pub sender: String,
+    pub read_at: u64,
+    pub signature: Vec<u8>,
+}
+
+impl ReadReceipt {
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut data = RECEIPT_DOMAIN.to_vec();
+        for field in [self.message_id.as_bytes(), self.reader.as_bytes(), self.sender.as_bytes()] {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field);
+        }
+        data.extend_from_slice(&self.read_at.to_le_bytes());
+        data
+    }
+}
+
+/// Payload sent between operators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OperatorEnvelope {
+    Message(SealedMessage),
+    Receipt(ReadReceipt),
+}
+
+impl OperatorEnvelope {
+    /// Node ID the envelope is addressed to
+    pub fn recipient(&self) -> &str {
+        match self {
+            OperatorEnvelope::Message(message) => &message.recipient,
+            OperatorEnvelope::Receipt(receipt) => &receipt.sender,
+        }
+    }
+}
+
+/// Encrypt and sign `body` from `sender` to `recipient`
+pub fn seal_message(
+    sender: &NodeIdentity,
+    recipient: &OperatorContact,
+    body: &str,
+) -> Result<SealedMessage, MessagingError> {
+    if body.len() > MAX_MESSAGE_BYTES {
+        return Err(MessagingError::TooLarge(body.len(), MAX_MESSAGE_BYTES));
+    }
+
+    let ephemeral = x25519_dalek::StaticSecret::from(rand::random::<[u8; 32]>());
+    let ephemeral_public = x25519_dalek::PublicKey::from(&ephemeral).to_bytes().to_vec();
+    let x25519_shared = ephemeral.diffie_hellman(&x25519_key(&recipient.x25519_public)?);
+
+    let kyber_public = kyber768::PublicKey::from_bytes(&recipient.kyber_public)
+        .map_err(|e| MessagingError::InvalidKey(format!("kyber_public: {}", e)))?;
+    let (kyber_shared, kem_ciphertext) = kyber768::encapsulate(&kyber_public);
+
+    let mut message = SealedMessage {
+        id: Uuid::new_v4().to_string(),
+        sender: sender.node_id.clone(),
+        recipient: recipient.node_id.clone(),
+        sent_at: chrono::Utc::now().timestamp() as u64,
+        ephemeral_public,
+        kem_ciphertext: kem_ciphertext.as_bytes().to_vec(),
+        nonce: rand::random::<[u8; 12]>().to_vec(),
+        ciphertext: Vec::new(),
+        signature: Vec::new(),
+    };
+
+    let cipher = message_cipher(x25519_shared.as_bytes(), kyber_shared.as_bytes(), &message);
+    message.ciphertext = cipher
+        .encrypt(Nonce::from_slice(&message.nonce), Payload { msg: body.as_bytes(), aad: &message.header() })
+        .map_err(|_| MessagingError::Encryption)?;
+    message.signature = sign(sender, &message.signed_bytes())?;
+    Ok(message)
+}
+
+/// Verify and decrypt a message sent by `sender` to `recipient`
+pub fn open_message(
+    recipient: &NodeIdentity,
+    sender: &OperatorContact,
+    message: &SealedMessage,
+) -> Result<String, MessagingError> {
+    if message.recipient != recipient.node_id {
+        return Err(MessagingError::NotRecipient(message.recipient.clone()));
+    }
+    if message.sender != sender.node_id {
+        return Err(MessagingError::UnknownContact(message.sender.clone()));
+    }
+    verify(&sender.ed25519_public, &message.signed_bytes(), &message.signature)?;
+
+    let x25519_secret = x25519_dalek::StaticSecret::from(key_array(&recipient.x25519_secret)?);
+    let x25519_shared = x25519_secret.diffie_hellman(&x25519_key(&message.ephemeral_public)?);
+
+    let kyber_secret = kyber768::SecretKey::from_bytes(&recipient.kyber_secret)
+        .map_err(|e| MessagingError::InvalidKey(format!("kyber_secret: {}", e)))?;
+    let kem_ciphertext = kyber768::Ciphertext::from_bytes(&message.kem_ciphertext)
+        .map_err(|_| MessagingError::Decryption)?;
+    let kyber_shared = kyber768::decapsulate(&kem_ciphertext, &kyber_secret);
+
+    let cipher = message_cipher(x25519_shared.as_bytes(), kyber_shared.as_bytes(), message);
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(&message.nonce),
+            Payload { msg: &message.ciphertext, aad: &message.header() },
+        )
+        .map_err(|_| MessagingError::Decryption)?;
+    String::from_utf8(plaintext).map_err(|_| MessagingError::Decryption)
+}
+
+fn message_cipher(x25519_shared: &[u8], kyber_shared: &[u8], message: &SealedMessage) -> ChaCha20Poly1305 {
+    let mut hasher = Sha3_256::new();
+    hasher.update(MESSAGE_DOMAIN);
+    hasher.update(x25519_shared);
+    hasher.update(kyber_shared);
+    hasher.update(&message.ephemeral_public);
+    hasher.update(&message.kem_ciphertext);
+    ChaCha20Poly1305::new(Key::from_slice(&hasher.finalize()))
+}
+
+fn key_array(bytes: &[u8]) -> Result<[u8; 32], MessagingError> {
+    bytes
+        .try_into()
+        .map_err(|_| MessagingError::InvalidKey(format!("expected 32-byte key, got {} bytes", bytes.len())))
+}
+
+fn x25519_key(bytes: &[u8]) -> Result<x25519_dalek::PublicKey, MessagingError> {
+    Ok(x25519_dalek::PublicKey::from(key_array(bytes)?))
+}
+
+fn sign(identity: &NodeIdentity, data: &[u8]) -> Result<Vec<u8>, MessagingError> {
+    let keypair = Keypair::from_bytes(&identity.ed25519_keypair)
+        .map_err(|e| MessagingError::InvalidKey(format!("ed25519_keypair: {}", e)))?;
+    Ok(keypair.sign(data).to_bytes().to_vec())
+}
+
+fn verify(public_key: &[u8], data: &[u8], signature: &[u8]) -> Result<(), MessagingError> {
+    let public_key = PublicKey::from_bytes(public_key)
+        .map_err(|e| MessagingError::InvalidKey(format!("ed25519_public: {}", e)))?;
+    let signature = Signature::try_from(signature).map_err(|_| MessagingError::InvalidSignature)?;
+    public_key.verify(data, &signature).map_err(|_| MessagingError::InvalidSignature)
+}
+
+/// Whether a stored message was sent or received by this node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageDirection {
+    Inbound,
+    Outbound,
+}
+
+/// Decrypted message kept in the local mailbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperatorMessage {
+    pub id: String,
+    /// The other operator's node ID
+    pub peer: String,
+    pub direction: MessageDirection,
+    pub body: String,
+    pub sent_at: u64,
+    /// When the recipient read the message; for outbound messages this is
+    /// filled in from their read receipt
+    pub read_at: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MailboxState {
+    contacts: HashMap<String, OperatorContact>,
+    messages: Vec<OperatorMessage>,
+}
+
+/// Local store of operator contacts, messages and read receipts
+#[derive(Debug)]
+pub struct OperatorMailbox {
+    path: Option<PathBuf>,
+    state: MailboxState,
+}
+
+impl OperatorMailbox {
+    /// Mailbox that is never written to disk
+    pub fn in_memory() -> Self {
+        Self { path: None, state: MailboxState::default() }
+    }
+
+    /// Load the mailbox stored at `path`, or start an empty one
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self, MessagingError> {
+        let path = path.into();
+        let state = match tokio::fs::read_to_string(&path).await {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| MessagingError::Storage(e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => MailboxState::default(),
+            Err(e) => return Err(MessagingError::Storage(e.to_string())),
+        };
+        Ok(Self { path: Some(path), state })
+    }
+
+    async fn save(&self) -> Result<(), MessagingError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| MessagingError::Storage(e.to_string()))?;
+        }
+        let json = serde_json::to_string_pretty(&self.state).map_err(|e| MessagingError::Storage(e.to_string()))?;
+        tokio::fs::write(path, json).await.map_err(|e| MessagingError::Storage(e.to_string()))
+    }
+
+    /// Add or replace a contact; only contacts can message this node
+    pub async fn add_contact(&mut self, contact: OperatorContact) -> Result<(), MessagingError> {
+        contact.validate()?;
+        log::info!("📇 Added operator contact {}", contact.node_id);
+        self.state.contacts.insert(contact.node_id.clone(), contact);
+        self.save().await
+    }
+
+    /// Remove a contact, keeping its message history
+    pub async fn remove_contact(&mut self, node_id: &str) -> Result<bool, MessagingError> {
+        let removed = self.state.contacts.remove(node_id).is_some();
+        if removed {
+            self.save().await?;
+        }
+        Ok(removed)
+    }
+
+    /// Known contacts
+    pub fn contacts(&self) -> Vec<OperatorContact> {
+        let mut contacts: Vec<_> = self.state.contacts.values().cloned().collect();
+        contacts.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        contacts
+    }
+
+    /// Stored messages, oldest first, optionally limited to one peer
+    pub fn messages(&self, peer: Option<&str>) -> Vec<OperatorMessage> {
+        self.state
+            .messages
+            .iter()
+            .filter(|message| peer.is_none_or(|peer| message.peer == peer))
+            .cloned()
+            .collect()
+    }
+
+    /// Number of received messages not yet read
+    pub fn unread_count(&self) -> usize {
+        self.state
+            .messages
+            .iter()
+            .filter(|message| message.direction == MessageDirection::Inbound && message.read_at.is_none())
+            .count()
+    }
+
+    /// Encrypt a message to a contact and store a copy
+    ///
+    /// Returns the stored copy and the envelope to deliver.
+    pub async fn send(
+        &mut self,
+        identity: &NodeIdentity,
+        recipient: &str,
+        body: &str,
+    ) -> Result<(OperatorMessage, OperatorEnvelope), MessagingError> {
+        let contact = self
+            .state
+            .contacts
+            .get(recipient)
+            .ok_or_else(|| MessagingError::UnknownContact(recipient.to_string()))?;
+        let sealed = seal_message(identity, contact, body)?;
+
+        let message = OperatorMessage {
+            id: sealed.id.clone(),
+            peer: recipient.to_string(),
+            direction: MessageDirection::Outbound,
+            body: body.to_string(),
+            sent_at: sealed.sent_at,
+            read_at: None,
+        };
+        self.state.messages.push(message.clone());
+        self.save().await?;
+        Ok((message, OperatorEnvelope::Message(sealed)))
+    }
+
+    /// Accept an envelope from another operator
+    ///
+    /// Returns the stored message for new messages and `None` for receipts
+    /// and duplicates.
+    pub async fn receive(
+        &mut self,
+        identity: &NodeIdentity,
+        envelope: OperatorEnvelope,
+    ) -> Result<Option<OperatorMessage>, MessagingError> {
+        match envelope {
+            OperatorEnvelope::Message(sealed) => {
+                let contact = self
+                    .state
+                    .contacts
+                    .get(&sealed.sender)
+                    .ok_or_else(|| MessagingError::UnknownContact(sealed.sender.clone()))?;
+                let body = open_message(identity, contact, &sealed)?;
+
+                if self.state.messages.iter().any(|message| message.id == sealed.id) {
+                    return Ok(None);
+                }
+                let message = OperatorMessage {
+                    id: sealed.id,
+                    peer: sealed.sender,
+                    direction: MessageDirection::Inbound,
+                    body,
+                    sent_at: sealed.sent_at,
+                    read_at: None,
+                };
+                log::info!("✉️ Operator message {} received from {}", message.id, message.peer);
+                self.state.messages.push(message.clone());
+                self.save().await?;
+                Ok(Some(message))
+            }
+            OperatorEnvelope::Receipt(receipt) => {
+                if receipt.sender != identity.node_id {
+                    return Err(MessagingError::NotRecipient(receipt.sender));
+                }
+                let contact = self
+                    .state
+                    .contacts
+                    .get(&receipt.reader)
+                    .ok_or_else(|| MessagingError::UnknownContact(receipt.reader.clone()))?;
+                verify(&contact.ed25519_public, &receipt.signed_bytes(), &receipt.signature)?;
+
+                let message = self
+                    .state
+                    .messages
+                    .iter_mut()
+                    .find(|message| {
+                        message.id == receipt.message_id
+                            && message.direction == MessageDirection::Outbound
+                            && message.peer == receipt.reader
+                    })
+                    .ok_or_else(|| MessagingError::MessageNotFound(receipt.message_id.clone()))?;
+                if message.read_at.is_none() {
+                    message.read_at = Some(receipt.read_at);
+                    self.save().await?;
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Mark a received message as read and return the receipt for its sender
+    pub async fn mark_read(
+        &mut self,
+        identity: &NodeIdentity,
+        message_id: &str,
+    ) -> Result<OperatorEnvelope, MessagingError> {
+        let message = self
+            .state
+            .messages
+            .iter_mut()
+            .find(|message| message.id == message_id && message.direction == MessageDirection::Inbound)
+            .ok_or_else(|| MessagingError::MessageNotFound(message_id.to_string()))?;
+        let read_at = *message.read_at.get_or_insert_with(|| chrono::Utc::now().timestamp() as u64);
+
+        let mut receipt = ReadReceipt {
+            message_id: message.id.clone(),
+            reader: identity.node_id.clone(),
+            sender: message.peer.clone(),
+            read_at,
+            signature: Vec::new(),
+        };
+        receipt.signature = sign(identity, &receipt.signed_bytes())?;
+        self.save().await?;
+        Ok(OperatorEnvelope::Receipt(receipt))
+    }
+}
+
+/// Operator messaging errors
+#[derive(Debug, thiserror::Error)]
+pub enum MessagingError {
+    #[error("Unknown operator contact: {0}")]
+    UnknownContact(String),
+    #[error("Envelope is addressed to {0}, not this node")]
+    NotRecipient(String),
+    #[error("Message not found: {0}")]
+    MessageNotFound(String),
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    #[error("Invalid signature")]
+    InvalidSignature,
+    #[error("Message of {0} bytes exceeds limit of {1} bytes")]
+    TooLarge(usize, usize),
+    #[error("Encryption failed")]
+    Encryption,
+    #[error("Message could not be decrypted")]
+    Decryption,
+    #[error("Mailbox storage error: {0}")]
+    Storage(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(name: &str) -> NodeIdentity {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&rand::random::<[u8; 32]>()).unwrap();
+        let ed25519 = Keypair { public: PublicKey::from(&secret), secret };
+        let x25519_secret = rand::random::<[u8; 32]>();
+        let x25519_public = x25519_dalek::PublicKey::from(&x25519_dalek::StaticSecret::from(x25519_secret));
+        let (kyber_public, kyber_secret) = kyber768::keypair();
+
+        NodeIdentity {
+            node_id: format!("qd_{}", name),
+            ed25519_keypair: ed25519.to_bytes().to_vec(),
+            ed25519_public: ed25519.public.to_bytes().to_vec(),
+            x25519_secret: x25519_secret.to_vec(),
+            x25519_public: x25519_public.to_bytes().to_vec(),
+            kyber_secret: kyber_secret.as_bytes().to_vec(),
+            kyber_public: kyber_public.as_bytes().to_vec(),
+            dilithium3_keypair: vec![],
+            dilithium3_public: vec![],
+            dilithium5_keypair: vec![],
+            dilithium5_public: vec![],
+            falcon512_keypair: vec![],
+            falcon512_public: vec![],
+            falcon1024_keypair: vec![],
+            falcon1024_public: vec![],
+            created_at: 0,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_message_round_trip_with_read_receipt() {
+        let alice = identity("alice");
+        let bob = identity("bob");
+        let mut alice_box = OperatorMailbox::in_memory();
+        let mut bob_box = OperatorMailbox::in_memory();
+        alice_box.add_contact(OperatorContact::from_identity(&bob)).await.unwrap();
+        bob_box.add_contact(OperatorContact::from_identity(&alice)).await.unwrap();
+
+        let (_, envelope) = alice_box.send(&alice, &bob.node_id, "upgrade at height 1000").await.unwrap();
+        let received = bob_box.receive(&bob, envelope.clone()).await.unwrap().unwrap();
+        assert_eq!(received.body, "upgrade at height 1000");
+        assert_eq!(bob_box.unread_count(), 1);
+
+        // Redelivery is ignored
+        assert!(bob_box.receive(&bob, envelope).await.unwrap().is_none());
+
+        let receipt = bob_box.mark_read(&bob, &received.id).await.unwrap();
+        assert_eq!(bob_box.unread_count(), 0);
+        alice_box.receive(&alice, receipt).await.unwrap();
+        assert!(alice_box.messages(Some(&bob.node_id))[0].read_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_strangers_and_tampering() {
+        let alice = identity("alice");
+        let bob = identity("bob");
+        let mallory = identity("mallory");
+        let bob_contact = OperatorContact::from_identity(&bob);
+        let alice_contact = OperatorContact::from_identity(&alice);
+
+        // Not a contact
+        let sealed = seal_message(&mallory, &bob_contact, "hi").unwrap();
+        let mut bob_box = OperatorMailbox::in_memory();
+        assert!(matches!(
+            bob_box.receive(&bob, OperatorEnvelope::Message(sealed)).await,
+            Err(MessagingError::UnknownContact(_))
+        ));
+
+        // Forged sender
+        let mut forged = seal_message(&mallory, &bob_contact, "hi").unwrap();
+        forged.sender = alice.node_id.clone();
+        assert!(matches!(open_message(&bob, &alice_contact, &forged), Err(MessagingError::InvalidSignature)));
+
+        // Modified ciphertext
+        let mut sealed = seal_message(&alice, &bob_contact, "hi").unwrap();
+        sealed.ciphertext[0] ^= 1;
+        assert!(open_message(&bob, &alice_contact, &sealed).is_err());
+
+        // Someone else's message
+        let sealed = seal_message(&alice, &bob_contact, "hi").unwrap();
+        assert!(matches!(open_message(&mallory, &alice_contact, &sealed), Err(MessagingError::NotRecipient(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mailbox_persists() {
+        let alice = identity("alice");
+        let bob = identity("bob");
+        let path = std::env::temp_dir().join(format!("operator-mailbox-{}.json", Uuid::new_v4()));
+
+        let mut mailbox = OperatorMailbox::open(&path).await.unwrap();
+        mailbox.add_contact(OperatorContact::from_identity(&bob)).await.unwrap();
+        mailbox.send(&alice, &bob.node_id, "incident resolved").await.unwrap();
+
+        let reopened = OperatorMailbox::open(&path).await.unwrap();
+        assert_eq!(reopened.contacts().len(), 1);
+        assert_eq!(reopened.messages(None)[0].body, "incident resolved");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -4,13 +4,38 @@
 //! and node identity management for post-quantum security.
 
 use crate::{BlockchainError, TransactionId, core::{Transaction, QuantumProof}};
+use crate::events::{EventBus, NodeEvent};
 use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
 use x25519_dalek::{StaticSecret};
 use pqcrypto_dilithium::{dilithium3, dilithium5};
+use pqcrypto_falcon::{falcon1024, falcon512};
+use pqcrypto_kyber::kyber768;
+use pqcrypto_traits::kem::{PublicKey as _, SecretKey as _};
+use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey as _, SecretKey as _};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 use std::sync::Arc;
+use keystore::StoredIdentity;
+
+pub mod attestation;
+pub mod disclosure;
+pub mod keystore;
+pub mod messaging;
+pub mod recovery;
+pub mod rotation;
+pub mod session;
+pub mod signature_policy;
+pub mod threshold;
+pub use attestation::{NodeAttestation, ATTESTATION_DOMAIN, MAX_ATTESTATION_CHALLENGE};
+pub use disclosure::{AttributeCredential, DisclosedAttribute, DisclosureError, DisclosureProof, SignedAttributes};
+pub use keystore::{keyring_supported, EncryptedIdentityFile, IdentityFileError, IdentityKeySource, KdfParams, IDENTITY_PASSPHRASE_ENV};
+pub use messaging::{MessageDirection, MessagingError, OperatorContact, OperatorEnvelope, OperatorMailbox, OperatorMessage, ReadReceipt, SealedMessage};
+pub use recovery::{combine_shares, split_secret, validate_identity, BackupShare, EncryptedIdentityBackup, RecoveryError, ShareScheme};
+pub use rotation::{PeerIdentityChange, RotationAttestation, RotationError, ROTATION_DOMAIN};
+pub use session::{accept_session, initiate_session, SessionError, SessionOffer, SessionSecret, MAX_OFFER_AGE_SECS};
+pub use signature_policy::{SignatureBand, SignaturePolicy};
+pub use threshold::{DkgParticipant, ThresholdError, ThresholdKeyShare, ThresholdSigners};
 
 /// Node identity with cryptographic keys
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,12 +50,28 @@ pub struct NodeIdentity {
     pub x25519_secret: Vec<u8>,
     /// X25519 public key for key exchange
     pub x25519_public: Vec<u8>,
+    /// Kyber768 secret key for post-quantum key exchange
+    #[serde(default)]
+    pub kyber_secret: Vec<u8>,
+    /// Kyber768 public key
+    #[serde(default)]
+    pub kyber_public: Vec<u8>,
     /// Dilithium3 keypair for post-quantum signing
     pub dilithium3_keypair: Vec<u8>,
     pub dilithium3_public: Vec<u8>,
     /// Dilithium5 keypair for higher security
     pub dilithium5_keypair: Vec<u8>,
     pub dilithium5_public: Vec<u8>,
+    /// Falcon-512 keypair for compact post-quantum signing
+    #[serde(default)]
+    pub falcon512_keypair: Vec<u8>,
+    #[serde(default)]
+    pub falcon512_public: Vec<u8>,
+    /// Falcon-1024 keypair
+    #[serde(default)]
+    pub falcon1024_keypair: Vec<u8>,
+    #[serde(default)]
+    pub falcon1024_public: Vec<u8>,
     /// Node creation timestamp
     pub created_at: u64,
     /// Node metadata
@@ -43,12 +84,22 @@ pub struct IdentityManager {
     current_identity: Arc<RwLock<Option<NodeIdentity>>>,
     /// Known peer identities
     peer_identities: HashMap<String, NodeIdentity>,
+    /// Public identities peers announced, by node ID
+    known_peers: HashMap<String, IdentityInfo>,
     /// Identity storage path
     storage_path: String,
+    /// Bus receiving rotation events
+    events: Option<EventBus>,
+    /// How backup keys are split
+    backup_scheme: ShareScheme,
+    /// Passphrase `identity.json` is encrypted with
+    key_source: IdentityKeySource,
+    /// Identities replaced by rotation, kept while their keys are in their grace window
+    retired: Vec<RetiredIdentity>,
 }
 
 /// Signature types supported by the identity system
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SignatureType {
     /// Ed25519 signature (classical)
     Ed25519,
@@ -57,7 +108,12 @@ pub enum SignatureType {
     /// Dilithium5 signature (post-quantum, level 5)
     Dilithium5,
     /// Hybrid signature (Ed25519 + Dilithium3)
+    #[default]
     Hybrid,
+    /// Falcon-512 signature (post-quantum, level 1), a fifth the size of Dilithium3
+    Falcon512,
+    /// Falcon-1024 signature (post-quantum, level 5)
+    Falcon1024,
 }
 
 /// Signature wrapper for different signature types
@@ -68,6 +124,9 @@ pub struct NodeSignature {
     pub public_key: Vec<u8>,
     pub timestamp: u64,
     pub nonce: u64,
+    /// Signers of an aggregated threshold signature under a group key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<ThresholdSigners>,
 }
 
 /// Identity information for API endpoints
@@ -76,27 +135,104 @@ pub struct IdentityInfo {
     pub node_id: String,
     pub ed25519_public: String,
     pub x25519_public: String,
+    #[serde(default)]
+    pub kyber_public: String,
     pub dilithium3_public: String,
     pub dilithium5_public: String,
+    #[serde(default)]
+    pub falcon512_public: String,
+    #[serde(default)]
+    pub falcon1024_public: String,
     pub signature_types: Vec<String>,
     pub created_at: u64,
     pub metadata: HashMap<String, String>,
 }
 
+impl IdentityInfo {
+    /// Public part of `identity`
+    pub fn from_identity(identity: &NodeIdentity) -> Self {
+        Self {
+            node_id: identity.node_id.clone(),
+            ed25519_public: hex::encode(&identity.ed25519_public),
+            x25519_public: hex::encode(&identity.x25519_public),
+            kyber_public: hex::encode(&identity.kyber_public),
+            dilithium3_public: hex::encode(&identity.dilithium3_public),
+            dilithium5_public: hex::encode(&identity.dilithium5_public),
+            falcon512_public: hex::encode(&identity.falcon512_public),
+            falcon1024_public: hex::encode(&identity.falcon1024_public),
+            signature_types: vec![
+                "ed25519".to_string(),
+                "dilithium3".to_string(),
+                "dilithium5".to_string(),
+                "hybrid".to_string(),
+                "falcon512".to_string(),
+                "falcon1024".to_string(),
+            ],
+            created_at: identity.created_at,
+            metadata: identity.metadata.clone(),
+        }
+    }
+
+    /// Whether `public_key` is one of the identity's signing keys
+    pub fn has_signing_key(&self, public_key: &[u8]) -> bool {
+        let public_key = hex::encode(public_key);
+        [&self.ed25519_public, &self.dilithium3_public, &self.dilithium5_public, &self.falcon512_public, &self.falcon1024_public]
+            .into_iter()
+            .any(|key| !key.is_empty() && *key == public_key)
+    }
+}
+
 impl IdentityManager {
     /// Create a new identity manager
     pub fn new(storage_path: String) -> Self {
         Self {
             current_identity: Arc::new(RwLock::new(None)),
             peer_identities: HashMap::new(),
+            known_peers: HashMap::new(),
             storage_path,
+            events: None,
+            backup_scheme: ShareScheme::default(),
+            key_source: IdentityKeySource::default(),
+            retired: Vec::new(),
         }
     }
 
+    /// Publish identity rotations to `events`
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = Some(events);
+    }
+
+    /// Split future backup keys according to `scheme`
+    pub fn set_backup_scheme(&mut self, scheme: ShareScheme) {
+        self.backup_scheme = scheme;
+    }
+
+    /// Encrypt the identity file with the passphrase from `source`
+    pub fn set_key_source(&mut self, source: IdentityKeySource) {
+        self.key_source = source;
+    }
+
     /// Generate or load node identity
     pub async fn initialize_identity(&mut self) -> Result<NodeIdentity, BlockchainError> {
+        self.retired = self.load_retired().await?;
+
         // Try to load existing identity
-        if let Some(identity) = self.load_identity().await? {
+        if let Some(mut identity) = self.load_identity().await? {
+            // Identities created before operator messaging have no Kyber keys
+            if identity.kyber_public.is_empty() {
+                let (kyber_public, kyber_secret) = kyber768::keypair();
+                identity.kyber_public = kyber_public.as_bytes().to_vec();
+                identity.kyber_secret = kyber_secret.as_bytes().to_vec();
+                self.save_identity(&identity).await?;
+            }
+            // Nor do those created before Falcon signatures
+            if identity.falcon512_public.is_empty() {
+                (
+                    (identity.falcon512_keypair, identity.falcon512_public),
+                    (identity.falcon1024_keypair, identity.falcon1024_public),
+                ) = Self::falcon_keys();
+                self.save_identity(&identity).await?;
+            }
             *self.current_identity.write().await = Some(identity.clone());
             log::info!("🔑 Loaded existing node identity: {}", identity.node_id);
             return Ok(identity);
@@ -123,6 +259,9 @@ impl IdentityManager {
         let x25519_secret = StaticSecret::random_from_rng(&mut rand::thread_rng());
         let x25519_public = x25519_dalek::PublicKey::from(&x25519_secret).to_bytes().to_vec();
 
+        // Generate Kyber768 keypair
+        let (kyber_public, kyber_secret) = kyber768::keypair();
+
         // Generate Dilithium3 keypair
         let (dilithium3_pk, dilithium3_sk) = dilithium3::keypair();
         let dilithium3_keypair = [dilithium3_pk.as_ref(), dilithium3_sk.as_ref()].concat();
@@ -133,13 +272,16 @@ impl IdentityManager {
         let dilithium5_keypair = [dilithium5_pk.as_ref(), dilithium5_sk.as_ref()].concat();
         let dilithium5_public = dilithium5_pk.as_ref().to_vec();
 
+        // Generate Falcon keypairs
+        let ((falcon512_keypair, falcon512_public), (falcon1024_keypair, falcon1024_public)) = Self::falcon_keys();
+
         // Generate node ID
-        let node_id = self.generate_node_id(&ed25519_public, &dilithium3_public);
+        let node_id = Self::generate_node_id(&ed25519_public, &dilithium3_public);
 
         let mut metadata = HashMap::new();
         metadata.insert("version".to_string(), env!("CARGO_PKG_VERSION").to_string());
         metadata.insert("network".to_string(), "quantum-dag".to_string());
-        metadata.insert("signature_schemes".to_string(), "ed25519,dilithium3,dilithium5".to_string());
+        metadata.insert("signature_schemes".to_string(), "ed25519,dilithium3,dilithium5,falcon512,falcon1024".to_string());
 
         Ok(NodeIdentity {
             node_id,
@@ -147,17 +289,33 @@ impl IdentityManager {
             ed25519_public,
             x25519_secret: x25519_secret.to_bytes().to_vec(),
             x25519_public,
+            kyber_secret: kyber_secret.as_bytes().to_vec(),
+            kyber_public: kyber_public.as_bytes().to_vec(),
             dilithium3_keypair,
             dilithium3_public,
             dilithium5_keypair,
             dilithium5_public,
+            falcon512_keypair,
+            falcon512_public,
+            falcon1024_keypair,
+            falcon1024_public,
             created_at: chrono::Utc::now().timestamp() as u64,
             metadata,
         })
     }
 
+    /// Falcon-512 and Falcon-1024 keypairs, each stored as public key followed by secret key
+    fn falcon_keys() -> ((Vec<u8>, Vec<u8>), (Vec<u8>, Vec<u8>)) {
+        let (falcon512_pk, falcon512_sk) = falcon512::keypair();
+        let (falcon1024_pk, falcon1024_sk) = falcon1024::keypair();
+        (
+            ([falcon512_pk.as_bytes(), falcon512_sk.as_bytes()].concat(), falcon512_pk.as_bytes().to_vec()),
+            ([falcon1024_pk.as_bytes(), falcon1024_sk.as_bytes()].concat(), falcon1024_pk.as_bytes().to_vec()),
+        )
+    }
+
     /// Generate node ID from public keys
-    fn generate_node_id(&self, ed25519_public: &[u8], dilithium_public: &[u8]) -> String {
+    fn generate_node_id(ed25519_public: &[u8], dilithium_public: &[u8]) -> String {
         use sha3::{Digest, Sha3_256};
         
         let mut hasher = Sha3_256::new();
@@ -205,6 +363,16 @@ impl IdentityManager {
                 
                 [ed25519_sig, dilithium_sig].concat()
             }
+            SignatureType::Falcon512 => {
+                let sk = falcon512::SecretKey::from_bytes(&identity.falcon512_keypair[identity.falcon512_public.len()..])
+                    .map_err(|e| BlockchainError::Other(format!("Invalid Falcon-512 secret key: {}", e)))?;
+                falcon512::detached_sign(data, &sk).as_bytes().to_vec()
+            }
+            SignatureType::Falcon1024 => {
+                let sk = falcon1024::SecretKey::from_bytes(&identity.falcon1024_keypair[identity.falcon1024_public.len()..])
+                    .map_err(|e| BlockchainError::Other(format!("Invalid Falcon-1024 secret key: {}", e)))?;
+                falcon1024::detached_sign(data, &sk).as_bytes().to_vec()
+            }
         };
 
         let public_key = match signature_type {
@@ -215,6 +383,8 @@ impl IdentityManager {
                 // For hybrid, use both public keys
                 [identity.ed25519_public.clone(), identity.dilithium3_public.clone()].concat()
             }
+            SignatureType::Falcon512 => identity.falcon512_public.clone(),
+            SignatureType::Falcon1024 => identity.falcon1024_public.clone(),
         };
 
         Ok(NodeSignature {
@@ -223,11 +393,19 @@ impl IdentityManager {
             public_key,
             timestamp: chrono::Utc::now().timestamp() as u64,
             nonce: rand::random(),
+            threshold: None,
         })
     }
 
     /// Verify a signature
     pub async fn verify(&self, data: &[u8], signature: &NodeSignature) -> Result<bool, BlockchainError> {
+        // A threshold signature is a plain Ed25519 signature under the group key
+        if let Some(signers) = &signature.threshold {
+            if signature.signature_type != SignatureType::Ed25519 || !signers.is_well_formed() {
+                return Ok(false);
+            }
+        }
+
         match signature.signature_type {
             SignatureType::Ed25519 => {
                 let public_key = PublicKey::from_bytes(&signature.public_key)?;
@@ -266,6 +444,24 @@ impl IdentityManager {
 
                 Ok(ed25519_valid && dilithium_valid)
             }
+            SignatureType::Falcon512 => {
+                let (Ok(pk), Ok(sig)) = (
+                    falcon512::PublicKey::from_bytes(&signature.public_key),
+                    falcon512::DetachedSignature::from_bytes(&signature.signature_data),
+                ) else {
+                    return Ok(false);
+                };
+                Ok(falcon512::verify_detached_signature(&sig, data, &pk).is_ok())
+            }
+            SignatureType::Falcon1024 => {
+                let (Ok(pk), Ok(sig)) = (
+                    falcon1024::PublicKey::from_bytes(&signature.public_key),
+                    falcon1024::DetachedSignature::from_bytes(&signature.signature_data),
+                ) else {
+                    return Ok(false);
+                };
+                Ok(falcon1024::verify_detached_signature(&sig, data, &pk).is_ok())
+            }
         }
     }
 
@@ -274,8 +470,8 @@ impl IdentityManager {
         // Create transaction hash for signing
         let tx_hash = self.create_transaction_hash(transaction)?;
         
-        // Use hybrid signature for maximum security
-        self.sign(&tx_hash, SignatureType::Hybrid).await
+        // Sign with the scheme recorded on the transaction
+        self.sign(&tx_hash, transaction.signature_scheme.clone()).await
     }
 
     /// Verify transaction signature
@@ -284,24 +480,11 @@ impl IdentityManager {
         self.verify(&tx_hash, signature).await
     }
 
-    /// Create transaction hash for signing
+    /// Create transaction hash for signing, over its canonical signing bytes
     fn create_transaction_hash(&self, transaction: &Transaction) -> Result<Vec<u8>, BlockchainError> {
         use sha3::{Digest, Sha3_256};
-        
-        let mut hasher = Sha3_256::new();
-        hasher.update(transaction.id.as_bytes());
-        hasher.update(&transaction.sender);
-        hasher.update(&transaction.receiver);
-        hasher.update(transaction.amount.to_le_bytes());
-        hasher.update(transaction.nonce.to_le_bytes());
-        hasher.update(transaction.timestamp.to_le_bytes());
-        
-        // Hash parent transactions
-        for parent_id in &transaction.parents {
-            hasher.update(parent_id.as_bytes());
-        }
-        
-        Ok(hasher.finalize().to_vec())
+
+        Ok(Sha3_256::digest(transaction.signing_bytes()).to_vec())
     }
 
     /// Get current node identity
@@ -314,22 +497,24 @@ impl IdentityManager {
         let identity = self.current_identity.read().await;
         let identity = identity.as_ref()
             .ok_or_else(|| BlockchainError::Other("Node identity not initialized".to_string()))?;
+        Ok(IdentityInfo::from_identity(identity))
+    }
 
-        Ok(IdentityInfo {
-            node_id: identity.node_id.clone(),
-            ed25519_public: hex::encode(&identity.ed25519_public),
-            x25519_public: hex::encode(&identity.x25519_public),
-            dilithium3_public: hex::encode(&identity.dilithium3_public),
-            dilithium5_public: hex::encode(&identity.dilithium5_public),
-            signature_types: vec![
-                "ed25519".to_string(),
-                "dilithium3".to_string(),
-                "dilithium5".to_string(),
-                "hybrid".to_string(),
-            ],
-            created_at: identity.created_at,
-            metadata: identity.metadata.clone(),
-        })
+    /// Identities replaced by rotation whose keys are still valid
+    pub fn retired_identities(&self) -> Vec<RetiredIdentity> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        self.retired.iter().filter(|retired| retired.valid_until > now).cloned().collect()
+    }
+
+    /// Whether `public_key` is a signing key of the current identity, or of
+    /// one retired less than its grace window ago
+    pub async fn is_own_key(&self, public_key: &[u8]) -> bool {
+        if let Some(identity) = self.current_identity.read().await.as_ref() {
+            if IdentityInfo::from_identity(identity).has_signing_key(public_key) {
+                return true;
+            }
+        }
+        self.retired_identities().iter().any(|retired| retired.identity.has_signing_key(public_key))
     }
 
     /// Add peer identity
@@ -343,7 +528,57 @@ impl IdentityManager {
         self.peer_identities.get(node_id)
     }
 
-    /// Save identity to storage
+    /// Public identity a peer announced
+    pub fn known_peer(&self, node_id: &str) -> Option<&IdentityInfo> {
+        self.known_peers.get(node_id)
+    }
+
+    /// Record a peer's identity announcement
+    ///
+    /// A node known under the announced previous node ID must prove the
+    /// rotation with an attestation by its previous Dilithium3 key; it is
+    /// then known under the new node ID only.
+    pub async fn accept_announcement(&mut self, announcement: &IdentityAnnouncement) -> Result<PeerIdentityChange, BlockchainError> {
+        let identity = &announcement.identity;
+        let previous = announcement.previous_node_id.as_ref()
+            .filter(|previous| **previous != identity.node_id)
+            .and_then(|previous| self.known_peers.get(previous));
+
+        let change = match previous {
+            Some(previous) => {
+                let attestation = announcement.attestation.as_ref()
+                    .ok_or_else(|| RotationError::MissingAttestation(previous.node_id.clone()))?;
+                attestation.verify(previous, identity, self).await?;
+                log::info!("🔗 Peer {} rotated its identity to {}", previous.node_id, identity.node_id);
+                let previous_node_id = previous.node_id.clone();
+                self.known_peers.remove(&previous_node_id);
+                PeerIdentityChange::Rotated { previous_node_id }
+            }
+            None if self.known_peers.contains_key(&identity.node_id) => PeerIdentityChange::Unchanged,
+            None => PeerIdentityChange::New,
+        };
+        self.known_peers.insert(identity.node_id.clone(), identity.clone());
+        Ok(change)
+    }
+
+    /// Start an encrypted session with `peer`: the shared secret, and the
+    /// offer the peer needs to derive it with `accept_session`
+    pub async fn derive_shared_secret(&self, peer: &OperatorContact) -> Result<(SessionSecret, SessionOffer), BlockchainError> {
+        let identity = self.current_identity.read().await;
+        let identity = identity.as_ref()
+            .ok_or_else(|| BlockchainError::Other("Node identity not initialized".to_string()))?;
+        Ok(initiate_session(identity, peer)?)
+    }
+
+    /// Derive the shared secret of a session `initiator` offered this node
+    pub async fn accept_session(&self, initiator: &OperatorContact, offer: &SessionOffer) -> Result<SessionSecret, BlockchainError> {
+        let identity = self.current_identity.read().await;
+        let identity = identity.as_ref()
+            .ok_or_else(|| BlockchainError::Other("Node identity not initialized".to_string()))?;
+        Ok(accept_session(identity, initiator, offer)?)
+    }
+
+    /// Save identity to storage, encrypted if a key source is set
     async fn save_identity(&self, identity: &NodeIdentity) -> Result<(), BlockchainError> {
         let identity_path = format!("{}/identity.json", self.storage_path);
         
@@ -352,14 +587,23 @@ impl IdentityManager {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let identity_json = serde_json::to_string_pretty(identity)?;
-        tokio::fs::write(&identity_path, identity_json).await?;
+        let identity_json = match self.key_source.passphrase(&self.storage_path, true)? {
+            Some(passphrase) => serde_json::to_string_pretty(&EncryptedIdentityFile::seal(identity, &passphrase, KdfParams::default())?)?,
+            None => {
+                log::warn!("⚠️ Writing node identity unencrypted to {}; set {} to encrypt it", identity_path, IDENTITY_PASSPHRASE_ENV);
+                serde_json::to_string_pretty(identity)?
+            }
+        };
+        // Replace the file whole, so an interrupted write never loses the keys
+        let temp_path = format!("{}.tmp", identity_path);
+        tokio::fs::write(&temp_path, identity_json).await?;
+        tokio::fs::rename(&temp_path, &identity_path).await?;
         
         log::debug!("Saved identity to {}", identity_path);
         Ok(())
     }
 
-    /// Load identity from storage
+    /// Load identity from storage, encrypting a plaintext file if a key source is set
     async fn load_identity(&self) -> Result<Option<NodeIdentity>, BlockchainError> {
         let identity_path = format!("{}/identity.json", self.storage_path);
         
@@ -368,7 +612,20 @@ impl IdentityManager {
         }
 
         let identity_json = tokio::fs::read_to_string(&identity_path).await?;
-        let identity: NodeIdentity = serde_json::from_str(&identity_json)?;
+        let identity = match serde_json::from_str(&identity_json)? {
+            StoredIdentity::Encrypted(file) => {
+                let passphrase = self.key_source.passphrase(&self.storage_path, false)?
+                    .ok_or(IdentityFileError::PassphraseRequired)?;
+                file.open(&passphrase)?
+            }
+            StoredIdentity::Plaintext(identity) => {
+                if self.key_source != IdentityKeySource::Plaintext {
+                    self.save_identity(&identity).await?;
+                    log::info!("🔐 Encrypted the plaintext identity file at {}", identity_path);
+                }
+                identity
+            }
+        };
         
         log::debug!("Loaded identity from {}", identity_path);
         Ok(Some(identity))
@@ -399,6 +656,8 @@ impl IdentityManager {
             SignatureType::Dilithium3 => score += 85, // Post-quantum secure
             SignatureType::Dilithium5 => score += 95, // Higher post-quantum security
             SignatureType::Hybrid => score += 90,    // Best of both worlds
+            SignatureType::Falcon512 => score += 80, // Post-quantum, level 1
+            SignatureType::Falcon1024 => score += 95, // Post-quantum, level 5
         }
 
         // Score from signature entropy
@@ -465,9 +724,60 @@ impl IdentityManager {
                 self.validate_hybrid_signature_structure(signature).await?;
                 Ok(true)
             }
+            SignatureType::Falcon512 | SignatureType::Falcon1024 => {
+                log::info!("✅ Transaction signed with post-quantum {:?}", signature.signature_type);
+                self.validate_falcon_signature_structure(signature).await?;
+                Ok(true)
+            }
         }
     }
 
+    /// Validate Falcon signature structure
+    ///
+    /// Falcon signatures are compressed, so only their maximum size is fixed.
+    async fn validate_falcon_signature_structure(&self, signature: &NodeSignature) -> Result<(), BlockchainError> {
+        let (max_size, pk_size) = match signature.signature_type {
+            SignatureType::Falcon512 => (falcon512::signature_bytes(), falcon512::public_key_bytes()),
+            SignatureType::Falcon1024 => (falcon1024::signature_bytes(), falcon1024::public_key_bytes()),
+            _ => return Err(BlockchainError::Other("Invalid signature type for Falcon validation".to_string())),
+        };
+
+        if signature.signature_data.is_empty() || signature.signature_data.len() > max_size {
+            return Err(BlockchainError::Other(format!(
+                "Invalid {:?} signature size: expected at most {}, got {}",
+                signature.signature_type,
+                max_size,
+                signature.signature_data.len()
+            )));
+        }
+        if signature.public_key.len() != pk_size {
+            return Err(BlockchainError::Other(format!(
+                "Invalid {:?} public key size: expected {}, got {}",
+                signature.signature_type,
+                pk_size,
+                signature.public_key.len()
+            )));
+        }
+
+        let entropy = self.calculate_signature_entropy(&signature.signature_data);
+        if entropy < 0.7 {
+            return Err(BlockchainError::Other(format!(
+                "Low signature entropy: {:.2} (minimum: 0.7)",
+                entropy
+            )));
+        }
+
+        let age = chrono::Utc::now().timestamp() as u64 - signature.timestamp;
+        if age > 86400 {
+            return Err(BlockchainError::Other(format!(
+                "Signature too old: {} seconds (maximum: 86400)",
+                age
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Validate Dilithium signature structure
     async fn validate_dilithium_signature_structure(&self, signature: &NodeSignature) -> Result<(), BlockchainError> {
         // Check minimum signature size for Dilithium
@@ -595,6 +905,7 @@ impl IdentityManager {
             public_key: vec![0u8; 32],
             timestamp: chrono::Utc::now().timestamp() as u64,
             nonce: rand::random(),
+            threshold: None,
         };
 
         match self.validate_pqc_key_usage(&invalid_sig).await {
@@ -618,6 +929,7 @@ impl IdentityManager {
             public_key: vec![1u8; dilithium3::public_key_size()],
             timestamp: chrono::Utc::now().timestamp() as u64 - 172800, // 2 days ago
             nonce: rand::random(),
+            threshold: None,
         };
 
         match self.validate_pqc_key_usage(&old_sig).await {
@@ -641,6 +953,7 @@ impl IdentityManager {
             public_key: vec![1u8; dilithium3::public_key_size()],
             timestamp: chrono::Utc::now().timestamp() as u64,
             nonce: rand::random(),
+            threshold: None,
         };
 
         match self.validate_pqc_key_usage(&low_entropy_sig).await {
@@ -766,42 +1079,116 @@ impl IdentityManager {
     }
 
     /// Rotate node identity (generate new keys)
-    pub async fn rotate_identity(&mut self) -> Result<NodeIdentity, BlockchainError> {
+    ///
+    /// The previous identity is backed up encrypted; the returned shares are
+    /// the only way to decrypt that backup. Its public keys stay valid for
+    /// `grace_secs`, see `is_own_key`.
+    pub async fn rotate_identity(&mut self, grace_secs: u64) -> Result<IdentityRotation, BlockchainError> {
         log::info!("🔄 Starting identity rotation...");
         
         // Generate new identity
-        let new_identity = self.generate_identity().await?;
+        let mut new_identity = self.generate_identity().await?;
+        let now = chrono::Utc::now().timestamp() as u64;
         
         // Backup old identity if it exists
+        let mut retired = None;
+        let mut backup_shares = Vec::new();
+        let mut rotation_count = 1;
         if let Some(old_identity) = self.current_identity.read().await.as_ref() {
-            self.backup_identity(old_identity).await?;
+            backup_shares = self.backup_identity(old_identity, "rotation").await?;
             log::info!("📦 Backed up previous identity: {}", old_identity.node_id);
+            rotation_count += old_identity.metadata.get("rotation_count")
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(0);
+            retired = Some(RetiredIdentity {
+                identity: IdentityInfo::from_identity(old_identity),
+                retired_at: now,
+                valid_until: now + grace_secs,
+            });
         }
         
-        // Update current identity
-        *self.current_identity.write().await = Some(new_identity.clone());
-        
-        // Save new identity
+        // Record the rotation before the identity is saved
+        new_identity.metadata.insert("last_rotation".to_string(), now.to_string());
+        new_identity.metadata.insert("rotation_count".to_string(), rotation_count.to_string());
+
+        // The outgoing identity vouches for the new one while it is still current
+        let attestation = match &retired {
+            Some(retired) => Some(RotationAttestation::sign(self, &retired.identity, &IdentityInfo::from_identity(&new_identity)).await?),
+            None => None,
+        };
         self.save_identity(&new_identity).await?;
+        if let Some(attestation) = &attestation {
+            let mut attestations = self.load_attestations().await?;
+            attestations.push(attestation.clone());
+            let path = format!("{}/rotation_attestations.json", self.storage_path);
+            tokio::fs::write(&path, serde_json::to_string_pretty(&attestations)?).await?;
+        }
+
+        // Keep the old keys valid through the grace window
+        if let Some(retired) = &retired {
+            self.retired.retain(|r| r.valid_until > now);
+            self.retired.push(retired.clone());
+            self.save_retired().await?;
+        }
         
-        // Update metadata with rotation info
-        let mut identity = self.current_identity.write().await;
-        if let Some(ref mut id) = *identity {
-            id.metadata.insert("last_rotation".to_string(), chrono::Utc::now().to_rfc3339());
-            id.metadata.insert("rotation_count".to_string(), 
-                id.metadata.get("rotation_count")
-                    .and_then(|s| s.parse::<u64>().ok())
-                    .map(|c| (c + 1).to_string())
-                    .unwrap_or_else(|| "1".to_string()));
+        // Update current identity
+        *self.current_identity.write().await = Some(new_identity.clone());
+
+        if let Some(events) = &self.events {
+            events.publish(NodeEvent::IdentityRotated {
+                previous_node_id: retired.as_ref().map(|r| r.identity.node_id.clone()),
+                node_id: new_identity.node_id.clone(),
+            });
         }
-        drop(identity);
         
         log::info!("✅ Identity rotation completed. New node ID: {}", new_identity.node_id);
-        Ok(new_identity)
+        Ok(IdentityRotation {
+            identity: new_identity,
+            backup_shares,
+            retired,
+            attestation,
+        })
+    }
+
+    /// Attestations of this node's past rotations, oldest first
+    async fn load_attestations(&self) -> Result<Vec<RotationAttestation>, BlockchainError> {
+        let path = format!("{}/rotation_attestations.json", self.storage_path);
+        if tokio::fs::metadata(&path).await.is_err() {
+            return Ok(Vec::new());
+        }
+        Ok(serde_json::from_str(&tokio::fs::read_to_string(&path).await?)?)
+    }
+
+    /// Save the retired identities, which hold public keys only
+    async fn save_retired(&self) -> Result<(), BlockchainError> {
+        let path = format!("{}/retired_identities.json", self.storage_path);
+        let temp_path = format!("{}.tmp", path);
+        tokio::fs::write(&temp_path, serde_json::to_string_pretty(&self.retired)?).await?;
+        tokio::fs::rename(&temp_path, &path).await?;
+        Ok(())
+    }
+
+    /// Retired identities still in their grace window
+    async fn load_retired(&self) -> Result<Vec<RetiredIdentity>, BlockchainError> {
+        let path = format!("{}/retired_identities.json", self.storage_path);
+        if tokio::fs::metadata(&path).await.is_err() {
+            return Ok(Vec::new());
+        }
+        let retired: Vec<RetiredIdentity> = serde_json::from_str(&tokio::fs::read_to_string(&path).await?)?;
+        let now = chrono::Utc::now().timestamp() as u64;
+        Ok(retired.into_iter().filter(|r| r.valid_until > now).collect())
+    }
+
+    /// Back up the current identity, returning the shares of its backup key
+    pub async fn export_backup(&self) -> Result<Vec<BackupShare>, BlockchainError> {
+        let identity = self.current_identity.read().await;
+        let identity = identity.as_ref()
+            .ok_or_else(|| BlockchainError::Other("Node identity not initialized".to_string()))?;
+        self.backup_identity(identity, "export").await
     }
 
-    /// Backup existing identity
-    async fn backup_identity(&self, identity: &NodeIdentity) -> Result<(), BlockchainError> {
+    /// Write an encrypted backup of `identity` and return its key shares
+    async fn backup_identity(&self, identity: &NodeIdentity, reason: &str) -> Result<Vec<BackupShare>, BlockchainError> {
         let timestamp = chrono::Utc::now().timestamp();
         let backup_path = format!("{}/identity_backup_{}.json", self.storage_path, timestamp);
         
@@ -811,19 +1198,64 @@ impl IdentityManager {
         }
 
         // Create backup metadata
-        let mut backup_data = identity.clone();
-        backup_data.metadata.insert("backup_timestamp".to_string(), timestamp.to_string());
-        backup_data.metadata.insert("backup_reason".to_string(), "rotation".to_string());
+        let mut metadata = identity.metadata.clone();
+        metadata.insert("backup_timestamp".to_string(), timestamp.to_string());
+        metadata.insert("backup_reason".to_string(), reason.to_string());
         
-        let backup_json = serde_json::to_string_pretty(&backup_data)?;
+        let (backup, shares) = EncryptedIdentityBackup::seal(identity, self.backup_scheme, metadata)?;
+        let backup_json = serde_json::to_string_pretty(&backup)?;
         tokio::fs::write(&backup_path, backup_json).await?;
         
-        log::debug!("Created identity backup at {}", backup_path);
+        log::info!(
+            "🔐 Created identity backup {} at {}, recoverable with {} of {} shares",
+            backup.backup_id, backup_path, backup.threshold, backup.shares
+        );
         
         // Clean up old backups (keep last 5)
         self.cleanup_old_backups().await?;
         
-        Ok(())
+        Ok(shares)
+    }
+
+    /// Restore the identity backed up under the shares' backup ID
+    ///
+    /// The recovered identity replaces the current one and is saved.
+    pub async fn recover_identity(&mut self, shares: &[BackupShare]) -> Result<NodeIdentity, BlockchainError> {
+        let backup_id = shares.first()
+            .map(|share| share.backup_id.clone())
+            .ok_or(RecoveryError::InsufficientShares { needed: 2, got: 0 })?;
+        let backup = self.find_backup(&backup_id).await?
+            .ok_or_else(|| RecoveryError::BackupNotFound(backup_id.clone()))?;
+        let identity = backup.open(shares)?;
+
+        *self.current_identity.write().await = Some(identity.clone());
+        self.save_identity(&identity).await?;
+
+        log::info!("✅ Recovered identity {} from backup {}", identity.node_id, backup_id);
+        Ok(identity)
+    }
+
+    /// Encrypted backup with `backup_id` in the storage directory
+    async fn find_backup(&self, backup_id: &str) -> Result<Option<EncryptedIdentityBackup>, BlockchainError> {
+        let backup_dir = std::path::Path::new(&self.storage_path);
+        if !backup_dir.exists() {
+            return Ok(None);
+        }
+
+        let mut entries = tokio::fs::read_dir(backup_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !(file_name.starts_with("identity_backup_") && file_name.ends_with(".json")) {
+                continue;
+            }
+            let content = tokio::fs::read_to_string(entry.path()).await?;
+            if let Ok(backup) = serde_json::from_str::<EncryptedIdentityBackup>(&content) {
+                if backup.backup_id == backup_id {
+                    return Ok(Some(backup));
+                }
+            }
+        }
+        Ok(None)
     }
 
     /// Clean up old identity backups
@@ -875,6 +1307,7 @@ impl IdentityManager {
         if !backup_dir.exists() {
             return Ok(events);
         }
+        let attestations = self.load_attestations().await?;
         
         let mut entries = tokio::fs::read_dir(backup_dir).await?;
         
@@ -885,19 +1318,28 @@ impl IdentityManager {
                     
                     match tokio::fs::read_to_string(&file_path).await {
                         Ok(content) => {
-                            match serde_json::from_str::<NodeIdentity>(&content) {
-                                Ok(identity) => {
+                            // Backups written before share recovery are plain identity copies
+                            let backup = serde_json::from_str::<EncryptedIdentityBackup>(&content)
+                                .map(|backup| (backup.node_id, backup.metadata))
+                                .or_else(|_| serde_json::from_str::<NodeIdentity>(&content)
+                                    .map(|identity| (identity.node_id, identity.metadata)));
+                            match backup {
+                                Ok((node_id, metadata)) => {
                                     // Extract timestamp from filename
                                     if let Some(timestamp_str) = file_name
                                         .strip_prefix("identity_backup_")
                                         .and_then(|s| s.strip_suffix(".json")) {
                                         
                                         if let Ok(timestamp) = timestamp_str.parse::<i64>() {
+                                            let attestation = attestations.iter()
+                                                .find(|attestation| attestation.previous_node_id == node_id)
+                                                .cloned();
                                             events.push(IdentityRotationEvent {
                                                 timestamp,
-                                                node_id: identity.node_id.clone(),
+                                                node_id,
                                                 backup_file: file_name,
-                                                metadata: identity.metadata.clone(),
+                                                metadata,
+                                                attestation,
                                             });
                                         }
                                     }
@@ -938,9 +1380,8 @@ impl IdentityManager {
             log::info!("⏰ Scheduled identity rotation triggered ({} hours since last rotation)", hours_since_rotation);
             drop(current_identity);
             
-            // Note: This would need to be called through a mutable reference
-            // In a real implementation, you'd use a message queue or similar
-            return Err(BlockchainError::Other("Rotation scheduled - call rotate_identity() with mutable access".to_string()));
+            // The caller rotates, e.g. through `Blockchain::rotate_identity`
+            return Err(BlockchainError::Other("Identity rotation is due - call Blockchain::rotate_identity".to_string()));
         }
         
         let hours_until_rotation = interval_hours as i64 - hours_since_rotation;
@@ -1018,6 +1459,50 @@ impl IdentityManager {
     }
 }
 
+/// Outcome of an identity rotation
+#[derive(Debug, Clone)]
+pub struct IdentityRotation {
+    pub identity: NodeIdentity,
+    /// Shares of the previous identity's backup key; empty if there was none
+    pub backup_shares: Vec<BackupShare>,
+    /// The previous identity, if there was one
+    pub retired: Option<RetiredIdentity>,
+    /// The previous identity's signature over the new one
+    pub attestation: Option<RotationAttestation>,
+}
+
+/// Identity replaced by a rotation, whose keys stay valid until `valid_until`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetiredIdentity {
+    pub identity: IdentityInfo,
+    pub retired_at: u64,
+    pub valid_until: u64,
+}
+
+/// New identity a node announces to its peers after rotating
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityAnnouncement {
+    pub identity: IdentityInfo,
+    pub previous_node_id: Option<String>,
+    /// Until when the previous identity's keys stay valid
+    pub previous_valid_until: Option<u64>,
+    /// Proof that the previous identity handed over to this one
+    #[serde(default)]
+    pub attestation: Option<RotationAttestation>,
+}
+
+impl IdentityRotation {
+    /// Announcement of the new identity for peers
+    pub fn announcement(&self) -> IdentityAnnouncement {
+        IdentityAnnouncement {
+            identity: IdentityInfo::from_identity(&self.identity),
+            previous_node_id: self.retired.as_ref().map(|r| r.identity.node_id.clone()),
+            previous_valid_until: self.retired.as_ref().map(|r| r.valid_until),
+            attestation: self.attestation.clone(),
+        }
+    }
+}
+
 /// Identity rotation event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IdentityRotationEvent {
@@ -1025,6 +1510,9 @@ pub struct IdentityRotationEvent {
     pub node_id: String,
     pub backup_file: String,
     pub metadata: HashMap<String, String>,
+    /// Signature of this identity over the one that replaced it
+    #[serde(default)]
+    pub attestation: Option<RotationAttestation>,
 }
 
 /// Identity rotation readiness check
@@ -1107,6 +1595,32 @@ mod tests {
         assert!(verified);
     }
 
+    #[tokio::test]
+    async fn test_rotation_keeps_old_keys_for_grace_window() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage_path = temp_dir.path().to_string_lossy().to_string();
+
+        let mut manager = IdentityManager::new(storage_path.clone());
+        let old = manager.initialize_identity().await.unwrap();
+        let rotation = manager.rotate_identity(3600).await.unwrap();
+        assert_ne!(rotation.identity.node_id, old.node_id);
+        assert_eq!(rotation.announcement().previous_node_id, Some(old.node_id.clone()));
+        assert_eq!(rotation.identity.metadata.get("rotation_count").map(String::as_str), Some("1"));
+
+        assert!(manager.is_own_key(&rotation.identity.dilithium3_public).await);
+        assert!(manager.is_own_key(&old.dilithium3_public).await);
+
+        // The window survives a restart, and the rotated identity is the one loaded
+        let mut reloaded = IdentityManager::new(storage_path);
+        assert_eq!(reloaded.initialize_identity().await.unwrap().node_id, rotation.identity.node_id);
+        assert!(reloaded.is_own_key(&old.ed25519_public).await);
+
+        // Without a grace window the outgoing keys are dropped at once
+        reloaded.rotate_identity(0).await.unwrap();
+        assert!(!reloaded.is_own_key(&rotation.identity.dilithium3_public).await);
+        assert!(reloaded.is_own_key(&old.dilithium3_public).await);
+    }
+
     #[tokio::test]
     async fn test_transaction_signing() {
         let temp_dir = TempDir::new().unwrap();
@@ -1120,10 +1634,12 @@ mod tests {
             sender: vec![1u8; 32],
             receiver: vec![2u8; 32],
             amount: 100,
+            fee: 0,
             nonce: 1,
             timestamp: chrono::Utc::now().timestamp() as u64,
             parents: vec![],
             signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
             quantum_proof: QuantumProof {
                 prime_hash: vec![0u8; 32],
                 resistance_score: 80,
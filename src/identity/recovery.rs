@@ -0,0 +1,443 @@
+//! Shamir-split identity backups
+//!
+//! A backup is the node identity encrypted with ChaCha20-Poly1305 under a
+//! fresh random key. The key is split with Shamir secret sharing over
+//! GF(256) into `shares` shares, any `threshold` of which reconstruct it.
+//! Only the encrypted backup is written to disk; the shares are handed to the
+//! operator once, as mnemonic phrases or QR payloads, to be stored apart.
+//!
+//! Recovery reconstructs the key, decrypts the backup and checks that the
+//! keys in it belong together and produce the recorded node ID before the
+//! identity is used again.
+
+use super::{IdentityManager, NodeIdentity};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Keypair, PublicKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Format version of `EncryptedIdentityBackup`
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const BACKUP_AAD_DOMAIN: &[u8] = b"qdag-identity-backup-v1";
+const MNEMONIC_PREFIX: &str = "qdag-share";
+const QR_PREFIX: &str = "QDAG-SHARE";
+const QR_VERSION: &str = "1";
+
+/// How many shares a backup key is split into and how many recover it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareScheme {
+    pub threshold: u8,
+    pub shares: u8,
+}
+
+impl Default for ShareScheme {
+    fn default() -> Self {
+        Self { threshold: 3, shares: 5 }
+    }
+}
+
+impl ShareScheme {
+    /// `threshold` of `shares`; a single share must never be enough
+    pub fn new(threshold: u8, shares: u8) -> Result<Self, RecoveryError> {
+        if threshold < 2 || threshold > shares {
+            return Err(RecoveryError::InvalidScheme(format!(
+                "threshold must be between 2 and the share count, got {} of {}",
+                threshold, shares
+            )));
+        }
+        Ok(Self { threshold, shares })
+    }
+}
+
+/// One share of a backup key
+#[derive(Clone, PartialEq, Eq)]
+pub struct BackupShare {
+    /// Backup the share belongs to
+    pub backup_id: String,
+    /// Evaluation point, 1-based
+    pub index: u8,
+    /// Shares needed to recover the key
+    pub threshold: u8,
+    value: [u8; 32],
+}
+
+impl std::fmt::Debug for BackupShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackupShare")
+            .field("backup_id", &self.backup_id)
+            .field("index", &self.index)
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BackupShare {
+    /// `qdag-share <backup id> <index>/<threshold>` followed by 24 BIP-39 words
+    pub fn to_mnemonic(&self) -> String {
+        let words = bip39::Mnemonic::from_entropy(&self.value).expect("32 bytes is a valid entropy length");
+        format!("{} {} {}/{} {}", MNEMONIC_PREFIX, self.backup_id, self.index, self.threshold, words)
+    }
+
+    /// Uppercase payload for a QR code, within the alphanumeric character set
+    pub fn to_qr_payload(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            QR_PREFIX,
+            QR_VERSION,
+            self.backup_id.to_uppercase(),
+            self.index,
+            self.threshold,
+            hex::encode_upper(self.value)
+        )
+    }
+
+    /// Parse a share from its mnemonic or QR form
+    pub fn parse(blob: &str) -> Result<Self, RecoveryError> {
+        let blob = blob.trim();
+        if blob.to_uppercase().starts_with(&format!("{}:", QR_PREFIX)) {
+            Self::parse_qr(blob)
+        } else {
+            Self::parse_mnemonic(blob)
+        }
+    }
+
+    fn parse_mnemonic(blob: &str) -> Result<Self, RecoveryError> {
+        let invalid = || RecoveryError::InvalidShare("expected `qdag-share <backup id> <index>/<threshold> <words>`".to_string());
+        let mut parts = blob.splitn(4, char::is_whitespace);
+        if parts.next() != Some(MNEMONIC_PREFIX) {
+            return Err(invalid());
+        }
+        let backup_id = parts.next().ok_or_else(invalid)?;
+        let (index, threshold) = parts.next().and_then(|p| p.split_once('/')).ok_or_else(invalid)?;
+        let words = bip39::Mnemonic::parse_normalized(parts.next().ok_or_else(invalid)?.trim())
+            .map_err(|e| RecoveryError::InvalidShare(format!("mnemonic: {}", e)))?;
+        let value = words.to_entropy().try_into()
+            .map_err(|_| RecoveryError::InvalidShare("mnemonic must have 24 words".to_string()))?;
+        Self::new(backup_id, index, threshold, value)
+    }
+
+    fn parse_qr(blob: &str) -> Result<Self, RecoveryError> {
+        let fields: Vec<&str> = blob.split(':').collect();
+        let [_, version, backup_id, index, threshold, value] = fields[..] else {
+            return Err(RecoveryError::InvalidShare("expected 6 QR payload fields".to_string()));
+        };
+        if version != QR_VERSION {
+            return Err(RecoveryError::InvalidShare(format!("unsupported QR payload version {}", version)));
+        }
+        let value = hex::decode(value)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| RecoveryError::InvalidShare("share value must be 32 hex bytes".to_string()))?;
+        Self::new(&backup_id.to_lowercase(), index, threshold, value)
+    }
+
+    fn new(backup_id: &str, index: &str, threshold: &str, value: [u8; 32]) -> Result<Self, RecoveryError> {
+        if backup_id.is_empty() || !backup_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(RecoveryError::InvalidShare(format!("invalid backup id {}", backup_id)));
+        }
+        let index: u8 = index.parse().map_err(|_| RecoveryError::InvalidShare(format!("invalid index {}", index)))?;
+        let threshold: u8 = threshold.parse()
+            .map_err(|_| RecoveryError::InvalidShare(format!("invalid threshold {}", threshold)))?;
+        if index == 0 || threshold < 2 {
+            return Err(RecoveryError::InvalidShare("index must be at least 1 and threshold at least 2".to_string()));
+        }
+        Ok(Self { backup_id: backup_id.to_string(), index, threshold, value })
+    }
+}
+
+/// Split `secret` into shares of `scheme` tagged with `backup_id`
+pub fn split_secret(secret: &[u8; 32], scheme: ShareScheme, backup_id: &str) -> Vec<BackupShare> {
+    let mut rng = rand::thread_rng();
+    let mut shares: Vec<BackupShare> = (1..=scheme.shares)
+        .map(|index| BackupShare {
+            backup_id: backup_id.to_string(),
+            index,
+            threshold: scheme.threshold,
+            value: [0u8; 32],
+        })
+        .collect();
+
+    // One random polynomial per byte, with the secret byte as constant term
+    let mut coefficients = vec![0u8; scheme.threshold as usize];
+    for (position, byte) in secret.iter().enumerate() {
+        coefficients[0] = *byte;
+        rng.fill_bytes(&mut coefficients[1..]);
+        for share in &mut shares {
+            share.value[position] = coefficients.iter().rev().fold(0, |acc, c| gf_mul(acc, share.index) ^ c);
+        }
+    }
+    shares
+}
+
+/// Reconstruct the secret of a backup from at least its threshold of shares
+pub fn combine_shares(shares: &[BackupShare]) -> Result<[u8; 32], RecoveryError> {
+    let first = shares.first().ok_or(RecoveryError::InsufficientShares { needed: 2, got: 0 })?;
+    if shares.iter().any(|s| s.backup_id != first.backup_id || s.threshold != first.threshold) {
+        return Err(RecoveryError::MixedBackups);
+    }
+    let mut seen = BTreeSet::new();
+    let distinct: Vec<&BackupShare> = shares.iter().filter(|s| seen.insert(s.index)).collect();
+    let needed = first.threshold as usize;
+    if distinct.len() < needed {
+        return Err(RecoveryError::InsufficientShares { needed, got: distinct.len() });
+    }
+
+    // Lagrange interpolation at zero over the first `threshold` shares
+    let points = &distinct[..needed];
+    let mut secret = [0u8; 32];
+    for share in points {
+        let basis = points.iter()
+            .filter(|other| other.index != share.index)
+            .fold(1, |acc, other| gf_mul(acc, gf_mul(other.index, gf_inv(other.index ^ share.index))));
+        for (byte, y) in secret.iter_mut().zip(share.value) {
+            *byte ^= gf_mul(y, basis);
+        }
+    }
+    Ok(secret)
+}
+
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse, as `a^254`
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Node identity encrypted under a Shamir-split key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedIdentityBackup {
+    pub version: u32,
+    pub backup_id: String,
+    pub node_id: String,
+    pub created_at: i64,
+    pub threshold: u8,
+    pub shares: u8,
+    /// Identity metadata plus the backup reason, readable without the key
+    pub metadata: HashMap<String, String>,
+    /// Hex ChaCha20-Poly1305 nonce
+    nonce: String,
+    /// Hex encrypted identity JSON
+    ciphertext: String,
+}
+
+impl EncryptedIdentityBackup {
+    /// Encrypt `identity` and split the key according to `scheme`
+    pub fn seal(
+        identity: &NodeIdentity,
+        scheme: ShareScheme,
+        metadata: HashMap<String, String>,
+    ) -> Result<(Self, Vec<BackupShare>), RecoveryError> {
+        let mut rng = rand::thread_rng();
+        let mut key = [0u8; 32];
+        let mut nonce = [0u8; 12];
+        let mut backup_id = [0u8; 8];
+        rng.fill_bytes(&mut key);
+        rng.fill_bytes(&mut nonce);
+        rng.fill_bytes(&mut backup_id);
+
+        let mut backup = Self {
+            version: BACKUP_FORMAT_VERSION,
+            backup_id: hex::encode(backup_id),
+            node_id: identity.node_id.clone(),
+            created_at: chrono::Utc::now().timestamp(),
+            threshold: scheme.threshold,
+            shares: scheme.shares,
+            metadata,
+            nonce: hex::encode(nonce),
+            ciphertext: String::new(),
+        };
+        let plaintext = serde_json::to_vec(identity).map_err(|e| RecoveryError::InvalidIdentity(e.to_string()))?;
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &backup.aad() })
+            .map_err(|_| RecoveryError::DecryptionFailed)?;
+        backup.ciphertext = hex::encode(ciphertext);
+
+        let shares = split_secret(&key, scheme, &backup.backup_id);
+        Ok((backup, shares))
+    }
+
+    /// Decrypt the identity with `shares` and check its keys
+    pub fn open(&self, shares: &[BackupShare]) -> Result<NodeIdentity, RecoveryError> {
+        if self.version != BACKUP_FORMAT_VERSION {
+            return Err(RecoveryError::UnsupportedVersion(self.version));
+        }
+        if shares.iter().any(|share| share.backup_id != self.backup_id) {
+            return Err(RecoveryError::MixedBackups);
+        }
+        let key = combine_shares(shares)?;
+        let nonce = hex::decode(&self.nonce).map_err(|_| RecoveryError::DecryptionFailed)?;
+        let ciphertext = hex::decode(&self.ciphertext).map_err(|_| RecoveryError::DecryptionFailed)?;
+        if nonce.len() != 12 {
+            return Err(RecoveryError::DecryptionFailed);
+        }
+        // A wrong or corrupted share yields a wrong key, which fails authentication
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &self.aad() })
+            .map_err(|_| RecoveryError::DecryptionFailed)?;
+        let identity: NodeIdentity = serde_json::from_slice(&plaintext)
+            .map_err(|e| RecoveryError::InvalidIdentity(e.to_string()))?;
+
+        if identity.node_id != self.node_id {
+            return Err(RecoveryError::InvalidIdentity(format!(
+                "backup of {} contains identity {}",
+                self.node_id, identity.node_id
+            )));
+        }
+        validate_identity(&identity)?;
+        Ok(identity)
+    }
+
+    fn aad(&self) -> Vec<u8> {
+        [BACKUP_AAD_DOMAIN, self.backup_id.as_bytes(), self.node_id.as_bytes()].join(&b'|')
+    }
+}
+
+/// Check that an identity's secret keys match its public keys and node ID
+pub fn validate_identity(identity: &NodeIdentity) -> Result<(), RecoveryError> {
+    let invalid = |reason: &str| RecoveryError::InvalidIdentity(reason.to_string());
+
+    let keypair = Keypair::from_bytes(&identity.ed25519_keypair).map_err(|_| invalid("malformed Ed25519 keypair"))?;
+    if PublicKey::from(&keypair.secret).as_bytes() != identity.ed25519_public.as_slice() {
+        return Err(invalid("Ed25519 secret does not match its public key"));
+    }
+
+    let x25519_secret: [u8; 32] = identity.x25519_secret.as_slice().try_into()
+        .map_err(|_| invalid("X25519 secret must be 32 bytes"))?;
+    let x25519_public = x25519_dalek::PublicKey::from(&x25519_dalek::StaticSecret::from(x25519_secret));
+    if x25519_public.as_bytes() != identity.x25519_public.as_slice() {
+        return Err(invalid("X25519 secret does not match its public key"));
+    }
+
+    // Dilithium keypairs are stored as public key followed by secret key
+    if !identity.dilithium3_keypair.starts_with(&identity.dilithium3_public)
+        || !identity.dilithium5_keypair.starts_with(&identity.dilithium5_public)
+    {
+        return Err(invalid("Dilithium keypair does not match its public key"));
+    }
+    // So are Falcon keypairs, which identities from before Falcon lack
+    if !identity.falcon512_keypair.starts_with(&identity.falcon512_public)
+        || !identity.falcon1024_keypair.starts_with(&identity.falcon1024_public)
+    {
+        return Err(invalid("Falcon keypair does not match its public key"));
+    }
+
+    if IdentityManager::generate_node_id(&identity.ed25519_public, &identity.dilithium3_public) != identity.node_id {
+        return Err(invalid("node ID does not match the public keys"));
+    }
+    Ok(())
+}
+
+/// Identity backup and recovery errors
+#[derive(Debug, thiserror::Error)]
+pub enum RecoveryError {
+    #[error("Invalid share scheme: {0}")]
+    InvalidScheme(String),
+    #[error("Invalid share: {0}")]
+    InvalidShare(String),
+    #[error("Need {needed} distinct shares, got {got}")]
+    InsufficientShares { needed: usize, got: usize },
+    #[error("Shares belong to different backups")]
+    MixedBackups,
+    #[error("No identity backup {0} found")]
+    BackupNotFound(String),
+    #[error("Unsupported backup version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Shares do not decrypt the backup")]
+    DecryptionFailed,
+    #[error("Invalid recovered identity: {0}")]
+    InvalidIdentity(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret() -> [u8; 32] {
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        secret
+    }
+
+    #[test]
+    fn test_any_threshold_subset_recovers_secret() {
+        let secret = secret();
+        let shares = split_secret(&secret, ShareScheme::new(3, 5).unwrap(), "00ff");
+
+        assert_eq!(combine_shares(&shares[..3]).unwrap(), secret);
+        assert_eq!(combine_shares(&[shares[4].clone(), shares[1].clone(), shares[3].clone()]).unwrap(), secret);
+        assert!(matches!(
+            combine_shares(&[shares[0].clone(), shares[0].clone(), shares[2].clone()]),
+            Err(RecoveryError::InsufficientShares { needed: 3, got: 2 })
+        ));
+        assert!(ShareScheme::new(1, 5).is_err());
+    }
+
+    #[test]
+    fn test_share_encodings_round_trip() {
+        let share = BackupShare { backup_id: "0123abcd".to_string(), index: 2, threshold: 3, value: [7u8; 32] };
+
+        let mnemonic = share.to_mnemonic();
+        assert_eq!(mnemonic.split_whitespace().count(), 3 + 24);
+        assert_eq!(BackupShare::parse(&mnemonic).unwrap(), share);
+
+        let qr = share.to_qr_payload();
+        assert!(qr.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == ':' || c == '-'));
+        assert_eq!(BackupShare::parse(&qr).unwrap(), share);
+
+        // A mistyped word fails the mnemonic checksum
+        let mut words: Vec<&str> = mnemonic.split_whitespace().collect();
+        words[5] = if words[5] == "zoo" { "abandon" } else { "zoo" };
+        assert!(BackupShare::parse(&words.join(" ")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotated_identity_recovers_from_shares() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut manager = IdentityManager::new(temp_dir.path().to_string_lossy().to_string());
+        let original = manager.initialize_identity().await.unwrap();
+
+        let rotation = manager.rotate_identity(3600).await.unwrap();
+        assert_eq!(rotation.backup_shares.len(), 5);
+
+        // The backup on disk holds no key material in the clear
+        let history = manager.get_rotation_history().await.unwrap();
+        let backup = std::fs::read_to_string(temp_dir.path().join(&history[0].backup_file)).unwrap();
+        assert!(!backup.contains(&hex::encode(&original.ed25519_keypair)));
+
+        let blobs = [rotation.backup_shares[4].to_qr_payload(), rotation.backup_shares[0].to_mnemonic()];
+        let mut shares: Vec<BackupShare> = blobs.iter().map(|blob| BackupShare::parse(blob).unwrap()).collect();
+        assert!(manager.recover_identity(&shares).await.is_err());
+
+        shares.push(rotation.backup_shares[2].clone());
+        let recovered = manager.recover_identity(&shares).await.unwrap();
+        assert_eq!(recovered.node_id, original.node_id);
+        assert_eq!(manager.get_current_identity().await.unwrap().unwrap().node_id, original.node_id);
+    }
+}
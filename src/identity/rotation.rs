@@ -0,0 +1,186 @@
+//! Rotation attestations chaining a node's identities
+//!
+//! When a node rotates, its outgoing identity signs the new one with its
+//! Dilithium3 key. The attestation is kept in the rotation history and sent
+//! with the identity announcement, so a peer that knew the previous node ID
+//! can follow the node to its new one instead of treating it as a stranger.
+//! It commits to the new node ID and all of its public keys.
+
+use super::{IdentityInfo, IdentityManager, NodeSignature, SignatureType};
+use crate::BlockchainError;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/// Domain separator for rotation attestation payloads
+pub const ROTATION_DOMAIN: &[u8] = b"qdag-identity-rotation-v1";
+
+/// Statement by a node's previous identity that it rotated to a new one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationAttestation {
+    pub previous_node_id: String,
+    pub node_id: String,
+    /// Hex SHA3-256 of the new identity's public keys
+    pub keys_hash: String,
+    pub issued_at: u64,
+    /// Dilithium3 signature by the previous identity over `signing_payload()`
+    pub signature: NodeSignature,
+}
+
+impl RotationAttestation {
+    /// Sign the rotation to `next` with the current identity of `identity`,
+    /// which must still be `previous`
+    pub async fn sign(identity: &IdentityManager, previous: &IdentityInfo, next: &IdentityInfo) -> Result<Self, BlockchainError> {
+        let keys_hash = keys_hash(next);
+        let issued_at = chrono::Utc::now().timestamp() as u64;
+        let payload = signing_payload(&previous.node_id, &next.node_id, &keys_hash, issued_at);
+        let signature = identity.sign(&payload, SignatureType::Dilithium3).await?;
+
+        Ok(Self {
+            previous_node_id: previous.node_id.clone(),
+            node_id: next.node_id.clone(),
+            keys_hash,
+            issued_at,
+            signature,
+        })
+    }
+
+    /// Bytes covered by the signature
+    pub fn signing_payload(&self) -> Vec<u8> {
+        signing_payload(&self.previous_node_id, &self.node_id, &self.keys_hash, self.issued_at)
+    }
+
+    /// Check that `previous` signed the rotation to exactly `next`
+    pub async fn verify(&self, previous: &IdentityInfo, next: &IdentityInfo, verifier: &IdentityManager) -> Result<(), RotationError> {
+        if self.previous_node_id != previous.node_id {
+            return Err(RotationError::WrongPrevious(self.previous_node_id.clone()));
+        }
+        if self.node_id != next.node_id || self.keys_hash != keys_hash(next) {
+            return Err(RotationError::WrongIdentity(next.node_id.clone()));
+        }
+        if self.signature.signature_type != SignatureType::Dilithium3
+            || hex::encode(&self.signature.public_key) != previous.dilithium3_public
+        {
+            return Err(RotationError::WrongKey);
+        }
+        match verifier.verify(&self.signing_payload(), &self.signature).await {
+            Ok(true) => Ok(()),
+            _ => Err(RotationError::InvalidSignature),
+        }
+    }
+}
+
+fn signing_payload(previous_node_id: &str, node_id: &str, keys_hash: &str, issued_at: u64) -> Vec<u8> {
+    let mut payload = ROTATION_DOMAIN.to_vec();
+    for field in [previous_node_id, node_id, keys_hash] {
+        payload.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        payload.extend_from_slice(field.as_bytes());
+    }
+    payload.extend_from_slice(&issued_at.to_le_bytes());
+    payload
+}
+
+/// Hash committing to an identity's node ID and public keys
+fn keys_hash(identity: &IdentityInfo) -> String {
+    let mut hasher = Sha3_256::new();
+    for key in [
+        &identity.node_id,
+        &identity.ed25519_public,
+        &identity.x25519_public,
+        &identity.kyber_public,
+        &identity.dilithium3_public,
+        &identity.dilithium5_public,
+        &identity.falcon512_public,
+        &identity.falcon1024_public,
+    ] {
+        hasher.update((key.len() as u32).to_le_bytes());
+        hasher.update(key.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// How a peer's identity announcement changed what this node knows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PeerIdentityChange {
+    /// A node not seen before
+    New,
+    /// A known node re-announcing its identity
+    Unchanged,
+    /// A known node that proved it rotated away from `previous_node_id`
+    Rotated { previous_node_id: String },
+}
+
+/// Rotation attestation errors
+#[derive(Debug, thiserror::Error)]
+pub enum RotationError {
+    #[error("Known node {0} announced a new identity without a rotation attestation")]
+    MissingAttestation(String),
+    #[error("Rotation attestation is from {0}, not the announced previous identity")]
+    WrongPrevious(String),
+    #[error("Rotation attestation does not cover the announced identity {0}")]
+    WrongIdentity(String),
+    #[error("Rotation attestation is not signed with the previous Dilithium3 key")]
+    WrongKey,
+    #[error("Invalid rotation attestation signature")]
+    InvalidSignature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnnouncement;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_peer_follows_attested_rotation() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut node = IdentityManager::new(temp_dir.path().to_string_lossy().to_string());
+        node.initialize_identity().await.unwrap();
+        let first = node.get_identity_info().await.unwrap();
+
+        let peer_dir = TempDir::new().unwrap();
+        let mut peer = IdentityManager::new(peer_dir.path().to_string_lossy().to_string());
+        let announcement = IdentityAnnouncement { identity: first.clone(), previous_node_id: None, previous_valid_until: None, attestation: None };
+        assert_eq!(peer.accept_announcement(&announcement).await.unwrap(), PeerIdentityChange::New);
+
+        let rotation = node.rotate_identity(3600).await.unwrap();
+        let announcement = rotation.announcement();
+        assert_eq!(
+            peer.accept_announcement(&announcement).await.unwrap(),
+            PeerIdentityChange::Rotated { previous_node_id: first.node_id.clone() }
+        );
+        assert!(peer.known_peer(&first.node_id).is_none());
+        assert!(peer.known_peer(&rotation.identity.node_id).is_some());
+
+        // The attestation is kept in the node's rotation history
+        let history = node.get_rotation_history().await.unwrap();
+        assert!(history.iter().any(|event| event.attestation.as_ref().map(|a| &a.node_id) == Some(&rotation.identity.node_id)));
+    }
+
+    #[tokio::test]
+    async fn test_unattested_or_forged_rotation_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut node = IdentityManager::new(temp_dir.path().to_string_lossy().to_string());
+        node.initialize_identity().await.unwrap();
+        let first = node.get_identity_info().await.unwrap();
+
+        let peer_dir = TempDir::new().unwrap();
+        let mut peer = IdentityManager::new(peer_dir.path().to_string_lossy().to_string());
+        let known = IdentityAnnouncement { identity: first.clone(), previous_node_id: None, previous_valid_until: None, attestation: None };
+        peer.accept_announcement(&known).await.unwrap();
+
+        // An impostor claiming to be the node's successor, with its own attestation
+        let impostor_dir = TempDir::new().unwrap();
+        let mut impostor = IdentityManager::new(impostor_dir.path().to_string_lossy().to_string());
+        impostor.initialize_identity().await.unwrap();
+        let mut forged = impostor.rotate_identity(3600).await.unwrap().announcement();
+        forged.previous_node_id = Some(first.node_id.clone());
+        assert!(peer.accept_announcement(&forged).await.is_err());
+
+        forged.attestation = None;
+        assert!(matches!(
+            peer.accept_announcement(&forged).await,
+            Err(BlockchainError::Rotation(RotationError::MissingAttestation(_)))
+        ));
+        assert!(peer.known_peer(&first.node_id).is_some());
+    }
+}
@@ -0,0 +1,209 @@
+//! Hybrid X25519 + Kyber768 key agreement for encrypted peer sessions
+//!
+//! The initiator derives a session secret from an ephemeral X25519 exchange
+//! with the responder's identity key and a Kyber768 encapsulation to it, and
+//! sends the responder a `SessionOffer` carrying the ephemeral key and KEM
+//! ciphertext, signed with its Ed25519 identity key. The responder verifies
+//! the offer and derives the same secret. The secret stays confidential as
+//! long as either scheme holds; turning it into channel keys is left to the
+//! network layer.
+
+use crate::identity::{NodeIdentity, OperatorContact};
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
+use pqcrypto_kyber::kyber768;
+use pqcrypto_traits::kem::{Ciphertext as _, PublicKey as _, SecretKey as _, SharedSecret as _};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/// Offers older than this are refused, in seconds
+pub const MAX_OFFER_AGE_SECS: u64 = 300;
+
+const SESSION_DOMAIN: &[u8] = b"quantum-dag-peer-session";
+
+/// Key agreement message from the initiator of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionOffer {
+    pub initiator: String,
+    pub responder: String,
+    pub created_at: u64,
+    /// Initiator's ephemeral X25519 public key
+    pub ephemeral_public: Vec<u8>,
+    /// Kyber768 encapsulation to the responder
+    pub kem_ciphertext: Vec<u8>,
+    /// Ed25519 signature over all other fields
+    pub signature: Vec<u8>,
+}
+
+impl SessionOffer {
+    fn transcript(&self) -> Vec<u8> {
+        let mut data = SESSION_DOMAIN.to_vec();
+        for field in [
+            self.initiator.as_bytes(),
+            self.responder.as_bytes(),
+            &self.ephemeral_public,
+            &self.kem_ciphertext,
+        ] {
+            data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            data.extend_from_slice(field);
+        }
+        data.extend_from_slice(&self.created_at.to_le_bytes());
+        data
+    }
+}
+
+/// Shared secret of a peer session
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionSecret([u8; 32]);
+
+impl SessionSecret {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SessionSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SessionSecret(..)")
+    }
+}
+
+/// Start a session from `initiator` to `peer`
+pub fn initiate_session(initiator: &NodeIdentity, peer: &OperatorContact) -> Result<(SessionSecret, SessionOffer), SessionError> {
+    let ephemeral = x25519_dalek::StaticSecret::from(rand::random::<[u8; 32]>());
+    let x25519_shared = ephemeral.diffie_hellman(&x25519_key(&peer.x25519_public)?);
+
+    let kyber_public = kyber768::PublicKey::from_bytes(&peer.kyber_public)
+        .map_err(|e| SessionError::InvalidKey(format!("kyber_public: {}", e)))?;
+    let (kyber_shared, kem_ciphertext) = kyber768::encapsulate(&kyber_public);
+
+    let mut offer = SessionOffer {
+        initiator: initiator.node_id.clone(),
+        responder: peer.node_id.clone(),
+        created_at: chrono::Utc::now().timestamp() as u64,
+        ephemeral_public: x25519_dalek::PublicKey::from(&ephemeral).to_bytes().to_vec(),
+        kem_ciphertext: kem_ciphertext.as_bytes().to_vec(),
+        signature: Vec::new(),
+    };
+    let keypair = Keypair::from_bytes(&initiator.ed25519_keypair)
+        .map_err(|e| SessionError::InvalidKey(format!("ed25519_keypair: {}", e)))?;
+    offer.signature = keypair.sign(&offer.transcript()).to_bytes().to_vec();
+
+    let secret = session_secret(x25519_shared.as_bytes(), kyber_shared.as_bytes(), &offer);
+    Ok((secret, offer))
+}
+
+/// Derive the secret of a session `initiator` offered to `responder`
+pub fn accept_session(responder: &NodeIdentity, initiator: &OperatorContact, offer: &SessionOffer) -> Result<SessionSecret, SessionError> {
+    if offer.responder != responder.node_id {
+        return Err(SessionError::NotResponder(offer.responder.clone()));
+    }
+    if offer.initiator != initiator.node_id {
+        return Err(SessionError::UnexpectedInitiator(offer.initiator.clone()));
+    }
+    let age = (chrono::Utc::now().timestamp() as u64).saturating_sub(offer.created_at);
+    if age > MAX_OFFER_AGE_SECS {
+        return Err(SessionError::Expired(age));
+    }
+
+    let public_key = PublicKey::from_bytes(&initiator.ed25519_public)
+        .map_err(|e| SessionError::InvalidKey(format!("ed25519_public: {}", e)))?;
+    let signature = Signature::try_from(offer.signature.as_slice()).map_err(|_| SessionError::InvalidSignature)?;
+    public_key.verify(&offer.transcript(), &signature).map_err(|_| SessionError::InvalidSignature)?;
+
+    let x25519_secret = x25519_dalek::StaticSecret::from(key_array(&responder.x25519_secret)?);
+    let x25519_shared = x25519_secret.diffie_hellman(&x25519_key(&offer.ephemeral_public)?);
+
+    let kyber_secret = kyber768::SecretKey::from_bytes(&responder.kyber_secret)
+        .map_err(|e| SessionError::InvalidKey(format!("kyber_secret: {}", e)))?;
+    let kem_ciphertext = kyber768::Ciphertext::from_bytes(&offer.kem_ciphertext)
+        .map_err(|e| SessionError::InvalidKey(format!("kem_ciphertext: {}", e)))?;
+    let kyber_shared = kyber768::decapsulate(&kem_ciphertext, &kyber_secret);
+
+    Ok(session_secret(x25519_shared.as_bytes(), kyber_shared.as_bytes(), offer))
+}
+
+fn session_secret(x25519_shared: &[u8], kyber_shared: &[u8], offer: &SessionOffer) -> SessionSecret {
+    let mut hasher = Sha3_256::new();
+    hasher.update(x25519_shared);
+    hasher.update(kyber_shared);
+    hasher.update(offer.transcript());
+    SessionSecret(hasher.finalize().into())
+}
+
+fn key_array(bytes: &[u8]) -> Result<[u8; 32], SessionError> {
+    bytes
+        .try_into()
+        .map_err(|_| SessionError::InvalidKey(format!("expected 32-byte key, got {} bytes", bytes.len())))
+}
+
+fn x25519_key(bytes: &[u8]) -> Result<x25519_dalek::PublicKey, SessionError> {
+    Ok(x25519_dalek::PublicKey::from(key_array(bytes)?))
+}
+
+/// Peer session errors
+#[derive(Debug, thiserror::Error)]
+pub enum SessionError {
+    #[error("Session offer is addressed to {0}, not this node")]
+    NotResponder(String),
+    #[error("Session offer comes from {0}, not the expected peer")]
+    UnexpectedInitiator(String),
+    #[error("Session offer is {0} seconds old")]
+    Expired(u64),
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    #[error("Invalid session offer signature")]
+    InvalidSignature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityManager;
+    use tempfile::TempDir;
+
+    async fn identity() -> (TempDir, NodeIdentity) {
+        let temp_dir = TempDir::new().unwrap();
+        let identity = IdentityManager::new(temp_dir.path().to_string_lossy().to_string())
+            .initialize_identity()
+            .await
+            .unwrap();
+        (temp_dir, identity)
+    }
+
+    #[tokio::test]
+    async fn test_both_sides_derive_the_same_secret() {
+        let (_a, alice) = identity().await;
+        let (_b, bob) = identity().await;
+
+        let (secret, offer) = initiate_session(&alice, &OperatorContact::from_identity(&bob)).unwrap();
+        let accepted = accept_session(&bob, &OperatorContact::from_identity(&alice), &offer).unwrap();
+        assert_eq!(secret, accepted);
+
+        // Each session gets a fresh secret
+        let (other, _) = initiate_session(&alice, &OperatorContact::from_identity(&bob)).unwrap();
+        assert_ne!(secret, other);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_or_misaddressed_offers_are_refused() {
+        let (_a, alice) = identity().await;
+        let (_b, bob) = identity().await;
+        let (_m, mallory) = identity().await;
+
+        let (_, mut offer) = initiate_session(&alice, &OperatorContact::from_identity(&bob)).unwrap();
+        assert!(matches!(
+            accept_session(&mallory, &OperatorContact::from_identity(&alice), &offer),
+            Err(SessionError::NotResponder(_))
+        ));
+        assert!(matches!(
+            accept_session(&bob, &OperatorContact::from_identity(&mallory), &offer),
+            Err(SessionError::UnexpectedInitiator(_))
+        ));
+
+        offer.ephemeral_public = x25519_dalek::PublicKey::from(&x25519_dalek::StaticSecret::from([7u8; 32])).to_bytes().to_vec();
+        assert!(matches!(
+            accept_session(&bob, &OperatorContact::from_identity(&alice), &offer),
+            Err(SessionError::InvalidSignature)
+        ));
+    }
+}
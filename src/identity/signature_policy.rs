@@ -0,0 +1,204 @@
+//! Signature scheme selection by transaction value
+//!
+//! A `SignaturePolicy` splits amounts into bands, each with the weakest
+//! scheme a transaction in that band may be signed with. Micro-payments can
+//! use the smaller Dilithium3 signatures while high-value transfers get
+//! Dilithium5. The node signs submissions with the scheme of their band, or
+//! one the submitter chose that is at least as strong, and records it on the
+//! transaction; validation rejects transactions whose recorded scheme is
+//! weaker than their band requires or does not match the size of their
+//! signature. Falcon-1024 meets any band with signatures under half the size
+//! of Dilithium3's, for bandwidth-constrained paths.
+
+use super::SignatureType;
+use crate::core::{amount, Amount, CoreError, Transaction};
+use pqcrypto_dilithium::{dilithium3, dilithium5};
+use pqcrypto_falcon::{falcon1024, falcon512};
+use serde::{Deserialize, Serialize};
+
+/// Length of an Ed25519 signature
+const ED25519_SIGNATURE_LEN: usize = 64;
+
+/// Amounts from `min_amount` up to the next band's minimum
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureBand {
+    #[serde(with = "amount")]
+    pub min_amount: Amount,
+    pub scheme: SignatureType,
+}
+
+/// Value bands mapping transaction amounts to signature schemes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignaturePolicy {
+    /// Bands in increasing order of `min_amount`, the first starting at 0
+    pub bands: Vec<SignatureBand>,
+}
+
+impl Default for SignaturePolicy {
+    fn default() -> Self {
+        Self {
+            bands: vec![
+                SignatureBand { min_amount: 0, scheme: SignatureType::Dilithium3 },
+                SignatureBand { min_amount: 10_000, scheme: SignatureType::Hybrid },
+                SignatureBand { min_amount: 1_000_000, scheme: SignatureType::Dilithium5 },
+            ],
+        }
+    }
+}
+
+impl SignaturePolicy {
+    /// Check that the bands cover every amount with a post-quantum scheme
+    pub fn validate(&self) -> Result<(), String> {
+        match self.bands.first() {
+            None => return Err("must have at least one band".to_string()),
+            Some(band) if band.min_amount != 0 => return Err("first band must start at 0".to_string()),
+            Some(_) => {}
+        }
+        if self.bands.windows(2).any(|pair| pair[1].min_amount <= pair[0].min_amount) {
+            return Err("band minimums must be strictly increasing".to_string());
+        }
+        if self.bands.iter().any(|band| band.scheme == SignatureType::Ed25519) {
+            return Err("Ed25519 is not quantum-resistant".to_string());
+        }
+        Ok(())
+    }
+
+    /// Scheme the node signs a transaction of `amount` with
+    pub fn scheme_for(&self, amount: Amount) -> SignatureType {
+        self.bands.iter()
+            .take_while(|band| band.min_amount <= amount)
+            .last()
+            .map(|band| band.scheme.clone())
+            .unwrap_or_default()
+    }
+
+    /// Reject transactions signed more weakly than their band requires
+    pub fn check(&self, transaction: &Transaction) -> Result<(), CoreError> {
+        let used = &transaction.signature_scheme;
+        if !signature_len_matches(used, transaction.signature.len()) {
+            return Err(CoreError::SignatureSchemeMismatch(used.clone()));
+        }
+        self.permits(transaction.amount, used)
+    }
+
+    /// Reject signing a transaction of `amount` with `scheme` if it is weaker than the band requires
+    pub fn permits(&self, amount: Amount, scheme: &SignatureType) -> Result<(), CoreError> {
+        let required = self.scheme_for(amount);
+        if strength(scheme) < strength(&required) {
+            return Err(CoreError::WeakSignatureScheme {
+                amount,
+                required,
+                used: scheme.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Relative security of a scheme by NIST level; hybrid adds a classical
+/// signature to Dilithium3
+fn strength(scheme: &SignatureType) -> u8 {
+    match scheme {
+        SignatureType::Ed25519 => 0,
+        SignatureType::Falcon512 => 1,
+        SignatureType::Dilithium3 => 2,
+        SignatureType::Hybrid => 3,
+        SignatureType::Dilithium5 | SignatureType::Falcon1024 => 4,
+    }
+}
+
+/// Size of a transaction signature made with `scheme`, or the largest for Falcon
+fn signature_len(scheme: &SignatureType) -> usize {
+    match scheme {
+        SignatureType::Ed25519 => ED25519_SIGNATURE_LEN,
+        SignatureType::Dilithium3 => dilithium3::signature_bytes(),
+        SignatureType::Dilithium5 => dilithium5::signature_bytes(),
+        SignatureType::Hybrid => ED25519_SIGNATURE_LEN + dilithium3::signature_bytes(),
+        SignatureType::Falcon512 => falcon512::signature_bytes(),
+        SignatureType::Falcon1024 => falcon1024::signature_bytes(),
+    }
+}
+
+/// Whether a signature of `len` bytes can have been made with `scheme`
+fn signature_len_matches(scheme: &SignatureType, len: usize) -> bool {
+    match scheme {
+        // Falcon signatures are compressed, so their size varies
+        SignatureType::Falcon512 | SignatureType::Falcon1024 => len > 0 && len <= signature_len(scheme),
+        _ => len == signature_len(scheme),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::TransactionId;
+
+    fn signed(amount: Amount, scheme: SignatureType) -> Transaction {
+        Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount,
+            fee: 0,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![7u8; signature_len(&scheme)],
+            signature_scheme: scheme,
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_scheme_for_bands() {
+        let policy = SignaturePolicy::default();
+        assert!(policy.validate().is_ok());
+        assert_eq!(policy.scheme_for(0), SignatureType::Dilithium3);
+        assert_eq!(policy.scheme_for(9_999), SignatureType::Dilithium3);
+        assert_eq!(policy.scheme_for(10_000), SignatureType::Hybrid);
+        assert_eq!(policy.scheme_for(Amount::MAX), SignatureType::Dilithium5);
+
+        let unordered = SignaturePolicy {
+            bands: vec![
+                SignatureBand { min_amount: 0, scheme: SignatureType::Dilithium5 },
+                SignatureBand { min_amount: 0, scheme: SignatureType::Dilithium3 },
+            ],
+        };
+        assert!(unordered.validate().is_err());
+    }
+
+    #[test]
+    fn test_check_enforces_band_and_signature_size() {
+        let policy = SignaturePolicy::default();
+        assert!(policy.check(&signed(5, SignatureType::Dilithium3)).is_ok());
+        // Stronger than required is fine
+        assert!(policy.check(&signed(5, SignatureType::Dilithium5)).is_ok());
+        assert!(matches!(
+            policy.check(&signed(2_000_000, SignatureType::Hybrid)),
+            Err(CoreError::WeakSignatureScheme { required: SignatureType::Dilithium5, .. })
+        ));
+
+        // A Dilithium3 signature cannot be passed off as Dilithium5
+        let mut relabeled = signed(2_000_000, SignatureType::Dilithium3);
+        relabeled.signature_scheme = SignatureType::Dilithium5;
+        assert!(matches!(policy.check(&relabeled), Err(CoreError::SignatureSchemeMismatch(_))));
+    }
+
+    #[test]
+    fn test_falcon_signatures_vary_in_size() {
+        let policy = SignaturePolicy::default();
+        let mut compact = signed(2_000_000, SignatureType::Falcon1024);
+        compact.signature.truncate(signature_len(&SignatureType::Falcon1024) - 40);
+        assert!(policy.check(&compact).is_ok());
+
+        // Falcon-512 is level 1, below the Dilithium3 band
+        assert!(matches!(
+            policy.check(&signed(5, SignatureType::Falcon512)),
+            Err(CoreError::WeakSignatureScheme { required: SignatureType::Dilithium3, .. })
+        ));
+        compact.signature = vec![7u8; signature_len(&SignatureType::Falcon1024) + 1];
+        assert!(matches!(policy.check(&compact), Err(CoreError::SignatureSchemeMismatch(_))));
+    }
+}
@@ -0,0 +1,300 @@
+//! Threshold Ed25519 signing for validator keys
+//!
+//! A validator key can be split across `participants` machines so that any
+//! `threshold` of them sign together and fewer learn nothing about the key.
+//! The key is generated with FROST's distributed key generation, so no
+//! machine ever holds it whole:
+//!
+//! 1. Each participant calls `DkgParticipant::start` and broadcasts its
+//!    round 1 package.
+//! 2. With everyone's round 1 packages, `round2` returns one package per
+//!    other participant, each sent privately (e.g. under a peer session key).
+//! 3. `finish` with the round 2 packages addressed to it gives the
+//!    participant its `ThresholdKeyShare`.
+//!
+//! Signing takes two rounds: each signer `commit`s, a coordinator builds a
+//! `SigningPackage` from the commitments, each signer returns a
+//! `sign_share`, and the coordinator `aggregate`s the shares. The result is a
+//! plain Ed25519 signature under the group key, carried in a `NodeSignature`
+//! that also records who signed.
+
+use super::{NodeSignature, SignatureType};
+use frost_ed25519 as frost;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub use frost::keys::dkg::{round1::Package as DkgRound1Package, round2::Package as DkgRound2Package};
+pub use frost::round1::{SigningCommitments, SigningNonces};
+pub use frost::round2::SignatureShare;
+pub use frost::SigningPackage;
+
+/// Signers behind a threshold signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdSigners {
+    pub threshold: u16,
+    pub participants: u16,
+    /// Indexes of the participants whose shares were aggregated
+    pub signers: Vec<u16>,
+}
+
+impl ThresholdSigners {
+    /// At least `threshold` distinct signers out of `participants`
+    pub fn is_well_formed(&self) -> bool {
+        let mut signers = self.signers.clone();
+        signers.sort_unstable();
+        signers.dedup();
+        self.threshold >= 2
+            && self.threshold <= self.participants
+            && signers.len() == self.signers.len()
+            && signers.len() >= self.threshold as usize
+            && signers.iter().all(|&index| index >= 1 && index <= self.participants)
+    }
+}
+
+/// A participant part way through distributed key generation
+pub struct DkgParticipant {
+    index: u16,
+    threshold: u16,
+    participants: u16,
+    round1_secret: Option<frost::keys::dkg::round1::SecretPackage>,
+    round2_secret: Option<frost::keys::dkg::round2::SecretPackage>,
+    round1_packages: BTreeMap<frost::Identifier, DkgRound1Package>,
+}
+
+impl DkgParticipant {
+    /// Start key generation as participant `index` (1-based) of a
+    /// `threshold`-of-`participants` key; the package goes to everyone else
+    pub fn start(index: u16, threshold: u16, participants: u16) -> Result<(Self, DkgRound1Package), ThresholdError> {
+        if threshold < 2 || threshold > participants {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "threshold {} of {} participants",
+                threshold, participants
+            )));
+        }
+        let identifier = identifier(index, participants)?;
+        let (secret, package) = frost::keys::dkg::part1(identifier, participants, threshold, rand::thread_rng())?;
+        let participant = Self {
+            index,
+            threshold,
+            participants,
+            round1_secret: Some(secret),
+            round2_secret: None,
+            round1_packages: BTreeMap::new(),
+        };
+        Ok((participant, package))
+    }
+
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Take the other participants' round 1 packages and return the round 2
+    /// package for each of them, keyed by index
+    pub fn round2(&mut self, round1: BTreeMap<u16, DkgRound1Package>) -> Result<BTreeMap<u16, DkgRound2Package>, ThresholdError> {
+        let secret = self.round1_secret.take().ok_or(ThresholdError::OutOfOrder("round 2"))?;
+        self.round1_packages = self.from_others(round1)?;
+        let (secret, packages) = frost::keys::dkg::part2(secret, &self.round1_packages)?;
+        self.round2_secret = Some(secret);
+        packages
+            .into_iter()
+            .map(|(identifier, package)| Ok((self.index_of(&identifier)?, package)))
+            .collect()
+    }
+
+    /// Finish with the round 2 packages the others addressed to this participant
+    pub fn finish(self, round2: BTreeMap<u16, DkgRound2Package>) -> Result<ThresholdKeyShare, ThresholdError> {
+        let secret = self.round2_secret.as_ref().ok_or(ThresholdError::OutOfOrder("finish"))?;
+        let round2 = self.from_others(round2)?;
+        let (key_package, public_key_package) = frost::keys::dkg::part3(secret, &self.round1_packages, &round2)?;
+        Ok(ThresholdKeyShare {
+            index: self.index,
+            threshold: self.threshold,
+            participants: self.participants,
+            key_package,
+            public_key_package,
+        })
+    }
+
+    fn from_others<P>(&self, packages: BTreeMap<u16, P>) -> Result<BTreeMap<frost::Identifier, P>, ThresholdError> {
+        if packages.len() != self.participants as usize - 1 || packages.contains_key(&self.index) {
+            return Err(ThresholdError::InvalidParameters(format!(
+                "expected packages from the {} other participants",
+                self.participants - 1
+            )));
+        }
+        packages
+            .into_iter()
+            .map(|(index, package)| Ok((identifier(index, self.participants)?, package)))
+            .collect()
+    }
+
+    fn index_of(&self, identifier: &frost::Identifier) -> Result<u16, ThresholdError> {
+        (1..=self.participants)
+            .find(|&index| frost::Identifier::try_from(index).ok().as_ref() == Some(identifier))
+            .ok_or_else(|| ThresholdError::InvalidParameters("unknown participant identifier".to_string()))
+    }
+}
+
+/// One participant's share of a threshold validator key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdKeyShare {
+    pub index: u16,
+    pub threshold: u16,
+    pub participants: u16,
+    key_package: frost::keys::KeyPackage,
+    public_key_package: frost::keys::PublicKeyPackage,
+}
+
+impl ThresholdKeyShare {
+    /// The group's Ed25519 public key, the same for every participant
+    pub fn group_public_key(&self) -> Result<Vec<u8>, ThresholdError> {
+        Ok(self.public_key_package.verifying_key().serialize()?)
+    }
+
+    /// Round 1 of signing: nonces to keep until `sign_share`, and the
+    /// commitments to send the coordinator
+    pub fn commit(&self) -> (SigningNonces, SigningCommitments) {
+        frost::round1::commit(self.key_package.signing_share(), &mut rand::thread_rng())
+    }
+
+    /// Round 2 of signing: this participant's share of the signature
+    ///
+    /// The nonces are consumed, as reusing them would leak the key share.
+    pub fn sign_share(&self, package: &SigningPackage, nonces: SigningNonces) -> Result<SignatureShare, ThresholdError> {
+        Ok(frost::round2::sign(package, &nonces, &self.key_package)?)
+    }
+
+    /// As coordinator, the signing package for `message` from the signers'
+    /// commitments, keyed by index
+    pub fn signing_package(&self, commitments: BTreeMap<u16, SigningCommitments>, message: &[u8]) -> Result<SigningPackage, ThresholdError> {
+        self.check_signers(commitments.len())?;
+        let commitments = commitments
+            .into_iter()
+            .map(|(index, commitment)| Ok((identifier(index, self.participants)?, commitment)))
+            .collect::<Result<BTreeMap<_, _>, ThresholdError>>()?;
+        Ok(SigningPackage::new(commitments, message))
+    }
+
+    /// As coordinator, verify the signers' shares and aggregate them into a signature
+    pub fn aggregate(&self, package: &SigningPackage, shares: BTreeMap<u16, SignatureShare>) -> Result<NodeSignature, ThresholdError> {
+        self.check_signers(shares.len())?;
+        let signers: Vec<u16> = shares.keys().copied().collect();
+        let shares = shares
+            .into_iter()
+            .map(|(index, share)| Ok((identifier(index, self.participants)?, share)))
+            .collect::<Result<BTreeMap<_, _>, ThresholdError>>()?;
+        let signature = frost::aggregate(package, &shares, &self.public_key_package)?;
+
+        Ok(NodeSignature {
+            signature_type: SignatureType::Ed25519,
+            signature_data: signature.serialize()?,
+            public_key: self.group_public_key()?,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            nonce: rand::random(),
+            threshold: Some(ThresholdSigners { threshold: self.threshold, participants: self.participants, signers }),
+        })
+    }
+
+    fn check_signers(&self, signers: usize) -> Result<(), ThresholdError> {
+        if signers < self.threshold as usize {
+            return Err(ThresholdError::NotEnoughSigners { have: signers, need: self.threshold });
+        }
+        Ok(())
+    }
+}
+
+fn identifier(index: u16, participants: u16) -> Result<frost::Identifier, ThresholdError> {
+    if index == 0 || index > participants {
+        return Err(ThresholdError::UnknownParticipant(index));
+    }
+    frost::Identifier::try_from(index).map_err(|_| ThresholdError::UnknownParticipant(index))
+}
+
+/// Threshold signing errors
+#[derive(Debug, thiserror::Error)]
+pub enum ThresholdError {
+    #[error("Invalid threshold parameters: {0}")]
+    InvalidParameters(String),
+    #[error("Unknown participant {0}")]
+    UnknownParticipant(u16),
+    #[error("Key generation step {0} called out of order")]
+    OutOfOrder(&'static str),
+    #[error("{have} signature shares, {need} needed")]
+    NotEnoughSigners { have: usize, need: u16 },
+    #[error("FROST error: {0}")]
+    Frost(#[from] frost::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityManager;
+
+    /// Run a full DKG between `participants` parties
+    fn generate(threshold: u16, participants: u16) -> Vec<ThresholdKeyShare> {
+        let mut parties = Vec::new();
+        let mut round1 = BTreeMap::new();
+        for index in 1..=participants {
+            let (party, package) = DkgParticipant::start(index, threshold, participants).unwrap();
+            parties.push(party);
+            round1.insert(index, package);
+        }
+
+        let mut inboxes: BTreeMap<u16, BTreeMap<u16, DkgRound2Package>> = BTreeMap::new();
+        for party in parties.iter_mut() {
+            let others = round1.iter().filter(|(&i, _)| i != party.index()).map(|(&i, p)| (i, p.clone())).collect();
+            for (recipient, package) in party.round2(others).unwrap() {
+                inboxes.entry(recipient).or_default().insert(party.index(), package);
+            }
+        }
+        parties
+            .into_iter()
+            .map(|party| {
+                let inbox = inboxes.remove(&party.index()).unwrap();
+                party.finish(inbox).unwrap()
+            })
+            .collect()
+    }
+
+    fn sign(shares: &[&ThresholdKeyShare], message: &[u8]) -> Result<NodeSignature, ThresholdError> {
+        let rounds: Vec<_> = shares.iter().map(|share| (share.index, share.commit())).collect();
+        let commitments = rounds.iter().map(|(index, (_, commitments))| (*index, *commitments)).collect();
+        let package = shares[0].signing_package(commitments, message)?;
+        let signature_shares = shares
+            .iter()
+            .zip(rounds)
+            .map(|(share, (index, (nonces, _)))| Ok((index, share.sign_share(&package, nonces)?)))
+            .collect::<Result<BTreeMap<_, _>, ThresholdError>>()?;
+        shares[0].aggregate(&package, signature_shares)
+    }
+
+    #[tokio::test]
+    async fn test_two_of_three_signature_verifies_as_ed25519() {
+        let shares = generate(2, 3);
+        let group_key = shares[0].group_public_key().unwrap();
+        assert!(shares.iter().all(|share| share.group_public_key().unwrap() == group_key));
+
+        let signature = sign(&[&shares[0], &shares[2]], b"checkpoint 42").unwrap();
+        assert_eq!(signature.public_key, group_key);
+        assert_eq!(signature.threshold.as_ref().unwrap().signers, vec![1, 3]);
+
+        let manager = IdentityManager::new(String::new());
+        assert!(manager.verify(b"checkpoint 42", &signature).await.unwrap());
+        assert!(!manager.verify(b"checkpoint 43", &signature).await.unwrap());
+
+        // Claiming fewer signers than the threshold fails verification
+        let mut understated = signature.clone();
+        understated.threshold.as_mut().unwrap().signers = vec![1];
+        assert!(!manager.verify(b"checkpoint 42", &understated).await.unwrap());
+    }
+
+    #[test]
+    fn test_below_threshold_cannot_sign() {
+        let shares = generate(3, 4);
+        assert!(matches!(
+            sign(&[&shares[0], &shares[1]], b"checkpoint 42"),
+            Err(ThresholdError::NotEnoughSigners { have: 2, need: 3 })
+        ));
+        assert!(matches!(DkgParticipant::start(1, 5, 4), Err(ThresholdError::InvalidParameters(_))));
+    }
+}
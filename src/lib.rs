@@ -14,6 +14,9 @@ pub mod storage;
 pub mod identity;
 pub mod metrics;
 pub mod governance;
+pub mod events;
+#[cfg(feature = "testkit")]
+pub mod testkit;
 
 pub use core::*;
 pub use math::*;
@@ -26,6 +29,7 @@ pub use storage::*;
 pub use identity::*;
 pub use metrics::*;
 pub use governance::*;
+pub use events::*;
 
 // Re-export identity types
 pub use identity::{IdentityRotationEvent, IdentityRotationReadiness};
@@ -69,6 +73,46 @@ pub struct Blockchain {
     identity: Arc<RwLock<IdentityManager>>,
     /// Metrics collector
     metrics: Arc<BlockchainMetrics>,
+    /// Intent log for asynchronous submissions
+    ingestion: Arc<IngestionQueue>,
+    /// Address filters for light client sync
+    filters: Arc<RwLock<FilterIndex>>,
+    /// Running settings, updated by hot reloads
+    settings: Arc<RwLock<NodeSettings>>,
+    /// Encrypted operator-to-operator messages
+    operator_mailbox: Arc<RwLock<OperatorMailbox>>,
+    /// Read-only replica serving analytical queries
+    read_replica: Arc<RwLock<Option<Arc<ReadReplica>>>>,
+    /// Test-network faucet, when enabled
+    faucet: Arc<RwLock<Option<Arc<Faucet>>>>,
+    /// Uploads finalized milestone ranges to cold storage, when enabled
+    archiver: Arc<RwLock<Option<Arc<Archiver>>>>,
+    /// Events published by subsystems
+    events: EventBus,
+    /// Halts transaction acceptance while keeping reads and sync alive
+    safe_mode: Arc<SafeMode>,
+    /// Background rebuild of derived tables
+    reindex: Arc<std::sync::RwLock<ReindexStatus>>,
+    /// Trust anchor whose validator set this node attests to
+    trust_anchor: Arc<RwLock<Option<TrustAnchor>>>,
+    /// Funds held by accepted transfers until they finalize
+    accounts: Arc<AccountState>,
+    /// Governance service, when this node runs one
+    governance: Arc<RwLock<Option<Arc<GovernanceService>>>>,
+    /// Concurrent signature, prime-layer and policy checks
+    validation: ValidationPipeline,
+    /// Fee rates of recently accepted transactions
+    fee_market: Arc<FeeMarket>,
+    /// Recent confirmation latencies
+    congestion: Arc<CongestionMonitor>,
+    /// Executes contract payloads of finalized transactions
+    contracts: Arc<RwLock<ContractEngine>>,
+    /// Finalizes transactions attested by a validator quorum
+    finality: Arc<FinalityGadget>,
+    /// Consensus round latency and fork rate against their objectives
+    slo: Arc<SloMonitor>,
+    /// Decides double spends by sampling peers, in sampling confirmation mode
+    sampler: Option<Arc<ConfirmationSampler>>,
 }
 
 impl Blockchain {
@@ -78,23 +122,86 @@ impl Blockchain {
         let db_config = DatabaseConfig {
             path: config.database.path.clone(),
             max_connections: config.database.cache_size_mb as u32 / 10, // Estimate connections from cache size
+            retention: RetentionConfig::default(),
+            checksums: ChecksumConfig::default(),
+            backend: config.database.backend,
+            // Batch the transaction and DAG node writes of a busy node
+            write_queue: WriteQueueConfig { enabled: true, ..WriteQueueConfig::default() },
+            auto_migrate: true,
+            encryption_key: crate::storage::resolve_encryption_key(config.security.database_key.as_deref()),
+            remote_backup: config.database.remote_backup.clone(),
+            pragmas: config.database.pragmas.clone(),
+            mode: config.database.mode,
+            metadata_index: config.database.metadata_index,
         };
-        let database = Arc::new(DatabaseManager::new(db_config).await?);
-        
+
+        // Subsystems publish to the event bus instead of calling each other
+        let events = EventBus::default();
+
+        let mut database = DatabaseManager::new(db_config).await?;
+        database.set_event_bus(events.clone());
+        let database = Arc::new(database);
+
         // Initialize identity manager
         let mut identity_manager = IdentityManager::new(config.database.path.clone());
+        identity_manager.set_event_bus(events.clone());
+        identity_manager.set_key_source(config.security.identity_key.clone().resolve());
         identity_manager.initialize_identity().await?;
         let identity = Arc::new(RwLock::new(identity_manager));
         
         // Initialize metrics
         let metrics = Arc::new(BlockchainMetrics::new()?);
+        metrics.register_storage(database.metrics()).map_err(|e| BlockchainError::Other(e.to_string()))?;
+        events.spawn_handler(metrics.clone());
         
         // Initialize components
-        let dag = Arc::new(RwLock::new(DAGCore::new_with_database(database.clone()).await?));
+        let mut dag_core = DAGCore::new_with_paging(database.clone(), &Self::paging_config(&config)).await?;
+        dag_core.set_event_bus(events.clone());
+        dag_core.set_confirmation_thresholds(config.consensus.confirmation_thresholds());
+        let sampler = match &config.consensus.confirmation {
+            ConfirmationMode::Local => None,
+            ConfirmationMode::Sampling(sampling) => {
+                dag_core.set_conflict_sampling(true);
+                Some(Arc::new(ConfirmationSampler::new(sampling.clone())))
+            }
+        };
+        let dag = Arc::new(RwLock::new(dag_core));
         let prime_layer = Arc::new(PrimeLayer::new()?);
         let network = Arc::new(NetworkLayer::new(&config.network).await?);
-        let consensus = Arc::new(ConsensusEngine::new(&config.consensus)?);
+        let mut consensus_engine = ConsensusEngine::new(&config.consensus)?;
+        consensus_engine.set_event_bus(events.clone());
+        events.spawn_handler(Arc::new(FeeAccrualHandler::new(consensus_engine.fee_ledger(), database.clone())));
+        let finality = Arc::new(FinalityGadget::new(FinalityConfig::default(), consensus_engine.validator_count() as usize));
+        events.spawn_handler(Arc::new(FinalityHandler::new(finality.clone(), dag.clone(), events.clone())));
+        events.spawn_handler(Arc::new(MilestoneRecorder::new(database.clone())));
+        let contracts = Arc::new(RwLock::new(ContractEngine::new()?));
+        let governance = Arc::new(RwLock::new(None));
+        events.spawn_handler(Arc::new(PayloadRouter::new(
+            database.clone(),
+            contracts.clone(),
+            consensus_engine.stake_ledger(),
+            governance.clone(),
+        )));
+        let consensus = Arc::new(consensus_engine);
         let security = Arc::new(SecurityManager::new(&config.security)?);
+        let node_settings = NodeSettings::from_config(&config);
+        security.set_fee_policy(node_settings.fees.clone());
+        database.set_checksum_config(node_settings.checksums.clone());
+        dag.write().await.set_limits(node_settings.limits.clone());
+        dag.write().await.set_orphan_config(node_settings.orphans.clone());
+        let validation = ValidationPipeline::new(security.clone(), prime_layer.clone(), &node_settings.validation);
+        let settings = Arc::new(RwLock::new(node_settings));
+        let operator_mailbox = OperatorMailbox::open(format!("{}/operator_messages.json", config.database.path)).await?;
+        let mut safe_mode = SafeMode::open(SafeModeConfig::default(), format!("{}/safe_mode.json", config.database.path)).await?;
+        safe_mode.set_event_bus(events.clone());
+        metrics.record_safe_mode(safe_mode.is_halted());
+        let accounts = Arc::new(AccountState::new());
+        events.spawn_handler(Arc::new(AccountStateHandler::new(accounts.clone(), database.clone())));
+        let congestion = Arc::new(CongestionMonitor::default());
+        events.spawn_handler(congestion.clone());
+        let slo = Arc::new(SloMonitor::new(settings.read().await.slo.clone(), events.clone()));
+        events.spawn_handler(slo.clone());
+        events.spawn_handler(Arc::new(JournalRecorder::new(database.clone())));
 
         Ok(Self {
             config,
@@ -106,9 +213,37 @@ impl Blockchain {
             database,
             identity,
             metrics,
+            ingestion: Arc::new(IngestionQueue::new(IngestionConfig::default())),
+            filters: Arc::new(RwLock::new(FilterIndex::default())),
+            settings,
+            operator_mailbox: Arc::new(RwLock::new(operator_mailbox)),
+            read_replica: Arc::new(RwLock::new(None)),
+            faucet: Arc::new(RwLock::new(None)),
+            archiver: Arc::new(RwLock::new(None)),
+            events,
+            safe_mode: Arc::new(safe_mode),
+            reindex: Arc::new(std::sync::RwLock::new(ReindexStatus::default())),
+            trust_anchor: Arc::new(RwLock::new(None)),
+            accounts,
+            governance,
+            validation,
+            fee_market: Arc::new(FeeMarket::default()),
+            congestion,
+            contracts,
+            finality,
+            slo,
+            sampler,
         })
     }
 
+    /// What the DAG loads into memory and caches
+    fn paging_config(config: &BlockchainConfig) -> PagingConfig {
+        PagingConfig {
+            cache_size_mb: config.database.cache_size_mb,
+            ..PagingConfig::default()
+        }
+    }
+
     /// Start the blockchain
     pub async fn start(&self) -> Result<(), BlockchainError> {
         log::info!("Starting Quantum-Proof DAG Blockchain...");
@@ -121,27 +256,251 @@ impl Blockchain {
         
         // Start security manager
         self.security.start().await?;
+
+        // Start the contract engine for contract payloads
+        self.contracts.write().await.start().await?;
+        
+        self.spawn_pruning();
+        self.spawn_expiry();
+        self.spawn_sampling();
+        self.spawn_state_checkpoints();
+        self.spawn_maintenance();
         
         log::info!("Blockchain started successfully");
         Ok(())
     }
 
+    /// Prune the DAG every `pruning.interval_secs` while pruning is enabled
+    fn spawn_pruning(&self) {
+        let dag = self.dag.clone();
+        let database = self.database.clone();
+        let settings = self.settings.clone();
+        spawn_instrumented(Subsystem::Core, "pruning", async move {
+            loop {
+                let config = settings.read().await.pruning.clone();
+                tokio::time::sleep(std::time::Duration::from_secs(config.interval_secs.max(1))).await;
+                if !config.enabled {
+                    continue;
+                }
+                if let Err(e) = prune_dag(&dag, &database, &config).await {
+                    log::error!("❌ DAG pruning failed: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Evict expired pending transactions every `expiry.interval_secs` while expiry is enabled,
+    /// and drop orphans whose parents did not arrive in time
+    fn spawn_expiry(&self) {
+        let dag = self.dag.clone();
+        let settings = self.settings.clone();
+        spawn_instrumented(Subsystem::Core, "expiry", async move {
+            loop {
+                let config = settings.read().await.expiry.clone();
+                tokio::time::sleep(std::time::Duration::from_secs(config.interval_secs.max(1))).await;
+                let now = chrono::Utc::now().timestamp() as u64;
+                dag.write().await.expire_orphans(now);
+                if !config.enabled {
+                    continue;
+                }
+                dag.write().await.evict_expired(now, &config);
+            }
+        });
+    }
+
+    /// Record a state checkpoint every `state_checkpoints.interval_secs` while checkpoints are enabled
+    fn spawn_state_checkpoints(&self) {
+        let dag = self.dag.clone();
+        let database = self.database.clone();
+        let consensus = self.consensus.clone();
+        let settings = self.settings.clone();
+        spawn_instrumented(Subsystem::Storage, "state-checkpoints", async move {
+            loop {
+                let config = settings.read().await.state_checkpoints.clone();
+                tokio::time::sleep(std::time::Duration::from_secs(config.interval_secs.max(1))).await;
+                if !config.enabled {
+                    continue;
+                }
+                if let Err(e) = checkpoint_state(&dag, &database, consensus.current_height(), config.keep).await {
+                    log::error!("❌ State checkpoint failed: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Check for due maintenance jobs every `maintenance.check_interval_secs` while maintenance is enabled
+    fn spawn_maintenance(&self) {
+        let database = self.database.clone();
+        let settings = self.settings.clone();
+        spawn_instrumented(Subsystem::Storage, "maintenance", async move {
+            let mut scheduler = MaintenanceScheduler::new();
+            loop {
+                let config = settings.read().await.maintenance.clone();
+                tokio::time::sleep(std::time::Duration::from_secs(config.check_interval_secs.max(1))).await;
+                if !config.enabled {
+                    continue;
+                }
+                if let Err(e) = scheduler.check(&database, &config).await {
+                    log::error!("❌ Database maintenance check failed: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Sample peers on unresolved double spends every `interval_ms` in sampling confirmation mode
+    fn spawn_sampling(&self) {
+        let Some(sampler) = self.sampler.clone() else {
+            return;
+        };
+        let dag = self.dag.clone();
+        let network = self.network.clone();
+        spawn_instrumented(Subsystem::Consensus, "sampling", async move {
+            let interval = std::time::Duration::from_millis(sampler.config().interval_ms);
+            loop {
+                tokio::time::sleep(interval).await;
+                sampler.run_round(&dag, network.as_ref()).await;
+            }
+        });
+    }
+
+    /// Evict expired pending transactions now with the running expiry settings
+    pub async fn evict_expired_transactions(&self) -> Vec<(TransactionId, EvictionReason)> {
+        let config = self.settings.read().await.expiry.clone();
+        self.dag.write().await.evict_expired(chrono::Utc::now().timestamp() as u64, &config)
+    }
+
+    /// Prune the DAG now with the running pruning settings, even if periodic pruning is off
+    pub async fn prune_dag(&self) -> Result<PruneReport, BlockchainError> {
+        let config = self.settings.read().await.pruning.clone();
+        prune_dag(&self.dag, &self.database, &config).await
+    }
+
+    /// Run a database maintenance job now, whether or not it is due
+    pub async fn run_maintenance(&self, job: MaintenanceJob) -> Result<MaintenanceRun, BlockchainError> {
+        self.database.run_maintenance(job, true).await
+    }
+
+    /// The last run of each database maintenance job
+    pub async fn maintenance_runs(&self) -> Result<Vec<MaintenanceRun>, BlockchainError> {
+        self.database.maintenance_runs().await
+    }
+
+    /// Finalized transactions left at the pruned boundary
+    pub async fn get_checkpoint_roots(&self) -> Vec<TransactionId> {
+        self.dag.read().await.checkpoint_roots()
+    }
+
+    /// Finalized height and attestations still short of a quorum
+    pub fn get_finality_status(&self) -> FinalityStatus {
+        self.finality.status()
+    }
+
+    /// Double spends this node is sampling peers on, empty unless in sampling confirmation mode
+    pub fn get_sampling_status(&self) -> Vec<SamplingStatus> {
+        self.sampler.as_ref().map(|sampler| sampler.status()).unwrap_or_default()
+    }
+
+    /// This node's answer to a peer asking which spend of `key` it prefers
+    pub async fn preferred_spend(&self, key: &SpendKey) -> Option<TransactionId> {
+        let conflict = self.dag.read().await.get_conflict_sets().into_iter().find(|conflict| conflict.key == *key)?;
+        match &self.sampler {
+            Some(sampler) => sampler.preference(&conflict),
+            None => conflict.winner.or_else(|| conflict.members.first().cloned()),
+        }
+    }
+
     /// Stop the blockchain
     pub async fn stop(&self) -> Result<(), BlockchainError> {
         log::info!("Stopping Quantum-Proof DAG Blockchain...");
         
         // Stop components in reverse order
+        self.contracts.write().await.stop().await?;
         self.security.stop().await?;
         self.consensus.stop().await?;
         self.network.stop().await?;
+        self.database.flush().await?;
         
         log::info!("Blockchain stopped successfully");
         Ok(())
     }
 
     /// Submit a transaction to the blockchain
-    pub async fn submit_transaction(&self, mut transaction: Transaction) -> Result<TransactionId, BlockchainError> {
-        let start_time = std::time::Instant::now();
+    pub async fn submit_transaction(&self, transaction: Transaction) -> Result<TransactionId, BlockchainError> {
+        self.submit_transaction_with_deadline(transaction, Deadline::never()).await
+    }
+
+    /// Submit a transaction that must be accepted by `deadline`
+    ///
+    /// The node's `submit_timeout_ms` applies if it is earlier. A submission
+    /// that runs out of time is abandoned at the current stage: reserved funds
+    /// are released and nothing is inserted. Once inserted, a transaction
+    /// stays accepted even if propagation runs out of time.
+    pub async fn submit_transaction_with_deadline(
+        &self,
+        transaction: Transaction,
+        deadline: Deadline,
+    ) -> Result<TransactionId, BlockchainError> {
+        self.submit(transaction, None, deadline).await
+    }
+
+    /// Submit a transaction signed with `scheme` rather than its value band's
+    ///
+    /// Lets bandwidth-constrained submitters pick the compact Falcon
+    /// signatures. The scheme must be at least as strong as the band requires.
+    pub async fn submit_transaction_with_scheme(
+        &self,
+        transaction: Transaction,
+        scheme: SignatureType,
+        deadline: Deadline,
+    ) -> Result<TransactionId, BlockchainError> {
+        self.submit(transaction, Some(scheme), deadline).await
+    }
+
+    async fn submit(
+        &self,
+        transaction: Transaction,
+        scheme: Option<SignatureType>,
+        deadline: Deadline,
+    ) -> Result<TransactionId, BlockchainError> {
+        self.safe_mode.ensure_accepting().await?;
+        let (signature_policy, submit_timeout_ms) = {
+            let settings = self.settings.read().await;
+            (settings.signature_policy.clone(), settings.submit_timeout_ms)
+        };
+        let deadline = deadline.earliest(Deadline::after(std::time::Duration::from_millis(submit_timeout_ms)));
+
+        let transaction = self.within(deadline, SubmitStage::Signing, self.sign_transaction(transaction, scheme, &signature_policy)).await??;
+        let validation = self.within(deadline, SubmitStage::Validation, self.validation.validate(&transaction, &signature_policy)).await?;
+        self.accept_transaction(transaction, validation, deadline).await
+    }
+
+    /// Run a submission stage under `deadline`, counting it if time runs out
+    async fn within<F: std::future::Future>(&self, deadline: Deadline, stage: SubmitStage, future: F) -> Result<F::Output, BlockchainError> {
+        deadline.run(stage, future).await.map_err(|e| {
+            self.metrics.record_deadline_exceeded(stage);
+            log::warn!("⏱️ Submission ran out of time during {}", stage);
+            e.into()
+        })
+    }
+
+    /// Give a submitted transaction its ID, signature and quantum proof
+    async fn sign_transaction(
+        &self,
+        mut transaction: Transaction,
+        scheme: Option<SignatureType>,
+        signature_policy: &SignaturePolicy,
+    ) -> Result<Transaction, BlockchainError> {
+        // The node derives the ID itself rather than trusting the submitter's
+        transaction.id = transaction.compute_id();
+        
+        // The value band decides which scheme the node signs with, unless the submitter chose a strong enough one
+        transaction.signature_scheme = match scheme {
+            Some(scheme) => {
+                signature_policy.permits(transaction.amount, &scheme)?;
+                scheme
+            }
+            None => signature_policy.scheme_for(transaction.amount),
+        };
         
         // Sign the transaction using identity manager
         let identity = self.identity.read().await;
@@ -166,36 +525,647 @@ impl Blockchain {
         self.metrics.record_quantum_proof_generation(quantum_proof_start.elapsed());
         transaction.quantum_proof = quantum_proof;
         
-        drop(identity); // Release the lock
-        
-        // Validate transaction
-        self.security.validate_transaction(&transaction).await?;
-        
-        // Apply prime layer validation
-        self.prime_layer.validate_transaction(&transaction).await?;
+        Ok(transaction)
+    }
+
+    /// Insert a locally submitted transaction given its validation result
+    async fn accept_transaction(
+        &self,
+        transaction: Transaction,
+        validation: Result<(), BlockchainError>,
+        deadline: Deadline,
+    ) -> Result<TransactionId, BlockchainError> {
+        if let Err(e) = validation {
+            self.dag.write().await.penalize_sender(&transaction.sender);
+            return Err(e);
+        }
         
-        // Add to DAG
-        let mut dag = self.dag.write().await;
-        let tx_id = dag.add_transaction(transaction).await?;
+        // The sender's balance must cover this and its other pending transfers
+        let balance = self.database.get_balance(&account_address(&transaction.sender)).await?;
+        self.accounts.reserve(&transaction, balance)?;
+
+        // Waiting for the DAG lock counts against the deadline; the insert itself
+        // runs to completion so storage and the in-memory DAG stay consistent
+        let mut dag = match self.within(deadline, SubmitStage::Insert, self.dag.write()).await {
+            Ok(dag) => dag,
+            Err(e) => {
+                self.accounts.release(&transaction.id);
+                return Err(e);
+            }
+        };
+        let tx_id = match dag.add_transaction(transaction.clone()).await {
+            Ok(tx_id) => tx_id,
+            Err(e) => {
+                self.accounts.release(&transaction.id);
+                return Err(e);
+            }
+        };
+        dag.record_fee(&transaction);
+        self.fee_market.record(&transaction);
+        self.filters.write().await.add_transaction(&transaction);
         
         // Update confidence scores
         dag.update_confidence_scores();
         
-        // Record transaction metric
-        self.metrics.record_transaction();
+        self.events.publish(NodeEvent::TxAccepted { transaction: transaction.clone(), from_peer: false });
+        self.adopt_orphans(&mut dag, &[tx_id.clone()]).await;
+        drop(dag);
         
-        // Propagate through network
-        self.network.propagate_transaction(&tx_id).await?;
+        // Propagate through network; the transaction is already accepted, so
+        // running out of time here does not fail the submission
+        if let Ok(propagated) = self.within(deadline, SubmitStage::Propagation, self.network.propagate_transaction(&transaction)).await {
+            propagated?;
+        }
         
         Ok(tx_id)
     }
 
+    /// Submit a bundle of related transactions, accepted all or none
+    ///
+    /// Members may approve each other by ID. Every member is signed and
+    /// validated, and the senders' balances must cover all of them, before
+    /// the bundle is inserted in one step. Returns the member IDs, parents
+    /// before the members approving them.
+    pub async fn submit_bundle(&self, transactions: Vec<Transaction>) -> Result<Vec<TransactionId>, BlockchainError> {
+        self.safe_mode.ensure_accepting().await?;
+        let signature_policy = self.settings.read().await.signature_policy.clone();
+
+        let mut signed = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            signed.push(self.sign_transaction(transaction, None, &signature_policy).await?);
+        }
+        let results = self.validation.validate_batch(&signed, &signature_policy).await;
+        for (transaction, result) in signed.iter().zip(results) {
+            if let Err(e) = result {
+                self.dag.write().await.penalize_sender(&transaction.sender);
+                return Err(BlockchainError::Core(CoreError::BundleRejected {
+                    tx_id: transaction.id.clone(),
+                    reason: e.to_string(),
+                }));
+            }
+        }
+
+        // Reservations are all released if any member cannot be covered
+        let release = |reserved: &[Transaction]| {
+            for transaction in reserved {
+                self.accounts.release(&transaction.id);
+            }
+        };
+        for (index, transaction) in signed.iter().enumerate() {
+            let reserved = match self.database.get_balance(&account_address(&transaction.sender)).await {
+                Ok(balance) => self.accounts.reserve(transaction, balance).map_err(BlockchainError::from),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = reserved {
+                release(&signed[..index]);
+                return Err(e);
+            }
+        }
+
+        let mut dag = self.dag.write().await;
+        let tx_ids = match dag.add_bundle(signed.clone()).await {
+            Ok(tx_ids) => tx_ids,
+            Err(e) => {
+                release(&signed);
+                return Err(e);
+            }
+        };
+        let mut filters = self.filters.write().await;
+        for transaction in &signed {
+            dag.record_fee(transaction);
+            self.fee_market.record(transaction);
+            filters.add_transaction(transaction);
+        }
+        drop(filters);
+        dag.update_confidence_scores();
+        for transaction in &signed {
+            self.events.publish(NodeEvent::TxAccepted { transaction: transaction.clone(), from_peer: false });
+        }
+        self.adopt_orphans(&mut dag, &tx_ids).await;
+        drop(dag);
+
+        for transaction in &signed {
+            self.network.propagate_transaction(transaction).await?;
+        }
+        log::info!("📦 Accepted bundle of {} transaction(s)", tx_ids.len());
+        Ok(tx_ids)
+    }
+
+    /// Validate and insert a transaction relayed by a peer
+    ///
+    /// Validation failures count against the sending peer's misbehavior score.
+    /// A transaction arriving before its parents is held as an orphan and
+    /// inserted once they have all been added.
+    pub async fn receive_peer_transaction(
+        &self,
+        peer: &libp2p::PeerId,
+        transaction: Transaction,
+        message_size: usize,
+    ) -> Result<TransactionId, BlockchainError> {
+        self.safe_mode.ensure_accepting().await?;
+        self.network.admit_message(peer, message_size).await?;
+
+        // Drop junk before paying for signature verification
+        let screened = self.network.screen_gossip(peer, &transaction).await;
+        self.metrics.record_gossip_verdict(screened.err());
+        screened.map_err(|reason| BlockchainError::Network(NetworkError::SpamFiltered(reason)))?;
+
+        let signature_policy = self.settings.read().await.signature_policy.clone();
+        if let Err(e) = self.validation.validate(&transaction, &signature_policy).await {
+            self.network.report_validation_failure(peer, &e).await;
+            self.dag.write().await.penalize_sender(&transaction.sender);
+            return Err(e);
+        }
+
+        let tx_id = transaction.id.clone();
+        let mut dag = self.dag.write().await;
+        let added = dag.add_or_hold(transaction, chrono::Utc::now().timestamp() as u64).await?;
+        if added.is_empty() {
+            return Ok(tx_id);
+        }
+        self.record_relayed(&mut dag, added).await;
+        dag.update_confidence_scores();
+
+        Ok(tx_id)
+    }
+
+    /// Decode and receive a transaction a peer sent in its canonical encoding
+    ///
+    /// Payloads that do not decode count against the peer as malformed.
+    pub async fn receive_peer_payload(&self, peer: &libp2p::PeerId, payload: &[u8]) -> Result<TransactionId, BlockchainError> {
+        let transaction = match Transaction::from_canonical_bytes(payload) {
+            Ok(transaction) => transaction,
+            Err(e) => {
+                self.network.report_misbehavior(peer, Misbehavior::MalformedMessage).await;
+                return Err(BlockchainError::Core(e.into()));
+            }
+        };
+        self.receive_peer_transaction(peer, transaction, payload.len()).await
+    }
+
+    /// Record fees and filters for transactions added on behalf of peers, and announce them
+    async fn record_relayed(&self, dag: &mut DAGCore, transactions: Vec<Transaction>) {
+        if transactions.is_empty() {
+            return;
+        }
+        let mut filters = self.filters.write().await;
+        for transaction in transactions {
+            dag.record_fee(&transaction);
+            self.fee_market.record(&transaction);
+            filters.add_transaction(&transaction);
+            self.events.publish(NodeEvent::TxAccepted { transaction, from_peer: true });
+        }
+    }
+
+    /// Add the orphans that were waiting on locally submitted `parents`
+    async fn adopt_orphans(&self, dag: &mut DAGCore, parents: &[TransactionId]) {
+        let adopted = dag.adopt_orphans(parents).await;
+        if !adopted.is_empty() {
+            self.record_relayed(dag, adopted).await;
+            dag.update_confidence_scores();
+        }
+    }
+
+    /// Relayed transactions held until their parents arrive
+    pub async fn get_orphan_stats(&self) -> OrphanStats {
+        self.dag.read().await.orphan_stats()
+    }
+
+    /// Suggested fees for `transaction` from recently accepted fee rates
+    ///
+    /// The fee already set on `transaction` does not affect the estimate.
+    pub fn estimate_fee(&self, transaction: &Transaction) -> FeeEstimate {
+        self.fee_market.estimate(&self.security.fee_policy(), transaction)
+    }
+
+    /// Compliance of recent consensus rounds with the configured objectives
+    pub fn slo_report(&self) -> SloReport {
+        self.slo.report()
+    }
+
+    /// Latest SLO breaches and recoveries from the event journal, newest first
+    pub async fn get_slo_incidents(&self, limit: usize) -> Result<Vec<JournalEntry>, BlockchainError> {
+        self.database.get_journal(&["SloBreached", "SloRecovered"], limit).await
+    }
+
+    /// Latest journaled events, optionally of one kind, newest first
+    pub async fn get_event_journal(&self, kind: Option<&str>, limit: usize) -> Result<Vec<JournalEntry>, BlockchainError> {
+        let kinds: Vec<&str> = kind.into_iter().collect();
+        self.database.get_journal(&kinds, limit).await
+    }
+
+    /// Intent log depth, confirmation latency and fee rates, classified into a congestion level
+    pub async fn congestion_report(&self) -> CongestionReport {
+        let stats = self.ingestion.stats().await;
+        CongestionReport::new(
+            self.congestion.config(),
+            stats.pending,
+            stats.capacity,
+            self.congestion.latency(),
+            self.fee_market.rates(&self.security.fee_policy()),
+        )
+    }
+
+    /// Fees accrued by validators of finalized rounds
+    pub fn fee_ledger(&self) -> Arc<FeeLedger> {
+        self.consensus.fee_ledger()
+    }
+
+    /// Stake bonded to validators by stake transactions
+    pub fn stake_ledger(&self) -> Arc<StakeLedger> {
+        self.consensus.stake_ledger()
+    }
+
+    /// Contract engine executing contract payloads
+    pub fn contract_engine(&self) -> Arc<RwLock<ContractEngine>> {
+        self.contracts.clone()
+    }
+
+    /// Trace a contract call against current state without keeping its changes
+    pub async fn debug_call(
+        &self,
+        contract_id: &ContractId,
+        function_name: &str,
+        input: Vec<u8>,
+        caller: Vec<u8>,
+        value: Amount,
+        gas_limit: u64,
+    ) -> Result<ExecutionTrace, BlockchainError> {
+        let (_, trace) = self.contracts.write().await
+            .debug_call(contract_id, function_name, input, caller, value, gas_limit)
+            .await?;
+        Ok(trace)
+    }
+
+    /// Replay a contract call transaction with tracing, without keeping its changes
+    ///
+    /// The call runs against current contract state, which may differ from
+    /// the state the transaction originally ran against.
+    pub async fn debug_transaction(&self, tx_id: &TransactionId) -> Result<ExecutionTrace, BlockchainError> {
+        let transaction = self.get_transaction(tx_id).await?
+            .ok_or_else(|| CoreError::TransactionNotFound(tx_id.clone()))?;
+        match TransactionPayload::from_transaction(&transaction)? {
+            TransactionPayload::ContractCall { contract_id, function, input, gas_limit } => {
+                let input = hex::decode(input).map_err(|_| CoreError::InvalidPayload("input is not hex".to_string()))?;
+                self.debug_call(&ContractId::new(contract_id), &function, input, transaction.sender.clone(), transaction.amount, gas_limit).await
+            }
+            other => Err(CoreError::InvalidPayload(format!("{} transactions run no contract code", other.kind())).into()),
+        }
+    }
+
+    /// Subscribe to node events published from now on
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<NodeEvent> {
+        self.events.subscribe()
+    }
+
+    /// Feed node events to `handler` on a background task
+    pub fn add_event_handler(&self, handler: Arc<dyn EventHandler>) {
+        self.events.spawn_handler(handler);
+    }
+
+    /// Set the provider used to screen transaction addresses
+    pub async fn set_compliance_provider(&self, provider: Arc<dyn ComplianceProvider>) {
+        self.security.set_compliance_provider(provider).await;
+    }
+
+    /// Get compliance screening decisions for audit
+    pub async fn get_compliance_records(&self) -> Vec<ComplianceRecord> {
+        self.security.compliance_records().await
+    }
+
+    /// Get effectiveness counters of the gossip spam filter
+    pub fn get_spam_filter_stats(&self) -> SpamFilterStats {
+        self.network.spam_filter_stats()
+    }
+
+    /// Get misbehavior scores for known peers
+    pub async fn get_peer_scores(&self) -> Vec<PeerScore> {
+        self.network.peer_scores().await
+    }
+
+    /// Negotiate the protocol with a connecting peer and update the capability metrics
+    pub async fn peer_handshake(&self, peer: &libp2p::PeerId, remote: &Capabilities) -> Result<NegotiatedProtocol, BlockchainError> {
+        let protocol = self.network.handshake(peer, remote).await?;
+        self.metrics.record_peer_capabilities(&self.network.capability_distribution().await);
+        Ok(protocol)
+    }
+
+    /// Forget a disconnected peer's negotiated protocol
+    pub async fn peer_disconnected(&self, peer: &libp2p::PeerId) {
+        self.network.forget_peer(peer).await;
+        self.metrics.record_peer_capabilities(&self.network.capability_distribution().await);
+    }
+
+    /// This node's capabilities and how connected peers' compare
+    pub async fn get_peer_capabilities(&self) -> (Capabilities, CapabilityDistribution) {
+        (self.network.local_capabilities().clone(), self.network.capability_distribution().await)
+    }
+
+    /// Replace the scoring function used for parent selection
+    pub async fn set_tip_scorer(&self, scorer: Arc<dyn TipScorer>) {
+        self.dag.write().await.set_tip_scorer(scorer);
+    }
+
+    /// Shared tip selection parameters, for wiring into governance execution
+    pub async fn tip_selection_params(&self) -> Arc<std::sync::RwLock<TipSelectionParams>> {
+        self.dag.read().await.tip_selector().params_handle()
+    }
+
+    /// Add another operator as a messaging contact from their published identity info
+    pub async fn add_operator_contact(&self, info: &IdentityInfo) -> Result<OperatorContact, BlockchainError> {
+        let contact = OperatorContact::from_identity_info(info)?;
+        self.operator_mailbox.write().await.add_contact(contact.clone()).await?;
+        Ok(contact)
+    }
+
+    /// Get messaging contacts
+    pub async fn get_operator_contacts(&self) -> Vec<OperatorContact> {
+        self.operator_mailbox.read().await.contacts()
+    }
+
+    /// Get stored operator messages, optionally limited to one peer
+    pub async fn get_operator_messages(&self, peer: Option<&str>) -> Vec<OperatorMessage> {
+        self.operator_mailbox.read().await.messages(peer)
+    }
+
+    /// Encrypt a message to another operator and send it directly
+    pub async fn send_operator_message(&self, recipient: &str, body: &str) -> Result<OperatorMessage, BlockchainError> {
+        let identity = self.current_node_identity().await?;
+        let (message, envelope) = self.operator_mailbox.write().await.send(&identity, recipient, body).await?;
+        self.network.send_direct(recipient, &serde_json::to_vec(&envelope)?).await?;
+        Ok(message)
+    }
+
+    /// Accept a message or read receipt delivered by another operator
+    pub async fn receive_operator_envelope(&self, envelope: OperatorEnvelope) -> Result<Option<OperatorMessage>, BlockchainError> {
+        let identity = self.current_node_identity().await?;
+        Ok(self.operator_mailbox.write().await.receive(&identity, envelope).await?)
+    }
+
+    /// Accept an identity announcement delivered by a peer
+    ///
+    /// A known node reappearing under a new node ID must carry an attestation
+    /// by its previous identity; a missing or forged one counts against the peer.
+    pub async fn receive_identity_announcement(&self, peer: &libp2p::PeerId, payload: &[u8]) -> Result<PeerIdentityChange, BlockchainError> {
+        let announcement: IdentityAnnouncement = match serde_json::from_slice(payload) {
+            Ok(announcement) => announcement,
+            Err(e) => {
+                self.network.report_misbehavior(peer, Misbehavior::MalformedMessage).await;
+                return Err(e.into());
+            }
+        };
+        let change = self.identity.write().await.accept_announcement(&announcement).await;
+        if let Err(BlockchainError::Rotation(e)) = &change {
+            log::warn!("❌ Identity announcement from {} rejected: {}", peer, e);
+            self.network.report_misbehavior(peer, Misbehavior::InvalidSignature).await;
+        }
+        change
+    }
+
+    /// Mark a received operator message as read and send a receipt to its sender
+    pub async fn mark_operator_message_read(&self, message_id: &str) -> Result<(), BlockchainError> {
+        let identity = self.current_node_identity().await?;
+        let receipt = self.operator_mailbox.write().await.mark_read(&identity, message_id).await?;
+        self.network.send_direct(receipt.recipient(), &serde_json::to_vec(&receipt)?).await
+    }
+
+    /// Prove selected metadata attributes of this node without revealing the others
+    pub async fn prove_identity_attributes(&self, names: &[&str]) -> Result<DisclosureProof, BlockchainError> {
+        let identity = self.current_node_identity().await?;
+        Ok(AttributeCredential::from_identity(&identity)?.disclose(names)?)
+    }
+
+    /// Verify a peer's selective disclosure proof and return the disclosed attributes
+    ///
+    /// When the subject is a known operator contact, the proof must be signed
+    /// with that contact's identity key.
+    pub async fn verify_disclosure_proof(&self, proof: &DisclosureProof) -> Result<std::collections::BTreeMap<String, String>, BlockchainError> {
+        let subject = &proof.attributes.subject;
+        if let Some(contact) = self.operator_mailbox.read().await.contacts().iter().find(|c| &c.node_id == subject) {
+            if contact.ed25519_public != proof.attributes.ed25519_public {
+                return Err(DisclosureError::SubjectMismatch(subject.clone()).into());
+            }
+        }
+        Ok(proof.verify()?)
+    }
+
+    async fn current_node_identity(&self) -> Result<NodeIdentity, BlockchainError> {
+        self.identity.read().await.get_current_identity().await?
+            .ok_or_else(|| BlockchainError::Other("Node identity not initialized".to_string()))
+    }
+
+    /// Get the running node settings
+    pub async fn get_settings(&self) -> NodeSettings {
+        self.settings.read().await.clone()
+    }
+
+    /// Apply the hot-reloadable fields of `proposed`
+    ///
+    /// If any hot-reloadable field is invalid nothing is applied. Changes to
+    /// other fields are returned as needing a restart and left untouched.
+    pub async fn reload_settings(&self, proposed: NodeSettings) -> Result<ReloadReport, BlockchainError> {
+        let mut settings = self.settings.write().await;
+        let report = settings.diff(&proposed);
+        if report.applied.is_empty() {
+            return Ok(report);
+        }
+        proposed.validate_hot()?;
+
+        log::set_max_level(proposed.log_level_filter()?);
+        self.ingestion.set_config(proposed.ingestion.clone());
+        self.network.set_peer_scoring_config(proposed.peer_scoring.clone());
+        self.filters.write().await.set_max_subscriptions(proposed.max_filter_subscriptions);
+        self.validation.set_config(&proposed.validation);
+        self.security.set_fee_policy(proposed.fees.clone());
+        self.database.set_checksum_config(proposed.checksums.clone());
+        self.slo.set_config(proposed.slo.clone());
+        self.dag.write().await.set_limits(proposed.limits.clone());
+        self.dag.write().await.set_orphan_config(proposed.orphans.clone());
+        *settings = settings.with_hot_fields(&proposed);
+
+        log::info!(
+            "🔁 Reloaded {} setting(s), {} change(s) need a restart",
+            report.applied.len(),
+            report.restart_required.len()
+        );
+        Ok(report)
+    }
+
+    /// Accept a transaction into the intent log without waiting for validation
+    pub async fn submit_transaction_async(&self, transaction: Transaction) -> Result<IngestionTicket, BlockchainError> {
+        self.safe_mode.ensure_accepting().await?;
+        let ticket = self.ingestion.enqueue(transaction).await?;
+        log::debug!("📥 Transaction accepted into intent log with ticket {}", ticket.as_string());
+        Ok(ticket)
+    }
+
+    /// Validate and insert up to `max_batch` queued intents, returning how many were processed
+    pub async fn process_ingestion_queue(&self, max_batch: usize) -> usize {
+        // Queued intents wait out safe mode instead of being rejected
+        if self.safe_mode.is_halted() {
+            return 0;
+        }
+        let mut intents = Vec::new();
+        while intents.len() < max_batch {
+            let Some(intent) = self.ingestion.next_intent().await else {
+                break;
+            };
+            intents.push(intent);
+        }
+        let processed = intents.len();
+
+        // Signing takes the identity lock, so it stays sequential
+        let signature_policy = self.settings.read().await.signature_policy.clone();
+        let mut tickets = Vec::with_capacity(processed);
+        let mut signed = Vec::with_capacity(processed);
+        for (ticket, transaction) in intents {
+            match self.sign_transaction(transaction, None, &signature_policy).await {
+                Ok(transaction) => {
+                    tickets.push(ticket);
+                    signed.push(transaction);
+                }
+                Err(e) => {
+                    log::warn!("❌ Intent {} rejected: {}", ticket.as_string(), e);
+                    self.ingestion.complete(ticket, &Err(e)).await;
+                }
+            }
+        }
+
+        // Validate the batch concurrently, then insert in intent order
+        let validated = self.validation.validate_batch(&signed, &signature_policy).await;
+        for ((ticket, transaction), validation) in tickets.into_iter().zip(signed).zip(validated) {
+            let result = self.accept_transaction(transaction, validation, Deadline::never()).await;
+            if let Err(e) = &result {
+                log::warn!("❌ Intent {} rejected: {}", ticket.as_string(), e);
+            }
+            self.ingestion.complete(ticket, &result).await;
+        }
+
+        if processed > 0 {
+            self.ingestion.prune_expired().await;
+        }
+        processed
+    }
+
+    /// Get the status of an asynchronous submission
+    pub async fn get_ingestion_status(&self, ticket: &IngestionTicket) -> Option<IngestionStatus> {
+        self.ingestion.status(ticket).await
+    }
+
+    /// Subscribe to asynchronous submission updates
+    pub fn subscribe_ingestion(&self) -> tokio::sync::broadcast::Receiver<IngestionUpdate> {
+        self.ingestion.subscribe()
+    }
+
+    /// Get the ingestion queue
+    pub fn ingestion(&self) -> Arc<IngestionQueue> {
+        self.ingestion.clone()
+    }
+
+    /// Register a light client's address filter
+    pub async fn register_address_filter(&self, filter: BloomFilter) -> Result<FilterSubscriptionId, BlockchainError> {
+        self.filters.write().await.register(filter)
+    }
+
+    /// Remove a light client's address filter
+    pub async fn unregister_address_filter(&self, id: &FilterSubscriptionId) -> bool {
+        self.filters.write().await.unregister(id)
+    }
+
+    /// Get transactions matching a registered filter from `from_segment` onwards
+    pub async fn get_filtered_transactions(
+        &self,
+        id: &FilterSubscriptionId,
+        from_segment: u64,
+        max_headers: usize,
+    ) -> Result<FilteredSync, BlockchainError> {
+        let filters = self.filters.read().await;
+        let filter = filters.subscription(id)
+            .ok_or_else(|| BlockchainError::Core(CoreError::InvalidFilter("unknown filter subscription".to_string())))?
+            .clone();
+        let headers = filters.headers_from(from_segment, max_headers);
+        let next_segment = from_segment + headers.len() as u64;
+
+        // Transactions in the open segment are included once the client has caught up
+        let mut transaction_ids: Vec<TransactionId> = headers.iter()
+            .filter_map(|header| filters.segment(header.segment))
+            .flat_map(|segment| segment.transaction_ids.iter().cloned())
+            .collect();
+        if next_segment >= filters.sealed_segments() {
+            transaction_ids.extend(filters.open_transaction_ids());
+        }
+        drop(filters);
+
+        let dag = self.dag.read().await;
+        let mut transactions = Vec::new();
+        for tx_id in &transaction_ids {
+            if let Some(transaction) = dag.get_transaction(tx_id).await? {
+                if filter.matches_transaction(&transaction) {
+                    transactions.push(transaction);
+                }
+            }
+        }
+
+        Ok(FilteredSync {
+            transactions,
+            headers,
+            next_segment,
+        })
+    }
+
+    /// Get filter headers from `from_segment` onwards
+    pub async fn get_filter_headers(&self, from_segment: u64, count: usize) -> Vec<FilterHeader> {
+        self.filters.read().await.headers_from(from_segment, count)
+    }
+
+    /// Get the compact filter of a sealed segment
+    pub async fn get_filter_segment(&self, index: u64) -> Option<FilterSegment> {
+        self.filters.read().await.segment(index).cloned()
+    }
+
     /// Get transaction by ID
     pub async fn get_transaction(&self, tx_id: &TransactionId) -> Result<Option<Transaction>, BlockchainError> {
         let dag = self.dag.read().await;
         dag.get_transaction(tx_id).await
     }
 
+    /// Status and confidence of a transaction against the threshold for its amount
+    pub async fn get_confirmation_status(&self, tx_id: &TransactionId) -> Result<Option<ConfirmationStatus>, BlockchainError> {
+        self.dag.read().await.get_confirmation_status(tx_id).await
+    }
+
+    /// Receipt of a finalized transaction; `None` until it finalizes
+    pub async fn get_receipt(&self, tx_id: &TransactionId) -> Result<Option<TransactionReceipt>, BlockchainError> {
+        self.database.get_receipt(tx_id).await
+    }
+
+    /// Receipts of transactions that deployed or called a contract, newest first
+    pub async fn get_receipts_by_contract(&self, contract_id: &str, limit: usize) -> Result<Vec<TransactionReceipt>, BlockchainError> {
+        self.database.get_receipts_by_contract(contract_id, limit).await
+    }
+
+    /// Transactions whose metadata matches every term of `query`, newest first
+    pub async fn search_transactions(&self, query: &str, limit: usize) -> Result<Vec<Transaction>, BlockchainError> {
+        self.database.search_transactions(query, limit).await
+    }
+
+    /// Contract events matching `filter`, oldest first
+    pub async fn get_events(&self, filter: &EventFilter) -> Result<Vec<EventLog>, BlockchainError> {
+        self.database.get_events(filter).await
+    }
+
+    /// Proof that a transaction is an ancestor of a finalized checkpoint
+    ///
+    /// Anchored at `checkpoint` if given, otherwise at the nearest finalized
+    /// descendant.
+    pub async fn get_inclusion_proof(
+        &self,
+        tx_id: &TransactionId,
+        checkpoint: Option<&TransactionId>,
+    ) -> Result<InclusionProof, BlockchainError> {
+        Ok(self.dag.read().await.inclusion_proof(tx_id, checkpoint)?)
+    }
+
     /// Get blockchain status
     pub async fn get_status(&self) -> BlockchainStatus {
         let dag = self.dag.read().await;
@@ -204,9 +1174,259 @@ impl Blockchain {
             network_peers: self.network.peer_count(),
             consensus_height: self.consensus.current_height(),
             quantum_resistance_score: self.prime_layer.quantum_resistance_score(),
+            safe_mode: self.safe_mode.status().await,
         }
     }
 
+    /// Enter safe mode on a local admin command
+    pub async fn halt(&self, operator: &str, reason: &str) -> Result<SafeModeTransition, BlockchainError> {
+        Ok(self.safe_mode.halt(HaltSource::Admin { operator: operator.to_string() }, reason).await?)
+    }
+
+    /// Leave safe mode with a signed resume authorization
+    pub async fn resume(&self, authorization: &ResumeAuthorization) -> Result<SafeModeTransition, BlockchainError> {
+        Ok(self.safe_mode.resume(authorization).await?)
+    }
+
+    /// Allow a hex Ed25519 public key to sign resume authorizations
+    pub fn add_safe_mode_resume_key(&self, public_key: &str) -> Result<(), BlockchainError> {
+        Ok(self.safe_mode.add_resume_key(public_key)?)
+    }
+
+    /// Safe mode switch, shared with governance emergency actions
+    pub fn safe_mode(&self) -> Arc<SafeMode> {
+        self.safe_mode.clone()
+    }
+
+    /// Get every safe mode transition, oldest first
+    pub async fn get_safe_mode_transitions(&self) -> Vec<SafeModeTransition> {
+        self.safe_mode.transitions().await
+    }
+
+    /// Snapshot the finalized state, for comparison across upgrades
+    pub async fn capture_state_snapshot(&self) -> StateSnapshot {
+        let dag = self.dag.read().await;
+        StateSnapshot::from_transactions(self.consensus.current_height(), dag.get_confirmed_transactions())
+    }
+
+    /// Compare the current state against a snapshot taken earlier
+    pub async fn diff_state_snapshot(&self, before: &StateSnapshot, samples: usize) -> SnapshotDiff {
+        before.diff(&self.capture_state_snapshot().await, samples)
+    }
+
+    /// Record a state checkpoint now with the running checkpoint settings, even if periodic checkpoints are off
+    pub async fn take_state_checkpoint(&self) -> Result<StateCheckpoint, BlockchainError> {
+        let keep = self.settings.read().await.state_checkpoints.keep;
+        checkpoint_state(&self.dag, &self.database, self.consensus.current_height(), keep).await
+    }
+
+    /// Write the finalized state to a compressed bootstrap snapshot at `path`
+    ///
+    /// The balances and applied IDs come from a state checkpoint taken for
+    /// the export, so they match each other. See the `storage::bootstrap`
+    /// module for what is included.
+    pub async fn export_snapshot(&self, path: &str) -> Result<BootstrapInfo, BlockchainError> {
+        let checkpoint = self.take_state_checkpoint().await?;
+        let (checkpoint_roots, nodes) = {
+            let dag = self.dag.read().await;
+            (dag.checkpoint_roots(), dag.loaded_nodes().cloned().collect())
+        };
+        let snapshot = BootstrapSnapshot::new(
+            checkpoint.height,
+            checkpoint.genesis,
+            self.database.state_checkpoint_accounts(checkpoint.height).await?,
+            self.database.state_checkpoint_applied(checkpoint.height).await?,
+            checkpoint_roots,
+            nodes,
+        );
+        let size = snapshot.save(path).await?;
+        log::info!("📤 Exported snapshot {} ({} bytes) to {}", snapshot.state_root, size, path);
+        Ok(snapshot.info(path, size))
+    }
+
+    /// Bootstrap an empty node from a snapshot written by `export_snapshot`
+    ///
+    /// The snapshot is verified before anything is stored, and the DAG is
+    /// reloaded from storage afterwards.
+    pub async fn import_snapshot(&self, path: &str) -> Result<BootstrapInfo, BlockchainError> {
+        let snapshot = BootstrapSnapshot::load(path).await?;
+        let size = tokio::fs::metadata(path).await?.len();
+
+        let mut dag = self.dag.write().await;
+        self.database.import_bootstrap(&snapshot).await?;
+        dag.load_recent(&Self::paging_config(&self.config)).await?;
+        drop(dag);
+
+        // The imported state is this node's first checkpoint, served to the next peer to sync
+        let nodes: Vec<&DAGNode> = snapshot.nodes.iter().collect();
+        let keep = self.settings.read().await.state_checkpoints.keep;
+        self.database.record_state_checkpoint(snapshot.consensus_height, &snapshot.genesis, &finalized_tips(&nodes), keep).await?;
+        Ok(snapshot.info(path, size))
+    }
+
+    /// Rows quarantined after failing checksum verification, newest first
+    pub async fn get_corrupted_rows(&self, limit: usize) -> Result<Vec<CorruptedRow>, BlockchainError> {
+        self.database.get_corrupted_rows(limit).await
+    }
+
+    /// Fetch transactions lost to storage corruption from `source`, e.g. peers
+    pub fn set_transaction_source(&self, source: Arc<dyn TransactionSource>) {
+        self.database.set_transaction_source(source);
+    }
+
+    /// Rebuild derived tables from the transactions table in the background
+    ///
+    /// The node keeps running; `config.throttle_ms` spaces out the batches.
+    pub fn start_reindex(&self, config: ReindexConfig) -> Result<(), BlockchainError> {
+        let mut status = self.reindex.write().unwrap_or_else(|e| e.into_inner());
+        if status.running {
+            return Err(BlockchainError::Other("A reindex is already running".to_string()));
+        }
+        *status = ReindexStatus {
+            running: true,
+            last_report: status.last_report.take(),
+            ..ReindexStatus::default()
+        };
+        drop(status);
+
+        let database = self.database.clone();
+        let state = self.reindex.clone();
+        crate::spawn_instrumented(crate::Subsystem::Storage, "reindex", async move {
+            let result = database.reindex(&config, |progress| {
+                state.write().unwrap_or_else(|e| e.into_inner()).progress = Some(progress.clone());
+            }).await;
+
+            let mut status = state.write().unwrap_or_else(|e| e.into_inner());
+            status.running = false;
+            match result {
+                Ok(report) => status.last_report = Some(report),
+                Err(e) => {
+                    log::error!("❌ Reindex failed: {}", e);
+                    status.last_error = Some(e.to_string());
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Progress of the running reindex, or the outcome of the last one
+    pub fn get_reindex_status(&self) -> ReindexStatus {
+        self.reindex.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Finalized balance of a hex address
+    pub async fn get_balance(&self, address: &str) -> Result<Amount, BlockchainError> {
+        self.database.get_balance(&normalize_address(address)?).await
+    }
+
+    /// An atomic swap by its hashlock
+    pub async fn get_swap(&self, hashlock: &str) -> Result<AtomicSwap, BlockchainError> {
+        self.database.get_swap(&hashlock.to_lowercase()).await?
+            .ok_or_else(|| CoreError::SwapNotFound(hashlock.to_string()).into())
+    }
+
+    /// Atomic swaps a hex address is party to, newest first
+    pub async fn get_swaps(&self, address: &str) -> Result<Vec<AtomicSwap>, BlockchainError> {
+        self.database.get_swaps_for(&normalize_address(address)?).await
+    }
+
+    /// Transactions a hex address sent or received, newest first, in pages of `limit` from page zero
+    pub async fn get_transactions_by_address(&self, address: &str, page: usize, limit: usize) -> Result<Vec<Transaction>, BlockchainError> {
+        let address = hex::decode(address.trim()).map_err(|_| CoreError::InvalidAddress(address.to_string()))?;
+        self.dag.read().await.get_transactions_by_address(&address, page, limit).await
+    }
+
+    /// Up to `limit` transactions a hex address sent or received, newest first, starting after `before`
+    pub async fn get_address_history(&self, address: &str, before: Option<&TransactionCursor>, limit: usize) -> Result<Vec<Transaction>, BlockchainError> {
+        let address = hex::decode(address.trim()).map_err(|_| CoreError::InvalidAddress(address.to_string()))?;
+        self.database.get_address_history(&address, before, limit).await
+    }
+
+    /// An issued token by the ID of the transaction that issued it
+    pub async fn get_token(&self, token_id: &str) -> Result<Token, BlockchainError> {
+        self.database.get_token(token_id).await?
+            .ok_or_else(|| CoreError::TokenNotFound(token_id.to_string()).into())
+    }
+
+    /// Finalized token balance of a hex address
+    pub async fn get_token_balance(&self, token_id: &str, address: &str) -> Result<Amount, BlockchainError> {
+        self.get_token(token_id).await?;
+        self.database.get_token_balance(token_id, &normalize_address(address)?).await
+    }
+
+    /// Finalized balance of a hex address and the amount its pending transfers hold
+    pub async fn get_account_balance(&self, address: &str) -> Result<AccountBalance, BlockchainError> {
+        let address = normalize_address(address)?;
+        Ok(AccountBalance {
+            balance: self.database.get_balance(&address).await?,
+            reserved: self.accounts.reserved(&address),
+            address,
+        })
+    }
+
+    /// Attest to the trust anchor clients were given for this network
+    ///
+    /// Without one, the node attests to an epoch-0 anchor of its current
+    /// validator set.
+    pub async fn set_trust_anchor(&self, anchor: TrustAnchor) {
+        log::info!("⚓ Attesting to trust anchor for epoch {}", anchor.epoch);
+        *self.trust_anchor.write().await = Some(anchor);
+    }
+
+    /// Sign the node's version, genesis and validator set over a client challenge
+    pub async fn attest(&self, challenge: &str) -> Result<NodeAttestation, BlockchainError> {
+        let genesis_hash = self.dag.read().await.genesis_id()
+            .map(|id| id.as_string())
+            .ok_or_else(|| BlockchainError::Other("DAG has no genesis transaction".to_string()))?;
+        let validator_set_hash = match self.trust_anchor.read().await.as_ref() {
+            Some(anchor) => anchor.validator_set_hash(),
+            None => self.consensus.export_trust_anchor(0, None).validator_set_hash(),
+        };
+
+        let identity = self.identity.read().await;
+        NodeAttestation::sign(
+            &identity,
+            env!("CARGO_PKG_VERSION"),
+            &genesis_hash,
+            &hex::encode(validator_set_hash),
+            challenge,
+        ).await
+    }
+
+    /// Serve governance data, such as participation credentials, from `governance`
+    pub async fn set_governance(&self, governance: Arc<GovernanceService>) {
+        *self.governance.write().await = Some(governance);
+    }
+
+    async fn governance(&self) -> Result<Arc<GovernanceService>, BlockchainError> {
+        self.governance.read().await.clone()
+            .ok_or_else(|| BlockchainError::Other("Governance is not enabled on this node".to_string()))
+    }
+
+    /// Get a participation credential by ID
+    pub async fn get_participation_credential(&self, credential_id: &str) -> Result<Option<ParticipationCredential>, BlockchainError> {
+        Ok(self.governance().await?.get_credential(credential_id).await)
+    }
+
+    /// List governance proposals, newest first
+    pub async fn list_proposals(&self) -> Result<Vec<governance::proposals::Proposal>, BlockchainError> {
+        let mut proposals = self.governance().await?.list_proposals().await;
+        proposals.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(proposals)
+    }
+
+    /// Get the consensus validator set, ordered by ID
+    pub fn get_validators(&self) -> Vec<PrimeValidator> {
+        let mut validators: Vec<PrimeValidator> = self.consensus.get_validators().values().cloned().collect();
+        validators.sort_by(|a, b| a.id.cmp(&b.id));
+        validators
+    }
+
+    /// Get the participation credentials issued to a voter
+    pub async fn get_voter_credentials(&self, voter: &str) -> Result<Vec<ParticipationCredential>, BlockchainError> {
+        Ok(self.governance().await?.get_voter_credentials(voter).await)
+    }
+
     /// Get node identity information
     pub async fn get_identity_info(&self) -> Result<IdentityInfo, BlockchainError> {
         let identity = self.identity.read().await;
@@ -217,6 +1437,7 @@ impl Blockchain {
     pub async fn get_metrics(&self) -> Result<String, BlockchainError> {
         // Update metrics from blockchain state
         self.metrics.update_from_blockchain(&self.dag).await;
+        self.metrics.record_slo_report(&self.slo.report());
         
         // Get metrics in Prometheus format
         self.metrics.get_metrics().map_err(|e| BlockchainError::Other(e.to_string()))
@@ -235,10 +1456,12 @@ impl Blockchain {
             sender: vec![1u8; 32],
             receiver: vec![2u8; 32],
             amount: 100,
+            fee: 0,
             nonce: 1,
             timestamp: chrono::Utc::now().timestamp() as u64,
             parents: vec![],
             signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
             quantum_proof: crate::core::QuantumProof {
                 prime_hash: vec![0u8; 32],
                 resistance_score: 80,
@@ -361,28 +1584,213 @@ impl Blockchain {
         dag.get_dag_stats()
     }
 
+    /// Serve analytical queries from a read-only replica
+    ///
+    /// The replica's lag is sampled every `refresh_interval_secs`; while its
+    /// staleness bound is above `max_staleness_secs` queries use the primary.
+    pub async fn attach_read_replica(&self, mut config: ReadReplicaConfig) -> Result<Staleness, BlockchainError> {
+        // Replicas copy the primary, so they share its key unless given one
+        if config.encryption_key.is_none() {
+            config.encryption_key = self.database.encryption_key().map(str::to_string);
+        }
+        let replica = Arc::new(ReadReplica::open(config).await?);
+        let staleness = replica.refresh_lag(&self.database).await?;
+        *self.read_replica.write().await = Some(replica.clone());
+
+        let primary = self.database.clone();
+        let interval = std::time::Duration::from_secs(replica.config().refresh_interval_secs.max(1));
+        spawn_instrumented(Subsystem::Storage, "replica-lag", async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = replica.refresh_lag(&primary).await {
+                    log::warn!("⚠️ Failed to sample read replica lag: {}", e);
+                }
+            }
+        });
+
+        Ok(staleness)
+    }
+
+    /// Staleness of the attached read replica, if any
+    pub async fn read_replica_staleness(&self) -> Option<Staleness> {
+        self.read_replica.read().await.as_ref().map(|replica| replica.staleness())
+    }
+
+    /// Database for analytical queries: the replica when fresh enough, else the primary
+    async fn analytics_database(&self) -> (Arc<DatabaseManager>, Staleness) {
+        if let Some(replica) = self.read_replica.read().await.as_ref() {
+            if replica.is_fresh() {
+                return (replica.database(), replica.staleness());
+            }
+            log::debug!("📖 Read replica too stale, serving analytical query from primary");
+        }
+        (self.database.clone(), Staleness::primary())
+    }
+
+    /// List stored transactions for explorer views
+    pub async fn explore_transactions(
+        &self,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        status: Option<&str>,
+    ) -> Result<(Vec<Transaction>, Staleness), BlockchainError> {
+        let (database, staleness) = self.analytics_database().await;
+        Ok((database.get_transactions(limit, offset, status).await?, staleness))
+    }
+
+    /// Database statistics for explorer views
+    pub async fn explore_stats(&self) -> Result<(DatabaseStats, Staleness), BlockchainError> {
+        let (database, staleness) = self.analytics_database().await;
+        Ok((database.get_stats().await?, staleness))
+    }
+
+    /// Enable the faucet; fails unless this node runs on devnet or testnet
+    pub async fn enable_faucet(&self, config: FaucetConfig) -> Result<Arc<Faucet>, BlockchainError> {
+        let faucet = Arc::new(Faucet::new(config, self.config.network.network_type)?);
+        // Test networks mint the faucet account's starting funds so its grants pass balance checks
+        let account = account_address(faucet.account());
+        let balance = self.database.get_balance(&account).await?;
+        self.database.credit_account(&account, faucet.config().initial_balance.saturating_sub(balance)).await?;
+        *self.faucet.write().await = Some(faucet.clone());
+        log::info!("🚰 Faucet enabled on {}", self.config.network.network_type);
+        Ok(faucet)
+    }
+
+    async fn faucet(&self) -> Result<Arc<Faucet>, BlockchainError> {
+        self.faucet.read().await.clone().ok_or_else(|| FaucetError::NotEnabled.into())
+    }
+
+    /// Pay test funds to the requested address from the faucet account
+    pub async fn request_faucet_funds(&self, request: FaucetRequest) -> Result<FaucetGrant, BlockchainError> {
+        let faucet = self.faucet().await?;
+        let reservation = faucet.reserve(&request).await?;
+
+        let submitted = match faucet.grant_transaction(&reservation) {
+            Ok(mut transaction) => {
+                transaction.fee = self.estimate_fee(&transaction).minimum;
+                self.submit_transaction(transaction).await
+            }
+            Err(e) => Err(e.into()),
+        };
+        match submitted {
+            Ok(tx_id) => Ok(faucet.complete(reservation, tx_id)),
+            Err(e) => {
+                faucet.release(&reservation);
+                Err(e)
+            }
+        }
+    }
+
+    /// Add funds to the faucet balance, returning the new balance
+    pub async fn top_up_faucet(&self, amount: Amount) -> Result<Amount, BlockchainError> {
+        let faucet = self.faucet().await?;
+        self.database.credit_account(&account_address(faucet.account()), amount).await?;
+        Ok(faucet.top_up(amount))
+    }
+
+    /// Get faucet statistics
+    pub async fn get_faucet_stats(&self) -> Result<FaucetStats, BlockchainError> {
+        Ok(self.faucet().await?.stats())
+    }
+
+    /// Archive finalized milestone ranges to `backend`
+    pub async fn enable_archival(&self, config: ArchivalConfig, backend: Arc<dyn ColdStorage>) -> Arc<Archiver> {
+        let archiver = Arc::new(Archiver::new(config, backend));
+        *self.archiver.write().await = Some(archiver.clone());
+        log::info!("🧊 Archival enabled to {} storage", archiver.backend_name());
+        archiver
+    }
+
+    async fn archiver(&self) -> Result<Arc<Archiver>, BlockchainError> {
+        self.archiver.read().await.clone().ok_or_else(|| ArchivalError::NotEnabled.into())
+    }
+
+    /// Upload every complete milestone range not yet archived and anchor it on chain
+    ///
+    /// Bundles whose anchor could not be submitted on an earlier run are
+    /// anchored again. Returns the bundles uploaded by this run.
+    pub async fn archive_milestones(&self) -> Result<Vec<ArchiveRecord>, BlockchainError> {
+        let archiver = self.archiver().await?;
+        let mut archived = Vec::new();
+        while let Some(record) = archiver.archive_next(&self.database).await? {
+            archived.push(record);
+        }
+        for record in self.database.get_archives().await? {
+            if record.anchor_tx.is_some() {
+                continue;
+            }
+            match self.anchor_archive(&record).await {
+                Ok(tx_id) => self.database.set_archive_anchor_tx(&record.content_id, &tx_id).await?,
+                Err(e) => log::warn!("⚠️ Archive {} not anchored yet: {}", record.content_id, e),
+            }
+        }
+        Ok(archived)
+    }
+
+    /// Submit a transaction from this node recording an archive on chain
+    async fn anchor_archive(&self, record: &ArchiveRecord) -> Result<TransactionId, BlockchainError> {
+        let identity = self.identity.read().await.get_current_identity().await?
+            .ok_or_else(|| BlockchainError::Other("Node identity is not initialized".to_string()))?;
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut transaction = Transaction {
+            id: TransactionId::new(),
+            sender: identity.ed25519_public.clone(),
+            receiver: identity.ed25519_public,
+            amount: 0,
+            fee: 0,
+            nonce: rand::random(),
+            timestamp: now,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: crate::core::QuantumProof {
+                prime_hash: vec![0u8; 32],
+                resistance_score: 80,
+                proof_timestamp: now,
+            },
+            metadata: None,
+        };
+        TransactionPayload::ArchiveAnchor {
+            content_id: record.content_id.clone(),
+            from_height: record.from_height,
+            to_height: record.to_height,
+            manifest_root: record.manifest_root.clone(),
+        }.attach(&mut transaction)?;
+        transaction.fee = self.estimate_fee(&transaction).minimum;
+        self.submit_transaction(transaction).await
+    }
+
+    /// Bundles this node uploaded
+    pub async fn get_archives(&self) -> Result<Vec<ArchiveRecord>, BlockchainError> {
+        self.database.get_archives().await
+    }
+
+    /// Fetch an archived bundle, verified against its content ID and on-chain anchor
+    pub async fn retrieve_archive(&self, content_id: &str) -> Result<ArchiveBundle, BlockchainError> {
+        self.archiver().await?.retrieve(&self.database, content_id).await
+    }
+
     /// Get storage size
     pub async fn get_storage_size(&self) -> Result<u64, BlockchainError> {
         let dag = self.dag.read().await;
         dag.get_storage_size()
     }
 
-    /// Rotate node identity
-    pub async fn rotate_identity(&self) -> Result<IdentityInfo, BlockchainError> {
-        // Note: This requires mutable access to the identity manager
-        // In a real implementation, you'd use a message queue or similar pattern
+    /// Rotate node identity and announce the new one to peers
+    ///
+    /// The previous identity's keys stay valid for the node's
+    /// `identity_rotation_grace_secs`. Signing waits for the rotation to finish.
+    pub async fn rotate_identity(&self) -> Result<IdentityRotation, BlockchainError> {
         log::info!("🔄 Identity rotation requested");
-        
-        // For now, return current identity info with rotation metadata
-        let identity = self.identity.read().await;
-        let mut identity_info = identity.get_identity_info().await?;
-        
-        // Add rotation metadata
-        identity_info.metadata.insert("rotation_requested".to_string(), chrono::Utc::now().to_rfc3339());
-        identity_info.metadata.insert("rotation_status".to_string(), "pending".to_string());
-        
-        log::info!("✅ Identity rotation request queued");
-        Ok(identity_info)
+        let grace_secs = self.settings.read().await.identity_rotation_grace_secs;
+        let rotation = self.identity.write().await.rotate_identity(grace_secs).await?;
+
+        // The rotation stands even if peers cannot be told now
+        if let Err(e) = self.network.announce_identity(&rotation.announcement()).await {
+            log::warn!("⚠️ Could not announce identity {} to peers: {}", rotation.identity.node_id, e);
+        }
+        Ok(rotation)
     }
 
     /// Get identity rotation history
@@ -414,6 +1822,7 @@ pub struct BlockchainStatus {
     pub network_peers: u32,
     pub consensus_height: u64,
     pub quantum_resistance_score: f64,
+    pub safe_mode: SafeModeStatus,
 }
 
 /// Blockchain error types
@@ -429,10 +1838,34 @@ pub enum BlockchainError {
     Security(#[from] SecurityError),
     #[error("Math error: {0}")]
     Math(#[from] MathError),
+    #[error("Config error: {0}")]
+    Config(#[from] ConfigError),
+    #[error("Messaging error: {0}")]
+    Messaging(#[from] MessagingError),
+    #[error("Disclosure error: {0}")]
+    Disclosure(#[from] DisclosureError),
+    #[error("Identity recovery error: {0}")]
+    Recovery(#[from] RecoveryError),
+    #[error("Identity file error: {0}")]
+    IdentityFile(#[from] crate::identity::IdentityFileError),
+    #[error("Peer session error: {0}")]
+    Session(#[from] crate::identity::SessionError),
+    #[error("Threshold signing error: {0}")]
+    Threshold(#[from] crate::identity::ThresholdError),
+    #[error("Identity rotation error: {0}")]
+    Rotation(#[from] crate::identity::RotationError),
+    #[error("Faucet error: {0}")]
+    Faucet(#[from] FaucetError),
+    #[error("Archival error: {0}")]
+    Archival(#[from] ArchivalError),
+    #[error("Safe mode error: {0}")]
+    SafeMode(#[from] SafeModeError),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("Transaction {0} was pruned; only its header is kept")]
+    Pruned(TransactionId),
     #[error("Other error: {0}")]
     Other(String),
 }
@@ -447,8 +1880,49 @@ impl From<anyhow::Error> for BlockchainError {
 pub mod config {
     use super::*;
 
+    /// Network a node belongs to
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum NetworkType {
+        #[default]
+        Mainnet,
+        Testnet,
+        Devnet,
+    }
+
+    impl NetworkType {
+        /// Whether this is a test network where tokens have no value
+        pub fn is_test_network(&self) -> bool {
+            matches!(self, NetworkType::Testnet | NetworkType::Devnet)
+        }
+    }
+
+    impl std::fmt::Display for NetworkType {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                NetworkType::Mainnet => write!(f, "mainnet"),
+                NetworkType::Testnet => write!(f, "testnet"),
+                NetworkType::Devnet => write!(f, "devnet"),
+            }
+        }
+    }
+
+    impl std::str::FromStr for NetworkType {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_lowercase().as_str() {
+                "mainnet" => Ok(NetworkType::Mainnet),
+                "testnet" => Ok(NetworkType::Testnet),
+                "devnet" => Ok(NetworkType::Devnet),
+                other => Err(format!("unknown network type: {}", other)),
+            }
+        }
+    }
+
     #[derive(Debug, Clone)]
     pub struct NetworkConfig {
+        pub network_type: NetworkType,
         pub listen_addr: String,
         pub bootstrap_nodes: Vec<String>,
         pub max_peers: u32,
@@ -461,6 +1935,8 @@ pub mod config {
         pub prime_modulus: u64,
         pub finality_threshold: f64,
         pub fork_resolution_enabled: bool,
+        pub confirmation: crate::consensus::ConfirmationMode,
+        pub confirmation_tiers: Vec<crate::consensus::ThresholdTier>,
     }
 
     #[derive(Debug, Clone)]
@@ -468,12 +1944,26 @@ pub mod config {
         pub quantum_resistance_level: u32,
         pub signature_scheme: String,
         pub key_rotation_interval_hours: u64,
+        /// SQLCipher key of the database; `QDAG_DATABASE_KEY` is used when unset
+        pub database_key: Option<String>,
+        /// Passphrase of the identity file; `QDAG_IDENTITY_PASSPHRASE` is used when plaintext
+        pub identity_key: crate::identity::IdentityKeySource,
     }
 
     #[derive(Debug, Clone)]
     pub struct DatabaseConfig {
         pub path: String,
         pub cache_size_mb: u64,
+        /// Where transactions and DAG nodes are kept
+        pub backend: crate::storage::StorageBackendKind,
+        /// Bucket every backup is also uploaded to
+        pub remote_backup: Option<crate::storage::S3BackupConfig>,
+        /// Journal mode, durability and caching of the SQLite connections
+        pub pragmas: crate::storage::SqlitePragmas,
+        /// Whether old transaction bodies are kept
+        pub mode: crate::storage::StorageMode,
+        /// Whether transaction metadata is indexed for search
+        pub metadata_index: bool,
     }
 }
 
@@ -487,6 +1977,7 @@ mod tests {
     async fn test_blockchain_creation() {
         let config = BlockchainConfig {
             network: NetworkConfig {
+                network_type: NetworkType::Devnet,
                 listen_addr: "/ip4/127.0.0.1/tcp/0".to_string(),
                 bootstrap_nodes: vec![],
                 max_peers: 10,
@@ -497,15 +1988,24 @@ mod tests {
                 prime_modulus: 2147483647, // Large prime
                 finality_threshold: 0.8,
                 fork_resolution_enabled: true,
+                confirmation: ConfirmationMode::Local,
+                confirmation_tiers: vec![],
             },
             security: SecurityConfig {
                 quantum_resistance_level: 128,
                 signature_scheme: "dilithium".to_string(),
                 key_rotation_interval_hours: 24,
+                database_key: None,
+                identity_key: Default::default(),
             },
             database: DatabaseConfig {
                 path: "./test_db".to_string(),
                 cache_size_mb: 1024,
+                backend: Default::default(),
+                remote_backup: None,
+                pragmas: Default::default(),
+                mode: Default::default(),
+                metadata_index: false,
             },
         };
 
@@ -1,6 +1,7 @@
 //! Mathematical foundation for quantum-proof blockchain
 
 use crate::{BlockchainError, TransactionId};
+use crate::core::Amount;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -363,7 +364,7 @@ pub struct ValidatorInfo {
     pub public_key: Vec<u8>,
     pub weight: u64,
     pub prime_base: u64,
-    pub stake_amount: u64,
+    pub stake_amount: Amount,
 }
 
 /// Mathematical error types
@@ -1,9 +1,20 @@
+pub mod profiling;
+pub mod slo;
+pub use profiling::{
+    cpu_profile, heap_stats, init_console, spawn_instrumented, task_stats, HeapStats, Subsystem,
+    SubsystemTaskStats, TrackingAllocator, DEFAULT_PROFILE_FREQUENCY, MAX_CPU_PROFILE_SECONDS,
+};
+pub use slo::{SloConfig, SloMonitor, SloObjective, SloReport, SloStatus, SloTarget};
+
 use prometheus::{
-    Counter, Gauge, Histogram, HistogramOpts, HistogramVec, Opts, Registry, TextEncoder, Encoder,
+    Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, Opts, Registry, TextEncoder, Encoder,
 };
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use crate::{Blockchain, Transaction, DAGNode, core::DAGCore};
+use crate::{Blockchain, Transaction, DAGNode, core::{DAGCore, EvictionReason, NodeStatus, SafeModeAction, SubmitStage}};
+use crate::events::{EventHandler, NodeEvent};
+use crate::network::{CapabilityDistribution, SpamReason};
+use crate::storage::{CorruptionResolution, StorageMetrics};
 use std::time::{Duration, Instant};
 
 /// Blockchain metrics collector
@@ -16,34 +27,48 @@ pub struct BlockchainMetrics {
     transactions_pending: Gauge,
     transactions_confirmed: Gauge,
     transaction_latency: Histogram,
+    submit_deadline_exceeded: CounterVec,
+    transactions_evicted: CounterVec,
     
     // DAG metrics
     dag_nodes_total: Gauge,
     dag_depth: Gauge,
     dag_width: Gauge,
     dag_forks_detected: Counter,
+    dag_reorgs: Counter,
+    dag_demoted_transactions: Counter,
     
     // Consensus metrics
     consensus_rounds_total: Counter,
     consensus_success_rate: Gauge,
     validator_score: GaugeVec,
     finality_time: Histogram,
+    finalized_height: Gauge,
+    slo_compliance: GaugeVec,
+    slo_met: GaugeVec,
+    slo_breaches: CounterVec,
     
     // Node metrics
     node_uptime: Gauge,
     memory_usage: Gauge,
     cpu_usage: Gauge,
     network_connections: Gauge,
+    safe_mode_active: Gauge,
+    peer_capabilities: GaugeVec,
     
     // Identity metrics
     identity_rotations: Counter,
     signature_verifications: Counter,
     signature_failures: Counter,
     
+    // Gossip intake metrics
+    gossip_filter_verdicts: CounterVec,
+    
     // Storage metrics
     storage_size: Gauge,
     storage_operations: Counter,
     storage_errors: Counter,
+    storage_corrupted_rows: CounterVec,
     
     start_time: Instant,
 }
@@ -78,6 +103,18 @@ impl BlockchainMetrics {
         ))?;
         registry.register(Box::new(transaction_latency.clone()))?;
         
+        let submit_deadline_exceeded = CounterVec::new(Opts::new(
+            "dag_submit_deadline_exceeded_total",
+            "Submissions that ran out of time, by stage"
+        ), &["stage"])?;
+        registry.register(Box::new(submit_deadline_exceeded.clone()))?;
+        
+        let transactions_evicted = CounterVec::new(Opts::new(
+            "dag_transactions_evicted_total",
+            "Pending transactions evicted unapproved, by reason"
+        ), &["reason"])?;
+        registry.register(Box::new(transactions_evicted.clone()))?;
+        
         // DAG metrics
         let dag_nodes_total = Gauge::with_opts(Opts::new(
             "dag_nodes_total",
@@ -103,6 +140,18 @@ impl BlockchainMetrics {
         ))?;
         registry.register(Box::new(dag_forks_detected.clone()))?;
         
+        let dag_reorgs = Counter::with_opts(Opts::new(
+            "dag_reorgs_total",
+            "Total number of losing branches rolled back"
+        ))?;
+        registry.register(Box::new(dag_reorgs.clone()))?;
+        
+        let dag_demoted_transactions = Counter::with_opts(Opts::new(
+            "dag_demoted_transactions_total",
+            "Total number of confirmed transactions rejected by a rollback"
+        ))?;
+        registry.register(Box::new(dag_demoted_transactions.clone()))?;
+        
         // Consensus metrics
         let consensus_rounds_total = Counter::with_opts(Opts::new(
             "dag_consensus_rounds_total",
@@ -128,6 +177,30 @@ impl BlockchainMetrics {
         ))?;
         registry.register(Box::new(finality_time.clone()))?;
         
+        let finalized_height = Gauge::with_opts(Opts::new(
+            "dag_finalized_height",
+            "Latest consensus round whose attestations finalized transactions"
+        ))?;
+        registry.register(Box::new(finalized_height.clone()))?;
+        
+        let slo_compliance = GaugeVec::new(Opts::new(
+            "dag_slo_measured",
+            "Measured value of each consensus SLO over its window"
+        ), &["slo"])?;
+        registry.register(Box::new(slo_compliance.clone()))?;
+        
+        let slo_met = GaugeVec::new(Opts::new(
+            "dag_slo_met",
+            "Whether each consensus SLO is currently met (0/1)"
+        ), &["slo"])?;
+        registry.register(Box::new(slo_met.clone()))?;
+        
+        let slo_breaches = CounterVec::new(Opts::new(
+            "dag_slo_breaches_total",
+            "Consensus SLO breaches, by objective"
+        ), &["slo"])?;
+        registry.register(Box::new(slo_breaches.clone()))?;
+        
         // Node metrics
         let node_uptime = Gauge::with_opts(Opts::new(
             "dag_node_uptime_seconds",
@@ -153,6 +226,12 @@ impl BlockchainMetrics {
         ))?;
         registry.register(Box::new(network_connections.clone()))?;
         
+        let safe_mode_active = Gauge::with_opts(Opts::new(
+            "dag_safe_mode_active",
+            "Whether the node is in safe mode and refusing new transactions (0/1)"
+        ))?;
+        registry.register(Box::new(safe_mode_active.clone()))?;
+        
         // Identity metrics
         let identity_rotations = Counter::with_opts(Opts::new(
             "dag_identity_rotations_total",
@@ -172,6 +251,19 @@ impl BlockchainMetrics {
         ))?;
         registry.register(Box::new(signature_failures.clone()))?;
         
+        // Gossip intake metrics
+        let gossip_filter_verdicts = CounterVec::new(Opts::new(
+            "dag_gossip_filter_total",
+            "Gossiped transactions screened before verification, by verdict"
+        ), &["verdict"])?;
+        registry.register(Box::new(gossip_filter_verdicts.clone()))?;
+        
+        let peer_capabilities = GaugeVec::new(Opts::new(
+            "dag_peer_capabilities",
+            "Connected peers by negotiated protocol capability"
+        ), &["capability", "value"])?;
+        registry.register(Box::new(peer_capabilities.clone()))?;
+        
         // Storage metrics
         let storage_size = Gauge::with_opts(Opts::new(
             "dag_storage_size_bytes",
@@ -191,30 +283,48 @@ impl BlockchainMetrics {
         ))?;
         registry.register(Box::new(storage_errors.clone()))?;
         
+        let storage_corrupted_rows = CounterVec::new(Opts::new(
+            "dag_storage_corrupted_rows_total",
+            "Stored rows that failed checksum verification, by table and resolution"
+        ), &["table", "resolution"])?;
+        registry.register(Box::new(storage_corrupted_rows.clone()))?;
+        
         Ok(Self {
             registry,
             transactions_total,
             transactions_pending,
             transactions_confirmed,
             transaction_latency,
+            submit_deadline_exceeded,
+            transactions_evicted,
             dag_nodes_total,
             dag_depth,
             dag_width,
             dag_forks_detected,
+            dag_reorgs,
+            dag_demoted_transactions,
             consensus_rounds_total,
             consensus_success_rate,
             validator_score,
             finality_time,
+            finalized_height,
+            slo_compliance,
+            slo_met,
+            slo_breaches,
             node_uptime,
             memory_usage,
             cpu_usage,
             network_connections,
+            safe_mode_active,
+            peer_capabilities,
             identity_rotations,
             signature_verifications,
             signature_failures,
+            gossip_filter_verdicts,
             storage_size,
             storage_operations,
             storage_errors,
+            storage_corrupted_rows,
             start_time: Instant::now(),
         })
     }
@@ -260,11 +370,27 @@ impl BlockchainMetrics {
         self.transaction_latency.observe(latency_seconds);
     }
     
+    /// Record a submission whose deadline passed during `stage`
+    pub fn record_deadline_exceeded(&self, stage: SubmitStage) {
+        self.submit_deadline_exceeded.with_label_values(&[stage.label()]).inc();
+    }
+    
+    /// Record a pending transaction evicted unapproved
+    pub fn record_transaction_eviction(&self, reason: EvictionReason) {
+        self.transactions_evicted.with_label_values(&[reason.as_str()]).inc();
+    }
+    
     /// Record a fork detection
     pub fn record_fork_detection(&self) {
         self.dag_forks_detected.inc();
     }
     
+    /// Record a rolled-back branch and the confirmed transactions it demoted
+    pub fn record_reorg(&self, demoted: usize) {
+        self.dag_reorgs.inc();
+        self.dag_demoted_transactions.inc_by(demoted as f64);
+    }
+    
     /// Record consensus round completion
     pub fn record_consensus_round(&self, success: bool) {
         self.consensus_rounds_total.inc();
@@ -276,6 +402,16 @@ impl BlockchainMetrics {
         self.finality_time.observe(time_seconds);
     }
     
+    /// Replace the per-objective SLO gauges with `report`
+    pub fn record_slo_report(&self, report: &SloReport) {
+        self.slo_compliance.reset();
+        self.slo_met.reset();
+        for status in &report.statuses {
+            self.slo_compliance.with_label_values(&[&status.name]).set(status.measured);
+            self.slo_met.with_label_values(&[&status.name]).set(if status.met { 1.0 } else { 0.0 });
+        }
+    }
+    
     /// Update validator score
     pub fn update_validator_score(&self, validator_id: &str, score: f64) {
         self.validator_score.with_label_values(&[validator_id]).set(score);
@@ -286,6 +422,16 @@ impl BlockchainMetrics {
         self.identity_rotations.inc();
     }
     
+    /// Record a row that failed checksum verification
+    pub fn record_storage_corruption(&self, table: &str, resolution: CorruptionResolution) {
+        self.storage_corrupted_rows.with_label_values(&[table, resolution.as_str()]).inc();
+    }
+    
+    /// Record whether the node is in safe mode
+    pub fn record_safe_mode(&self, active: bool) {
+        self.safe_mode_active.set(if active { 1.0 } else { 0.0 });
+    }
+    
     /// Record signature verification
     pub fn record_signature_verification(&self, success: bool) {
         self.signature_verifications.inc();
@@ -294,6 +440,36 @@ impl BlockchainMetrics {
         }
     }
     
+    /// Record the spam filter verdict on a gossiped transaction
+    pub fn record_gossip_verdict(&self, dropped: Option<SpamReason>) {
+        let verdict = dropped.map(|reason| reason.label()).unwrap_or("passed");
+        self.gossip_filter_verdicts.with_label_values(&[verdict]).inc();
+    }
+    
+    /// Replace the negotiated capability counts of connected peers
+    pub fn record_peer_capabilities(&self, distribution: &CapabilityDistribution) {
+        self.peer_capabilities.reset();
+        let mut set = |capability: &str, value: String, peers: usize| {
+            self.peer_capabilities.with_label_values(&[capability, &value]).set(peers as f64);
+        };
+        for (version, peers) in &distribution.protocol_versions {
+            set("protocol_version", version.to_string(), *peers);
+        }
+        for (format, peers) in &distribution.wire_formats {
+            set("wire_format", format!("{:?}", format).to_lowercase(), *peers);
+        }
+        for (compression, peers) in &distribution.compression {
+            set("compression", format!("{:?}", compression).to_lowercase(), *peers);
+        }
+        for (protocol, peers) in &distribution.sync_protocols {
+            set("sync_protocol", format!("{:?}", protocol).to_lowercase(), *peers);
+        }
+        for (feature, peers) in &distribution.consensus_features {
+            set("consensus_feature", feature.clone(), *peers);
+        }
+        set("degraded", "true".to_string(), distribution.degraded_peers);
+    }
+    
     /// Record storage operation
     pub fn record_storage_operation(&self, success: bool) {
         self.storage_operations.inc();
@@ -302,6 +478,11 @@ impl BlockchainMetrics {
         }
     }
     
+    /// Export the latency, commit and queue metrics of the node's database
+    pub fn register_storage(&self, storage: &StorageMetrics) -> Result<(), prometheus::Error> {
+        storage.register(&self.registry)
+    }
+    
     /// Get metrics in Prometheus format
     pub fn get_metrics(&self) -> Result<String, prometheus::Error> {
         let encoder = TextEncoder::new();
@@ -315,8 +496,42 @@ impl BlockchainMetrics {
     }
 }
 
+#[async_trait::async_trait]
+impl EventHandler for BlockchainMetrics {
+    fn name(&self) -> String {
+        "metrics".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        match event {
+            NodeEvent::TxAccepted { .. } => self.record_transaction(),
+            NodeEvent::StatusChanged { status: NodeStatus::Confirmed, submitted_at, .. } => {
+                let now = chrono::Utc::now().timestamp() as u64;
+                self.record_transaction_confirmation(now.saturating_sub(*submitted_at) as f64);
+            }
+            NodeEvent::StatusChanged { .. } => {}
+            NodeEvent::TipSetChanged { tip_count, .. } => self.dag_width.set(*tip_count as f64),
+            NodeEvent::RoundFinalized { duration_ms, .. } => {
+                self.record_consensus_round(true);
+                self.record_finality_time(*duration_ms as f64 / 1000.0);
+            }
+            NodeEvent::IdentityRotated { .. } => self.record_identity_rotation(),
+            NodeEvent::SafeModeChanged { transition } => {
+                self.record_safe_mode(transition.action == SafeModeAction::Halt);
+            }
+            NodeEvent::StorageCorruption { table, resolution, .. } => self.record_storage_corruption(table, *resolution),
+            NodeEvent::FinalityAdvanced { height, .. } => self.finalized_height.set(*height as f64),
+            NodeEvent::TransactionEvicted { reason, .. } => self.record_transaction_eviction(*reason),
+            NodeEvent::ForkDetected { .. } => self.record_fork_detection(),
+            NodeEvent::Reorganized { demoted, .. } => self.record_reorg(demoted.len()),
+            NodeEvent::SloBreached { slo, .. } => self.slo_breaches.with_label_values(&[slo]).inc(),
+            NodeEvent::SloRecovered { .. } => {}
+        }
+    }
+}
+
 /// DAG statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DAGStats {
     pub node_count: usize,
     pub depth: usize,
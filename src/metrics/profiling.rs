@@ -0,0 +1,318 @@
+//! On-demand runtime profiling
+//!
+//! CPU profiles are sampled with `pprof` for a fixed window and returned as
+//! pprof protobuf, so `go tool pprof` and similar tools read them directly.
+//! Heap statistics come from `TrackingAllocator`, which a node binary installs
+//! as its global allocator. Background tasks spawned through
+//! `spawn_instrumented` are counted per subsystem and, when built with the
+//! `profiling` feature and `--cfg tokio_unstable`, named after their subsystem
+//! so tokio-console can group them.
+
+use crate::BlockchainError;
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Longest CPU profile that can be requested
+pub const MAX_CPU_PROFILE_SECONDS: u64 = 60;
+/// Default CPU sampling frequency in Hz
+pub const DEFAULT_PROFILE_FREQUENCY: i32 = 99;
+
+/// Node subsystem that owns a background task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Core,
+    Network,
+    Consensus,
+    Security,
+    Storage,
+    Api,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 6] = [
+        Subsystem::Core,
+        Subsystem::Network,
+        Subsystem::Consensus,
+        Subsystem::Security,
+        Subsystem::Storage,
+        Subsystem::Api,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Subsystem::Core => "core",
+            Subsystem::Network => "network",
+            Subsystem::Consensus => "consensus",
+            Subsystem::Security => "security",
+            Subsystem::Storage => "storage",
+            Subsystem::Api => "api",
+        }
+    }
+}
+
+impl std::fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+struct TaskCounters {
+    spawned: AtomicU64,
+    active: AtomicU64,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_COUNTERS: TaskCounters = TaskCounters {
+    spawned: AtomicU64::new(0),
+    active: AtomicU64::new(0),
+};
+
+static TASK_COUNTERS: [TaskCounters; Subsystem::ALL.len()] = [EMPTY_COUNTERS; Subsystem::ALL.len()];
+
+/// Task counts for one subsystem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemTaskStats {
+    pub subsystem: Subsystem,
+    pub spawned: u64,
+    pub active: u64,
+}
+
+/// Decrements the active count when the task finishes or is aborted
+struct ActiveTask(Subsystem);
+
+impl Drop for ActiveTask {
+    fn drop(&mut self) {
+        TASK_COUNTERS[self.0 as usize].active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Spawn a background task attributed to `subsystem`
+pub fn spawn_instrumented<F>(subsystem: Subsystem, name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let counters = &TASK_COUNTERS[subsystem as usize];
+    counters.spawned.fetch_add(1, Ordering::Relaxed);
+    counters.active.fetch_add(1, Ordering::Relaxed);
+
+    let guard = ActiveTask(subsystem);
+    let future = async move {
+        let _guard = guard;
+        future.await
+    };
+
+    #[cfg(all(feature = "profiling", tokio_unstable))]
+    {
+        tokio::task::Builder::new()
+            .name(&format!("{}::{}", subsystem, name))
+            .spawn(future)
+            .expect("failed to spawn instrumented task")
+    }
+
+    #[cfg(not(all(feature = "profiling", tokio_unstable)))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
+
+/// Task counts for every subsystem
+pub fn task_stats() -> Vec<SubsystemTaskStats> {
+    Subsystem::ALL
+        .iter()
+        .map(|subsystem| {
+            let counters = &TASK_COUNTERS[*subsystem as usize];
+            SubsystemTaskStats {
+                subsystem: *subsystem,
+                spawned: counters.spawned.load(Ordering::Relaxed),
+                active: counters.active.load(Ordering::Relaxed),
+            }
+        })
+        .collect()
+}
+
+/// Start the tokio-console subscriber
+///
+/// Only available with the `profiling` feature and `--cfg tokio_unstable`;
+/// otherwise this logs a warning and does nothing.
+pub fn init_console() {
+    #[cfg(all(feature = "profiling", tokio_unstable))]
+    {
+        console_subscriber::init();
+        log::info!("🩺 tokio-console subscriber started");
+    }
+
+    #[cfg(not(all(feature = "profiling", tokio_unstable)))]
+    log::warn!("⚠️ tokio-console requires the `profiling` feature and RUSTFLAGS=\"--cfg tokio_unstable\"");
+}
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static DEALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Global allocator that keeps heap statistics
+///
+/// Install it in a binary with
+/// `#[global_allocator] static ALLOCATOR: TrackingAllocator = TrackingAllocator;`
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    fn record_alloc(size: usize) {
+        let allocated = ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_BYTES.fetch_max(allocated, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: usize) {
+        ALLOCATED_BYTES.fetch_sub(size, Ordering::Relaxed);
+        DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::record_dealloc(layout.size());
+            Self::record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Heap usage as seen by `TrackingAllocator`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeapStats {
+    /// False when the running binary does not install `TrackingAllocator`
+    pub tracking: bool,
+    pub allocated_bytes: usize,
+    pub peak_bytes: usize,
+    pub allocations: u64,
+    pub deallocations: u64,
+}
+
+/// Current heap statistics
+pub fn heap_stats() -> HeapStats {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    HeapStats {
+        tracking: allocations > 0,
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        allocations,
+        deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+    }
+}
+
+static CPU_PROFILE_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Sample the CPU for `duration` and return a pprof protobuf profile
+///
+/// Only one profile runs at a time. Needs the `profiling` feature.
+pub async fn cpu_profile(duration: Duration, frequency: i32) -> Result<Vec<u8>, BlockchainError> {
+    let duration = duration.min(Duration::from_secs(MAX_CPU_PROFILE_SECONDS));
+    if frequency <= 0 {
+        return Err(BlockchainError::Other("Profile frequency must be positive".to_string()));
+    }
+    if CPU_PROFILE_RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(BlockchainError::Other("A CPU profile is already being captured".to_string()));
+    }
+
+    log::info!("🔬 Capturing {}s CPU profile at {}Hz", duration.as_secs(), frequency);
+    let result = tokio::task::spawn_blocking(move || sample_cpu(duration, frequency))
+        .await
+        .map_err(|e| BlockchainError::Other(format!("CPU profile task failed: {}", e)))
+        .and_then(|result| result);
+
+    CPU_PROFILE_RUNNING.store(false, Ordering::SeqCst);
+    result
+}
+
+#[cfg(feature = "profiling")]
+fn sample_cpu(duration: Duration, frequency: i32) -> Result<Vec<u8>, BlockchainError> {
+    use pprof::protos::Message;
+
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .map_err(|e| BlockchainError::Other(format!("Failed to start CPU profiler: {}", e)))?;
+
+    std::thread::sleep(duration);
+
+    let profile = guard
+        .report()
+        .build()
+        .and_then(|report| report.pprof())
+        .map_err(|e| BlockchainError::Other(format!("Failed to build CPU profile: {}", e)))?;
+
+    let mut body = Vec::new();
+    profile
+        .encode(&mut body)
+        .map_err(|e| BlockchainError::Other(format!("Failed to encode CPU profile: {}", e)))?;
+    Ok(body)
+}
+
+#[cfg(not(feature = "profiling"))]
+fn sample_cpu(_duration: Duration, _frequency: i32) -> Result<Vec<u8>, BlockchainError> {
+    Err(BlockchainError::Other(
+        "CPU profiling is not available; rebuild with the `profiling` feature".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_instrumented_tasks_are_counted() {
+        let before = task_stats()[Subsystem::Storage as usize].spawned;
+
+        let (release, wait) = tokio::sync::oneshot::channel::<()>();
+        let handle = spawn_instrumented(Subsystem::Storage, "test", async move {
+            let _ = wait.await;
+        });
+
+        let stats = task_stats();
+        assert_eq!(stats[Subsystem::Storage as usize].subsystem, Subsystem::Storage);
+        assert_eq!(stats[Subsystem::Storage as usize].spawned, before + 1);
+        assert!(stats[Subsystem::Storage as usize].active >= 1);
+
+        release.send(()).unwrap();
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cpu_profile_rejects_bad_frequency() {
+        assert!(cpu_profile(Duration::from_millis(10), 0).await.is_err());
+        // The guard is released after a failed request
+        assert!(!CPU_PROFILE_RUNNING.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,356 @@
+//! Consensus service level objectives
+//!
+//! `SloMonitor` keeps the latest finalized consensus rounds in a rolling
+//! window and measures them against operator-defined targets, such as 95% of
+//! rounds finalizing within two seconds or fewer than 0.1% of rounds forking.
+//! When an objective starts failing it publishes `SloBreached`, and
+//! `SloRecovered` once it is met again, so the breach is journaled for
+//! postmortems. Objectives are not judged until the window holds
+//! `min_rounds` rounds.
+
+use crate::events::{EventBus, EventHandler, NodeEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// What an objective measures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SloObjective {
+    /// At least `target` of rounds finalize in under `threshold_ms`
+    FinalityLatency { threshold_ms: u64, target: f64 },
+    /// At most `max_rate` of rounds end in a detected fork
+    ForkRate { max_rate: f64 },
+}
+
+/// A named objective
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SloTarget {
+    pub name: String,
+    pub objective: SloObjective,
+}
+
+/// Objectives and the window they are measured over
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SloConfig {
+    /// Finalized rounds the objectives are measured over
+    pub window_rounds: usize,
+    /// Rounds needed before an objective can be breached
+    pub min_rounds: usize,
+    pub targets: Vec<SloTarget>,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            window_rounds: 1000,
+            min_rounds: 20,
+            targets: vec![
+                SloTarget {
+                    name: "finality_latency".to_string(),
+                    objective: SloObjective::FinalityLatency { threshold_ms: 2000, target: 0.95 },
+                },
+                SloTarget {
+                    name: "fork_rate".to_string(),
+                    objective: SloObjective::ForkRate { max_rate: 0.001 },
+                },
+            ],
+        }
+    }
+}
+
+impl SloConfig {
+    /// Check the window and every target, naming the first invalid field
+    pub fn validate(&self) -> Result<(), (String, String)> {
+        let invalid = |field: &str, reason: &str| Err((field.to_string(), reason.to_string()));
+        if self.window_rounds == 0 {
+            return invalid("slo.window_rounds", "must be greater than zero");
+        }
+        if self.min_rounds > self.window_rounds {
+            return invalid("slo.min_rounds", "must not exceed window_rounds");
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for target in &self.targets {
+            if target.name.is_empty() || !names.insert(&target.name) {
+                return invalid("slo.targets", &format!("target names must be unique and non-empty: '{}'", target.name));
+            }
+            match target.objective {
+                SloObjective::FinalityLatency { threshold_ms, target: fraction } => {
+                    if threshold_ms == 0 {
+                        return invalid("slo.targets", &format!("{}: threshold_ms must be greater than zero", target.name));
+                    }
+                    if !(0.0..=1.0).contains(&fraction) {
+                        return invalid("slo.targets", &format!("{}: target must be between 0 and 1", target.name));
+                    }
+                }
+                SloObjective::ForkRate { max_rate } => {
+                    if !(0.0..=1.0).contains(&max_rate) {
+                        return invalid("slo.targets", &format!("{}: max_rate must be between 0 and 1", target.name));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How one objective stands over the current window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SloStatus {
+    pub name: String,
+    pub objective: SloObjective,
+    /// Fraction of rounds finalizing in time, or fraction of rounds forking
+    pub measured: f64,
+    pub met: bool,
+    /// Rounds the measurement covers
+    pub rounds: usize,
+    /// Unix time the current breach started, if the objective is failing
+    pub breached_since: Option<u64>,
+}
+
+/// Compliance with every objective
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SloReport {
+    pub window_rounds: usize,
+    /// Finalized rounds in the window
+    pub rounds: usize,
+    /// Whether enough rounds were seen to judge the objectives
+    pub judged: bool,
+    pub statuses: Vec<SloStatus>,
+}
+
+impl SloReport {
+    /// Names of objectives currently breached
+    pub fn breached(&self) -> Vec<&str> {
+        self.statuses.iter().filter(|status| !status.met).map(|status| status.name.as_str()).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RoundSample {
+    round_number: u64,
+    duration_ms: u64,
+    forked: bool,
+}
+
+#[derive(Debug, Default)]
+struct SloState {
+    samples: VecDeque<RoundSample>,
+    /// Breach start times of failing objectives, by name
+    breaches: HashMap<String, u64>,
+}
+
+/// Measures finalized rounds against the configured objectives
+pub struct SloMonitor {
+    config: Mutex<SloConfig>,
+    state: Mutex<SloState>,
+    events: EventBus,
+}
+
+impl SloMonitor {
+    pub fn new(config: SloConfig, events: EventBus) -> Self {
+        Self {
+            config: Mutex::new(config),
+            state: Mutex::new(SloState::default()),
+            events,
+        }
+    }
+
+    pub fn config(&self) -> SloConfig {
+        self.config.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the objectives, keeping the rounds already seen
+    pub fn set_config(&self, config: SloConfig) {
+        *self.config.lock().unwrap_or_else(|e| e.into_inner()) = config;
+        self.evaluate(chrono::Utc::now().timestamp() as u64);
+    }
+
+    /// Record a finalized round
+    pub fn record_round(&self, round_number: u64, duration_ms: u64, now: u64) {
+        let window = self.config().window_rounds;
+        {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.samples.push_back(RoundSample { round_number, duration_ms, forked: false });
+            while state.samples.len() > window {
+                state.samples.pop_front();
+            }
+        }
+        self.evaluate(now);
+    }
+
+    /// Record a fork detected at `round_number`
+    pub fn record_fork(&self, round_number: u64, now: u64) {
+        {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            match state.samples.iter_mut().rev().find(|sample| sample.round_number == round_number) {
+                Some(sample) => sample.forked = true,
+                None => match state.samples.back_mut() {
+                    Some(latest) => latest.forked = true,
+                    None => return,
+                },
+            }
+        }
+        self.evaluate(now);
+    }
+
+    /// Current compliance with every objective
+    pub fn report(&self) -> SloReport {
+        let config = self.config();
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Self::measure(&config, &state)
+    }
+
+    fn measure(config: &SloConfig, state: &SloState) -> SloReport {
+        let rounds = state.samples.len().min(config.window_rounds);
+        let window: Vec<&RoundSample> = state.samples.iter().rev().take(rounds).collect();
+        let judged = rounds >= config.min_rounds.max(1);
+        let fraction = |count: usize| if rounds == 0 { 0.0 } else { count as f64 / rounds as f64 };
+
+        let statuses = config.targets.iter()
+            .map(|target| {
+                let (measured, met) = match target.objective {
+                    SloObjective::FinalityLatency { threshold_ms, target } => {
+                        let in_time = fraction(window.iter().filter(|sample| sample.duration_ms < threshold_ms).count());
+                        (in_time, in_time >= target)
+                    }
+                    SloObjective::ForkRate { max_rate } => {
+                        let rate = fraction(window.iter().filter(|sample| sample.forked).count());
+                        (rate, rate <= max_rate)
+                    }
+                };
+                SloStatus {
+                    name: target.name.clone(),
+                    objective: target.objective.clone(),
+                    measured,
+                    met: met || !judged,
+                    rounds,
+                    breached_since: state.breaches.get(&target.name).copied(),
+                }
+            })
+            .collect();
+
+        SloReport { window_rounds: config.window_rounds, rounds, judged, statuses }
+    }
+
+    /// Open and close breaches for objectives that changed state
+    fn evaluate(&self, now: u64) {
+        let config = self.config();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let report = Self::measure(&config, &state);
+
+        let mut transitions = Vec::new();
+        for status in &report.statuses {
+            match (status.met, state.breaches.get(&status.name).copied()) {
+                (false, None) => {
+                    state.breaches.insert(status.name.clone(), now);
+                    transitions.push(NodeEvent::SloBreached {
+                        slo: status.name.clone(),
+                        objective: status.objective.clone(),
+                        measured: status.measured,
+                        rounds: status.rounds,
+                    });
+                }
+                (true, Some(since)) => {
+                    state.breaches.remove(&status.name);
+                    transitions.push(NodeEvent::SloRecovered {
+                        slo: status.name.clone(),
+                        measured: status.measured,
+                        breached_for_secs: now.saturating_sub(since),
+                    });
+                }
+                _ => {}
+            }
+        }
+        // Objectives removed from the config no longer count as breached
+        state.breaches.retain(|name, _| config.targets.iter().any(|target| &target.name == name));
+        drop(state);
+
+        for event in transitions {
+            match &event {
+                NodeEvent::SloBreached { slo, measured, .. } => log::warn!("📉 SLO {} breached: measured {:.4}", slo, measured),
+                NodeEvent::SloRecovered { slo, breached_for_secs, .. } => {
+                    log::info!("📈 SLO {} recovered after {}s", slo, breached_for_secs)
+                }
+                _ => {}
+            }
+            self.events.publish(event);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for SloMonitor {
+    fn name(&self) -> String {
+        "slo".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        let now = chrono::Utc::now().timestamp() as u64;
+        match event {
+            NodeEvent::RoundFinalized { round_number, duration_ms, .. } => self.record_round(*round_number, *duration_ms, now),
+            NodeEvent::ForkDetected { round_number, .. } => self.record_fork(*round_number, now),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SloConfig {
+        SloConfig { window_rounds: 10, min_rounds: 4, ..SloConfig::default() }
+    }
+
+    #[test]
+    fn test_compliance_over_window() {
+        let monitor = SloMonitor::new(config(), EventBus::default());
+        for round in 0..3 {
+            monitor.record_round(round, 5000, 100);
+        }
+        // Too few rounds to judge
+        assert!(monitor.report().breached().is_empty());
+
+        for round in 3..10 {
+            monitor.record_round(round, 500, 100);
+        }
+        let report = monitor.report();
+        assert_eq!(report.rounds, 10);
+        assert!((report.statuses[0].measured - 0.7).abs() < 1e-9);
+        assert_eq!(report.breached(), vec!["finality_latency"]);
+
+        // Slow rounds age out of the window
+        for round in 10..13 {
+            monitor.record_round(round, 500, 100);
+        }
+        assert!(monitor.report().breached().is_empty());
+
+        monitor.record_fork(12, 100);
+        assert_eq!(monitor.report().breached(), vec!["fork_rate"]);
+        assert!(config().validate().is_ok());
+        assert!(SloConfig { min_rounds: 11, ..config() }.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_breach_and_recovery_are_published() {
+        let events = EventBus::default();
+        let mut receiver = events.subscribe();
+        let monitor = SloMonitor::new(config(), events);
+        for round in 0..4 {
+            monitor.record_round(round, 100, 1_000);
+        }
+        monitor.record_fork(3, 1_000);
+        let Ok(NodeEvent::SloBreached { slo, rounds, .. }) = receiver.try_recv() else { panic!("no breach") };
+        assert_eq!((slo.as_str(), rounds), ("fork_rate", 4));
+        assert_eq!(monitor.report().statuses[1].breached_since, Some(1_000));
+
+        // Loosening the objective ends the breach
+        let mut loose = config();
+        loose.targets[1].objective = SloObjective::ForkRate { max_rate: 0.5 };
+        monitor.set_config(loose);
+        assert!(matches!(receiver.try_recv(), Ok(NodeEvent::SloRecovered { .. })));
+    }
+}
@@ -0,0 +1,312 @@
+//! Protocol version and capability negotiation
+//!
+//! Peers exchange `Capabilities` when they connect. Each side settles on the
+//! highest protocol version both speak and, in its own order of preference,
+//! the first wire format, compression and sync protocol the other side also
+//! supports. Consensus feature bits are intersected. A peer is refused only
+//! when no version or wire format is shared; anything else falls back to the
+//! baseline (no compression, full sync, no optional consensus features) and
+//! is listed in the negotiated protocol as a degradation.
+//!
+//! Capabilities a newer peer advertises that this node does not know
+//! deserialize as `Unknown` and are never selected.
+
+use super::NetworkError;
+use crate::NetworkType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Protocol version this node speaks
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Oldest protocol version this node still accepts
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Message encoding on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireFormat {
+    Json,
+    Bincode,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Compression applied to message payloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    None,
+    Gzip,
+    #[serde(other)]
+    Unknown,
+}
+
+/// How a peer serves DAG sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncProtocol {
+    /// Every transaction from genesis
+    Full,
+    /// Recent nodes first, older ones on demand
+    Paged,
+    /// Compact address filters for light clients
+    Filtered,
+    /// Verified state snapshot, then recent history
+    Bootstrap,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Optional consensus features, as bits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConsensusFeatures(pub u64);
+
+impl ConsensusFeatures {
+    /// Transactions are identified by their content hash
+    pub const CONTENT_ADDRESSED_IDS: Self = Self(1 << 0);
+    /// Transactions carry typed payloads
+    pub const TYPED_PAYLOADS: Self = Self(1 << 1);
+    /// Checkpoints are certified by the BLS committee
+    pub const CHECKPOINT_CERTIFICATES: Self = Self(1 << 2);
+    /// Finality comes from validator attestation quorums
+    pub const FINALITY_ATTESTATIONS: Self = Self(1 << 3);
+    /// Answers which member of a double spend it prefers
+    pub const PREFERENCE_QUERIES: Self = Self(1 << 4);
+
+    const NAMED: [(Self, &'static str); 5] = [
+        (Self::CONTENT_ADDRESSED_IDS, "content_addressed_ids"),
+        (Self::TYPED_PAYLOADS, "typed_payloads"),
+        (Self::CHECKPOINT_CERTIFICATES, "checkpoint_certificates"),
+        (Self::FINALITY_ATTESTATIONS, "finality_attestations"),
+        (Self::PREFERENCE_QUERIES, "preference_queries"),
+    ];
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersection(&self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    pub fn union(&self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Names of the known features set
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMED.iter().filter(|(feature, _)| self.contains(*feature)).map(|(_, name)| *name).collect()
+    }
+}
+
+/// What a node supports, sent in its handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub network_type: NetworkType,
+    pub protocol_version: u32,
+    pub min_protocol_version: u32,
+    /// Software version, for operators only
+    pub node_version: String,
+    /// Supported wire formats, most preferred first
+    pub wire_formats: Vec<WireFormat>,
+    pub compression: Vec<Compression>,
+    pub sync_protocols: Vec<SyncProtocol>,
+    pub consensus_features: ConsensusFeatures,
+}
+
+impl Capabilities {
+    /// What this build supports on `network_type`
+    pub fn local(network_type: NetworkType) -> Self {
+        Self {
+            network_type,
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            node_version: env!("CARGO_PKG_VERSION").to_string(),
+            wire_formats: vec![WireFormat::Bincode, WireFormat::Json],
+            compression: vec![Compression::Gzip, Compression::None],
+            sync_protocols: vec![SyncProtocol::Bootstrap, SyncProtocol::Paged, SyncProtocol::Filtered, SyncProtocol::Full],
+            consensus_features: ConsensusFeatures::CONTENT_ADDRESSED_IDS
+                .union(ConsensusFeatures::TYPED_PAYLOADS)
+                .union(ConsensusFeatures::CHECKPOINT_CERTIFICATES)
+                .union(ConsensusFeatures::FINALITY_ATTESTATIONS)
+                .union(ConsensusFeatures::PREFERENCE_QUERIES),
+        }
+    }
+
+    /// Settle on a protocol with `remote`, from this node's side
+    pub fn negotiate(&self, remote: &Capabilities) -> Result<NegotiatedProtocol, NetworkError> {
+        if remote.network_type != self.network_type {
+            return Err(NetworkError::IncompatiblePeer(format!(
+                "peer is on {:?}, this node on {:?}",
+                remote.network_type, self.network_type
+            )));
+        }
+
+        let version = self.protocol_version.min(remote.protocol_version);
+        let oldest = self.min_protocol_version.max(remote.min_protocol_version);
+        if version < oldest {
+            return Err(NetworkError::IncompatiblePeer(format!(
+                "no common protocol version: peer speaks {}..={}, this node {}..={}",
+                remote.min_protocol_version, remote.protocol_version, self.min_protocol_version, self.protocol_version
+            )));
+        }
+
+        let wire_format = first_shared(&self.wire_formats, &remote.wire_formats, WireFormat::Unknown)
+            .ok_or_else(|| NetworkError::IncompatiblePeer("no common wire format".to_string()))?;
+
+        let mut degraded = Vec::new();
+        let compression = first_shared(&self.compression, &remote.compression, Compression::Unknown)
+            .unwrap_or(Compression::None);
+        if self.compression.first().is_some_and(|preferred| *preferred != compression) {
+            degraded.push(format!("compression: {:?}", compression).to_lowercase());
+        }
+        let sync_protocol = first_shared(&self.sync_protocols, &remote.sync_protocols, SyncProtocol::Unknown)
+            .unwrap_or(SyncProtocol::Full);
+        if self.sync_protocols.first().is_some_and(|preferred| *preferred != sync_protocol) {
+            degraded.push(format!("sync: {:?}", sync_protocol).to_lowercase());
+        }
+        let consensus_features = self.consensus_features.intersection(remote.consensus_features);
+        let missing = ConsensusFeatures(self.consensus_features.0 & !consensus_features.0);
+        degraded.extend(missing.names().into_iter().map(|name| format!("without {}", name)));
+
+        Ok(NegotiatedProtocol {
+            protocol_version: version,
+            peer_node_version: remote.node_version.clone(),
+            wire_format,
+            compression,
+            sync_protocol,
+            consensus_features,
+            degraded,
+        })
+    }
+}
+
+/// First entry of `preferred` that `supported` also lists, skipping `unknown`
+fn first_shared<T: PartialEq + Copy>(preferred: &[T], supported: &[T], unknown: T) -> Option<T> {
+    preferred.iter().copied().find(|entry| *entry != unknown && supported.contains(entry))
+}
+
+/// Protocol agreed with a peer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegotiatedProtocol {
+    pub protocol_version: u32,
+    pub peer_node_version: String,
+    pub wire_format: WireFormat,
+    pub compression: Compression,
+    pub sync_protocol: SyncProtocol,
+    pub consensus_features: ConsensusFeatures,
+    /// What fell back below this node's preference, e.g. "without typed_payloads"
+    pub degraded: Vec<String>,
+}
+
+impl NegotiatedProtocol {
+    /// Whether messages relying on `feature` may be sent to the peer
+    pub fn supports(&self, feature: ConsensusFeatures) -> bool {
+        self.consensus_features.contains(feature)
+    }
+}
+
+/// Connected peers by negotiated capability
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityDistribution {
+    pub peers: usize,
+    pub protocol_versions: BTreeMap<u32, usize>,
+    pub wire_formats: BTreeMap<WireFormat, usize>,
+    pub compression: BTreeMap<Compression, usize>,
+    pub sync_protocols: BTreeMap<SyncProtocol, usize>,
+    /// Peers supporting each known consensus feature
+    pub consensus_features: BTreeMap<String, usize>,
+    /// Peers with at least one degradation
+    pub degraded_peers: usize,
+}
+
+impl CapabilityDistribution {
+    pub fn from_protocols<'a>(protocols: impl IntoIterator<Item = &'a NegotiatedProtocol>) -> Self {
+        let mut distribution = Self::default();
+        for protocol in protocols {
+            distribution.peers += 1;
+            *distribution.protocol_versions.entry(protocol.protocol_version).or_default() += 1;
+            *distribution.wire_formats.entry(protocol.wire_format).or_default() += 1;
+            *distribution.compression.entry(protocol.compression).or_default() += 1;
+            *distribution.sync_protocols.entry(protocol.sync_protocol).or_default() += 1;
+            for name in protocol.consensus_features.names() {
+                *distribution.consensus_features.entry(name.to_string()).or_default() += 1;
+            }
+            if !protocol.degraded.is_empty() {
+                distribution.degraded_peers += 1;
+            }
+        }
+        distribution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiation_degrades_to_older_peer() {
+        let local = Capabilities::local(NetworkType::Devnet);
+        let older = Capabilities {
+            protocol_version: 1,
+            node_version: "0.3.0".to_string(),
+            wire_formats: vec![WireFormat::Json],
+            compression: vec![Compression::None],
+            sync_protocols: vec![SyncProtocol::Full, SyncProtocol::Paged],
+            consensus_features: ConsensusFeatures::CONTENT_ADDRESSED_IDS,
+            ..local.clone()
+        };
+
+        let protocol = local.negotiate(&older).unwrap();
+        assert_eq!(protocol.protocol_version, 1);
+        assert_eq!(protocol.wire_format, WireFormat::Json);
+        assert_eq!(protocol.compression, Compression::None);
+        assert_eq!(protocol.sync_protocol, SyncProtocol::Paged);
+        assert!(protocol.supports(ConsensusFeatures::CONTENT_ADDRESSED_IDS));
+        assert!(!protocol.supports(ConsensusFeatures::FINALITY_ATTESTATIONS));
+        assert_eq!(protocol.degraded, vec![
+            "compression: none",
+            "sync: paged",
+            "without typed_payloads",
+            "without checkpoint_certificates",
+            "without finality_attestations",
+        ]);
+
+        // Two current nodes get everything
+        assert!(local.negotiate(&local).unwrap().degraded.is_empty());
+    }
+
+    #[test]
+    fn test_incompatible_peers_are_refused() {
+        let local = Capabilities::local(NetworkType::Devnet);
+        let future = Capabilities { protocol_version: 9, min_protocol_version: 5, ..local.clone() };
+        assert!(matches!(local.negotiate(&future), Err(NetworkError::IncompatiblePeer(_))));
+
+        let mainnet = Capabilities::local(NetworkType::Mainnet);
+        assert!(matches!(local.negotiate(&mainnet), Err(NetworkError::IncompatiblePeer(_))));
+
+        // Capabilities added by newer releases are ignored rather than rejected
+        let newer: Capabilities = serde_json::from_value(serde_json::json!({
+            "network_type": "devnet",
+            "protocol_version": 3,
+            "min_protocol_version": 2,
+            "node_version": "9.0.0",
+            "wire_formats": ["cbor"],
+            "compression": ["zstd", "gzip"],
+            "sync_protocols": ["snapshot_stream"],
+            "consensus_features": (1u64 << 40) | 1,
+        })).unwrap();
+        assert!(matches!(local.negotiate(&newer), Err(NetworkError::IncompatiblePeer(_))));
+        let newer = Capabilities { wire_formats: vec![WireFormat::Unknown, WireFormat::Json], ..newer };
+        let protocol = local.negotiate(&newer).unwrap();
+        assert_eq!((protocol.protocol_version, protocol.compression), (2, Compression::Gzip));
+        assert_eq!(protocol.sync_protocol, SyncProtocol::Full);
+        assert_eq!(protocol.consensus_features, ConsensusFeatures::CONTENT_ADDRESSED_IDS);
+
+        let distribution = CapabilityDistribution::from_protocols([&protocol]);
+        assert_eq!(distribution.consensus_features.get("content_addressed_ids"), Some(&1));
+        assert_eq!(distribution.degraded_peers, 1);
+    }
+}
@@ -1,14 +1,25 @@
 //! Network layer for P2P communication
 
-use crate::{BlockchainError, TransactionId};
+use crate::{core::Transaction, BlockchainError, NetworkType, TransactionId};
 use async_trait::async_trait;
 use libp2p::{Multiaddr, PeerId};
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 
+pub mod handshake;
+pub mod scoring;
+pub mod spam;
+pub use handshake::{
+    CapabilityDistribution, Capabilities, Compression, ConsensusFeatures, NegotiatedProtocol, SyncProtocol, WireFormat,
+    MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+pub use scoring::{Misbehavior, PeerAction, PeerScore, PeerScoreboard, PeerScoringConfig};
+pub use spam::{SpamFilter, SpamFilterConfig, SpamFilterStats, SpamReason};
+
 /// Network configuration
 #[derive(Debug, Clone)]
 pub struct NetworkConfig {
+    pub network_type: NetworkType,
     pub listen_addr: String,
     pub bootstrap_nodes: Vec<String>,
     pub max_peers: u32,
@@ -19,6 +30,10 @@ pub struct NetworkLayer {
     config: NetworkConfig,
     peers: HashMap<PeerId, PeerInfo>,
     is_running: bool,
+    scoreboard: PeerScoreboard,
+    spam_filter: SpamFilter,
+    capabilities: Capabilities,
+    peer_protocols: RwLock<HashMap<PeerId, NegotiatedProtocol>>,
 }
 
 /// Peer information
@@ -37,6 +52,10 @@ impl NetworkLayer {
             config: config.clone(),
             peers: HashMap::new(),
             is_running: false,
+            scoreboard: PeerScoreboard::new(PeerScoringConfig::default()),
+            spam_filter: SpamFilter::default(),
+            capabilities: Capabilities::local(config.network_type),
+            peer_protocols: RwLock::new(HashMap::new()),
         })
     }
 
@@ -59,32 +78,218 @@ impl NetworkLayer {
         println!("🌐 Stopping network layer");
         self.is_running = false;
         self.peers.clear();
+        self.peer_protocols.get_mut().clear();
         Ok(())
     }
 
     /// Propagate transaction to network
-    pub async fn propagate_transaction(&self, tx_id: &TransactionId) -> Result<(), BlockchainError> {
+    ///
+    /// Peers receive its canonical encoding; see `Blockchain::receive_peer_payload`.
+    pub async fn propagate_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
         if !self.is_running {
             return Err(BlockchainError::Network(NetworkError::NotRunning));
         }
 
-        println!("📦 Propagating transaction {} to {} peers", tx_id, self.peers.len());
+        let payload = transaction.to_canonical_bytes();
+        println!("📦 Propagating transaction {} ({} bytes) to {} peers", transaction.id, payload.len(), self.peers.len());
         
-        // In a real implementation, this would serialize and send the transaction
-        // to all connected peers
+        // In a real implementation, this would send the payload to all
+        // connected peers
         
         Ok(())
     }
 
+    /// Announce this node's new identity to all connected peers after a rotation
+    pub async fn announce_identity(&self, announcement: &crate::identity::IdentityAnnouncement) -> Result<(), BlockchainError> {
+        if !self.is_running {
+            return Err(BlockchainError::Network(NetworkError::NotRunning));
+        }
+
+        let payload = serde_json::to_vec(announcement)?;
+        println!("🪪 Announcing identity {} ({} bytes) to {} peers", announcement.identity.node_id, payload.len(), self.peers.len());
+
+        // In a real implementation, this would send the payload to all
+        // connected peers
+
+        Ok(())
+    }
+
+    /// Send an operator envelope directly to the node with `node_id`
+    ///
+    /// Direct messages are not gossiped and never enter the DAG.
+    pub async fn send_direct(&self, node_id: &str, payload: &[u8]) -> Result<(), BlockchainError> {
+        if !self.is_running {
+            return Err(BlockchainError::Network(NetworkError::NotRunning));
+        }
+
+        println!("✉️ Sending {} byte direct message to node {}", payload.len(), node_id);
+
+        // In a real implementation, this would open a stream to the peer
+        // advertising `node_id` and write the payload
+
+        Ok(())
+    }
+
+    /// Up to `size` peers answering preference queries, chosen at random
+    pub async fn sample_peers(&self, size: usize) -> Vec<PeerId> {
+        use rand::seq::SliceRandom;
+        let candidates = self.peers_supporting(ConsensusFeatures::PREFERENCE_QUERIES).await;
+        candidates.choose_multiple(&mut rand::thread_rng(), size).copied().collect()
+    }
+
+    /// Ask `peer` which member of the double spend on `key` it prefers
+    ///
+    /// Returns `None` when the peer did not answer.
+    pub async fn query_preference(
+        &self,
+        peer: &PeerId,
+        key: &crate::core::SpendKey,
+        members: &[TransactionId],
+    ) -> Result<Option<TransactionId>, BlockchainError> {
+        if !self.is_running {
+            return Err(BlockchainError::Network(NetworkError::NotRunning));
+        }
+
+        log::debug!("🗳️ Asking peer {} to choose between {} spends of {}", peer, members.len(), key);
+
+        // In a real implementation, this would send the query on a stream to
+        // the peer and wait for the member it names
+
+        Ok(None)
+    }
+
     /// Get number of connected peers
     pub fn peer_count(&self) -> u32 {
         self.peers.len() as u32
     }
 
+    /// What this node offers peers in its handshake
+    pub fn local_capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Negotiate the protocol with a connecting peer from its advertised capabilities
+    ///
+    /// The peer is refused if it shares no protocol version or wire format
+    /// with this node; otherwise the agreed protocol is kept for as long as
+    /// the peer stays connected.
+    pub async fn handshake(&self, peer: &PeerId, remote: &Capabilities) -> Result<NegotiatedProtocol, BlockchainError> {
+        let protocol = self.capabilities.negotiate(remote).map_err(|e| {
+            log::warn!("🤝 Refusing peer {} running {}: {}", peer, remote.node_version, e);
+            BlockchainError::Network(e)
+        })?;
+
+        if protocol.degraded.is_empty() {
+            log::debug!("🤝 Peer {} speaks protocol v{}", peer, protocol.protocol_version);
+        } else {
+            log::info!(
+                "🤝 Peer {} speaks protocol v{} ({})",
+                peer, protocol.protocol_version, protocol.degraded.join(", ")
+            );
+        }
+        self.peer_protocols.write().await.insert(*peer, protocol.clone());
+        Ok(protocol)
+    }
+
+    /// Protocol agreed with a connected peer
+    pub async fn peer_protocol(&self, peer: &PeerId) -> Option<NegotiatedProtocol> {
+        self.peer_protocols.read().await.get(peer).cloned()
+    }
+
+    /// Drop what was negotiated with a disconnected peer
+    pub async fn forget_peer(&self, peer: &PeerId) {
+        self.peer_protocols.write().await.remove(peer);
+    }
+
+    /// Peers that agreed to `feature`, so messages relying on it skip the rest
+    pub async fn peers_supporting(&self, feature: ConsensusFeatures) -> Vec<PeerId> {
+        self.peer_protocols.read().await.iter()
+            .filter(|(_, protocol)| protocol.supports(feature))
+            .map(|(peer, _)| *peer)
+            .collect()
+    }
+
+    /// Connected peers counted by negotiated capability
+    pub async fn capability_distribution(&self) -> CapabilityDistribution {
+        CapabilityDistribution::from_protocols(self.peer_protocols.read().await.values())
+    }
+
+    /// Check whether a message from `peer` may be processed
+    pub async fn admit_message(&self, peer: &PeerId, message_size: usize) -> Result<(), BlockchainError> {
+        let max_size = self.scoreboard.config().max_message_size;
+        if message_size > max_size {
+            self.report_misbehavior(peer, Misbehavior::OversizedMessage).await;
+            return Err(BlockchainError::Network(NetworkError::MessageTooLarge(message_size, max_size)));
+        }
+
+        match self.scoreboard.admit(peer).await {
+            PeerAction::None => Ok(()),
+            PeerAction::Throttle => Err(BlockchainError::Network(NetworkError::PeerThrottled(peer.to_string()))),
+            PeerAction::Disconnect | PeerAction::Ban => {
+                Err(BlockchainError::Network(NetworkError::PeerBanned(peer.to_string())))
+            },
+        }
+    }
+
+    /// Screen a gossiped transaction before it is verified
+    ///
+    /// Malformed transactions count against the relaying peer; duplicates
+    /// and busy senders are dropped without penalty.
+    pub async fn screen_gossip(&self, peer: &PeerId, transaction: &crate::core::Transaction) -> Result<(), SpamReason> {
+        let result = self.spam_filter.check(transaction);
+        if let Err(reason) = result {
+            log::debug!("🧹 Dropped gossip from {} before verification: {}", peer, reason);
+            if reason.is_malformed() {
+                self.report_misbehavior(peer, Misbehavior::MalformedMessage).await;
+            }
+        }
+        result
+    }
+
+    /// Get spam filter effectiveness counters
+    pub fn spam_filter_stats(&self) -> SpamFilterStats {
+        self.spam_filter.stats()
+    }
+
+    /// Record misbehavior by a peer and return the resulting penalty
+    pub async fn report_misbehavior(&self, peer: &PeerId, misbehavior: Misbehavior) -> PeerAction {
+        let action = self.scoreboard.record(peer, misbehavior).await;
+        match action {
+            PeerAction::None => log::debug!("⚠️ Peer {} misbehaved: {:?}", peer, misbehavior),
+            PeerAction::Throttle => log::warn!("🐢 Throttling peer {} after {:?}", peer, misbehavior),
+            PeerAction::Disconnect => log::warn!("🔌 Disconnecting peer {} after {:?}", peer, misbehavior),
+            PeerAction::Ban => log::warn!("🚫 Banning peer {} after {:?}", peer, misbehavior),
+        }
+        action
+    }
+
+    /// Report a validation failure on a message from `peer`
+    ///
+    /// Errors that do not reflect on the sender, such as storage failures, are ignored.
+    pub async fn report_validation_failure(&self, peer: &PeerId, error: &BlockchainError) -> Option<PeerAction> {
+        let misbehavior = Misbehavior::from_error(error)?;
+        Some(self.report_misbehavior(peer, misbehavior).await)
+    }
+
+    /// Get misbehavior scores for all tracked peers
+    pub async fn peer_scores(&self) -> Vec<PeerScore> {
+        self.scoreboard.scores().await
+    }
+
+    /// Get the peer scoring configuration
+    pub fn peer_scoring_config(&self) -> PeerScoringConfig {
+        self.scoreboard.config()
+    }
+
+    /// Replace the peer scoring configuration
+    pub fn set_peer_scoring_config(&self, config: PeerScoringConfig) {
+        self.scoreboard.set_config(config);
+    }
+
     /// Start peer discovery
     async fn start_discovery(&self) {
         // Simplified discovery - in real implementation would use libp2p discovery
-        tokio::spawn(async {
+        crate::spawn_instrumented(crate::Subsystem::Network, "discovery", async {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
             
             while interval.tick().await.is_some() {
@@ -96,7 +301,7 @@ impl NetworkLayer {
 
     /// Start network maintenance
     async fn start_maintenance(&self) {
-        tokio::spawn(async {
+        crate::spawn_instrumented(crate::Subsystem::Network, "maintenance", async {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60);
             
             while interval.tick().await.is_some() {
@@ -118,6 +323,16 @@ pub enum NetworkError {
     ConnectionFailed(String),
     #[error("Protocol error: {0}")]
     ProtocolError(String),
+    #[error("Message of {0} bytes exceeds limit of {1} bytes")]
+    MessageTooLarge(usize, usize),
+    #[error("Peer throttled: {0}")]
+    PeerThrottled(String),
+    #[error("Peer banned: {0}")]
+    PeerBanned(String),
+    #[error("Gossip dropped by spam filter: {0}")]
+    SpamFiltered(SpamReason),
+    #[error("Incompatible peer: {0}")]
+    IncompatiblePeer(String),
 }
 
 /// Network trait for extensibility
@@ -125,7 +340,7 @@ pub enum NetworkError {
 pub trait NetworkService: Send + Sync {
     async fn start(&mut self) -> Result<(), BlockchainError>;
     async fn stop(&mut self) -> Result<(), BlockchainError>;
-    async fn propagate_transaction(&self, tx_id: &TransactionId) -> Result<(), BlockchainError>;
+    async fn propagate_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError>;
     fn peer_count(&self) -> u32;
 }
 
@@ -139,8 +354,8 @@ impl NetworkService for NetworkLayer {
         self.stop().await
     }
 
-    async fn propagate_transaction(&self, tx_id: &TransactionId) -> Result<(), BlockchainError> {
-        self.propagate_transaction(tx_id).await
+    async fn propagate_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
+        self.propagate_transaction(transaction).await
     }
 
     fn peer_count(&self) -> u32 {
@@ -155,6 +370,7 @@ mod tests {
     #[tokio::test]
     async fn test_network_layer_creation() {
         let config = NetworkConfig {
+            network_type: NetworkType::Devnet,
             listen_addr: "/ip4/127.0.0.1/tcp/0".to_string(),
             bootstrap_nodes: vec![],
             max_peers: 10,
@@ -167,6 +383,7 @@ mod tests {
     #[tokio::test]
     async fn test_network_start_stop() {
         let config = NetworkConfig {
+            network_type: NetworkType::Devnet,
             listen_addr: "/ip4/127.0.0.1/tcp/0".to_string(),
             bootstrap_nodes: vec![],
             max_peers: 10,
@@ -186,6 +403,7 @@ mod tests {
     #[tokio::test]
     async fn test_peer_count() {
         let config = NetworkConfig {
+            network_type: NetworkType::Devnet,
             listen_addr: "/ip4/127.0.0.1/tcp/0".to_string(),
             bootstrap_nodes: vec![],
             max_peers: 10,
@@ -194,4 +412,62 @@ mod tests {
         let network = NetworkLayer::new(&config).await.unwrap();
         assert_eq!(network.peer_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_validation_failures_lead_to_ban() {
+        let config = NetworkConfig {
+            network_type: NetworkType::Devnet,
+            listen_addr: "/ip4/127.0.0.1/tcp/0".to_string(),
+            bootstrap_nodes: vec![],
+            max_peers: 10,
+        };
+
+        let network = NetworkLayer::new(&config).await.unwrap();
+        let peer = PeerId::random();
+
+        let oversized = network.admit_message(&peer, 10 * 1024 * 1024).await;
+        assert!(matches!(oversized, Err(BlockchainError::Network(NetworkError::MessageTooLarge(..)))));
+
+        let invalid = BlockchainError::Security(crate::security::SecurityError::InvalidSignature);
+        for _ in 0..4 {
+            network.report_validation_failure(&peer, &invalid).await;
+        }
+
+        let result = network.admit_message(&peer, 128).await;
+        assert!(matches!(result, Err(BlockchainError::Network(NetworkError::PeerBanned(_)))));
+        assert_eq!(network.peer_scores().await[0].action, PeerAction::Ban);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_tracks_peer_capabilities() {
+        let config = NetworkConfig {
+            network_type: NetworkType::Devnet,
+            listen_addr: "/ip4/127.0.0.1/tcp/0".to_string(),
+            bootstrap_nodes: vec![],
+            max_peers: 10,
+        };
+
+        let network = NetworkLayer::new(&config).await.unwrap();
+        let current = PeerId::random();
+        let older = PeerId::random();
+        network.handshake(&current, network.local_capabilities()).await.unwrap();
+        let legacy = Capabilities {
+            protocol_version: MIN_PROTOCOL_VERSION,
+            consensus_features: ConsensusFeatures::CONTENT_ADDRESSED_IDS,
+            ..network.local_capabilities().clone()
+        };
+        network.handshake(&older, &legacy).await.unwrap();
+
+        let refused = Capabilities { network_type: NetworkType::Mainnet, ..legacy };
+        assert!(network.handshake(&PeerId::random(), &refused).await.is_err());
+
+        assert_eq!(network.peers_supporting(ConsensusFeatures::FINALITY_ATTESTATIONS).await, vec![current]);
+        let distribution = network.capability_distribution().await;
+        assert_eq!((distribution.peers, distribution.degraded_peers), (2, 1));
+        assert_eq!(distribution.protocol_versions.get(&MIN_PROTOCOL_VERSION), Some(&1));
+
+        network.forget_peer(&older).await;
+        assert!(network.peer_protocol(&older).await.is_none());
+        assert_eq!(network.capability_distribution().await.peers, 1);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,319 @@
+//! Peer misbehavior scoring
+//!
+//! Validation failures on messages received from a peer are reported here and
+//! accumulate into a per-peer score, weighted by how serious the offense is.
+//! Scores decay over time so that occasional mistakes are forgiven. Crossing
+//! the configured thresholds throttles the peer, then disconnects it, and
+//! finally bans it for a fixed period.
+
+use crate::{BlockchainError, core::CoreError, math::MathError, security::SecurityError};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Kinds of peer misbehavior
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Misbehavior {
+    InvalidSignature,
+    InvalidProof,
+    InvalidTimestamp,
+    OversizedMessage,
+    MalformedMessage,
+}
+
+impl Misbehavior {
+    /// Score added for a single offense
+    pub fn weight(&self) -> f64 {
+        match self {
+            Misbehavior::InvalidSignature => 50.0,
+            Misbehavior::InvalidProof => 40.0,
+            Misbehavior::MalformedMessage => 25.0,
+            Misbehavior::OversizedMessage => 20.0,
+            Misbehavior::InvalidTimestamp => 10.0,
+        }
+    }
+
+    /// Classify a validation error, if it reflects on the sending peer
+    pub fn from_error(error: &BlockchainError) -> Option<Self> {
+        match error {
+            BlockchainError::Security(SecurityError::InvalidSignature) => Some(Misbehavior::InvalidSignature),
+            BlockchainError::Security(SecurityError::InsufficientQuantumResistance)
+            | BlockchainError::Math(MathError::InvalidPrimeHash)
+            | BlockchainError::Math(MathError::InsufficientQuantumResistance) => Some(Misbehavior::InvalidProof),
+            BlockchainError::Security(SecurityError::InvalidTimestamp)
+            | BlockchainError::Math(MathError::InvalidTimestamp) => Some(Misbehavior::InvalidTimestamp),
+            BlockchainError::Serialization(_)
+            | BlockchainError::Core(CoreError::Serialization(_)) => Some(Misbehavior::MalformedMessage),
+            _ => None,
+        }
+    }
+}
+
+/// Penalty applied to a peer, in increasing order of severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerAction {
+    None,
+    Throttle,
+    Disconnect,
+    Ban,
+}
+
+/// Peer scoring configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PeerScoringConfig {
+    /// Score at which messages from the peer are rate limited
+    pub throttle_threshold: f64,
+    /// Score at which the peer is disconnected
+    pub disconnect_threshold: f64,
+    /// Score at which the peer is banned
+    pub ban_threshold: f64,
+    /// How long a ban lasts
+    pub ban_duration_secs: u64,
+    /// Score forgiven per minute without offenses
+    pub decay_per_minute: f64,
+    /// Minimum gap between accepted messages from a throttled peer
+    pub throttle_interval_ms: u64,
+    /// Largest message accepted from a peer
+    pub max_message_size: usize,
+}
+
+impl Default for PeerScoringConfig {
+    fn default() -> Self {
+        Self {
+            throttle_threshold: 50.0,
+            disconnect_threshold: 100.0,
+            ban_threshold: 200.0,
+            ban_duration_secs: 3600,
+            decay_per_minute: 5.0,
+            throttle_interval_ms: 1000,
+            max_message_size: 1024 * 1024,
+        }
+    }
+}
+
+/// Score snapshot exposed through the peers API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerScore {
+    pub peer_id: String,
+    pub score: f64,
+    pub action: PeerAction,
+    pub offenses: HashMap<Misbehavior, u32>,
+    pub banned_until: Option<u64>,
+    pub last_offense_at: u64,
+}
+
+#[derive(Debug, Clone)]
+struct ScoreEntry {
+    score: f64,
+    offenses: HashMap<Misbehavior, u32>,
+    banned_until: Option<u64>,
+    last_offense_at: u64,
+    updated_at_ms: u64,
+    last_admitted_ms: u64,
+}
+
+/// Per-peer misbehavior scores
+pub struct PeerScoreboard {
+    config: std::sync::RwLock<PeerScoringConfig>,
+    entries: RwLock<HashMap<PeerId, ScoreEntry>>,
+}
+
+impl PeerScoreboard {
+    /// Create a new scoreboard
+    pub fn new(config: PeerScoringConfig) -> Self {
+        Self {
+            config: std::sync::RwLock::new(config),
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get the scoring configuration
+    pub fn config(&self) -> PeerScoringConfig {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the scoring configuration; existing scores are kept and judged
+    /// against the new thresholds
+    pub fn set_config(&self, config: PeerScoringConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    /// Record an offense and return the resulting penalty
+    pub async fn record(&self, peer: &PeerId, misbehavior: Misbehavior) -> PeerAction {
+        let now = now_ms();
+        let mut entries = self.entries.write().await;
+        let entry = entries.entry(*peer).or_insert_with(|| ScoreEntry {
+            score: 0.0,
+            offenses: HashMap::new(),
+            banned_until: None,
+            last_offense_at: 0,
+            updated_at_ms: now,
+            last_admitted_ms: 0,
+        });
+
+        self.decay(entry, now);
+        entry.score += misbehavior.weight();
+        *entry.offenses.entry(misbehavior).or_insert(0) += 1;
+        entry.last_offense_at = now / 1000;
+
+        let config = self.config();
+        if entry.score >= config.ban_threshold && entry.banned_until.is_none() {
+            entry.banned_until = Some(now / 1000 + config.ban_duration_secs);
+        }
+
+        self.action_for(entry, now)
+    }
+
+    /// Current penalty for a peer
+    pub async fn action(&self, peer: &PeerId) -> PeerAction {
+        let now = now_ms();
+        let mut entries = self.entries.write().await;
+        match entries.get_mut(peer) {
+            Some(entry) => {
+                self.decay(entry, now);
+                self.action_for(entry, now)
+            },
+            None => PeerAction::None,
+        }
+    }
+
+    /// Decide whether to accept a message from a peer
+    ///
+    /// Throttled peers get one message per `throttle_interval_ms`; disconnected
+    /// and banned peers get none.
+    pub async fn admit(&self, peer: &PeerId) -> PeerAction {
+        let now = now_ms();
+        let mut entries = self.entries.write().await;
+        let Some(entry) = entries.get_mut(peer) else {
+            return PeerAction::None;
+        };
+
+        self.decay(entry, now);
+        let action = self.action_for(entry, now);
+        if action == PeerAction::Throttle {
+            if now.saturating_sub(entry.last_admitted_ms) < self.config().throttle_interval_ms {
+                return PeerAction::Throttle;
+            }
+            entry.last_admitted_ms = now;
+            return PeerAction::None;
+        }
+        action
+    }
+
+    /// Snapshot of all tracked peers, worst first
+    pub async fn scores(&self) -> Vec<PeerScore> {
+        let now = now_ms();
+        let mut entries = self.entries.write().await;
+        let mut scores: Vec<PeerScore> = entries.iter_mut()
+            .map(|(peer, entry)| {
+                self.decay(entry, now);
+                PeerScore {
+                    peer_id: peer.to_string(),
+                    score: entry.score,
+                    action: self.action_for(entry, now),
+                    offenses: entry.offenses.clone(),
+                    banned_until: entry.banned_until,
+                    last_offense_at: entry.last_offense_at,
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scores
+    }
+
+    /// Forget peers whose score has fully decayed and who are not banned
+    pub async fn prune(&self) -> usize {
+        let now = now_ms();
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+        entries.retain(|_, entry| {
+            self.decay(entry, now);
+            entry.score > 0.0 || entry.banned_until.is_some()
+        });
+        before - entries.len()
+    }
+
+    fn decay(&self, entry: &mut ScoreEntry, now: u64) {
+        let elapsed_minutes = now.saturating_sub(entry.updated_at_ms) as f64 / 60_000.0;
+        entry.score = (entry.score - elapsed_minutes * self.config().decay_per_minute).max(0.0);
+        entry.updated_at_ms = now;
+
+        if entry.banned_until.is_some_and(|until| until <= now / 1000) {
+            entry.banned_until = None;
+        }
+    }
+
+    fn action_for(&self, entry: &ScoreEntry, now: u64) -> PeerAction {
+        let config = self.config();
+        if entry.banned_until.is_some_and(|until| until > now / 1000) {
+            PeerAction::Ban
+        } else if entry.score >= config.disconnect_threshold {
+            PeerAction::Disconnect
+        } else if entry.score >= config.throttle_threshold {
+            PeerAction::Throttle
+        } else {
+            PeerAction::None
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> PeerScoringConfig {
+        PeerScoringConfig {
+            decay_per_minute: 0.0,
+            ..PeerScoringConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_escalating_penalties() {
+        let scoreboard = PeerScoreboard::new(test_config());
+        let peer = PeerId::random();
+
+        assert_eq!(scoreboard.record(&peer, Misbehavior::InvalidTimestamp).await, PeerAction::None);
+        assert_eq!(scoreboard.record(&peer, Misbehavior::InvalidProof).await, PeerAction::Throttle);
+        assert_eq!(scoreboard.record(&peer, Misbehavior::InvalidSignature).await, PeerAction::Disconnect);
+        assert_eq!(scoreboard.record(&peer, Misbehavior::InvalidSignature).await, PeerAction::Disconnect);
+        assert_eq!(scoreboard.record(&peer, Misbehavior::InvalidSignature).await, PeerAction::Ban);
+
+        let scores = scoreboard.scores().await;
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].offenses.get(&Misbehavior::InvalidSignature), Some(&3));
+        assert!(scores[0].banned_until.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_throttled_peer_is_rate_limited() {
+        let scoreboard = PeerScoreboard::new(test_config());
+        let peer = PeerId::random();
+        let honest = PeerId::random();
+
+        scoreboard.record(&peer, Misbehavior::InvalidSignature).await;
+        assert_eq!(scoreboard.admit(&peer).await, PeerAction::None);
+        assert_eq!(scoreboard.admit(&peer).await, PeerAction::Throttle);
+        assert_eq!(scoreboard.admit(&honest).await, PeerAction::None);
+    }
+
+    #[test]
+    fn test_classify_validation_errors() {
+        let error = BlockchainError::Security(SecurityError::InvalidSignature);
+        assert_eq!(Misbehavior::from_error(&error), Some(Misbehavior::InvalidSignature));
+
+        let error = BlockchainError::Math(MathError::InvalidPrimeHash);
+        assert_eq!(Misbehavior::from_error(&error), Some(Misbehavior::InvalidProof));
+
+        let error = BlockchainError::Other("database unavailable".to_string());
+        assert_eq!(Misbehavior::from_error(&error), None);
+    }
+}
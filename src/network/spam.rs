@@ -0,0 +1,392 @@
+//! Pre-validation spam filter for gossiped transactions
+//!
+//! Verifying a Dilithium signature and quantum proof is the most expensive
+//! part of accepting a transaction from a peer. Before that work is done,
+//! gossip is screened with cheap checks: stateless sanity checks on the
+//! transaction fields, a per-sender rate limit, and a cuckoo filter of
+//! recently seen transaction hashes that drops re-gossiped duplicates. The
+//! duplicate filter is probabilistic; a false positive drops a new
+//! transaction, which the sender's other peers will relay again.
+
+use crate::core::Transaction;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Spam filter configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpamFilterConfig {
+    /// Transactions accepted per sender address in each window
+    pub max_per_sender: u32,
+    /// Length of the per-sender rate window
+    pub sender_window_secs: u64,
+    /// Transaction hashes remembered per generation of the duplicate filter
+    pub duplicate_capacity: usize,
+    /// How far a transaction timestamp may run ahead of the local clock
+    pub max_future_skew_secs: u64,
+    /// Largest plausible signature (hybrid Ed25519 + Dilithium5 with framing)
+    pub max_signature_size: usize,
+    /// Most parents a transaction may reference
+    pub max_parents: usize,
+}
+
+impl Default for SpamFilterConfig {
+    fn default() -> Self {
+        Self {
+            max_per_sender: 50,
+            sender_window_secs: 10,
+            duplicate_capacity: 65_536,
+            max_future_skew_secs: 300,
+            max_signature_size: 8 * 1024,
+            max_parents: 8,
+        }
+    }
+}
+
+/// Why a transaction was dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpamReason {
+    Duplicate,
+    SenderRateExceeded,
+    MissingField,
+    OversizedSignature,
+    TooManyParents,
+    FutureTimestamp,
+}
+
+impl SpamReason {
+    /// Whether the reason means the message itself was malformed
+    ///
+    /// Duplicates and busy senders are normal gossip; malformed messages
+    /// count against the relaying peer.
+    pub fn is_malformed(&self) -> bool {
+        !matches!(self, SpamReason::Duplicate | SpamReason::SenderRateExceeded)
+    }
+
+    /// Metric label
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpamReason::Duplicate => "duplicate",
+            SpamReason::SenderRateExceeded => "sender_rate",
+            SpamReason::MissingField => "missing_field",
+            SpamReason::OversizedSignature => "oversized_signature",
+            SpamReason::TooManyParents => "too_many_parents",
+            SpamReason::FutureTimestamp => "future_timestamp",
+        }
+    }
+}
+
+impl std::fmt::Display for SpamReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Filter effectiveness counters
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpamFilterStats {
+    pub inspected: u64,
+    pub passed: u64,
+    pub dropped_duplicate: u64,
+    pub dropped_rate_limited: u64,
+    pub dropped_malformed: u64,
+    /// Share of inspected gossip dropped before signature verification
+    pub drop_rate: f64,
+    /// Senders currently tracked by the rate limiter
+    pub tracked_senders: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    inspected: AtomicU64,
+    passed: AtomicU64,
+    duplicate: AtomicU64,
+    rate_limited: AtomicU64,
+    malformed: AtomicU64,
+}
+
+struct SenderWindow {
+    started_at: u64,
+    count: u32,
+}
+
+/// Screens gossiped transactions before full validation
+pub struct SpamFilter {
+    config: SpamFilterConfig,
+    recent: Mutex<RecentHashes>,
+    senders: Mutex<HashMap<Vec<u8>, SenderWindow>>,
+    counters: Counters,
+}
+
+impl SpamFilter {
+    /// Create a filter
+    pub fn new(config: SpamFilterConfig) -> Self {
+        Self {
+            recent: Mutex::new(RecentHashes::new(config.duplicate_capacity)),
+            senders: Mutex::new(HashMap::new()),
+            counters: Counters::default(),
+            config,
+        }
+    }
+
+    /// Get the filter configuration
+    pub fn config(&self) -> &SpamFilterConfig {
+        &self.config
+    }
+
+    /// Screen a transaction, remembering it for duplicate detection
+    pub fn check(&self, transaction: &Transaction) -> Result<(), SpamReason> {
+        self.check_at(transaction, chrono::Utc::now().timestamp() as u64)
+    }
+
+    fn check_at(&self, transaction: &Transaction, now: u64) -> Result<(), SpamReason> {
+        self.counters.inspected.fetch_add(1, Ordering::Relaxed);
+        let result = self.screen(transaction, now);
+        let counter = match result {
+            Ok(()) => &self.counters.passed,
+            Err(SpamReason::Duplicate) => &self.counters.duplicate,
+            Err(SpamReason::SenderRateExceeded) => &self.counters.rate_limited,
+            Err(_) => &self.counters.malformed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    fn screen(&self, transaction: &Transaction, now: u64) -> Result<(), SpamReason> {
+        // Stateless checks first; they cost nothing and touch no shared state
+        if transaction.sender.is_empty() || transaction.receiver.is_empty() || transaction.signature.is_empty() {
+            return Err(SpamReason::MissingField);
+        }
+        if transaction.signature.len() > self.config.max_signature_size {
+            return Err(SpamReason::OversizedSignature);
+        }
+        if transaction.parents.len() > self.config.max_parents {
+            return Err(SpamReason::TooManyParents);
+        }
+        if transaction.timestamp > now + self.config.max_future_skew_secs {
+            return Err(SpamReason::FutureTimestamp);
+        }
+
+        if !self.recent.lock().unwrap_or_else(|e| e.into_inner()).insert(&transaction_hash(transaction)) {
+            return Err(SpamReason::Duplicate);
+        }
+
+        let mut senders = self.senders.lock().unwrap_or_else(|e| e.into_inner());
+        let window = self.config.sender_window_secs;
+        if senders.len() > self.config.duplicate_capacity {
+            senders.retain(|_, entry| now < entry.started_at + window);
+        }
+        let entry = senders.entry(transaction.sender.clone())
+            .or_insert(SenderWindow { started_at: now, count: 0 });
+        if now >= entry.started_at + window {
+            *entry = SenderWindow { started_at: now, count: 0 };
+        }
+        if entry.count >= self.config.max_per_sender {
+            return Err(SpamReason::SenderRateExceeded);
+        }
+        entry.count += 1;
+        Ok(())
+    }
+
+    /// Get filter effectiveness counters
+    pub fn stats(&self) -> SpamFilterStats {
+        let inspected = self.counters.inspected.load(Ordering::Relaxed);
+        let passed = self.counters.passed.load(Ordering::Relaxed);
+        SpamFilterStats {
+            inspected,
+            passed,
+            dropped_duplicate: self.counters.duplicate.load(Ordering::Relaxed),
+            dropped_rate_limited: self.counters.rate_limited.load(Ordering::Relaxed),
+            dropped_malformed: self.counters.malformed.load(Ordering::Relaxed),
+            drop_rate: if inspected == 0 { 0.0 } else { (inspected - passed) as f64 / inspected as f64 },
+            tracked_senders: self.senders.lock().unwrap_or_else(|e| e.into_inner()).len(),
+        }
+    }
+}
+
+impl Default for SpamFilter {
+    fn default() -> Self {
+        Self::new(SpamFilterConfig::default())
+    }
+}
+
+fn transaction_hash(transaction: &Transaction) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(transaction.id.as_bytes());
+    hasher.update(&transaction.sender);
+    hasher.update(transaction.nonce.to_le_bytes());
+    hasher.update(&transaction.signature);
+    hasher.finalize().into()
+}
+
+/// Two generations of cuckoo filters; the older one is dropped when the
+/// current one fills, so only recent hashes are remembered
+struct RecentHashes {
+    current: CuckooFilter,
+    previous: CuckooFilter,
+    capacity: usize,
+}
+
+impl RecentHashes {
+    fn new(capacity: usize) -> Self {
+        Self {
+            current: CuckooFilter::with_capacity(capacity),
+            previous: CuckooFilter::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Remember `hash`, returning false if it was (probably) seen already
+    fn insert(&mut self, hash: &[u8; 32]) -> bool {
+        if self.current.contains(hash) || self.previous.contains(hash) {
+            return false;
+        }
+        if self.current.len() >= self.capacity || !self.current.insert(hash) {
+            self.previous = std::mem::replace(&mut self.current, CuckooFilter::with_capacity(self.capacity));
+            self.current.insert(hash);
+        }
+        true
+    }
+}
+
+const BUCKET_SIZE: usize = 4;
+const MAX_KICKS: usize = 500;
+
+/// Cuckoo filter with 16-bit fingerprints and four slots per bucket
+struct CuckooFilter {
+    buckets: Vec<[u16; BUCKET_SIZE]>,
+    len: usize,
+    victim: u64,
+}
+
+impl CuckooFilter {
+    fn with_capacity(capacity: usize) -> Self {
+        // Keep the load factor around 50% so inserts rarely need long kick chains
+        let buckets = (capacity * 2 / BUCKET_SIZE).max(1).next_power_of_two();
+        Self { buckets: vec![[0; BUCKET_SIZE]; buckets], len: 0, victim: 0 }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn mask(&self) -> usize {
+        self.buckets.len() - 1
+    }
+
+    fn locate(&self, hash: &[u8; 32]) -> (u16, usize, usize) {
+        let index = u64::from_le_bytes(hash[0..8].try_into().unwrap_or_default()) as usize & self.mask();
+        // Zero marks an empty slot
+        let fingerprint = u16::from_le_bytes([hash[8], hash[9]]).max(1);
+        (fingerprint, index, self.alternate(index, fingerprint))
+    }
+
+    fn alternate(&self, index: usize, fingerprint: u16) -> usize {
+        let spread = (fingerprint as u64).wrapping_mul(0x5bd1_e995) as usize;
+        (index ^ spread) & self.mask()
+    }
+
+    fn contains(&self, hash: &[u8; 32]) -> bool {
+        let (fingerprint, first, second) = self.locate(hash);
+        self.buckets[first].contains(&fingerprint) || self.buckets[second].contains(&fingerprint)
+    }
+
+    /// Insert a hash; false when the filter is too full to place it
+    fn insert(&mut self, hash: &[u8; 32]) -> bool {
+        let (mut fingerprint, first, second) = self.locate(hash);
+        for index in [first, second] {
+            if let Some(slot) = self.buckets[index].iter_mut().find(|slot| **slot == 0) {
+                *slot = fingerprint;
+                self.len += 1;
+                return true;
+            }
+        }
+
+        let mut index = first;
+        for _ in 0..MAX_KICKS {
+            self.victim = self.victim.wrapping_add(1);
+            let slot = (self.victim as usize) % BUCKET_SIZE;
+            std::mem::swap(&mut fingerprint, &mut self.buckets[index][slot]);
+            index = self.alternate(index, fingerprint);
+            if let Some(slot) = self.buckets[index].iter_mut().find(|slot| **slot == 0) {
+                *slot = fingerprint;
+                self.len += 1;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::TransactionId;
+
+    fn transaction(sender: u8) -> Transaction {
+        Transaction {
+            id: TransactionId::new(),
+            sender: vec![sender; 32],
+            receiver: vec![9; 32],
+            amount: 1,
+            fee: 0,
+            nonce: 0,
+            timestamp: 1_000,
+            parents: vec![],
+            signature: vec![1; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 0, proof_timestamp: 0 },
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_duplicates_and_malformed_are_dropped() {
+        let filter = SpamFilter::default();
+        let tx = transaction(1);
+        assert_eq!(filter.check_at(&tx, 1_000), Ok(()));
+        assert_eq!(filter.check_at(&tx, 1_000), Err(SpamReason::Duplicate));
+
+        let mut unsigned = transaction(1);
+        unsigned.signature.clear();
+        assert_eq!(filter.check_at(&unsigned, 1_000), Err(SpamReason::MissingField));
+
+        let mut future = transaction(1);
+        future.timestamp = 1_000 + 3_600;
+        assert_eq!(filter.check_at(&future, 1_000), Err(SpamReason::FutureTimestamp));
+
+        let stats = filter.stats();
+        assert_eq!((stats.inspected, stats.passed, stats.dropped_duplicate, stats.dropped_malformed), (4, 1, 1, 2));
+        assert_eq!(stats.drop_rate, 0.75);
+    }
+
+    #[test]
+    fn test_sender_rate_window() {
+        let filter = SpamFilter::new(SpamFilterConfig { max_per_sender: 2, sender_window_secs: 10, ..Default::default() });
+        assert!(filter.check_at(&transaction(1), 1_000).is_ok());
+        assert!(filter.check_at(&transaction(1), 1_001).is_ok());
+        assert_eq!(filter.check_at(&transaction(1), 1_002), Err(SpamReason::SenderRateExceeded));
+        assert!(filter.check_at(&transaction(2), 1_002).is_ok());
+        assert!(filter.check_at(&transaction(1), 1_010).is_ok());
+    }
+
+    #[test]
+    fn test_recent_hashes_forget_old_generations() {
+        let mut recent = RecentHashes::new(64);
+        let hashes: Vec<[u8; 32]> = (0..200u32)
+            .map(|i| Sha3_256::digest(i.to_le_bytes()).into())
+            .collect();
+
+        for hash in &hashes {
+            assert!(recent.insert(hash));
+        }
+        // The newest hashes are still remembered, the oldest have aged out
+        assert!(!recent.insert(&hashes[199]));
+        assert!(recent.insert(&hashes[0]));
+    }
+}
@@ -0,0 +1,235 @@
+//! Address risk screening
+//!
+//! A `ComplianceProvider` is consulted for every transaction during
+//! validation. It sees the hex-encoded sender and receiver and answers allow,
+//! deny or review. Denied transactions are rejected; transactions flagged for
+//! review are accepted but recorded with a warning so an operator can follow
+//! up. Every decision is kept in an audit log.
+
+use crate::BlockchainError;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Outcome of screening a pair of addresses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ComplianceDecision {
+    Allow,
+    Deny { reasons: Vec<String> },
+    Review { reasons: Vec<String> },
+}
+
+impl ComplianceDecision {
+    /// Reasons attached to the decision
+    pub fn reasons(&self) -> &[String] {
+        match self {
+            ComplianceDecision::Allow => &[],
+            ComplianceDecision::Deny { reasons } | ComplianceDecision::Review { reasons } => reasons,
+        }
+    }
+}
+
+/// Screens transaction addresses against a compliance source
+#[async_trait]
+pub trait ComplianceProvider: Send + Sync {
+    /// Provider name recorded in the audit log
+    fn name(&self) -> String;
+
+    /// Screen a transfer from `sender` to `receiver`
+    async fn screen(&self, sender: &str, receiver: &str) -> Result<ComplianceDecision, BlockchainError>;
+}
+
+/// Provider that allows everything
+#[derive(Debug, Default)]
+pub struct NoopComplianceProvider;
+
+#[async_trait]
+impl ComplianceProvider for NoopComplianceProvider {
+    fn name(&self) -> String {
+        "noop".to_string()
+    }
+
+    async fn screen(&self, _sender: &str, _receiver: &str) -> Result<ComplianceDecision, BlockchainError> {
+        Ok(ComplianceDecision::Allow)
+    }
+}
+
+/// Provider backed by static deny and review lists
+#[derive(Debug, Default)]
+pub struct ListComplianceProvider {
+    deny: HashMap<String, String>,
+    review: HashMap<String, String>,
+}
+
+impl ListComplianceProvider {
+    /// Create an empty list provider
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deny transfers involving `address`
+    pub fn deny(mut self, address: &str, reason: &str) -> Self {
+        self.deny.insert(address.to_lowercase(), reason.to_string());
+        self
+    }
+
+    /// Flag transfers involving `address` for review
+    pub fn review(mut self, address: &str, reason: &str) -> Self {
+        self.review.insert(address.to_lowercase(), reason.to_string());
+        self
+    }
+
+    fn matches(list: &HashMap<String, String>, sender: &str, receiver: &str) -> Vec<String> {
+        [("sender", sender), ("receiver", receiver)]
+            .iter()
+            .filter_map(|(role, address)| {
+                list.get(&address.to_lowercase()).map(|reason| format!("{} {}: {}", role, address, reason))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ComplianceProvider for ListComplianceProvider {
+    fn name(&self) -> String {
+        "list".to_string()
+    }
+
+    async fn screen(&self, sender: &str, receiver: &str) -> Result<ComplianceDecision, BlockchainError> {
+        let reasons = Self::matches(&self.deny, sender, receiver);
+        if !reasons.is_empty() {
+            return Ok(ComplianceDecision::Deny { reasons });
+        }
+
+        let reasons = Self::matches(&self.review, sender, receiver);
+        if !reasons.is_empty() {
+            return Ok(ComplianceDecision::Review { reasons });
+        }
+
+        Ok(ComplianceDecision::Allow)
+    }
+}
+
+/// Audit record of a screening decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceRecord {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub provider: String,
+    pub transaction_id: String,
+    pub sender: String,
+    pub receiver: String,
+    pub decision: ComplianceDecision,
+}
+
+/// Runs a provider and keeps an audit log of its decisions
+pub struct ComplianceScreener {
+    provider: RwLock<Arc<dyn ComplianceProvider>>,
+    records: RwLock<VecDeque<ComplianceRecord>>,
+    max_records: usize,
+}
+
+impl ComplianceScreener {
+    /// Create a screener around `provider`
+    pub fn new(provider: Arc<dyn ComplianceProvider>) -> Self {
+        Self {
+            provider: RwLock::new(provider),
+            records: RwLock::new(VecDeque::new()),
+            max_records: 10_000,
+        }
+    }
+
+    /// Replace the provider used for subsequent screenings
+    pub async fn set_provider(&self, provider: Arc<dyn ComplianceProvider>) {
+        log::info!("🛂 Compliance provider set to {}", provider.name());
+        *self.provider.write().await = provider;
+    }
+
+    /// Screen a transaction and record the decision
+    pub async fn screen(
+        &self,
+        transaction_id: &str,
+        sender: &str,
+        receiver: &str,
+    ) -> Result<ComplianceDecision, BlockchainError> {
+        let provider = self.provider.read().await.clone();
+        let decision = provider.screen(sender, receiver).await?;
+
+        match &decision {
+            ComplianceDecision::Allow => {},
+            ComplianceDecision::Deny { reasons } => {
+                log::warn!("⛔ Compliance denied transaction {}: {}", transaction_id, reasons.join("; "));
+            },
+            ComplianceDecision::Review { reasons } => {
+                log::warn!("🔎 Transaction {} flagged for compliance review: {}", transaction_id, reasons.join("; "));
+            },
+        }
+
+        let mut records = self.records.write().await;
+        if records.len() >= self.max_records {
+            records.pop_front();
+        }
+        records.push_back(ComplianceRecord {
+            timestamp: chrono::Utc::now(),
+            provider: provider.name(),
+            transaction_id: transaction_id.to_string(),
+            sender: sender.to_string(),
+            receiver: receiver.to_string(),
+            decision: decision.clone(),
+        });
+
+        Ok(decision)
+    }
+
+    /// Recorded decisions, oldest first
+    pub async fn records(&self) -> Vec<ComplianceRecord> {
+        self.records.read().await.iter().cloned().collect()
+    }
+}
+
+impl Default for ComplianceScreener {
+    fn default() -> Self {
+        Self::new(Arc::new(NoopComplianceProvider))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_provider_decisions() {
+        let provider = ListComplianceProvider::new()
+            .deny("aa", "sanctioned")
+            .review("bb", "high risk exchange");
+
+        let decision = provider.screen("AA", "cc").await.unwrap();
+        assert!(matches!(decision, ComplianceDecision::Deny { .. }));
+        assert_eq!(decision.reasons(), ["sender AA: sanctioned"]);
+
+        let decision = provider.screen("cc", "bb").await.unwrap();
+        assert!(matches!(decision, ComplianceDecision::Review { .. }));
+
+        // Deny takes precedence over review
+        let decision = provider.screen("bb", "aa").await.unwrap();
+        assert!(matches!(decision, ComplianceDecision::Deny { .. }));
+
+        assert_eq!(provider.screen("cc", "dd").await.unwrap(), ComplianceDecision::Allow);
+    }
+
+    #[tokio::test]
+    async fn test_screener_records_decisions() {
+        let screener = ComplianceScreener::new(Arc::new(ListComplianceProvider::new().deny("aa", "sanctioned")));
+
+        screener.screen("tx1", "cc", "dd").await.unwrap();
+        screener.screen("tx2", "aa", "dd").await.unwrap();
+
+        let records = screener.records().await;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].decision, ComplianceDecision::Allow);
+        assert_eq!(records[1].transaction_id, "tx2");
+        assert_eq!(records[1].provider, "list");
+    }
+}
@@ -1,8 +1,16 @@
 //! Security layer for blockchain protection
 
 use crate::{BlockchainError, TransactionId};
+use crate::core::{payload_size, Amount, FeePolicy};
 use std::collections::HashMap;
 use tokio::sync::RwLock;
+use std::sync::Arc;
+
+pub mod compliance;
+pub use compliance::{
+    ComplianceDecision, ComplianceProvider, ComplianceRecord, ComplianceScreener,
+    ListComplianceProvider, NoopComplianceProvider,
+};
 
 /// Security configuration
 #[derive(Debug, Clone)]
@@ -10,6 +18,10 @@ pub struct SecurityConfig {
     pub quantum_resistance_level: u32,
     pub signature_scheme: String,
     pub key_rotation_interval_hours: u64,
+    /// SQLCipher key of the database; `QDAG_DATABASE_KEY` is used when unset
+    pub database_key: Option<String>,
+    /// Passphrase of the identity file; `QDAG_IDENTITY_PASSPHRASE` is used when plaintext
+    pub identity_key: crate::identity::IdentityKeySource,
 }
 
 /// Security manager implementation
@@ -17,6 +29,8 @@ pub struct SecurityManager {
     config: SecurityConfig,
     threat_level: ThreatLevel,
     blocked_addresses: HashMap<String, std::time::Instant>,
+    compliance: ComplianceScreener,
+    fee_policy: std::sync::RwLock<FeePolicy>,
     is_running: bool,
 }
 
@@ -36,6 +50,8 @@ impl SecurityManager {
             config: config.clone(),
             threat_level: ThreatLevel::Low,
             blocked_addresses: HashMap::new(),
+            compliance: ComplianceScreener::default(),
+            fee_policy: std::sync::RwLock::new(FeePolicy::default()),
             is_running: false,
         })
     }
@@ -68,6 +84,24 @@ impl SecurityManager {
             return Err(BlockchainError::Security(SecurityError::AddressBlocked(sender_addr)));
         }
 
+        // Screen addresses with the compliance provider
+        let receiver_addr = hex::encode(&transaction.receiver);
+        let decision = self.compliance
+            .screen(&transaction.id.to_string(), &sender_addr, &receiver_addr)
+            .await?;
+        if let ComplianceDecision::Deny { reasons } = decision {
+            return Err(BlockchainError::Security(SecurityError::ComplianceDenied(reasons.join("; "))));
+        }
+
+        // Check the fee covers the minimum for its size
+        let required = self.fee_policy().required_fee(payload_size(transaction));
+        if transaction.fee < required {
+            return Err(BlockchainError::Security(SecurityError::InsufficientFee {
+                required,
+                offered: transaction.fee,
+            }));
+        }
+
         // Validate signature
         if !self.validate_signature(&transaction.sender, &transaction.signature, &transaction.id)? {
             return Err(BlockchainError::Security(SecurityError::InvalidSignature));
@@ -127,6 +161,26 @@ impl SecurityManager {
         self.blocked_addresses.insert(address, std::time::Instant::now());
     }
 
+    /// Set the provider used to screen transaction addresses
+    pub async fn set_compliance_provider(&self, provider: Arc<dyn ComplianceProvider>) {
+        self.compliance.set_provider(provider).await;
+    }
+
+    /// Replace the minimum fee policy
+    pub fn set_fee_policy(&self, policy: FeePolicy) {
+        *self.fee_policy.write().unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+
+    /// Current minimum fee policy
+    pub fn fee_policy(&self) -> FeePolicy {
+        self.fee_policy.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Get the compliance decisions recorded so far
+    pub async fn compliance_records(&self) -> Vec<ComplianceRecord> {
+        self.compliance.records().await
+    }
+
     /// Get current threat level
     pub fn threat_level(&self) -> ThreatLevel {
         self.threat_level.clone()
@@ -142,7 +196,7 @@ impl SecurityManager {
 
     /// Start threat detection
     async fn start_threat_detection(&self) {
-        tokio::spawn(async {
+        crate::spawn_instrumented(crate::Subsystem::Security, "threat_detection", async {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
             
             while interval.tick().await.is_some() {
@@ -164,7 +218,7 @@ impl SecurityManager {
             self.config.key_rotation_interval_hours * 3600
         );
         
-        tokio::spawn(async move {
+        crate::spawn_instrumented(crate::Subsystem::Security, "key_rotation", async move {
             let mut interval = tokio::time::interval(rotation_interval);
             
             while interval.tick().await.is_some() {
@@ -218,6 +272,10 @@ pub enum SecurityError {
     KeyGenerationFailed(String),
     #[error("Encryption failed: {0}")]
     EncryptionFailed(String),
+    #[error("Denied by compliance screening: {0}")]
+    ComplianceDenied(String),
+    #[error("Insufficient fee: {offered} offered, {required} required")]
+    InsufficientFee { required: Amount, offered: Amount },
 }
 
 /// Security service trait
@@ -256,6 +314,8 @@ mod tests {
             quantum_resistance_level: 128,
             signature_scheme: "dilithium".to_string(),
             key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
         };
 
         let manager = SecurityManager::new(&config);
@@ -272,6 +332,8 @@ mod tests {
             quantum_resistance_level: 128,
             signature_scheme: "dilithium".to_string(),
             key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
         };
 
         let mut manager = SecurityManager::new(&config).unwrap();
@@ -289,6 +351,8 @@ mod tests {
             quantum_resistance_level: 128,
             signature_scheme: "dilithium".to_string(),
             key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
         };
 
         let mut manager = SecurityManager::new(&config).unwrap();
@@ -305,6 +369,8 @@ mod tests {
             quantum_resistance_level: 128,
             signature_scheme: "dilithium".to_string(),
             key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
         };
 
         let manager = SecurityManager::new(&config).unwrap();
@@ -327,12 +393,49 @@ mod tests {
         assert!(!result.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_fee_below_policy_is_rejected() {
+        use crate::core::{QuantumProof, Transaction};
+
+        let config = SecurityConfig {
+            quantum_resistance_level: 0,
+            signature_scheme: "dilithium".to_string(),
+            key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
+        };
+        let manager = SecurityManager::new(&config).unwrap();
+        manager.set_fee_policy(FeePolicy { min_fee: 10, min_fee_rate: 1 });
+
+        let transaction = Transaction {
+            id: TransactionId::new(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 100,
+            fee: 9,
+            nonce: 1,
+            timestamp: 0,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        let result = manager.validate_transaction(&transaction).await;
+        assert!(matches!(
+            result,
+            Err(BlockchainError::Security(SecurityError::InsufficientFee { required: 10, offered: 9 }))
+        ));
+    }
+
     #[test]
     fn test_security_report() {
         let config = SecurityConfig {
             quantum_resistance_level: 128,
             signature_scheme: "dilithium".to_string(),
             key_rotation_interval_hours: 24,
+            database_key: None,
+            identity_key: Default::default(),
         };
 
         let manager = SecurityManager::new(&config).unwrap();
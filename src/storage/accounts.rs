@@ -0,0 +1,210 @@
+//! Stored account balances
+//!
+//! `account_balances` holds the balance of every address that has been
+//! credited. `applied_transactions` records which finalized transactions have
+//! been applied, so applying one twice, e.g. when a finalization is replayed
+//! after a restart, leaves balances unchanged.
+
+use super::DatabaseManager;
+use crate::core::{account_address, Amount, CoreError, Transaction};
+use crate::BlockchainError;
+use chrono::Utc;
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+
+impl DatabaseManager {
+    /// Balance of a hex address; unknown addresses hold nothing
+    pub async fn get_balance(&self, address: &str) -> Result<Amount, BlockchainError> {
+        let row = sqlx::query("SELECT balance FROM account_balances WHERE address = ?")
+            .bind(address)
+            .fetch_optional(&self.pool().await)
+            .await?;
+        row.map_or(Ok(0), |row| read_amount(&row, "balance"))
+    }
+
+    /// Add funds to an address outside of any transaction, returning the new balance
+    ///
+    /// Used for allocations such as a test network faucet's starting funds.
+    pub async fn credit_account(&self, address: &str, amount: Amount) -> Result<Amount, BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        credit(&mut tx, address, amount).await?;
+        tx.commit().await?;
+        self.get_balance(address).await
+    }
+
+    /// Debit the sender and credit the receiver of a finalized transaction
+    ///
+    /// The sender pays the amount plus the fee; the fee is credited to the
+    /// round's validator separately. Returns `false` if the transaction was
+    /// already applied. Fails without changing anything if the sender cannot
+    /// cover the amount and fee.
+    pub async fn apply_finalized_transaction(&self, transaction: &Transaction) -> Result<bool, BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        let inserted = sqlx::query("INSERT OR IGNORE INTO applied_transactions (transaction_id, applied_at) VALUES (?, ?)")
+            .bind(transaction.id.as_string())
+            .bind(Utc::now().timestamp())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        let sender = account_address(&transaction.sender);
+        debit(&mut tx, &sender, transaction.amount.saturating_add(transaction.fee)).await?;
+        credit(&mut tx, &account_address(&transaction.receiver), transaction.amount).await?;
+
+        tx.commit().await?;
+        log::debug!("💰 Applied {} from {} to balances", transaction.amount, transaction.id);
+        Ok(true)
+    }
+
+    /// Number of finalized transactions applied to balances
+    pub async fn get_applied_transaction_count(&self) -> Result<u64, BlockchainError> {
+        let row = sqlx::query("SELECT COUNT(*) FROM applied_transactions")
+            .fetch_one(&self.pool().await)
+            .await?;
+        Ok(row.get::<i64, _>(0) as u64)
+    }
+}
+
+/// Balance of an address within a storage transaction
+async fn balance_in(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, address: &str) -> Result<Amount, BlockchainError> {
+    let row = sqlx::query("SELECT balance FROM account_balances WHERE address = ?")
+        .bind(address)
+        .fetch_optional(&mut **tx)
+        .await?;
+    row.map_or(Ok(0), |row| read_amount(&row, "balance"))
+}
+
+async fn set_balance(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, address: &str, balance: Amount) -> Result<(), BlockchainError> {
+    sqlx::query(
+        r#"
+        INSERT INTO account_balances (address, balance, updated_at) VALUES (?, ?, ?)
+        ON CONFLICT(address) DO UPDATE SET balance = excluded.balance, updated_at = excluded.updated_at
+        "#
+    )
+    .bind(address)
+    .bind(stored_amount(balance))
+    .bind(Utc::now().timestamp())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Take `amount` from an address, failing if its balance does not cover it
+pub(super) async fn debit(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, address: &str, required: Amount) -> Result<(), BlockchainError> {
+    if required == 0 {
+        return Ok(());
+    }
+    let available = balance_in(tx, address).await?;
+    if available < required {
+        return Err(CoreError::InsufficientBalance { address: address.to_string(), available, required }.into());
+    }
+    set_balance(tx, address, available - required).await
+}
+
+pub(super) async fn credit(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, address: &str, amount: Amount) -> Result<(), BlockchainError> {
+    let balance = balance_in(tx, address).await?.checked_add(amount)
+        .ok_or_else(|| BlockchainError::Other(format!("Crediting {} to {} overflows its balance", amount, address)))?;
+    set_balance(tx, address, balance).await
+}
+
+/// Amounts are stored as decimal text, since SQLite integers stop at `i64::MAX`
+pub(super) fn stored_amount(amount: Amount) -> String {
+    amount.to_string()
+}
+
+/// Read an amount column written by `stored_amount`
+pub(super) fn read_amount(row: &SqliteRow, column: &str) -> Result<Amount, BlockchainError> {
+    let value: String = row.try_get(column)?;
+    value.parse().map_err(|_| BlockchainError::Other(format!("Stored {} {:?} is not an amount", column, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::storage::DatabaseConfig;
+    use crate::TransactionId;
+    use tempfile::TempDir;
+
+    fn transfer(sender: u8, receiver: u8, amount: Amount) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![sender; 32],
+            receiver: vec![receiver; 32],
+            amount,
+            fee: 0,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![1u8; 32],
+                resistance_score: 80,
+                proof_timestamp: 1_700_000_000,
+            },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[tokio::test]
+    async fn test_finalized_transfer_moves_funds_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        let (alice, bob) = (account_address(&[1u8; 32]), account_address(&[2u8; 32]));
+
+        assert_eq!(db.credit_account(&alice, 100).await.unwrap(), 100);
+        let payment = transfer(1, 2, 70);
+        assert!(db.apply_finalized_transaction(&payment).await.unwrap());
+        assert!(!db.apply_finalized_transaction(&payment).await.unwrap());
+
+        assert_eq!(db.get_balance(&alice).await.unwrap(), 30);
+        assert_eq!(db.get_balance(&bob).await.unwrap(), 70);
+        assert_eq!(db.get_applied_transaction_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_overdraft_changes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        let alice = account_address(&[1u8; 32]);
+        db.credit_account(&alice, 10).await.unwrap();
+
+        let payment = transfer(1, 2, 11);
+        assert!(matches!(
+            db.apply_finalized_transaction(&payment).await,
+            Err(BlockchainError::Core(CoreError::InsufficientBalance { available: 10, required: 11, .. }))
+        ));
+        assert_eq!(db.get_balance(&alice).await.unwrap(), 10);
+        assert_eq!(db.get_balance(&account_address(&[2u8; 32])).await.unwrap(), 0);
+        assert_eq!(db.get_applied_transaction_count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_balances_beyond_u64() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        let alice = account_address(&[1u8; 32]);
+        let supply: Amount = 10u128.pow(27);
+
+        assert_eq!(db.credit_account(&alice, supply).await.unwrap(), supply);
+        assert!(db.apply_finalized_transaction(&transfer(1, 2, supply - 1)).await.unwrap());
+        assert_eq!(db.get_balance(&alice).await.unwrap(), 1);
+        assert_eq!(db.get_balance(&account_address(&[2u8; 32])).await.unwrap(), supply - 1);
+        assert!(db.credit_account(&alice, Amount::MAX).await.is_err());
+    }
+}
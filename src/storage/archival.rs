@@ -0,0 +1,694 @@
+//! Long-term archival to content-addressed cold storage
+//!
+//! Every finality advance is recorded as a milestone: the height it reached
+//! and the transactions it finalized. The archiver packages complete ranges
+//! of `milestone_span` heights into bundles. A bundle holds a manifest
+//! listing each transaction's height and SHA3-256 hash, a root over those
+//! entries, and the transactions themselves, as gzip-compressed JSON. It is
+//! addressed by the SHA3-256 of its bytes, so any copy can be checked
+//! against its content ID wherever it was fetched from.
+//!
+//! Bundles are uploaded to a pluggable `ColdStorage` backend, and the node
+//! anchors each content ID and manifest root on chain with an
+//! `ArchiveAnchor` payload. Nodes record finalized anchors. Retrieval checks
+//! the bytes against the content ID, every transaction against the manifest,
+//! and the manifest against the finalized anchor.
+
+use super::DatabaseManager;
+use crate::core::Transaction;
+use crate::events::{EventHandler, NodeEvent};
+use crate::{BlockchainError, TransactionId};
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use sqlx::Row;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Version of the bundle layout
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Domain separating manifest roots from other hashes
+const MANIFEST_ROOT_DOMAIN: &[u8] = b"quantum-dag-archive-manifest";
+
+/// What the archiver packages, and how often
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchivalConfig {
+    /// Finalized heights per bundle
+    pub milestone_span: u64,
+    /// Seconds between archival runs
+    pub interval_secs: u64,
+}
+
+impl Default for ArchivalConfig {
+    fn default() -> Self {
+        Self {
+            milestone_span: 1000,
+            interval_secs: 3600,
+        }
+    }
+}
+
+/// One transaction listed in a bundle manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub tx_id: String,
+    /// Height at which the transaction was finalized
+    pub height: u64,
+    /// Hex SHA3-256 of the transaction's JSON encoding
+    pub hash: String,
+}
+
+/// Contents of a bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub created_at: u64,
+    /// Entries in the order of the bundled transactions
+    pub entries: Vec<ManifestEntry>,
+    /// Hex digest over the height range and entries
+    pub root: String,
+}
+
+/// Finalized transactions of a milestone range with their manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveBundle {
+    pub manifest: ArchiveManifest,
+    pub transactions: Vec<Transaction>,
+}
+
+impl ArchiveBundle {
+    /// Package transactions finalized at heights `from_height..=to_height`
+    pub fn pack(from_height: u64, to_height: u64, milestones: Vec<(u64, Transaction)>) -> Result<Self, ArchivalError> {
+        let mut entries = Vec::with_capacity(milestones.len());
+        let mut transactions = Vec::with_capacity(milestones.len());
+        for (height, transaction) in milestones {
+            entries.push(ManifestEntry {
+                tx_id: transaction.id.as_string(),
+                height,
+                hash: transaction_hash(&transaction)?,
+            });
+            transactions.push(transaction);
+        }
+        Ok(Self {
+            manifest: ArchiveManifest {
+                format_version: ARCHIVE_FORMAT_VERSION,
+                from_height,
+                to_height,
+                created_at: Utc::now().timestamp() as u64,
+                root: manifest_root(from_height, to_height, &entries),
+                entries,
+            },
+            transactions,
+        })
+    }
+
+    /// Bundle bytes, as uploaded and addressed
+    pub fn encode(&self) -> Result<Vec<u8>, ArchivalError> {
+        let data = serde_json::to_vec(self).map_err(|e| ArchivalError::Corrupt(e.to_string()))?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&data).map_err(|e| ArchivalError::Corrupt(e.to_string()))?;
+        encoder.finish().map_err(|e| ArchivalError::Corrupt(e.to_string()))
+    }
+
+    /// Read bundle bytes; `verify` before trusting the result
+    pub fn decode(data: &[u8]) -> Result<Self, ArchivalError> {
+        let mut json = Vec::new();
+        GzDecoder::new(data).read_to_end(&mut json).map_err(|e| ArchivalError::Corrupt(e.to_string()))?;
+        serde_json::from_slice(&json).map_err(|e| ArchivalError::Corrupt(e.to_string()))
+    }
+
+    /// Check every transaction against the manifest and the manifest against its root
+    pub fn verify(&self) -> Result<(), ArchivalError> {
+        let manifest = &self.manifest;
+        if manifest.entries.len() != self.transactions.len() {
+            return Err(ArchivalError::Corrupt(format!(
+                "manifest lists {} transactions, bundle holds {}",
+                manifest.entries.len(),
+                self.transactions.len()
+            )));
+        }
+        for (entry, transaction) in manifest.entries.iter().zip(&self.transactions) {
+            if entry.tx_id != transaction.id.as_string() || !transaction.has_valid_id() {
+                return Err(ArchivalError::Corrupt(format!("transaction {} does not match its ID", entry.tx_id)));
+            }
+            if entry.hash != transaction_hash(transaction)? {
+                return Err(ArchivalError::Corrupt(format!("transaction {} does not match its hash", entry.tx_id)));
+            }
+            if entry.height < manifest.from_height || entry.height > manifest.to_height {
+                return Err(ArchivalError::Corrupt(format!("transaction {} lies outside the range", entry.tx_id)));
+            }
+        }
+        if manifest_root(manifest.from_height, manifest.to_height, &manifest.entries) != manifest.root {
+            return Err(ArchivalError::Corrupt("manifest root does not match its entries".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Content ID of bundle bytes: their hex SHA3-256
+pub fn content_id(data: &[u8]) -> String {
+    hex::encode(Sha3_256::digest(data))
+}
+
+fn transaction_hash(transaction: &Transaction) -> Result<String, ArchivalError> {
+    let data = serde_json::to_vec(transaction).map_err(|e| ArchivalError::Corrupt(e.to_string()))?;
+    Ok(hex::encode(Sha3_256::digest(data)))
+}
+
+fn manifest_root(from_height: u64, to_height: u64, entries: &[ManifestEntry]) -> String {
+    let mut hasher = Sha3_256::new();
+    hasher.update(MANIFEST_ROOT_DOMAIN);
+    hasher.update(from_height.to_le_bytes());
+    hasher.update(to_height.to_le_bytes());
+    for entry in entries {
+        hasher.update(entry.height.to_le_bytes());
+        hasher.update((entry.tx_id.len() as u64).to_le_bytes());
+        hasher.update(entry.tx_id.as_bytes());
+        hasher.update(entry.hash.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Bundle this node uploaded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveRecord {
+    pub content_id: String,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub transaction_count: u64,
+    pub manifest_root: String,
+    pub size_bytes: u64,
+    /// Backend holding the bundle
+    pub backend: String,
+    /// Where the backend put it
+    pub locator: String,
+    pub created_at: u64,
+    /// Transaction anchoring the bundle on chain, once submitted
+    pub anchor_tx: Option<TransactionId>,
+}
+
+/// Finalized on-chain record of a bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveAnchor {
+    pub content_id: String,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub manifest_root: String,
+    pub anchored_by: TransactionId,
+    pub anchored_at: u64,
+}
+
+/// Cold storage that bundles are uploaded to
+#[async_trait::async_trait]
+pub trait ColdStorage: Send + Sync {
+    /// Backend name recorded with each upload
+    fn name(&self) -> String;
+
+    /// Store a bundle under its content ID, returning where it was put
+    async fn put(&self, content_id: &str, data: &[u8]) -> Result<String, ArchivalError>;
+
+    /// Fetch the bundle stored under a content ID
+    async fn get(&self, content_id: &str) -> Result<Vec<u8>, ArchivalError>;
+}
+
+/// Keeps bundles as files named by content ID, e.g. on a mounted archive volume
+pub struct FilesystemColdStorage {
+    dir: PathBuf,
+}
+
+impl FilesystemColdStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, content_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.qdag-archive", content_id))
+    }
+}
+
+#[async_trait::async_trait]
+impl ColdStorage for FilesystemColdStorage {
+    fn name(&self) -> String {
+        "filesystem".to_string()
+    }
+
+    async fn put(&self, content_id: &str, data: &[u8]) -> Result<String, ArchivalError> {
+        let path = self.path(content_id);
+        tokio::fs::create_dir_all(&self.dir).await.map_err(|e| ArchivalError::Backend(e.to_string()))?;
+        tokio::fs::write(&path, data).await.map_err(|e| ArchivalError::Backend(e.to_string()))?;
+        Ok(path.display().to_string())
+    }
+
+    async fn get(&self, content_id: &str) -> Result<Vec<u8>, ArchivalError> {
+        tokio::fs::read(self.path(content_id)).await.map_err(|e| ArchivalError::Backend(e.to_string()))
+    }
+}
+
+/// Uploads bundles with HTTP PUT to `<base_url>/<content_id>` and fetches them with GET
+///
+/// Works with S3-compatible buckets, including ones whose lifecycle rules
+/// move objects to Glacier, and with IPFS pinning gateways that accept
+/// uploads by name.
+pub struct HttpColdStorage {
+    base_url: String,
+    bearer_token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl HttpColdStorage {
+    pub fn new(base_url: &str, bearer_token: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            bearer_token,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.bearer_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ColdStorage for HttpColdStorage {
+    fn name(&self) -> String {
+        "http".to_string()
+    }
+
+    async fn put(&self, content_id: &str, data: &[u8]) -> Result<String, ArchivalError> {
+        let url = format!("{}/{}", self.base_url, content_id);
+        let response = self.authorized(self.client.put(&url))
+            .body(data.to_vec())
+            .send()
+            .await
+            .map_err(|e| ArchivalError::Backend(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ArchivalError::Backend(format!("upload to {} failed with status {}", url, response.status())));
+        }
+        Ok(url)
+    }
+
+    async fn get(&self, content_id: &str) -> Result<Vec<u8>, ArchivalError> {
+        let url = format!("{}/{}", self.base_url, content_id);
+        let response = self.authorized(self.client.get(&url))
+            .send()
+            .await
+            .map_err(|e| ArchivalError::Backend(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ArchivalError::Backend(format!("fetch from {} failed with status {}", url, response.status())));
+        }
+        let data = response.bytes().await.map_err(|e| ArchivalError::Backend(e.to_string()))?;
+        Ok(data.to_vec())
+    }
+}
+
+/// Packages finalized milestone ranges and uploads them to cold storage
+pub struct Archiver {
+    config: ArchivalConfig,
+    backend: Arc<dyn ColdStorage>,
+}
+
+impl Archiver {
+    pub fn new(config: ArchivalConfig, backend: Arc<dyn ColdStorage>) -> Self {
+        Self { config, backend }
+    }
+
+    pub fn config(&self) -> &ArchivalConfig {
+        &self.config
+    }
+
+    pub fn backend_name(&self) -> String {
+        self.backend.name()
+    }
+
+    /// Next milestone range not yet archived, once later heights have finalized
+    pub async fn next_range(&self, database: &DatabaseManager) -> Result<Option<(u64, u64)>, BlockchainError> {
+        let archived_to = database.get_archived_height().await?;
+        let Some(from_height) = database.next_milestone_height(archived_to).await? else {
+            return Ok(None);
+        };
+        let to_height = from_height.saturating_add(self.config.milestone_span.max(1) - 1);
+        // Heights only grow, so nothing more can finalize in the range once a later height has
+        let complete = database.latest_milestone_height().await?.is_some_and(|latest| latest > to_height);
+        Ok(complete.then_some((from_height, to_height)))
+    }
+
+    /// Package and upload the next complete milestone range, if there is one
+    pub async fn archive_next(&self, database: &DatabaseManager) -> Result<Option<ArchiveRecord>, BlockchainError> {
+        let Some((from_height, to_height)) = self.next_range(database).await? else {
+            return Ok(None);
+        };
+        let mut milestones = Vec::new();
+        for (height, tx_id) in database.get_milestone_transactions(from_height, to_height).await? {
+            let transaction = database.get_transaction(&tx_id).await?
+                .ok_or_else(|| ArchivalError::MissingTransaction(tx_id.as_string()))?;
+            milestones.push((height, transaction));
+        }
+
+        let bundle = ArchiveBundle::pack(from_height, to_height, milestones)?;
+        let data = bundle.encode()?;
+        let content_id = content_id(&data);
+        let locator = self.backend.put(&content_id, &data).await?;
+        let record = ArchiveRecord {
+            content_id,
+            from_height,
+            to_height,
+            transaction_count: bundle.transactions.len() as u64,
+            manifest_root: bundle.manifest.root,
+            size_bytes: data.len() as u64,
+            backend: self.backend.name(),
+            locator,
+            created_at: Utc::now().timestamp() as u64,
+            anchor_tx: None,
+        };
+        database.store_archive(&record).await?;
+
+        log::info!(
+            "🧊 Archived heights {}..={} ({} transactions, {} bytes) as {}",
+            from_height, to_height, record.transaction_count, record.size_bytes, record.content_id
+        );
+        Ok(Some(record))
+    }
+
+    /// Fetch a bundle and verify it against its content ID and on-chain anchor
+    pub async fn retrieve(&self, database: &DatabaseManager, content_id: &str) -> Result<ArchiveBundle, BlockchainError> {
+        let anchor = database.get_archive_anchor(content_id).await?
+            .ok_or_else(|| ArchivalError::NotAnchored(content_id.to_string()))?;
+        let data = self.backend.get(content_id).await?;
+        let actual = self::content_id(&data);
+        if actual != content_id {
+            return Err(ArchivalError::ContentMismatch { expected: content_id.to_string(), actual }.into());
+        }
+
+        let bundle = ArchiveBundle::decode(&data)?;
+        bundle.verify()?;
+        let manifest = &bundle.manifest;
+        if manifest.root != anchor.manifest_root
+            || (manifest.from_height, manifest.to_height) != (anchor.from_height, anchor.to_height)
+        {
+            return Err(ArchivalError::AnchorMismatch(content_id.to_string()).into());
+        }
+        Ok(bundle)
+    }
+}
+
+impl DatabaseManager {
+    /// Record the transactions a finality advance finalized at `height`
+    pub async fn record_milestone(&self, height: u64, finalized: &[TransactionId]) -> Result<(), BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        for tx_id in finalized {
+            sqlx::query("INSERT OR IGNORE INTO finality_milestones (height, transaction_id) VALUES (?, ?)")
+                .bind(height as i64)
+                .bind(tx_id.as_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Transactions finalized at heights `from_height..=to_height`, in height order
+    pub async fn get_milestone_transactions(&self, from_height: u64, to_height: u64) -> Result<Vec<(u64, TransactionId)>, BlockchainError> {
+        let rows = sqlx::query(
+            "SELECT height, transaction_id FROM finality_milestones WHERE height BETWEEN ? AND ? ORDER BY height, transaction_id"
+        )
+        .bind(from_height as i64)
+        .bind(to_height as i64)
+        .fetch_all(&self.pool().await)
+        .await?;
+        rows.into_iter()
+            .map(|row| Ok((row.get::<i64, _>("height") as u64, TransactionId::from_string(&row.get::<String, _>("transaction_id"))?)))
+            .collect()
+    }
+
+    pub async fn latest_milestone_height(&self) -> Result<Option<u64>, BlockchainError> {
+        let height: Option<i64> = sqlx::query_scalar("SELECT MAX(height) FROM finality_milestones")
+            .fetch_one(&self.pool().await)
+            .await?;
+        Ok(height.map(|height| height as u64))
+    }
+
+    /// Lowest milestone height above `after`
+    pub async fn next_milestone_height(&self, after: Option<u64>) -> Result<Option<u64>, BlockchainError> {
+        let height: Option<i64> = sqlx::query_scalar("SELECT MIN(height) FROM finality_milestones WHERE height > ?")
+            .bind(after.map_or(-1, |after| after as i64))
+            .fetch_one(&self.pool().await)
+            .await?;
+        Ok(height.map(|height| height as u64))
+    }
+
+    /// Highest height covered by an uploaded bundle
+    pub async fn get_archived_height(&self) -> Result<Option<u64>, BlockchainError> {
+        let height: Option<i64> = sqlx::query_scalar("SELECT MAX(to_height) FROM archives")
+            .fetch_one(&self.pool().await)
+            .await?;
+        Ok(height.map(|height| height as u64))
+    }
+
+    pub async fn store_archive(&self, record: &ArchiveRecord) -> Result<(), BlockchainError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO archives
+                (content_id, from_height, to_height, transaction_count, manifest_root, size_bytes, backend, locator, created_at, anchor_tx)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&record.content_id)
+        .bind(record.from_height as i64)
+        .bind(record.to_height as i64)
+        .bind(record.transaction_count as i64)
+        .bind(&record.manifest_root)
+        .bind(record.size_bytes as i64)
+        .bind(&record.backend)
+        .bind(&record.locator)
+        .bind(record.created_at as i64)
+        .bind(record.anchor_tx.as_ref().map(|tx_id| tx_id.as_string()))
+        .execute(&self.pool().await)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the transaction submitted to anchor an uploaded bundle
+    pub async fn set_archive_anchor_tx(&self, content_id: &str, anchor_tx: &TransactionId) -> Result<(), BlockchainError> {
+        sqlx::query("UPDATE archives SET anchor_tx = ? WHERE content_id = ?")
+            .bind(anchor_tx.as_string())
+            .bind(content_id)
+            .execute(&self.pool().await)
+            .await?;
+        Ok(())
+    }
+
+    /// Uploaded bundles, oldest range first
+    pub async fn get_archives(&self) -> Result<Vec<ArchiveRecord>, BlockchainError> {
+        let rows = sqlx::query(
+            "SELECT content_id, from_height, to_height, transaction_count, manifest_root, size_bytes, backend, locator, created_at, anchor_tx FROM archives ORDER BY from_height"
+        )
+        .fetch_all(&self.pool().await)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(ArchiveRecord {
+                    content_id: row.get("content_id"),
+                    from_height: row.get::<i64, _>("from_height") as u64,
+                    to_height: row.get::<i64, _>("to_height") as u64,
+                    transaction_count: row.get::<i64, _>("transaction_count") as u64,
+                    manifest_root: row.get("manifest_root"),
+                    size_bytes: row.get::<i64, _>("size_bytes") as u64,
+                    backend: row.get("backend"),
+                    locator: row.get("locator"),
+                    created_at: row.get::<i64, _>("created_at") as u64,
+                    anchor_tx: row.get::<Option<String>, _>("anchor_tx")
+                        .map(|tx_id| TransactionId::from_string(&tx_id))
+                        .transpose()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Record a finalized anchor; the first anchor of a content ID stands
+    pub async fn record_archive_anchor(&self, anchor: &ArchiveAnchor) -> Result<(), BlockchainError> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO archive_anchors (content_id, from_height, to_height, manifest_root, anchored_by, anchored_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&anchor.content_id)
+        .bind(anchor.from_height as i64)
+        .bind(anchor.to_height as i64)
+        .bind(&anchor.manifest_root)
+        .bind(anchor.anchored_by.as_string())
+        .bind(anchor.anchored_at as i64)
+        .execute(&self.pool().await)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_archive_anchor(&self, content_id: &str) -> Result<Option<ArchiveAnchor>, BlockchainError> {
+        let row = sqlx::query(
+            "SELECT content_id, from_height, to_height, manifest_root, anchored_by, anchored_at FROM archive_anchors WHERE content_id = ?"
+        )
+        .bind(content_id)
+        .fetch_optional(&self.pool().await)
+        .await?;
+        row.map(|row| {
+            Ok(ArchiveAnchor {
+                content_id: row.get("content_id"),
+                from_height: row.get::<i64, _>("from_height") as u64,
+                to_height: row.get::<i64, _>("to_height") as u64,
+                manifest_root: row.get("manifest_root"),
+                anchored_by: TransactionId::from_string(&row.get::<String, _>("anchored_by"))?,
+                anchored_at: row.get::<i64, _>("anchored_at") as u64,
+            })
+        })
+        .transpose()
+    }
+}
+
+/// Records the milestones that archival ranges are cut from, pruning old
+/// transaction bodies after each on a pruned node
+pub struct MilestoneRecorder {
+    database: Arc<DatabaseManager>,
+}
+
+impl MilestoneRecorder {
+    pub fn new(database: Arc<DatabaseManager>) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for MilestoneRecorder {
+    fn name(&self) -> String {
+        "milestones".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        let NodeEvent::FinalityAdvanced { height, finalized } = event else {
+            return;
+        };
+        if let Err(e) = self.database.record_milestone(*height, finalized).await {
+            log::error!("❌ Failed to record milestone at height {}: {}", height, e);
+            return;
+        }
+        if let Err(e) = self.database.prune_bodies().await {
+            log::error!("❌ Failed to prune transaction bodies at height {}: {}", height, e);
+        }
+    }
+}
+
+/// Archival errors
+#[derive(Debug, thiserror::Error)]
+pub enum ArchivalError {
+    #[error("Archival is not enabled on this node")]
+    NotEnabled,
+    #[error("Cold storage error: {0}")]
+    Backend(String),
+    #[error("Finalized transaction {0} is not stored")]
+    MissingTransaction(String),
+    #[error("Corrupt bundle: {0}")]
+    Corrupt(String),
+    #[error("Bundle content hashes to {actual}, expected {expected}")]
+    ContentMismatch { expected: String, actual: String },
+    #[error("Bundle {0} has no finalized anchor")]
+    NotAnchored(String),
+    #[error("Bundle {0} does not match its anchor")]
+    AnchorMismatch(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+
+    fn transaction(nonce: u64) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![3u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![4u8; 32], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[test]
+    fn test_bundle_round_trips_and_detects_tampering() {
+        let bundle = ArchiveBundle::pack(1, 10, vec![(1, transaction(1)), (4, transaction(2))]).unwrap();
+        let data = bundle.encode().unwrap();
+        let decoded = ArchiveBundle::decode(&data).unwrap();
+        decoded.verify().unwrap();
+        assert_eq!(decoded.manifest, bundle.manifest);
+
+        let mut tampered = decoded.clone();
+        tampered.transactions[1].signature = vec![9u8; 64];
+        assert!(matches!(tampered.verify(), Err(ArchivalError::Corrupt(_))));
+        let mut reordered = decoded;
+        reordered.manifest.entries[0].height = 2;
+        assert!(matches!(reordered.verify(), Err(ArchivalError::Corrupt(_))));
+    }
+
+    #[tokio::test]
+    async fn test_complete_ranges_are_archived_and_retrieved() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().into_owned(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        let archiver = Archiver::new(
+            ArchivalConfig { milestone_span: 5, ..ArchivalConfig::default() },
+            Arc::new(FilesystemColdStorage::new(temp_dir.path().join("cold"))),
+        );
+        for (height, nonce) in [(2, 1), (3, 2), (6, 3)] {
+            let transaction = transaction(nonce);
+            database.store_transaction(&transaction).await.unwrap();
+            database.record_milestone(height, &[transaction.id]).await.unwrap();
+        }
+
+        // Heights 2..=6 are not complete until something finalizes above 6
+        assert!(archiver.archive_next(&database).await.unwrap().is_none());
+        let later = transaction(4);
+        database.store_transaction(&later).await.unwrap();
+        database.record_milestone(7, &[later.id]).await.unwrap();
+        let record = archiver.archive_next(&database).await.unwrap().unwrap();
+        assert_eq!((record.from_height, record.to_height, record.transaction_count), (2, 6, 3));
+        assert!(archiver.archive_next(&database).await.unwrap().is_none());
+
+        assert!(matches!(
+            archiver.retrieve(&database, &record.content_id).await,
+            Err(BlockchainError::Archival(ArchivalError::NotAnchored(_)))
+        ));
+        database.record_archive_anchor(&ArchiveAnchor {
+            content_id: record.content_id.clone(),
+            from_height: 2,
+            to_height: 6,
+            manifest_root: record.manifest_root.clone(),
+            anchored_by: TransactionId::new(),
+            anchored_at: 1_700_000_000,
+        }).await.unwrap();
+        let bundle = archiver.retrieve(&database, &record.content_id).await.unwrap();
+        assert_eq!(bundle.transactions.len(), 3);
+        assert_eq!(database.get_archives().await.unwrap(), vec![record]);
+    }
+}
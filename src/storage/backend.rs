@@ -0,0 +1,844 @@
+//! Pluggable storage for transactions and DAG nodes
+//!
+//! `DatabaseManager` keeps transactions and DAG nodes in a `StorageBackend`,
+//! chosen by `DatabaseConfig::backend`. SQLite is the default; RocksDB
+//! and SQLite sharded across files sustain higher write rates, and the
+//! memory backend suits tests and ephemeral nodes. Accounts, receipts, tokens, swaps, the event
+//! journal and quarantined rows stay in SQLite with either backend, as do
+//! the maintenance tools that work on the SQLite tables directly: reindexing,
+//! retention, ID migration, integrity scans and read replicas.
+//!
+//! Backends store rows as written, checksum included; verifying checksums
+//! and recovering corrupted rows is left to `DatabaseManager`. Lookups every
+//! backend can answer by scanning have default implementations, which the
+//! SQLite backend overrides with indexed queries.
+
+use super::accounts::{read_amount, stored_amount};
+use super::cursor::TransactionCursor;
+use super::pool::SharedPool;
+use super::integrity::{dag_node_checksum, stored_dag_node_checksum, transaction_checksum};
+use crate::core::{DAGNode, NodeStatus, QuantumProof, Transaction};
+use crate::identity::SignatureType;
+use crate::{BlockchainError, TransactionId};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Rows read per page when a default method scans the whole store
+const SCAN_PAGE: usize = 1024;
+
+/// Where transactions and DAG nodes are stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackendKind {
+    /// The `transactions` and `dag_nodes` tables of the node's SQLite database
+    #[default]
+    Sqlite,
+    /// A RocksDB database next to the SQLite file, see `DatabaseConfig::rocksdb_path`
+    RocksDb,
+    /// Memory only, with the SQLite tables in an in-memory database; see `DatabaseConfig::in_memory`
+    Memory,
+    /// SQLite files next to the node's database, split by ID prefix; see `ShardedBackend`
+    Sharded { shards: u32 },
+}
+
+/// One change in a `WriteBatch`
+#[derive(Debug, Clone)]
+pub enum WriteOp {
+    /// Insert or replace a transaction and its parent links
+    PutTransaction(Transaction),
+    /// Insert or replace the DAG node of a stored transaction
+    PutDagNode(DAGNode),
+    /// Change the status and confidence of a stored DAG node, if there is one
+    SetStatus { tx_id: TransactionId, status: NodeStatus, confidence: f64 },
+}
+
+/// Changes applied all or none
+#[derive(Debug, Clone, Default)]
+pub struct WriteBatch {
+    ops: Vec<WriteOp>,
+}
+
+impl WriteBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put_transaction(&mut self, transaction: &Transaction) -> &mut Self {
+        self.ops.push(WriteOp::PutTransaction(transaction.clone()));
+        self
+    }
+
+    pub fn put_dag_node(&mut self, node: &DAGNode) -> &mut Self {
+        self.ops.push(WriteOp::PutDagNode(node.clone()));
+        self
+    }
+
+    pub fn set_status(&mut self, tx_id: &TransactionId, status: NodeStatus, confidence: f64) -> &mut Self {
+        self.ops.push(WriteOp::SetStatus { tx_id: tx_id.clone(), status, confidence });
+        self
+    }
+
+    /// Append the changes of `later`, which apply after these
+    pub fn append(&mut self, later: WriteBatch) -> &mut Self {
+        self.ops.extend(later.ops);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn into_ops(self) -> Vec<WriteOp> {
+        self.ops
+    }
+}
+
+/// A transaction with the checksum it was stored with
+#[derive(Debug, Clone)]
+pub struct StoredTransaction {
+    pub transaction: Transaction,
+    /// `None` for rows written before checksums existed
+    pub checksum: Option<String>,
+}
+
+/// A DAG node as stored, without its transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredDagNode {
+    pub tx_id: TransactionId,
+    pub children: Vec<TransactionId>,
+    pub weight: u64,
+    pub confidence: f64,
+    pub status: NodeStatus,
+    pub quantum_score: u32,
+    /// `None` for rows written before checksums existed
+    pub checksum: Option<String>,
+}
+
+impl StoredDagNode {
+    /// `node` as written, with a fresh checksum
+    pub fn from_node(node: &DAGNode) -> Self {
+        Self {
+            tx_id: node.transaction.id.clone(),
+            children: node.children.clone(),
+            weight: node.weight,
+            confidence: node.confidence,
+            status: node.status.clone(),
+            quantum_score: node.quantum_score,
+            checksum: Some(dag_node_checksum(node)),
+        }
+    }
+
+    /// Change status and confidence, recomputing the checksum
+    pub fn set_status(&mut self, status: NodeStatus, confidence: f64) {
+        self.status = status;
+        self.confidence = confidence;
+        self.checksum = Some(stored_dag_node_checksum(self));
+    }
+
+    /// The node with `transaction` attached
+    pub fn into_node(self, transaction: Transaction) -> DAGNode {
+        DAGNode {
+            transaction,
+            children: self.children,
+            weight: self.weight,
+            confidence: self.confidence,
+            status: self.status,
+            quantum_score: self.quantum_score,
+        }
+    }
+}
+
+/// Storage for transactions and DAG nodes
+///
+/// Keys are ordered by the string form of transaction IDs, which the scan
+/// methods page through.
+#[async_trait::async_trait]
+pub trait StorageBackend: Send + Sync {
+    fn kind(&self) -> StorageBackendKind;
+
+    /// Apply every change in `batch`, or none if one fails
+    async fn write(&self, batch: WriteBatch) -> Result<(), BlockchainError>;
+
+    /// Delete transactions with their DAG nodes and parent links, returning how many were stored
+    async fn delete(&self, ids: &[TransactionId]) -> Result<u64, BlockchainError>;
+
+    async fn get_transaction(&self, tx_id: &TransactionId) -> Result<Option<StoredTransaction>, BlockchainError>;
+
+    async fn get_dag_node(&self, tx_id: &TransactionId) -> Result<Option<StoredDagNode>, BlockchainError>;
+
+    /// Stored transactions listing `tx_id` as a parent, in key order
+    async fn child_ids(&self, tx_id: &TransactionId) -> Result<Vec<TransactionId>, BlockchainError>;
+
+    /// Up to `limit` transactions in key order, starting after `after`
+    async fn scan_transactions(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredTransaction>, BlockchainError>;
+
+    /// Up to `limit` DAG nodes in key order, starting after `after`
+    async fn scan_dag_nodes(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredDagNode>, BlockchainError>;
+
+    /// Flush buffered writes to disk
+    async fn flush(&self) -> Result<(), BlockchainError> {
+        Ok(())
+    }
+
+    async fn transaction_count(&self) -> Result<u64, BlockchainError> {
+        Ok(all_transactions(self).await?.len() as u64)
+    }
+
+    /// DAG nodes with `status`, in key order
+    async fn dag_nodes_with_status(&self, status: &NodeStatus) -> Result<Vec<StoredDagNode>, BlockchainError> {
+        let mut nodes = Vec::new();
+        let mut after = None;
+        loop {
+            let page = self.scan_dag_nodes(after, SCAN_PAGE).await?;
+            let done = page.len() < SCAN_PAGE;
+            after = page.last().map(|node| node.tx_id.clone());
+            nodes.extend(page.into_iter().filter(|node| &node.status == status));
+            if done {
+                return Ok(nodes);
+            }
+        }
+    }
+
+    async fn count_dag_nodes(&self, status: &NodeStatus) -> Result<u64, BlockchainError> {
+        Ok(self.dag_nodes_with_status(status).await?.len() as u64)
+    }
+
+    /// Transactions newest first, optionally only those whose DAG node has `status`
+    async fn recent_transactions(
+        &self,
+        status: Option<&NodeStatus>,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let mut transactions = all_transactions(self).await?;
+        if let Some(status) = status {
+            let ids: std::collections::HashSet<TransactionId> = self.dag_nodes_with_status(status).await?
+                .into_iter()
+                .map(|node| node.tx_id)
+                .collect();
+            transactions.retain(|stored| ids.contains(&stored.transaction.id));
+        }
+        transactions.sort_by_key(|stored| Reverse(stored.transaction.timestamp));
+        Ok(transactions.into_iter().skip(offset).take(limit.unwrap_or(usize::MAX)).collect())
+    }
+
+    /// Up to `limit` transactions newest first, starting after `cursor`,
+    /// optionally only those whose DAG node has `status`
+    async fn transactions_before(
+        &self,
+        status: Option<&NodeStatus>,
+        cursor: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        // One pass over the store, keeping the newest `limit` seen so far
+        let mut transactions: Vec<StoredTransaction> = Vec::new();
+        let mut after = None;
+        loop {
+            let page = self.scan_transactions(after, SCAN_PAGE).await?;
+            let done = page.len() < SCAN_PAGE;
+            after = page.last().map(|stored| stored.transaction.id.clone());
+            for stored in page {
+                if cursor.map_or(false, |cursor| !cursor.precedes(&stored.transaction)) {
+                    continue;
+                }
+                if let Some(status) = status {
+                    match self.get_dag_node(&stored.transaction.id).await? {
+                        Some(node) if &node.status == status => {}
+                        _ => continue,
+                    }
+                }
+                transactions.push(stored);
+            }
+            transactions.sort_by_key(|stored| Reverse(TransactionCursor::of(&stored.transaction)));
+            transactions.truncate(limit);
+            if done {
+                return Ok(transactions);
+            }
+        }
+    }
+
+    /// Transactions sent or received by `address`, newest first
+    async fn transactions_by_address(&self, address: &[u8], limit: usize, offset: usize) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let mut transactions = all_transactions(self).await?;
+        transactions.retain(|stored| stored.transaction.sender == address || stored.transaction.receiver == address);
+        transactions.sort_by_key(|stored| Reverse(stored.transaction.timestamp));
+        Ok(transactions.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Up to `limit` transactions sent or received by `address`, newest first, starting after `cursor`
+    async fn address_history(
+        &self,
+        address: &[u8],
+        cursor: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let mut transactions = all_transactions(self).await?;
+        transactions.retain(|stored| {
+            (stored.transaction.sender == address || stored.transaction.receiver == address)
+                && cursor.map_or(true, |cursor| cursor.precedes(&stored.transaction))
+        });
+        transactions.sort_by_key(|stored| Reverse(TransactionCursor::of(&stored.transaction)));
+        transactions.truncate(limit);
+        Ok(transactions)
+    }
+
+    /// Oldest transaction without parents
+    async fn genesis_transaction(&self) -> Result<Option<StoredTransaction>, BlockchainError> {
+        Ok(all_transactions(self).await?
+            .into_iter()
+            .filter(|stored| stored.transaction.parents.is_empty())
+            .min_by_key(|stored| stored.transaction.timestamp))
+    }
+}
+
+/// Every stored transaction, in key order
+async fn all_transactions<B: StorageBackend + ?Sized>(backend: &B) -> Result<Vec<StoredTransaction>, BlockchainError> {
+    let mut transactions: Vec<StoredTransaction> = Vec::new();
+    loop {
+        let after = transactions.last().map(|stored| stored.transaction.id.clone());
+        let page = backend.scan_transactions(after, SCAN_PAGE).await?;
+        let done = page.len() < SCAN_PAGE;
+        transactions.extend(page);
+        if done {
+            return Ok(transactions);
+        }
+    }
+}
+
+pub(super) const TRANSACTION_COLUMNS: &str =
+    "t.id, t.sender, t.receiver, t.amount, t.fee, t.nonce, t.timestamp, t.signature, t.signature_scheme, t.prime_hash, t.resistance_score, t.proof_timestamp, t.metadata, t.checksum";
+
+pub(super) const DAG_NODE_COLUMNS: &str = "d.transaction_id, d.children, d.weight, d.confidence, d.status, d.quantum_score, d.checksum";
+
+/// Transactions and DAG nodes in the node's SQLite database
+pub struct SqliteBackend {
+    pool: SharedPool,
+}
+
+impl SqliteBackend {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self::shared(SharedPool::new(pool))
+    }
+
+    /// Backend following `pool` through restores
+    pub fn shared(pool: SharedPool) -> Self {
+        Self { pool }
+    }
+
+    async fn pool(&self) -> SqlitePool {
+        self.pool.get().await
+    }
+
+    /// Parent links of the transactions `ids`, in one query however many there are
+    async fn parents_of(&self, ids: &[String]) -> Result<HashMap<String, Vec<TransactionId>>, BlockchainError> {
+        let mut parents: HashMap<String, Vec<TransactionId>> = HashMap::new();
+        if ids.is_empty() {
+            return Ok(parents);
+        }
+        // One JSON array instead of a parameter per ID, which SQLite limits
+        let rows = sqlx::query(
+            "SELECT transaction_id, parent_id FROM transaction_parents
+             WHERE transaction_id IN (SELECT value FROM json_each(?))
+             ORDER BY transaction_id, parent_id"
+        )
+        .bind(serde_json::to_string(ids)?)
+        .fetch_all(&self.pool().await)
+        .await?;
+        for row in rows {
+            let parent = TransactionId::from_string(&row.get::<String, _>(1))?;
+            parents.entry(row.get(0)).or_default().push(parent);
+        }
+        Ok(parents)
+    }
+
+    /// Decode transaction rows, fetching their parent links
+    async fn stored_transactions(&self, rows: Vec<SqliteRow>) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let ids: Vec<String> = rows.iter().map(|row| row.get("id")).collect();
+        let mut parents = self.parents_of(&ids).await?;
+        let mut transactions = Vec::with_capacity(rows.len());
+        for (row, id) in rows.iter().zip(ids) {
+            let tx_id = TransactionId::from_string(&id)?;
+            let parents = parents.remove(&id).unwrap_or_default();
+            transactions.push(StoredTransaction {
+                checksum: row.get("checksum"),
+                transaction: transaction_from_row(row, tx_id, parents)?,
+            });
+        }
+        Ok(transactions)
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for SqliteBackend {
+    fn kind(&self) -> StorageBackendKind {
+        StorageBackendKind::Sqlite
+    }
+
+    async fn write(&self, batch: WriteBatch) -> Result<(), BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        for op in batch.into_ops() {
+            match op {
+                WriteOp::PutTransaction(transaction) => write_transaction(&mut tx, &transaction).await?,
+                WriteOp::PutDagNode(node) => write_dag_node(&mut tx, &StoredDagNode::from_node(&node)).await?,
+                WriteOp::SetStatus { tx_id, status, confidence } => {
+                    let row = sqlx::query(&format!("SELECT {} FROM dag_nodes d WHERE d.transaction_id = ?", DAG_NODE_COLUMNS))
+                        .bind(tx_id.as_string())
+                        .fetch_optional(&mut *tx)
+                        .await?;
+                    if let Some(row) = row {
+                        let mut node = dag_node_from_row(&row)?;
+                        node.set_status(status, confidence);
+                        write_dag_node(&mut tx, &node).await?;
+                    }
+                }
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, ids: &[TransactionId]) -> Result<u64, BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        let mut deleted = 0;
+        for id in ids {
+            let id = id.as_string();
+            sqlx::query("DELETE FROM transaction_parents WHERE transaction_id = ?").bind(&id).execute(&mut *tx).await?;
+            sqlx::query("DELETE FROM dag_nodes WHERE transaction_id = ?").bind(&id).execute(&mut *tx).await?;
+            deleted += sqlx::query("DELETE FROM transactions WHERE id = ?").bind(&id).execute(&mut *tx).await?.rows_affected();
+        }
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    async fn get_transaction(&self, tx_id: &TransactionId) -> Result<Option<StoredTransaction>, BlockchainError> {
+        let row = sqlx::query(&format!("SELECT {} FROM transactions t WHERE t.id = ?", TRANSACTION_COLUMNS))
+            .bind(tx_id.as_string())
+            .fetch_optional(&self.pool().await)
+            .await?;
+        Ok(self.stored_transactions(row.into_iter().collect()).await?.pop())
+    }
+
+    async fn get_dag_node(&self, tx_id: &TransactionId) -> Result<Option<StoredDagNode>, BlockchainError> {
+        let row = sqlx::query(&format!("SELECT {} FROM dag_nodes d WHERE d.transaction_id = ?", DAG_NODE_COLUMNS))
+            .bind(tx_id.as_string())
+            .fetch_optional(&self.pool().await)
+            .await?;
+        row.map(|row| dag_node_from_row(&row)).transpose()
+    }
+
+    async fn child_ids(&self, tx_id: &TransactionId) -> Result<Vec<TransactionId>, BlockchainError> {
+        let rows = sqlx::query("SELECT transaction_id FROM transaction_parents WHERE parent_id = ? ORDER BY transaction_id")
+            .bind(tx_id.as_string())
+            .fetch_all(&self.pool().await)
+            .await?;
+        rows.iter().map(|row| TransactionId::from_string(&row.get::<String, _>(0))).collect()
+    }
+
+    async fn scan_transactions(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let rows = sqlx::query(&format!("SELECT {} FROM transactions t WHERE t.id > ? ORDER BY t.id LIMIT ?", TRANSACTION_COLUMNS))
+            .bind(after.map(|id| id.as_string()).unwrap_or_default())
+            .bind(limit as i64)
+            .fetch_all(&self.pool().await)
+            .await?;
+        self.stored_transactions(rows).await
+    }
+
+    async fn scan_dag_nodes(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredDagNode>, BlockchainError> {
+        let rows = sqlx::query(&format!("SELECT {} FROM dag_nodes d WHERE d.transaction_id > ? ORDER BY d.transaction_id LIMIT ?", DAG_NODE_COLUMNS))
+            .bind(after.map(|id| id.as_string()).unwrap_or_default())
+            .bind(limit as i64)
+            .fetch_all(&self.pool().await)
+            .await?;
+        rows.iter().map(dag_node_from_row).collect()
+    }
+
+    async fn transaction_count(&self) -> Result<u64, BlockchainError> {
+        let row = sqlx::query("SELECT COUNT(*) FROM transactions").fetch_one(&self.pool().await).await?;
+        Ok(row.get::<i64, _>(0) as u64)
+    }
+
+    async fn dag_nodes_with_status(&self, status: &NodeStatus) -> Result<Vec<StoredDagNode>, BlockchainError> {
+        let rows = sqlx::query(&format!("SELECT {} FROM dag_nodes d WHERE d.status = ? ORDER BY d.transaction_id", DAG_NODE_COLUMNS))
+            .bind(format!("{:?}", status))
+            .fetch_all(&self.pool().await)
+            .await?;
+        rows.iter().map(dag_node_from_row).collect()
+    }
+
+    async fn count_dag_nodes(&self, status: &NodeStatus) -> Result<u64, BlockchainError> {
+        let row = sqlx::query("SELECT COUNT(*) FROM dag_nodes WHERE status = ?")
+            .bind(format!("{:?}", status))
+            .fetch_one(&self.pool().await)
+            .await?;
+        Ok(row.get::<i64, _>(0) as u64)
+    }
+
+    async fn recent_transactions(
+        &self,
+        status: Option<&NodeStatus>,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let mut query = format!("SELECT {} FROM transactions t", TRANSACTION_COLUMNS);
+        if status.is_some() {
+            query.push_str(" JOIN dag_nodes d ON t.id = d.transaction_id WHERE d.status = ?");
+        }
+        query.push_str(" ORDER BY t.timestamp DESC LIMIT ? OFFSET ?");
+
+        let mut query = sqlx::query(&query);
+        if let Some(status) = status {
+            query = query.bind(format!("{:?}", status));
+        }
+        let rows = query
+            .bind(limit.map_or(-1, |limit| limit as i64))
+            .bind(offset as i64)
+            .fetch_all(&self.pool().await)
+            .await?;
+        self.stored_transactions(rows).await
+    }
+
+    async fn transactions_before(
+        &self,
+        status: Option<&NodeStatus>,
+        cursor: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let mut query = format!("SELECT {} FROM transactions t", TRANSACTION_COLUMNS);
+        let mut conditions = Vec::new();
+        if status.is_some() {
+            query.push_str(" JOIN dag_nodes d ON t.id = d.transaction_id");
+            conditions.push("d.status = ?");
+        }
+        if cursor.is_some() {
+            conditions.push("(t.timestamp, t.id) < (?, ?)");
+        }
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+        query.push_str(" ORDER BY t.timestamp DESC, t.id DESC LIMIT ?");
+
+        let mut query = sqlx::query(&query);
+        if let Some(status) = status {
+            query = query.bind(format!("{:?}", status));
+        }
+        if let Some(cursor) = cursor {
+            query = query.bind(cursor.timestamp as i64).bind(&cursor.id);
+        }
+        let rows = query.bind(limit as i64).fetch_all(&self.pool().await).await?;
+        self.stored_transactions(rows).await
+    }
+
+    async fn transactions_by_address(&self, address: &[u8], limit: usize, offset: usize) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM transactions t WHERE t.sender = ? OR t.receiver = ? ORDER BY t.timestamp DESC, t.id LIMIT ? OFFSET ?",
+            TRANSACTION_COLUMNS
+        ))
+        .bind(address)
+        .bind(address)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool().await)
+        .await?;
+        self.stored_transactions(rows).await
+    }
+
+    async fn address_history(
+        &self,
+        address: &[u8],
+        cursor: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        // Each half seeks its own index; the union is ordered again
+        let seek = if cursor.is_some() { " AND (timestamp, id) < (?, ?)" } else { "" };
+        let query = format!(
+            "SELECT {columns} FROM transactions t WHERE t.id IN (
+                 SELECT id FROM (SELECT id FROM transactions WHERE sender_hex = ?{seek} ORDER BY timestamp DESC, id DESC LIMIT ?)
+                 UNION
+                 SELECT id FROM (SELECT id FROM transactions WHERE receiver_hex = ?{seek} ORDER BY timestamp DESC, id DESC LIMIT ?)
+             )
+             ORDER BY t.timestamp DESC, t.id DESC LIMIT ?",
+            columns = TRANSACTION_COLUMNS,
+            seek = seek,
+        );
+
+        let address = hex::encode(address);
+        let mut query = sqlx::query(&query);
+        for _ in 0..2 {
+            query = query.bind(&address);
+            if let Some(cursor) = cursor {
+                query = query.bind(cursor.timestamp as i64).bind(&cursor.id);
+            }
+            query = query.bind(limit as i64);
+        }
+        let rows = query.bind(limit as i64).fetch_all(&self.pool().await).await?;
+        self.stored_transactions(rows).await
+    }
+
+    async fn genesis_transaction(&self) -> Result<Option<StoredTransaction>, BlockchainError> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM transactions t
+             WHERE NOT EXISTS (SELECT 1 FROM transaction_parents p WHERE p.transaction_id = t.id)
+             ORDER BY t.timestamp ASC LIMIT 1",
+            TRANSACTION_COLUMNS
+        ))
+        .fetch_optional(&self.pool().await)
+        .await?;
+        Ok(self.stored_transactions(row.into_iter().collect()).await?.pop())
+    }
+}
+
+/// Write a transaction row and its parent links
+async fn write_transaction(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, transaction: &Transaction) -> Result<(), BlockchainError> {
+    write_transaction_row(tx, transaction).await?;
+    for parent_id in &transaction.parents {
+        sqlx::query(
+            "INSERT OR REPLACE INTO transaction_parents (transaction_id, parent_id) VALUES (?, ?)"
+        )
+        .bind(transaction.id.as_string())
+        .bind(parent_id.as_string())
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Write a transaction row without its parent links, which a reindex derives from it
+pub(super) async fn write_transaction_row(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, transaction: &Transaction) -> Result<(), BlockchainError> {
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO transactions
+        (id, sender, receiver, amount, nonce, timestamp, signature, prime_hash, resistance_score, proof_timestamp, metadata, parents, signature_scheme, fee, checksum)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#
+    )
+    .bind(transaction.id.as_string())
+    .bind(&transaction.sender)
+    .bind(&transaction.receiver)
+    .bind(stored_amount(transaction.amount))
+    .bind(transaction.nonce as i64)
+    .bind(transaction.timestamp as i64)
+    .bind(&transaction.signature)
+    .bind(&transaction.quantum_proof.prime_hash)
+    .bind(transaction.quantum_proof.resistance_score)
+    .bind(transaction.quantum_proof.proof_timestamp as i64)
+    .bind(&transaction.metadata)
+    .bind(serde_json::to_string(&transaction.parents.iter().map(|id| id.as_string()).collect::<Vec<String>>())?)
+    .bind(format!("{:?}", transaction.signature_scheme))
+    .bind(stored_amount(transaction.fee))
+    .bind(transaction_checksum(transaction))
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Write a DAG node row
+async fn write_dag_node(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, node: &StoredDagNode) -> Result<(), BlockchainError> {
+    let children_json = serde_json::to_string(&node.children.iter().map(|id| id.as_string()).collect::<Vec<String>>())?;
+
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO dag_nodes
+        (transaction_id, children, weight, confidence, status, quantum_score, checksum)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#
+    )
+    .bind(node.tx_id.as_string())
+    .bind(children_json)
+    .bind(node.weight as i64)
+    .bind(node.confidence)
+    .bind(format!("{:?}", node.status))
+    .bind(node.quantum_score)
+    .bind(&node.checksum)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Decode a row selected with `TRANSACTION_COLUMNS`
+pub(super) fn transaction_from_row(row: &SqliteRow, id: TransactionId, parents: Vec<TransactionId>) -> Result<Transaction, BlockchainError> {
+    Ok(Transaction {
+        id,
+        sender: row.get("sender"),
+        receiver: row.get("receiver"),
+        amount: read_amount(row, "amount")?,
+        fee: read_amount(row, "fee")?,
+        nonce: row.get::<i64, _>("nonce") as u64,
+        timestamp: row.get::<i64, _>("timestamp") as u64,
+        parents,
+        signature: row.get("signature"),
+        signature_scheme: parse_signature_scheme(row.get::<Option<String>, _>("signature_scheme").as_deref())?,
+        quantum_proof: QuantumProof {
+            prime_hash: row.get("prime_hash"),
+            resistance_score: row.get::<i64, _>("resistance_score") as u32,
+            proof_timestamp: row.get::<i64, _>("proof_timestamp") as u64,
+        },
+        metadata: row.get("metadata"),
+    })
+}
+
+/// Decode a row selected with `DAG_NODE_COLUMNS`
+pub(super) fn dag_node_from_row(row: &SqliteRow) -> Result<StoredDagNode, BlockchainError> {
+    let children: Vec<String> = serde_json::from_str(&row.get::<String, _>("children"))?;
+    Ok(StoredDagNode {
+        tx_id: TransactionId::from_string(&row.get::<String, _>("transaction_id"))?,
+        children: children.iter().map(String::as_str).map(TransactionId::from_string).collect::<Result<_, _>>()?,
+        weight: row.get::<i64, _>("weight") as u64,
+        confidence: row.get("confidence"),
+        status: parse_node_status(&row.get::<String, _>("status"))?,
+        quantum_score: row.get::<i64, _>("quantum_score") as u32,
+        checksum: row.get("checksum"),
+    })
+}
+
+/// Parse a node status as stored, e.g. `Confirmed`
+pub fn parse_node_status(value: &str) -> Result<NodeStatus, BlockchainError> {
+    match value {
+        "Pending" => Ok(NodeStatus::Pending),
+        "Confirmed" => Ok(NodeStatus::Confirmed),
+        "Finalized" => Ok(NodeStatus::Finalized),
+        "Rejected" => Ok(NodeStatus::Rejected),
+        other => Err(BlockchainError::Other(format!("Unknown node status {:?}", other))),
+    }
+}
+
+/// Parse a signature scheme as stored; rows from before schemes were stored hold none
+pub(super) fn parse_signature_scheme(value: Option<&str>) -> Result<SignatureType, BlockchainError> {
+    match value {
+        None => Ok(SignatureType::default()),
+        Some("Ed25519") => Ok(SignatureType::Ed25519),
+        Some("Dilithium3") => Ok(SignatureType::Dilithium3),
+        Some("Dilithium5") => Ok(SignatureType::Dilithium5),
+        Some("Hybrid") => Ok(SignatureType::Hybrid),
+        Some("Falcon512") => Ok(SignatureType::Falcon512),
+        Some("Falcon1024") => Ok(SignatureType::Falcon1024),
+        Some(other) => Err(BlockchainError::Other(format!("Unknown signature scheme {:?}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{DatabaseConfig, DatabaseManager};
+    use tempfile::TempDir;
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10u128.pow(20),
+            fee: 3,
+            nonce,
+            timestamp: 1_700_000_000 + nonce,
+            parents,
+            signature: vec![0u8; 64],
+            signature_scheme: SignatureType::Dilithium5,
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+            metadata: Some(vec![7u8; 4]),
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    fn node(transaction: &Transaction, children: Vec<TransactionId>) -> DAGNode {
+        DAGNode {
+            transaction: transaction.clone(),
+            children,
+            weight: 3,
+            confidence: 0.5,
+            status: NodeStatus::Pending,
+            quantum_score: 80,
+        }
+    }
+
+    /// Exercised against each backend
+    async fn check_backend(backend: &dyn StorageBackend) {
+        let parent = transaction(1, vec![]);
+        let child = transaction(2, vec![parent.id.clone()]);
+        let mut batch = WriteBatch::new();
+        batch.put_transaction(&parent)
+            .put_dag_node(&node(&parent, vec![child.id.clone()]))
+            .put_transaction(&child)
+            .put_dag_node(&node(&child, vec![]))
+            .set_status(&parent.id, NodeStatus::Confirmed, 0.9);
+        backend.write(batch).await.unwrap();
+
+        let stored = backend.get_transaction(&child.id).await.unwrap().unwrap();
+        assert_eq!(stored.transaction.to_canonical_bytes(), child.to_canonical_bytes());
+        assert_eq!(stored.checksum, Some(transaction_checksum(&child)));
+        let stored = backend.get_dag_node(&parent.id).await.unwrap().unwrap();
+        assert_eq!((stored.status.clone(), stored.confidence), (NodeStatus::Confirmed, 0.9));
+        assert_eq!(stored.checksum, Some(dag_node_checksum(&stored.clone().into_node(parent.clone()))));
+
+        assert_eq!(backend.child_ids(&parent.id).await.unwrap(), vec![child.id.clone()]);
+        assert_eq!(backend.transaction_count().await.unwrap(), 2);
+        assert_eq!(backend.count_dag_nodes(&NodeStatus::Pending).await.unwrap(), 1);
+        assert_eq!(backend.genesis_transaction().await.unwrap().unwrap().transaction.id, parent.id);
+        let recent = backend.recent_transactions(None, None, 0).await.unwrap();
+        assert_eq!(recent[0].transaction.id, child.id);
+        assert_eq!((recent[0].transaction.parents.clone(), recent[1].transaction.parents.len()), (vec![parent.id.clone()], 0));
+
+        let newest = backend.transactions_before(None, None, 1).await.unwrap();
+        assert_eq!(newest[0].transaction.id, child.id);
+        let cursor = TransactionCursor::of(&newest[0].transaction);
+        let older = backend.transactions_before(None, Some(&cursor), 10).await.unwrap();
+        assert_eq!(older.iter().map(|stored| stored.transaction.id.clone()).collect::<Vec<_>>(), vec![parent.id.clone()]);
+        let confirmed = backend.transactions_before(Some(&NodeStatus::Confirmed), None, 10).await.unwrap();
+        assert_eq!(confirmed.iter().map(|stored| stored.transaction.id.clone()).collect::<Vec<_>>(), vec![parent.id.clone()]);
+        let history = backend.address_history(&[2u8; 32], None, 10).await.unwrap();
+        assert_eq!(history.iter().map(|stored| stored.transaction.id.clone()).collect::<Vec<_>>(), vec![child.id.clone(), parent.id.clone()]);
+        let history = backend.address_history(&[1u8; 32], Some(&cursor), 10).await.unwrap();
+        assert_eq!(history.iter().map(|stored| stored.transaction.id.clone()).collect::<Vec<_>>(), vec![parent.id.clone()]);
+        assert!(backend.address_history(&[3u8; 32], None, 10).await.unwrap().is_empty());
+
+        let first = backend.scan_transactions(None, 1).await.unwrap();
+        let rest = backend.scan_transactions(Some(first[0].transaction.id.clone()), 10).await.unwrap();
+        assert_eq!((first.len(), rest.len()), (1, 1));
+        assert_ne!(first[0].transaction.id, rest[0].transaction.id);
+
+        assert_eq!(backend.delete(&[child.id.clone(), child.id.clone()]).await.unwrap(), 1);
+        assert!(backend.get_dag_node(&child.id).await.unwrap().is_none());
+        assert!(backend.child_ids(&parent.id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_backend() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        check_backend(&SqliteBackend::shared(db.pool.clone())).await;
+    }
+
+    #[tokio::test]
+    async fn test_rocksdb_backend() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = crate::storage::RocksDbBackend::open(temp_dir.path().join("rocksdb")).unwrap();
+        check_backend(&backend).await;
+        backend.flush().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend() {
+        check_backend(&crate::storage::MemoryBackend::new()).await;
+    }
+
+    #[test]
+    fn test_stored_names_parse() {
+        for status in [NodeStatus::Pending, NodeStatus::Confirmed, NodeStatus::Finalized, NodeStatus::Rejected] {
+            assert_eq!(parse_node_status(&format!("{:?}", status)).unwrap(), status);
+        }
+        assert!(parse_node_status("Orphaned").is_err());
+        assert_eq!(parse_signature_scheme(Some("Ed25519")).unwrap(), SignatureType::Ed25519);
+        assert_eq!(parse_signature_scheme(None).unwrap(), SignatureType::default());
+    }
+}
@@ -0,0 +1,339 @@
+//! Bootstrap snapshots
+//!
+//! A bootstrap snapshot carries what a new node needs to join without
+//! replaying history: every account balance, the IDs of the finalized
+//! transactions already applied to them, the checkpoint roots left by
+//! pruning, and the in-memory part of the DAG (genesis, the recent window
+//! and the current tips). It is written as gzip-compressed bincode with a
+//! digest over its contents, which is checked again on import.
+//!
+//! Imports are only accepted into a node that holds nothing but its own
+//! genesis, so a snapshot never merges with local history.
+
+use super::DatabaseManager;
+use crate::core::{Amount, DAGNode};
+use crate::{BlockchainError, TransactionId};
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use sqlx::Row;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Version of the snapshot file layout; 2 widened amounts to `u128`
+pub const BOOTSTRAP_FORMAT_VERSION: u32 = 2;
+
+/// Balance of one account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotAccount {
+    /// Hex address
+    pub address: String,
+    #[serde(with = "crate::core::amount")]
+    pub balance: Amount,
+}
+
+/// Finalized state of a node, for bootstrapping another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapSnapshot {
+    pub format_version: u32,
+    /// Node version that exported the snapshot
+    pub node_version: String,
+    pub taken_at: u64,
+    /// Consensus height of the exporting node, for reference
+    pub consensus_height: u64,
+    pub genesis: TransactionId,
+    pub accounts: Vec<SnapshotAccount>,
+    /// IDs of finalized transactions applied to the balances
+    pub applied_transactions: Vec<String>,
+    /// Finalized transactions whose parents were pruned
+    pub checkpoint_roots: Vec<TransactionId>,
+    /// Genesis, recent transactions and tips, with their DAG state
+    pub nodes: Vec<DAGNode>,
+    /// Digest over everything above except the version fields and time
+    pub state_root: String,
+}
+
+/// Summary of an exported or imported snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapInfo {
+    pub path: String,
+    pub consensus_height: u64,
+    pub accounts: usize,
+    pub applied_transactions: usize,
+    pub nodes: usize,
+    pub tips: usize,
+    /// Size of the compressed file
+    pub size_bytes: u64,
+    pub state_root: String,
+}
+
+impl BootstrapSnapshot {
+    /// Snapshot of the given state; accounts, applied IDs and nodes are sorted
+    pub fn new(
+        consensus_height: u64,
+        genesis: TransactionId,
+        mut accounts: Vec<SnapshotAccount>,
+        mut applied_transactions: Vec<String>,
+        checkpoint_roots: Vec<TransactionId>,
+        mut nodes: Vec<DAGNode>,
+    ) -> Self {
+        accounts.sort_by(|a, b| a.address.cmp(&b.address));
+        applied_transactions.sort();
+        nodes.sort_by_key(|node| node.transaction.id.as_string());
+        let mut snapshot = Self {
+            format_version: BOOTSTRAP_FORMAT_VERSION,
+            node_version: env!("CARGO_PKG_VERSION").to_string(),
+            taken_at: Utc::now().timestamp() as u64,
+            consensus_height,
+            genesis,
+            accounts,
+            applied_transactions,
+            checkpoint_roots,
+            nodes,
+            state_root: String::new(),
+        };
+        snapshot.state_root = snapshot.compute_state_root();
+        snapshot
+    }
+
+    /// Digest over the snapshot's state
+    pub fn compute_state_root(&self) -> String {
+        let mut hasher = Sha3_256::new();
+        hasher.update(self.consensus_height.to_le_bytes());
+        hasher.update(self.genesis.as_bytes());
+        for account in &self.accounts {
+            hasher.update(account.address.as_bytes());
+            hasher.update(account.balance.to_le_bytes());
+        }
+        for id in &self.applied_transactions {
+            hasher.update(id.as_bytes());
+        }
+        for id in &self.checkpoint_roots {
+            hasher.update(id.as_bytes());
+        }
+        for node in &self.nodes {
+            hasher.update(node.transaction.compute_id().as_bytes());
+            hasher.update(format!("{:?}", node.status).as_bytes());
+            for child in &node.children {
+                hasher.update(child.as_bytes());
+            }
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Check the format version, the state root and every transaction ID
+    pub fn verify(&self) -> Result<(), BlockchainError> {
+        if self.format_version != BOOTSTRAP_FORMAT_VERSION {
+            return Err(BlockchainError::Other(format!(
+                "Unsupported snapshot format {} (expected {})",
+                self.format_version, BOOTSTRAP_FORMAT_VERSION
+            )));
+        }
+        if let Some(node) = self.nodes.iter().find(|node| !node.transaction.has_valid_id()) {
+            return Err(BlockchainError::Other(format!("Snapshot transaction {} does not match its ID", node.transaction.id)));
+        }
+        if !self.nodes.iter().any(|node| node.transaction.id == self.genesis) {
+            return Err(BlockchainError::Other("Snapshot does not contain its genesis transaction".to_string()));
+        }
+        if self.compute_state_root() != self.state_root {
+            return Err(BlockchainError::Other("Snapshot state root does not match its contents".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Read and verify a snapshot written with `save`
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, BlockchainError> {
+        let compressed = tokio::fs::read(path).await?;
+        let mut data = Vec::new();
+        GzDecoder::new(compressed.as_slice()).read_to_end(&mut data)?;
+        let snapshot: Self = bincode::deserialize(&data)
+            .map_err(|e| BlockchainError::Other(format!("Invalid snapshot: {}", e)))?;
+        snapshot.verify()?;
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot compressed, returning the file size
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<u64, BlockchainError> {
+        let data = bincode::serialize(self)
+            .map_err(|e| BlockchainError::Other(format!("Failed to encode snapshot: {}", e)))?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&data)?;
+        let compressed = encoder.finish()?;
+        if let Some(parent) = path.as_ref().parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, &compressed).await?;
+        Ok(compressed.len() as u64)
+    }
+
+    /// Summary of the snapshot as stored at `path`
+    pub fn info(&self, path: impl AsRef<Path>, size_bytes: u64) -> BootstrapInfo {
+        BootstrapInfo {
+            path: path.as_ref().display().to_string(),
+            consensus_height: self.consensus_height,
+            accounts: self.accounts.len(),
+            applied_transactions: self.applied_transactions.len(),
+            nodes: self.nodes.len(),
+            tips: self.nodes.iter().filter(|node| node.status == crate::core::NodeStatus::Pending).count(),
+            size_bytes,
+            state_root: self.state_root.clone(),
+        }
+    }
+}
+
+impl DatabaseManager {
+    /// Every stored account balance
+    pub async fn get_account_balances(&self) -> Result<Vec<SnapshotAccount>, BlockchainError> {
+        let rows = sqlx::query("SELECT address, balance FROM account_balances ORDER BY address")
+            .fetch_all(&self.pool().await)
+            .await?;
+        rows.into_iter()
+            .map(|row| Ok(SnapshotAccount {
+                address: row.get("address"),
+                balance: super::accounts::read_amount(&row, "balance")?,
+            }))
+            .collect()
+    }
+
+    /// IDs of every finalized transaction applied to balances
+    pub async fn get_applied_transaction_ids(&self) -> Result<Vec<String>, BlockchainError> {
+        let rows = sqlx::query("SELECT transaction_id FROM applied_transactions ORDER BY transaction_id")
+            .fetch_all(&self.pool().await)
+            .await?;
+        Ok(rows.into_iter().map(|row| row.get(0)).collect())
+    }
+
+    /// Replace a fresh database's contents with a snapshot
+    ///
+    /// Fails if anything besides the node's own genesis is stored or any
+    /// balance has been applied.
+    pub async fn import_bootstrap(&self, snapshot: &BootstrapSnapshot) -> Result<(), BlockchainError> {
+        if self.get_transaction_count().await? > 1 || self.get_applied_transaction_count().await? > 0 {
+            return Err(BlockchainError::Other("Snapshots can only be imported into an empty node".to_string()));
+        }
+        if let Some(genesis) = self.get_genesis_transaction().await? {
+            self.delete_transactions(&[genesis.id]).await?;
+        }
+
+        for node in &snapshot.nodes {
+            self.store_transaction(&node.transaction).await?;
+            self.store_dag_node(node).await?;
+        }
+
+        let now = Utc::now().timestamp();
+        let mut tx = self.pool().await.begin().await?;
+        sqlx::query("DELETE FROM account_balances").execute(&mut *tx).await?;
+        for account in &snapshot.accounts {
+            sqlx::query("INSERT INTO account_balances (address, balance, updated_at) VALUES (?, ?, ?)")
+                .bind(&account.address)
+                .bind(super::accounts::stored_amount(account.balance))
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+        for id in &snapshot.applied_transactions {
+            sqlx::query("INSERT OR IGNORE INTO applied_transactions (transaction_id, applied_at) VALUES (?, ?)")
+                .bind(id)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        log::info!(
+            "📥 Imported snapshot {} with {} account(s) and {} transaction(s)",
+            snapshot.state_root, snapshot.accounts.len(), snapshot.nodes.len()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{NodeStatus, QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+
+    fn node(nonce: u64, parents: Vec<TransactionId>, status: NodeStatus) -> DAGNode {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 1,
+            nonce,
+            timestamp: 1_000 + nonce,
+            parents,
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![0u8; 32], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        DAGNode { transaction, children: Vec::new(), weight: 1, confidence: 1.0, status, quantum_score: 80 }
+    }
+
+    fn snapshot() -> BootstrapSnapshot {
+        let genesis = node(0, vec![], NodeStatus::Finalized);
+        let tip = node(1, vec![genesis.transaction.id.clone()], NodeStatus::Pending);
+        BootstrapSnapshot::new(
+            3,
+            genesis.transaction.id.clone(),
+            vec![SnapshotAccount { address: "02".repeat(32), balance: 5 }],
+            vec![tip.transaction.id.as_string()],
+            vec![],
+            vec![tip, genesis],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("bootstrap.snap");
+        let snapshot = snapshot();
+
+        let size = snapshot.save(&path).await.unwrap();
+        let loaded = BootstrapSnapshot::load(&path).await.unwrap();
+        assert_eq!(loaded.state_root, snapshot.state_root);
+        assert_eq!(loaded.accounts, snapshot.accounts);
+
+        let info = loaded.info(&path, size);
+        assert_eq!((info.nodes, info.tips, info.consensus_height), (2, 1, 3));
+    }
+
+    #[tokio::test]
+    async fn test_import_only_into_empty_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        let snapshot = snapshot();
+
+        db.import_bootstrap(&snapshot).await.unwrap();
+        assert_eq!(db.get_genesis_transaction().await.unwrap().unwrap().id, snapshot.genesis);
+        assert_eq!(db.get_account_balances().await.unwrap(), snapshot.accounts);
+        assert_eq!(db.get_applied_transaction_ids().await.unwrap(), snapshot.applied_transactions);
+        assert_eq!(db.get_dag_tips().await.unwrap().len(), 1);
+
+        // A second import would merge with the first
+        assert!(db.import_bootstrap(&snapshot).await.is_err());
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let mut snapshot = snapshot();
+        assert!(snapshot.verify().is_ok());
+
+        snapshot.accounts[0].balance += 1;
+        assert!(snapshot.verify().is_err());
+
+        let mut snapshot = self::snapshot();
+        snapshot.nodes[0].transaction.amount += 1;
+        assert!(snapshot.verify().is_err());
+    }
+}
@@ -0,0 +1,574 @@
+//! Bulk import of historical transactions
+//!
+//! Loads transactions from a stream far larger than memory, e.g. a testnet
+//! export, into the SQLite backend:
+//!
+//! - JSON lines: one serialized `Transaction` per line.
+//! - CSV: a header row naming transaction columns, as in the `transactions`
+//!   table, then one transaction per row. Blobs are hex; `parents` is either
+//!   a JSON array or IDs separated by `;`.
+//! - Export: the JSON lines `export` writes. Rows of other tables are skipped.
+//!
+//! Secondary indexes of the transaction tables are dropped while rows are
+//! written, and only transaction rows are written, in batches. Afterwards the
+//! indexes are created again and `reindex` derives parent links and DAG nodes
+//! from the rows, so nothing is indexed one row at a time.
+//!
+//! Each batch commits together with the number of lines it consumed, in
+//! `bulk_imports` under the source's name. Importing the same source again
+//! after an interruption skips the lines already imported and carries on.
+
+use super::backend::{parse_signature_scheme, write_transaction_row};
+use super::{DatabaseManager, ReindexConfig, ReindexReport, StorageBackendKind};
+use crate::core::{amount::parse_amount, QuantumProof, Transaction};
+use crate::{BlockchainError, TransactionId};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+
+/// Tables whose secondary indexes are rebuilt after the rows are written
+const INDEXED_TABLES: [&str; 3] = ["transactions", "transaction_parents", "dag_nodes"];
+
+/// Layout of a bulk import source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkImportFormat {
+    Jsonl,
+    Csv,
+    Export,
+}
+
+impl BulkImportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BulkImportFormat::Jsonl => "jsonl",
+            BulkImportFormat::Csv => "csv",
+            BulkImportFormat::Export => "export",
+        }
+    }
+}
+
+impl fmt::Display for BulkImportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BulkImportFormat {
+    type Err = BlockchainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [BulkImportFormat::Jsonl, BulkImportFormat::Csv, BulkImportFormat::Export].into_iter()
+            .find(|format| format.as_str() == s)
+            .ok_or_else(|| BlockchainError::Other(format!("Unknown bulk import format: {}", s)))
+    }
+}
+
+/// Bulk import settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BulkImportConfig {
+    /// Transactions written per database transaction
+    pub batch_size: usize,
+    /// Count lines that are not a transaction and go on, instead of failing
+    pub skip_invalid: bool,
+    /// Batches of the derived table rebuild
+    pub reindex: ReindexConfig,
+}
+
+impl Default for BulkImportConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 10_000,
+            skip_invalid: false,
+            reindex: ReindexConfig::default(),
+        }
+    }
+}
+
+/// Stage of a bulk import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BulkImportPhase {
+    /// Writing transaction rows
+    Rows,
+    /// Creating the dropped indexes again
+    Indexes,
+    /// Deriving parent links and DAG nodes
+    DerivedTables,
+}
+
+/// Progress reported after every batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkImportProgress {
+    pub phase: BulkImportPhase,
+    /// Lines of the source consumed, including those of earlier runs
+    pub lines: u64,
+    pub imported: u64,
+    /// Transactions per second written in this run
+    pub rate: f64,
+    /// Transactions the derived tables were rebuilt for so far
+    pub derived: u64,
+}
+
+/// Outcome of a bulk import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkImportReport {
+    pub source: String,
+    pub lines: u64,
+    pub imported: u64,
+    /// Lines that hold no transaction, e.g. rows of other tables
+    pub skipped: u64,
+    /// Lines that could not be read as a transaction, with `skip_invalid`
+    pub invalid: u64,
+    /// Lines already imported by an interrupted run
+    pub resumed_from: u64,
+    /// Indexes dropped and created again
+    pub indexes_rebuilt: Vec<String>,
+    pub reindex: Option<ReindexReport>,
+    pub started_at: i64,
+    pub completed_at: i64,
+}
+
+/// One source line
+enum ImportLine {
+    Transaction(Box<Transaction>),
+    Skipped,
+}
+
+impl DatabaseManager {
+    /// Bulk import the file at `path`, under its path as the source name
+    pub async fn bulk_import<F>(
+        &self,
+        path: &str,
+        format: BulkImportFormat,
+        config: &BulkImportConfig,
+        progress: F,
+    ) -> Result<BulkImportReport, BlockchainError>
+    where
+        F: FnMut(&BulkImportProgress),
+    {
+        let file = tokio::fs::File::open(path).await?;
+        self.bulk_import_from(path, BufReader::new(file), format, config, progress).await
+    }
+
+    /// Bulk import `reader`, resuming an interrupted import of `source`
+    ///
+    /// `progress` is called after every batch.
+    pub async fn bulk_import_from<R, F>(
+        &self,
+        source: &str,
+        reader: R,
+        format: BulkImportFormat,
+        config: &BulkImportConfig,
+        mut progress: F,
+    ) -> Result<BulkImportReport, BlockchainError>
+    where
+        R: AsyncBufRead + Unpin,
+        F: FnMut(&BulkImportProgress),
+    {
+        if self.backend_kind() != StorageBackendKind::Sqlite {
+            return Err(BlockchainError::Other("Bulk import needs the SQLite backend".to_string()));
+        }
+        self.commit_queued().await?;
+
+        let mut report = match self.bulk_import_state(source).await? {
+            Some((report, Some(_))) => {
+                log::info!("📥 {} was already imported", source);
+                return Ok(report);
+            }
+            Some((report, None)) => {
+                log::info!("📥 Resuming the import of {} after line {}", source, report.lines);
+                // Opening the database creates some of them again
+                for name in &report.indexes_rebuilt {
+                    sqlx::query(&format!("DROP INDEX IF EXISTS \"{}\"", name)).execute(&self.pool().await).await?;
+                }
+                BulkImportReport { resumed_from: report.lines, ..report }
+            }
+            None => self.start_bulk_import(source, format).await?,
+        };
+
+        let start = Instant::now();
+        let batch_size = config.batch_size.max(1);
+        let mut lines = reader.lines();
+        let mut header: Option<Vec<String>> = None;
+        let mut line_number = 0u64;
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut batch_lines = 0u64;
+        let (mut batch_skipped, mut batch_invalid) = (0u64, 0u64);
+        loop {
+            let line = lines.next_line().await?;
+            if let Some(line) = &line {
+                line_number += 1;
+                if format == BulkImportFormat::Csv && header.is_none() {
+                    header = Some(line.split(',').map(|name| name.trim().to_string()).collect());
+                    if line_number > report.lines {
+                        batch_lines += 1;
+                    }
+                    continue;
+                }
+                if line_number <= report.lines {
+                    continue;
+                }
+                batch_lines += 1;
+                match parse_line(line, format, header.as_deref()) {
+                    Ok(ImportLine::Transaction(transaction)) => batch.push(*transaction),
+                    Ok(ImportLine::Skipped) => batch_skipped += 1,
+                    Err(e) if config.skip_invalid => {
+                        log::warn!("⚠️ Line {} of {} skipped: {}", line_number, source, e);
+                        batch_invalid += 1;
+                    }
+                    Err(e) => return Err(BlockchainError::Other(format!("Line {} of {}: {}", line_number, source, e))),
+                }
+            }
+            if batch.len() < batch_size && line.is_some() {
+                continue;
+            }
+
+            report.lines += batch_lines;
+            report.imported += batch.len() as u64;
+            report.skipped += batch_skipped;
+            report.invalid += batch_invalid;
+            let mut tx = self.pool().await.begin().await?;
+            for transaction in &batch {
+                write_transaction_row(&mut tx, transaction).await?;
+            }
+            sqlx::query("UPDATE bulk_imports SET lines_done = ?, imported = ?, skipped = ?, invalid = ? WHERE source = ?")
+                .bind(report.lines as i64)
+                .bind(report.imported as i64)
+                .bind(report.skipped as i64)
+                .bind(report.invalid as i64)
+                .bind(source)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            let imported_now = report.imported - report.resumed_from.min(report.imported);
+            progress(&BulkImportProgress {
+                phase: BulkImportPhase::Rows,
+                lines: report.lines,
+                imported: report.imported,
+                rate: imported_now as f64 / start.elapsed().as_secs_f64().max(0.001),
+                derived: 0,
+            });
+            batch.clear();
+            (batch_lines, batch_skipped, batch_invalid) = (0, 0, 0);
+            if line.is_none() {
+                break;
+            }
+        }
+
+        self.finish_bulk_import(source, config, &mut report, &mut progress).await?;
+        log::info!(
+            "📥 Imported {} transaction(s) from {} ({} skipped, {} invalid)",
+            report.imported, source, report.skipped, report.invalid
+        );
+        Ok(report)
+    }
+
+    /// Recorded progress of `source`, with when it completed
+    async fn bulk_import_state(&self, source: &str) -> Result<Option<(BulkImportReport, Option<i64>)>, BlockchainError> {
+        let row = sqlx::query(
+            "SELECT lines_done, imported, skipped, invalid, dropped_indexes, started_at, completed_at FROM bulk_imports WHERE source = ?"
+        )
+        .bind(source)
+        .fetch_optional(&self.pool().await)
+        .await?;
+        row.map(|row| {
+            let dropped: Vec<(String, String)> = serde_json::from_str(&row.get::<String, _>("dropped_indexes"))?;
+            let completed_at: Option<i64> = row.get("completed_at");
+            Ok((
+                BulkImportReport {
+                    source: source.to_string(),
+                    lines: row.get::<i64, _>("lines_done") as u64,
+                    imported: row.get::<i64, _>("imported") as u64,
+                    skipped: row.get::<i64, _>("skipped") as u64,
+                    invalid: row.get::<i64, _>("invalid") as u64,
+                    indexes_rebuilt: dropped.into_iter().map(|(name, _)| name).collect(),
+                    started_at: row.get("started_at"),
+                    completed_at: completed_at.unwrap_or_default(),
+                    ..BulkImportReport::default()
+                },
+                completed_at,
+            ))
+        })
+        .transpose()
+    }
+
+    /// Record a new import of `source` and drop the secondary indexes
+    async fn start_bulk_import(&self, source: &str, format: BulkImportFormat) -> Result<BulkImportReport, BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        let mut dropped = Vec::new();
+        for table in INDEXED_TABLES {
+            let indexes = sqlx::query("SELECT name, sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ? AND sql IS NOT NULL")
+                .bind(table)
+                .fetch_all(&mut *tx)
+                .await?;
+            for index in indexes {
+                let (name, sql): (String, String) = (index.get("name"), index.get("sql"));
+                sqlx::query(&format!("DROP INDEX \"{}\"", name)).execute(&mut *tx).await?;
+                dropped.push((name, sql));
+            }
+        }
+        let started_at = Utc::now().timestamp();
+        sqlx::query(
+            "INSERT INTO bulk_imports (source, format, lines_done, imported, skipped, invalid, dropped_indexes, started_at) VALUES (?, ?, 0, 0, 0, 0, ?, ?)"
+        )
+        .bind(source)
+        .bind(format.as_str())
+        .bind(serde_json::to_string(&dropped)?)
+        .bind(started_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        log::info!("📥 Importing {} as {}, with {} index(es) deferred", source, format, dropped.len());
+
+        Ok(BulkImportReport {
+            source: source.to_string(),
+            indexes_rebuilt: dropped.into_iter().map(|(name, _)| name).collect(),
+            started_at,
+            ..BulkImportReport::default()
+        })
+    }
+
+    /// Create the dropped indexes again, derive parent links and DAG nodes,
+    /// and mark `source` complete
+    async fn finish_bulk_import<F>(
+        &self,
+        source: &str,
+        config: &BulkImportConfig,
+        report: &mut BulkImportReport,
+        progress: &mut F,
+    ) -> Result<(), BlockchainError>
+    where
+        F: FnMut(&BulkImportProgress),
+    {
+        let dropped: String = sqlx::query_scalar("SELECT dropped_indexes FROM bulk_imports WHERE source = ?")
+            .bind(source)
+            .fetch_one(&self.pool().await)
+            .await?;
+        let dropped: Vec<(String, String)> = serde_json::from_str(&dropped)?;
+        // An interrupted finish may have created some already
+        for (name, sql) in &dropped {
+            let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = ?")
+                .bind(name)
+                .fetch_optional(&self.pool().await)
+                .await?;
+            if exists.is_none() {
+                sqlx::query(sql).execute(&self.pool().await).await?;
+            }
+        }
+        let (lines, imported) = (report.lines, report.imported);
+        progress(&BulkImportProgress { phase: BulkImportPhase::Indexes, lines, imported, rate: 0.0, derived: 0 });
+
+        report.reindex = Some(self.reindex(&config.reindex, |reindex| {
+            progress(&BulkImportProgress { phase: BulkImportPhase::DerivedTables, lines, imported, rate: 0.0, derived: reindex.processed });
+        }).await?);
+
+        report.completed_at = Utc::now().timestamp();
+        sqlx::query("UPDATE bulk_imports SET completed_at = ? WHERE source = ?")
+            .bind(report.completed_at)
+            .bind(source)
+            .execute(&self.pool().await)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Read one line of `format`; CSV lines need the header's column names
+fn parse_line(line: &str, format: BulkImportFormat, header: Option<&[String]>) -> Result<ImportLine, BlockchainError> {
+    if line.trim().is_empty() {
+        return Ok(ImportLine::Skipped);
+    }
+    let transaction = match format {
+        BulkImportFormat::Jsonl => serde_json::from_str(line)?,
+        BulkImportFormat::Csv => {
+            let header = header.unwrap_or_default();
+            let fields: Vec<&str> = line.split(',').collect();
+            if fields.len() != header.len() {
+                return Err(BlockchainError::Other(format!("{} field(s), expected {}", fields.len(), header.len())));
+            }
+            let columns = header.iter()
+                .zip(fields)
+                .filter(|(_, field)| !field.is_empty())
+                .map(|(name, field)| (name.clone(), serde_json::Value::String(field.trim_matches('"').to_string())))
+                .collect();
+            transaction_from_columns(&columns)?
+        }
+        BulkImportFormat::Export => {
+            let mut entry: HashMap<String, serde_json::Value> = serde_json::from_str(line)?;
+            if entry.get("table").and_then(|table| table.as_str()) != Some("transactions") {
+                return Ok(ImportLine::Skipped);
+            }
+            let columns = match entry.remove("row") {
+                Some(serde_json::Value::Object(row)) => row.into_iter().filter(|(_, value)| !value.is_null()).collect(),
+                _ => return Err(BlockchainError::Other("Export line has no row".to_string())),
+            };
+            transaction_from_columns(&columns)?
+        }
+    };
+    Ok(ImportLine::Transaction(Box::new(transaction)))
+}
+
+/// A transaction from its `transactions` table columns, blobs in hex
+fn transaction_from_columns(columns: &HashMap<String, serde_json::Value>) -> Result<Transaction, BlockchainError> {
+    let text = |name: &str| -> Option<String> {
+        columns.get(name).map(|value| match value {
+            serde_json::Value::String(value) => value.clone(),
+            other => other.to_string(),
+        })
+    };
+    let required = |name: &str| text(name).ok_or_else(|| BlockchainError::Other(format!("Missing column {}", name)));
+    let bytes = |name: &str| -> Result<Vec<u8>, BlockchainError> {
+        hex::decode(required(name)?).map_err(|_| BlockchainError::Other(format!("Column {} is not hex", name)))
+    };
+    let number = |name: &str| -> Result<u64, BlockchainError> {
+        required(name)?.parse().map_err(|_| BlockchainError::Other(format!("Column {} is not a number", name)))
+    };
+
+    let parents: Vec<String> = match text("parents") {
+        None => Vec::new(),
+        Some(parents) if parents.starts_with('[') => serde_json::from_str(&parents)?,
+        Some(parents) => parents.split(';').filter(|id| !id.is_empty()).map(str::to_string).collect(),
+    };
+    let mut transaction = Transaction {
+        id: TransactionId::default(),
+        sender: bytes("sender")?,
+        receiver: bytes("receiver")?,
+        amount: parse_amount(&required("amount")?).map_err(BlockchainError::Other)?,
+        fee: text("fee").map(|fee| parse_amount(&fee)).transpose().map_err(BlockchainError::Other)?.unwrap_or(0),
+        nonce: number("nonce")?,
+        timestamp: number("timestamp")?,
+        parents: parents.iter().map(|id| TransactionId::from_string(id)).collect::<Result<_, _>>()?,
+        signature: bytes("signature")?,
+        signature_scheme: parse_signature_scheme(text("signature_scheme").as_deref())?,
+        quantum_proof: QuantumProof {
+            prime_hash: bytes("prime_hash")?,
+            resistance_score: number("resistance_score")? as u32,
+            proof_timestamp: number("proof_timestamp")?,
+        },
+        metadata: text("metadata").map(|_| bytes("metadata")).transpose()?,
+    };
+    transaction.id = match text("id") {
+        Some(id) => TransactionId::from_string(&id)?,
+        None => transaction.compute_id(),
+    };
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000 + nonce,
+            parents,
+            signature: vec![3u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![4u8; 32], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    /// Genesis and a chain of `count` transactions on it
+    fn chain(count: u64) -> Vec<Transaction> {
+        let mut transactions = vec![transaction(0, vec![])];
+        for nonce in 1..=count {
+            let parent = transactions.last().unwrap().id.clone();
+            transactions.push(transaction(nonce, vec![parent]));
+        }
+        transactions
+    }
+
+    async fn database(temp_dir: &tempfile::TempDir) -> DatabaseManager {
+        DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_import_resumes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = database(&temp_dir).await;
+        let transactions = chain(9);
+        let lines: Vec<String> = transactions.iter().map(|transaction| serde_json::to_string(transaction).unwrap()).collect();
+        let config = BulkImportConfig { batch_size: 4, ..BulkImportConfig::default() };
+
+        // The first run fails on a line that is cut short, after two batches
+        let mut truncated = lines[..8].join("\n");
+        truncated.push_str("\n{\"id\":");
+        assert!(db.bulk_import_from("history", truncated.as_bytes(), BulkImportFormat::Jsonl, &config, |_| {}).await.is_err());
+        assert_eq!(db.get_transaction_count().await.unwrap(), 8);
+
+        let mut phases = Vec::new();
+        let full = lines.join("\n");
+        let report = db.bulk_import_from("history", full.as_bytes(), BulkImportFormat::Jsonl, &config, |progress| {
+            phases.push(progress.phase);
+        }).await.unwrap();
+        assert_eq!((report.resumed_from, report.lines, report.imported), (8, 10, 10));
+        assert_eq!(phases[0], BulkImportPhase::Rows);
+        assert_eq!(phases.last(), Some(&BulkImportPhase::DerivedTables));
+        assert!(report.indexes_rebuilt.contains(&"idx_transactions_timestamp_id".to_string()));
+
+        // Parent links and DAG nodes were derived from the rows
+        let last = &transactions[9];
+        assert_eq!(db.get_transaction(&last.id).await.unwrap().unwrap().parents, last.parents);
+        assert!(db.get_dag_node(&transactions[3].id).await.unwrap().unwrap().children.contains(&transactions[4].id));
+
+        // A completed source is not imported again
+        let again = db.bulk_import_from("history", full.as_bytes(), BulkImportFormat::Jsonl, &config, |_| {}).await.unwrap();
+        assert_eq!(again.imported, 10);
+    }
+
+    #[tokio::test]
+    async fn test_csv_and_export_lines() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = database(&temp_dir).await;
+        let transactions = chain(1);
+        let (genesis, child) = (&transactions[0], &transactions[1]);
+
+        let csv = format!(
+            "id,sender,receiver,amount,fee,nonce,timestamp,parents,signature,signature_scheme,prime_hash,resistance_score,proof_timestamp,metadata\n\
+             {},{},{},5,1,0,{},,{},Hybrid,{},80,0,\n",
+            genesis.id, hex::encode(&genesis.sender), hex::encode(&genesis.receiver), genesis.timestamp,
+            hex::encode(&genesis.signature), hex::encode(&genesis.quantum_proof.prime_hash),
+        );
+        let report = db.bulk_import_from("genesis.csv", csv.as_bytes(), BulkImportFormat::Csv, &BulkImportConfig::default(), |_| {}).await.unwrap();
+        assert_eq!((report.lines, report.imported), (2, 1));
+
+        let export = format!(
+            "{}\n{}\n",
+            serde_json::json!({"table": "accounts", "row": {"address": "ab"}}),
+            serde_json::json!({"table": "transactions", "row": {
+                "id": child.id.as_string(), "sender": hex::encode(&child.sender), "receiver": hex::encode(&child.receiver),
+                "amount": "5", "fee": "1", "nonce": 1, "timestamp": child.timestamp,
+                "parents": serde_json::to_string(&vec![genesis.id.as_string()]).unwrap(),
+                "signature": hex::encode(&child.signature), "signature_scheme": "Hybrid",
+                "prime_hash": hex::encode(&child.quantum_proof.prime_hash), "resistance_score": 80, "proof_timestamp": 0,
+                "metadata": null,
+            }}),
+        );
+        let report = db.bulk_import_from("export.json", export.as_bytes(), BulkImportFormat::Export, &BulkImportConfig::default(), |_| {}).await.unwrap();
+        assert_eq!((report.imported, report.skipped), (1, 1));
+
+        let stored = db.get_transaction(&child.id).await.unwrap().unwrap();
+        assert!(stored.has_valid_id());
+        assert_eq!(stored.parents, vec![genesis.id.clone()]);
+        assert!(db.get_transaction(&genesis.id).await.unwrap().unwrap().has_valid_id());
+    }
+}
@@ -0,0 +1,195 @@
+//! Streaming transactions with keyset cursors
+//!
+//! `get_transactions` pages with `OFFSET`, which rereads every skipped row
+//! and holds the whole page in memory. `stream_transactions` walks the same
+//! newest-first order a page at a time, each page starting after the last
+//! row of the one before, so memory stays bounded however many rows there
+//! are. A caller that stops can resume from `TransactionCursor::of` the last
+//! transaction it handled.
+//!
+//! `get_address_history` pages one address's transactions the same way,
+//! seeking the indexed hex `sender_hex` and `receiver_hex` columns.
+
+use super::backend::parse_node_status;
+use super::DatabaseManager;
+use crate::core::Transaction;
+use crate::BlockchainError;
+use futures::{Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+/// Transactions read per page of a stream
+pub const STREAM_PAGE: usize = 512;
+
+/// Position in the newest-first order of transactions
+///
+/// Transactions order by timestamp, then by the string form of their ID,
+/// both descending; a cursor stands just after the transaction it was made of.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TransactionCursor {
+    pub timestamp: u64,
+    pub id: String,
+}
+
+impl TransactionCursor {
+    /// Cursor continuing after `transaction`
+    pub fn of(transaction: &Transaction) -> Self {
+        Self {
+            timestamp: transaction.timestamp,
+            id: transaction.id.as_string(),
+        }
+    }
+
+    /// Whether `transaction` comes after the cursor
+    pub fn precedes(&self, transaction: &Transaction) -> bool {
+        Self::of(transaction) < *self
+    }
+}
+
+impl DatabaseManager {
+    /// Every transaction newest first, optionally only those whose DAG node
+    /// has `status`, starting after `after`
+    pub fn stream_transactions(
+        &self,
+        status: Option<&str>,
+        after: Option<TransactionCursor>,
+    ) -> impl Stream<Item = Result<Transaction, BlockchainError>> + '_ {
+        let (status, start) = match status.map(parse_node_status).transpose() {
+            Ok(status) => (status, Some(after)),
+            // No node has a status that does not exist
+            Err(_) => (None, None),
+        };
+
+        futures::stream::try_unfold(start, move |cursor| {
+            let status = status.clone();
+            async move {
+                let cursor = match cursor {
+                    Some(cursor) => cursor,
+                    None => return Ok::<_, BlockchainError>(None),
+                };
+                self.commit_queued().await?;
+                let page = self.backend.transactions_before(status.as_ref(), cursor.as_ref(), STREAM_PAGE).await?;
+                // Cursors follow stored rows, including any dropped as corrupted
+                let next = match page.last() {
+                    Some(last) if page.len() == STREAM_PAGE => Some(Some(TransactionCursor::of(&last.transaction))),
+                    _ => None,
+                };
+                let transactions = self.verified_transactions(page).await?;
+                Ok(Some((futures::stream::iter(transactions.into_iter().map(Ok::<_, BlockchainError>)), next)))
+            }
+        })
+        .try_flatten()
+    }
+
+    /// Up to `limit` transactions sent or received by `address`, newest
+    /// first, starting after `before`
+    pub async fn get_address_history(
+        &self,
+        address: &[u8],
+        before: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<Vec<Transaction>, BlockchainError> {
+        self.commit_queued().await?;
+        let stored = self.backend.address_history(address, before, limit).await?;
+        self.verified_transactions(stored).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DAGNode, NodeStatus, QuantumProof};
+    use crate::storage::DatabaseConfig;
+    use crate::TransactionId;
+
+    fn transaction(nonce: u64, timestamp: u64) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 1,
+            nonce,
+            timestamp,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: timestamp },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[tokio::test]
+    async fn test_stream_walks_every_page() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        // Several transactions share each timestamp, so pages split ties
+        let count = STREAM_PAGE * 2 + 7;
+        let nodes: Vec<DAGNode> = (0..count as u64)
+            .map(|nonce| DAGNode {
+                transaction: transaction(nonce, 1_700_000_000 + nonce / 3),
+                children: vec![],
+                weight: 1,
+                confidence: 0.0,
+                status: if nonce % 2 == 0 { NodeStatus::Confirmed } else { NodeStatus::Pending },
+                quantum_score: 80,
+            })
+            .collect();
+        db.store_bundle(&nodes).await.unwrap();
+
+        let streamed: Vec<Transaction> = db.stream_transactions(None, None).try_collect().await.unwrap();
+        let mut expected: Vec<TransactionCursor> = nodes.iter().map(|node| TransactionCursor::of(&node.transaction)).collect();
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(streamed.iter().map(TransactionCursor::of).collect::<Vec<_>>(), expected);
+
+        // Resuming after a transaction continues with the one following it
+        let resumed: Vec<Transaction> = db.stream_transactions(None, Some(expected[99].clone())).try_collect().await.unwrap();
+        assert_eq!(resumed.len(), count - 100);
+        assert_eq!(TransactionCursor::of(&resumed[0]), expected[100]);
+
+        let confirmed: Vec<Transaction> = db.stream_transactions(Some("Confirmed"), None).try_collect().await.unwrap();
+        assert_eq!(confirmed.len(), (count + 1) / 2);
+        assert!(confirmed.iter().all(|transaction| transaction.nonce % 2 == 0));
+
+        let unknown: Vec<Transaction> = db.stream_transactions(Some("Unknown"), None).try_collect().await.unwrap();
+        assert!(unknown.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_address_history_pages() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        let nodes: Vec<DAGNode> = (0..25u64)
+            .map(|nonce| {
+                let mut transaction = transaction(nonce, 1_700_000_000 + nonce / 2);
+                // Every third transaction is sent back to the usual sender
+                if nonce % 3 == 0 {
+                    std::mem::swap(&mut transaction.sender, &mut transaction.receiver);
+                    transaction.id = transaction.compute_id();
+                }
+                DAGNode { transaction, children: vec![], weight: 1, confidence: 0.0, status: NodeStatus::Pending, quantum_score: 80 }
+            })
+            .collect();
+        db.store_bundle(&nodes).await.unwrap();
+
+        let mut history = Vec::new();
+        let mut before = None;
+        loop {
+            let page = db.get_address_history(&[1u8; 32], before.as_ref(), 4).await.unwrap();
+            before = page.last().map(TransactionCursor::of);
+            history.extend(page.iter().map(TransactionCursor::of));
+            if page.len() < 4 {
+                break;
+            }
+        }
+        let mut expected: Vec<TransactionCursor> = nodes.iter().map(|node| TransactionCursor::of(&node.transaction)).collect();
+        expected.sort_by(|a, b| b.cmp(a));
+        assert_eq!(history, expected);
+
+        let hex: Option<String> = sqlx::query_scalar("SELECT receiver_hex FROM transactions WHERE id = ?")
+            .bind(nodes[0].transaction.id.as_string())
+            .fetch_one(&db.pool().await)
+            .await
+            .unwrap();
+        assert_eq!(hex, Some(hex::encode([1u8; 32])));
+    }
+}
@@ -0,0 +1,163 @@
+//! Encryption at rest with SQLCipher
+//!
+//! Nodes built with the `sqlcipher` feature link SQLCipher in place of
+//! SQLite, and open the database with `DatabaseConfig::encryption_key` when
+//! one is set. The key comes from `SecurityConfig::database_key`, or else
+//! from the `QDAG_DATABASE_KEY` environment variable. Backups and the
+//! databases attached while taking them share the node's key. RocksDB files
+//! are not encrypted; nodes on the RocksDB backend only encrypt the SQLite
+//! tables.
+//!
+//! `rekey_database` changes the key of a stopped node's database, and also
+//! encrypts a plaintext database or decrypts an encrypted one.
+
+use super::DatabaseManager;
+use crate::BlockchainError;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{ConnectOptions, Connection};
+use std::str::FromStr;
+
+/// Environment variable holding the database key when the config has none
+pub const DATABASE_KEY_ENV: &str = "QDAG_DATABASE_KEY";
+
+/// Schema the re-encrypted copy is attached as
+const REKEY_SCHEMA: &str = "rekeyed";
+
+/// Whether this build links SQLCipher
+pub fn encryption_supported() -> bool {
+    cfg!(feature = "sqlcipher")
+}
+
+/// The configured key, or the one in `QDAG_DATABASE_KEY`
+pub fn resolve_encryption_key(configured: Option<&str>) -> Option<String> {
+    configured
+        .map(str::to_string)
+        .or_else(|| std::env::var(DATABASE_KEY_ENV).ok())
+        .filter(|key| !key.is_empty())
+}
+
+/// `key` as an SQL string literal
+fn key_literal(key: &str) -> String {
+    format!("'{}'", key.replace('\'', "''"))
+}
+
+/// Open with `key`, which SQLCipher applies before anything else reads the file
+pub(crate) fn with_key(options: SqliteConnectOptions, key: Option<&str>) -> Result<SqliteConnectOptions, BlockchainError> {
+    match key {
+        None => Ok(options),
+        Some(_) if !encryption_supported() => Err(BlockchainError::Other(
+            "Database encryption needs a node built with the `sqlcipher` feature".to_string(),
+        )),
+        Some(key) => Ok(options.pragma("key", key_literal(key))),
+    }
+}
+
+impl DatabaseManager {
+    /// Whether the database is opened with an encryption key
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption_key.is_some()
+    }
+
+    /// Key the database is opened with
+    pub(crate) fn encryption_key(&self) -> Option<&str> {
+        self.encryption_key.as_deref()
+    }
+
+    /// Change the key of the database at `path`, which must not be open
+    ///
+    /// `None` stands for a plaintext database, so going from `None` to a key
+    /// encrypts the database and going from a key to `None` decrypts it.
+    pub async fn rekey_database(path: &str, current: Option<&str>, new: Option<&str>) -> Result<(), BlockchainError> {
+        if !encryption_supported() {
+            return Err(BlockchainError::Other(
+                "Database encryption needs a node built with the `sqlcipher` feature".to_string(),
+            ));
+        }
+        let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", path))?;
+        let mut conn = with_key(options, current)?.connect().await?;
+        // Fails on a wrong key, before anything is written
+        sqlx::query("SELECT COUNT(*) FROM sqlite_master").fetch_one(&mut conn).await?;
+
+        if current == new {
+            conn.close().await?;
+            return Ok(());
+        }
+        match (current, new) {
+            (Some(_), Some(new)) => {
+                // Rekeying rewrites every page in place, outside a write-ahead log
+                sqlx::query("PRAGMA journal_mode = DELETE").execute(&mut conn).await?;
+                sqlx::query(&format!("PRAGMA rekey = {}", key_literal(new))).execute(&mut conn).await?;
+            }
+            // SQLCipher only rekeys between keys; other changes go through a copy
+            _ => {
+                let copy = format!("{}.rekeying", path);
+                let _ = tokio::fs::remove_file(&copy).await;
+                export_copy(&mut conn, &copy, new.unwrap_or("")).await?;
+                conn.close().await?;
+                tokio::fs::rename(&copy, path).await?;
+                log::info!("🔐 {} database {}", if new.is_some() { "Encrypted" } else { "Decrypted" }, path);
+                return Ok(());
+            }
+        }
+        conn.close().await?;
+        log::info!("🔐 Rekeyed database {}", path);
+        Ok(())
+    }
+}
+
+/// Copy the open database to `copy`, encrypted with `key` or in plaintext when empty
+async fn export_copy(conn: &mut SqliteConnection, copy: &str, key: &str) -> Result<(), BlockchainError> {
+    sqlx::query(&format!("ATTACH DATABASE ? AS {} KEY {}", REKEY_SCHEMA, key_literal(key)))
+        .bind(copy)
+        .execute(&mut *conn)
+        .await?;
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version").fetch_one(&mut *conn).await?;
+    sqlx::query(&format!("SELECT sqlcipher_export('{}')", REKEY_SCHEMA)).execute(&mut *conn).await?;
+    sqlx::query(&format!("PRAGMA {}.user_version = {}", REKEY_SCHEMA, version)).execute(&mut *conn).await?;
+    sqlx::query(&format!("DETACH DATABASE {}", REKEY_SCHEMA)).execute(&mut *conn).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_literal_escapes_quotes() {
+        assert_eq!(key_literal("it's"), "'it''s'");
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[test]
+    fn test_key_needs_sqlcipher() {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:").unwrap();
+        assert!(with_key(options.clone(), None).is_ok());
+        assert!(with_key(options, Some("secret")).is_err());
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn test_rekey_round_trip() {
+        use crate::storage::DatabaseConfig;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db").to_string_lossy().to_string();
+        let open = |key: Option<&str>| DatabaseManager::new(DatabaseConfig {
+            path: path.clone(),
+            encryption_key: key.map(str::to_string),
+            ..DatabaseConfig::default()
+        });
+
+        open(Some("first")).await.unwrap().close().await.unwrap();
+        assert!(open(None).await.is_err());
+        assert!(open(Some("wrong")).await.is_err());
+
+        DatabaseManager::rekey_database(&path, Some("first"), Some("second")).await.unwrap();
+        open(Some("second")).await.unwrap().close().await.unwrap();
+
+        DatabaseManager::rekey_database(&path, Some("second"), None).await.unwrap();
+        let db = open(None).await.unwrap();
+        assert!(!db.is_encrypted());
+        db.close().await.unwrap();
+    }
+}
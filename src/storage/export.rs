@@ -0,0 +1,762 @@
+//! Export and import of the SQLite tables
+//!
+//! Everything goes through sqlx, so no `sqlite3` binary is needed. Rows are
+//! streamed from one read transaction to a buffered file, and imports read
+//! their file line by line, so memory use does not grow with the tables.
+//!
+//! - SQL: a dump like `sqlite3 .dump` writes; the schema, one `INSERT` per
+//!   row, then indexes and triggers. Importing one replaces the database and
+//!   also accepts dumps written by `sqlite3`.
+//! - JSON: JSON lines, one `{"table": ..., "row": {...}}` object per row.
+//! - CSV: a directory with one `<table>.csv` per table, with a header row.
+//!   Text is always quoted, so an empty unquoted field is NULL.
+//!
+//! JSON and CSV carry no schema. Importing them replaces the rows of each
+//! table they hold, converting values by the declared column type; blobs are
+//! hex-encoded in both. Imports run in one transaction, so a failed import
+//! leaves the database as it was.
+//!
+//! On the RocksDB and in-memory backends transactions and DAG nodes are not
+//! kept in SQLite, so they are not exported.
+
+use super::{DatabaseManager, ExportFormat, ExportResult, ImportFormat, ImportResult};
+use crate::BlockchainError;
+use chrono::Utc;
+use futures::TryStreamExt;
+use sqlx::query::Query;
+use sqlx::sqlite::{Sqlite, SqliteArguments, SqliteConnection, SqliteRow};
+use sqlx::{Connection, Row, TypeInfo, ValueRef};
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter, Lines};
+
+/// A value as SQLite stores it
+#[derive(Debug, Clone, PartialEq)]
+enum SqlValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl SqlValue {
+    fn read(row: &SqliteRow, index: usize) -> Result<Self, BlockchainError> {
+        let raw = row.try_get_raw(index)?;
+        if raw.is_null() {
+            return Ok(SqlValue::Null);
+        }
+        let value = match raw.type_info().name() {
+            "INTEGER" | "BIGINT" => SqlValue::Integer(row.try_get_unchecked(index)?),
+            "REAL" => SqlValue::Real(row.try_get_unchecked(index)?),
+            "BLOB" => SqlValue::Blob(row.try_get_unchecked(index)?),
+            _ => SqlValue::Text(row.try_get_unchecked(index)?),
+        };
+        Ok(value)
+    }
+
+    /// The value as a SQL literal
+    fn literal(&self) -> String {
+        match self {
+            SqlValue::Null => "NULL".to_string(),
+            SqlValue::Integer(value) => value.to_string(),
+            SqlValue::Real(value) if value.is_finite() => format!("{:?}", value),
+            // SQLite reads out-of-range literals as infinities
+            SqlValue::Real(value) if *value > 0.0 => "9.0e+999".to_string(),
+            SqlValue::Real(_) => "-9.0e+999".to_string(),
+            SqlValue::Text(value) => format!("'{}'", value.replace('\'', "''")),
+            SqlValue::Blob(value) => format!("X'{}'", hex::encode(value)),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            SqlValue::Null => serde_json::Value::Null,
+            SqlValue::Integer(value) => (*value).into(),
+            SqlValue::Real(value) => serde_json::Number::from_f64(*value).map_or(serde_json::Value::Null, Into::into),
+            SqlValue::Text(value) => value.clone().into(),
+            SqlValue::Blob(value) => hex::encode(value).into(),
+        }
+    }
+
+    /// A JSON value read into a column declared as `declared`
+    fn from_json(value: serde_json::Value, declared: &str) -> Result<Self, BlockchainError> {
+        Ok(match value {
+            serde_json::Value::Null => SqlValue::Null,
+            serde_json::Value::Bool(value) => SqlValue::Integer(value.into()),
+            serde_json::Value::Number(number) => match number.as_i64() {
+                Some(value) => SqlValue::Integer(value),
+                None => SqlValue::Real(number.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(value) if blob_affinity(declared) => SqlValue::Blob(decode_hex(&value)?),
+            serde_json::Value::String(value) => SqlValue::Text(value),
+            other => SqlValue::Text(other.to_string()),
+        })
+    }
+
+    fn to_csv(&self) -> String {
+        match self {
+            SqlValue::Null => String::new(),
+            SqlValue::Integer(value) => value.to_string(),
+            SqlValue::Real(value) => format!("{:?}", value),
+            SqlValue::Text(value) => format!("\"{}\"", value.replace('"', "\"\"")),
+            SqlValue::Blob(value) => hex::encode(value),
+        }
+    }
+
+    /// A CSV field read into a column declared as `declared`
+    fn from_csv(field: CsvField, declared: &str) -> Result<Self, BlockchainError> {
+        if field.quoted {
+            return Ok(SqlValue::Text(field.text));
+        }
+        if field.text.is_empty() {
+            return Ok(SqlValue::Null);
+        }
+        if blob_affinity(declared) {
+            return Ok(SqlValue::Blob(decode_hex(&field.text)?));
+        }
+        if let Ok(value) = field.text.parse() {
+            return Ok(SqlValue::Integer(value));
+        }
+        match field.text.parse() {
+            Ok(value) => Ok(SqlValue::Real(value)),
+            Err(_) => Ok(SqlValue::Text(field.text)),
+        }
+    }
+
+    fn bind<'q>(self, query: Query<'q, Sqlite, SqliteArguments<'q>>) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+        match self {
+            SqlValue::Null => query.bind(None::<i64>),
+            SqlValue::Integer(value) => query.bind(value),
+            SqlValue::Real(value) => query.bind(value),
+            SqlValue::Text(value) => query.bind(value),
+            SqlValue::Blob(value) => query.bind(value),
+        }
+    }
+}
+
+/// Whether SQLite gives a column declared as `declared` blob affinity
+fn blob_affinity(declared: &str) -> bool {
+    let declared = declared.to_ascii_uppercase();
+    let other = ["INT", "CHAR", "CLOB", "TEXT"].iter().any(|name| declared.contains(name));
+    !other && (declared.is_empty() || declared.contains("BLOB"))
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, BlockchainError> {
+    hex::decode(value).map_err(|e| BlockchainError::Other(format!("Invalid hex blob in import: {}", e)))
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Tables to export, in creation order
+async fn user_tables(conn: &mut SqliteConnection) -> Result<Vec<(String, String)>, BlockchainError> {
+    Ok(sqlx::query_as("SELECT name, sql FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY rowid")
+        .fetch_all(conn)
+        .await?)
+}
+
+/// Column names and declared types of `table`, in the order `SELECT *` returns them
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<(String, String)>, BlockchainError> {
+    Ok(sqlx::query_as("SELECT name, type FROM pragma_table_info(?) ORDER BY cid")
+        .bind(table)
+        .fetch_all(conn)
+        .await?)
+}
+
+impl DatabaseManager {
+    /// Export the database to `export_path` in `format`
+    pub async fn export(&self, export_path: &str, format: ExportFormat) -> Result<ExportResult, BlockchainError> {
+        self.commit_queued().await?;
+        let export_path = match format {
+            ExportFormat::SQL if !export_path.ends_with(".sql") => format!("{}.sql", export_path),
+            ExportFormat::JSON if !export_path.ends_with(".jsonl") => format!("{}.jsonl", export_path),
+            _ => export_path.to_string(),
+        };
+
+        // Ensure export directory exists
+        let directory = match format {
+            ExportFormat::CSV => Some(Path::new(&export_path)),
+            ExportFormat::SQL | ExportFormat::JSON => Path::new(&export_path).parent(),
+        };
+        if let Some(directory) = directory {
+            tokio::fs::create_dir_all(directory).await?;
+        }
+
+        // One read transaction, so every table is exported as of the same moment
+        let mut tx = self.pool().await.begin().await?;
+        let (rows, file_size) = match format {
+            ExportFormat::SQL => write_sql(&mut tx, &export_path).await?,
+            ExportFormat::JSON => write_json(&mut tx, &export_path).await?,
+            ExportFormat::CSV => write_csv(&mut tx, &export_path).await?,
+        };
+        tx.commit().await?;
+
+        log::info!("📤 Database exported to {:?}: {} ({} rows)", format, export_path, rows);
+        Ok(ExportResult {
+            success: true,
+            export_path,
+            export_format: format,
+            file_size,
+            export_timestamp: Utc::now().timestamp(),
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Export database to SQL format
+    pub async fn export_sql(&self, export_path: &str) -> Result<ExportResult, BlockchainError> {
+        self.export(export_path, ExportFormat::SQL).await
+    }
+
+    /// Import `import_path`, written in `format`, into the database
+    ///
+    /// A copy of the database file is kept first, unless it is in memory.
+    pub async fn import(&self, import_path: &str, format: ImportFormat) -> Result<ImportResult, BlockchainError> {
+        if tokio::fs::metadata(import_path).await.is_err() {
+            return Err(BlockchainError::Other(format!("Import file not found: {}", import_path)));
+        }
+        self.commit_queued().await?;
+
+        // Create backup before import
+        let pre_import_backup = match self.get_database_path().await.ok() {
+            Some(database_path) if tokio::fs::metadata(&database_path).await.is_ok() => {
+                let pre_import_backup = format!("{}.pre_import_{}", database_path, Utc::now().timestamp());
+                tokio::fs::copy(&database_path, &pre_import_backup).await?;
+                log::info!("📦 Created pre-import backup: {}", pre_import_backup);
+                Some(pre_import_backup)
+            }
+            _ => None,
+        };
+
+        // Rows arrive in file order, not in the order foreign keys need
+        let mut conn = self.pool().await.acquire().await?;
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await?;
+        let imported = match format {
+            ImportFormat::SQL => read_sql(&mut conn, import_path).await,
+            ImportFormat::JSON => read_json(&mut conn, import_path).await,
+            ImportFormat::CSV => read_csv(&mut conn, import_path).await,
+        };
+        sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await?;
+        drop(conn);
+        let warnings = imported?;
+
+        // Dumps from older nodes get the tables and triggers added since
+        self.init_database().await?;
+        self.init_migrations(true).await?;
+
+        log::info!("📥 Database imported from {:?}: {}", format, import_path);
+        Ok(ImportResult {
+            success: true,
+            import_path: import_path.to_string(),
+            import_format: format,
+            pre_import_backup,
+            import_timestamp: Utc::now().timestamp(),
+            warnings,
+        })
+    }
+
+    /// Import database from SQL format
+    pub async fn import_sql(&self, sql_path: &str) -> Result<ImportResult, BlockchainError> {
+        self.import(sql_path, ImportFormat::SQL).await
+    }
+}
+
+async fn create_writer(path: &str) -> Result<BufWriter<File>, BlockchainError> {
+    Ok(BufWriter::new(File::create(path).await?))
+}
+
+async fn finish_writer(mut writer: BufWriter<File>, path: &str) -> Result<u64, BlockchainError> {
+    writer.flush().await?;
+    Ok(tokio::fs::metadata(path).await?.len())
+}
+
+/// Write a SQL dump; returns the rows written and the file size
+async fn write_sql(conn: &mut SqliteConnection, path: &str) -> Result<(u64, u64), BlockchainError> {
+    let mut writer = create_writer(path).await?;
+    writer.write_all(b"PRAGMA foreign_keys=OFF;\nBEGIN TRANSACTION;\n").await?;
+
+    let mut rows = 0;
+    for (table, sql) in user_tables(conn).await? {
+        writer.write_all(format!("{};\n", sql).as_bytes()).await?;
+        rows += write_inserts(conn, &mut writer, &table).await?;
+    }
+
+    let has_sequence: bool = sqlx::query_scalar("SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'sqlite_sequence'")
+        .fetch_one(&mut *conn)
+        .await?;
+    if has_sequence {
+        writer.write_all(b"DELETE FROM sqlite_sequence;\n").await?;
+        write_inserts(conn, &mut writer, "sqlite_sequence").await?;
+    }
+
+    let schema: Vec<String> = sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type IN ('index', 'trigger', 'view') AND sql IS NOT NULL ORDER BY rowid")
+        .fetch_all(&mut *conn)
+        .await?;
+    for sql in schema {
+        writer.write_all(format!("{};\n", sql).as_bytes()).await?;
+    }
+    writer.write_all(b"COMMIT;\n").await?;
+    Ok((rows, finish_writer(writer, path).await?))
+}
+
+async fn write_inserts(conn: &mut SqliteConnection, writer: &mut BufWriter<File>, table: &str) -> Result<u64, BlockchainError> {
+    let query = format!("SELECT * FROM {}", quote_identifier(table));
+    let mut stream = sqlx::query(&query).fetch(conn);
+    let mut rows = 0;
+    while let Some(row) = stream.try_next().await? {
+        let values = (0..row.len())
+            .map(|index| SqlValue::read(&row, index).map(|value| value.literal()))
+            .collect::<Result<Vec<_>, _>>()?;
+        writer.write_all(format!("INSERT INTO {} VALUES({});\n", quote_identifier(table), values.join(",")).as_bytes()).await?;
+        rows += 1;
+    }
+    Ok(rows)
+}
+
+/// Write JSON lines; returns the rows written and the file size
+async fn write_json(conn: &mut SqliteConnection, path: &str) -> Result<(u64, u64), BlockchainError> {
+    let mut writer = create_writer(path).await?;
+    let mut rows = 0;
+    for (table, _) in user_tables(conn).await? {
+        let columns = table_columns(conn, &table).await?;
+        let query = format!("SELECT * FROM {}", quote_identifier(&table));
+        let mut stream = sqlx::query(&query).fetch(&mut *conn);
+        while let Some(row) = stream.try_next().await? {
+            let mut fields = serde_json::Map::new();
+            for (index, (column, _)) in columns.iter().enumerate() {
+                fields.insert(column.clone(), SqlValue::read(&row, index)?.to_json());
+            }
+            let line = serde_json::json!({ "table": table, "row": fields });
+            writer.write_all(format!("{}\n", line).as_bytes()).await?;
+            rows += 1;
+        }
+    }
+    Ok((rows, finish_writer(writer, path).await?))
+}
+
+/// Write one CSV file per table into `directory`; returns the rows written and their total size
+async fn write_csv(conn: &mut SqliteConnection, directory: &str) -> Result<(u64, u64), BlockchainError> {
+    let (mut rows, mut size) = (0, 0);
+    for (table, _) in user_tables(conn).await? {
+        let path = Path::new(directory).join(format!("{}.csv", table)).to_string_lossy().into_owned();
+        let mut writer = create_writer(&path).await?;
+        let columns = table_columns(conn, &table).await?;
+        let header: Vec<String> = columns.iter().map(|(column, _)| column.clone()).collect();
+        writer.write_all(format!("{}\n", header.join(",")).as_bytes()).await?;
+
+        let query = format!("SELECT * FROM {}", quote_identifier(&table));
+        let mut stream = sqlx::query(&query).fetch(&mut *conn);
+        while let Some(row) = stream.try_next().await? {
+            let fields = (0..columns.len())
+                .map(|index| SqlValue::read(&row, index).map(|value| value.to_csv()))
+                .collect::<Result<Vec<_>, _>>()?;
+            writer.write_all(format!("{}\n", fields.join(",")).as_bytes()).await?;
+            rows += 1;
+        }
+        drop(stream);
+        size += finish_writer(writer, &path).await?;
+    }
+    Ok((rows, size))
+}
+
+/// Replace the database with a SQL dump; returns import warnings
+async fn read_sql(conn: &mut SqliteConnection, path: &str) -> Result<Vec<String>, BlockchainError> {
+    let mut lines = BufReader::new(File::open(path).await?).lines();
+    let mut tx = conn.begin().await?;
+
+    // The dump recreates every table, with its indexes and triggers
+    let objects: Vec<(String, String)> = sqlx::query_as("SELECT type, name FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'")
+        .fetch_all(&mut *tx)
+        .await?;
+    for (kind, name) in objects {
+        sqlx::query(&format!("DROP {} IF EXISTS {}", kind.to_uppercase(), quote_identifier(&name)))
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let mut splitter = StatementSplitter::default();
+    let mut executed = 0;
+    loop {
+        let line = lines.next_line().await?;
+        let statements = match &line {
+            Some(line) => splitter.push_line(line),
+            None => splitter.finish().into_iter().collect(),
+        };
+        for statement in statements {
+            if is_dump_framing(&statement) {
+                continue;
+            }
+            executed += 1;
+            sqlx::query(&statement)
+                .persistent(false)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| BlockchainError::Other(format!("SQL import failed at statement {}: {}", executed, e)))?;
+        }
+        if line.is_none() {
+            break;
+        }
+    }
+    tx.commit().await?;
+    log::debug!("Imported {} SQL statement(s)", executed);
+    Ok(Vec::new())
+}
+
+/// Whether `statement` only frames a dump; imports run in a transaction of their own
+fn is_dump_framing(statement: &str) -> bool {
+    let statement = statement.trim().trim_end_matches(';').trim().to_ascii_uppercase();
+    statement == "BEGIN TRANSACTION" || statement == "BEGIN" || statement == "COMMIT" || statement.starts_with("PRAGMA FOREIGN_KEYS")
+}
+
+/// Splits a SQL script into statements as its lines arrive
+#[derive(Default)]
+struct StatementSplitter {
+    statement: String,
+    /// Character closing the quoted string or identifier being read
+    quote: Option<char>,
+}
+
+impl StatementSplitter {
+    /// Add a line of the script, returning the statements it completes
+    fn push_line(&mut self, line: &str) -> Vec<String> {
+        let mut complete = Vec::new();
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match self.quote {
+                Some(close) => {
+                    self.statement.push(c);
+                    if c == close {
+                        self.quote = None;
+                    }
+                }
+                // A comment runs to the end of the line
+                None if c == '-' && chars.peek() == Some(&'-') => break,
+                None => {
+                    self.statement.push(c);
+                    match c {
+                        '\'' | '"' | '`' => self.quote = Some(c),
+                        '[' => self.quote = Some(']'),
+                        ';' if !self.in_trigger_body() => {
+                            complete.push(std::mem::take(&mut self.statement).trim().to_string());
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        self.statement.push('\n');
+        complete
+    }
+
+    /// Whatever is left once the script ends
+    fn finish(&mut self) -> Option<String> {
+        let statement = std::mem::take(&mut self.statement);
+        let statement = statement.trim();
+        (!statement.is_empty()).then(|| statement.to_string())
+    }
+
+    /// Whether the `;` just read ends a statement inside a trigger rather than the trigger
+    fn in_trigger_body(&self) -> bool {
+        let upper = self.statement.to_ascii_uppercase();
+        let words: Vec<&str> = upper.split_whitespace().take(3).collect();
+        let is_trigger = match words.as_slice() {
+            ["CREATE", "TRIGGER", ..] => true,
+            ["CREATE", "TEMP" | "TEMPORARY", "TRIGGER"] => true,
+            _ => false,
+        };
+        if !is_trigger {
+            return false;
+        }
+        let body = upper.trim_end_matches(';').trim_end();
+        let ends_trigger = body.strip_suffix("END")
+            .is_some_and(|before| !before.ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_'));
+        !ends_trigger
+    }
+}
+
+/// Declared column types of the tables an import has reached; `None` for tables missing here
+type ImportedTables = HashMap<String, Option<HashMap<String, String>>>;
+
+/// Column types of `table`, emptying it the first time an import reaches it
+async fn prepare_table<'t>(
+    conn: &mut SqliteConnection,
+    tables: &'t mut ImportedTables,
+    table: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Option<&'t HashMap<String, String>>, BlockchainError> {
+    if !tables.contains_key(table) {
+        let columns = table_columns(conn, table).await?;
+        let columns = if columns.is_empty() {
+            warnings.push(format!("Skipped rows of table {}, which does not exist", table));
+            None
+        } else {
+            sqlx::query(&format!("DELETE FROM {}", quote_identifier(table))).execute(&mut *conn).await?;
+            Some(columns.into_iter().collect())
+        };
+        tables.insert(table.to_string(), columns);
+    }
+    Ok(tables[table].as_ref())
+}
+
+async fn insert_row(conn: &mut SqliteConnection, table: &str, values: Vec<(String, SqlValue)>) -> Result<(), BlockchainError> {
+    let columns: Vec<String> = values.iter().map(|(column, _)| quote_identifier(column)).collect();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote_identifier(table),
+        columns.join(", "),
+        vec!["?"; columns.len()].join(", ")
+    );
+    let mut query = sqlx::query(&sql);
+    for (_, value) in values {
+        query = value.bind(query);
+    }
+    query.execute(conn).await?;
+    Ok(())
+}
+
+/// Replace the rows of the tables in a JSON lines export; returns import warnings
+async fn read_json(conn: &mut SqliteConnection, path: &str) -> Result<Vec<String>, BlockchainError> {
+    #[derive(serde::Deserialize)]
+    struct JsonRow {
+        table: String,
+        row: serde_json::Map<String, serde_json::Value>,
+    }
+
+    let mut lines = BufReader::new(File::open(path).await?).lines();
+    let mut tx = conn.begin().await?;
+    let mut tables = ImportedTables::new();
+    let mut warnings = Vec::new();
+    let mut line_number = 0;
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let JsonRow { table, row } = serde_json::from_str(&line)
+            .map_err(|e| BlockchainError::Other(format!("JSON import failed at line {}: {}", line_number, e)))?;
+        let Some(columns) = prepare_table(&mut tx, &mut tables, &table, &mut warnings).await? else {
+            continue;
+        };
+        let values = row.into_iter()
+            .map(|(column, value)| {
+                let declared = columns.get(&column).map(String::as_str).unwrap_or_default();
+                SqlValue::from_json(value, declared).map(|value| (column, value))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        insert_row(&mut tx, &table, values).await?;
+    }
+    tx.commit().await?;
+    Ok(warnings)
+}
+
+/// Replace the rows of the tables in a CSV export directory; returns import warnings
+async fn read_csv(conn: &mut SqliteConnection, directory: &str) -> Result<Vec<String>, BlockchainError> {
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(directory).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if let (Some(table), Some("csv")) = (path.file_stem().and_then(|stem| stem.to_str()), path.extension().and_then(|ext| ext.to_str())) {
+            files.push((table.to_string(), path.clone()));
+        }
+    }
+    files.sort();
+
+    let mut tx = conn.begin().await?;
+    let mut tables = ImportedTables::new();
+    let mut warnings = Vec::new();
+    for (table, path) in files {
+        let Some(columns) = prepare_table(&mut tx, &mut tables, &table, &mut warnings).await? else {
+            continue;
+        };
+        let columns = columns.clone();
+        let mut lines = BufReader::new(File::open(&path).await?).lines();
+        let Some(header) = next_csv_record(&mut lines, &path).await? else {
+            continue;
+        };
+        let names: Vec<String> = header.into_iter().map(|field| field.text).collect();
+        while let Some(fields) = next_csv_record(&mut lines, &path).await? {
+            if fields.len() != names.len() {
+                return Err(BlockchainError::Other(format!("CSV import failed: {} has a row of {} fields for {} columns", path.display(), fields.len(), names.len())));
+            }
+            let values = names.iter()
+                .zip(fields)
+                .map(|(column, field)| {
+                    let declared = columns.get(column).map(String::as_str).unwrap_or_default();
+                    SqlValue::from_csv(field, declared).map(|value| (column.clone(), value))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            insert_row(&mut tx, &table, values).await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(warnings)
+}
+
+/// The next record of a CSV file, whose quoted fields may span lines
+async fn next_csv_record(lines: &mut Lines<BufReader<File>>, path: &Path) -> Result<Option<Vec<CsvField>>, BlockchainError> {
+    let mut record = String::new();
+    while let Some(line) = lines.next_line().await? {
+        if !record.is_empty() {
+            record.push('\n');
+        }
+        record.push_str(&line);
+        if let Some(fields) = split_csv_record(&record) {
+            return Ok(Some(fields));
+        }
+    }
+    if record.is_empty() {
+        return Ok(None);
+    }
+    Err(BlockchainError::Other(format!("CSV import failed: {} ends inside a quoted field", path.display())))
+}
+
+/// A CSV field, and whether it was quoted
+#[derive(Debug, PartialEq)]
+struct CsvField {
+    text: String,
+    quoted: bool,
+}
+
+/// The fields of a CSV record, or `None` if it ends inside a quoted field
+fn split_csv_record(record: &str) -> Option<Vec<CsvField>> {
+    let mut fields = Vec::new();
+    let mut field = CsvField { text: String::new(), quoted: false };
+    let mut in_quotes = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (in_quotes, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.text.push('"');
+            }
+            (true, '"') => in_quotes = false,
+            (true, c) => field.text.push(c),
+            (false, '"') => {
+                in_quotes = true;
+                field.quoted = true;
+            }
+            (false, ',') => fields.push(std::mem::replace(&mut field, CsvField { text: String::new(), quoted: false })),
+            (false, c) => field.text.push(c),
+        }
+    }
+    if in_quotes {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DAGNode, NodeStatus, QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+    use crate::TransactionId;
+    use tempfile::TempDir;
+
+    const PAYLOAD: &str = "it's \"quoted\", with; a\nnewline -- and no comment";
+
+    async fn database(temp_dir: &TempDir, name: &str) -> DatabaseManager {
+        DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join(name).to_string_lossy().into_owned(),
+            ..Default::default()
+        }).await.unwrap()
+    }
+
+    /// A database with a DAG node, a journal entry and a row with NULLs
+    async fn populated(temp_dir: &TempDir) -> (DatabaseManager, TransactionId) {
+        let db = database(temp_dir, "source.db").await;
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 1,
+            nonce: 1,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        let node = DAGNode { transaction, children: vec![], weight: 1, confidence: 0.5, status: NodeStatus::Confirmed, quantum_score: 80 };
+        db.store_bundle(std::slice::from_ref(&node)).await.unwrap();
+        sqlx::query("INSERT INTO event_journal (kind, payload, recorded_at) VALUES ('test', ?, 1)")
+            .bind(PAYLOAD)
+            .execute(&db.pool().await)
+            .await
+            .unwrap();
+        (db, node.transaction.id)
+    }
+
+    async fn assert_imported(db: &DatabaseManager, tx_id: &TransactionId) {
+        let transaction = db.get_transaction(tx_id).await.unwrap().unwrap();
+        assert_eq!((transaction.sender, transaction.metadata), (vec![1u8; 32], None));
+        let node = db.get_dag_node(tx_id).await.unwrap().unwrap();
+        assert_eq!((node.status, node.confidence), (NodeStatus::Confirmed, 0.5));
+        let payload: String = sqlx::query_scalar("SELECT payload FROM event_journal WHERE kind = 'test'")
+            .fetch_one(&db.pool().await)
+            .await
+            .unwrap();
+        assert_eq!(payload, PAYLOAD);
+    }
+
+    #[tokio::test]
+    async fn test_sql_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let (source, tx_id) = populated(&temp_dir).await;
+        let export = source.export_sql(temp_dir.path().join("dump").to_str().unwrap()).await.unwrap();
+        assert!(export.export_path.ends_with(".sql"));
+
+        let target = database(&temp_dir, "target.db").await;
+        let import = target.import_sql(&export.export_path).await.unwrap();
+        assert!(import.pre_import_backup.is_some());
+        assert_imported(&target, &tx_id).await;
+
+        // Triggers survive, so changes are still logged
+        let triggers: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE type = 'trigger'")
+            .fetch_one(&target.pool().await)
+            .await
+            .unwrap();
+        assert!(triggers > 0);
+    }
+
+    #[tokio::test]
+    async fn test_json_and_csv_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let (source, tx_id) = populated(&temp_dir).await;
+        for (format, name) in [(ExportFormat::JSON, "export"), (ExportFormat::CSV, "csv")] {
+            let export = source.export(temp_dir.path().join(name).to_str().unwrap(), format.clone()).await.unwrap();
+            let target = database(&temp_dir, &format!("{}.db", name)).await;
+            let import_format = match format {
+                ExportFormat::JSON => ImportFormat::JSON,
+                _ => ImportFormat::CSV,
+            };
+            target.import(&export.export_path, import_format).await.unwrap();
+            assert_imported(&target, &tx_id).await;
+        }
+    }
+
+    #[test]
+    fn test_statement_splitter() {
+        let mut splitter = StatementSplitter::default();
+        let mut statements = Vec::new();
+        for line in [
+            "INSERT INTO t VALUES('a;b', 1); -- trailing; comment",
+            "CREATE TRIGGER t_log AFTER INSERT ON t BEGIN",
+            "INSERT INTO log VALUES(1); END;",
+            "INSERT INTO t VALUES('multi",
+            "line')",
+        ] {
+            statements.extend(splitter.push_line(line));
+        }
+        statements.extend(splitter.finish());
+        assert_eq!(statements.len(), 3);
+        assert!(statements[1].ends_with("END;"));
+        assert_eq!(statements[2], "INSERT INTO t VALUES('multi\nline')");
+    }
+}
@@ -0,0 +1,282 @@
+//! Migration of stored transaction IDs to content hashes
+//!
+//! Releases before content addressing stored transactions under random UUIDs.
+//! The migration re-keys every transaction, hot or archived, to the hash
+//! `Transaction::compute_id` derives for it. A child's hash covers its
+//! parents' IDs, so parents are re-keyed first and the new IDs are carried
+//! into the parent links and lists, DAG nodes and child lists. Signatures stored with
+//! migrated transactions were made over the old IDs and are kept unchanged.
+
+use super::accounts::read_amount;
+use super::DatabaseManager;
+use crate::{BlockchainError, TransactionId, core::{QuantumProof, Transaction}};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Outcome of a transaction ID migration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdMigrationReport {
+    /// Transactions examined, hot and archived
+    pub scanned: u64,
+    /// Transactions whose stored ID changed
+    pub rekeyed: u64,
+}
+
+impl DatabaseManager {
+    /// Whether any stored transaction still has a legacy UUID ID
+    pub async fn has_legacy_transaction_ids(&self) -> Result<bool, BlockchainError> {
+        let row = sqlx::query(
+            "SELECT (SELECT COUNT(*) FROM transactions WHERE length(id) != 64)
+                  + (SELECT COUNT(*) FROM transactions_archive WHERE length(id) != 64)"
+        )
+        .fetch_one(&self.pool().await)
+        .await?;
+        Ok(row.get::<i64, _>(0) > 0)
+    }
+
+    /// Re-key stored transactions to their content-addressed IDs
+    ///
+    /// Runs in a single database transaction and changes nothing once every
+    /// stored ID matches its content hash.
+    pub async fn migrate_transaction_ids(&self) -> Result<IdMigrationReport, BlockchainError> {
+        let mut transactions: HashMap<String, Transaction> = HashMap::new();
+
+        let rows = sqlx::query("SELECT id, sender, receiver, amount, fee, nonce, timestamp, metadata FROM transactions")
+            .fetch_all(&self.pool().await)
+            .await?;
+        for row in rows {
+            let id: String = row.get("id");
+            transactions.insert(id.clone(), Transaction {
+                id: TransactionId::from_string(&id)?,
+                sender: row.get("sender"),
+                receiver: row.get("receiver"),
+                amount: read_amount(&row, "amount")?,
+                fee: read_amount(&row, "fee")?,
+                nonce: row.get::<i64, _>("nonce") as u64,
+                timestamp: row.get::<i64, _>("timestamp") as u64,
+                parents: Vec::new(),
+                // Not covered by the content hash
+                signature: Vec::new(),
+                signature_scheme: Default::default(),
+                quantum_proof: QuantumProof { prime_hash: Vec::new(), resistance_score: 0, proof_timestamp: 0 },
+                metadata: row.get("metadata"),
+            });
+        }
+
+        // The parents table has no order, so links are sorted to match the
+        // order the transactions were hashed and stored in
+        let rows = sqlx::query("SELECT transaction_id, parent_id FROM transaction_parents ORDER BY rowid")
+            .fetch_all(&self.pool().await)
+            .await?;
+        for row in rows {
+            let id: String = row.get("transaction_id");
+            if let Some(transaction) = transactions.get_mut(&id) {
+                transaction.parents.push(TransactionId::from_string(&row.get::<String, _>("parent_id"))?);
+            }
+        }
+
+        let rows = sqlx::query("SELECT id, data FROM transactions_archive")
+            .fetch_all(&self.pool().await)
+            .await?;
+        let mut archived = HashSet::new();
+        for row in rows {
+            let id: String = row.get("id");
+            let transaction: Transaction = serde_json::from_str(&row.get::<String, _>("data"))?;
+            archived.insert(id.clone());
+            transactions.insert(id, transaction);
+        }
+
+        let mut report = IdMigrationReport { scanned: transactions.len() as u64, rekeyed: 0 };
+        let new_ids = content_ids(&transactions);
+        let changed: Vec<(&String, &TransactionId)> = new_ids.iter()
+            .filter(|(old, new)| **old != new.as_string())
+            .collect();
+        if changed.is_empty() {
+            return Ok(report);
+        }
+
+        let mut tx = self.pool().await.begin().await?;
+        // Keys change in parent and child tables within the same transaction
+        sqlx::query("PRAGMA defer_foreign_keys = ON").execute(&mut *tx).await?;
+
+        for (old, new) in &changed {
+            let new = new.as_string();
+            for query in [
+                "UPDATE transactions SET id = ? WHERE id = ?",
+                "UPDATE transaction_parents SET transaction_id = ? WHERE transaction_id = ?",
+                "UPDATE transaction_parents SET parent_id = ? WHERE parent_id = ?",
+                "UPDATE dag_nodes SET transaction_id = ? WHERE transaction_id = ?",
+                "UPDATE transactions_archive SET id = ? WHERE id = ?",
+            ] {
+                sqlx::query(query).bind(&new).bind(old.as_str()).execute(&mut *tx).await?;
+            }
+        }
+
+        let remap = |id: &TransactionId| new_ids.get(&id.as_string()).cloned().unwrap_or_else(|| id.clone());
+
+        for (old, transaction) in &transactions {
+            if archived.contains(old) {
+                continue;
+            }
+            let parents: Vec<String> = transaction.parents.iter().map(|parent| remap(parent).as_string()).collect();
+            sqlx::query("UPDATE transactions SET parents = ? WHERE id = ?")
+                .bind(serde_json::to_string(&parents)?)
+                .bind(remap(&transaction.id).as_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for old in &archived {
+            let mut transaction = transactions[old].clone();
+            transaction.id = remap(&transaction.id);
+            transaction.parents = transaction.parents.iter().map(remap).collect();
+            sqlx::query("UPDATE transactions_archive SET data = ? WHERE id = ?")
+                .bind(serde_json::to_string(&transaction)?)
+                .bind(transaction.id.as_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let rows = sqlx::query("SELECT transaction_id, children FROM dag_nodes")
+            .fetch_all(&mut *tx)
+            .await?;
+        for row in rows {
+            let children: Vec<String> = serde_json::from_str(&row.get::<String, _>("children"))?;
+            let migrated: Vec<String> = children.iter()
+                .map(|child| new_ids.get(child).map(|id| id.as_string()).unwrap_or_else(|| child.clone()))
+                .collect();
+            if migrated != children {
+                sqlx::query("UPDATE dag_nodes SET children = ? WHERE transaction_id = ?")
+                    .bind(serde_json::to_string(&migrated)?)
+                    .bind(row.get::<String, _>("transaction_id"))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        report.rekeyed = changed.len() as u64;
+        log::info!("🔑 Migrated {} of {} transaction ID(s) to content hashes", report.rekeyed, report.scanned);
+        Ok(report)
+    }
+}
+
+/// Content-addressed ID of every transaction, keyed by its stored ID
+///
+/// Transactions are hashed parents first, with parent links rewritten to the
+/// parents' new IDs. Parents missing from the store keep their IDs.
+fn content_ids(transactions: &HashMap<String, Transaction>) -> HashMap<String, TransactionId> {
+    let mut children: HashMap<String, Vec<&String>> = HashMap::new();
+    let mut waiting: HashMap<&String, usize> = HashMap::new();
+    for (id, transaction) in transactions {
+        let stored_parents: Vec<String> = transaction.parents.iter()
+            .map(|parent| parent.as_string())
+            .filter(|parent| transactions.contains_key(parent))
+            .collect();
+        waiting.insert(id, stored_parents.len());
+        for parent in stored_parents {
+            children.entry(parent).or_default().push(id);
+        }
+    }
+
+    let mut ready: VecDeque<&String> = waiting.iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(id, _)| *id)
+        .collect();
+    let mut new_ids: HashMap<String, TransactionId> = HashMap::new();
+    while let Some(id) = ready.pop_front() {
+        let mut transaction = transactions[id].clone();
+        transaction.parents = transaction.parents.iter()
+            .map(|parent| new_ids.get(&parent.as_string()).cloned().unwrap_or_else(|| parent.clone()))
+            .collect();
+        new_ids.insert(id.clone(), transaction.compute_id());
+
+        for child in children.get(id).into_iter().flatten() {
+            let count = waiting.get_mut(child).expect("child is stored");
+            *count -= 1;
+            if *count == 0 {
+                ready.push_back(child);
+            }
+        }
+    }
+
+    new_ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DAGNode, NodeStatus};
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn legacy_transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        Transaction {
+            id: TransactionId::Legacy(Uuid::new_v4()),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 100,
+            fee: 0,
+            nonce,
+            timestamp: 1_700_000_000 + nonce,
+            parents,
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![1u8; 32],
+                resistance_score: 80,
+                proof_timestamp: 1_700_000_000,
+            },
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrates_legacy_ids_parents_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+
+        let parent = legacy_transaction(1, vec![]);
+        let child = legacy_transaction(2, vec![parent.id.clone()]);
+        for transaction in [&parent, &child] {
+            db.store_transaction(transaction).await.unwrap();
+        }
+        db.store_dag_node(&DAGNode {
+            transaction: parent.clone(),
+            children: vec![child.id.clone()],
+            weight: 1,
+            confidence: 1.0,
+            status: NodeStatus::Finalized,
+            quantum_score: 80,
+        }).await.unwrap();
+        assert!(db.has_legacy_transaction_ids().await.unwrap());
+
+        let report = db.migrate_transaction_ids().await.unwrap();
+        assert_eq!((report.scanned, report.rekeyed), (2, 2));
+        assert!(!db.has_legacy_transaction_ids().await.unwrap());
+
+        let parent_id = parent.compute_id();
+        let mut migrated_child = child.clone();
+        migrated_child.parents = vec![parent_id.clone()];
+        let child_id = migrated_child.compute_id();
+
+        let link = sqlx::query("SELECT transaction_id, parent_id FROM transaction_parents")
+            .fetch_one(&db.pool().await).await.unwrap();
+        assert_eq!(link.get::<String, _>(0), child_id.as_string());
+        assert_eq!(link.get::<String, _>(1), parent_id.as_string());
+
+        let node = sqlx::query("SELECT transaction_id, children FROM dag_nodes")
+            .fetch_one(&db.pool().await).await.unwrap();
+        assert_eq!(node.get::<String, _>(0), parent_id.as_string());
+        assert_eq!(node.get::<String, _>(1), serde_json::to_string(&[child_id.as_string()]).unwrap());
+
+        // Nothing left to do
+        assert_eq!(db.migrate_transaction_ids().await.unwrap().rekeyed, 0);
+    }
+}
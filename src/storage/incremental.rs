@@ -0,0 +1,435 @@
+//! Incremental and differential backups
+//!
+//! Triggers on every table holding chain data append the primary key of each
+//! inserted, updated or deleted row to `change_log`. Every backup records the
+//! last change it holds in `backup_checkpoints`. An incremental backup
+//! captures the rows changed since the latest backup of any kind; a
+//! differential one, those changed since the latest full backup. Changes a
+//! full backup holds are pruned from the log.
+//!
+//! Incremental and differential backups are SQLite files with the current
+//! contents of each changed row, in tables named after their source, and the
+//! key of every changed row in `changed_rows`; a key without contents was
+//! deleted. The `.meta` file of each names the backup it builds on, so a
+//! restore walks back to a full backup and replays the chain forwards.
+//! Replaying deletes each changed row and inserts the captured contents, so
+//! it does not matter which state a row was in before.
+
+use super::{BackupInfo, BackupType, DatabaseManager};
+use crate::BlockchainError;
+use chrono::Utc;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{ConnectOptions, Connection};
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Tables whose changes are logged, with their primary key columns
+const TRACKED_TABLES: [(&str, &[&str]); 15] = [
+    ("transactions", &["id"]),
+    ("dag_nodes", &["transaction_id"]),
+    ("transaction_parents", &["transaction_id", "parent_id"]),
+    ("transactions_archive", &["id"]),
+    ("account_balances", &["address"]),
+    ("applied_transactions", &["transaction_id"]),
+    ("tokens", &["token_id"]),
+    ("token_balances", &["token_id", "address"]),
+    ("swaps", &["hashlock"]),
+    ("finality_milestones", &["height", "transaction_id"]),
+    ("transaction_receipts", &["transaction_id"]),
+    ("archives", &["content_id"]),
+    ("archive_anchors", &["content_id"]),
+    ("event_journal", &["id"]),
+    ("corrupted_rows", &["id"]),
+];
+
+/// Backup metadata naming the backup an incremental or differential one builds on
+pub const BASE_BACKUP_KEY: &str = "base_backup";
+/// Backup metadata holding the last change in the backup
+pub const LAST_CHANGE_KEY: &str = "last_change";
+
+/// Schema the backup being written or replayed is attached as
+const BACKUP_SCHEMA: &str = "backup";
+
+/// SQL computing the key `change_log` records for a row, from columns prefixed with `prefix`
+fn row_key(prefix: &str, keys: &[&str]) -> String {
+    let columns: Vec<String> = keys.iter().map(|key| format!("{}{}", prefix, key)).collect();
+    format!("json_array({})", columns.join(", "))
+}
+
+impl DatabaseManager {
+    /// Create the change log and the triggers filling it
+    pub(crate) async fn init_change_log(&self) -> Result<(), BlockchainError> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS change_log (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                row_key TEXT NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS backup_checkpoints (
+                backup_path TEXT PRIMARY KEY,
+                backup_type TEXT NOT NULL,
+                change_seq INTEGER NOT NULL,
+                created_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        for (table, keys) in TRACKED_TABLES {
+            let (new_key, old_key) = (row_key("NEW.", keys), row_key("OLD.", keys));
+            // An update may change the key, so both are logged
+            for (event, values) in [
+                ("insert", format!("('{}', {})", table, new_key)),
+                ("update", format!("('{}', {}), ('{}', {})", table, old_key, table, new_key)),
+                ("delete", format!("('{}', {})", table, old_key)),
+            ] {
+                sqlx::query(&format!(
+                    "CREATE TRIGGER IF NOT EXISTS {table}_{event}_log AFTER {} ON {table} BEGIN INSERT INTO change_log (table_name, row_key) VALUES {values}; END",
+                    event.to_uppercase(),
+                ))
+                .execute(&self.pool().await)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sequence number of the latest logged change, including pruned ones
+    pub(crate) async fn last_change(&self) -> Result<i64, BlockchainError> {
+        let seq: Option<i64> = sqlx::query_scalar("SELECT seq FROM sqlite_sequence WHERE name = 'change_log'")
+            .fetch_optional(&self.pool().await)
+            .await?;
+        Ok(seq.unwrap_or(0))
+    }
+
+    /// Record that the backup at `backup_path` holds changes up to `change_seq`
+    ///
+    /// After a full backup, the changes it holds are no longer needed.
+    pub(crate) async fn record_backup_checkpoint(&self, backup_path: &str, backup_type: &BackupType, change_seq: i64) -> Result<(), BlockchainError> {
+        sqlx::query("INSERT OR REPLACE INTO backup_checkpoints (backup_path, backup_type, change_seq, created_at) VALUES (?, ?, ?, ?)")
+            .bind(backup_path)
+            .bind(format!("{:?}", backup_type))
+            .bind(change_seq)
+            .bind(Utc::now().timestamp())
+            .execute(&self.pool().await)
+            .await?;
+
+        if matches!(backup_type, BackupType::Full) {
+            let pruned = sqlx::query("DELETE FROM change_log WHERE seq <= ?")
+                .bind(change_seq)
+                .execute(&self.pool().await)
+                .await?
+                .rows_affected();
+            log::debug!("Pruned {} change log entries held by full backup {}", pruned, backup_path);
+        }
+        Ok(())
+    }
+
+    /// Back up the rows changed since the latest backup of any kind
+    pub async fn create_incremental_backup(&self, backup_path: &str) -> Result<BackupInfo, BlockchainError> {
+        self.create_change_backup(backup_path, BackupType::Incremental).await
+    }
+
+    /// Back up the rows changed since the latest full backup
+    pub async fn create_differential_backup(&self, backup_path: &str) -> Result<BackupInfo, BlockchainError> {
+        self.create_change_backup(backup_path, BackupType::Differential).await
+    }
+
+    async fn create_change_backup(&self, backup_path: &str, backup_type: BackupType) -> Result<BackupInfo, BlockchainError> {
+        self.commit_queued().await?;
+        let (base_backup, from_change) = self.base_checkpoint(&backup_type).await?
+            .ok_or_else(|| BlockchainError::Other("No full backup to build on; create a full backup first".to_string()))?;
+        let to_change = self.last_change().await?;
+
+        let timestamp = Utc::now();
+        let backup_path = if backup_path.ends_with(".db") {
+            backup_path.to_string()
+        } else {
+            format!("{}_{}.db", backup_path, timestamp.timestamp())
+        };
+        if let Some(parent) = Path::new(&backup_path).parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        if tokio::fs::metadata(&backup_path).await.is_ok() {
+            tokio::fs::remove_file(&backup_path).await?;
+        }
+
+        let mut conn = self.pool().await.acquire().await?;
+        sqlx::query(&format!("ATTACH DATABASE ? AS {}", BACKUP_SCHEMA))
+            .bind(&backup_path)
+            .execute(&mut *conn)
+            .await?;
+        let captured = capture_changes(&mut conn, from_change, to_change).await;
+        sqlx::query(&format!("DETACH DATABASE {}", BACKUP_SCHEMA))
+            .execute(&mut *conn)
+            .await?;
+        drop(conn);
+        let (changed_rows, total_transactions, total_nodes) = captured?;
+
+        let backup_info = BackupInfo {
+            timestamp: timestamp.timestamp(),
+            backup_path: backup_path.clone(),
+            database_path: self.get_database_path().await?,
+            file_size: self.get_file_size(&backup_path).await?,
+            total_transactions,
+            total_nodes,
+            backup_type,
+            compression_enabled: false,
+            checksum: self.calculate_checksum(&backup_path).await?,
+            metadata: {
+                let mut meta = std::collections::HashMap::new();
+                meta.insert("created_by".to_string(), "quantum-dag-blockchain".to_string());
+                meta.insert("version".to_string(), env!("CARGO_PKG_VERSION").to_string());
+                meta.insert("backup_reason".to_string(), "manual".to_string());
+                meta.insert(BASE_BACKUP_KEY.to_string(), base_backup.clone());
+                meta.insert(LAST_CHANGE_KEY.to_string(), to_change.to_string());
+                meta.insert("changed_rows".to_string(), changed_rows.to_string());
+                meta
+            },
+        };
+
+        let metadata_path = format!("{}.meta", backup_path);
+        tokio::fs::write(&metadata_path, serde_json::to_string_pretty(&backup_info)?).await?;
+        self.record_backup_checkpoint(&backup_path, &backup_info.backup_type, to_change).await?;
+        if self.has_backup_destination() {
+            self.upload_backup(&backup_info).await?;
+        }
+
+        log::info!("📦 {:?} backup created: {} ({} changed row(s) since {})", backup_info.backup_type, backup_path, changed_rows, base_backup);
+        Ok(backup_info)
+    }
+
+    /// Path and last change of the backup a new one of `backup_type` builds on
+    async fn base_checkpoint(&self, backup_type: &BackupType) -> Result<Option<(String, i64)>, BlockchainError> {
+        let filter = match backup_type {
+            BackupType::Differential => "WHERE backup_type = 'Full'",
+            BackupType::Full | BackupType::Incremental => "",
+        };
+        let checkpoint = sqlx::query_as(&format!(
+            "SELECT backup_path, change_seq FROM backup_checkpoints {} ORDER BY rowid DESC LIMIT 1",
+            filter
+        ))
+        .fetch_optional(&self.pool().await)
+        .await?;
+        Ok(checkpoint)
+    }
+
+    /// Rebuild the database an incremental or differential backup was taken
+    /// from, in a staging file next to the live one
+    ///
+    /// Returns the path of the staging file.
+    pub(crate) async fn replay_backup_chain(&self, backup_info: &BackupInfo) -> Result<String, BlockchainError> {
+        // Walk back to the full backup the chain starts from
+        let mut chain = vec![backup_info.clone()];
+        let mut visited = HashSet::from([backup_info.backup_path.clone()]);
+        while !matches!(chain[chain.len() - 1].backup_type, BackupType::Full) {
+            let latest = &chain[chain.len() - 1];
+            let base_path = latest.metadata.get(BASE_BACKUP_KEY).cloned().ok_or_else(|| {
+                BlockchainError::Other(format!("Backup {} does not name its base backup", latest.backup_path))
+            })?;
+            if !visited.insert(base_path.clone()) {
+                return Err(BlockchainError::Other(format!("Backup chain loops back to {}", base_path)));
+            }
+            chain.push(self.load_chained_backup(&base_path).await?);
+        }
+        chain.reverse();
+
+        let staged = format!("{}.restoring", self.get_database_path().await?);
+        tokio::fs::copy(&chain[0].backup_path, &staged).await?;
+        // The rows replayed were consistent when captured; checking foreign
+        // keys row by row would fail on the order they are replayed in
+        let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", staged))?.foreign_keys(false);
+        let mut conn = super::encryption::with_key(options, self.encryption_key.as_deref())?
+            .connect()
+            .await?;
+        for backup in &chain[1..] {
+            sqlx::query(&format!("ATTACH DATABASE ? AS {}", BACKUP_SCHEMA))
+                .bind(&backup.backup_path)
+                .execute(&mut conn)
+                .await?;
+            let replayed = replay_changes(&mut conn).await;
+            sqlx::query(&format!("DETACH DATABASE {}", BACKUP_SCHEMA))
+                .execute(&mut conn)
+                .await?;
+            replayed?;
+            log::info!("🔁 Replayed {:?} backup {}", backup.backup_type, backup.backup_path);
+        }
+        conn.close().await?;
+        Ok(staged)
+    }
+
+    /// Metadata of a backup in a chain, after checking the file is intact
+    async fn load_chained_backup(&self, backup_path: &str) -> Result<BackupInfo, BlockchainError> {
+        let metadata_path = format!("{}.meta", backup_path);
+        let metadata = tokio::fs::read_to_string(&metadata_path).await
+            .map_err(|e| BlockchainError::Other(format!("Backup metadata {} is unreadable: {}", metadata_path, e)))?;
+        let backup_info: BackupInfo = serde_json::from_str(&metadata)?;
+        if self.calculate_checksum(backup_path).await? != backup_info.checksum {
+            return Err(BlockchainError::Other(format!("Backup integrity check failed: {}", backup_path)));
+        }
+        Ok(backup_info)
+    }
+}
+
+/// Copy rows changed after `from_change`, up to `to_change`, into the attached backup
+///
+/// Returns how many rows changed, and how many transactions and DAG nodes were copied.
+async fn capture_changes(conn: &mut SqliteConnection, from_change: i64, to_change: i64) -> Result<(u64, u64, u64), BlockchainError> {
+    let mut tx = conn.begin().await?;
+    sqlx::query(&format!(
+        "CREATE TABLE {}.changed_rows (table_name TEXT NOT NULL, row_key TEXT NOT NULL, PRIMARY KEY (table_name, row_key))",
+        BACKUP_SCHEMA
+    ))
+    .execute(&mut *tx)
+    .await?;
+    let changed_rows = sqlx::query(&format!(
+        "INSERT INTO {}.changed_rows SELECT DISTINCT table_name, row_key FROM main.change_log WHERE seq > ? AND seq <= ?",
+        BACKUP_SCHEMA
+    ))
+    .bind(from_change)
+    .bind(to_change)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    for (table, keys) in TRACKED_TABLES {
+        for statement in [
+            format!("CREATE TABLE {schema}.{table} AS SELECT * FROM main.{table} WHERE 0", schema = BACKUP_SCHEMA),
+            format!(
+                "INSERT INTO {schema}.{table} SELECT * FROM main.{table} WHERE {key} IN (SELECT row_key FROM {schema}.changed_rows WHERE table_name = '{table}')",
+                schema = BACKUP_SCHEMA,
+                key = row_key("", keys),
+            ),
+        ] {
+            sqlx::query(&statement).execute(&mut *tx).await?;
+        }
+    }
+
+    let count = |table: &str| format!("SELECT COUNT(*) FROM {}.{}", BACKUP_SCHEMA, table);
+    let transactions: i64 = sqlx::query_scalar(&count("transactions")).fetch_one(&mut *tx).await?;
+    let nodes: i64 = sqlx::query_scalar(&count("dag_nodes")).fetch_one(&mut *tx).await?;
+    tx.commit().await?;
+    Ok((changed_rows, transactions as u64, nodes as u64))
+}
+
+/// Replay the rows of the attached backup over the main database, all or none
+async fn replay_changes(conn: &mut SqliteConnection) -> Result<(), BlockchainError> {
+    let mut tx = conn.begin().await?;
+    for (table, keys) in TRACKED_TABLES {
+        // Columns are named, as tables may have gained some since the backup
+        let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?, ?)")
+            .bind(table)
+            .bind(BACKUP_SCHEMA)
+            .fetch_all(&mut *tx)
+            .await?;
+        if columns.is_empty() {
+            continue;
+        }
+        let columns = columns.join(", ");
+        for statement in [
+            format!(
+                "DELETE FROM main.{table} WHERE {key} IN (SELECT row_key FROM {schema}.changed_rows WHERE table_name = '{table}')",
+                schema = BACKUP_SCHEMA,
+                key = row_key("", keys),
+            ),
+            format!("INSERT INTO main.{table} ({columns}) SELECT {columns} FROM {schema}.{table}", schema = BACKUP_SCHEMA),
+        ] {
+            sqlx::query(&statement).execute(&mut *tx).await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DAGNode, NodeStatus, QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+    use crate::TransactionId;
+    use tempfile::TempDir;
+
+    fn node(nonce: u64) -> DAGNode {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        DAGNode { transaction, children: vec![], weight: 1, confidence: 0.0, status: NodeStatus::Pending, quantum_score: 80 }
+    }
+
+    async fn database(path: &Path) -> DatabaseManager {
+        DatabaseManager::new(DatabaseConfig {
+            path: path.to_string_lossy().into_owned(),
+            ..Default::default()
+        }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_change_backups_need_a_full_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = database(&temp_dir.path().join("chain.db")).await;
+        let backup = temp_dir.path().join("incremental.db");
+        assert!(db.create_incremental_backup(backup.to_str().unwrap()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_restore_replays_incremental_chain() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("chain.db");
+        let backups = temp_dir.path().join("backups");
+        let backup = |name: &str| backups.join(name).to_string_lossy().into_owned();
+        let (first, second, third) = (node(1), node(2), node(3));
+
+        let db = database(&path).await;
+        db.store_bundle(&[first.clone()]).await.unwrap();
+        db.create_backup(&backup("full.db")).await.unwrap();
+
+        db.store_bundle(&[second.clone()]).await.unwrap();
+        let incremental = db.create_incremental_backup(&backup("incremental_1.db")).await.unwrap();
+        assert_eq!((incremental.total_transactions, incremental.total_nodes), (1, 1));
+
+        db.store_bundle(&[third.clone()]).await.unwrap();
+        db.update_node_status(&second.transaction.id, NodeStatus::Finalized, 1.0).await.unwrap();
+        db.delete_transactions(std::slice::from_ref(&first.transaction.id)).await.unwrap();
+        let incremental = db.create_incremental_backup(&backup("incremental_2.db")).await.unwrap();
+        assert_eq!(incremental.metadata[BASE_BACKUP_KEY], backup("incremental_1.db"));
+        assert_eq!(incremental.total_transactions, 1);
+
+        // A differential backup holds every change since the full one
+        let differential = db.create_differential_backup(&backup("differential.db")).await.unwrap();
+        assert_eq!(differential.metadata[BASE_BACKUP_KEY], backup("full.db"));
+        assert_eq!(differential.total_transactions, 2);
+
+        // Changes after the last backup are lost on restore
+        db.store_bundle(&[node(4)]).await.unwrap();
+        db.restore_from_backup(&backup("incremental_2.db")).await.unwrap();
+
+        // The same manager serves the restored database
+        let restored = &db;
+        assert!(restored.get_transaction(&first.transaction.id).await.unwrap().is_none());
+        assert_eq!(restored.get_dag_node(&second.transaction.id).await.unwrap().unwrap().status, NodeStatus::Finalized);
+        assert!(restored.get_transaction(&third.transaction.id).await.unwrap().is_some());
+        assert_eq!(restored.get_stats().await.unwrap().total_transactions, 2);
+    }
+}
@@ -0,0 +1,447 @@
+//! Row checksums and corruption quarantine
+//!
+//! Transactions and DAG nodes are stored with a SHA3 checksum of their
+//! decoded contents, computed on write. Reads verify it as configured by
+//! `ChecksumConfig`: never, for a random sample of rows, or for every row.
+//! Rows written before checksums existed have none and are not verified.
+//!
+//! A row failing verification is moved to the `corrupted_rows` table along
+//! with both checksums and its decoded contents, and a `StorageCorruption`
+//! event is published. The read then falls back: a DAG node is re-derived
+//! from its transaction, its stored parent links and whether it was applied
+//! to balances; a transaction is fetched again through the registered
+//! `TransactionSource`. Without a source the transaction is deleted along
+//! with its DAG node, and the read behaves as if it were missing.
+
+use super::{DatabaseManager, StoredDagNode};
+use crate::core::amount::digest_bytes;
+use crate::core::{DAGNode, NodeStatus, Transaction};
+use crate::events::NodeEvent;
+use crate::{BlockchainError, TransactionId};
+use chrono::Utc;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use sqlx::Row;
+use std::sync::Arc;
+
+pub(crate) const TRANSACTIONS_TABLE: &str = "transactions";
+pub(crate) const DAG_NODES_TABLE: &str = "dag_nodes";
+
+/// Which reads verify row checksums
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksumMode {
+    Off,
+    /// A random `sample_percent` of reads
+    Sampled,
+    Full,
+}
+
+/// Checksum verification settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChecksumConfig {
+    pub mode: ChecksumMode,
+    /// Share of reads verified in sampled mode, 0 to 100
+    pub sample_percent: u8,
+}
+
+impl Default for ChecksumConfig {
+    fn default() -> Self {
+        Self {
+            mode: ChecksumMode::Sampled,
+            sample_percent: 10,
+        }
+    }
+}
+
+impl ChecksumConfig {
+    /// Whether the next read should be verified
+    pub fn should_verify(&self) -> bool {
+        match self.mode {
+            ChecksumMode::Off => false,
+            ChecksumMode::Full => true,
+            ChecksumMode::Sampled => rand::thread_rng().gen_range(0..100) < self.sample_percent,
+        }
+    }
+}
+
+/// How a read recovered from a corrupted row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorruptionResolution {
+    /// Rebuilt from other stored data
+    Rederived,
+    /// Fetched again from the transaction source
+    Refetched,
+    /// Nothing to recover from; the row is gone until re-synced
+    Unrecovered,
+}
+
+impl CorruptionResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorruptionResolution::Rederived => "rederived",
+            CorruptionResolution::Refetched => "refetched",
+            CorruptionResolution::Unrecovered => "unrecovered",
+        }
+    }
+}
+
+/// A row moved to quarantine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorruptedRow {
+    pub id: i64,
+    pub table: String,
+    pub row_id: String,
+    pub stored_checksum: String,
+    pub computed_checksum: String,
+    /// The row as it was read, as JSON
+    pub contents: String,
+    pub detected_at: i64,
+    pub resolution: CorruptionResolution,
+}
+
+/// Where transactions lost to corruption can be fetched again, e.g. from peers
+#[async_trait::async_trait]
+pub trait TransactionSource: Send + Sync {
+    async fn fetch_transaction(&self, tx_id: &TransactionId) -> Option<Transaction>;
+}
+
+/// Checksum over the stored fields of a transaction
+///
+/// Parents are hashed in sorted order, since their order is not kept.
+pub fn transaction_checksum(transaction: &Transaction) -> String {
+    let mut parents: Vec<&[u8]> = transaction.parents.iter().map(|parent| parent.as_bytes()).collect();
+    parents.sort_unstable();
+
+    let mut hasher = Sha3_256::new();
+    for field in [transaction.id.as_bytes(), &transaction.sender, &transaction.receiver, &transaction.signature, &transaction.quantum_proof.prime_hash] {
+        hash_bytes(&mut hasher, field);
+    }
+    for amount in [transaction.amount, transaction.fee] {
+        hasher.update(digest_bytes(amount));
+    }
+    for value in [transaction.nonce, transaction.timestamp, transaction.quantum_proof.proof_timestamp] {
+        hasher.update(value.to_le_bytes());
+    }
+    hasher.update(transaction.quantum_proof.resistance_score.to_le_bytes());
+    hash_bytes(&mut hasher, format!("{:?}", transaction.signature_scheme).as_bytes());
+    match &transaction.metadata {
+        Some(metadata) => {
+            hasher.update([1]);
+            hash_bytes(&mut hasher, metadata);
+        }
+        None => hasher.update([0]),
+    }
+    for parent in parents {
+        hash_bytes(&mut hasher, parent);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Checksum over the stored fields of a DAG node
+pub fn dag_node_checksum(node: &DAGNode) -> String {
+    node_checksum(&node.transaction.id, &node.children, node.weight, node.confidence, &node.status, node.quantum_score)
+}
+
+/// `dag_node_checksum` of a node read without its transaction
+pub(crate) fn stored_dag_node_checksum(node: &StoredDagNode) -> String {
+    node_checksum(&node.tx_id, &node.children, node.weight, node.confidence, &node.status, node.quantum_score)
+}
+
+fn node_checksum(
+    tx_id: &TransactionId,
+    children: &[TransactionId],
+    weight: u64,
+    confidence: f64,
+    status: &NodeStatus,
+    quantum_score: u32,
+) -> String {
+    let mut children: Vec<&[u8]> = children.iter().map(|child| child.as_bytes()).collect();
+    children.sort_unstable();
+
+    let mut hasher = Sha3_256::new();
+    hash_bytes(&mut hasher, tx_id.as_bytes());
+    hasher.update(weight.to_le_bytes());
+    hasher.update(confidence.to_bits().to_le_bytes());
+    hash_bytes(&mut hasher, format!("{:?}", status).as_bytes());
+    hasher.update(quantum_score.to_le_bytes());
+    for child in children {
+        hash_bytes(&mut hasher, child);
+    }
+    hex::encode(hasher.finalize())
+}
+
+fn hash_bytes(hasher: &mut Sha3_256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+impl DatabaseManager {
+    /// Change which reads verify checksums
+    pub fn set_checksum_config(&self, config: ChecksumConfig) {
+        *self.checksums.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    pub fn checksum_config(&self) -> ChecksumConfig {
+        self.checksums.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Fetch transactions lost to corruption from `source`
+    pub fn set_transaction_source(&self, source: Arc<dyn TransactionSource>) {
+        *self.transaction_source.write().unwrap_or_else(|e| e.into_inner()) = Some(source);
+    }
+
+    /// Quarantined rows, newest first
+    pub async fn get_corrupted_rows(&self, limit: usize) -> Result<Vec<CorruptedRow>, BlockchainError> {
+        let rows = sqlx::query(
+            "SELECT id, table_name, row_id, stored_checksum, computed_checksum, contents, detected_at, resolution
+             FROM corrupted_rows ORDER BY id DESC LIMIT ?"
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool().await)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let resolution: String = row.get("resolution");
+                Ok(CorruptedRow {
+                    id: row.get("id"),
+                    table: row.get("table_name"),
+                    row_id: row.get("row_id"),
+                    stored_checksum: row.get("stored_checksum"),
+                    computed_checksum: row.get("computed_checksum"),
+                    contents: row.get("contents"),
+                    detected_at: row.get("detected_at"),
+                    resolution: serde_json::from_value(serde_json::Value::String(resolution))?,
+                })
+            })
+            .collect()
+    }
+
+    /// `transaction` as read with checksum `stored`, or its replacement if the row is corrupted
+    pub(crate) async fn verified_transaction(
+        &self,
+        transaction: Transaction,
+        stored: Option<String>,
+    ) -> Result<Option<Transaction>, BlockchainError> {
+        let Some(stored) = stored else {
+            return Ok(Some(transaction));
+        };
+        if !self.checksum_config().should_verify() {
+            return Ok(Some(transaction));
+        }
+        let computed = transaction_checksum(&transaction);
+        if computed == stored {
+            return Ok(Some(transaction));
+        }
+
+        let tx_id = transaction.id.clone();
+        let source = self.transaction_source.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let refetched = match source {
+            Some(source) => source.fetch_transaction(&tx_id).await
+                .filter(|fetched| fetched.id == tx_id && fetched.has_valid_id()),
+            None => None,
+        };
+        let resolution = if refetched.is_some() { CorruptionResolution::Refetched } else { CorruptionResolution::Unrecovered };
+        self.quarantine(TRANSACTIONS_TABLE, &tx_id, &stored, &computed, serde_json::to_string(&transaction)?, resolution).await?;
+
+        match &refetched {
+            Some(fetched) => self.store_transaction(fetched).await?,
+            None => {
+                self.backend.delete(std::slice::from_ref(&tx_id)).await?;
+            }
+        }
+        Ok(refetched)
+    }
+
+    /// `node` as read with checksum `stored`, re-derived if the row is corrupted
+    pub(crate) async fn verified_dag_node(&self, node: DAGNode, stored: Option<String>) -> Result<DAGNode, BlockchainError> {
+        let Some(stored) = stored else {
+            return Ok(node);
+        };
+        if !self.checksum_config().should_verify() {
+            return Ok(node);
+        }
+        let computed = dag_node_checksum(&node);
+        if computed == stored {
+            return Ok(node);
+        }
+
+        let tx_id = node.transaction.id.clone();
+        let contents = serde_json::to_string(&node)?;
+        let rederived = self.rederive_dag_node(node.transaction).await?;
+        self.quarantine(DAG_NODES_TABLE, &tx_id, &stored, &computed, contents, CorruptionResolution::Rederived).await?;
+        self.store_dag_node(&rederived).await?;
+        Ok(rederived)
+    }
+
+    /// DAG node rebuilt from its transaction, the parent links of its
+    /// children and whether it was applied to balances
+    ///
+    /// Weight and confidence restart from their initial values; the DAG
+    /// recomputes them once the node is loaded.
+    pub(super) async fn rederive_dag_node(&self, transaction: Transaction) -> Result<DAGNode, BlockchainError> {
+        let id = transaction.id.as_string();
+        let children = self.backend.child_ids(&transaction.id).await?;
+        let applied = sqlx::query("SELECT 1 FROM applied_transactions WHERE transaction_id = ?")
+            .bind(&id)
+            .fetch_optional(&self.pool().await)
+            .await?
+            .is_some();
+
+        Ok(DAGNode {
+            children,
+            weight: 1,
+            confidence: if applied { 1.0 } else { 0.0 },
+            status: if applied { NodeStatus::Finalized } else { NodeStatus::Pending },
+            quantum_score: transaction.quantum_proof.resistance_score,
+            transaction,
+        })
+    }
+
+    async fn quarantine(
+        &self,
+        table: &str,
+        row_id: &TransactionId,
+        stored: &str,
+        computed: &str,
+        contents: String,
+        resolution: CorruptionResolution,
+    ) -> Result<(), BlockchainError> {
+        sqlx::query(
+            "INSERT INTO corrupted_rows (table_name, row_id, stored_checksum, computed_checksum, contents, detected_at, resolution)
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(table)
+        .bind(row_id.as_string())
+        .bind(stored)
+        .bind(computed)
+        .bind(contents)
+        .bind(Utc::now().timestamp())
+        .bind(resolution.as_str())
+        .execute(&self.pool().await)
+        .await?;
+
+        log::error!("🚨 Corrupted {} row {} quarantined ({})", table, row_id, resolution.as_str());
+        if let Some(events) = &self.events {
+            events.publish(NodeEvent::StorageCorruption {
+                table: table.to_string(),
+                row_id: row_id.clone(),
+                resolution,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{account_address, QuantumProof};
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents,
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    async fn database(temp_dir: &TempDir) -> DatabaseManager {
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        db.set_checksum_config(ChecksumConfig { mode: ChecksumMode::Full, sample_percent: 0 });
+        db
+    }
+
+    struct Peer(Transaction);
+
+    #[async_trait::async_trait]
+    impl TransactionSource for Peer {
+        async fn fetch_transaction(&self, tx_id: &TransactionId) -> Option<Transaction> {
+            (&self.0.id == tx_id).then(|| self.0.clone())
+        }
+    }
+
+    #[test]
+    fn test_checksum_ignores_parent_order() {
+        let (a, b) = (transaction(1, vec![]).id, transaction(2, vec![]).id);
+        let first = transaction(3, vec![a.clone(), b.clone()]);
+        let mut second = first.clone();
+        second.parents = vec![b, a];
+        assert_eq!(transaction_checksum(&first), transaction_checksum(&second));
+
+        second.amount += 1;
+        assert_ne!(transaction_checksum(&first), transaction_checksum(&second));
+        assert!(!ChecksumConfig { mode: ChecksumMode::Sampled, sample_percent: 0 }.should_verify());
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_transaction_is_quarantined_and_refetched() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = database(&temp_dir).await;
+        let tx = transaction(1, vec![]);
+        db.store_transaction(&tx).await.unwrap();
+
+        sqlx::query("UPDATE transactions SET amount = 999 WHERE id = ?").bind(tx.id.as_string()).execute(&db.pool().await).await.unwrap();
+        assert!(db.get_transaction(&tx.id).await.unwrap().is_none());
+        assert!(db.get_transaction(&tx.id).await.unwrap().is_none());
+
+        db.store_transaction(&tx).await.unwrap();
+        sqlx::query("UPDATE transactions SET amount = 999 WHERE id = ?").bind(tx.id.as_string()).execute(&db.pool().await).await.unwrap();
+        db.set_transaction_source(Arc::new(Peer(tx.clone())));
+        assert_eq!(db.get_transaction(&tx.id).await.unwrap().unwrap().amount, 10);
+
+        let quarantined = db.get_corrupted_rows(10).await.unwrap();
+        let resolutions: Vec<_> = quarantined.iter().map(|row| row.resolution).collect();
+        assert_eq!(resolutions, vec![CorruptionResolution::Refetched, CorruptionResolution::Unrecovered]);
+        assert_eq!(quarantined[0].table, TRANSACTIONS_TABLE);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_dag_node_is_rederived() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = database(&temp_dir).await;
+        let parent = transaction(1, vec![]);
+        let child = transaction(2, vec![parent.id.clone()]);
+        db.store_transaction(&parent).await.unwrap();
+        db.store_transaction(&child).await.unwrap();
+        db.credit_account(&account_address(&parent.sender), 100).await.unwrap();
+        db.apply_finalized_transaction(&parent).await.unwrap();
+        db.store_dag_node(&DAGNode {
+            transaction: parent.clone(),
+            children: vec![child.id.clone()],
+            weight: 90,
+            confidence: 1.0,
+            status: NodeStatus::Finalized,
+            quantum_score: 80,
+        }).await.unwrap();
+
+        sqlx::query("UPDATE dag_nodes SET status = 'Pending' WHERE transaction_id = ?").bind(parent.id.as_string()).execute(&db.pool().await).await.unwrap();
+        let node = db.get_dag_node(&parent.id).await.unwrap().unwrap();
+        assert_eq!(node.children, vec![child.id.clone()]);
+        assert_eq!(node.status, NodeStatus::Finalized);
+
+        // The re-derived node is stored with a fresh checksum
+        assert_eq!(db.get_dag_node(&parent.id).await.unwrap().unwrap().status, NodeStatus::Finalized);
+        assert_eq!(db.get_corrupted_rows(10).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+//! Event journal
+//!
+//! The event bus forgets an event once it is delivered. Events an operator
+//! will want during a postmortem, such as SLO breaches, safe mode changes and
+//! storage corruption, are also appended to the `event_journal` table by
+//! `JournalRecorder` so they outlive restarts.
+
+use super::DatabaseManager;
+use crate::events::{EventHandler, NodeEvent};
+use crate::BlockchainError;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::sync::Arc;
+
+/// Event kinds `JournalRecorder` appends to the journal
+pub const JOURNALED_EVENTS: &[&str] = &[
+    "SloBreached",
+    "SloRecovered",
+    "ForkDetected",
+    "Reorganized",
+    "SafeModeChanged",
+    "StorageCorruption",
+];
+
+/// An event as recorded in the journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub kind: String,
+    pub event: NodeEvent,
+    pub recorded_at: u64,
+}
+
+impl DatabaseManager {
+    /// Append an event to the journal
+    pub async fn append_journal(&self, event: &NodeEvent, recorded_at: u64) -> Result<i64, BlockchainError> {
+        let payload = serde_json::to_string(event)?;
+        let result = sqlx::query("INSERT INTO event_journal (kind, payload, recorded_at) VALUES (?, ?, ?)")
+            .bind(event.kind())
+            .bind(payload)
+            .bind(recorded_at as i64)
+            .execute(&self.pool().await)
+            .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Latest journal entries of the given kinds, or of every kind if empty, newest first
+    pub async fn get_journal(&self, kinds: &[&str], limit: usize) -> Result<Vec<JournalEntry>, BlockchainError> {
+        let filter = if kinds.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE kind IN ({})", vec!["?"; kinds.len()].join(", "))
+        };
+        let sql = format!("SELECT id, kind, payload, recorded_at FROM event_journal {} ORDER BY id DESC LIMIT ?", filter);
+        let mut query = sqlx::query(&sql);
+        for kind in kinds {
+            query = query.bind(*kind);
+        }
+        let rows = query.bind(limit as i64).fetch_all(&self.pool().await).await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(JournalEntry {
+                    id: row.get("id"),
+                    kind: row.get("kind"),
+                    event: serde_json::from_str(&row.get::<String, _>("payload"))?,
+                    recorded_at: row.get::<i64, _>("recorded_at") as u64,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Appends the events in `JOURNALED_EVENTS` to the journal
+pub struct JournalRecorder {
+    database: Arc<DatabaseManager>,
+}
+
+impl JournalRecorder {
+    pub fn new(database: Arc<DatabaseManager>) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventHandler for JournalRecorder {
+    fn name(&self) -> String {
+        "journal".to_string()
+    }
+
+    async fn handle(&self, event: &NodeEvent) {
+        if !JOURNALED_EVENTS.contains(&event.kind()) {
+            return;
+        }
+        let now = chrono::Utc::now().timestamp() as u64;
+        if let Err(e) = self.database.append_journal(event, now).await {
+            log::error!("❌ Failed to journal {} event: {}", event.kind(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+
+    #[tokio::test]
+    async fn test_journal_round_trip() {
+        let database = Arc::new(DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap());
+
+        let recorder = JournalRecorder::new(database.clone());
+        recorder.handle(&NodeEvent::TipSetChanged { added: vec![], removed: vec![], tip_count: 1 }).await;
+        recorder.handle(&NodeEvent::ForkDetected { round_number: 7, validators: vec!["a".to_string()] }).await;
+        recorder.handle(&NodeEvent::SloRecovered { slo: "fork_rate".to_string(), measured: 0.0, breached_for_secs: 60 }).await;
+
+        let entries = database.get_journal(&[], 10).await.unwrap();
+        assert_eq!(entries.iter().map(|entry| entry.kind.as_str()).collect::<Vec<_>>(), vec!["SloRecovered", "ForkDetected"]);
+        let forks = database.get_journal(&["ForkDetected"], 10).await.unwrap();
+        assert!(matches!(forks[0].event, NodeEvent::ForkDetected { round_number: 7, .. }));
+    }
+}
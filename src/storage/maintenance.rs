@@ -0,0 +1,283 @@
+//! Periodic database maintenance
+//!
+//! Deleted and rewritten rows leave free pages behind, the write-ahead log
+//! grows between checkpoints, and query plans go stale as tables grow, so a
+//! long-running node's SQLite file keeps growing. `MaintenanceScheduler`
+//! runs `ANALYZE`, `VACUUM` and a truncating WAL checkpoint, each once its
+//! interval has passed since it last ran. It only does so while the node is
+//! quiet, meaning fewer than `quiet_below` transactions were stored since
+//! the previous check, because `VACUUM` holds the write lock while it
+//! rewrites the whole file.
+//!
+//! The last run of each job is kept in `maintenance_runs`, so intervals
+//! carry over restarts. Jobs can also be run on demand with
+//! `DatabaseManager::run_maintenance`.
+
+use super::DatabaseManager;
+use crate::BlockchainError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+
+/// A maintenance job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceJob {
+    /// Refresh the statistics the query planner uses
+    Analyze,
+    /// Rebuild the file without its free pages
+    Vacuum,
+    /// Copy the write-ahead log into the database and truncate it
+    WalCheckpoint,
+}
+
+impl MaintenanceJob {
+    /// Every job, in the order a check runs them; checkpointing last
+    /// empties the log `VACUUM` wrote to
+    pub const ALL: [MaintenanceJob; 3] = [MaintenanceJob::Analyze, MaintenanceJob::Vacuum, MaintenanceJob::WalCheckpoint];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MaintenanceJob::Analyze => "analyze",
+            MaintenanceJob::Vacuum => "vacuum",
+            MaintenanceJob::WalCheckpoint => "wal_checkpoint",
+        }
+    }
+
+    fn statement(&self) -> &'static str {
+        match self {
+            MaintenanceJob::Analyze => "ANALYZE",
+            MaintenanceJob::Vacuum => "VACUUM",
+            MaintenanceJob::WalCheckpoint => "PRAGMA wal_checkpoint(TRUNCATE)",
+        }
+    }
+}
+
+impl fmt::Display for MaintenanceJob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MaintenanceJob {
+    type Err = BlockchainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MaintenanceJob::ALL.into_iter()
+            .find(|job| job.as_str() == s)
+            .ok_or_else(|| BlockchainError::Other(format!("Unknown maintenance job: {}", s)))
+    }
+}
+
+/// Periodic maintenance settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    pub enabled: bool,
+    /// Seconds between checks for due jobs
+    pub check_interval_secs: u64,
+    /// Transactions stored since the previous check below which the node is quiet
+    pub quiet_below: u64,
+    /// Seconds between runs of each job; zero never runs it
+    pub analyze_interval_secs: u64,
+    pub vacuum_interval_secs: u64,
+    pub wal_checkpoint_interval_secs: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_secs: 300,
+            quiet_below: 50,
+            analyze_interval_secs: 24 * 3600,
+            vacuum_interval_secs: 7 * 24 * 3600,
+            wal_checkpoint_interval_secs: 3600,
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    /// Seconds between runs of `job`
+    pub fn interval_secs(&self, job: MaintenanceJob) -> u64 {
+        match job {
+            MaintenanceJob::Analyze => self.analyze_interval_secs,
+            MaintenanceJob::Vacuum => self.vacuum_interval_secs,
+            MaintenanceJob::WalCheckpoint => self.wal_checkpoint_interval_secs,
+        }
+    }
+}
+
+/// The last run of a job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceRun {
+    pub job: MaintenanceJob,
+    pub started_at: i64,
+    pub duration_ms: u64,
+    /// Whether it was run on demand rather than by the scheduler
+    pub manual: bool,
+    /// Bytes of the database file and its write-ahead log before and after
+    pub size_before: u64,
+    pub size_after: u64,
+    pub error: Option<String>,
+}
+
+impl DatabaseManager {
+    /// Run `job` now and record it as the job's last run
+    pub async fn run_maintenance(&self, job: MaintenanceJob, manual: bool) -> Result<MaintenanceRun, BlockchainError> {
+        self.commit_queued().await?;
+        let size_before = self.file_size().await;
+        let started_at = Utc::now().timestamp();
+        let start = Instant::now();
+        let result = sqlx::query(job.statement()).execute(&self.pool().await).await;
+
+        let run = MaintenanceRun {
+            job,
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+            manual,
+            size_before,
+            size_after: self.file_size().await,
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        sqlx::query(
+            "INSERT OR REPLACE INTO maintenance_runs (job, started_at, duration_ms, manual, size_before, size_after, error) VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(job.as_str())
+        .bind(run.started_at)
+        .bind(run.duration_ms as i64)
+        .bind(run.manual)
+        .bind(run.size_before as i64)
+        .bind(run.size_after as i64)
+        .bind(&run.error)
+        .execute(&self.pool().await)
+        .await?;
+
+        if let Err(e) = result {
+            return Err(BlockchainError::Other(format!("Maintenance job {} failed: {}", job, e)));
+        }
+        log::info!(
+            "🧹 Ran {} in {} ms, {} -> {} bytes",
+            job,
+            run.duration_ms,
+            run.size_before,
+            run.size_after
+        );
+        Ok(run)
+    }
+
+    /// The last run of each job that has run
+    pub async fn maintenance_runs(&self) -> Result<Vec<MaintenanceRun>, BlockchainError> {
+        let rows = sqlx::query("SELECT job, started_at, duration_ms, manual, size_before, size_after, error FROM maintenance_runs ORDER BY job")
+            .fetch_all(&self.pool().await)
+            .await?;
+        rows.iter()
+            .map(|row| Ok(MaintenanceRun {
+                job: row.get::<String, _>("job").parse()?,
+                started_at: row.get("started_at"),
+                duration_ms: row.get::<i64, _>("duration_ms") as u64,
+                manual: row.get("manual"),
+                size_before: row.get::<i64, _>("size_before") as u64,
+                size_after: row.get::<i64, _>("size_after") as u64,
+                error: row.get("error"),
+            }))
+            .collect()
+    }
+
+    /// Bytes of the database file and its write-ahead log; zero in memory
+    async fn file_size(&self) -> u64 {
+        let mut size = 0;
+        for path in [self.path.clone(), format!("{}-wal", self.path)] {
+            if let Ok(metadata) = tokio::fs::metadata(&path).await {
+                size += metadata.len();
+            }
+        }
+        size
+    }
+}
+
+/// Runs due maintenance jobs while the node is quiet
+#[derive(Debug, Default)]
+pub struct MaintenanceScheduler {
+    /// Transactions stored as of the previous check
+    last_transaction_count: Option<u64>,
+}
+
+impl MaintenanceScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run every job whose interval has passed, if the node has been quiet
+    /// since the previous check; the first check only records the activity
+    pub async fn check(&mut self, database: &DatabaseManager, config: &MaintenanceConfig) -> Result<Vec<MaintenanceRun>, BlockchainError> {
+        let count = database.get_transaction_count().await?;
+        let previous = self.last_transaction_count.replace(count);
+        let quiet = previous.map_or(false, |previous| count.saturating_sub(previous) < config.quiet_below);
+        if !quiet {
+            return Ok(Vec::new());
+        }
+
+        let last_runs = database.maintenance_runs().await?;
+        let now = Utc::now().timestamp();
+        let mut runs = Vec::new();
+        for job in MaintenanceJob::ALL {
+            let interval = config.interval_secs(job);
+            if interval == 0 {
+                continue;
+            }
+            let due = last_runs.iter()
+                .find(|run| run.job == job)
+                .map_or(true, |run| now - run.started_at >= interval as i64);
+            if !due {
+                continue;
+            }
+            match database.run_maintenance(job, false).await {
+                Ok(run) => runs.push(run),
+                Err(e) => log::error!("❌ {}", e),
+            }
+        }
+        Ok(runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+
+    #[tokio::test]
+    async fn test_manual_runs_are_recorded() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+
+        for job in MaintenanceJob::ALL {
+            let run = db.run_maintenance(job, true).await.unwrap();
+            assert!(run.size_after > 0 && run.error.is_none());
+        }
+        let runs = db.maintenance_runs().await.unwrap();
+        assert_eq!(runs.iter().map(|run| run.job).collect::<Vec<_>>(), MaintenanceJob::ALL.to_vec());
+        assert!(runs.iter().all(|run| run.manual));
+        assert!("defragment".parse::<MaintenanceJob>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_waits_for_quiet_and_due_jobs() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        let config = MaintenanceConfig { wal_checkpoint_interval_secs: 0, ..MaintenanceConfig::default() };
+        let mut scheduler = MaintenanceScheduler::new();
+
+        // The first check has nothing to compare activity with
+        assert!(scheduler.check(&db, &config).await.unwrap().is_empty());
+        let runs = scheduler.check(&db, &config).await.unwrap();
+        assert_eq!(runs.iter().map(|run| run.job).collect::<Vec<_>>(), vec![MaintenanceJob::Analyze, MaintenanceJob::Vacuum]);
+        // Neither is due again yet
+        assert!(scheduler.check(&db, &config).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+//! In-memory storage backend
+//!
+//! Keeps transactions and DAG nodes in ordered maps, for tests and
+//! ephemeral devnet nodes. Nothing touches the filesystem: a manager on this
+//! backend keeps its SQLite tables in an in-memory database too, and
+//! everything is gone once it is dropped.
+
+use super::backend::{StorageBackend, StorageBackendKind, StoredDagNode, StoredTransaction, WriteBatch, WriteOp};
+use super::integrity::transaction_checksum;
+use crate::{BlockchainError, TransactionId};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::sync::RwLock;
+
+/// Transactions and DAG nodes held in memory
+#[derive(Default)]
+pub struct MemoryBackend {
+    store: RwLock<MemoryStore>,
+}
+
+#[derive(Default)]
+struct MemoryStore {
+    transactions: BTreeMap<String, StoredTransaction>,
+    dag_nodes: BTreeMap<String, StoredDagNode>,
+    /// Parent links as (parent, child)
+    children: BTreeSet<(String, String)>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Up to `limit` values of `map` after `after`, in key order
+fn page<T: Clone>(map: &BTreeMap<String, T>, after: Option<TransactionId>, limit: usize) -> Vec<T> {
+    let start = match after {
+        Some(id) => Bound::Excluded(id.as_string()),
+        None => Bound::Unbounded,
+    };
+    map.range((start, Bound::Unbounded)).take(limit).map(|(_, value)| value.clone()).collect()
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for MemoryBackend {
+    fn kind(&self) -> StorageBackendKind {
+        StorageBackendKind::Memory
+    }
+
+    async fn write(&self, batch: WriteBatch) -> Result<(), BlockchainError> {
+        // No change can fail, so applying them under one lock is all or none
+        let mut store = self.store.write().unwrap_or_else(|e| e.into_inner());
+        for op in batch.into_ops() {
+            match op {
+                WriteOp::PutTransaction(transaction) => {
+                    let key = transaction.id.as_string();
+                    for parent in &transaction.parents {
+                        store.children.insert((parent.as_string(), key.clone()));
+                    }
+                    let checksum = Some(transaction_checksum(&transaction));
+                    store.transactions.insert(key, StoredTransaction { transaction, checksum });
+                }
+                WriteOp::PutDagNode(node) => {
+                    store.dag_nodes.insert(node.transaction.id.as_string(), StoredDagNode::from_node(&node));
+                }
+                WriteOp::SetStatus { tx_id, status, confidence } => {
+                    if let Some(node) = store.dag_nodes.get_mut(&tx_id.as_string()) {
+                        node.set_status(status, confidence);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, ids: &[TransactionId]) -> Result<u64, BlockchainError> {
+        let mut store = self.store.write().unwrap_or_else(|e| e.into_inner());
+        let mut deleted = 0;
+        for id in ids {
+            let key = id.as_string();
+            store.dag_nodes.remove(&key);
+            if let Some(stored) = store.transactions.remove(&key) {
+                for parent in &stored.transaction.parents {
+                    store.children.remove(&(parent.as_string(), key.clone()));
+                }
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    async fn get_transaction(&self, tx_id: &TransactionId) -> Result<Option<StoredTransaction>, BlockchainError> {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        Ok(store.transactions.get(&tx_id.as_string()).cloned())
+    }
+
+    async fn get_dag_node(&self, tx_id: &TransactionId) -> Result<Option<StoredDagNode>, BlockchainError> {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        Ok(store.dag_nodes.get(&tx_id.as_string()).cloned())
+    }
+
+    async fn child_ids(&self, tx_id: &TransactionId) -> Result<Vec<TransactionId>, BlockchainError> {
+        let parent = tx_id.as_string();
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        store.children
+            .range((parent.clone(), String::new())..)
+            .take_while(|(linked, _)| linked == &parent)
+            .map(|(_, child)| TransactionId::from_string(child))
+            .collect()
+    }
+
+    async fn scan_transactions(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        Ok(page(&store.transactions, after, limit))
+    }
+
+    async fn scan_dag_nodes(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredDagNode>, BlockchainError> {
+        let store = self.store.read().unwrap_or_else(|e| e.into_inner());
+        Ok(page(&store.dag_nodes, after, limit))
+    }
+
+    async fn transaction_count(&self) -> Result<u64, BlockchainError> {
+        Ok(self.store.read().unwrap_or_else(|e| e.into_inner()).transactions.len() as u64)
+    }
+}
@@ -0,0 +1,214 @@
+//! Search over transaction metadata
+//!
+//! Transactions carry optional JSON metadata. With `metadata_index` on,
+//! triggers on `transactions` write every scalar leaf of a transaction's
+//! metadata to `transaction_metadata` as a lowercased value under its JSON
+//! path, e.g. `{"order": {"id": "A-17"}, "tags": ["gift"]}` as `order.id` =
+//! `a-17` and `tags` = `gift`. Metadata that is not JSON is not indexed.
+//!
+//! Every indexed leaf costs a row and two index entries per transaction
+//! written, so the index is off by default. Turning it on indexes the stored
+//! transactions; turning it off drops the triggers and the indexed rows.
+
+use super::DatabaseManager;
+use super::StorageBackendKind;
+use crate::core::Transaction;
+use crate::{BlockchainError, TransactionId};
+
+const TRIGGERS: [&str; 3] = ["transaction_metadata_insert", "transaction_metadata_update", "transaction_metadata_delete"];
+
+/// Path and value of each scalar leaf of `j`; array elements are indexed
+/// under the array's path
+const LEAF_COLUMNS: &str =
+    "substr(CASE WHEN typeof(j.key) = 'integer' THEN j.path ELSE j.fullkey END, 3), lower(j.atom)";
+
+/// `json_tree` over the metadata of `row`, or over nothing if it is not JSON
+fn leaves(row: &str) -> String {
+    format!(
+        "json_tree(CASE WHEN json_valid(CAST({row}.metadata AS TEXT)) THEN CAST({row}.metadata AS TEXT) ELSE 'null' END) j",
+        row = row
+    )
+}
+
+/// Maximum results of `search_transactions`
+pub const MAX_SEARCH_RESULTS: usize = 1000;
+
+impl DatabaseManager {
+    /// Whether transaction metadata is indexed for search
+    pub async fn metadata_index_enabled(&self) -> Result<bool, BlockchainError> {
+        let trigger: Option<i64> = sqlx::query_scalar("SELECT 1 FROM sqlite_master WHERE type = 'trigger' AND name = ?")
+            .bind(TRIGGERS[0])
+            .fetch_optional(&self.pool().await)
+            .await?;
+        Ok(trigger.is_some())
+    }
+
+    /// Turn the metadata index on, indexing stored transactions, or off
+    pub async fn set_metadata_index(&self, enabled: bool) -> Result<(), BlockchainError> {
+        if self.metadata_index_enabled().await? == enabled {
+            return Ok(());
+        }
+        self.commit_queued().await?;
+        let mut tx = self.pool().await.begin().await?;
+        sqlx::query("DELETE FROM transaction_metadata").execute(&mut *tx).await?;
+        if enabled {
+            let index = format!(
+                "INSERT INTO transaction_metadata (transaction_id, path, value) SELECT NEW.id, {} FROM {} WHERE j.atom IS NOT NULL;",
+                LEAF_COLUMNS,
+                leaves("NEW")
+            );
+            let clear = |row: &str| format!("DELETE FROM transaction_metadata WHERE transaction_id = {}.id;", row);
+            // `INSERT OR REPLACE` does not fire delete triggers, so inserts clear the old rows
+            for (trigger, event, body) in [
+                (TRIGGERS[0], "INSERT", format!("{} {}", clear("NEW"), index)),
+                (TRIGGERS[1], "UPDATE OF metadata", format!("{} {}", clear("OLD"), index)),
+                (TRIGGERS[2], "DELETE", clear("OLD")),
+            ] {
+                sqlx::query(&format!("CREATE TRIGGER {} AFTER {} ON transactions BEGIN {} END", trigger, event, body))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            sqlx::query(&format!(
+                "INSERT INTO transaction_metadata (transaction_id, path, value) SELECT t.id, {} FROM transactions t, {} WHERE j.atom IS NOT NULL",
+                LEAF_COLUMNS,
+                leaves("t")
+            ))
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            for trigger in TRIGGERS {
+                sqlx::query(&format!("DROP TRIGGER IF EXISTS {}", trigger)).execute(&mut *tx).await?;
+            }
+        }
+        tx.commit().await?;
+        log::info!("🔎 Transaction metadata index {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Transactions whose metadata matches every term of `query`, newest first
+    ///
+    /// A term `path=value` matches a leaf at that JSON path, e.g.
+    /// `order.id=A-17`; a bare `value` matches a leaf at any path. Values
+    /// match whole and ignore case.
+    pub async fn search_transactions(&self, query: &str, limit: usize) -> Result<Vec<Transaction>, BlockchainError> {
+        if self.backend_kind() != StorageBackendKind::Sqlite {
+            return Err(BlockchainError::Other("Metadata search needs the SQLite backend".to_string()));
+        }
+        if !self.metadata_index_enabled().await? {
+            return Err(BlockchainError::Other("Metadata search is disabled; enable `metadata_index`".to_string()));
+        }
+        let terms: Vec<(Option<String>, String)> = query.split_whitespace()
+            .map(|term| match term.split_once('=') {
+                Some((path, value)) => (Some(path.to_string()), value.to_lowercase()),
+                None => (None, term.to_lowercase()),
+            })
+            .collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.commit_queued().await?;
+
+        // Each term narrows the IDs matching the terms before it
+        let mut sql = String::from("SELECT t.id FROM transactions t WHERE 1");
+        for (path, _) in &terms {
+            sql.push_str(match path {
+                Some(_) => " AND t.id IN (SELECT transaction_id FROM transaction_metadata WHERE value = ? AND path = ?)",
+                None => " AND t.id IN (SELECT transaction_id FROM transaction_metadata WHERE value = ?)",
+            });
+        }
+        sql.push_str(" ORDER BY t.timestamp DESC, t.id LIMIT ?");
+
+        let mut statement = sqlx::query_scalar::<_, String>(&sql);
+        for (path, value) in &terms {
+            statement = statement.bind(value);
+            if let Some(path) = path {
+                statement = statement.bind(path);
+            }
+        }
+        let ids = statement
+            .bind(limit.min(MAX_SEARCH_RESULTS) as i64)
+            .fetch_all(&self.pool().await)
+            .await?;
+
+        let mut transactions = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(transaction) = self.get_transaction(&TransactionId::from_string(&id)?).await? {
+                transactions.push(transaction);
+            }
+        }
+        Ok(transactions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+
+    fn transaction(nonce: u64, metadata: Option<serde_json::Value>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000 + nonce,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+            metadata: metadata.map(|metadata| metadata.to_string().into_bytes()),
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    async fn database(temp_dir: &TempDir, metadata_index: bool) -> DatabaseManager {
+        DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            metadata_index,
+            ..DatabaseConfig::default()
+        }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_by_path_and_value() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = database(&temp_dir, true).await;
+        let order = transaction(1, Some(serde_json::json!({"order": {"id": "A-17"}, "tags": ["gift", "express"]})));
+        let other = transaction(2, Some(serde_json::json!({"invoice": "A-17", "tags": ["gift"]})));
+        let plain = transaction(3, None);
+        for transaction in [&order, &other, &plain] {
+            db.store_transaction(transaction).await.unwrap();
+        }
+
+        let ids = |found: Vec<Transaction>| found.into_iter().map(|transaction| transaction.id).collect::<Vec<_>>();
+        assert_eq!(ids(db.search_transactions("a-17", 10).await.unwrap()), vec![other.id.clone(), order.id.clone()]);
+        assert_eq!(ids(db.search_transactions("order.id=A-17", 10).await.unwrap()), vec![order.id.clone()]);
+        assert_eq!(ids(db.search_transactions("tags=gift express", 10).await.unwrap()), vec![order.id.clone()]);
+        assert!(db.search_transactions("tags=missing", 10).await.unwrap().is_empty());
+
+        // Rewriting a transaction does not duplicate its leaves
+        db.store_transaction(&order).await.unwrap();
+        assert_eq!(db.search_transactions("gift", 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_enabling_indexes_stored_transactions() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = database(&temp_dir, false).await;
+        let order = transaction(1, Some(serde_json::json!({"order": 42})));
+        db.store_transaction(&order).await.unwrap();
+        assert!(db.search_transactions("42", 10).await.is_err());
+
+        db.set_metadata_index(true).await.unwrap();
+        assert_eq!(db.search_transactions("order=42", 10).await.unwrap()[0].id, order.id);
+
+        db.set_metadata_index(false).await.unwrap();
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transaction_metadata").fetch_one(&db.pool().await).await.unwrap();
+        assert_eq!(rows, 0);
+    }
+}
@@ -0,0 +1,92 @@
+//! Storage latency and throughput metrics
+//!
+//! `DatabaseManager` times its reads and writes per operation, and records
+//! the size and duration of every batch committed to the backend, whether
+//! from the write queue or from a write made directly. The collectors are
+//! created unregistered, so databases opened by tools and tests cost
+//! nothing to export; the node registers them with `BlockchainMetrics`.
+
+use prometheus::{exponential_buckets, Histogram, HistogramOpts, HistogramTimer, HistogramVec, IntGauge, Opts, Registry};
+use std::time::Duration;
+
+/// Latency, commit and queue collectors of one database
+#[derive(Clone)]
+pub struct StorageMetrics {
+    operation_seconds: HistogramVec,
+    commit_seconds: Histogram,
+    commit_size: Histogram,
+    queue_depth: IntGauge,
+}
+
+impl StorageMetrics {
+    pub fn new() -> Result<Self, prometheus::Error> {
+        // From 100µs, where a cached read lands, to several seconds
+        let latency_buckets = exponential_buckets(0.0001, 4.0, 9)?;
+        Ok(Self {
+            operation_seconds: HistogramVec::new(
+                HistogramOpts::new("dag_storage_operation_seconds", "Time spent in each storage operation")
+                    .buckets(latency_buckets.clone()),
+                &["operation"],
+            )?,
+            commit_seconds: Histogram::with_opts(
+                HistogramOpts::new("dag_storage_commit_seconds", "Time to commit a batch of writes to the backend")
+                    .buckets(latency_buckets),
+            )?,
+            commit_size: Histogram::with_opts(
+                HistogramOpts::new("dag_storage_commit_size", "Changes per batch committed to the backend")
+                    .buckets(exponential_buckets(1.0, 4.0, 8)?),
+            )?,
+            queue_depth: IntGauge::with_opts(Opts::new(
+                "dag_storage_write_queue_depth",
+                "Changes queued and not yet committed",
+            ))?,
+        })
+    }
+
+    /// Timer recording the duration of `operation` when dropped
+    pub fn time(&self, operation: &str) -> HistogramTimer {
+        self.operation_seconds.with_label_values(&[operation]).start_timer()
+    }
+
+    /// Record a batch of `changes` committed in `elapsed`
+    pub fn record_commit(&self, changes: usize, elapsed: Duration) {
+        self.commit_size.observe(changes as f64);
+        self.commit_seconds.observe(elapsed.as_secs_f64());
+    }
+
+    pub fn set_queue_depth(&self, changes: usize) {
+        self.queue_depth.set(changes as i64);
+    }
+
+    /// Export the collectors through `registry`
+    pub fn register(&self, registry: &Registry) -> Result<(), prometheus::Error> {
+        registry.register(Box::new(self.operation_seconds.clone()))?;
+        registry.register(Box::new(self.commit_seconds.clone()))?;
+        registry.register(Box::new(self.commit_size.clone()))?;
+        registry.register(Box::new(self.queue_depth.clone()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{DatabaseConfig, DatabaseManager};
+
+    #[tokio::test]
+    async fn test_operations_are_exported() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        let registry = Registry::new();
+        db.metrics().register(&registry).unwrap();
+
+        db.get_transaction(&crate::TransactionId::default()).await.unwrap();
+        db.metrics().record_commit(3, Duration::from_millis(2));
+
+        let families = registry.gather();
+        let family = |name: &str| families.iter().find(|family| family.get_name() == name).unwrap();
+        let operations = family("dag_storage_operation_seconds").get_metric();
+        assert_eq!(operations[0].get_label()[0].get_value(), "get_transaction");
+        assert_eq!(operations[0].get_histogram().get_sample_count(), 1);
+        assert_eq!(family("dag_storage_commit_size").get_metric()[0].get_histogram().get_sample_sum(), 3.0);
+    }
+}
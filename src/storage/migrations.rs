@@ -0,0 +1,373 @@
+//! Versioned schema migrations
+//!
+//! `init_database` creates the version 1 schema, upgrading databases from
+//! before migrations were recorded along the way; it must not change any
+//! further. Every later schema change is a new entry at the end of
+//! `MIGRATIONS`, whose statements run in one transaction together with the
+//! row recording it in `schema_migrations`. Statements that SQLite refuses
+//! inside a transaction, such as `VACUUM`, cannot be part of a migration.
+//!
+//! Nodes apply pending migrations on startup unless `auto_migrate` is off,
+//! in which case `dag-cli migrate` applies them, or shows what it would
+//! apply with `--dry-run`. A dry run applies every pending migration and
+//! rolls all of them back, so it fails where the real run would.
+
+use super::DatabaseManager;
+use crate::BlockchainError;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::sqlite::SqliteConnection;
+
+/// One step of the schema's history
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub statements: &'static [&'static str],
+}
+
+/// Every migration, in the order they apply
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        // Created by `init_database`
+        statements: &[],
+    },
+    Migration {
+        version: 2,
+        name: "transactions_timestamp_id_index",
+        // Keyset pages of `stream_transactions` seek on (timestamp, id)
+        statements: &["CREATE INDEX IF NOT EXISTS idx_transactions_timestamp_id ON transactions(timestamp, id)"],
+    },
+    Migration {
+        version: 3,
+        name: "state_checkpoints",
+        statements: &[
+            "CREATE TABLE state_checkpoints (
+                height INTEGER PRIMARY KEY,
+                taken_at INTEGER NOT NULL,
+                genesis_id TEXT NOT NULL,
+                state_root TEXT NOT NULL,
+                accounts INTEGER NOT NULL,
+                applied_transactions INTEGER NOT NULL
+            )",
+            "CREATE TABLE state_checkpoint_balances (
+                height INTEGER NOT NULL,
+                address TEXT NOT NULL,
+                balance TEXT NOT NULL,
+                PRIMARY KEY (height, address)
+            )",
+            "CREATE TABLE state_checkpoint_applied (
+                height INTEGER NOT NULL,
+                transaction_id TEXT NOT NULL,
+                PRIMARY KEY (height, transaction_id)
+            )",
+            "CREATE TABLE state_checkpoint_tips (
+                height INTEGER NOT NULL,
+                transaction_id TEXT NOT NULL,
+                PRIMARY KEY (height, transaction_id)
+            )",
+        ],
+    },
+    Migration {
+        version: 4,
+        name: "transactions_address_columns",
+        // `get_address_history` seeks on the hex addresses, which the
+        // triggers keep filled whichever code path writes the row
+        statements: &[
+            "ALTER TABLE transactions ADD COLUMN sender_hex TEXT",
+            "ALTER TABLE transactions ADD COLUMN receiver_hex TEXT",
+            "UPDATE transactions SET sender_hex = lower(hex(sender)), receiver_hex = lower(hex(receiver))",
+            "CREATE TRIGGER transactions_address_hex AFTER INSERT ON transactions BEGIN
+                UPDATE transactions SET sender_hex = lower(hex(NEW.sender)), receiver_hex = lower(hex(NEW.receiver)) WHERE id = NEW.id;
+            END",
+            "CREATE TRIGGER transactions_address_hex_update AFTER UPDATE OF sender, receiver ON transactions BEGIN
+                UPDATE transactions SET sender_hex = lower(hex(NEW.sender)), receiver_hex = lower(hex(NEW.receiver)) WHERE id = NEW.id;
+            END",
+            "CREATE INDEX idx_transactions_sender_hex ON transactions(sender_hex, timestamp, id)",
+            "CREATE INDEX idx_transactions_receiver_hex ON transactions(receiver_hex, timestamp, id)",
+        ],
+    },
+    Migration {
+        version: 5,
+        name: "maintenance_runs",
+        statements: &["CREATE TABLE maintenance_runs (
+            job TEXT PRIMARY KEY,
+            started_at INTEGER NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            manual INTEGER NOT NULL,
+            size_before INTEGER NOT NULL,
+            size_after INTEGER NOT NULL,
+            error TEXT
+        )"],
+    },
+    Migration {
+        version: 6,
+        name: "receipt_contracts_and_events",
+        // Receipts written before kept their contract only in their events
+        statements: &[
+            "ALTER TABLE transaction_receipts ADD COLUMN contract_id TEXT",
+            "UPDATE transaction_receipts SET contract_id = json_extract(data, '$.events[0].contract_id')",
+            "CREATE INDEX idx_transaction_receipts_contract ON transaction_receipts(contract_id, finalization_height)",
+            "CREATE TABLE contract_events (
+                transaction_id TEXT NOT NULL,
+                log_index INTEGER NOT NULL,
+                contract_id TEXT NOT NULL,
+                topic TEXT NOT NULL,
+                finalization_height INTEGER NOT NULL,
+                data TEXT NOT NULL,
+                PRIMARY KEY (transaction_id, log_index)
+            )",
+            "INSERT INTO contract_events (transaction_id, log_index, contract_id, topic, finalization_height, data)
+                SELECT r.transaction_id, e.key, json_extract(e.value, '$.contract_id'), json_extract(e.value, '$.name'), r.finalization_height, json(e.value)
+                FROM transaction_receipts r, json_each(r.data, '$.events') e",
+            "CREATE INDEX idx_contract_events_contract ON contract_events(contract_id, finalization_height)",
+            "CREATE INDEX idx_contract_events_topic ON contract_events(topic, finalization_height)",
+        ],
+    },
+    Migration {
+        version: 7,
+        name: "pruned_transactions",
+        statements: &[
+            "CREATE TABLE pruned_transactions (
+                transaction_id TEXT PRIMARY KEY,
+                finalization_height INTEGER NOT NULL,
+                pruned_at INTEGER NOT NULL,
+                link TEXT NOT NULL
+            )",
+            "CREATE INDEX idx_pruned_transactions_height ON pruned_transactions(finalization_height)",
+        ],
+    },
+    Migration {
+        version: 8,
+        name: "bulk_imports",
+        statements: &[
+            "CREATE TABLE bulk_imports (
+                source TEXT PRIMARY KEY,
+                format TEXT NOT NULL,
+                lines_done INTEGER NOT NULL,
+                imported INTEGER NOT NULL,
+                skipped INTEGER NOT NULL,
+                invalid INTEGER NOT NULL,
+                dropped_indexes TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                completed_at INTEGER
+            )",
+        ],
+    },
+    Migration {
+        version: 9,
+        name: "transaction_metadata",
+        statements: &[
+            "CREATE TABLE transaction_metadata (
+                transaction_id TEXT NOT NULL,
+                path TEXT NOT NULL,
+                value TEXT NOT NULL
+            )",
+            "CREATE INDEX idx_transaction_metadata_value ON transaction_metadata(value, path)",
+            "CREATE INDEX idx_transaction_metadata_transaction ON transaction_metadata(transaction_id)",
+        ],
+    },
+];
+
+/// A migration applied to the database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub applied_at: i64,
+}
+
+/// Outcome of applying, or dry-running, pending migrations
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Versions and names of the migrations applied, in order
+    pub applied: Vec<(u32, String)>,
+    /// Whether the migrations were rolled back
+    pub dry_run: bool,
+}
+
+/// Latest schema version this build knows
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+impl DatabaseManager {
+    /// Record the schema version, then apply pending migrations when `auto_migrate` is set
+    pub(crate) async fn init_migrations(&self, auto_migrate: bool) -> Result<(), BlockchainError> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        // `init_database` has just brought the schema to version 1
+        sqlx::query("INSERT OR IGNORE INTO schema_migrations (version, name, applied_at) VALUES (?, ?, ?)")
+            .bind(MIGRATIONS[0].version)
+            .bind(MIGRATIONS[0].name)
+            .bind(Utc::now().timestamp())
+            .execute(&self.pool().await)
+            .await?;
+
+        let version = self.schema_version().await?;
+        if version > latest_version() {
+            return Err(BlockchainError::Other(format!(
+                "Database schema version {} is newer than this node supports ({}); upgrade the node",
+                version,
+                latest_version()
+            )));
+        }
+
+        let pending = self.pending_migrations().await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if auto_migrate {
+            self.migrate(false).await?;
+        } else {
+            log::warn!("⚠️ {} schema migration(s) pending at version {}; run `dag-cli migrate` to apply them", pending.len(), version);
+        }
+        Ok(())
+    }
+
+    /// Version of the latest migration applied
+    pub async fn schema_version(&self) -> Result<u32, BlockchainError> {
+        let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_migrations")
+            .fetch_one(&self.pool().await)
+            .await?;
+        Ok(version.unwrap_or(0) as u32)
+    }
+
+    /// Migrations applied so far, oldest first
+    pub async fn applied_migrations(&self) -> Result<Vec<AppliedMigration>, BlockchainError> {
+        let rows: Vec<(i64, String, i64)> = sqlx::query_as("SELECT version, name, applied_at FROM schema_migrations ORDER BY version")
+            .fetch_all(&self.pool().await)
+            .await?;
+        Ok(rows.into_iter()
+            .map(|(version, name, applied_at)| AppliedMigration { version: version as u32, name, applied_at })
+            .collect())
+    }
+
+    /// Migrations not applied yet, in the order they would apply
+    pub async fn pending_migrations(&self) -> Result<Vec<Migration>, BlockchainError> {
+        let version = self.schema_version().await?;
+        Ok(MIGRATIONS.iter().filter(|migration| migration.version > version).copied().collect())
+    }
+
+    /// Apply pending migrations, or with `dry_run` apply and roll them back
+    pub async fn migrate(&self, dry_run: bool) -> Result<MigrationReport, BlockchainError> {
+        self.run_migrations(MIGRATIONS, dry_run).await
+    }
+
+    async fn run_migrations(&self, migrations: &[Migration], dry_run: bool) -> Result<MigrationReport, BlockchainError> {
+        self.commit_queued().await?;
+        let from_version = self.schema_version().await?;
+        let pending: Vec<&Migration> = migrations.iter().filter(|migration| migration.version > from_version).collect();
+
+        if dry_run {
+            let mut tx = self.pool().await.begin().await?;
+            for migration in &pending {
+                apply_migration(&mut tx, migration).await?;
+            }
+            tx.rollback().await?;
+        } else {
+            for migration in &pending {
+                let mut tx = self.pool().await.begin().await?;
+                apply_migration(&mut tx, migration).await?;
+                tx.commit().await?;
+                log::info!("🗃️ Applied schema migration {} ({})", migration.version, migration.name);
+            }
+        }
+
+        Ok(MigrationReport {
+            from_version,
+            to_version: pending.last().map_or(from_version, |migration| migration.version),
+            applied: pending.iter().map(|migration| (migration.version, migration.name.to_string())).collect(),
+            dry_run,
+        })
+    }
+}
+
+async fn apply_migration(conn: &mut SqliteConnection, migration: &Migration) -> Result<(), BlockchainError> {
+    for statement in migration.statements {
+        sqlx::query(statement)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| BlockchainError::Other(format!("Schema migration {} ({}) failed: {}", migration.version, migration.name, e)))?;
+    }
+    sqlx::query("INSERT INTO schema_migrations (version, name, applied_at) VALUES (?, ?, ?)")
+        .bind(migration.version)
+        .bind(migration.name)
+        .bind(Utc::now().timestamp())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+
+    /// The real migrations, then one adding a table
+    fn test_migrations() -> Vec<Migration> {
+        let mut migrations = MIGRATIONS.to_vec();
+        migrations.push(Migration {
+            version: latest_version() + 1,
+            name: "add_notes",
+            statements: &["CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL)"],
+        });
+        migrations
+    }
+
+    async fn has_notes_table(db: &DatabaseManager) -> bool {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM sqlite_master WHERE name = 'notes'")
+            .fetch_one(&db.pool().await)
+            .await
+            .unwrap() > 0
+    }
+
+    #[tokio::test]
+    async fn test_new_database_is_at_latest_version() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), latest_version());
+        assert!(db.pending_migrations().await.unwrap().is_empty());
+        assert_eq!(db.applied_migrations().await.unwrap()[0].name, "initial_schema");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_rolls_back() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+
+        let (latest, migrations) = (latest_version(), test_migrations());
+
+        let report = db.run_migrations(&migrations, true).await.unwrap();
+        assert_eq!((report.from_version, report.to_version, report.dry_run), (latest, latest + 1, true));
+        assert_eq!(db.schema_version().await.unwrap(), latest);
+        assert!(!has_notes_table(&db).await);
+
+        let report = db.run_migrations(&migrations, false).await.unwrap();
+        assert_eq!(report.applied, vec![(latest + 1, "add_notes".to_string())]);
+        assert_eq!(db.schema_version().await.unwrap(), latest + 1);
+        assert!(has_notes_table(&db).await);
+
+        // Applied migrations are not run again
+        assert!(db.run_migrations(&migrations, false).await.unwrap().applied.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_newer_schema_is_refused() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        db.run_migrations(&test_migrations(), false).await.unwrap();
+        assert!(db.init_migrations(true).await.is_err());
+    }
+}
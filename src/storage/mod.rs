@@ -4,9 +4,9 @@
 //! blockchain data including transactions, DAG nodes, and consensus state.
 //! Includes backup and recovery functionality for data persistence.
 
-use crate::{BlockchainError, TransactionId, core::{Transaction, DAGNode, NodeStatus, QuantumProof}};
+use crate::{BlockchainError, EventBus, TransactionId, core::{Amount, Transaction, DAGNode, NodeStatus, QuantumProof}};
 use serde::{Deserialize, Serialize};
-use sqlx::{SqlitePool, sqlite::SqliteRow, Row, sqlite::SqliteConnectOptions};
+use sqlx::{SqlitePool, Row, sqlite::SqliteConnectOptions};
 use std::path::Path;
 use std::str::FromStr;
 use chrono::{DateTime, Utc};
@@ -14,9 +14,110 @@ use std::fs;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 
+pub mod accounts;
+pub mod archival;
+pub mod backend;
+pub mod bootstrap;
+pub mod bulk_import;
+pub mod cursor;
+pub mod encryption;
+pub mod export;
+pub mod id_migration;
+pub mod incremental;
+pub mod integrity;
+pub mod journal;
+pub mod maintenance;
+pub mod memory;
+pub mod metadata_search;
+pub mod metrics;
+pub mod migrations;
+pub mod pragmas;
+pub mod pool;
+pub mod receipts;
+pub mod reindex;
+pub mod remote_backup;
+pub mod retention;
+pub mod replica;
+pub mod rocks;
+pub mod sharded;
+pub mod snapshot;
+pub mod storage_mode;
+pub mod state_checkpoint;
+pub mod swaps;
+pub mod tokens;
+pub mod verify;
+pub mod write_queue;
+
+pub use archival::{content_id, ArchivalConfig, ArchivalError, ArchiveAnchor, ArchiveBundle, ArchiveManifest, ArchiveRecord, Archiver, ColdStorage, FilesystemColdStorage, HttpColdStorage, ManifestEntry, MilestoneRecorder, ARCHIVE_FORMAT_VERSION};
+pub use backend::{parse_node_status, SqliteBackend, StorageBackend, StorageBackendKind, StoredDagNode, StoredTransaction, WriteBatch, WriteOp};
+pub use bootstrap::{SnapshotAccount, BootstrapInfo, BootstrapSnapshot, BOOTSTRAP_FORMAT_VERSION};
+pub use bulk_import::{BulkImportConfig, BulkImportFormat, BulkImportPhase, BulkImportProgress, BulkImportReport};
+pub use cursor::{TransactionCursor, STREAM_PAGE};
+pub use encryption::{encryption_supported, resolve_encryption_key, DATABASE_KEY_ENV};
+pub use id_migration::IdMigrationReport;
+pub use journal::{JournalEntry, JournalRecorder, JOURNALED_EVENTS};
+pub use maintenance::{MaintenanceConfig, MaintenanceJob, MaintenanceRun, MaintenanceScheduler};
+pub use memory::MemoryBackend;
+pub use metadata_search::MAX_SEARCH_RESULTS;
+pub use metrics::StorageMetrics;
+pub use migrations::{AppliedMigration, Migration, MigrationReport, MIGRATIONS};
+pub use pool::SharedPool;
+pub use receipts::{EventFilter, EventLog, ReceiptStatus, TransactionReceipt, DEFAULT_EVENT_LIMIT};
+pub use integrity::{dag_node_checksum, transaction_checksum, ChecksumConfig, ChecksumMode, CorruptedRow, CorruptionResolution, TransactionSource};
+pub use reindex::{ReindexConfig, ReindexPhase, ReindexProgress, ReindexReport, ReindexStatus};
+pub use pragmas::{JournalMode, SqlitePragmas, SynchronousLevel};
+pub use remote_backup::{BackupDestination, DirectoryBackupDestination, S3BackupConfig, S3BackupDestination, REMOTE_KEY, S3_ACCESS_KEY_ENV, S3_SECRET_KEY_ENV};
+pub use rocks::RocksDbBackend;
+pub use sharded::{shard_path, ShardStats, ShardedBackend, MAX_SHARDS};
+pub use retention::{RetentionConfig, RetentionReport, RetentionTable, TableRetentionPolicy, TableRetentionReport};
+pub use replica::{ReadReplica, ReadReplicaConfig, ReadSource, ReplicationMarker, Staleness};
+pub use snapshot::{BalanceDivergence, DivergenceSet, SnapshotDiff, StateSnapshot, DEFAULT_DIFF_SAMPLES};
+pub use storage_mode::{PrunedHeader, StorageMode};
+pub use state_checkpoint::{checkpoint_state, finalized_tips, StateCheckpoint, StateCheckpointConfig};
+pub use tokens::Token;
+pub use verify::{DatabaseIntegrityReport, IntegrityIssue, IntegrityIssueKind};
+pub use write_queue::WriteQueueConfig;
+
+use write_queue::{QueuedNode, WriteQueue};
+
+/// Columns holding amounts, stored as decimal text by `accounts::stored_amount`
+const AMOUNT_COLUMNS: [(&str, &str); 7] = [
+    ("transactions", "amount"),
+    ("transactions", "fee"),
+    ("account_balances", "balance"),
+    ("tokens", "supply"),
+    ("token_balances", "balance"),
+    ("swaps", "initiator_amount"),
+    ("swaps", "counter_amount"),
+];
+
 /// Database manager for blockchain persistence
 pub struct DatabaseManager {
-    pool: SqlitePool,
+    /// Replaced when a backup is restored
+    pool: SharedPool,
+    /// SQLite file the pool is connected to
+    path: String,
+    /// SQLCipher key the database is opened with
+    encryption_key: Option<String>,
+    /// Set on every connection, including those opened after a restore
+    pragmas: SqlitePragmas,
+    /// Whether old transaction bodies are pruned
+    mode: StorageMode,
+    /// Where transactions and DAG nodes are kept
+    backend: std::sync::Arc<dyn StorageBackend>,
+    /// The backend's shards, backed up and restored with the database
+    shards: Option<std::sync::Arc<ShardedBackend>>,
+    /// Writes waiting to be committed, when write-behind is enabled
+    queue: Option<std::sync::Arc<WriteQueue>>,
+    retention: RetentionConfig,
+    /// Which reads verify row checksums
+    checksums: std::sync::RwLock<ChecksumConfig>,
+    /// Where transactions lost to corruption are fetched again
+    transaction_source: std::sync::RwLock<Option<std::sync::Arc<dyn TransactionSource>>>,
+    /// Where backups are copied after they are written
+    backup_destination: std::sync::RwLock<Option<std::sync::Arc<dyn BackupDestination>>>,
+    events: Option<EventBus>,
+    metrics: StorageMetrics,
 }
 
 /// Database transaction record
@@ -25,7 +126,10 @@ pub struct DbTransaction {
     pub id: String,
     pub sender: Vec<u8>,
     pub receiver: Vec<u8>,
-    pub amount: u64,
+    #[serde(with = "crate::core::amount")]
+    pub amount: Amount,
+    #[serde(with = "crate::core::amount")]
+    pub fee: Amount,
     pub nonce: u64,
     pub timestamp: i64,
     pub signature: Vec<u8>,
@@ -51,6 +155,24 @@ pub struct DbDagNode {
 pub struct DatabaseConfig {
     pub path: String,
     pub max_connections: u32,
+    pub retention: RetentionConfig,
+    pub checksums: ChecksumConfig,
+    /// Where transactions and DAG nodes are kept
+    pub backend: StorageBackendKind,
+    /// Batching of transaction and DAG node writes
+    pub write_queue: WriteQueueConfig,
+    /// Whether pending schema migrations are applied on startup
+    pub auto_migrate: bool,
+    /// SQLCipher key of the database file, see `encryption::resolve_encryption_key`
+    pub encryption_key: Option<String>,
+    /// Bucket every backup is also uploaded to
+    pub remote_backup: Option<S3BackupConfig>,
+    /// Journal mode, durability and caching of the SQLite connections
+    pub pragmas: SqlitePragmas,
+    /// Whether old transaction bodies are kept
+    pub mode: StorageMode,
+    /// Whether transaction metadata is indexed for `search_transactions`
+    pub metadata_index: bool,
 }
 
 impl Default for DatabaseConfig {
@@ -58,33 +180,136 @@ impl Default for DatabaseConfig {
         Self {
             path: "./blockchain.db".to_string(),
             max_connections: 10,
+            retention: RetentionConfig::default(),
+            checksums: ChecksumConfig::default(),
+            backend: StorageBackendKind::default(),
+            write_queue: WriteQueueConfig::default(),
+            auto_migrate: true,
+            encryption_key: None,
+            remote_backup: None,
+            pragmas: SqlitePragmas::default(),
+            mode: StorageMode::default(),
+            metadata_index: false,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    /// A database that never touches the filesystem, for tests and ephemeral nodes
+    pub fn in_memory() -> Self {
+        Self {
+            path: ":memory:".to_string(),
+            backend: StorageBackendKind::Memory,
+            ..Self::default()
         }
     }
+
+    /// Directory of the RocksDB backend, next to the SQLite file
+    pub fn rocksdb_path(&self) -> String {
+        format!("{}.rocksdb", self.path)
+    }
 }
 
 impl DatabaseManager {
     /// Create a new database manager
     pub async fn new(config: DatabaseConfig) -> Result<Self, BlockchainError> {
-        // Ensure database directory exists
-        if let Some(parent) = Path::new(&config.path).parent() {
-            tokio::fs::create_dir_all(parent).await?;
-        }
+        config.mode.validate()?;
+        let pool = match config.backend {
+            StorageBackendKind::Memory => Self::memory_pool().await?,
+            StorageBackendKind::Sqlite | StorageBackendKind::RocksDb | StorageBackendKind::Sharded { .. } => {
+                // Ensure database directory exists
+                if let Some(parent) = Path::new(&config.path).parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
 
-        // Create database connection pool
-        let pool = SqlitePool::connect_with(
-            sqlx::sqlite::SqliteConnectOptions::from_str(&format!("sqlite://{}", config.path))?
-                .create_if_missing(true)
-        ).await?;
+                // Create database connection pool
+                config.pragmas.validate()?;
+                if config.write_queue.enabled && config.pragmas.journal_mode != JournalMode::Wal {
+                    log::warn!("⚠️ Queued writes block readers while they commit outside WAL mode");
+                }
+                let options = config.pragmas.apply(
+                    sqlx::sqlite::SqliteConnectOptions::from_str(&format!("sqlite://{}", config.path))?.create_if_missing(true),
+                );
+                if config.encryption_key.is_some() && config.backend == StorageBackendKind::RocksDb {
+                    log::warn!("⚠️ Only the SQLite tables are encrypted; RocksDB files at {} are not", config.rocksdb_path());
+                }
+                SqlitePool::connect_with(encryption::with_key(options, config.encryption_key.as_deref())?).await?
+            }
+        };
 
-        let manager = Self { pool };
+        let pool = SharedPool::new(pool);
+        let shards = match config.backend {
+            StorageBackendKind::Sharded { shards } => Some(std::sync::Arc::new(ShardedBackend::open(&config, shards).await?)),
+            _ => None,
+        };
+        let backend: std::sync::Arc<dyn StorageBackend> = match config.backend {
+            StorageBackendKind::Sqlite => std::sync::Arc::new(SqliteBackend::shared(pool.clone())),
+            StorageBackendKind::RocksDb => std::sync::Arc::new(RocksDbBackend::open(config.rocksdb_path())?),
+            StorageBackendKind::Memory => std::sync::Arc::new(MemoryBackend::new()),
+            StorageBackendKind::Sharded { .. } => shards.clone().expect("shards are opened for the sharded backend"),
+        };
+
+        let metrics = StorageMetrics::new().map_err(|e| BlockchainError::Other(e.to_string()))?;
+        let queue = config.write_queue.enabled.then(|| WriteQueue::start(config.write_queue.clone(), backend.clone(), metrics.clone()));
+
+        let manager = Self {
+            pool,
+            path: config.path.clone(),
+            // Nothing of an in-memory database is at rest
+            encryption_key: config.encryption_key.clone().filter(|_| config.backend != StorageBackendKind::Memory),
+            pragmas: config.pragmas.clone(),
+            mode: config.mode,
+            backend,
+            shards,
+            queue,
+            retention: config.retention.clone(),
+            checksums: std::sync::RwLock::new(config.checksums.clone()),
+            transaction_source: std::sync::RwLock::new(None),
+            backup_destination: std::sync::RwLock::new(config.remote_backup.clone()
+                .map(|remote| S3BackupDestination::new(remote).map(|destination| std::sync::Arc::new(destination) as std::sync::Arc<dyn BackupDestination>))
+                .transpose()?),
+            events: None,
+            metrics,
+        };
         
         // Initialize database schema
         manager.init_database().await?;
+
+        // Re-key transactions stored before IDs were content-addressed
+        if manager.has_legacy_transaction_ids().await? {
+            manager.migrate_transaction_ids().await?;
+        }
+
+        manager.init_migrations(config.auto_migrate).await?;
+        manager.set_metadata_index(config.metadata_index).await?;
         
-        log::info!("Database initialized at: {}", config.path);
+        log::info!("Database initialized at: {} ({:?} backend)", config.path, config.backend);
         Ok(manager)
     }
 
+    /// Pool over an in-memory SQLite database
+    ///
+    /// The database lives as long as one of its connections, so the pool
+    /// keeps its only connection open for good.
+    async fn memory_pool() -> Result<SqlitePool, BlockchainError> {
+        Ok(sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(SqliteConnectOptions::from_str("sqlite::memory:")?)
+            .await?)
+    }
+
+    /// Pool to run the next query on
+    pub(crate) async fn pool(&self) -> SqlitePool {
+        self.pool.get().await
+    }
+
+    /// Publish corruption alerts to `events`
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = Some(events);
+    }
+
     /// Initialize database schema
     async fn init_database(&self) -> Result<(), BlockchainError> {
         // Create transactions table
@@ -94,18 +319,22 @@ impl DatabaseManager {
                 id TEXT PRIMARY KEY,
                 sender BLOB NOT NULL,
                 receiver BLOB NOT NULL,
-                amount INTEGER NOT NULL,
+                amount TEXT NOT NULL,
                 nonce INTEGER NOT NULL,
                 timestamp INTEGER NOT NULL,
                 signature BLOB NOT NULL,
                 prime_hash BLOB NOT NULL,
                 resistance_score INTEGER NOT NULL,
                 proof_timestamp INTEGER NOT NULL,
-                metadata BLOB
+                metadata BLOB,
+                parents TEXT,
+                signature_scheme TEXT,
+                fee TEXT NOT NULL DEFAULT '0',
+                checksum TEXT
             )
             "#
         )
-        .execute(&self.pool)
+        .execute(&self.pool().await)
         .await?;
 
         // Create DAG nodes table
@@ -118,11 +347,12 @@ impl DatabaseManager {
                 confidence REAL NOT NULL,
                 status TEXT NOT NULL,
                 quantum_score INTEGER NOT NULL,
+                checksum TEXT,
                 FOREIGN KEY (transaction_id) REFERENCES transactions (id)
             )
             "#
         )
-        .execute(&self.pool)
+        .execute(&self.pool().await)
         .await?;
 
         // Create transaction parents table (for DAG relationships)
@@ -137,125 +367,438 @@ impl DatabaseManager {
             )
             "#
         )
-        .execute(&self.pool)
+        .execute(&self.pool().await)
         .await?;
 
-        // Create indexes for better performance
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_transactions_timestamp ON transactions(timestamp)")
-            .execute(&self.pool)
-            .await?;
+        // Create archive table for finalized transactions past their hot window
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS transactions_archive (
+                id TEXT PRIMARY KEY,
+                sender BLOB NOT NULL,
+                receiver BLOB NOT NULL,
+                timestamp INTEGER NOT NULL,
+                data TEXT NOT NULL,
+                archived_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_dag_nodes_status ON dag_nodes(status)")
-            .execute(&self.pool)
-            .await?;
+        // Create account balance tables, updated as transactions finalize
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS account_balances (
+                address TEXT PRIMARY KEY,
+                balance TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_transaction_parents_parent ON transaction_parents(parent_id)")
-            .execute(&self.pool)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS applied_transactions (
+                transaction_id TEXT PRIMARY KEY,
+                applied_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        // Issued tokens, their balances, and swaps escrowing them
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS tokens (
+                token_id TEXT PRIMARY KEY,
+                issuer TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                supply TEXT NOT NULL,
+                issued_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS token_balances (
+                token_id TEXT NOT NULL,
+                address TEXT NOT NULL,
+                balance TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (token_id, address)
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS swaps (
+                hashlock TEXT PRIMARY KEY,
+                initiator TEXT NOT NULL,
+                initiator_asset TEXT NOT NULL,
+                initiator_amount TEXT NOT NULL,
+                counterparty TEXT NOT NULL,
+                counter_asset TEXT NOT NULL,
+                counter_amount TEXT NOT NULL,
+                expires_at INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                preimage TEXT,
+                initiated_by TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_swaps_initiator ON swaps(initiator)")
+            .execute(&self.pool().await)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_swaps_counterparty ON swaps(counterparty)")
+            .execute(&self.pool().await)
             .await?;
 
-        log::debug!("Database schema initialized");
-        Ok(())
-    }
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS finality_milestones (
+                height INTEGER NOT NULL,
+                transaction_id TEXT NOT NULL,
+                PRIMARY KEY (height, transaction_id)
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
 
-    /// Store a transaction in the database
-    pub async fn store_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
-        let mut tx = self.pool.begin().await?;
+        // Outcomes of finalized transactions
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS transaction_receipts (
+                transaction_id TEXT PRIMARY KEY,
+                finalization_height INTEGER NOT NULL,
+                data TEXT NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS archives (
+                content_id TEXT PRIMARY KEY,
+                from_height INTEGER NOT NULL,
+                to_height INTEGER NOT NULL,
+                transaction_count INTEGER NOT NULL,
+                manifest_root TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                backend TEXT NOT NULL,
+                locator TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                anchor_tx TEXT
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS archive_anchors (
+                content_id TEXT PRIMARY KEY,
+                from_height INTEGER NOT NULL,
+                to_height INTEGER NOT NULL,
+                manifest_root TEXT NOT NULL,
+                anchored_by TEXT NOT NULL,
+                anchored_at INTEGER NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool().await)
+        .await?;
 
-        // Store transaction
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO transactions 
-            (id, sender, receiver, amount, nonce, timestamp, signature, prime_hash, resistance_score, proof_timestamp, metadata)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            CREATE TABLE IF NOT EXISTS event_journal (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                recorded_at INTEGER NOT NULL
+            )
             "#
         )
-        .bind(transaction.id.as_string())
-        .bind(&transaction.sender)
-        .bind(&transaction.receiver)
-        .bind(transaction.amount)
-        .bind(transaction.nonce)
-        .bind(transaction.timestamp as i64)
-        .bind(&transaction.signature)
-        .bind(&transaction.quantum_proof.prime_hash)
-        .bind(transaction.quantum_proof.resistance_score)
-        .bind(transaction.quantum_proof.proof_timestamp as i64)
-        .bind(&transaction.metadata)
-        .execute(&mut *tx)
+        .execute(&self.pool().await)
         .await?;
 
-        // Store parent relationships
-        for parent_id in &transaction.parents {
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_event_journal_kind ON event_journal(kind, id)")
+            .execute(&self.pool().await)
+            .await?;
+
+        // Databases created before parents were kept on the transaction row
+        // get the column, filled in from the parents table
+        let has_parents = sqlx::query("SELECT COUNT(*) FROM pragma_table_info('transactions') WHERE name = 'parents'")
+            .fetch_one(&self.pool().await)
+            .await?
+            .get::<i64, _>(0) > 0;
+        if !has_parents {
+            sqlx::query("ALTER TABLE transactions ADD COLUMN parents TEXT")
+                .execute(&self.pool().await)
+                .await?;
             sqlx::query(
-                "INSERT OR REPLACE INTO transaction_parents (transaction_id, parent_id) VALUES (?, ?)"
+                r#"
+                UPDATE transactions SET parents = (
+                    SELECT json_group_array(parent_id) FROM (
+                        SELECT parent_id FROM transaction_parents
+                        WHERE transaction_id = transactions.id ORDER BY rowid
+                    )
+                )
+                "#
             )
-            .bind(transaction.id.as_string())
-            .bind(parent_id.as_string())
-            .execute(&mut *tx)
+            .execute(&self.pool().await)
             .await?;
         }
 
-        tx.commit().await?;
-        log::debug!("Stored transaction: {}", transaction.id);
-        Ok(())
-    }
+        // Rows from before the signature scheme was recorded are hybrid-signed
+        let has_signature_scheme = sqlx::query("SELECT COUNT(*) FROM pragma_table_info('transactions') WHERE name = 'signature_scheme'")
+            .fetch_one(&self.pool().await)
+            .await?
+            .get::<i64, _>(0) > 0;
+        if !has_signature_scheme {
+            sqlx::query("ALTER TABLE transactions ADD COLUMN signature_scheme TEXT")
+                .execute(&self.pool().await)
+                .await?;
+        }
 
-    /// Store a DAG node in the database
-    pub async fn store_dag_node(&self, node: &DAGNode) -> Result<(), BlockchainError> {
-        let children_json = serde_json::to_string(&node.children.iter().map(|id| id.as_string()).collect::<Vec<String>>())?;
-        
+        // Transactions from before fees paid none
+        let has_fee = sqlx::query("SELECT COUNT(*) FROM pragma_table_info('transactions') WHERE name = 'fee'")
+            .fetch_one(&self.pool().await)
+            .await?
+            .get::<i64, _>(0) > 0;
+        if !has_fee {
+            sqlx::query("ALTER TABLE transactions ADD COLUMN fee TEXT NOT NULL DEFAULT '0'")
+                .execute(&self.pool().await)
+                .await?;
+        }
+
+        // Amounts were stored as integers before they widened past i64
+        for (table, column) in AMOUNT_COLUMNS {
+            self.widen_amount_column(table, column).await?;
+        }
+
+        // Rows from before checksums have none and are not verified
+        for table in [integrity::TRANSACTIONS_TABLE, integrity::DAG_NODES_TABLE] {
+            let has_checksum = sqlx::query(&format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = 'checksum'", table))
+                .fetch_one(&self.pool().await)
+                .await?
+                .get::<i64, _>(0) > 0;
+            if !has_checksum {
+                sqlx::query(&format!("ALTER TABLE {} ADD COLUMN checksum TEXT", table))
+                    .execute(&self.pool().await)
+                    .await?;
+            }
+        }
+
+        // Rows that failed checksum verification
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO dag_nodes 
-            (transaction_id, children, weight, confidence, status, quantum_score)
-            VALUES (?, ?, ?, ?, ?, ?)
+            CREATE TABLE IF NOT EXISTS corrupted_rows (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                row_id TEXT NOT NULL,
+                stored_checksum TEXT NOT NULL,
+                computed_checksum TEXT NOT NULL,
+                contents TEXT NOT NULL,
+                detected_at INTEGER NOT NULL,
+                resolution TEXT NOT NULL
+            )
             "#
         )
-        .bind(node.transaction.id.as_string())
-        .bind(children_json)
-        .bind(node.weight)
-        .bind(node.confidence)
-        .bind(format!("{:?}", node.status))
-        .bind(node.quantum_score)
-        .execute(&self.pool)
+        .execute(&self.pool().await)
         .await?;
 
+        // Create indexes for better performance
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_transactions_timestamp ON transactions(timestamp)")
+            .execute(&self.pool().await)
+            .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_transactions_sender ON transactions(sender, timestamp)")
+            .execute(&self.pool().await)
+            .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_transactions_receiver ON transactions(receiver, timestamp)")
+            .execute(&self.pool().await)
+            .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_dag_nodes_status ON dag_nodes(status)")
+            .execute(&self.pool().await)
+            .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_transaction_parents_parent ON transaction_parents(parent_id)")
+            .execute(&self.pool().await)
+            .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_transactions_archive_timestamp ON transactions_archive(timestamp)")
+            .execute(&self.pool().await)
+            .await?;
+
+        // Changes since the latest backup, for incremental backups
+        self.init_change_log().await?;
+
+        log::debug!("Database schema initialized");
+        Ok(())
+    }
+
+    /// Convert an integer amount column to text, keeping its values
+    ///
+    /// SQLite cannot change a column's type, so the values are copied into
+    /// a new text column that then takes the old one's place.
+    async fn widen_amount_column(&self, table: &str, column: &str) -> Result<(), BlockchainError> {
+        let declared: Option<String> = sqlx::query_scalar(&format!("SELECT type FROM pragma_table_info('{}') WHERE name = ?", table))
+            .bind(column)
+            .fetch_optional(&self.pool().await)
+            .await?;
+        if !declared.is_some_and(|declared| declared.eq_ignore_ascii_case("INTEGER")) {
+            return Ok(());
+        }
+
+        let widened = format!("{}_text", column);
+        let mut tx = self.pool().await.begin().await?;
+        for statement in [
+            format!("ALTER TABLE {} ADD COLUMN {} TEXT NOT NULL DEFAULT '0'", table, widened),
+            format!("UPDATE {} SET {} = CAST({} AS TEXT)", table, widened, column),
+            format!("ALTER TABLE {} DROP COLUMN {}", table, column),
+            format!("ALTER TABLE {} RENAME COLUMN {} TO {}", table, widened, column),
+        ] {
+            sqlx::query(&statement).execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        log::info!("🔢 Widened {}.{} to hold amounts beyond 64 bits", table, column);
+        Ok(())
+    }
+
+    /// Store a transaction in the database
+    pub async fn store_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
+        let _timer = self.metrics.time("store_transaction");
+        if let Some(queue) = &self.queue {
+            return queue.store_transaction(transaction).await;
+        }
+        let mut batch = WriteBatch::new();
+        batch.put_transaction(transaction);
+        self.commit_batch(batch).await?;
+        log::debug!("Stored transaction: {}", transaction.id);
+        Ok(())
+    }
+
+    /// Store a DAG node in the database
+    pub async fn store_dag_node(&self, node: &DAGNode) -> Result<(), BlockchainError> {
+        let _timer = self.metrics.time("store_dag_node");
+        if let Some(queue) = &self.queue {
+            return queue.store_dag_node(node).await;
+        }
+        let mut batch = WriteBatch::new();
+        batch.put_dag_node(node);
+        self.commit_batch(batch).await?;
         log::debug!("Stored DAG node: {}", node.transaction.id);
         Ok(())
     }
 
+    /// Store the transactions and DAG nodes of a bundle, all or none
+    pub async fn store_bundle(&self, nodes: &[DAGNode]) -> Result<(), BlockchainError> {
+        let _timer = self.metrics.time("store_bundle");
+        if let Some(queue) = &self.queue {
+            return queue.store_bundle(nodes).await;
+        }
+        let mut batch = WriteBatch::new();
+        for node in nodes {
+            batch.put_transaction(&node.transaction).put_dag_node(node);
+        }
+        self.commit_batch(batch).await?;
+        log::debug!("Stored bundle of {} transaction(s)", nodes.len());
+        Ok(())
+    }
+
+    /// Apply `batch` to the storage backend, all or none
+    ///
+    /// Queued writes are committed first, so `batch` applies after them.
+    pub async fn write_batch(&self, batch: WriteBatch) -> Result<(), BlockchainError> {
+        self.commit_queued().await?;
+        self.commit_batch(batch).await
+    }
+
+    /// Write `batch` to the backend, recording its size and commit time
+    async fn commit_batch(&self, batch: WriteBatch) -> Result<(), BlockchainError> {
+        let (changes, start) = (batch.len(), std::time::Instant::now());
+        self.backend.write(batch).await?;
+        self.metrics.record_commit(changes, start.elapsed());
+        Ok(())
+    }
+
+    /// Latency, commit and queue metrics of this database
+    pub fn metrics(&self) -> &StorageMetrics {
+        &self.metrics
+    }
+
+    /// Commit queued writes and flush the storage backend to disk
+    pub async fn flush(&self) -> Result<(), BlockchainError> {
+        self.commit_queued().await?;
+        self.backend.flush().await
+    }
+
+    /// Commit queued writes, before reads that cannot see them
+    pub(crate) async fn commit_queued(&self) -> Result<(), BlockchainError> {
+        match &self.queue {
+            Some(queue) => queue.commit().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Which backend holds transactions and DAG nodes
+    pub fn backend_kind(&self) -> StorageBackendKind {
+        self.backend.kind()
+    }
+
     /// Retrieve a transaction by ID
     pub async fn get_transaction(&self, tx_id: &TransactionId) -> Result<Option<Transaction>, BlockchainError> {
-        let row = sqlx::query(
-            "SELECT id, sender, receiver, amount, nonce, timestamp, signature, prime_hash, resistance_score, proof_timestamp, metadata FROM transactions WHERE id = ?"
-        )
-        .bind(tx_id.as_string())
-        .fetch_optional(&self.pool)
-        .await?;
-
-        match row {
-            Some(row) => {
-                let parents = self.get_transaction_parents(tx_id).await?;
-                let transaction = Self::row_to_transaction(row, parents)?;
-                Ok(Some(transaction))
-            }
+        let _timer = self.metrics.time("get_transaction");
+        if let Some(transaction) = self.queue.as_ref().and_then(|queue| queue.transaction(tx_id)) {
+            return Ok(Some(transaction));
+        }
+        match self.backend.get_transaction(tx_id).await? {
+            Some(stored) => self.verified_transaction(stored.transaction, stored.checksum).await,
+            None if self.is_pruned(tx_id).await? => Err(BlockchainError::Pruned(tx_id.clone())),
             None => Ok(None),
         }
     }
 
     /// Retrieve a DAG node by ID
     pub async fn get_dag_node(&self, tx_id: &TransactionId) -> Result<Option<DAGNode>, BlockchainError> {
-        let row = sqlx::query(
-            "SELECT transaction_id, children, weight, confidence, status, quantum_score FROM dag_nodes WHERE transaction_id = ?"
-        )
-        .bind(tx_id.as_string())
-        .fetch_optional(&self.pool)
-        .await?;
+        let _timer = self.metrics.time("get_dag_node");
+        let queued = self.queue.as_ref().and_then(|queue| queue.dag_node(tx_id));
+        let status = match queued {
+            Some(QueuedNode::Node(node)) => return Ok(Some(node)),
+            Some(QueuedNode::Status(status, confidence)) => Some((status, confidence)),
+            None => None,
+        };
 
-        match row {
-            Some(row) => {
+        match self.backend.get_dag_node(tx_id).await? {
+            Some(stored) => {
                 let transaction = self.get_transaction(tx_id).await?
                     .ok_or_else(|| BlockchainError::Other("Transaction not found for DAG node".to_string()))?;
-                let node = Self::row_to_dag_node(row, transaction)?;
+                let checksum = stored.checksum.clone();
+                let mut node = self.verified_dag_node(stored.into_node(transaction), checksum).await?;
+                if let Some((status, confidence)) = status {
+                    node.status = status;
+                    node.confidence = confidence;
+                }
                 Ok(Some(node))
             }
             None => Ok(None),
@@ -264,133 +807,104 @@ impl DatabaseManager {
 
     /// Get all transactions with optional filtering
     pub async fn get_transactions(&self, limit: Option<usize>, offset: Option<usize>, status: Option<&str>) -> Result<Vec<Transaction>, BlockchainError> {
-        let mut query = String::from(
-            "SELECT t.id, t.sender, t.receiver, t.amount, t.nonce, t.timestamp, t.signature, t.prime_hash, t.resistance_score, t.proof_timestamp, t.metadata 
-             FROM transactions t"
-        );
-
-        if status.is_some() {
-            query.push_str(" JOIN dag_nodes d ON t.id = d.transaction_id WHERE d.status = ?");
-        }
-
-        query.push_str(" ORDER BY t.timestamp DESC");
-
-        if let Some(limit) = limit {
-            query.push_str(&format!(" LIMIT {}", limit));
-        }
+        let _timer = self.metrics.time("get_transactions");
+        let status = match status {
+            Some(status) => match parse_node_status(status) {
+                Ok(status) => Some(status),
+                // No node has a status that does not exist
+                Err(_) => return Ok(Vec::new()),
+            },
+            None => None,
+        };
+        self.commit_queued().await?;
+        let stored = self.backend.recent_transactions(status.as_ref(), limit, offset.unwrap_or(0)).await?;
+        self.verified_transactions(stored).await
+    }
 
-        if let Some(offset) = offset {
-            query.push_str(&format!(" OFFSET {}", offset));
-        }
+    /// Transactions sent or received by `address`, newest first
+    pub async fn get_transactions_by_address(&self, address: &[u8], limit: usize, offset: usize) -> Result<Vec<Transaction>, BlockchainError> {
+        self.commit_queued().await?;
+        let stored = self.backend.transactions_by_address(address, limit, offset).await?;
+        self.verified_transactions(stored).await
+    }
 
-        let mut query_builder = sqlx::query(&query);
-        
-        if let Some(status) = status {
-            query_builder = query_builder.bind(status);
+    /// Oldest transaction without parents
+    pub async fn get_genesis_transaction(&self) -> Result<Option<Transaction>, BlockchainError> {
+        self.commit_queued().await?;
+        match self.backend.genesis_transaction().await? {
+            Some(stored) => self.verified_transaction(stored.transaction, stored.checksum).await,
+            None => Ok(None),
         }
+    }
 
-        let rows = query_builder.fetch_all(&self.pool).await?;
-        
-        let mut transactions = Vec::new();
-        for row in rows {
-            let tx_id = TransactionId::from_bytes(&hex::decode(row.get::<_, String>(0))?)?;
-            let parents = self.get_transaction_parents(&tx_id).await?;
-            let transaction = Self::row_to_transaction(row, parents)?;
-            transactions.push(transaction);
+    /// Verify stored transactions, dropping those lost to corruption
+    async fn verified_transactions(&self, stored: Vec<StoredTransaction>) -> Result<Vec<Transaction>, BlockchainError> {
+        let mut transactions = Vec::with_capacity(stored.len());
+        for stored in stored {
+            if let Some(transaction) = self.verified_transaction(stored.transaction, stored.checksum).await? {
+                transactions.push(transaction);
+            }
         }
-
         Ok(transactions)
     }
 
     /// Get all DAG tips (unconfirmed transactions)
     pub async fn get_dag_tips(&self) -> Result<Vec<DAGNode>, BlockchainError> {
-        let rows = sqlx::query(
-            "SELECT d.transaction_id, d.children, d.weight, d.confidence, d.status, d.quantum_score 
-             FROM dag_nodes d 
-             WHERE d.status = 'Pending' 
-             ORDER BY d.confidence DESC"
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        self.commit_queued().await?;
+        let mut pending = self.backend.dag_nodes_with_status(&NodeStatus::Pending).await?;
+        pending.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
 
         let mut tips = Vec::new();
-        for row in rows {
-            let tx_id_str = row.get::<_, String>(0);
-            let tx_id = TransactionId::from_bytes(&hex::decode(&tx_id_str)?)?;
-            let transaction = self.get_transaction(&tx_id).await?
-                .ok_or_else(|| BlockchainError::Other("Transaction not found for DAG tip".to_string()))?;
-            let node = Self::row_to_dag_node(row, transaction)?;
-            tips.push(node);
+        for stored in pending {
+            // The transaction may have been quarantined without a replacement
+            let Some(transaction) = self.get_transaction(&stored.tx_id).await? else {
+                log::warn!("⚠️ Skipping DAG tip {} without a readable transaction", stored.tx_id);
+                continue;
+            };
+            let checksum = stored.checksum.clone();
+            let node = self.verified_dag_node(stored.into_node(transaction), checksum).await?;
+            // A re-derived node may turn out not to be a tip
+            if node.status == NodeStatus::Pending {
+                tips.push(node);
+            }
         }
 
         Ok(tips)
     }
 
-    /// Get transaction parents
-    async fn get_transaction_parents(&self, tx_id: &TransactionId) -> Result<Vec<TransactionId>, BlockchainError> {
-        let rows = sqlx::query(
-            "SELECT parent_id FROM transaction_parents WHERE transaction_id = ? ORDER BY parent_id"
-        )
-        .bind(tx_id.as_string())
-        .fetch_all(&self.pool)
-        .await?;
-
-        let mut parents = Vec::new();
-        for row in rows {
-            let parent_id_str: String = row.get(0);
-            let parent_id = TransactionId::from_bytes(&hex::decode(&parent_id_str)?)?;
-            parents.push(parent_id);
-        }
-
-        Ok(parents)
-    }
-
     /// Update DAG node status
     pub async fn update_node_status(&self, tx_id: &TransactionId, status: NodeStatus, confidence: f64) -> Result<(), BlockchainError> {
-        sqlx::query(
-            "UPDATE dag_nodes SET status = ?, confidence = ? WHERE transaction_id = ?"
-        )
-        .bind(format!("{:?}", status))
-        .bind(confidence)
-        .bind(tx_id.as_string())
-        .execute(&self.pool)
-        .await?;
+        let _timer = self.metrics.time("update_node_status");
+        if let Some(queue) = &self.queue {
+            return queue.set_status(tx_id, status, confidence).await;
+        }
+        let mut batch = WriteBatch::new();
+        batch.set_status(tx_id, status, confidence);
+        self.commit_batch(batch).await
+    }
 
-        Ok(())
+    /// Delete transactions with their DAG nodes and parent links, returning how many were stored
+    ///
+    /// Links from remaining transactions to deleted parents are kept.
+    pub async fn delete_transactions(&self, ids: &[TransactionId]) -> Result<u64, BlockchainError> {
+        self.commit_queued().await?;
+        self.backend.delete(ids).await
     }
 
     /// Get transaction count
     pub async fn get_transaction_count(&self) -> Result<u64, BlockchainError> {
-        let count = sqlx::query("SELECT COUNT(*) FROM transactions")
-            .fetch_one(&self.pool)
-            .await?;
-        
-        Ok(count.get::<_, i64>(0) as u64)
+        self.commit_queued().await?;
+        self.backend.transaction_count().await
     }
 
     /// Get database statistics
     pub async fn get_stats(&self) -> Result<DatabaseStats, BlockchainError> {
-        let total_tx = sqlx::query("SELECT COUNT(*) FROM transactions")
-            .fetch_one(&self.pool)
-            .await?;
-        
-        let pending_nodes = sqlx::query("SELECT COUNT(*) FROM dag_nodes WHERE status = 'Pending'")
-            .fetch_one(&self.pool)
-            .await?;
-        
-        let confirmed_nodes = sqlx::query("SELECT COUNT(*) FROM dag_nodes WHERE status = 'Confirmed'")
-            .fetch_one(&self.pool)
-            .await?;
-        
-        let finalized_nodes = sqlx::query("SELECT COUNT(*) FROM dag_nodes WHERE status = 'Finalized'")
-            .fetch_one(&self.pool)
-            .await?;
-
+        self.commit_queued().await?;
         Ok(DatabaseStats {
-            total_transactions: total_tx.get::<_, i64>(0) as u64,
-            pending_nodes: pending_nodes.get::<_, i64>(0) as u64,
-            confirmed_nodes: confirmed_nodes.get::<_, i64>(0) as u64,
-            finalized_nodes: finalized_nodes.get::<_, i64>(0) as u64,
+            total_transactions: self.backend.transaction_count().await?,
+            pending_nodes: self.backend.count_dag_nodes(&NodeStatus::Pending).await?,
+            confirmed_nodes: self.backend.count_dag_nodes(&NodeStatus::Confirmed).await?,
+            finalized_nodes: self.backend.count_dag_nodes(&NodeStatus::Finalized).await?,
         })
     }
 
@@ -398,7 +912,7 @@ impl DatabaseManager {
     pub async fn get_storage_size(&self) -> Result<u64, BlockchainError> {
         // Get database file size
         let row = sqlx::query("SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()")
-            .fetch_one(&self.pool)
+            .fetch_one(&self.pool().await)
             .await?;
         
         let db_size = row.get::<_, i64>(0) as u64;
@@ -407,22 +921,25 @@ impl DatabaseManager {
         let index_size = sqlx::query(
             "SELECT SUM(pgsize) FROM dbstat WHERE name LIKE 'idx_%'"
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&self.pool().await)
         .await?;
         
         let index_size = index_size.get::<_, Option<i64>>(0).unwrap_or(0) as u64;
+        let shard_size: u64 = self.shard_stats().await?.iter().map(|shard| shard.size).sum();
         
-        Ok(db_size + index_size)
+        Ok(db_size + index_size + shard_size)
     }
 
     /// Close database connections
     pub async fn close(&self) -> Result<(), BlockchainError> {
-        self.pool.close().await;
+        self.flush().await?;
+        self.pool().await.close().await;
         Ok(())
     }
 
     /// Create a backup of the database
     pub async fn create_backup(&self, backup_path: &str) -> Result<BackupInfo, BlockchainError> {
+        self.commit_queued().await?;
         let timestamp = Utc::now();
         let backup_path = if backup_path.ends_with(".db") {
             backup_path.to_string()
@@ -438,8 +955,12 @@ impl DatabaseManager {
         // Get database path from pool
         let database_path = self.get_database_path().await?;
 
-        // Copy database file
+        // Copy database file, which holds every change logged so far
+        let last_change = self.last_change().await?;
         tokio::fs::copy(&database_path, &backup_path).await?;
+        if let Some(shards) = &self.shards {
+            shards.backup_to(&backup_path).await?;
+        }
 
         // Create backup metadata
         let stats = self.get_stats().await?;
@@ -458,6 +979,10 @@ impl DatabaseManager {
                 meta.insert("created_by".to_string(), "quantum-dag-blockchain".to_string());
                 meta.insert("version".to_string(), env!("CARGO_PKG_VERSION").to_string());
                 meta.insert("backup_reason".to_string(), "manual".to_string());
+                meta.insert(incremental::LAST_CHANGE_KEY.to_string(), last_change.to_string());
+                if let Some(shards) = &self.shards {
+                    meta.insert("shards".to_string(), shards.shard_count().to_string());
+                }
                 meta
             },
         };
@@ -466,6 +991,10 @@ impl DatabaseManager {
         let metadata_path = format!("{}.meta", backup_path);
         let metadata_json = serde_json::to_string_pretty(&backup_info)?;
         tokio::fs::write(&metadata_path, metadata_json).await?;
+        self.record_backup_checkpoint(&backup_path, &BackupType::Full, last_change).await?;
+        if self.has_backup_destination() {
+            self.upload_backup(&backup_info).await?;
+        }
 
         log::info!("📦 Database backup created: {}", backup_path);
         Ok(backup_info)
@@ -511,29 +1040,61 @@ impl DatabaseManager {
             return Err(BlockchainError::Other("Backup integrity check failed".to_string()));
         }
 
-        // Create backup of current database before restore
+        // Incremental and differential backups are replayed over their base
+        let restore_source = match backup_info.backup_type {
+            BackupType::Full => backup_path.clone(),
+            BackupType::Incremental | BackupType::Differential => self.replay_backup_chain(&backup_info).await?,
+        };
+
+        // Callers wait for the restored database from here on
+        self.commit_queued().await?;
         let current_db_path = self.get_database_path().await?;
+        let mut pool = self.pool.lock().await;
+        pool.close().await;
+
+        // Closing the last connection checkpoints the WAL, so the file is complete
+        let mut pre_restore_backup = None;
+        let mut restored = Ok(());
         if tokio::fs::metadata(&current_db_path).await.is_ok() {
-            let timestamp = Utc::now().timestamp();
-            let pre_restore_backup = format!("{}.pre_restore_{}", current_db_path, timestamp);
-            tokio::fs::copy(&current_db_path, &pre_restore_backup).await?;
-            log::info!("📦 Created pre-restore backup: {}", pre_restore_backup);
+            let path = format!("{}.pre_restore_{}", current_db_path, Utc::now().timestamp());
+            restored = tokio::fs::copy(&current_db_path, &path).await.map(|_| ());
+            if restored.is_ok() {
+                log::info!("📦 Created pre-restore backup: {}", path);
+                pre_restore_backup = Some(path);
+            }
+        }
+        if restored.is_ok() {
+            restored = tokio::fs::copy(&restore_source, &current_db_path).await.map(|_| ());
         }
 
-        // Close database connections
-        self.pool.close().await;
-
-        // Restore database from backup
-        tokio::fs::copy(&backup_path, &current_db_path).await?;
-
-        // Reopen database
-        let new_pool = SqlitePool::connect_with(
-            SqliteConnectOptions::from_str(&format!("sqlite://{}", current_db_path))?
-                .create_if_missing(true)
-        ).await?;
+        // Reconnect even when the copy failed, so callers never find the pool closed
+        *pool = self.connect(&current_db_path).await?;
+        drop(pool);
+        restored?;
+        if restore_source != backup_path {
+            tokio::fs::remove_file(&restore_source).await?;
+        }
 
-        // Update pool reference (this is simplified - in real implementation you'd need proper pool management)
-        log::warn!("Database pool updated - this is a simplified implementation");
+        // The backup may predate schema changes
+        self.init_database().await?;
+        self.init_migrations(true).await?;
+
+        // Shards are copied with full backups only
+        let mut warnings = Vec::new();
+        if let Some(shards) = &self.shards {
+            let base = match backup_info.backup_type {
+                BackupType::Full => Some(backup_path.as_str()),
+                BackupType::Incremental | BackupType::Differential => None,
+            };
+            let restored = match base {
+                Some(base) => shards.restore_from(base).await?,
+                None => false,
+            };
+            if !restored {
+                log::warn!("⚠️ Backup {} holds no copy of the storage shards; they were left as they are", backup_path);
+                warnings.push("Storage shards were not restored".to_string());
+            }
+        }
 
         log::info!("✅ Database restored from backup: {}", backup_path);
 
@@ -541,11 +1102,17 @@ impl DatabaseManager {
             success: true,
             backup_info,
             restore_timestamp: Utc::now().timestamp(),
-            pre_restore_backup: None,
-            warnings: vec!["Database pool management simplified in this implementation".to_string()],
+            pre_restore_backup,
+            warnings,
         })
     }
 
+    /// Pool over the database file at `path`, opened with the node's key
+    async fn connect(&self, path: &str) -> Result<SqlitePool, BlockchainError> {
+        let options = self.pragmas.apply(SqliteConnectOptions::from_str(&format!("sqlite://{}", path))?.create_if_missing(true));
+        Ok(SqlitePool::connect_with(encryption::with_key(options, self.encryption_key.as_deref())?).await?)
+    }
+
     /// List available backups
     pub async fn list_backups(&self, backup_dir: &str) -> Result<Vec<BackupInfo>, BlockchainError> {
         let mut backups = Vec::new();
@@ -686,109 +1253,13 @@ impl DatabaseManager {
         })
     }
 
-    /// Export database to SQL format
-    pub async fn export_sql(&self, export_path: &str) -> Result<ExportResult, BlockchainError> {
-        let export_path = if export_path.ends_with(".sql") {
-            export_path.to_string()
-        } else {
-            format!("{}.sql", export_path)
-        };
-
-        // Ensure export directory exists
-        if let Some(parent) = Path::new(&export_path).parent() {
-            tokio::fs::create_dir_all(parent).await?;
-        }
-
-        // Get database path
-        let database_path = self.get_database_path().await?;
-
-        // Use sqlite3 command line tool to export
-        let output = std::process::Command::new("sqlite3")
-            .arg(&database_path)
-            .arg(".output")
-            .arg(&export_path)
-            .arg(".dump")
-            .output();
-
-        match output {
-            Ok(output) => {
-                if output.status.success() {
-                    let file_size = self.get_file_size(&export_path).await?;
-                    log::info!("📤 Database exported to SQL: {}", export_path);
-                    
-                    Ok(ExportResult {
-                        success: true,
-                        export_path,
-                        export_format: ExportFormat::SQL,
-                        file_size,
-                        export_timestamp: Utc::now().timestamp(),
-                        warnings: Vec::new(),
-                    })
-                } else {
-                    Err(BlockchainError::Other(format!("SQLite export failed: {}", String::from_utf8_lossy(&output.stderr))))
-                }
-            }
-            Err(e) => {
-                Err(BlockchainError::Other(format!("Failed to execute sqlite3: {}", e)))
-            }
-        }
-    }
-
-    /// Import database from SQL format
-    pub async fn import_sql(&self, sql_path: &str) -> Result<ImportResult, BlockchainError> {
-        if !tokio::fs::metadata(sql_path).await.is_ok() {
-            return Err(BlockchainError::Other(format!("SQL file not found: {}", sql_path)));
-        }
-
-        // Get database path
-        let database_path = self.get_database_path().await?;
-
-        // Create backup before import
-        let timestamp = Utc::now().timestamp();
-        let pre_import_backup = format!("{}.pre_import_{}", database_path, timestamp);
-        if tokio::fs::metadata(&database_path).await.is_ok() {
-            tokio::fs::copy(&database_path, &pre_import_backup).await?;
-            log::info!("📦 Created pre-import backup: {}", pre_import_backup);
-        }
-
-        // Close database connections
-        self.pool.close().await;
-
-        // Use sqlite3 command line tool to import
-        let output = std::process::Command::new("sqlite3")
-            .arg(&database_path)
-            .arg(&format!(".read {}", sql_path))
-            .output();
-
-        match output {
-            Ok(output) => {
-                if output.status.success() {
-                    log::info!("📥 Database imported from SQL: {}", sql_path);
-                    
-                    Ok(ImportResult {
-                        success: true,
-                        import_path: sql_path.to_string(),
-                        import_format: ImportFormat::SQL,
-                        pre_import_backup: Some(pre_import_backup),
-                        import_timestamp: Utc::now().timestamp(),
-                        warnings: Vec::new(),
-                    })
-                } else {
-                    Err(BlockchainError::Other(format!("SQLite import failed: {}", String::from_utf8_lossy(&output.stderr))))
-                }
-            }
-            Err(e) => {
-                Err(BlockchainError::Other(format!("Failed to execute sqlite3: {}", e)))
-            }
-        }
-    }
-
     // Helper methods
 
     async fn get_database_path(&self) -> Result<String, BlockchainError> {
-        // This is a simplified implementation
-        // In a real implementation, you'd extract the path from the pool configuration
-        Ok("./blockchain.db".to_string())
+        if self.path == ":memory:" {
+            return Err(BlockchainError::Other("An in-memory database has no file to back up".to_string()));
+        }
+        Ok(self.path.clone())
     }
 
     async fn get_file_size(&self, file_path: &str) -> Result<u64, BlockchainError> {
@@ -815,7 +1286,7 @@ impl DatabaseManager {
 }
 
 /// Backup type
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BackupType {
     Full,
     Incremental,
@@ -825,16 +1296,22 @@ pub enum BackupType {
 /// Export format
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExportFormat {
+    /// A dump of the schema and rows, as `sqlite3 .dump` writes
     SQL,
+    /// JSON lines, one object per row
     JSON,
+    /// A directory with one CSV file per table
     CSV,
 }
 
 /// Import format
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ImportFormat {
+    /// A dump replacing the whole database
     SQL,
+    /// JSON lines replacing the rows of the tables they hold
     JSON,
+    /// A directory of CSV files replacing the rows of their tables
     CSV,
 }
 
@@ -925,12 +1402,32 @@ mod tests {
         let config = DatabaseConfig {
             path: db_path.to_string_lossy().to_string(),
             max_connections: 5,
+            ..DatabaseConfig::default()
         };
 
         let db_manager = DatabaseManager::new(config).await;
         assert!(db_manager.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_integer_amount_columns_are_widened() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let legacy = SqlitePool::connect_with(SqliteConnectOptions::new().filename(&db_path).create_if_missing(true)).await.unwrap();
+        sqlx::query("CREATE TABLE account_balances (address TEXT PRIMARY KEY, balance INTEGER NOT NULL, updated_at INTEGER NOT NULL)")
+            .execute(&legacy).await.unwrap();
+        sqlx::query("INSERT INTO account_balances (address, balance, updated_at) VALUES ('aa', 9000000000000000000, 0)")
+            .execute(&legacy).await.unwrap();
+        legacy.close().await;
+
+        let db_manager = DatabaseManager::new(DatabaseConfig {
+            path: db_path.to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        assert_eq!(db_manager.get_balance("aa").await.unwrap(), 9_000_000_000_000_000_000);
+        assert_eq!(db_manager.credit_account("aa", 10u128.pow(19)).await.unwrap(), 19_000_000_000_000_000_000);
+    }
+
     #[tokio::test]
     async fn test_transaction_storage() {
         let temp_dir = TempDir::new().unwrap();
@@ -939,6 +1436,7 @@ mod tests {
         let config = DatabaseConfig {
             path: db_path.to_string_lossy().to_string(),
             max_connections: 5,
+            ..DatabaseConfig::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
@@ -948,10 +1446,12 @@ mod tests {
             sender: vec![1u8; 32],
             receiver: vec![2u8; 32],
             amount: 100,
+            fee: 0,
             nonce: 1,
             timestamp: Utc::now().timestamp() as u64,
             parents: vec![],
             signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
             quantum_proof: QuantumProof {
                 prime_hash: vec![1u8; 32],
                 resistance_score: 80,
@@ -0,0 +1,67 @@
+//! Connection pool shared by `DatabaseManager` and the SQLite backend
+//!
+//! Restoring a backup replaces the database file under the node, which
+//! needs every connection closed first. Callers take the current pool from
+//! `SharedPool` for each query instead of keeping their own, so a restore can
+//! close it, swap the file and install a pool over the new one. Callers
+//! arriving meanwhile wait for the new pool rather than failing on the
+//! closed one.
+
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::{RwLock, RwLockWriteGuard};
+
+/// The node's connection pool, replaced when the database file is swapped
+#[derive(Clone)]
+pub struct SharedPool(Arc<RwLock<SqlitePool>>);
+
+impl SharedPool {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self(Arc::new(RwLock::new(pool)))
+    }
+
+    /// Pool to run the next query on; waits while a swap is in progress
+    pub async fn get(&self) -> SqlitePool {
+        self.0.read().await.clone()
+    }
+
+    /// Hold off other callers until the guard, holding the pool to replace, is dropped
+    pub(crate) async fn lock(&self) -> RwLockWriteGuard<'_, SqlitePool> {
+        self.0.write().await
+    }
+}
+
+impl From<SqlitePool> for SharedPool {
+    fn from(pool: SqlitePool) -> Self {
+        Self::new(pool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// In-memory database holding `value`, on the pool's only connection
+    async fn pool_with(value: i64) -> SqlitePool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE marker (value INTEGER)").execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO marker VALUES (?)").bind(value).execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_callers_wait_for_the_new_pool() {
+        let shared = SharedPool::new(pool_with(1).await);
+        let mut guard = shared.lock().await;
+        guard.close().await;
+
+        let reader = shared.clone();
+        let read = tokio::spawn(async move {
+            sqlx::query_scalar::<_, i64>("SELECT value FROM marker").fetch_one(&reader.get().await).await.unwrap()
+        });
+        *guard = pool_with(2).await;
+        drop(guard);
+        assert_eq!(read.await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,162 @@
+//! SQLite connection pragmas
+//!
+//! Applied to every connection the node's pool opens. The defaults favour
+//! write throughput: WAL, so readers never block the writer, with
+//! `synchronous = NORMAL`, which in WAL mode can lose the last commits on
+//! power loss but never corrupts the file, plus a larger page cache and a
+//! memory-mapped window over the file for reads.
+//!
+//! `page_size` only applies to a database created with it; an existing file
+//! keeps its page size until it is vacuumed outside WAL mode.
+
+use crate::BlockchainError;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqliteSynchronous};
+use std::time::Duration;
+
+/// How SQLite keeps the changes of a transaction until it commits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    /// Write-ahead log; commits don't block readers
+    #[default]
+    Wal,
+    Off,
+}
+
+impl From<JournalMode> for SqliteJournalMode {
+    fn from(mode: JournalMode) -> Self {
+        match mode {
+            JournalMode::Delete => SqliteJournalMode::Delete,
+            JournalMode::Truncate => SqliteJournalMode::Truncate,
+            JournalMode::Persist => SqliteJournalMode::Persist,
+            JournalMode::Memory => SqliteJournalMode::Memory,
+            JournalMode::Wal => SqliteJournalMode::Wal,
+            JournalMode::Off => SqliteJournalMode::Off,
+        }
+    }
+}
+
+/// How often SQLite waits for writes to reach the disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SynchronousLevel {
+    Off,
+    /// At checkpoints only, in WAL mode
+    #[default]
+    Normal,
+    /// At every commit
+    Full,
+    Extra,
+}
+
+impl From<SynchronousLevel> for SqliteSynchronous {
+    fn from(level: SynchronousLevel) -> Self {
+        match level {
+            SynchronousLevel::Off => SqliteSynchronous::Off,
+            SynchronousLevel::Normal => SqliteSynchronous::Normal,
+            SynchronousLevel::Full => SqliteSynchronous::Full,
+            SynchronousLevel::Extra => SqliteSynchronous::Extra,
+        }
+    }
+}
+
+/// Pragmas set on every connection to the database file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SqlitePragmas {
+    pub journal_mode: JournalMode,
+    pub synchronous: SynchronousLevel,
+    /// Bytes per page of a new database; a power of two from 512 to 65536
+    pub page_size: u32,
+    /// Bytes of the file read through a memory map; zero disables it
+    pub mmap_size: u64,
+    /// KiB of pages cached per connection
+    pub cache_size_kib: u64,
+    /// Milliseconds a connection waits for a lock before failing
+    pub busy_timeout_ms: u64,
+}
+
+impl Default for SqlitePragmas {
+    fn default() -> Self {
+        Self {
+            journal_mode: JournalMode::Wal,
+            synchronous: SynchronousLevel::Normal,
+            page_size: 4096,
+            mmap_size: 256 * 1024 * 1024,
+            cache_size_kib: 64 * 1024,
+            busy_timeout_ms: 5000,
+        }
+    }
+}
+
+impl SqlitePragmas {
+    pub fn validate(&self) -> Result<(), BlockchainError> {
+        if !self.page_size.is_power_of_two() || !(512..=65536).contains(&self.page_size) {
+            return Err(BlockchainError::Other(format!(
+                "SQLite page size must be a power of two from 512 to 65536, got {}",
+                self.page_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// `options` with these pragmas set on connect
+    pub fn apply(&self, options: SqliteConnectOptions) -> SqliteConnectOptions {
+        options
+            .page_size(self.page_size)
+            .journal_mode(self.journal_mode.into())
+            .synchronous(self.synchronous.into())
+            .busy_timeout(Duration::from_millis(self.busy_timeout_ms))
+            .pragma("mmap_size", self.mmap_size.to_string())
+            // Negative sizes are in KiB rather than pages
+            .pragma("cache_size", format!("-{}", self.cache_size_kib))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{DatabaseConfig, DatabaseManager};
+
+    #[tokio::test]
+    async fn test_pragmas_are_applied_on_connect() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pragmas = SqlitePragmas {
+            journal_mode: JournalMode::Truncate,
+            synchronous: SynchronousLevel::Full,
+            page_size: 8192,
+            mmap_size: 0,
+            ..SqlitePragmas::default()
+        };
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            pragmas,
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+
+        assert_eq!(pragma(&db, "journal_mode").await, "truncate");
+        // FULL
+        assert_eq!(pragma(&db, "synchronous").await, "2");
+        assert_eq!(pragma(&db, "page_size").await, "8192");
+        assert_eq!(pragma(&db, "cache_size").await, "-65536");
+    }
+
+    async fn pragma(db: &DatabaseManager, name: &str) -> String {
+        sqlx::query_scalar(&format!("SELECT CAST({} AS TEXT) FROM pragma_{}", name, name))
+            .fetch_one(&db.pool().await)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_page_size_must_be_a_power_of_two() {
+        assert!(SqlitePragmas::default().validate().is_ok());
+        assert!(SqlitePragmas { page_size: 3000, ..SqlitePragmas::default() }.validate().is_err());
+        assert!(SqlitePragmas { page_size: 256, ..SqlitePragmas::default() }.validate().is_err());
+    }
+}
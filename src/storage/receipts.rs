@@ -0,0 +1,232 @@
+//! Transaction receipts and contract event logs
+//!
+//! A receipt records what happened when a transaction finalized: whether its
+//! payload executed, the gas a contract used, the contract's output and
+//! events, and the finalized height. `PayloadRouter` writes the receipts of
+//! every finalized height in one batch, plain transfers included, to the
+//! `transaction_receipts` table.
+//!
+//! Each event is also kept as a row of `contract_events`, indexed by
+//! contract and by topic, the event's name, so the API can follow a
+//! contract's events without reading every receipt.
+
+use super::DatabaseManager;
+use crate::contracts::ContractEvent;
+use crate::{BlockchainError, TransactionId};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+/// Whether a finalized transaction's payload executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptStatus {
+    Success,
+    /// The payload failed; the transfer and fee still applied
+    Failed,
+}
+
+/// Outcome of a finalized transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionReceipt {
+    pub tx_id: TransactionId,
+    pub status: ReceiptStatus,
+    /// Payload kind, e.g. `transfer` or `contract_call`
+    pub kind: String,
+    /// Contract deployed or called
+    #[serde(default)]
+    pub contract_id: Option<String>,
+    pub gas_used: u64,
+    /// Hex contract output
+    pub output: String,
+    pub events: Vec<ContractEvent>,
+    pub error: Option<String>,
+    pub finalization_height: u64,
+    /// Unix time the receipt was written
+    pub finalized_at: u64,
+}
+
+/// A contract event with where it was emitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventLog {
+    pub tx_id: TransactionId,
+    /// Position of the event among those of its transaction
+    pub log_index: u32,
+    pub finalization_height: u64,
+    pub event: ContractEvent,
+}
+
+/// Which events `get_events` returns
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventFilter {
+    pub contract_id: Option<String>,
+    /// Event name
+    pub topic: Option<String>,
+    /// Finalized heights, inclusive
+    pub from_height: Option<u64>,
+    pub to_height: Option<u64>,
+    /// Defaults to `DEFAULT_EVENT_LIMIT`
+    pub limit: Option<usize>,
+}
+
+pub const DEFAULT_EVENT_LIMIT: usize = 100;
+
+impl DatabaseManager {
+    /// Store a receipt, keeping the first one written for a transaction
+    pub async fn store_receipt(&self, receipt: &TransactionReceipt) -> Result<(), BlockchainError> {
+        self.store_receipts(std::slice::from_ref(receipt)).await
+    }
+
+    /// Store receipts and their events in one transaction, keeping the first
+    /// receipt written for a transaction
+    pub async fn store_receipts(&self, receipts: &[TransactionReceipt]) -> Result<(), BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        for receipt in receipts {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO transaction_receipts (transaction_id, finalization_height, contract_id, data) VALUES (?, ?, ?, ?)"
+            )
+            .bind(receipt.tx_id.as_string())
+            .bind(receipt.finalization_height as i64)
+            .bind(&receipt.contract_id)
+            .bind(serde_json::to_string(receipt)?)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if inserted == 0 {
+                continue;
+            }
+            for (log_index, event) in receipt.events.iter().enumerate() {
+                sqlx::query(
+                    "INSERT INTO contract_events (transaction_id, log_index, contract_id, topic, finalization_height, data) VALUES (?, ?, ?, ?, ?, ?)"
+                )
+                .bind(receipt.tx_id.as_string())
+                .bind(log_index as i64)
+                .bind(event.contract_id.as_str())
+                .bind(&event.name)
+                .bind(receipt.finalization_height as i64)
+                .bind(serde_json::to_string(event)?)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Receipt of a finalized transaction
+    pub async fn get_receipt(&self, tx_id: &TransactionId) -> Result<Option<TransactionReceipt>, BlockchainError> {
+        let data: Option<String> = sqlx::query_scalar("SELECT data FROM transaction_receipts WHERE transaction_id = ?")
+            .bind(tx_id.as_string())
+            .fetch_optional(&self.pool().await)
+            .await?;
+        Ok(data.map(|data| serde_json::from_str(&data)).transpose()?)
+    }
+
+    /// Receipts of transactions that deployed or called `contract_id`, newest first
+    pub async fn get_receipts_by_contract(&self, contract_id: &str, limit: usize) -> Result<Vec<TransactionReceipt>, BlockchainError> {
+        let rows: Vec<String> = sqlx::query_scalar(
+            "SELECT data FROM transaction_receipts WHERE contract_id = ? ORDER BY finalization_height DESC, transaction_id LIMIT ?"
+        )
+        .bind(contract_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool().await)
+        .await?;
+        rows.iter().map(|data| Ok(serde_json::from_str(data)?)).collect()
+    }
+
+    /// Events matching `filter`, oldest first
+    pub async fn get_events(&self, filter: &EventFilter) -> Result<Vec<EventLog>, BlockchainError> {
+        let rows = sqlx::query(
+            "SELECT transaction_id, log_index, finalization_height, data FROM contract_events
+             WHERE (?1 IS NULL OR contract_id = ?1) AND (?2 IS NULL OR topic = ?2)
+               AND finalization_height >= ?3 AND finalization_height <= ?4
+             ORDER BY finalization_height, transaction_id, log_index LIMIT ?5"
+        )
+        .bind(&filter.contract_id)
+        .bind(&filter.topic)
+        .bind(filter.from_height.unwrap_or(0) as i64)
+        .bind(filter.to_height.map_or(i64::MAX, |height| height as i64))
+        .bind(filter.limit.unwrap_or(DEFAULT_EVENT_LIMIT) as i64)
+        .fetch_all(&self.pool().await)
+        .await?;
+        rows.iter()
+            .map(|row| Ok(EventLog {
+                tx_id: TransactionId::from_string(&row.get::<String, _>("transaction_id"))?,
+                log_index: row.get::<i64, _>("log_index") as u32,
+                finalization_height: row.get::<i64, _>("finalization_height") as u64,
+                event: serde_json::from_str(&row.get::<String, _>("data"))?,
+            }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::ContractId;
+    use crate::storage::DatabaseConfig;
+
+    fn receipt(contract_id: Option<&str>, events: &[(&str, &str)], finalization_height: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            tx_id: TransactionId::new(),
+            status: ReceiptStatus::Success,
+            kind: if contract_id.is_some() { "contract_call" } else { "transfer" }.to_string(),
+            contract_id: contract_id.map(str::to_string),
+            gas_used: 0,
+            output: String::new(),
+            events: events.iter()
+                .map(|(contract, name)| ContractEvent {
+                    contract_id: ContractId::new(contract.to_string()),
+                    name: name.to_string(),
+                    data: vec![1, 2],
+                    block_number: 0,
+                })
+                .collect(),
+            error: None,
+            finalization_height,
+            finalized_at: 1_700_000_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_receipt_is_kept() {
+        let database = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+
+        let receipt = receipt(None, &[], 12);
+        database.store_receipt(&receipt).await.unwrap();
+        database.store_receipt(&TransactionReceipt { finalization_height: 13, ..receipt.clone() }).await.unwrap();
+
+        assert_eq!(database.get_receipt(&receipt.tx_id).await.unwrap(), Some(receipt));
+        assert_eq!(database.get_receipt(&TransactionId::new()).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_receipts_and_events_by_contract_and_topic() {
+        let database = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        let receipts = vec![
+            receipt(Some("token"), &[("token", "Transfer"), ("token", "Approval")], 1),
+            receipt(Some("dex"), &[("dex", "Swap"), ("token", "Transfer")], 2),
+            receipt(None, &[], 2),
+        ];
+        database.store_receipts(&receipts).await.unwrap();
+        // Written again, e.g. by a replay, the events are not duplicated
+        database.store_receipts(&receipts[..1]).await.unwrap();
+
+        let by_contract = database.get_receipts_by_contract("token", 10).await.unwrap();
+        assert_eq!(by_contract, vec![receipts[0].clone()]);
+
+        let transfers = database.get_events(&EventFilter {
+            contract_id: Some("token".to_string()),
+            topic: Some("Transfer".to_string()),
+            ..EventFilter::default()
+        }).await.unwrap();
+        assert_eq!(
+            transfers.iter().map(|log| (log.tx_id.clone(), log.log_index)).collect::<Vec<_>>(),
+            vec![(receipts[0].tx_id.clone(), 0), (receipts[1].tx_id.clone(), 1)]
+        );
+        assert_eq!(transfers[1].event, receipts[1].events[1]);
+
+        let at_height = database.get_events(&EventFilter { from_height: Some(2), ..EventFilter::default() }).await.unwrap();
+        assert_eq!(at_height.len(), 2);
+    }
+}
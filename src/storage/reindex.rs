@@ -0,0 +1,333 @@
+//! Rebuild of derived tables from the transactions table
+//!
+//! `transaction_parents` and `dag_nodes` can be derived from the transaction
+//! rows alone, so a corrupted copy can be regenerated without restoring a
+//! backup. Work runs in batches, each in its own database transaction, with an
+//! optional pause between batches so a live node keeps serving writes.
+
+use super::DatabaseManager;
+use crate::BlockchainError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::time::Duration;
+
+/// Reindex settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReindexConfig {
+    /// Transactions processed per database transaction
+    pub batch_size: usize,
+    /// Pause between batches, to leave room for a live node's writes
+    pub throttle_ms: u64,
+}
+
+impl Default for ReindexConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 1000,
+            throttle_ms: 0,
+        }
+    }
+}
+
+/// Stage of a reindex
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReindexPhase {
+    /// Rebuilding `transaction_parents` from each row's parent list
+    Parents,
+    /// Rebuilding `dag_nodes` with children from the rebuilt parent links
+    DagNodes,
+    /// Rebuilding SQLite indexes
+    Indexes,
+}
+
+/// Progress reported after every batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReindexProgress {
+    pub phase: ReindexPhase,
+    pub processed: u64,
+    pub total: u64,
+}
+
+/// Outcome of a reindex
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReindexReport {
+    pub transactions: u64,
+    /// Parent links written
+    pub parent_links: u64,
+    /// Parent links skipped because the parent is no longer stored, e.g. archived
+    pub missing_parents: u64,
+    /// Transactions without a stored parent list, whose links were left as they were
+    pub unlinked_transactions: u64,
+    /// DAG nodes rewritten from an existing row
+    pub dag_nodes_rebuilt: u64,
+    /// DAG nodes recreated for transactions that had none
+    pub dag_nodes_created: u64,
+    /// Parent links and DAG nodes removed for transactions that no longer exist
+    pub orphans_removed: u64,
+    pub started_at: i64,
+    pub completed_at: i64,
+}
+
+/// State of a background reindex on a running node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReindexStatus {
+    pub running: bool,
+    pub progress: Option<ReindexProgress>,
+    pub last_report: Option<ReindexReport>,
+    pub last_error: Option<String>,
+}
+
+impl DatabaseManager {
+    /// Rebuild derived tables from the transactions table
+    ///
+    /// DAG node children and quantum scores are recomputed. Weight, status and
+    /// confidence are consensus state rather than derived data, so they are
+    /// kept from the existing row when one can be read; recreated nodes start
+    /// as pending, weighted by their quantum score. `progress` is called after
+    /// every batch.
+    pub async fn reindex<F>(&self, config: &ReindexConfig, mut progress: F) -> Result<ReindexReport, BlockchainError>
+    where
+        F: FnMut(&ReindexProgress),
+    {
+        self.commit_queued().await?;
+        let batch_size = config.batch_size.max(1) as i64;
+        let throttle = Duration::from_millis(config.throttle_ms);
+        let mut report = ReindexReport {
+            started_at: Utc::now().timestamp(),
+            ..ReindexReport::default()
+        };
+        report.transactions = self.get_transaction_count().await?;
+        log::info!("🔧 Reindexing derived tables for {} transaction(s)", report.transactions);
+
+        for phase in [ReindexPhase::Parents, ReindexPhase::DagNodes] {
+            let mut last_rowid = 0i64;
+            let mut processed = 0u64;
+            loop {
+                let rows = sqlx::query(
+                    "SELECT rowid, id, parents, resistance_score FROM transactions WHERE rowid > ? ORDER BY rowid LIMIT ?"
+                )
+                .bind(last_rowid)
+                .bind(batch_size)
+                .fetch_all(&self.pool().await)
+                .await?;
+                let Some(last) = rows.last() else {
+                    break;
+                };
+                last_rowid = last.get("rowid");
+
+                let mut tx = self.pool().await.begin().await?;
+                for row in &rows {
+                    let id: String = row.get("id");
+                    match phase {
+                        ReindexPhase::Parents => {
+                            let Some(parents) = row.get::<Option<String>, _>("parents") else {
+                                report.unlinked_transactions += 1;
+                                continue;
+                            };
+                            let parents: Vec<String> = serde_json::from_str(&parents)?;
+
+                            sqlx::query("DELETE FROM transaction_parents WHERE transaction_id = ?")
+                                .bind(&id)
+                                .execute(&mut *tx)
+                                .await?;
+                            for parent in parents {
+                                let written = sqlx::query(
+                                    r#"
+                                    INSERT OR REPLACE INTO transaction_parents (transaction_id, parent_id)
+                                    SELECT ?, id FROM transactions WHERE id = ?
+                                    "#
+                                )
+                                .bind(&id)
+                                .bind(&parent)
+                                .execute(&mut *tx)
+                                .await?
+                                .rows_affected();
+                                if written > 0 {
+                                    report.parent_links += 1;
+                                } else {
+                                    report.missing_parents += 1;
+                                }
+                            }
+                        }
+                        ReindexPhase::DagNodes => {
+                            let children: Vec<String> = sqlx::query(
+                                "SELECT transaction_id FROM transaction_parents WHERE parent_id = ? ORDER BY rowid"
+                            )
+                            .bind(&id)
+                            .fetch_all(&mut *tx)
+                            .await?
+                            .iter()
+                            .map(|child| child.get("transaction_id"))
+                            .collect();
+                            let resistance_score: i64 = row.get("resistance_score");
+
+                            let existing = sqlx::query("SELECT weight, confidence, status FROM dag_nodes WHERE transaction_id = ?")
+                                .bind(&id)
+                                .fetch_optional(&mut *tx)
+                                .await
+                                .ok()
+                                .flatten()
+                                .and_then(|node| Some((
+                                    node.try_get::<i64, _>("weight").ok()?,
+                                    node.try_get::<f64, _>("confidence").ok()?,
+                                    node.try_get::<String, _>("status").ok()?,
+                                )));
+                            let (weight, confidence, status) = match existing {
+                                Some(state) => {
+                                    report.dag_nodes_rebuilt += 1;
+                                    state
+                                }
+                                None => {
+                                    report.dag_nodes_created += 1;
+                                    (resistance_score, 0.0, "Pending".to_string())
+                                }
+                            };
+
+                            sqlx::query(
+                                r#"
+                                INSERT OR REPLACE INTO dag_nodes
+                                (transaction_id, children, weight, confidence, status, quantum_score)
+                                VALUES (?, ?, ?, ?, ?, ?)
+                                "#
+                            )
+                            .bind(&id)
+                            .bind(serde_json::to_string(&children)?)
+                            .bind(weight)
+                            .bind(confidence)
+                            .bind(status)
+                            .bind(resistance_score)
+                            .execute(&mut *tx)
+                            .await?;
+                        }
+                        ReindexPhase::Indexes => unreachable!("indexes are rebuilt after the row phases"),
+                    }
+                }
+                tx.commit().await?;
+
+                processed += rows.len() as u64;
+                progress(&ReindexProgress { phase, processed, total: report.transactions });
+                if !throttle.is_zero() {
+                    tokio::time::sleep(throttle).await;
+                }
+            }
+        }
+
+        for query in [
+            "DELETE FROM transaction_parents WHERE transaction_id NOT IN (SELECT id FROM transactions)",
+            "DELETE FROM dag_nodes WHERE transaction_id NOT IN (SELECT id FROM transactions)",
+        ] {
+            report.orphans_removed += sqlx::query(query).execute(&self.pool().await).await?.rows_affected();
+        }
+
+        sqlx::query("REINDEX").execute(&self.pool().await).await?;
+        progress(&ReindexProgress { phase: ReindexPhase::Indexes, processed: report.transactions, total: report.transactions });
+
+        report.completed_at = Utc::now().timestamp();
+        log::info!(
+            "🔧 Reindex complete: {} parent link(s), {} DAG node(s) rebuilt, {} recreated, {} orphan(s) removed",
+            report.parent_links, report.dag_nodes_rebuilt, report.dag_nodes_created, report.orphans_removed
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DAGNode, NodeStatus, QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+    use crate::TransactionId;
+    use tempfile::TempDir;
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 100,
+            fee: 0,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents,
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![1u8; 32],
+                resistance_score: 80,
+                proof_timestamp: 1_700_000_000,
+            },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[tokio::test]
+    async fn test_reindex_rebuilds_links_and_nodes() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+
+        let parent = transaction(1, vec![]);
+        let child = transaction(2, vec![parent.id.clone()]);
+        db.store_transaction(&parent).await.unwrap();
+        db.store_transaction(&child).await.unwrap();
+        db.store_dag_node(&DAGNode {
+            transaction: parent.clone(),
+            children: vec![child.id.clone()],
+            weight: 7,
+            confidence: 0.9,
+            status: NodeStatus::Confirmed,
+            quantum_score: 80,
+        }).await.unwrap();
+
+        // Lose the parent links and corrupt the parent's children
+        sqlx::query("DELETE FROM transaction_parents").execute(&db.pool().await).await.unwrap();
+        sqlx::query("UPDATE dag_nodes SET children = '[]'").execute(&db.pool().await).await.unwrap();
+
+        let mut updates = Vec::new();
+        let config = ReindexConfig { batch_size: 1, throttle_ms: 0 };
+        let report = db.reindex(&config, |p| updates.push((p.phase, p.processed))).await.unwrap();
+
+        assert_eq!(report.parent_links, 1);
+        assert_eq!((report.dag_nodes_rebuilt, report.dag_nodes_created), (1, 1));
+        assert_eq!(updates, vec![
+            (ReindexPhase::Parents, 1), (ReindexPhase::Parents, 2),
+            (ReindexPhase::DagNodes, 1), (ReindexPhase::DagNodes, 2),
+            (ReindexPhase::Indexes, 2),
+        ]);
+
+        let node = sqlx::query("SELECT children, weight, status FROM dag_nodes WHERE transaction_id = ?")
+            .bind(parent.id.as_string())
+            .fetch_one(&db.pool().await).await.unwrap();
+        assert_eq!(node.get::<String, _>(0), serde_json::to_string(&[child.id.as_string()]).unwrap());
+        // Consensus state survives the rebuild
+        assert_eq!(node.get::<i64, _>(1), 7);
+        assert_eq!(node.get::<String, _>(2), "Confirmed");
+    }
+
+    #[tokio::test]
+    async fn test_reindex_removes_orphans() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+
+        let stored = transaction(1, vec![]);
+        db.store_transaction(&stored).await.unwrap();
+        let mut conn = db.pool().await.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await.unwrap();
+        sqlx::query("INSERT INTO dag_nodes (transaction_id, children, weight, confidence, status, quantum_score) VALUES ('gone', '[]', 1, 0, 'Pending', 80)")
+            .execute(&mut *conn).await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await.unwrap();
+        drop(conn);
+
+        let report = db.reindex(&ReindexConfig::default(), |_| {}).await.unwrap();
+        assert_eq!(report.orphans_removed, 1);
+        assert_eq!(report.dag_nodes_created, 1);
+    }
+}
@@ -0,0 +1,301 @@
+//! Read-only replica for analytical queries
+//!
+//! Explorer and analytics reads can be served from a second, read-only
+//! connection pool so they do not compete with the writer pool. The replica
+//! may point at the primary database file or at a copy kept up to date by an
+//! external process. Either way its lag behind the primary is sampled
+//! periodically and every analytical response carries a staleness bound: the
+//! time since the replica was last seen holding everything the primary had.
+//! When that bound exceeds `max_staleness_secs`, reads fall back to the
+//! primary.
+
+use super::DatabaseManager;
+use crate::BlockchainError;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// Read replica configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadReplicaConfig {
+    /// Database file to open read-only
+    pub path: String,
+    pub max_connections: u32,
+    /// Largest staleness bound at which the replica is still used
+    pub max_staleness_secs: u64,
+    /// How often lag behind the primary is sampled
+    pub refresh_interval_secs: u64,
+    /// SQLCipher key of the replica file, the primary's when it is a copy
+    #[serde(skip)]
+    pub encryption_key: Option<String>,
+}
+
+impl Default for ReadReplicaConfig {
+    fn default() -> Self {
+        Self {
+            path: "./blockchain.db".to_string(),
+            max_connections: 4,
+            max_staleness_secs: 30,
+            refresh_interval_secs: 5,
+            encryption_key: None,
+        }
+    }
+}
+
+/// Position of a database in the transaction stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicationMarker {
+    pub transaction_count: u64,
+    pub latest_timestamp: u64,
+}
+
+/// Database an analytical read was served from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadSource {
+    Primary,
+    Replica,
+}
+
+/// How out of date an analytical response may be
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Staleness {
+    pub source: ReadSource,
+    /// Upper bound on how old the data is, in seconds
+    pub max_staleness_secs: u64,
+    /// Transactions the replica was missing at the last sample
+    pub lag_transactions: u64,
+    /// When lag was last sampled
+    pub measured_at: u64,
+}
+
+impl Staleness {
+    /// Staleness of a read served by the primary
+    pub fn primary() -> Self {
+        Self {
+            source: ReadSource::Primary,
+            max_staleness_secs: 0,
+            lag_transactions: 0,
+            measured_at: Utc::now().timestamp() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ReplicaLag {
+    /// Last sample at which the replica had caught up with the primary
+    in_sync_at: u64,
+    measured_at: u64,
+    lag_transactions: u64,
+}
+
+/// Read-only database used for analytical queries
+pub struct ReadReplica {
+    database: Arc<DatabaseManager>,
+    config: ReadReplicaConfig,
+    lag: RwLock<ReplicaLag>,
+}
+
+impl ReadReplica {
+    /// Open the replica; its lag is unknown until the first `refresh_lag`
+    pub async fn open(config: ReadReplicaConfig) -> Result<Self, BlockchainError> {
+        let database = DatabaseManager::open_read_only(&config.path, config.max_connections, config.encryption_key.as_deref()).await?;
+        log::info!("📖 Read replica opened at {}", config.path);
+
+        Ok(Self {
+            database: Arc::new(database),
+            config,
+            lag: RwLock::new(ReplicaLag { in_sync_at: 0, measured_at: 0, lag_transactions: 0 }),
+        })
+    }
+
+    /// Replica database handle
+    pub fn database(&self) -> Arc<DatabaseManager> {
+        self.database.clone()
+    }
+
+    /// Replica configuration
+    pub fn config(&self) -> &ReadReplicaConfig {
+        &self.config
+    }
+
+    /// Sample the replica's lag behind `primary`
+    pub async fn refresh_lag(&self, primary: &DatabaseManager) -> Result<Staleness, BlockchainError> {
+        // Read the replica first so anything committed in between counts as lag
+        let replica = self.database.replication_marker().await?;
+        let primary = primary.replication_marker().await?;
+        let now = Utc::now().timestamp() as u64;
+
+        let lag_transactions = primary.transaction_count.saturating_sub(replica.transaction_count);
+        let caught_up = lag_transactions == 0 && replica.latest_timestamp >= primary.latest_timestamp;
+        {
+            let mut lag = self.lag.write().unwrap_or_else(|e| e.into_inner());
+            lag.measured_at = now;
+            lag.lag_transactions = lag_transactions;
+            if caught_up {
+                lag.in_sync_at = now;
+            }
+        }
+
+        if !caught_up {
+            log::debug!("📖 Read replica is {} transaction(s) behind", lag_transactions);
+        }
+        Ok(self.staleness())
+    }
+
+    /// Current staleness bound
+    pub fn staleness(&self) -> Staleness {
+        let lag = *self.lag.read().unwrap_or_else(|e| e.into_inner());
+        let now = Utc::now().timestamp() as u64;
+        Staleness {
+            source: ReadSource::Replica,
+            // Never caught up means the bound is unknown
+            max_staleness_secs: if lag.in_sync_at == 0 { u64::MAX } else { now.saturating_sub(lag.in_sync_at) },
+            lag_transactions: lag.lag_transactions,
+            measured_at: lag.measured_at,
+        }
+    }
+
+    /// Whether the replica is fresh enough to serve reads
+    pub fn is_fresh(&self) -> bool {
+        self.staleness().max_staleness_secs <= self.config.max_staleness_secs
+    }
+}
+
+impl DatabaseManager {
+    /// Open an existing database through a read-only connection pool
+    ///
+    /// The schema is not created or migrated; the file must already exist.
+    pub async fn open_read_only(path: &str, max_connections: u32, encryption_key: Option<&str>) -> Result<Self, BlockchainError> {
+        let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", path))?.read_only(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_connections.max(1))
+            .connect_with(super::encryption::with_key(options, encryption_key)?)
+            .await?;
+        let pool = super::SharedPool::new(pool);
+
+        Ok(Self {
+            // Replicas follow the SQLite tables, so they only serve nodes on the SQLite backend
+            backend: std::sync::Arc::new(super::SqliteBackend::shared(pool.clone())),
+            shards: None,
+            queue: None,
+            pool,
+            path: path.to_string(),
+            encryption_key: encryption_key.map(str::to_string),
+            // The read-only pool above is opened without them
+            pragmas: super::SqlitePragmas::default(),
+            mode: super::StorageMode::Archival,
+            retention: super::RetentionConfig::default(),
+            // Quarantining needs writes, so the replica leaves verification to the primary
+            checksums: std::sync::RwLock::new(super::ChecksumConfig { mode: super::ChecksumMode::Off, ..Default::default() }),
+            transaction_source: std::sync::RwLock::new(None),
+            backup_destination: std::sync::RwLock::new(None),
+            events: None,
+            metrics: super::StorageMetrics::new().map_err(|e| BlockchainError::Other(e.to_string()))?,
+        })
+    }
+
+    /// Transaction count and newest transaction timestamp
+    pub async fn replication_marker(&self) -> Result<ReplicationMarker, BlockchainError> {
+        self.commit_queued().await?;
+        let row = sqlx::query("SELECT COUNT(*), COALESCE(MAX(timestamp), 0) FROM transactions")
+            .fetch_one(&self.pool().await)
+            .await?;
+
+        Ok(ReplicationMarker {
+            transaction_count: row.get::<i64, _>(0) as u64,
+            latest_timestamp: row.get::<i64, _>(1) as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+    use crate::TransactionId;
+    use tempfile::TempDir;
+
+    fn transaction() -> Transaction {
+        let now = Utc::now().timestamp() as u64;
+        Transaction {
+            id: TransactionId::new(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 100,
+            fee: 0,
+            nonce: 1,
+            timestamp: now,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![1u8; 32],
+                resistance_score: 80,
+                proof_timestamp: now,
+            },
+            metadata: None,
+        }
+    }
+
+    async fn primary(path: &std::path::Path) -> DatabaseManager {
+        DatabaseManager::new(DatabaseConfig {
+            path: path.to_string_lossy().to_string(),
+            max_connections: 5,
+            ..DatabaseConfig::default()
+        }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replica_on_primary_file_is_read_only_and_in_sync() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+        let db = primary(&path).await;
+        db.store_transaction(&transaction()).await.unwrap();
+
+        let replica = ReadReplica::open(ReadReplicaConfig {
+            path: path.to_string_lossy().to_string(),
+            ..ReadReplicaConfig::default()
+        }).await.unwrap();
+        assert!(!replica.is_fresh());
+
+        let staleness = replica.refresh_lag(&db).await.unwrap();
+        assert_eq!(staleness.lag_transactions, 0);
+        assert!(replica.is_fresh());
+
+        // Writes through the replica pool are refused
+        assert!(replica.database().store_transaction(&transaction()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lagging_replica_keeps_last_sync_time() {
+        let temp_dir = TempDir::new().unwrap();
+        let primary_path = temp_dir.path().join("primary.db");
+        let replica_path = temp_dir.path().join("replica.db");
+        let db = primary(&primary_path).await;
+        db.store_transaction(&transaction()).await.unwrap();
+        db.close().await.unwrap();
+        std::fs::copy(&primary_path, &replica_path).unwrap();
+
+        let db = primary(&primary_path).await;
+        let replica = ReadReplica::open(ReadReplicaConfig {
+            path: replica_path.to_string_lossy().to_string(),
+            max_staleness_secs: 0,
+            ..ReadReplicaConfig::default()
+        }).await.unwrap();
+        replica.refresh_lag(&db).await.unwrap();
+        let in_sync = replica.staleness();
+
+        db.store_transaction(&transaction()).await.unwrap();
+        let staleness = replica.refresh_lag(&db).await.unwrap();
+        assert_eq!(staleness.source, ReadSource::Replica);
+        assert_eq!(staleness.lag_transactions, 1);
+        assert!(staleness.max_staleness_secs >= in_sync.max_staleness_secs);
+        assert!(staleness.max_staleness_secs < u64::MAX);
+    }
+}
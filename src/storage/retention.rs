@@ -0,0 +1,457 @@
+//! Data retention policies
+//!
+//! Finalized transactions move through three tiers:
+//! 1. the hot tables, for `hot_days`;
+//! 2. the `transactions_archive` table, until `archive_after_days`;
+//! 3. JSON-lines audit copies in `archive_dir`, deleted after
+//!    `delete_audit_copies_after_years`.
+//!
+//! Rows touching an address on the legal hold list are never moved or deleted.
+
+use super::DatabaseManager;
+use crate::{BlockchainError, TransactionId, core::Transaction};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashSet;
+
+const DAY_SECS: i64 = 24 * 3600;
+const AUDIT_COPY_PREFIX: &str = "transactions_archive_";
+const AUDIT_COPY_SUFFIX: &str = ".jsonl";
+
+/// Tables covered by retention policies
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum RetentionTable {
+    /// Finalized transactions with their parents and DAG nodes
+    Transactions,
+    /// DAG node metadata of finalized transactions
+    DagNodes,
+}
+
+/// Retention policy for one table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableRetentionPolicy {
+    pub table: RetentionTable,
+    /// Days finalized rows stay in the hot tables
+    pub hot_days: u64,
+    /// Days after which archived rows are exported to audit copies
+    pub archive_after_days: Option<u64>,
+    /// Years after which audit copies are deleted
+    pub delete_audit_copies_after_years: Option<u64>,
+}
+
+/// Retention configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    pub enabled: bool,
+    pub archive_dir: String,
+    pub policies: Vec<TableRetentionPolicy>,
+    /// Hex-encoded addresses exempt from archiving and deletion
+    pub legal_hold_addresses: Vec<String>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            archive_dir: "./archive".to_string(),
+            policies: vec![
+                TableRetentionPolicy {
+                    table: RetentionTable::Transactions,
+                    hot_days: 90,
+                    archive_after_days: Some(365),
+                    delete_audit_copies_after_years: Some(7),
+                },
+                TableRetentionPolicy {
+                    table: RetentionTable::DagNodes,
+                    hot_days: 30,
+                    archive_after_days: None,
+                    delete_audit_copies_after_years: None,
+                },
+            ],
+            legal_hold_addresses: Vec::new(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Policy for a table, if one is declared
+    pub fn policy(&self, table: RetentionTable) -> Option<&TableRetentionPolicy> {
+        self.policies.iter().find(|p| p.table == table)
+    }
+
+    fn legal_holds(&self) -> HashSet<String> {
+        self.legal_hold_addresses.iter()
+            .map(|a| a.trim_start_matches("0x").to_lowercase())
+            .collect()
+    }
+}
+
+/// Outcome of a retention run for one table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableRetentionReport {
+    /// Rows moved from the hot tables to the archive table
+    pub archived: u64,
+    /// Archived rows written to audit copies
+    pub exported: u64,
+    /// Rows deleted outright
+    pub deleted: u64,
+    /// Rows kept because of a legal hold
+    pub held: u64,
+    pub audit_copies_written: Vec<String>,
+    pub audit_copies_deleted: Vec<String>,
+}
+
+/// Outcome of a retention run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub run_timestamp: i64,
+    pub transactions: TableRetentionReport,
+    pub dag_nodes: TableRetentionReport,
+}
+
+impl DatabaseManager {
+    /// Get the configured retention policies
+    pub fn retention_config(&self) -> &RetentionConfig {
+        &self.retention
+    }
+
+    /// Enforce retention policies; with `dry_run` nothing is changed
+    pub async fn run_retention_job(&self, dry_run: bool) -> Result<RetentionReport, BlockchainError> {
+        self.commit_queued().await?;
+        let now = Utc::now().timestamp();
+        let mut report = RetentionReport {
+            dry_run,
+            run_timestamp: now,
+            transactions: TableRetentionReport::default(),
+            dag_nodes: TableRetentionReport::default(),
+        };
+
+        if !self.retention.enabled {
+            log::debug!("Retention job skipped, retention is disabled");
+            return Ok(report);
+        }
+
+        let holds = self.retention.legal_holds();
+
+        if let Some(policy) = self.retention.policy(RetentionTable::Transactions) {
+            self.retain_transactions(policy, &holds, now, dry_run, &mut report.transactions).await?;
+        }
+        if let Some(policy) = self.retention.policy(RetentionTable::DagNodes) {
+            self.retain_dag_nodes(policy, &holds, now, dry_run, &mut report.dag_nodes).await?;
+        }
+
+        log::info!(
+            "🗄️  Retention {}: {} archived, {} exported, {} DAG nodes pruned, {} held",
+            if dry_run { "dry run" } else { "run" },
+            report.transactions.archived,
+            report.transactions.exported,
+            report.dag_nodes.deleted,
+            report.transactions.held + report.dag_nodes.held,
+        );
+        Ok(report)
+    }
+
+    async fn retain_transactions(
+        &self,
+        policy: &TableRetentionPolicy,
+        holds: &HashSet<String>,
+        now: i64,
+        dry_run: bool,
+        report: &mut TableRetentionReport,
+    ) -> Result<(), BlockchainError> {
+        // Hot tables -> archive table
+        let hot_cutoff = now - policy.hot_days as i64 * DAY_SECS;
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.sender, t.receiver FROM transactions t
+            JOIN dag_nodes d ON d.transaction_id = t.id
+            WHERE d.status = 'Finalized' AND t.timestamp < ?
+            "#
+        )
+        .bind(hot_cutoff)
+        .fetch_all(&self.pool().await)
+        .await?;
+
+        for row in rows {
+            if is_held(holds, &row.get::<Vec<u8>, _>("sender"), &row.get::<Vec<u8>, _>("receiver")) {
+                report.held += 1;
+                continue;
+            }
+            report.archived += 1;
+            if dry_run {
+                continue;
+            }
+
+            let id: String = row.get("id");
+            let tx_id = TransactionId::from_string(&id)?;
+            if let Some(transaction) = self.get_transaction(&tx_id).await? {
+                self.move_to_archive(&transaction, now).await?;
+            }
+        }
+
+        // Archive table -> audit copy
+        if let Some(archive_days) = policy.archive_after_days {
+            let archive_cutoff = now - archive_days as i64 * DAY_SECS;
+            let rows = sqlx::query(
+                "SELECT id, sender, receiver, data FROM transactions_archive WHERE timestamp < ? ORDER BY timestamp"
+            )
+            .bind(archive_cutoff)
+            .fetch_all(&self.pool().await)
+            .await?;
+
+            let mut lines = Vec::new();
+            let mut ids = Vec::new();
+            for row in rows {
+                if is_held(holds, &row.get::<Vec<u8>, _>("sender"), &row.get::<Vec<u8>, _>("receiver")) {
+                    report.held += 1;
+                    continue;
+                }
+                ids.push(row.get::<String, _>("id"));
+                lines.push(row.get::<String, _>("data"));
+            }
+            report.exported = ids.len() as u64;
+
+            if !ids.is_empty() {
+                let path = format!("{}/{}{}{}", self.retention.archive_dir, AUDIT_COPY_PREFIX, now, AUDIT_COPY_SUFFIX);
+                if !dry_run {
+                    tokio::fs::create_dir_all(&self.retention.archive_dir).await?;
+                    tokio::fs::write(&path, lines.join("\n") + "\n").await?;
+
+                    let mut tx = self.pool().await.begin().await?;
+                    for id in &ids {
+                        sqlx::query("DELETE FROM transactions_archive WHERE id = ?")
+                            .bind(id)
+                            .execute(&mut *tx)
+                            .await?;
+                    }
+                    tx.commit().await?;
+                }
+                report.audit_copies_written.push(path);
+            }
+        }
+
+        // Expired audit copies
+        if let Some(years) = policy.delete_audit_copies_after_years {
+            let cutoff = now - years as i64 * 365 * DAY_SECS;
+            report.audit_copies_deleted = self.expire_audit_copies(cutoff, dry_run).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn retain_dag_nodes(
+        &self,
+        policy: &TableRetentionPolicy,
+        holds: &HashSet<String>,
+        now: i64,
+        dry_run: bool,
+        report: &mut TableRetentionReport,
+    ) -> Result<(), BlockchainError> {
+        let cutoff = now - policy.hot_days as i64 * DAY_SECS;
+        let rows = sqlx::query(
+            r#"
+            SELECT d.transaction_id, t.sender, t.receiver FROM dag_nodes d
+            JOIN transactions t ON t.id = d.transaction_id
+            WHERE d.status = 'Finalized' AND t.timestamp < ?
+            "#
+        )
+        .bind(cutoff)
+        .fetch_all(&self.pool().await)
+        .await?;
+
+        let mut tx = self.pool().await.begin().await?;
+        for row in rows {
+            if is_held(holds, &row.get::<Vec<u8>, _>("sender"), &row.get::<Vec<u8>, _>("receiver")) {
+                report.held += 1;
+                continue;
+            }
+            report.deleted += 1;
+            if !dry_run {
+                sqlx::query("DELETE FROM dag_nodes WHERE transaction_id = ?")
+                    .bind(row.get::<String, _>("transaction_id"))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn move_to_archive(&self, transaction: &Transaction, now: i64) -> Result<(), BlockchainError> {
+        let id = transaction.id.as_string();
+        let mut tx = self.pool().await.begin().await?;
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO transactions_archive (id, sender, receiver, timestamp, data, archived_at) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&id)
+        .bind(&transaction.sender)
+        .bind(&transaction.receiver)
+        .bind(transaction.timestamp as i64)
+        .bind(serde_json::to_string(transaction)?)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        for query in [
+            "DELETE FROM transaction_parents WHERE transaction_id = ?",
+            "DELETE FROM dag_nodes WHERE transaction_id = ?",
+            "DELETE FROM transactions WHERE id = ?",
+        ] {
+            sqlx::query(query).bind(&id).execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn expire_audit_copies(&self, cutoff: i64, dry_run: bool) -> Result<Vec<String>, BlockchainError> {
+        let mut deleted = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&self.retention.archive_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(deleted),
+            Err(e) => return Err(e.into()),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let created = name.strip_prefix(AUDIT_COPY_PREFIX)
+                .and_then(|rest| rest.strip_suffix(AUDIT_COPY_SUFFIX))
+                .and_then(|ts| ts.parse::<i64>().ok());
+
+            if let Some(created) = created {
+                if created < cutoff {
+                    let path = entry.path().to_string_lossy().to_string();
+                    if !dry_run {
+                        tokio::fs::remove_file(&path).await?;
+                    }
+                    deleted.push(path);
+                }
+            }
+        }
+
+        Ok(deleted)
+    }
+}
+
+fn is_held(holds: &HashSet<String>, sender: &[u8], receiver: &[u8]) -> bool {
+    !holds.is_empty() && (holds.contains(&hex::encode(sender)) || holds.contains(&hex::encode(receiver)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DAGNode, NodeStatus, QuantumProof};
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+
+    fn old_transaction(sender: u8, days_old: i64) -> Transaction {
+        let timestamp = (Utc::now().timestamp() - days_old * DAY_SECS) as u64;
+        Transaction {
+            id: TransactionId::new(),
+            sender: vec![sender; 32],
+            receiver: vec![2u8; 32],
+            amount: 100,
+            fee: 0,
+            nonce: 1,
+            timestamp,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![1u8; 32],
+                resistance_score: 80,
+                proof_timestamp: timestamp,
+            },
+            metadata: None,
+        }
+    }
+
+    async fn store_finalized(db: &DatabaseManager, transaction: Transaction) {
+        db.store_transaction(&transaction).await.unwrap();
+        db.store_dag_node(&DAGNode {
+            transaction,
+            children: vec![],
+            weight: 1,
+            confidence: 1.0,
+            status: NodeStatus::Finalized,
+            quantum_score: 80,
+        }).await.unwrap();
+    }
+
+    async fn setup(temp_dir: &TempDir, archive_after_days: u64) -> DatabaseManager {
+        let retention = RetentionConfig {
+            enabled: true,
+            archive_dir: temp_dir.path().join("archive").to_string_lossy().to_string(),
+            policies: vec![TableRetentionPolicy {
+                table: RetentionTable::Transactions,
+                hot_days: 30,
+                archive_after_days: Some(archive_after_days),
+                delete_audit_copies_after_years: Some(7),
+            }],
+            legal_hold_addresses: vec![hex::encode([9u8; 32])],
+        };
+        DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            max_connections: 5,
+            retention,
+        }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_changes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = setup(&temp_dir, 365).await;
+        let old = old_transaction(1, 60);
+        store_finalized(&db, old.clone()).await;
+
+        let report = db.run_retention_job(true).await.unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.transactions.archived, 1);
+        assert!(db.get_transaction(&old.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_archives_and_respects_legal_hold() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = setup(&temp_dir, 365).await;
+        let old = old_transaction(1, 60);
+        let held = old_transaction(9, 60);
+        let recent = old_transaction(1, 1);
+        for tx in [&old, &held, &recent] {
+            store_finalized(&db, tx.clone()).await;
+        }
+
+        let report = db.run_retention_job(false).await.unwrap();
+        assert_eq!(report.transactions.archived, 1);
+        assert_eq!(report.transactions.held, 1);
+        assert!(db.get_transaction(&old.id).await.unwrap().is_none());
+        assert!(db.get_transaction(&held.id).await.unwrap().is_some());
+        assert!(db.get_transaction(&recent.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_exports_audit_copies_and_expires_them() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = setup(&temp_dir, 45).await;
+        store_finalized(&db, old_transaction(1, 60)).await;
+
+        let report = db.run_retention_job(false).await.unwrap();
+        assert_eq!(report.transactions.exported, 1);
+        let written = &report.transactions.audit_copies_written[0];
+        assert!(std::path::Path::new(written).exists());
+
+        // An audit copy from eight years ago is past the seven year limit
+        let expired_ts = Utc::now().timestamp() - 8 * 365 * DAY_SECS;
+        let expired = format!("{}/{}{}{}", db.retention_config().archive_dir, AUDIT_COPY_PREFIX, expired_ts, AUDIT_COPY_SUFFIX);
+        tokio::fs::write(&expired, "{}\n").await.unwrap();
+
+        let report = db.run_retention_job(false).await.unwrap();
+        assert_eq!(report.transactions.audit_copies_deleted, vec![expired.clone()]);
+        assert!(!std::path::Path::new(&expired).exists());
+        assert!(std::path::Path::new(written).exists());
+    }
+}
@@ -0,0 +1,290 @@
+//! RocksDB storage backend
+//!
+//! Transactions and DAG nodes live in column families of their own, keyed
+//! by the string form of their ID so keys sort as they do in SQLite. Values
+//! use the canonical encoding followed by the checksum the row was written
+//! with. A third column family, `children`, holds one empty value per parent
+//! link under `<parent>/<child>`, standing in for the `transaction_parents`
+//! table.
+//!
+//! RocksDB calls block, so every call runs on tokio's blocking pool.
+
+use super::backend::{StorageBackend, StorageBackendKind, StoredDagNode, StoredTransaction, WriteBatch, WriteOp};
+use super::integrity::transaction_checksum;
+use crate::core::{CanonicalDecoder, CanonicalEncoder, EncodingError, Transaction};
+use crate::{BlockchainError, TransactionId};
+use rocksdb::{ColumnFamily, Direction, IteratorMode, Options, DB};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+const TRANSACTIONS_CF: &str = "transactions";
+const DAG_NODES_CF: &str = "dag_nodes";
+const CHILDREN_CF: &str = "children";
+
+/// Separates parent and child in `children` keys; IDs never contain it
+const LINK_SEPARATOR: char = '/';
+
+/// Transactions and DAG nodes in a RocksDB database
+pub struct RocksDbBackend {
+    db: Arc<DB>,
+}
+
+impl RocksDbBackend {
+    /// Open the database at `path`, creating it if missing
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BlockchainError> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        let db = DB::open_cf(&options, path.as_ref(), [TRANSACTIONS_CF, DAG_NODES_CF, CHILDREN_CF])
+            .map_err(rocks_error)?;
+        log::info!("🪨 Opened RocksDB storage at {}", path.as_ref().display());
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Run `task` on the blocking pool
+    async fn run<T, F>(&self, task: F) -> Result<T, BlockchainError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&DB) -> Result<T, BlockchainError> + Send + 'static,
+    {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || task(&db))
+            .await
+            .map_err(|e| BlockchainError::Other(format!("RocksDB task failed: {}", e)))?
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for RocksDbBackend {
+    fn kind(&self) -> StorageBackendKind {
+        StorageBackendKind::RocksDb
+    }
+
+    async fn write(&self, batch: WriteBatch) -> Result<(), BlockchainError> {
+        self.run(move |db| {
+            let mut writes = rocksdb::WriteBatch::default();
+            // Nodes put earlier in this batch, which reads would not see yet
+            let mut nodes: HashMap<String, StoredDagNode> = HashMap::new();
+            for op in batch.into_ops() {
+                match op {
+                    WriteOp::PutTransaction(transaction) => {
+                        let key = transaction.id.as_string();
+                        writes.put_cf(cf(db, TRANSACTIONS_CF)?, &key, encode_transaction(&transaction));
+                        for parent in &transaction.parents {
+                            writes.put_cf(cf(db, CHILDREN_CF)?, link_key(parent, &transaction.id), []);
+                        }
+                    }
+                    WriteOp::PutDagNode(node) => {
+                        let node = StoredDagNode::from_node(&node);
+                        let key = node.tx_id.as_string();
+                        writes.put_cf(cf(db, DAG_NODES_CF)?, &key, encode_dag_node(&node));
+                        nodes.insert(key, node);
+                    }
+                    WriteOp::SetStatus { tx_id, status, confidence } => {
+                        let key = tx_id.as_string();
+                        let stored = match nodes.remove(&key) {
+                            Some(node) => Some(node),
+                            None => read_dag_node(db, &key)?,
+                        };
+                        if let Some(mut node) = stored {
+                            node.set_status(status, confidence);
+                            writes.put_cf(cf(db, DAG_NODES_CF)?, &key, encode_dag_node(&node));
+                            nodes.insert(key, node);
+                        }
+                    }
+                }
+            }
+            db.write(writes).map_err(rocks_error)
+        }).await
+    }
+
+    async fn delete(&self, ids: &[TransactionId]) -> Result<u64, BlockchainError> {
+        let ids = ids.to_vec();
+        self.run(move |db| {
+            let mut writes = rocksdb::WriteBatch::default();
+            let mut deleted = HashSet::new();
+            for id in ids {
+                let key = id.as_string();
+                if let Some(stored) = read_transaction(db, &key)? {
+                    for parent in &stored.transaction.parents {
+                        writes.delete_cf(cf(db, CHILDREN_CF)?, link_key(parent, &id));
+                    }
+                    deleted.insert(key.clone());
+                }
+                writes.delete_cf(cf(db, TRANSACTIONS_CF)?, &key);
+                writes.delete_cf(cf(db, DAG_NODES_CF)?, &key);
+            }
+            db.write(writes).map_err(rocks_error)?;
+            Ok(deleted.len() as u64)
+        }).await
+    }
+
+    async fn get_transaction(&self, tx_id: &TransactionId) -> Result<Option<StoredTransaction>, BlockchainError> {
+        let key = tx_id.as_string();
+        self.run(move |db| read_transaction(db, &key)).await
+    }
+
+    async fn get_dag_node(&self, tx_id: &TransactionId) -> Result<Option<StoredDagNode>, BlockchainError> {
+        let key = tx_id.as_string();
+        self.run(move |db| read_dag_node(db, &key)).await
+    }
+
+    async fn child_ids(&self, tx_id: &TransactionId) -> Result<Vec<TransactionId>, BlockchainError> {
+        let prefix = format!("{}{}", tx_id.as_string(), LINK_SEPARATOR);
+        self.run(move |db| {
+            let mut children = Vec::new();
+            for item in db.iterator_cf(cf(db, CHILDREN_CF)?, IteratorMode::From(prefix.as_bytes(), Direction::Forward)) {
+                let (key, _) = item.map_err(rocks_error)?;
+                let Some(child) = key.strip_prefix(prefix.as_bytes()) else {
+                    break;
+                };
+                children.push(TransactionId::from_string(&String::from_utf8_lossy(child))?);
+            }
+            Ok(children)
+        }).await
+    }
+
+    async fn scan_transactions(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        self.run(move |db| {
+            scan(db, TRANSACTIONS_CF, after, limit)?
+                .into_iter()
+                .map(|value| decode_transaction(&value))
+                .collect()
+        }).await
+    }
+
+    async fn scan_dag_nodes(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredDagNode>, BlockchainError> {
+        self.run(move |db| {
+            scan(db, DAG_NODES_CF, after, limit)?
+                .into_iter()
+                .map(|value| decode_dag_node(&value))
+                .collect()
+        }).await
+    }
+
+    async fn flush(&self) -> Result<(), BlockchainError> {
+        self.run(|db| {
+            for name in [TRANSACTIONS_CF, DAG_NODES_CF, CHILDREN_CF] {
+                db.flush_cf(cf(db, name)?).map_err(rocks_error)?;
+            }
+            Ok(())
+        }).await
+    }
+
+    async fn transaction_count(&self) -> Result<u64, BlockchainError> {
+        self.run(|db| {
+            let mut count = 0;
+            for item in db.iterator_cf(cf(db, TRANSACTIONS_CF)?, IteratorMode::Start) {
+                item.map_err(rocks_error)?;
+                count += 1;
+            }
+            Ok(count)
+        }).await
+    }
+}
+
+fn rocks_error(error: rocksdb::Error) -> BlockchainError {
+    BlockchainError::Other(format!("RocksDB error: {}", error))
+}
+
+fn decode_error(error: EncodingError) -> BlockchainError {
+    BlockchainError::Core(error.into())
+}
+
+fn cf<'a>(db: &'a DB, name: &str) -> Result<&'a ColumnFamily, BlockchainError> {
+    db.cf_handle(name)
+        .ok_or_else(|| BlockchainError::Other(format!("RocksDB column family {} is missing", name)))
+}
+
+fn link_key(parent: &TransactionId, child: &TransactionId) -> String {
+    format!("{}{}{}", parent.as_string(), LINK_SEPARATOR, child.as_string())
+}
+
+/// Values of up to `limit` keys in `cf_name`, starting after `after`
+fn scan(db: &DB, cf_name: &str, after: Option<TransactionId>, limit: usize) -> Result<Vec<Box<[u8]>>, BlockchainError> {
+    let after = after.map(|id| id.as_string());
+    let mode = match &after {
+        Some(key) => IteratorMode::From(key.as_bytes(), Direction::Forward),
+        None => IteratorMode::Start,
+    };
+    let mut values = Vec::new();
+    for item in db.iterator_cf(cf(db, cf_name)?, mode) {
+        if values.len() == limit {
+            break;
+        }
+        let (key, value) = item.map_err(rocks_error)?;
+        if after.as_deref().is_some_and(|after| after.as_bytes() == &*key) {
+            continue;
+        }
+        values.push(value);
+    }
+    Ok(values)
+}
+
+fn read_transaction(db: &DB, key: &str) -> Result<Option<StoredTransaction>, BlockchainError> {
+    db.get_cf(cf(db, TRANSACTIONS_CF)?, key)
+        .map_err(rocks_error)?
+        .map(|value| decode_transaction(&value))
+        .transpose()
+}
+
+fn read_dag_node(db: &DB, key: &str) -> Result<Option<StoredDagNode>, BlockchainError> {
+    db.get_cf(cf(db, DAG_NODES_CF)?, key)
+        .map_err(rocks_error)?
+        .map(|value| decode_dag_node(&value))
+        .transpose()
+}
+
+fn encode_checksum(encoder: &mut CanonicalEncoder, checksum: Option<&String>) {
+    encoder.option(checksum.map(|checksum| checksum.as_bytes().to_vec()).as_ref());
+}
+
+fn decode_checksum(decoder: &mut CanonicalDecoder<'_>) -> Result<Option<String>, BlockchainError> {
+    decoder.option::<Vec<u8>>()
+        .map_err(decode_error)?
+        .map(|checksum| String::from_utf8(checksum).map_err(|_| BlockchainError::Other("Stored checksum is not UTF-8".to_string())))
+        .transpose()
+}
+
+fn encode_transaction(transaction: &Transaction) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new();
+    encoder.value(transaction);
+    encode_checksum(&mut encoder, Some(&transaction_checksum(transaction)));
+    encoder.finish()
+}
+
+fn decode_transaction(value: &[u8]) -> Result<StoredTransaction, BlockchainError> {
+    let mut decoder = CanonicalDecoder::new(value);
+    let transaction = decoder.value().map_err(decode_error)?;
+    let checksum = decode_checksum(&mut decoder)?;
+    decoder.finish().map_err(decode_error)?;
+    Ok(StoredTransaction { transaction, checksum })
+}
+
+fn encode_dag_node(node: &StoredDagNode) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new();
+    encoder.value(&node.tx_id)
+        .seq(&node.children)
+        .u64(node.weight)
+        .f64(node.confidence)
+        .value(&node.status)
+        .u32(node.quantum_score);
+    encode_checksum(&mut encoder, node.checksum.as_ref());
+    encoder.finish()
+}
+
+fn decode_dag_node(value: &[u8]) -> Result<StoredDagNode, BlockchainError> {
+    let mut decoder = CanonicalDecoder::new(value);
+    let node = StoredDagNode {
+        tx_id: decoder.value().map_err(decode_error)?,
+        children: decoder.seq().map_err(decode_error)?,
+        weight: decoder.u64().map_err(decode_error)?,
+        confidence: decoder.f64().map_err(decode_error)?,
+        status: decoder.value().map_err(decode_error)?,
+        quantum_score: decoder.u32().map_err(decode_error)?,
+        checksum: decode_checksum(&mut decoder)?,
+    };
+    decoder.finish().map_err(decode_error)?;
+    Ok(node)
+}
@@ -0,0 +1,486 @@
+//! Transactions and DAG nodes sharded across SQLite files
+//!
+//! Every write to one SQLite file waits for its single write lock. The
+//! sharded backend splits transactions, their DAG nodes and their parent
+//! links across `shards` files next to the node's database,
+//! `<path>.shard<i>`, by the first byte of the transaction ID: shard `i`
+//! holds the IDs whose first byte is in the `i`th of `shards` equal ranges.
+//! Shards cover consecutive key ranges, so key-ordered scans read them one
+//! after another; other listings and counts fan out to every shard and
+//! merge the results.
+//!
+//! Parent links are stored with the child, so a parent may live in another
+//! shard and shards do not enforce foreign keys. A batch is written in one
+//! transaction per shard it touches, so a failing shard leaves the others
+//! written. The shard count is recorded in every shard and cannot change
+//! once the shards exist.
+
+use super::backend::{SqliteBackend, StorageBackend, StorageBackendKind, StoredDagNode, StoredTransaction, WriteBatch, WriteOp};
+use super::cursor::TransactionCursor;
+use super::pool::SharedPool;
+use super::{encryption, DatabaseConfig, DatabaseManager};
+use crate::core::NodeStatus;
+use crate::{BlockchainError, TransactionId};
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::SqlitePool;
+use std::cmp::Reverse;
+use std::str::FromStr;
+
+/// Most shards a database can be split into, one per first byte of an ID
+pub const MAX_SHARDS: u32 = 256;
+
+/// Tables, indexes and triggers of a shard
+const SHARD_SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS transactions (
+        id TEXT PRIMARY KEY,
+        sender BLOB NOT NULL,
+        receiver BLOB NOT NULL,
+        amount TEXT NOT NULL,
+        nonce INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        signature BLOB NOT NULL,
+        prime_hash BLOB NOT NULL,
+        resistance_score INTEGER NOT NULL,
+        proof_timestamp INTEGER NOT NULL,
+        metadata BLOB,
+        parents TEXT,
+        signature_scheme TEXT,
+        fee TEXT NOT NULL DEFAULT '0',
+        checksum TEXT,
+        sender_hex TEXT,
+        receiver_hex TEXT
+    )",
+    "CREATE TABLE IF NOT EXISTS dag_nodes (
+        transaction_id TEXT PRIMARY KEY,
+        children TEXT NOT NULL,
+        weight INTEGER NOT NULL,
+        confidence REAL NOT NULL,
+        status TEXT NOT NULL,
+        quantum_score INTEGER NOT NULL,
+        checksum TEXT
+    )",
+    "CREATE TABLE IF NOT EXISTS transaction_parents (
+        transaction_id TEXT NOT NULL,
+        parent_id TEXT NOT NULL,
+        PRIMARY KEY (transaction_id, parent_id)
+    )",
+    "CREATE TABLE IF NOT EXISTS shard_layout (
+        shard INTEGER NOT NULL,
+        shards INTEGER NOT NULL
+    )",
+    "CREATE TRIGGER IF NOT EXISTS transactions_address_hex AFTER INSERT ON transactions BEGIN
+        UPDATE transactions SET sender_hex = lower(hex(NEW.sender)), receiver_hex = lower(hex(NEW.receiver)) WHERE id = NEW.id;
+    END",
+    "CREATE TRIGGER IF NOT EXISTS transactions_address_hex_update AFTER UPDATE OF sender, receiver ON transactions BEGIN
+        UPDATE transactions SET sender_hex = lower(hex(NEW.sender)), receiver_hex = lower(hex(NEW.receiver)) WHERE id = NEW.id;
+    END",
+    "CREATE INDEX IF NOT EXISTS idx_transactions_timestamp_id ON transactions(timestamp, id)",
+    "CREATE INDEX IF NOT EXISTS idx_transactions_sender ON transactions(sender, timestamp)",
+    "CREATE INDEX IF NOT EXISTS idx_transactions_receiver ON transactions(receiver, timestamp)",
+    "CREATE INDEX IF NOT EXISTS idx_transactions_sender_hex ON transactions(sender_hex, timestamp, id)",
+    "CREATE INDEX IF NOT EXISTS idx_transactions_receiver_hex ON transactions(receiver_hex, timestamp, id)",
+    "CREATE INDEX IF NOT EXISTS idx_dag_nodes_status ON dag_nodes(status)",
+    "CREATE INDEX IF NOT EXISTS idx_transaction_parents_parent ON transaction_parents(parent_id)",
+];
+
+/// File of shard `index` of the database at `path`, or of a backup of it
+pub fn shard_path(path: &str, index: usize) -> String {
+    format!("{}.shard{}", path, index)
+}
+
+/// Size and contents of one shard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardStats {
+    pub index: usize,
+    pub path: String,
+    pub transactions: u64,
+    pub dag_nodes: u64,
+    /// Bytes in use by the shard's pages
+    pub size: u64,
+}
+
+struct Shard {
+    path: String,
+    /// Replaced when the shard is restored from a backup
+    pool: SharedPool,
+    backend: SqliteBackend,
+    options: SqliteConnectOptions,
+}
+
+impl Shard {
+    async fn pool(&self) -> SqlitePool {
+        self.pool.get().await
+    }
+
+    async fn init_schema(&self, index: usize, shards: usize) -> Result<(), BlockchainError> {
+        let pool = self.pool().await;
+        for statement in SHARD_SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        let layout: Option<(i64, i64)> = sqlx::query_as("SELECT shard, shards FROM shard_layout")
+            .fetch_optional(&pool)
+            .await?;
+        match layout {
+            None => {
+                sqlx::query("INSERT INTO shard_layout (shard, shards) VALUES (?, ?)")
+                    .bind(index as i64)
+                    .bind(shards as i64)
+                    .execute(&pool)
+                    .await?;
+            }
+            Some((shard, count)) if shard as usize == index && count as usize == shards => {}
+            Some((shard, count)) => {
+                return Err(BlockchainError::Other(format!(
+                    "{} is shard {} of {}, not {} of {}; changing the shard count is not supported",
+                    self.path, shard, count, index, shards
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Transactions and DAG nodes split across SQLite files by ID prefix
+pub struct ShardedBackend {
+    shards: Vec<Shard>,
+}
+
+impl ShardedBackend {
+    /// Open the shards of the database configured by `config`, creating missing ones
+    pub async fn open(config: &DatabaseConfig, shards: u32) -> Result<Self, BlockchainError> {
+        if shards == 0 || shards > MAX_SHARDS {
+            return Err(BlockchainError::Other(format!("Shard count must be between 1 and {}, not {}", MAX_SHARDS, shards)));
+        }
+        let mut opened = Vec::with_capacity(shards as usize);
+        for index in 0..shards as usize {
+            let path = shard_path(&config.path, index);
+            // A parent link may name a transaction in another shard
+            let options = config.pragmas
+                .apply(SqliteConnectOptions::from_str(&format!("sqlite://{}", path))?.create_if_missing(true))
+                .foreign_keys(false);
+            let options = encryption::with_key(options, config.encryption_key.as_deref())?;
+            let pool = SharedPool::new(SqlitePool::connect_with(options.clone()).await?);
+            let shard = Shard { path, backend: SqliteBackend::shared(pool.clone()), pool, options };
+            shard.init_schema(index, shards as usize).await?;
+            opened.push(shard);
+        }
+        log::info!("🧩 Opened {} storage shard(s) next to {}", shards, config.path);
+        Ok(Self { shards: opened })
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Shard holding `tx_id`
+    pub fn shard_of(&self, tx_id: &TransactionId) -> usize {
+        let prefix = tx_id.as_bytes().first().copied().unwrap_or(0) as usize;
+        prefix * self.shards.len() / 256
+    }
+
+    fn backend_of(&self, tx_id: &TransactionId) -> &SqliteBackend {
+        &self.shards[self.shard_of(tx_id)].backend
+    }
+
+    pub async fn stats(&self) -> Result<Vec<ShardStats>, BlockchainError> {
+        try_join_all(self.shards.iter().enumerate().map(|(index, shard)| async move {
+            let pool = shard.pool().await;
+            let (transactions, dag_nodes, size): (i64, i64, i64) = sqlx::query_as(
+                "SELECT (SELECT COUNT(*) FROM transactions), (SELECT COUNT(*) FROM dag_nodes),
+                        (SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size())"
+            )
+            .fetch_one(&pool)
+            .await?;
+            Ok::<_, BlockchainError>(ShardStats {
+                index,
+                path: shard.path.clone(),
+                transactions: transactions as u64,
+                dag_nodes: dag_nodes as u64,
+                size: size as u64,
+            })
+        }))
+        .await
+    }
+
+    /// Copy every shard next to the backup at `backup_path`, returning the copies
+    pub async fn backup_to(&self, backup_path: &str) -> Result<Vec<String>, BlockchainError> {
+        let mut copies = Vec::with_capacity(self.shards.len());
+        for (index, shard) in self.shards.iter().enumerate() {
+            // Move committed pages out of the WAL so the file holds them
+            sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&shard.pool().await).await?;
+            let copy = shard_path(backup_path, index);
+            tokio::fs::copy(&shard.path, &copy).await?;
+            copies.push(copy);
+        }
+        Ok(copies)
+    }
+
+    /// Replace every shard with its copy next to the backup at `backup_path`;
+    /// returns false, changing nothing, if the backup has no copy of a shard
+    pub async fn restore_from(&self, backup_path: &str) -> Result<bool, BlockchainError> {
+        for index in 0..self.shards.len() {
+            if tokio::fs::metadata(shard_path(backup_path, index)).await.is_err() {
+                return Ok(false);
+            }
+        }
+        for (index, shard) in self.shards.iter().enumerate() {
+            let mut pool = shard.pool.lock().await;
+            pool.close().await;
+            let copied = tokio::fs::copy(shard_path(backup_path, index), &shard.path).await;
+            // Reconnect even when the copy failed, so callers never find the pool closed
+            *pool = SqlitePool::connect_with(shard.options.clone()).await?;
+            drop(pool);
+            copied?;
+            shard.init_schema(index, self.shards.len()).await?;
+        }
+        Ok(true)
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageBackend for ShardedBackend {
+    fn kind(&self) -> StorageBackendKind {
+        StorageBackendKind::Sharded { shards: self.shards.len() as u32 }
+    }
+
+    async fn write(&self, batch: WriteBatch) -> Result<(), BlockchainError> {
+        let mut batches = vec![WriteBatch::new(); self.shards.len()];
+        for op in batch.into_ops() {
+            match op {
+                WriteOp::PutTransaction(transaction) => batches[self.shard_of(&transaction.id)].put_transaction(&transaction),
+                WriteOp::PutDagNode(node) => batches[self.shard_of(&node.transaction.id)].put_dag_node(&node),
+                WriteOp::SetStatus { tx_id, status, confidence } => batches[self.shard_of(&tx_id)].set_status(&tx_id, status, confidence),
+            };
+        }
+        try_join_all(self.shards.iter().zip(batches)
+            .filter(|(_, batch)| !batch.is_empty())
+            .map(|(shard, batch)| shard.backend.write(batch)))
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, ids: &[TransactionId]) -> Result<u64, BlockchainError> {
+        let mut grouped = vec![Vec::new(); self.shards.len()];
+        for id in ids {
+            grouped[self.shard_of(id)].push(id.clone());
+        }
+        let deleted = try_join_all(self.shards.iter().zip(&grouped)
+            .filter(|(_, ids)| !ids.is_empty())
+            .map(|(shard, ids)| shard.backend.delete(ids)))
+            .await?;
+        Ok(deleted.into_iter().sum())
+    }
+
+    async fn get_transaction(&self, tx_id: &TransactionId) -> Result<Option<StoredTransaction>, BlockchainError> {
+        self.backend_of(tx_id).get_transaction(tx_id).await
+    }
+
+    async fn get_dag_node(&self, tx_id: &TransactionId) -> Result<Option<StoredDagNode>, BlockchainError> {
+        self.backend_of(tx_id).get_dag_node(tx_id).await
+    }
+
+    async fn child_ids(&self, tx_id: &TransactionId) -> Result<Vec<TransactionId>, BlockchainError> {
+        let children = try_join_all(self.shards.iter().map(|shard| shard.backend.child_ids(tx_id))).await?;
+        // Shards hold consecutive key ranges, so their results are already in order
+        Ok(children.into_iter().flatten().collect())
+    }
+
+    async fn scan_transactions(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let first = after.as_ref().map_or(0, |id| self.shard_of(id));
+        let mut transactions = Vec::new();
+        for shard in &self.shards[first..] {
+            if transactions.len() >= limit {
+                break;
+            }
+            transactions.extend(shard.backend.scan_transactions(after.clone(), limit - transactions.len()).await?);
+        }
+        Ok(transactions)
+    }
+
+    async fn scan_dag_nodes(&self, after: Option<TransactionId>, limit: usize) -> Result<Vec<StoredDagNode>, BlockchainError> {
+        let first = after.as_ref().map_or(0, |id| self.shard_of(id));
+        let mut nodes = Vec::new();
+        for shard in &self.shards[first..] {
+            if nodes.len() >= limit {
+                break;
+            }
+            nodes.extend(shard.backend.scan_dag_nodes(after.clone(), limit - nodes.len()).await?);
+        }
+        Ok(nodes)
+    }
+
+    async fn transaction_count(&self) -> Result<u64, BlockchainError> {
+        let counts = try_join_all(self.shards.iter().map(|shard| shard.backend.transaction_count())).await?;
+        Ok(counts.into_iter().sum())
+    }
+
+    async fn dag_nodes_with_status(&self, status: &NodeStatus) -> Result<Vec<StoredDagNode>, BlockchainError> {
+        let nodes = try_join_all(self.shards.iter().map(|shard| shard.backend.dag_nodes_with_status(status))).await?;
+        Ok(nodes.into_iter().flatten().collect())
+    }
+
+    async fn count_dag_nodes(&self, status: &NodeStatus) -> Result<u64, BlockchainError> {
+        let counts = try_join_all(self.shards.iter().map(|shard| shard.backend.count_dag_nodes(status))).await?;
+        Ok(counts.into_iter().sum())
+    }
+
+    async fn recent_transactions(
+        &self,
+        status: Option<&NodeStatus>,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        // Each shard returns enough to fill the page however the shards interleave
+        let per_shard = limit.map(|limit| limit + offset);
+        let pages = try_join_all(self.shards.iter().map(|shard| shard.backend.recent_transactions(status, per_shard, 0))).await?;
+        let mut transactions: Vec<StoredTransaction> = pages.into_iter().flatten().collect();
+        transactions.sort_by_key(|stored| Reverse(stored.transaction.timestamp));
+        Ok(transactions.into_iter().skip(offset).take(limit.unwrap_or(usize::MAX)).collect())
+    }
+
+    async fn transactions_before(
+        &self,
+        status: Option<&NodeStatus>,
+        cursor: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let pages = try_join_all(self.shards.iter().map(|shard| shard.backend.transactions_before(status, cursor, limit))).await?;
+        let mut transactions: Vec<StoredTransaction> = pages.into_iter().flatten().collect();
+        transactions.sort_by_key(|stored| Reverse(TransactionCursor::of(&stored.transaction)));
+        transactions.truncate(limit);
+        Ok(transactions)
+    }
+
+    async fn transactions_by_address(&self, address: &[u8], limit: usize, offset: usize) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let pages = try_join_all(self.shards.iter().map(|shard| shard.backend.transactions_by_address(address, limit + offset, 0))).await?;
+        let mut transactions: Vec<StoredTransaction> = pages.into_iter().flatten().collect();
+        transactions.sort_by_key(|stored| (Reverse(stored.transaction.timestamp), stored.transaction.id.as_string()));
+        Ok(transactions.into_iter().skip(offset).take(limit).collect())
+    }
+
+    async fn address_history(
+        &self,
+        address: &[u8],
+        cursor: Option<&TransactionCursor>,
+        limit: usize,
+    ) -> Result<Vec<StoredTransaction>, BlockchainError> {
+        let pages = try_join_all(self.shards.iter().map(|shard| shard.backend.address_history(address, cursor, limit))).await?;
+        let mut transactions: Vec<StoredTransaction> = pages.into_iter().flatten().collect();
+        transactions.sort_by_key(|stored| Reverse(TransactionCursor::of(&stored.transaction)));
+        transactions.truncate(limit);
+        Ok(transactions)
+    }
+
+    async fn genesis_transaction(&self) -> Result<Option<StoredTransaction>, BlockchainError> {
+        let candidates = try_join_all(self.shards.iter().map(|shard| shard.backend.genesis_transaction())).await?;
+        Ok(candidates.into_iter().flatten().min_by_key(|stored| stored.transaction.timestamp))
+    }
+}
+
+impl DatabaseManager {
+    /// Contents and size of each shard; empty unless the sharded backend is used
+    pub async fn shard_stats(&self) -> Result<Vec<ShardStats>, BlockchainError> {
+        match &self.shards {
+            Some(shards) => shards.stats().await,
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{QuantumProof, Transaction};
+    use tempfile::TempDir;
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000 + nonce,
+            parents,
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    fn config(temp_dir: &TempDir, shards: u32) -> DatabaseConfig {
+        DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            backend: StorageBackendKind::Sharded { shards },
+            ..DatabaseConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queries_span_every_shard() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(config(&temp_dir, 4)).await.unwrap();
+
+        let genesis = transaction(0, vec![]);
+        let mut chain = vec![genesis.clone()];
+        for nonce in 1..40 {
+            chain.push(transaction(nonce, vec![genesis.id.clone()]));
+        }
+        for transaction in &chain {
+            db.store_transaction(transaction).await.unwrap();
+        }
+
+        let stats = db.shard_stats().await.unwrap();
+        assert_eq!(stats.iter().map(|shard| shard.transactions).sum::<u64>(), 40);
+        assert!(stats.iter().filter(|shard| shard.transactions > 0).count() > 1);
+        assert_eq!(db.get_stats().await.unwrap().total_transactions, 40);
+
+        // Children live in other shards than their parent
+        let mut ids: Vec<String> = chain[1..].iter().map(|transaction| transaction.id.as_string()).collect();
+        ids.sort();
+        let children: Vec<String> = db.backend.child_ids(&genesis.id).await.unwrap().iter().map(TransactionId::as_string).collect();
+        assert_eq!(children, ids);
+
+        // Scans page through the shards in key order
+        let mut scanned = Vec::new();
+        loop {
+            let page = db.backend.scan_transactions(scanned.last().map(|id: &String| TransactionId::from_string(id).unwrap()), 7).await.unwrap();
+            if page.is_empty() {
+                break;
+            }
+            scanned.extend(page.into_iter().map(|stored| stored.transaction.id.as_string()));
+        }
+        ids.push(genesis.id.as_string());
+        ids.sort();
+        assert_eq!(scanned, ids);
+
+        let recent = db.backend.recent_transactions(None, Some(3), 1).await.unwrap();
+        assert_eq!(recent.iter().map(|stored| stored.transaction.nonce).collect::<Vec<_>>(), vec![38, 37, 36]);
+        assert_eq!(db.get_genesis_transaction().await.unwrap().unwrap().id, genesis.id);
+    }
+
+    #[tokio::test]
+    async fn test_shard_count_is_fixed_and_backups_cover_shards() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(config(&temp_dir, 2)).await.unwrap();
+        let genesis = transaction(0, vec![]);
+        db.store_transaction(&genesis).await.unwrap();
+
+        let backup_path = temp_dir.path().join("backup.db").to_string_lossy().to_string();
+        let backup = db.create_backup(&backup_path).await.unwrap();
+        assert_eq!(backup.metadata.get("shards").map(String::as_str), Some("2"));
+        db.store_transaction(&transaction(1, vec![genesis.id.clone()])).await.unwrap();
+
+        db.restore_from_backup(&backup_path).await.unwrap();
+        assert_eq!(db.get_transaction_count().await.unwrap(), 1);
+        drop(db);
+
+        assert!(DatabaseManager::new(config(&temp_dir, 3)).await.is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! State snapshots for upgrade validation
+//!
+//! A snapshot records the state a node has agreed on: the consensus height,
+//! the set of confirmed or finalized transactions and the net balance each
+//! account has from them. Operators take one before and one after an upgrade
+//! and diff the two. Any divergence is reported with a bounded sample of the
+//! accounts and transactions involved.
+
+use crate::core::Transaction;
+use crate::BlockchainError;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Divergent items listed per category when no limit is given
+pub const DEFAULT_DIFF_SAMPLES: usize = 10;
+
+/// Agreed state of a node at a point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// Node version that took the snapshot
+    pub node_version: String,
+    pub taken_at: u64,
+    pub consensus_height: u64,
+    /// Net balance change per account (hex public key) over finalized transactions;
+    /// senders are debited the fee as well, validator fee credits are not included
+    pub balances: BTreeMap<String, i128>,
+    /// IDs of confirmed and finalized transactions
+    pub finalized: BTreeSet<String>,
+    /// Digest over balances and finalized transactions
+    pub state_root: String,
+}
+
+impl StateSnapshot {
+    /// Snapshot the state produced by `finalized` transactions at `consensus_height`
+    pub fn from_transactions<'a>(consensus_height: u64, finalized: impl IntoIterator<Item = &'a Transaction>) -> Self {
+        let mut balances: BTreeMap<String, i128> = BTreeMap::new();
+        let mut ids = BTreeSet::new();
+        for transaction in finalized {
+            *balances.entry(hex::encode(&transaction.sender)).or_default() -= transaction.amount as i128 + transaction.fee as i128;
+            *balances.entry(hex::encode(&transaction.receiver)).or_default() += transaction.amount as i128;
+            ids.insert(transaction.id.as_string());
+        }
+
+        Self {
+            node_version: env!("CARGO_PKG_VERSION").to_string(),
+            taken_at: chrono::Utc::now().timestamp() as u64,
+            consensus_height,
+            state_root: state_root(&balances, &ids),
+            balances,
+            finalized: ids,
+        }
+    }
+
+    /// Read a snapshot saved with `save`
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, BlockchainError> {
+        let data = tokio::fs::read(path).await?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Write the snapshot as JSON
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), BlockchainError> {
+        tokio::fs::write(path, serde_json::to_vec_pretty(self)?).await?;
+        Ok(())
+    }
+
+    /// Compare with a later snapshot, listing up to `samples` divergent items per category
+    pub fn diff(&self, after: &StateSnapshot, samples: usize) -> SnapshotDiff {
+        let mut balances = DivergenceSet::default();
+        let accounts: BTreeSet<&String> = self.balances.keys().chain(after.balances.keys()).collect();
+        for account in accounts {
+            let (before_balance, after_balance) = (self.balances.get(account), after.balances.get(account));
+            if before_balance != after_balance {
+                balances.push(BalanceDivergence {
+                    account: account.clone(),
+                    before: before_balance.copied(),
+                    after: after_balance.copied(),
+                }, samples);
+            }
+        }
+
+        let mut missing = DivergenceSet::default();
+        for id in self.finalized.difference(&after.finalized) {
+            missing.push(id.clone(), samples);
+        }
+        let mut added = DivergenceSet::default();
+        for id in after.finalized.difference(&self.finalized) {
+            added.push(id.clone(), samples);
+        }
+
+        SnapshotDiff {
+            before_height: self.consensus_height,
+            after_height: after.consensus_height,
+            state_root_matches: self.state_root == after.state_root,
+            balances,
+            missing_finalized: missing,
+            added_finalized: added,
+        }
+    }
+}
+
+fn state_root(balances: &BTreeMap<String, i128>, finalized: &BTreeSet<String>) -> String {
+    let mut hasher = Sha3_256::new();
+    for (account, balance) in balances {
+        hasher.update(account.as_bytes());
+        hasher.update(balance.to_le_bytes());
+    }
+    for id in finalized {
+        hasher.update(id.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Account whose balance differs between snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceDivergence {
+    pub account: String,
+    pub before: Option<i128>,
+    pub after: Option<i128>,
+}
+
+/// Count of divergent items and the first few of them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceSet<T> {
+    pub count: usize,
+    pub samples: Vec<T>,
+}
+
+impl<T> Default for DivergenceSet<T> {
+    fn default() -> Self {
+        Self { count: 0, samples: Vec::new() }
+    }
+}
+
+impl<T> DivergenceSet<T> {
+    fn push(&mut self, item: T, samples: usize) {
+        self.count += 1;
+        if self.samples.len() < samples {
+            self.samples.push(item);
+        }
+    }
+}
+
+/// Differences between two state snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub before_height: u64,
+    pub after_height: u64,
+    pub state_root_matches: bool,
+    pub balances: DivergenceSet<BalanceDivergence>,
+    /// Finalized before but not after
+    pub missing_finalized: DivergenceSet<String>,
+    /// Finalized after but not before
+    pub added_finalized: DivergenceSet<String>,
+}
+
+impl SnapshotDiff {
+    /// Whether the snapshots hold the same state at the same height
+    pub fn is_identical(&self) -> bool {
+        self.state_root_matches && self.before_height == self.after_height
+    }
+
+    /// Whether the later snapshot only extends the earlier one
+    ///
+    /// A node that kept running between snapshots finalizes new transactions
+    /// and advances its height, but must never lose a finalized transaction.
+    pub fn is_consistent_extension(&self) -> bool {
+        self.missing_finalized.count == 0 && self.after_height >= self.before_height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Amount, QuantumProof};
+    use crate::TransactionId;
+
+    fn transfer(sender: u8, receiver: u8, amount: Amount) -> Transaction {
+        Transaction {
+            id: TransactionId::new(),
+            sender: vec![sender; 32],
+            receiver: vec![receiver; 32],
+            amount,
+            fee: 0,
+            nonce: 0,
+            timestamp: 0,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof {
+                prime_hash: vec![],
+                resistance_score: 0,
+                proof_timestamp: 0,
+            },
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_identical_snapshots() {
+        let transactions = vec![transfer(1, 2, 50), transfer(2, 3, 20)];
+        let before = StateSnapshot::from_transactions(7, &transactions);
+        let after = StateSnapshot::from_transactions(7, transactions.iter().rev());
+
+        assert_eq!(before.balances[&hex::encode([2u8; 32])], 30);
+        let diff = before.diff(&after, DEFAULT_DIFF_SAMPLES);
+        assert!(diff.is_identical());
+        assert_eq!(diff.balances.count, 0);
+    }
+
+    #[test]
+    fn test_divergence_is_sampled() {
+        let transactions: Vec<_> = (0..5).map(|i| transfer(1, 10 + i, 1)).collect();
+        let before = StateSnapshot::from_transactions(3, &transactions);
+        let after = StateSnapshot::from_transactions(4, &transactions[2..]);
+
+        let diff = before.diff(&after, 1);
+        assert!(!diff.is_identical());
+        assert!(!diff.is_consistent_extension());
+        assert_eq!(diff.missing_finalized.count, 2);
+        assert_eq!(diff.missing_finalized.samples.len(), 1);
+        // Sender plus the two receivers that lost their transfer
+        assert_eq!(diff.balances.count, 3);
+        assert_eq!(diff.added_finalized.count, 0);
+
+        let mut grown = transactions.clone();
+        grown.push(transfer(1, 20, 1));
+        let extension = before.diff(&StateSnapshot::from_transactions(4, &grown), 1);
+        assert!(extension.is_consistent_extension());
+        assert_eq!(extension.added_finalized.count, 1);
+    }
+}
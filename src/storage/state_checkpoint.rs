@@ -0,0 +1,315 @@
+//! Materialized state checkpoints
+//!
+//! A state checkpoint copies the account balances and the IDs of the
+//! finalized transactions applied to them at a consensus height into
+//! tables of their own, next to the finalized tips of the DAG at that
+//! height. All of it is copied in one database transaction, so the
+//! balances and applied IDs always match. Bootstrap snapshots served to
+//! fast-syncing peers are built from a checkpoint rather than from the live
+//! tables, and a node bootstrapped from a snapshot records it as its first
+//! checkpoint, so neither side replays history.
+//!
+//! Only the newest `StateCheckpointConfig::keep` checkpoints are kept.
+
+use super::bootstrap::SnapshotAccount;
+use super::DatabaseManager;
+use crate::core::{DAGCore, DAGNode, NodeStatus};
+use crate::{BlockchainError, TransactionId};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use sqlx::Row;
+use std::collections::HashSet;
+use tokio::sync::RwLock;
+
+/// Periodic state checkpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateCheckpointConfig {
+    pub enabled: bool,
+    /// Seconds between checkpoints
+    pub interval_secs: u64,
+    /// Checkpoints kept, newest first
+    pub keep: usize,
+}
+
+impl Default for StateCheckpointConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 600,
+            keep: 2,
+        }
+    }
+}
+
+/// A recorded checkpoint, without its balances and applied IDs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateCheckpoint {
+    pub height: u64,
+    pub taken_at: i64,
+    pub genesis: TransactionId,
+    /// Finalized transactions without a finalized child
+    pub finalized_tips: Vec<TransactionId>,
+    pub accounts: u64,
+    pub applied_transactions: u64,
+    /// Digest over the height, genesis, balances, applied IDs and tips
+    pub state_root: String,
+}
+
+/// Finalized nodes none of whose children are finalized, sorted
+pub fn finalized_tips(nodes: &[&DAGNode]) -> Vec<TransactionId> {
+    let finalized: HashSet<&TransactionId> = nodes.iter()
+        .filter(|node| node.status == NodeStatus::Finalized)
+        .map(|node| &node.transaction.id)
+        .collect();
+    let mut tips: Vec<TransactionId> = nodes.iter()
+        .filter(|node| node.status == NodeStatus::Finalized)
+        .filter(|node| !node.children.iter().any(|child| finalized.contains(child)))
+        .map(|node| node.transaction.id.clone())
+        .collect();
+    tips.sort_by_key(|id| id.as_string());
+    tips
+}
+
+/// Record a checkpoint of `dag`'s finalized tips and `database`'s balances at `height`
+pub async fn checkpoint_state(
+    dag: &RwLock<DAGCore>,
+    database: &DatabaseManager,
+    height: u64,
+    keep: usize,
+) -> Result<StateCheckpoint, BlockchainError> {
+    let (genesis, tips) = {
+        let dag = dag.read().await;
+        let genesis = dag.genesis_id().cloned()
+            .ok_or_else(|| BlockchainError::Other("DAG has no genesis transaction".to_string()))?;
+        let nodes: Vec<&DAGNode> = dag.loaded_nodes().collect();
+        (genesis, finalized_tips(&nodes))
+    };
+    database.record_state_checkpoint(height, &genesis, &tips, keep).await
+}
+
+impl DatabaseManager {
+    /// Copy the current balances and applied IDs into a checkpoint at `height`,
+    /// replacing any checkpoint already there, and drop all but the newest `keep`
+    pub async fn record_state_checkpoint(
+        &self,
+        height: u64,
+        genesis: &TransactionId,
+        finalized_tips: &[TransactionId],
+        keep: usize,
+    ) -> Result<StateCheckpoint, BlockchainError> {
+        self.commit_queued().await?;
+        let height_key = height as i64;
+        let mut tx = self.pool().await.begin().await?;
+
+        for table in ["state_checkpoint_balances", "state_checkpoint_applied", "state_checkpoint_tips"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE height = ?", table)).bind(height_key).execute(&mut *tx).await?;
+        }
+        let accounts = sqlx::query(
+            "INSERT INTO state_checkpoint_balances (height, address, balance) SELECT ?, address, balance FROM account_balances"
+        )
+        .bind(height_key)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let applied_transactions = sqlx::query(
+            "INSERT INTO state_checkpoint_applied (height, transaction_id) SELECT ?, transaction_id FROM applied_transactions"
+        )
+        .bind(height_key)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        for tip in finalized_tips {
+            sqlx::query("INSERT OR IGNORE INTO state_checkpoint_tips (height, transaction_id) VALUES (?, ?)")
+                .bind(height_key)
+                .bind(tip.as_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let mut tips = finalized_tips.to_vec();
+        tips.sort_by_key(|id| id.as_string());
+        tips.dedup();
+        let mut checkpoint = StateCheckpoint {
+            height,
+            taken_at: Utc::now().timestamp(),
+            genesis: genesis.clone(),
+            finalized_tips: tips,
+            accounts,
+            applied_transactions,
+            state_root: String::new(),
+        };
+
+        // Hashed from the copies, as a reader of the checkpoint would
+        let mut hasher = Sha3_256::new();
+        hasher.update(height.to_le_bytes());
+        hasher.update(genesis.as_bytes());
+        let balances = sqlx::query("SELECT address, balance FROM state_checkpoint_balances WHERE height = ? ORDER BY address")
+            .bind(height_key)
+            .fetch_all(&mut *tx)
+            .await?;
+        for row in &balances {
+            hasher.update(row.get::<String, _>("address").as_bytes());
+            hasher.update(super::accounts::read_amount(row, "balance")?.to_le_bytes());
+        }
+        let applied: Vec<String> = sqlx::query_scalar("SELECT transaction_id FROM state_checkpoint_applied WHERE height = ? ORDER BY transaction_id")
+            .bind(height_key)
+            .fetch_all(&mut *tx)
+            .await?;
+        for id in &applied {
+            hasher.update(id.as_bytes());
+        }
+        for tip in &checkpoint.finalized_tips {
+            hasher.update(tip.as_bytes());
+        }
+        checkpoint.state_root = hex::encode(hasher.finalize());
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO state_checkpoints (height, taken_at, genesis_id, state_root, accounts, applied_transactions) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(height_key)
+        .bind(checkpoint.taken_at)
+        .bind(genesis.as_string())
+        .bind(&checkpoint.state_root)
+        .bind(accounts as i64)
+        .bind(applied_transactions as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        // Everything older than the `keep` newest goes
+        let oldest_kept: Option<i64> = sqlx::query_scalar("SELECT height FROM state_checkpoints ORDER BY height DESC LIMIT 1 OFFSET ?")
+            .bind(keep.max(1) as i64 - 1)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if let Some(oldest_kept) = oldest_kept {
+            for table in ["state_checkpoint_balances", "state_checkpoint_applied", "state_checkpoint_tips", "state_checkpoints"] {
+                sqlx::query(&format!("DELETE FROM {} WHERE height < ?", table)).bind(oldest_kept).execute(&mut *tx).await?;
+            }
+        }
+        tx.commit().await?;
+
+        log::info!(
+            "📸 Recorded state checkpoint at height {} with {} account(s) and {} applied transaction(s)",
+            height, accounts, applied_transactions
+        );
+        Ok(checkpoint)
+    }
+
+    /// Recorded checkpoints, newest first
+    pub async fn state_checkpoints(&self) -> Result<Vec<StateCheckpoint>, BlockchainError> {
+        let rows = sqlx::query("SELECT height, taken_at, genesis_id, state_root, accounts, applied_transactions FROM state_checkpoints ORDER BY height DESC")
+            .fetch_all(&self.pool().await)
+            .await?;
+        let mut checkpoints = Vec::with_capacity(rows.len());
+        for row in rows {
+            let height: i64 = row.get("height");
+            let tips: Vec<String> = sqlx::query_scalar("SELECT transaction_id FROM state_checkpoint_tips WHERE height = ? ORDER BY transaction_id")
+                .bind(height)
+                .fetch_all(&self.pool().await)
+                .await?;
+            checkpoints.push(StateCheckpoint {
+                height: height as u64,
+                taken_at: row.get("taken_at"),
+                genesis: TransactionId::from_string(&row.get::<String, _>("genesis_id"))?,
+                finalized_tips: tips.iter().map(|id| TransactionId::from_string(id)).collect::<Result<_, _>>()?,
+                accounts: row.get::<i64, _>("accounts") as u64,
+                applied_transactions: row.get::<i64, _>("applied_transactions") as u64,
+                state_root: row.get("state_root"),
+            });
+        }
+        Ok(checkpoints)
+    }
+
+    /// Newest recorded checkpoint
+    pub async fn latest_state_checkpoint(&self) -> Result<Option<StateCheckpoint>, BlockchainError> {
+        Ok(self.state_checkpoints().await?.into_iter().next())
+    }
+
+    /// Balances of the checkpoint at `height`
+    pub async fn state_checkpoint_accounts(&self, height: u64) -> Result<Vec<SnapshotAccount>, BlockchainError> {
+        let rows = sqlx::query("SELECT address, balance FROM state_checkpoint_balances WHERE height = ? ORDER BY address")
+            .bind(height as i64)
+            .fetch_all(&self.pool().await)
+            .await?;
+        rows.into_iter()
+            .map(|row| Ok(SnapshotAccount {
+                address: row.get("address"),
+                balance: super::accounts::read_amount(&row, "balance")?,
+            }))
+            .collect()
+    }
+
+    /// IDs of the finalized transactions applied to the balances of the checkpoint at `height`
+    pub async fn state_checkpoint_applied(&self, height: u64) -> Result<Vec<String>, BlockchainError> {
+        Ok(sqlx::query_scalar("SELECT transaction_id FROM state_checkpoint_applied WHERE height = ? ORDER BY transaction_id")
+            .bind(height as i64)
+            .fetch_all(&self.pool().await)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+
+    fn node(nonce: u64, children: Vec<TransactionId>, status: NodeStatus) -> DAGNode {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 1,
+            nonce,
+            timestamp: 1_000 + nonce,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![0u8; 32], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        DAGNode { transaction, children, weight: 1, confidence: 1.0, status, quantum_score: 80 }
+    }
+
+    #[test]
+    fn test_finalized_tips_skip_nodes_with_finalized_children() {
+        let pending = node(3, vec![], NodeStatus::Pending);
+        let tip = node(2, vec![pending.transaction.id.clone()], NodeStatus::Finalized);
+        let genesis = node(1, vec![tip.transaction.id.clone()], NodeStatus::Finalized);
+        assert_eq!(finalized_tips(&[&genesis, &tip, &pending]), vec![tip.transaction.id.clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_copy_state_and_keep_the_newest() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        let genesis = node(1, vec![], NodeStatus::Finalized).transaction.id;
+        sqlx::query("INSERT INTO account_balances (address, balance, updated_at) VALUES ('aa', '7', 0)")
+            .execute(&db.pool().await)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO applied_transactions (transaction_id, applied_at) VALUES ('t1', 0)")
+            .execute(&db.pool().await)
+            .await
+            .unwrap();
+
+        let first = db.record_state_checkpoint(1, &genesis, &[genesis.clone()], 2).await.unwrap();
+        assert_eq!((first.accounts, first.applied_transactions), (1, 1));
+
+        // Later changes leave the checkpoint as it was
+        sqlx::query("UPDATE account_balances SET balance = '9'").execute(&db.pool().await).await.unwrap();
+        assert_eq!(db.state_checkpoint_accounts(1).await.unwrap()[0].balance, 7);
+        assert_eq!(db.state_checkpoint_applied(1).await.unwrap(), vec!["t1".to_string()]);
+
+        let second = db.record_state_checkpoint(2, &genesis, &[genesis.clone()], 2).await.unwrap();
+        assert_ne!(second.state_root, first.state_root);
+        db.record_state_checkpoint(3, &genesis, &[genesis.clone()], 2).await.unwrap();
+
+        let heights: Vec<u64> = db.state_checkpoints().await.unwrap().iter().map(|checkpoint| checkpoint.height).collect();
+        assert_eq!(heights, vec![3, 2]);
+        assert!(db.state_checkpoint_accounts(1).await.unwrap().is_empty());
+        assert_eq!(db.latest_state_checkpoint().await.unwrap().unwrap().finalized_tips, vec![genesis]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Archival and pruned storage modes
+//!
+//! Archival nodes keep every transaction. Pruned nodes keep the bodies of
+//! the transactions finalized in their newest `keep_checkpoints` finality
+//! milestones, and delete older ones from the backend with their DAG nodes
+//! each time finality advances. The fields a transaction's ID is hashed
+//! from, its `ProofLink`, stay in `pruned_transactions`, so inclusion
+//! proofs through it can still be checked and `get_transaction` answers
+//! `BlockchainError::Pruned` rather than not found.
+//!
+//! A transaction is only pruned together with, or after, every stored
+//! transaction naming it as a parent, since their parent links reference
+//! it. The genesis transaction is never pruned.
+
+use super::DatabaseManager;
+use crate::core::ProofLink;
+use crate::{BlockchainError, TransactionId};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+
+/// Which transaction bodies a node keeps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageMode {
+    /// Every transaction
+    #[default]
+    Archival,
+    /// Those finalized in the newest `keep_checkpoints` milestones, and
+    /// those not finalized yet
+    Pruned { keep_checkpoints: u64 },
+}
+
+/// What is kept of a pruned transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrunedHeader {
+    pub tx_id: TransactionId,
+    pub finalization_height: u64,
+    pub pruned_at: i64,
+    pub link: ProofLink,
+}
+
+impl StorageMode {
+    pub fn validate(&self) -> Result<(), BlockchainError> {
+        if *self == (StorageMode::Pruned { keep_checkpoints: 0 }) {
+            // Receipts of the newest height are written after it finalizes
+            return Err(BlockchainError::Other("A pruned node must keep at least one checkpoint".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl DatabaseManager {
+    pub fn storage_mode(&self) -> StorageMode {
+        self.mode
+    }
+
+    /// Delete the bodies of transactions finalized before the newest
+    /// `keep_checkpoints` milestones, returning how many were deleted; does
+    /// nothing on an archival node
+    pub async fn prune_bodies(&self) -> Result<u64, BlockchainError> {
+        let StorageMode::Pruned { keep_checkpoints } = self.mode else {
+            return Ok(0);
+        };
+        let cutoff: Option<i64> = sqlx::query_scalar(
+            "SELECT DISTINCT height FROM finality_milestones ORDER BY height DESC LIMIT 1 OFFSET ?"
+        )
+        .bind(keep_checkpoints as i64)
+        .fetch_optional(&self.pool().await)
+        .await?;
+        let Some(cutoff) = cutoff else {
+            return Ok(0);
+        };
+        self.commit_queued().await?;
+
+        // Heights from the last pruned one, which may have left transactions
+        // waiting for their children
+        let rows = sqlx::query(
+            "SELECT m.height, m.transaction_id FROM finality_milestones m
+             WHERE m.height >= (SELECT IFNULL(MAX(finalization_height), 0) FROM pruned_transactions) AND m.height <= ?
+               AND NOT EXISTS (SELECT 1 FROM pruned_transactions p WHERE p.transaction_id = m.transaction_id)"
+        )
+        .bind(cutoff)
+        .fetch_all(&self.pool().await)
+        .await?;
+
+        let mut candidates = HashMap::new();
+        for row in rows {
+            let tx_id = TransactionId::from_string(&row.get::<String, _>("transaction_id"))?;
+            let Some(stored) = self.backend.get_transaction(&tx_id).await? else {
+                continue;
+            };
+            if stored.transaction.parents.is_empty() {
+                continue;
+            }
+            let children = self.backend.child_ids(&tx_id).await?;
+            let height = row.get::<i64, _>("height") as u64;
+            candidates.insert(tx_id, (height, stored.transaction, children));
+        }
+
+        // Drop candidates with a child that stays, until none are left
+        loop {
+            let staying: Vec<TransactionId> = candidates.iter()
+                .filter(|(_, (_, _, children))| children.iter().any(|child| !candidates.contains_key(child)))
+                .map(|(id, _)| id.clone())
+                .collect();
+            if staying.is_empty() {
+                break;
+            }
+            for id in staying {
+                candidates.remove(&id);
+            }
+        }
+        if candidates.is_empty() {
+            return Ok(0);
+        }
+
+        let now = Utc::now().timestamp();
+        let mut tx = self.pool().await.begin().await?;
+        for (id, (height, transaction, _)) in &candidates {
+            sqlx::query("INSERT OR IGNORE INTO pruned_transactions (transaction_id, finalization_height, pruned_at, link) VALUES (?, ?, ?, ?)")
+                .bind(id.as_string())
+                .bind(*height as i64)
+                .bind(now)
+                .bind(serde_json::to_string(&ProofLink::from_transaction(transaction))?)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        // Children go before the parents their links reference
+        let mut order = Vec::with_capacity(candidates.len());
+        let mut placed = HashSet::new();
+        while order.len() < candidates.len() {
+            for (id, (_, _, children)) in &candidates {
+                if !placed.contains(id) && children.iter().all(|child| placed.contains(child)) {
+                    placed.insert(id.clone());
+                    order.push(id.clone());
+                }
+            }
+        }
+        let deleted = self.backend.delete(&order).await?;
+        log::info!("✂️ Pruned the bodies of {} transaction(s) finalized at or below height {}", deleted, cutoff);
+        Ok(deleted)
+    }
+
+    /// What is kept of a pruned transaction
+    pub async fn get_pruned_header(&self, tx_id: &TransactionId) -> Result<Option<PrunedHeader>, BlockchainError> {
+        let row = sqlx::query("SELECT finalization_height, pruned_at, link FROM pruned_transactions WHERE transaction_id = ?")
+            .bind(tx_id.as_string())
+            .fetch_optional(&self.pool().await)
+            .await?;
+        row.map(|row| {
+            Ok(PrunedHeader {
+                tx_id: tx_id.clone(),
+                finalization_height: row.get::<i64, _>("finalization_height") as u64,
+                pruned_at: row.get("pruned_at"),
+                link: serde_json::from_str(&row.get::<String, _>("link"))?,
+            })
+        })
+        .transpose()
+    }
+
+    pub(crate) async fn is_pruned(&self, tx_id: &TransactionId) -> Result<bool, BlockchainError> {
+        let pruned: Option<i64> = sqlx::query_scalar("SELECT 1 FROM pruned_transactions WHERE transaction_id = ?")
+            .bind(tx_id.as_string())
+            .fetch_optional(&self.pool().await)
+            .await?;
+        Ok(pruned.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 5,
+            fee: 0,
+            nonce,
+            timestamp: 1_700_000_000 + nonce,
+            parents,
+            signature: vec![3u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![4u8; 32], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    #[tokio::test]
+    async fn test_pruned_node_keeps_headers_of_old_checkpoints() {
+        let db = DatabaseManager::new(DatabaseConfig {
+            mode: StorageMode::Pruned { keep_checkpoints: 1 },
+            ..DatabaseConfig::in_memory()
+        }).await.unwrap();
+
+        let genesis = transaction(0, vec![]);
+        let first = transaction(1, vec![genesis.id.clone()]);
+        let second = transaction(2, vec![first.id.clone()]);
+        let pending = transaction(3, vec![second.id.clone()]);
+        for (height, transaction) in [(1, &genesis), (2, &first), (3, &second)] {
+            db.store_transaction(transaction).await.unwrap();
+            db.record_milestone(height, &[transaction.id.clone()]).await.unwrap();
+        }
+        db.store_transaction(&pending).await.unwrap();
+
+        // Only height 1 and 2 are old enough, and `first` has a child that stays
+        assert_eq!(db.prune_bodies().await.unwrap(), 0);
+        db.record_milestone(4, &[pending.id.clone()]).await.unwrap();
+        // `second` has a child not finalized in time, holding back its ancestors
+        assert_eq!(db.prune_bodies().await.unwrap(), 0);
+        db.record_milestone(5, &[TransactionId::new()]).await.unwrap();
+        assert_eq!(db.prune_bodies().await.unwrap(), 3);
+
+        assert!(matches!(db.get_transaction(&first.id).await, Err(BlockchainError::Pruned(id)) if id == first.id));
+        let header = db.get_pruned_header(&second.id).await.unwrap().unwrap();
+        assert_eq!((header.finalization_height, header.link.compute_id().unwrap()), (3, second.id.clone()));
+        assert!(db.get_transaction(&genesis.id).await.unwrap().is_some());
+        assert!(db.get_transaction(&TransactionId::new()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_archival_node_keeps_everything() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+        let genesis = transaction(0, vec![]);
+        let first = transaction(1, vec![genesis.id.clone()]);
+        for (height, transaction) in [(1, &genesis), (2, &first)] {
+            db.store_transaction(transaction).await.unwrap();
+            db.record_milestone(height, &[transaction.id.clone()]).await.unwrap();
+        }
+        assert_eq!(db.prune_bodies().await.unwrap(), 0);
+        assert!(db.get_transaction(&first.id).await.unwrap().is_some());
+    }
+}
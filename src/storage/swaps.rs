@@ -0,0 +1,270 @@
+//! Swap escrow
+//!
+//! `swaps` holds every atomic swap and the legs it has locked. Locked legs
+//! are taken out of the parties' balances when they are locked and paid out
+//! on claim or refund, each step in one storage transaction together with
+//! the swap's status, so escrowed funds are never counted twice or lost.
+
+use super::accounts::{read_amount, stored_amount};
+use super::tokens::{credit_asset, debit_asset};
+use super::DatabaseManager;
+use crate::core::{swap_hashlock, Asset, AtomicSwap, CoreError, SwapLeg, SwapStatus};
+use crate::{BlockchainError, TransactionId};
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+
+const SWAP_COLUMNS: &str = "hashlock, initiator, initiator_asset, initiator_amount, counterparty, counter_asset, counter_amount, \
+     expires_at, status, preimage, initiated_by, updated_at";
+
+fn invalid(hashlock: &str, reason: &str) -> BlockchainError {
+    CoreError::InvalidSwap { hashlock: hashlock.to_string(), reason: reason.to_string() }.into()
+}
+
+impl DatabaseManager {
+    /// Record a new swap and lock the initiator's leg
+    pub async fn initiate_swap(&self, swap: &AtomicSwap) -> Result<(), BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        let inserted = sqlx::query(&format!(
+            "INSERT OR IGNORE INTO swaps ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            SWAP_COLUMNS
+        ))
+        .bind(&swap.hashlock)
+        .bind(&swap.initiator.party)
+        .bind(swap.initiator.asset.as_column())
+        .bind(stored_amount(swap.initiator.amount))
+        .bind(&swap.counterparty.party)
+        .bind(swap.counterparty.asset.as_column())
+        .bind(stored_amount(swap.counterparty.amount))
+        .bind(swap.expires_at as i64)
+        .bind(SwapStatus::Initiated.as_str())
+        .bind(&swap.preimage)
+        .bind(swap.initiated_by.as_string())
+        .bind(swap.updated_at as i64)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Err(invalid(&swap.hashlock, "a swap with this hashlock already exists"));
+        }
+        debit_asset(&mut tx, &swap.initiator.asset, &swap.initiator.party, swap.initiator.amount).await?;
+        tx.commit().await?;
+
+        log::info!(
+            "🔐 Swap {} initiated: {} {} for {} {}",
+            swap.hashlock, swap.initiator.amount, swap.initiator.asset.as_column(),
+            swap.counterparty.amount, swap.counterparty.asset.as_column()
+        );
+        Ok(())
+    }
+
+    /// Lock the counterparty's leg of an initiated swap at time `at`
+    pub async fn participate_swap(&self, hashlock: &str, participant: &str, at: u64) -> Result<AtomicSwap, BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        let mut swap = load_swap(&mut tx, hashlock).await?;
+        if swap.counterparty.party != participant {
+            return Err(invalid(hashlock, "only the named counterparty can participate"));
+        }
+        if swap.status != SwapStatus::Initiated {
+            return Err(invalid(hashlock, &format!("swap is {}", swap.status.as_str())));
+        }
+        if swap.is_expired(at) {
+            return Err(invalid(hashlock, "swap has expired"));
+        }
+
+        debit_asset(&mut tx, &swap.counterparty.asset, participant, swap.counterparty.amount).await?;
+        swap.status = SwapStatus::Locked;
+        swap.updated_at = at;
+        save_status(&mut tx, &swap).await?;
+        tx.commit().await?;
+        Ok(swap)
+    }
+
+    /// Reveal the secret of a locked swap at time `at`, settling both legs
+    pub async fn claim_swap(&self, hashlock: &str, preimage: &str, claimer: &str, at: u64) -> Result<AtomicSwap, BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        let mut swap = load_swap(&mut tx, hashlock).await?;
+        if !swap.is_party(claimer) {
+            return Err(invalid(hashlock, "only a party to the swap can claim it"));
+        }
+        if swap.status != SwapStatus::Locked {
+            return Err(invalid(hashlock, &format!("swap is {}", swap.status.as_str())));
+        }
+        if swap.is_expired(at) {
+            return Err(invalid(hashlock, "swap has expired"));
+        }
+        let secret = hex::decode(preimage).map_err(|_| invalid(hashlock, "secret is not hex"))?;
+        if swap_hashlock(&secret) != swap.hashlock {
+            return Err(invalid(hashlock, "secret does not match the hashlock"));
+        }
+
+        credit_asset(&mut tx, &swap.initiator.asset, &swap.counterparty.party, swap.initiator.amount).await?;
+        credit_asset(&mut tx, &swap.counterparty.asset, &swap.initiator.party, swap.counterparty.amount).await?;
+        swap.status = SwapStatus::Completed;
+        swap.preimage = Some(preimage.to_string());
+        swap.updated_at = at;
+        save_status(&mut tx, &swap).await?;
+        tx.commit().await?;
+
+        log::info!("🤝 Swap {} completed", hashlock);
+        Ok(swap)
+    }
+
+    /// Return the locked legs of an expired swap to their owners
+    pub async fn refund_swap(&self, hashlock: &str, requester: &str, at: u64) -> Result<AtomicSwap, BlockchainError> {
+        let mut tx = self.pool().await.begin().await?;
+        let mut swap = load_swap(&mut tx, hashlock).await?;
+        if !swap.is_party(requester) {
+            return Err(invalid(hashlock, "only a party to the swap can refund it"));
+        }
+        if swap.status.is_settled() {
+            return Err(invalid(hashlock, &format!("swap is {}", swap.status.as_str())));
+        }
+        if !swap.is_expired(at) {
+            return Err(invalid(hashlock, "swap has not expired"));
+        }
+
+        credit_asset(&mut tx, &swap.initiator.asset, &swap.initiator.party, swap.initiator.amount).await?;
+        if swap.status == SwapStatus::Locked {
+            credit_asset(&mut tx, &swap.counterparty.asset, &swap.counterparty.party, swap.counterparty.amount).await?;
+        }
+        swap.status = SwapStatus::Refunded;
+        swap.updated_at = at;
+        save_status(&mut tx, &swap).await?;
+        tx.commit().await?;
+
+        log::info!("↩️ Swap {} refunded", hashlock);
+        Ok(swap)
+    }
+
+    pub async fn get_swap(&self, hashlock: &str) -> Result<Option<AtomicSwap>, BlockchainError> {
+        let row = sqlx::query(&format!("SELECT {} FROM swaps WHERE hashlock = ?", SWAP_COLUMNS))
+            .bind(hashlock)
+            .fetch_optional(&self.pool().await)
+            .await?;
+        row.map(|row| swap_from_row(&row)).transpose()
+    }
+
+    /// Swaps a hex address is party to, newest first
+    pub async fn get_swaps_for(&self, address: &str) -> Result<Vec<AtomicSwap>, BlockchainError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM swaps WHERE initiator = ? OR counterparty = ? ORDER BY updated_at DESC",
+            SWAP_COLUMNS
+        ))
+        .bind(address)
+        .bind(address)
+        .fetch_all(&self.pool().await)
+        .await?;
+        rows.iter().map(swap_from_row).collect()
+    }
+}
+
+async fn load_swap(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, hashlock: &str) -> Result<AtomicSwap, BlockchainError> {
+    let row = sqlx::query(&format!("SELECT {} FROM swaps WHERE hashlock = ?", SWAP_COLUMNS))
+        .bind(hashlock)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| CoreError::SwapNotFound(hashlock.to_string()))?;
+    swap_from_row(&row)
+}
+
+async fn save_status(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, swap: &AtomicSwap) -> Result<(), BlockchainError> {
+    sqlx::query("UPDATE swaps SET status = ?, preimage = ?, updated_at = ? WHERE hashlock = ?")
+        .bind(swap.status.as_str())
+        .bind(&swap.preimage)
+        .bind(swap.updated_at as i64)
+        .bind(&swap.hashlock)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+fn swap_from_row(row: &SqliteRow) -> Result<AtomicSwap, BlockchainError> {
+    let hashlock: String = row.get("hashlock");
+    let status = SwapStatus::from_str(row.get("status"))
+        .ok_or_else(|| invalid(&hashlock, "unknown status"))?;
+    Ok(AtomicSwap {
+        initiator: SwapLeg {
+            party: row.get("initiator"),
+            asset: Asset::from_column(row.get("initiator_asset")),
+            amount: read_amount(row, "initiator_amount")?,
+        },
+        counterparty: SwapLeg {
+            party: row.get("counterparty"),
+            asset: Asset::from_column(row.get("counter_asset")),
+            amount: read_amount(row, "counter_amount")?,
+        },
+        expires_at: row.get::<i64, _>("expires_at") as u64,
+        status,
+        preimage: row.get("preimage"),
+        initiated_by: TransactionId::from_string(row.get("initiated_by"))?,
+        updated_at: row.get::<i64, _>("updated_at") as u64,
+        hashlock,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, DatabaseManager, AtomicSwap) {
+        let temp_dir = TempDir::new().unwrap();
+        let db = DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap();
+        db.credit_account("aa", 500).await.unwrap();
+        let token = db.issue_token(&TransactionId::new(), "bb", "GOLD", 100).await.unwrap();
+
+        let swap = AtomicSwap {
+            hashlock: swap_hashlock(b"secret"),
+            initiator: SwapLeg { party: "aa".to_string(), asset: Asset::Native, amount: 300 },
+            counterparty: SwapLeg { party: "bb".to_string(), asset: Asset::Token(token.token_id), amount: 40 },
+            expires_at: 1_000,
+            status: SwapStatus::Initiated,
+            preimage: None,
+            initiated_by: TransactionId::new(),
+            updated_at: 100,
+        };
+        db.initiate_swap(&swap).await.unwrap();
+        (temp_dir, db, swap)
+    }
+
+    #[tokio::test]
+    async fn test_claimed_swap_settles_both_legs() {
+        let (_temp_dir, db, swap) = setup().await;
+        let Asset::Token(token_id) = swap.counterparty.asset.clone() else { unreachable!() };
+        assert_eq!(db.get_balance("aa").await.unwrap(), 200);
+        assert!(db.initiate_swap(&swap).await.is_err());
+
+        // Only the counterparty can lock the other leg, and only before the secret is used
+        assert!(db.participate_swap(&swap.hashlock, "cc", 200).await.is_err());
+        assert!(db.claim_swap(&swap.hashlock, &hex::encode(b"secret"), "aa", 200).await.is_err());
+        db.participate_swap(&swap.hashlock, "bb", 200).await.unwrap();
+        assert_eq!(db.get_token_balance(&token_id, "bb").await.unwrap(), 60);
+
+        assert!(db.claim_swap(&swap.hashlock, &hex::encode(b"wrong"), "aa", 300).await.is_err());
+        let claimed = db.claim_swap(&swap.hashlock, &hex::encode(b"secret"), "aa", 300).await.unwrap();
+        assert_eq!(claimed.status, SwapStatus::Completed);
+        assert_eq!(db.get_balance("bb").await.unwrap(), 300);
+        assert_eq!(db.get_token_balance(&token_id, "aa").await.unwrap(), 40);
+        assert!(db.refund_swap(&swap.hashlock, "aa", 2_000).await.is_err());
+        assert_eq!(db.get_swaps_for("bb").await.unwrap(), vec![claimed]);
+    }
+
+    #[tokio::test]
+    async fn test_expired_swap_is_refunded_not_claimed() {
+        let (_temp_dir, db, swap) = setup().await;
+        let Asset::Token(token_id) = swap.counterparty.asset.clone() else { unreachable!() };
+        db.participate_swap(&swap.hashlock, "bb", 200).await.unwrap();
+
+        assert!(db.refund_swap(&swap.hashlock, "aa", 999).await.is_err());
+        assert!(db.claim_swap(&swap.hashlock, &hex::encode(b"secret"), "bb", 1_000).await.is_err());
+        let refunded = db.refund_swap(&swap.hashlock, "bb", 1_000).await.unwrap();
+        assert_eq!(refunded.status, SwapStatus::Refunded);
+        assert_eq!(db.get_balance("aa").await.unwrap(), 500);
+        assert_eq!(db.get_token_balance(&token_id, "bb").await.unwrap(), 100);
+        assert_eq!(db.get_swap(&swap.hashlock).await.unwrap(), Some(refunded));
+    }
+}
@@ -0,0 +1,192 @@
+//! Issued tokens
+//!
+//! A token is issued by a finalized `TokenIssue` transaction and takes that
+//! transaction's ID as its own. The whole supply is credited to the issuer,
+//! who can then move it with `TokenTransfer` transactions. `token_balances`
+//! holds every non-native balance; the native coin stays in
+//! `account_balances`. Swap escrow moves either through `debit_asset` and
+//! `credit_asset`.
+
+use super::accounts::{credit, debit, read_amount, stored_amount};
+use super::DatabaseManager;
+use crate::core::{amount, Amount, Asset, CoreError};
+use crate::{BlockchainError, TransactionId};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+/// An issued token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub token_id: String,
+    /// Hex address the supply was issued to
+    pub issuer: String,
+    pub symbol: String,
+    #[serde(with = "amount")]
+    pub supply: Amount,
+    pub issued_at: u64,
+}
+
+impl DatabaseManager {
+    /// Issue a token, crediting its whole supply to the issuer
+    ///
+    /// Issuing the same token twice, e.g. when a finalization is replayed,
+    /// changes nothing and returns the existing token.
+    pub async fn issue_token(&self, issued_by: &TransactionId, issuer: &str, symbol: &str, supply: Amount) -> Result<Token, BlockchainError> {
+        let token = Token {
+            token_id: issued_by.as_string(),
+            issuer: issuer.to_string(),
+            symbol: symbol.to_string(),
+            supply,
+            issued_at: Utc::now().timestamp() as u64,
+        };
+
+        let mut tx = self.pool().await.begin().await?;
+        let inserted = sqlx::query("INSERT OR IGNORE INTO tokens (token_id, issuer, symbol, supply, issued_at) VALUES (?, ?, ?, ?, ?)")
+            .bind(&token.token_id)
+            .bind(&token.issuer)
+            .bind(&token.symbol)
+            .bind(stored_amount(supply))
+            .bind(token.issued_at as i64)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if inserted == 0 {
+            return self.get_token(&token.token_id).await?.ok_or_else(|| CoreError::TokenNotFound(token.token_id).into());
+        }
+        credit_token(&mut tx, &token.token_id, issuer, supply).await?;
+        tx.commit().await?;
+
+        log::info!("🪙 Issued {} {} to {} as token {}", supply, symbol, issuer, token.token_id);
+        Ok(token)
+    }
+
+    pub async fn get_token(&self, token_id: &str) -> Result<Option<Token>, BlockchainError> {
+        let row = sqlx::query("SELECT token_id, issuer, symbol, supply, issued_at FROM tokens WHERE token_id = ?")
+            .bind(token_id)
+            .fetch_optional(&self.pool().await)
+            .await?;
+        row.map(|row| Ok(Token {
+            token_id: row.get("token_id"),
+            issuer: row.get("issuer"),
+            symbol: row.get("symbol"),
+            supply: read_amount(&row, "supply")?,
+            issued_at: row.get::<i64, _>("issued_at") as u64,
+        })).transpose()
+    }
+
+    /// Token balance of a hex address; unknown tokens and addresses hold nothing
+    pub async fn get_token_balance(&self, token_id: &str, address: &str) -> Result<Amount, BlockchainError> {
+        let row = sqlx::query("SELECT balance FROM token_balances WHERE token_id = ? AND address = ?")
+            .bind(token_id)
+            .bind(address)
+            .fetch_optional(&self.pool().await)
+            .await?;
+        row.map_or(Ok(0), |row| read_amount(&row, "balance"))
+    }
+
+    /// Move tokens between addresses, failing without change if the sender is short
+    pub async fn transfer_token(&self, token_id: &str, from: &str, to: &str, amount: Amount) -> Result<(), BlockchainError> {
+        if self.get_token(token_id).await?.is_none() {
+            return Err(CoreError::TokenNotFound(token_id.to_string()).into());
+        }
+        let mut tx = self.pool().await.begin().await?;
+        debit_token(&mut tx, token_id, from, amount).await?;
+        credit_token(&mut tx, token_id, to, amount).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Take `amount` of `asset` from an address
+pub(super) async fn debit_asset(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, asset: &Asset, address: &str, amount: Amount) -> Result<(), BlockchainError> {
+    match asset {
+        Asset::Native => debit(tx, address, amount).await,
+        Asset::Token(token_id) => debit_token(tx, token_id, address, amount).await,
+    }
+}
+
+/// Give `amount` of `asset` to an address
+pub(super) async fn credit_asset(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, asset: &Asset, address: &str, amount: Amount) -> Result<(), BlockchainError> {
+    match asset {
+        Asset::Native => credit(tx, address, amount).await,
+        Asset::Token(token_id) => credit_token(tx, token_id, address, amount).await,
+    }
+}
+
+/// Token balance of an address within a storage transaction
+async fn token_balance_in(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, token_id: &str, address: &str) -> Result<Amount, BlockchainError> {
+    let row = sqlx::query("SELECT balance FROM token_balances WHERE token_id = ? AND address = ?")
+        .bind(token_id)
+        .bind(address)
+        .fetch_optional(&mut **tx)
+        .await?;
+    row.map_or(Ok(0), |row| read_amount(&row, "balance"))
+}
+
+async fn set_token_balance(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, token_id: &str, address: &str, balance: Amount) -> Result<(), BlockchainError> {
+    sqlx::query(
+        r#"
+        INSERT INTO token_balances (token_id, address, balance, updated_at) VALUES (?, ?, ?, ?)
+        ON CONFLICT(token_id, address) DO UPDATE SET balance = excluded.balance, updated_at = excluded.updated_at
+        "#
+    )
+    .bind(token_id)
+    .bind(address)
+    .bind(stored_amount(balance))
+    .bind(Utc::now().timestamp())
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn debit_token(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, token_id: &str, address: &str, required: Amount) -> Result<(), BlockchainError> {
+    if required == 0 {
+        return Ok(());
+    }
+    let available = token_balance_in(tx, token_id, address).await?;
+    if available < required {
+        return Err(CoreError::InsufficientTokenBalance {
+            token_id: token_id.to_string(),
+            address: address.to_string(),
+            available,
+            required,
+        }.into());
+    }
+    set_token_balance(tx, token_id, address, available - required).await
+}
+
+async fn credit_token(tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>, token_id: &str, address: &str, amount: Amount) -> Result<(), BlockchainError> {
+    let balance = token_balance_in(tx, token_id, address).await?.checked_add(amount)
+        .ok_or_else(|| BlockchainError::Other(format!("Crediting {} of {} to {} overflows its balance", amount, token_id, address)))?;
+    set_token_balance(tx, token_id, address, balance).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+
+    #[tokio::test]
+    async fn test_issue_and_transfer_tokens() {
+        let db = DatabaseManager::new(DatabaseConfig::in_memory()).await.unwrap();
+
+        let issued_by = TransactionId::new();
+        let token = db.issue_token(&issued_by, "aa", "GOLD", 1_000).await.unwrap();
+        // A replayed issue does not mint again
+        db.issue_token(&issued_by, "aa", "GOLD", 1_000).await.unwrap();
+        assert_eq!(db.get_token_balance(&token.token_id, "aa").await.unwrap(), 1_000);
+
+        db.transfer_token(&token.token_id, "aa", "bb", 400).await.unwrap();
+        assert!(matches!(
+            db.transfer_token(&token.token_id, "bb", "aa", 500).await,
+            Err(BlockchainError::Core(CoreError::InsufficientTokenBalance { available: 400, .. }))
+        ));
+        assert_eq!(db.get_token_balance(&token.token_id, "aa").await.unwrap(), 600);
+        assert_eq!(db.get_token_balance(&token.token_id, "bb").await.unwrap(), 400);
+        assert!(matches!(
+            db.transfer_token("missing", "aa", "bb", 1).await,
+            Err(BlockchainError::Core(CoreError::TokenNotFound(_)))
+        ));
+    }
+}
@@ -0,0 +1,455 @@
+//! Full integrity verification of the transaction tables
+//!
+//! `verify_integrity` reads every transaction and DAG node row rather than a
+//! sample of reads: rows must decode, match their stored checksum, and a
+//! content-addressed transaction must hash to its ID. It then checks the
+//! references between `transactions`, `dag_nodes` and `transaction_parents`:
+//! every DAG node and parent link belongs to a stored transaction, every
+//! stored parent of a transaction has a link, and each DAG node's children
+//! are the transactions linking to it.
+//!
+//! With `repair`, orphans are deleted, missing links written, and DAG nodes
+//! that are corrupt, missing or have the wrong children re-derived as reads
+//! do. Transaction rows cannot be derived from anything else and are only
+//! reported; reading them quarantines them and fetches them again through the
+//! `TransactionSource`.
+
+use super::backend::{dag_node_from_row, transaction_from_row, DAG_NODE_COLUMNS, TRANSACTION_COLUMNS};
+use super::integrity::{stored_dag_node_checksum, transaction_checksum, DAG_NODES_TABLE, TRANSACTIONS_TABLE};
+use super::{DatabaseManager, StorageBackendKind};
+use crate::{BlockchainError, TransactionId};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+const PARENT_LINKS_TABLE: &str = "transaction_parents";
+
+/// Rows read per query
+const VERIFY_BATCH: i64 = 1000;
+
+/// What is wrong with a row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    /// The row's columns do not decode
+    Undecodable,
+    /// The contents differ from the stored checksum
+    ChecksumMismatch,
+    /// The transaction does not hash to its ID
+    IdMismatch,
+    /// A DAG node without its transaction
+    OrphanDagNode,
+    /// A transaction without a DAG node
+    MissingDagNode,
+    /// A DAG node's children differ from the transactions linking to it
+    ChildrenMismatch,
+    /// A parent link from or to a transaction that is not stored
+    OrphanParentLink,
+    /// A stored parent of a transaction without a link
+    MissingParentLink,
+}
+
+/// A row failing verification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub table: String,
+    /// Transaction ID, or `child>parent` for a parent link
+    pub row_id: String,
+    pub kind: IntegrityIssueKind,
+    pub detail: Option<String>,
+}
+
+/// Outcome of `verify_integrity`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseIntegrityReport {
+    pub transactions_checked: u64,
+    pub dag_nodes_checked: u64,
+    pub parent_links_checked: u64,
+    pub issues: Vec<IntegrityIssue>,
+    /// With `repair`, DAG nodes written again from their transaction
+    pub dag_nodes_rederived: u64,
+    /// With `repair`, parent links written for stored parents
+    pub parent_links_restored: u64,
+    /// With `repair`, DAG nodes and parent links deleted
+    pub orphans_removed: u64,
+    pub repaired: bool,
+    pub started_at: i64,
+    pub completed_at: i64,
+}
+
+impl DatabaseIntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Issues per kind
+    pub fn counts(&self) -> HashMap<IntegrityIssueKind, u64> {
+        let mut counts = HashMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.kind).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Issues `repair` leaves, i.e. those of transaction rows
+    pub fn unrepairable(&self) -> impl Iterator<Item = &IntegrityIssue> {
+        self.issues.iter().filter(|issue| issue.table == TRANSACTIONS_TABLE)
+    }
+
+    fn push(&mut self, table: &str, row_id: String, kind: IntegrityIssueKind, detail: Option<String>) {
+        self.issues.push(IntegrityIssue { table: table.to_string(), row_id, kind, detail });
+    }
+}
+
+impl DatabaseManager {
+    /// Check every transaction, DAG node and parent link, repairing what can
+    /// be derived again if `repair` is set
+    pub async fn verify_integrity(&self, repair: bool) -> Result<DatabaseIntegrityReport, BlockchainError> {
+        if self.backend_kind() != StorageBackendKind::Sqlite {
+            return Err(BlockchainError::Other("Integrity verification needs the SQLite backend".to_string()));
+        }
+        self.commit_queued().await?;
+        let mut report = DatabaseIntegrityReport {
+            repaired: repair,
+            started_at: Utc::now().timestamp(),
+            ..DatabaseIntegrityReport::default()
+        };
+        log::info!("🔍 Verifying database integrity");
+
+        self.verify_transaction_rows(&mut report).await?;
+        self.verify_dag_node_rows(&mut report).await?;
+        self.verify_references(&mut report).await?;
+
+        if repair {
+            self.repair_integrity(&mut report).await?;
+        }
+        report.completed_at = Utc::now().timestamp();
+        if report.is_clean() {
+            log::info!("✅ {} transaction(s) and {} DAG node(s) verified", report.transactions_checked, report.dag_nodes_checked);
+        } else {
+            log::warn!("⚠️ Integrity verification found {} issue(s)", report.issues.len());
+        }
+        Ok(report)
+    }
+
+    /// Decode, checksum and hash every transaction row
+    async fn verify_transaction_rows(&self, report: &mut DatabaseIntegrityReport) -> Result<(), BlockchainError> {
+        let mut last_rowid = 0i64;
+        loop {
+            let rows = sqlx::query(&format!(
+                "SELECT t.rowid, {} FROM transactions t WHERE t.rowid > ? ORDER BY t.rowid LIMIT ?",
+                TRANSACTION_COLUMNS
+            ))
+            .bind(last_rowid)
+            .bind(VERIFY_BATCH)
+            .fetch_all(&self.pool().await)
+            .await?;
+            let Some(last) = rows.last() else {
+                return Ok(());
+            };
+            last_rowid = last.get("rowid");
+
+            let ids: Vec<String> = rows.iter().map(|row| row.get("id")).collect();
+            let mut links = self.links_of("transaction_id", "parent_id", &ids).await?;
+            for (row, id) in rows.iter().zip(ids) {
+                report.transactions_checked += 1;
+                let parents = links.remove(&id).unwrap_or_default();
+                let decoded = TransactionId::from_string(&id).and_then(|tx_id| {
+                    let parents = parents.iter().map(|parent| TransactionId::from_string(parent)).collect::<Result<_, _>>()?;
+                    transaction_from_row(row, tx_id, parents)
+                });
+                let transaction = match decoded {
+                    Ok(transaction) => transaction,
+                    Err(e) => {
+                        report.push(TRANSACTIONS_TABLE, id, IntegrityIssueKind::Undecodable, Some(e.to_string()));
+                        continue;
+                    }
+                };
+
+                let stored: Option<String> = row.get("checksum");
+                let computed = transaction_checksum(&transaction);
+                if stored.as_ref().is_some_and(|stored| *stored != computed) {
+                    report.push(TRANSACTIONS_TABLE, id.clone(), IntegrityIssueKind::ChecksumMismatch, Some(computed));
+                }
+                // Legacy IDs are random rather than content hashes
+                if matches!(transaction.id, TransactionId::Hash(_)) && !transaction.has_valid_id() {
+                    report.push(TRANSACTIONS_TABLE, id, IntegrityIssueKind::IdMismatch, Some(transaction.compute_id().as_string()));
+                }
+            }
+        }
+    }
+
+    /// Decode and checksum every DAG node row, and compare its children with
+    /// the links to it
+    async fn verify_dag_node_rows(&self, report: &mut DatabaseIntegrityReport) -> Result<(), BlockchainError> {
+        let mut last_rowid = 0i64;
+        loop {
+            let rows = sqlx::query(&format!(
+                "SELECT d.rowid, {} FROM dag_nodes d WHERE d.rowid > ? ORDER BY d.rowid LIMIT ?",
+                DAG_NODE_COLUMNS
+            ))
+            .bind(last_rowid)
+            .bind(VERIFY_BATCH)
+            .fetch_all(&self.pool().await)
+            .await?;
+            let Some(last) = rows.last() else {
+                return Ok(());
+            };
+            last_rowid = last.get("rowid");
+
+            let ids: Vec<String> = rows.iter().map(|row| row.get("transaction_id")).collect();
+            let mut children = self.links_of("parent_id", "transaction_id", &ids).await?;
+            for (row, id) in rows.iter().zip(ids) {
+                report.dag_nodes_checked += 1;
+                let linked: BTreeSet<String> = children.remove(&id).unwrap_or_default().into_iter().collect();
+                let node = match dag_node_from_row(row) {
+                    Ok(node) => node,
+                    Err(e) => {
+                        report.push(DAG_NODES_TABLE, id, IntegrityIssueKind::Undecodable, Some(e.to_string()));
+                        continue;
+                    }
+                };
+
+                let computed = stored_dag_node_checksum(&node);
+                if node.checksum.as_ref().is_some_and(|stored| *stored != computed) {
+                    report.push(DAG_NODES_TABLE, id, IntegrityIssueKind::ChecksumMismatch, Some(computed));
+                    continue;
+                }
+                let stored: BTreeSet<String> = node.children.iter().map(TransactionId::as_string).collect();
+                if stored != linked {
+                    let detail = format!("{} stored, {} linked", stored.len(), linked.len());
+                    report.push(DAG_NODES_TABLE, id, IntegrityIssueKind::ChildrenMismatch, Some(detail));
+                }
+            }
+        }
+    }
+
+    /// Find orphan DAG nodes and links, transactions without a DAG node, and
+    /// stored parents without a link
+    async fn verify_references(&self, report: &mut DatabaseIntegrityReport) -> Result<(), BlockchainError> {
+        report.parent_links_checked = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM transaction_parents")
+            .fetch_one(&self.pool().await)
+            .await? as u64;
+
+        let checks = [
+            (
+                DAG_NODES_TABLE,
+                IntegrityIssueKind::OrphanDagNode,
+                "SELECT transaction_id FROM dag_nodes WHERE transaction_id NOT IN (SELECT id FROM transactions)",
+            ),
+            (
+                TRANSACTIONS_TABLE,
+                IntegrityIssueKind::MissingDagNode,
+                "SELECT id FROM transactions WHERE id NOT IN (SELECT transaction_id FROM dag_nodes)",
+            ),
+            (
+                PARENT_LINKS_TABLE,
+                IntegrityIssueKind::OrphanParentLink,
+                "SELECT transaction_id || '>' || parent_id FROM transaction_parents
+                 WHERE transaction_id NOT IN (SELECT id FROM transactions) OR parent_id NOT IN (SELECT id FROM transactions)",
+            ),
+            (
+                PARENT_LINKS_TABLE,
+                IntegrityIssueKind::MissingParentLink,
+                // A corrupted parent list is reported as undecodable instead
+                "SELECT t.id || '>' || p.value FROM transactions t,
+                     json_each(CASE WHEN json_valid(t.parents) THEN t.parents ELSE '[]' END) p
+                 WHERE p.value IN (SELECT id FROM transactions)
+                   AND NOT EXISTS (SELECT 1 FROM transaction_parents l WHERE l.transaction_id = t.id AND l.parent_id = p.value)",
+            ),
+        ];
+        for (table, kind, query) in checks {
+            let rows: Vec<String> = sqlx::query_scalar(query).fetch_all(&self.pool().await).await?;
+            for row_id in rows {
+                // A missing DAG node of an undecodable transaction is one issue
+                if kind == IntegrityIssueKind::MissingDagNode
+                    && report.issues.iter().any(|issue| issue.row_id == row_id && issue.kind == IntegrityIssueKind::Undecodable)
+                {
+                    continue;
+                }
+                report.push(table, row_id, kind, None);
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete orphans, restore parent links and re-derive DAG nodes
+    async fn repair_integrity(&self, report: &mut DatabaseIntegrityReport) -> Result<(), BlockchainError> {
+        for query in [
+            "DELETE FROM transaction_parents WHERE transaction_id NOT IN (SELECT id FROM transactions) OR parent_id NOT IN (SELECT id FROM transactions)",
+            "DELETE FROM dag_nodes WHERE transaction_id NOT IN (SELECT id FROM transactions)",
+        ] {
+            report.orphans_removed += sqlx::query(query).execute(&self.pool().await).await?.rows_affected();
+        }
+
+        let mut rederive = HashSet::new();
+        for issue in &report.issues {
+            match issue.kind {
+                IntegrityIssueKind::MissingParentLink => {
+                    let Some((child, parent)) = issue.row_id.split_once('>') else {
+                        continue;
+                    };
+                    report.parent_links_restored += sqlx::query("INSERT OR IGNORE INTO transaction_parents (transaction_id, parent_id) VALUES (?, ?)")
+                        .bind(child)
+                        .bind(parent)
+                        .execute(&self.pool().await)
+                        .await?
+                        .rows_affected();
+                    // The parent's children change with the link
+                    rederive.insert(parent.to_string());
+                }
+                IntegrityIssueKind::OrphanParentLink => {
+                    if let Some((_, parent)) = issue.row_id.split_once('>') {
+                        rederive.insert(parent.to_string());
+                    }
+                }
+                _ if issue.table == DAG_NODES_TABLE && issue.kind != IntegrityIssueKind::OrphanDagNode => {
+                    rederive.insert(issue.row_id.clone());
+                }
+                IntegrityIssueKind::MissingDagNode => {
+                    rederive.insert(issue.row_id.clone());
+                }
+                _ => {}
+            }
+        }
+
+        for id in rederive {
+            let Ok(tx_id) = TransactionId::from_string(&id) else {
+                continue;
+            };
+            // Skips transactions that are gone or do not decode themselves
+            let Ok(Some(stored)) = self.backend.get_transaction(&tx_id).await else {
+                continue;
+            };
+            let node = self.rederive_dag_node(stored.transaction).await?;
+            self.store_dag_node(&node).await?;
+            report.dag_nodes_rederived += 1;
+        }
+        self.commit_queued().await?;
+        log::info!(
+            "🔧 Integrity repair: {} DAG node(s) re-derived, {} parent link(s) restored, {} orphan(s) removed",
+            report.dag_nodes_rederived, report.parent_links_restored, report.orphans_removed
+        );
+        Ok(())
+    }
+
+    /// Links of `ids` from column `key` to column `value` of
+    /// `transaction_parents`, in one query however many there are
+    async fn links_of(&self, key: &str, value: &str, ids: &[String]) -> Result<HashMap<String, Vec<String>>, BlockchainError> {
+        let rows = sqlx::query(&format!(
+            "SELECT {key}, {value} FROM transaction_parents WHERE {key} IN (SELECT value FROM json_each(?))",
+            key = key,
+            value = value,
+        ))
+        .bind(serde_json::to_string(ids)?)
+        .fetch_all(&self.pool().await)
+        .await?;
+        let mut links: HashMap<String, Vec<String>> = HashMap::new();
+        for row in rows {
+            links.entry(row.get(0)).or_default().push(row.get(1));
+        }
+        Ok(links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DAGNode, NodeStatus, QuantumProof, Transaction};
+    use crate::storage::DatabaseConfig;
+    use tempfile::TempDir;
+
+    fn transaction(nonce: u64, parents: Vec<TransactionId>) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents,
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+
+    fn node(transaction: &Transaction, children: Vec<TransactionId>) -> DAGNode {
+        DAGNode {
+            transaction: transaction.clone(),
+            children,
+            weight: 80,
+            confidence: 0.0,
+            status: NodeStatus::Pending,
+            quantum_score: 80,
+        }
+    }
+
+    async fn database(temp_dir: &TempDir) -> DatabaseManager {
+        DatabaseManager::new(DatabaseConfig {
+            path: temp_dir.path().join("test.db").to_string_lossy().to_string(),
+            ..DatabaseConfig::default()
+        }).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_consistent_database_is_clean() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = database(&temp_dir).await;
+        let parent = transaction(1, vec![]);
+        let child = transaction(2, vec![parent.id.clone()]);
+        for (transaction, children) in [(&parent, vec![child.id.clone()]), (&child, vec![])] {
+            db.store_transaction(transaction).await.unwrap();
+            db.store_dag_node(&node(transaction, children)).await.unwrap();
+        }
+
+        let report = db.verify_integrity(false).await.unwrap();
+        assert!(report.is_clean(), "{:?}", report.issues);
+        assert_eq!((report.transactions_checked, report.dag_nodes_checked, report.parent_links_checked), (2, 2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_issues_are_found_and_dag_nodes_repaired() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = database(&temp_dir).await;
+        let parent = transaction(1, vec![]);
+        let child = transaction(2, vec![parent.id.clone()]);
+        let corrupt = transaction(3, vec![]);
+        for transaction in [&parent, &child, &corrupt] {
+            db.store_transaction(transaction).await.unwrap();
+        }
+        // The parent's node lacks its child, the child has none, and the
+        // third transaction's amount no longer matches its checksum
+        db.store_dag_node(&node(&parent, vec![])).await.unwrap();
+        db.store_dag_node(&node(&corrupt, vec![])).await.unwrap();
+        let pool = db.pool().await;
+        sqlx::query("UPDATE transactions SET amount = '999' WHERE id = ?").bind(corrupt.id.as_string()).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM transaction_parents").execute(&pool).await.unwrap();
+
+        let report = db.verify_integrity(false).await.unwrap();
+        let counts = report.counts();
+        assert_eq!(counts[&IntegrityIssueKind::ChecksumMismatch], 1);
+        assert_eq!(counts[&IntegrityIssueKind::IdMismatch], 1);
+        assert_eq!(counts[&IntegrityIssueKind::MissingDagNode], 1);
+        assert_eq!(counts[&IntegrityIssueKind::MissingParentLink], 1);
+        assert!(!counts.contains_key(&IntegrityIssueKind::ChildrenMismatch));
+
+        let repaired = db.verify_integrity(true).await.unwrap();
+        assert_eq!((repaired.parent_links_restored, repaired.dag_nodes_rederived), (1, 2));
+        assert_eq!(db.get_dag_node(&parent.id).await.unwrap().unwrap().children, vec![child.id.clone()]);
+
+        // Only the corrupt transaction row is left
+        let after = db.verify_integrity(false).await.unwrap();
+        let left: Vec<_> = after.issues.iter().map(|issue| (issue.row_id.clone(), issue.kind)).collect();
+        assert_eq!(left, vec![
+            (corrupt.id.as_string(), IntegrityIssueKind::ChecksumMismatch),
+            (corrupt.id.as_string(), IntegrityIssueKind::IdMismatch),
+        ]);
+        assert_eq!(after.unrepairable().count(), 2);
+    }
+}
@@ -0,0 +1,324 @@
+//! Write-behind queue for transactions and DAG nodes
+//!
+//! With the queue enabled, `store_transaction`, `store_dag_node`,
+//! `store_bundle` and `update_node_status` return once their change is
+//! queued. Queued changes are committed together as one `WriteBatch` every
+//! `flush_interval_ms`, or as soon as `max_batch` of them are waiting, so a
+//! burst of transactions costs one commit instead of one each. Lookups of a
+//! single transaction or DAG node see queued changes; listings, counts,
+//! deletes and the maintenance jobs flush the queue first.
+//!
+//! `DatabaseManager::flush` commits everything queued and returns once it is
+//! on disk. A crash loses changes queued since the last commit, as if the
+//! node had stopped before receiving those transactions; peers relay them
+//! again during sync. Commits themselves are atomic: the SQLite database
+//! runs in write-ahead-log mode while the queue is enabled, so a crash
+//! during a commit leaves the previous batch intact. A commit that fails
+//! puts its changes back at the front of the queue.
+
+use super::backend::{StorageBackend, WriteBatch};
+use super::metrics::StorageMetrics;
+use crate::core::{DAGNode, NodeStatus, Transaction};
+use crate::{BlockchainError, TransactionId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// Write-behind queue settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriteQueueConfig {
+    /// Whether writes are queued; otherwise each is committed before returning
+    pub enabled: bool,
+    /// Queued changes that trigger a commit before the interval is up
+    pub max_batch: usize,
+    /// Longest a change waits to be committed, in milliseconds
+    pub flush_interval_ms: u64,
+}
+
+impl Default for WriteQueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_batch: 1000,
+            flush_interval_ms: 100,
+        }
+    }
+}
+
+/// Changes not yet committed, indexed for lookups
+#[derive(Default)]
+struct Queued {
+    batch: WriteBatch,
+    transactions: HashMap<TransactionId, Transaction>,
+    nodes: HashMap<TransactionId, DAGNode>,
+    /// Status changes to nodes that are not queued themselves
+    statuses: HashMap<TransactionId, (NodeStatus, f64)>,
+}
+
+impl Queued {
+    fn put_transaction(&mut self, transaction: &Transaction) {
+        self.batch.put_transaction(transaction);
+        self.transactions.insert(transaction.id.clone(), transaction.clone());
+    }
+
+    fn put_dag_node(&mut self, node: &DAGNode) {
+        self.batch.put_dag_node(node);
+        self.statuses.remove(&node.transaction.id);
+        self.nodes.insert(node.transaction.id.clone(), node.clone());
+    }
+
+    fn set_status(&mut self, tx_id: &TransactionId, status: NodeStatus, confidence: f64) {
+        self.batch.set_status(tx_id, status.clone(), confidence);
+        match self.nodes.get_mut(tx_id) {
+            Some(node) => {
+                node.status = status;
+                node.confidence = confidence;
+            }
+            None => {
+                self.statuses.insert(tx_id.clone(), (status, confidence));
+            }
+        }
+    }
+
+    /// Add the changes of `later`, which were queued after these
+    fn append(&mut self, later: Queued) {
+        self.batch.append(later.batch);
+        self.transactions.extend(later.transactions);
+        for (tx_id, node) in later.nodes {
+            self.statuses.remove(&tx_id);
+            self.nodes.insert(tx_id, node);
+        }
+        for (tx_id, (status, confidence)) in later.statuses {
+            match self.nodes.get_mut(&tx_id) {
+                Some(node) => {
+                    node.status = status;
+                    node.confidence = confidence;
+                }
+                None => {
+                    self.statuses.insert(tx_id, (status, confidence));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: Queued,
+    /// The batch being committed, still visible to lookups
+    committing: Option<Arc<Queued>>,
+}
+
+/// A queued DAG node, or a status change to apply to the stored one
+pub(crate) enum QueuedNode {
+    Node(DAGNode),
+    Status(NodeStatus, f64),
+}
+
+/// Changes waiting to be committed to a storage backend
+pub(crate) struct WriteQueue {
+    config: WriteQueueConfig,
+    backend: Arc<dyn StorageBackend>,
+    metrics: StorageMetrics,
+    state: Mutex<QueueState>,
+    /// Held while committing, so batches commit in the order they were queued
+    commit_lock: tokio::sync::Mutex<()>,
+}
+
+impl WriteQueue {
+    /// Queue writes for `backend`, committing them in the background every interval
+    pub fn start(config: WriteQueueConfig, backend: Arc<dyn StorageBackend>, metrics: StorageMetrics) -> Arc<Self> {
+        let queue = Arc::new(Self {
+            config,
+            backend,
+            metrics,
+            state: Mutex::new(QueueState::default()),
+            commit_lock: tokio::sync::Mutex::new(()),
+        });
+        let interval = Duration::from_millis(queue.config.flush_interval_ms.max(1));
+        tokio::spawn(Self::commit_periodically(Arc::downgrade(&queue), interval));
+        log::info!("📥 Write queue enabled: up to {} change(s) per commit, every {:?}", queue.config.max_batch, interval);
+        queue
+    }
+
+    /// Commit queued changes every `interval` until the queue is dropped
+    async fn commit_periodically(queue: Weak<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let Some(queue) = queue.upgrade() else {
+                return;
+            };
+            if let Err(e) = queue.commit().await {
+                log::error!("❌ Failed to commit queued writes: {}", e);
+            }
+        }
+    }
+
+    pub async fn store_transaction(&self, transaction: &Transaction) -> Result<(), BlockchainError> {
+        self.enqueue(|queued| queued.put_transaction(transaction)).await
+    }
+
+    pub async fn store_dag_node(&self, node: &DAGNode) -> Result<(), BlockchainError> {
+        self.enqueue(|queued| queued.put_dag_node(node)).await
+    }
+
+    pub async fn store_bundle(&self, nodes: &[DAGNode]) -> Result<(), BlockchainError> {
+        self.enqueue(|queued| {
+            for node in nodes {
+                queued.put_transaction(&node.transaction);
+                queued.put_dag_node(node);
+            }
+        }).await
+    }
+
+    pub async fn set_status(&self, tx_id: &TransactionId, status: NodeStatus, confidence: f64) -> Result<(), BlockchainError> {
+        self.enqueue(|queued| queued.set_status(tx_id, status, confidence)).await
+    }
+
+    /// Queue changes, committing right away once `max_batch` are waiting
+    async fn enqueue(&self, change: impl FnOnce(&mut Queued)) -> Result<(), BlockchainError> {
+        let full = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            change(&mut state.pending);
+            self.metrics.set_queue_depth(state.pending.batch.len());
+            state.pending.batch.len() >= self.config.max_batch
+        };
+        if full {
+            self.commit().await?;
+        }
+        Ok(())
+    }
+
+    /// A queued transaction
+    pub fn transaction(&self, tx_id: &TransactionId) -> Option<Transaction> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let transaction = [Some(&state.pending), state.committing.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|queued| queued.transactions.get(tx_id).cloned());
+        transaction
+    }
+
+    /// A queued DAG node, or the latest queued status change to it
+    pub fn dag_node(&self, tx_id: &TransactionId) -> Option<QueuedNode> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut status = None;
+        // Changes still pending are newer than those being committed
+        for queued in [Some(&state.pending), state.committing.as_deref()].into_iter().flatten() {
+            if let Some(node) = queued.nodes.get(tx_id) {
+                let mut node = node.clone();
+                if let Some((status, confidence)) = status {
+                    node.status = status;
+                    node.confidence = confidence;
+                }
+                return Some(QueuedNode::Node(node));
+            }
+            if status.is_none() {
+                status = queued.statuses.get(tx_id).cloned();
+            }
+        }
+        status.map(|(status, confidence)| QueuedNode::Status(status, confidence))
+    }
+
+    /// Commit everything queued so far
+    pub async fn commit(&self) -> Result<(), BlockchainError> {
+        let _commit = self.commit_lock.lock().await;
+        let committing = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if state.pending.batch.is_empty() {
+                return Ok(());
+            }
+            let committing = Arc::new(std::mem::take(&mut state.pending));
+            state.committing = Some(committing.clone());
+            committing
+        };
+
+        let start = std::time::Instant::now();
+        let result = self.backend.write(committing.batch.clone()).await;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let committed = state.committing.take();
+        match result {
+            Ok(()) => {
+                self.metrics.record_commit(committing.batch.len(), start.elapsed());
+                self.metrics.set_queue_depth(state.pending.batch.len());
+                log::debug!("Committed {} queued write(s)", committing.batch.len());
+                Ok(())
+            }
+            Err(e) => {
+                drop(committing);
+                // Only the state and this function held the batch
+                if let Some(Ok(mut failed)) = committed.map(Arc::try_unwrap) {
+                    failed.append(std::mem::take(&mut state.pending));
+                    state.pending = failed;
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::QuantumProof;
+    use crate::storage::{DatabaseConfig, DatabaseManager};
+
+    fn node(nonce: u64) -> DAGNode {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: vec![1u8; 32],
+            receiver: vec![2u8; 32],
+            amount: 10,
+            fee: 1,
+            nonce,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![0u8; 64],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![1u8; 32], resistance_score: 80, proof_timestamp: 1_700_000_000 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        DAGNode { transaction, children: vec![], weight: 1, confidence: 0.0, status: NodeStatus::Pending, quantum_score: 80 }
+    }
+
+    #[tokio::test]
+    async fn test_queued_writes_are_visible_and_committed_together() {
+        let db = DatabaseManager::new(DatabaseConfig {
+            write_queue: WriteQueueConfig { enabled: true, max_batch: 100, flush_interval_ms: 60_000 },
+            ..DatabaseConfig::in_memory()
+        }).await.unwrap();
+        let (first, second) = (node(1), node(2));
+        db.store_bundle(&[first.clone(), second.clone()]).await.unwrap();
+        db.update_node_status(&first.transaction.id, NodeStatus::Confirmed, 0.8).await.unwrap();
+
+        // Lookups see queued writes before anything is committed
+        assert!(db.backend.get_transaction(&first.transaction.id).await.unwrap().is_none());
+        assert_eq!(db.get_transaction(&second.transaction.id).await.unwrap().unwrap().nonce, 2);
+        assert_eq!(db.get_dag_node(&first.transaction.id).await.unwrap().unwrap().status, NodeStatus::Confirmed);
+
+        db.flush().await.unwrap();
+        assert_eq!(db.backend.transaction_count().await.unwrap(), 2);
+        let stored = db.backend.get_dag_node(&first.transaction.id).await.unwrap().unwrap();
+        assert_eq!((stored.status, stored.confidence), (NodeStatus::Confirmed, 0.8));
+
+        // A status change to a committed node is applied on lookup
+        db.update_node_status(&second.transaction.id, NodeStatus::Finalized, 1.0).await.unwrap();
+        assert_eq!(db.get_dag_node(&second.transaction.id).await.unwrap().unwrap().status, NodeStatus::Finalized);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_commits_before_returning() {
+        let db = DatabaseManager::new(DatabaseConfig {
+            write_queue: WriteQueueConfig { enabled: true, max_batch: 2, flush_interval_ms: 60_000 },
+            ..DatabaseConfig::in_memory()
+        }).await.unwrap();
+        db.store_transaction(&node(1).transaction).await.unwrap();
+        assert_eq!(db.backend.transaction_count().await.unwrap(), 0);
+        db.store_transaction(&node(2).transaction).await.unwrap();
+        assert_eq!(db.backend.transaction_count().await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,177 @@
+//! Proptest strategies for test scenarios
+//!
+//! Scenarios are plain data so they shrink well: a DAG is a list of parent
+//! indices, a transfer names accounts by index. They are turned into real
+//! transactions only when a harness runs them.
+
+use crate::core::{Amount, QuantumProof, Transaction};
+use crate::TransactionId;
+use proptest::prelude::*;
+
+/// Most accounts a ledger scenario can use, one per `account_key` byte
+pub const MAX_ACCOUNTS: usize = 255;
+
+/// Public key of the ledger account at `index`
+pub fn account_key(index: usize) -> Vec<u8> {
+    vec![(index % MAX_ACCOUNTS) as u8 + 1; 32]
+}
+
+/// Shape of a DAG grown on top of genesis
+///
+/// `parents[k]` lists the parents of the `k`th transaction. Index 0 is
+/// genesis and index `j + 1` is the `j`th transaction, so every transaction
+/// only approves genesis or transactions before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DagShape {
+    pub parents: Vec<Vec<usize>>,
+}
+
+impl DagShape {
+    /// Transactions for this shape, in insertion order
+    ///
+    /// Each transaction has its own sender so none of them conflict.
+    pub fn transactions(&self, genesis: &TransactionId) -> Vec<Transaction> {
+        let timestamp = chrono::Utc::now().timestamp() as u64;
+        let mut ids = vec![genesis.clone()];
+        let mut transactions = Vec::with_capacity(self.parents.len());
+
+        for (k, parents) in self.parents.iter().enumerate() {
+            let mut sender = vec![0xda; 32];
+            sender[..8].copy_from_slice(&(k as u64).to_le_bytes());
+            let mut transaction = Transaction {
+                id: TransactionId::default(),
+                sender,
+                receiver: vec![0xdb; 32],
+                amount: 1,
+                fee: 0,
+                nonce: k as u64,
+                timestamp,
+                parents: parents.iter().map(|p| ids[*p].clone()).collect(),
+                signature: vec![0u8; 64],
+                signature_scheme: Default::default(),
+                quantum_proof: QuantumProof { prime_hash: vec![0u8; 32], resistance_score: 80, proof_timestamp: timestamp },
+                metadata: None,
+            };
+            transaction.id = transaction.compute_id();
+            ids.push(transaction.id.clone());
+            transactions.push(transaction);
+        }
+        transactions
+    }
+}
+
+/// DAGs of 1 to `max_transactions` transactions with 1 to `max_parents`
+/// distinct parents each
+pub fn arb_dag_shape(max_transactions: usize, max_parents: usize) -> impl Strategy<Value = DagShape> {
+    let picks = prop::collection::vec(any::<usize>(), 1..=max_parents.max(1));
+    prop::collection::vec(picks, 1..=max_transactions.max(1)).prop_map(|raw| {
+        let parents = raw.into_iter().enumerate().map(|(k, picks)| {
+            // Transaction k may approve genesis or any of the k before it
+            let mut parents: Vec<usize> = picks.into_iter().map(|p| p % (k + 1)).collect();
+            parents.sort_unstable();
+            parents.dedup();
+            parents
+        }).collect();
+        DagShape { parents }
+    })
+}
+
+/// A validator and its stake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorSpec {
+    pub validator_id: String,
+    pub stake: Amount,
+}
+
+/// 1 to `max_validators` validators staking 1 to 1000 each
+pub fn arb_validator_set(max_validators: usize) -> impl Strategy<Value = Vec<ValidatorSpec>> {
+    prop::collection::vec(1 as Amount..=1000, 1..=max_validators.max(1)).prop_map(|stakes| {
+        stakes.into_iter().enumerate()
+            .map(|(i, stake)| ValidatorSpec { validator_id: format!("validator_{}", i), stake })
+            .collect()
+    })
+}
+
+/// Validator ID used for votes from outside the set
+pub const OUTSIDER: &str = "outsider";
+
+/// Up to `max_votes` votes from `validators`, with repeats and the odd
+/// vote from an `OUTSIDER`
+pub fn arb_vote_sequence(validators: &[ValidatorSpec], max_votes: usize) -> impl Strategy<Value = Vec<String>> {
+    let ids: Vec<String> = validators.iter().map(|v| v.validator_id.clone()).collect();
+    // One index past the set picks the outsider
+    prop::collection::vec(0..=ids.len(), 0..=max_votes).prop_map(move |picks| {
+        picks.into_iter()
+            .map(|i| ids.get(i).cloned().unwrap_or_else(|| OUTSIDER.to_string()))
+            .collect()
+    })
+}
+
+/// Validator set and the votes cast on one checkpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointScenario {
+    pub validators: Vec<ValidatorSpec>,
+    pub votes: Vec<String>,
+}
+
+pub fn arb_checkpoint_scenario(max_validators: usize, max_votes: usize) -> impl Strategy<Value = CheckpointScenario> {
+    arb_validator_set(max_validators).prop_flat_map(move |validators| {
+        arb_vote_sequence(&validators, max_votes)
+            .prop_map(move |votes| CheckpointScenario { validators: validators.clone(), votes })
+    })
+}
+
+/// Transfer between ledger accounts named by index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: usize,
+    pub to: usize,
+    pub amount: Amount,
+    pub fee: Amount,
+    pub nonce: u64,
+}
+
+impl Transfer {
+    pub fn transaction(&self) -> Transaction {
+        let mut transaction = Transaction {
+            id: TransactionId::default(),
+            sender: account_key(self.from),
+            receiver: account_key(self.to),
+            amount: self.amount,
+            fee: self.fee,
+            nonce: self.nonce,
+            timestamp: 1_700_000_000,
+            parents: vec![],
+            signature: vec![],
+            signature_scheme: Default::default(),
+            quantum_proof: QuantumProof { prime_hash: vec![], resistance_score: 80, proof_timestamp: 0 },
+            metadata: None,
+        };
+        transaction.id = transaction.compute_id();
+        transaction
+    }
+}
+
+/// Starting balances and a sequence of transfers between them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerScenario {
+    /// Starting balance of each account
+    pub balances: Vec<Amount>,
+    pub transfers: Vec<Transfer>,
+}
+
+/// `accounts` accounts holding up to 1000 each and up to `max_transfers`
+/// transfers between them, many of which overdraw their sender
+pub fn arb_ledger_scenario(accounts: usize, max_transfers: usize) -> impl Strategy<Value = LedgerScenario> {
+    let accounts = accounts.clamp(1, MAX_ACCOUNTS);
+    let transfer = (0..accounts, 0..accounts, 0 as Amount..=500, 0 as Amount..=20);
+    (
+        prop::collection::vec(0 as Amount..=1000, accounts),
+        prop::collection::vec(transfer, 0..=max_transfers),
+    ).prop_map(|(balances, transfers)| LedgerScenario {
+        balances,
+        transfers: transfers.into_iter().enumerate()
+            .map(|(nonce, (from, to, amount, fee))| Transfer { from, to, amount, fee, nonce: nonce as u64 })
+            .collect(),
+    })
+}
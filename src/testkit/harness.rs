@@ -0,0 +1,216 @@
+//! Engine-versus-model checks
+//!
+//! Each check runs one scenario through a real engine and its reference
+//! model, comparing after every step, and returns the first `Divergence`.
+
+use super::generators::{account_key, CheckpointScenario, DagShape, LedgerScenario};
+use super::model::{ConsensusModel, DagModel, LedgerModel};
+use super::Divergence;
+use crate::consensus::{BlsKeypair, CheckpointCertificate, CheckpointCommittee, CheckpointVote, CommitteeMember};
+use crate::core::{account_address, AccountState, DAGCore};
+use crate::identity::{NodeSignature, SignatureType};
+use crate::storage::DatabaseManager;
+use crate::TransactionId;
+use std::collections::HashSet;
+
+/// Grow `dag` by `shape` and compare tips, children, depth and cumulative
+/// weights with a `DagModel`
+///
+/// `dag` should be freshly created; the shape is grown on its genesis.
+pub async fn check_dag(dag: &mut DAGCore, shape: &DagShape) -> Result<(), Divergence> {
+    let genesis = dag.genesis_id().cloned()
+        .ok_or_else(|| Divergence::new(0, "genesis", "a genesis transaction", "none"))?;
+    let mut model = DagModel::new(genesis.clone());
+
+    for (step, transaction) in shape.transactions(&genesis).into_iter().enumerate() {
+        let expected = model.insert(&transaction);
+        let actual = dag.add_transaction(transaction.clone()).await;
+        if expected != actual.is_ok() {
+            return Err(Divergence::new(step, "add_transaction", expected, actual.map_err(|e| e.to_string())));
+        }
+
+        let tips: HashSet<_> = dag.get_tips().into_iter().map(|node| node.transaction.id.clone()).collect();
+        if &tips != model.tips() {
+            return Err(Divergence::new(step, "tips", model.tips(), tips));
+        }
+        for parent in &transaction.parents {
+            let children = dag.get_node(parent).map(|node| node.children.clone()).unwrap_or_default();
+            if children != model.children(parent) {
+                return Err(Divergence::new(step, format!("children of {}", parent), model.children(parent), children));
+            }
+        }
+    }
+
+    let step = shape.parents.len();
+    let own_weight = |id: &TransactionId| dag.get_node(id).map_or(0, |node| node.weight);
+    let expected = model.cumulative_weight(&genesis, &own_weight);
+    let actual = dag.calculate_cumulative_weight(&genesis);
+    if expected != actual {
+        return Err(Divergence::new(step, "cumulative weight of genesis", expected, actual));
+    }
+    let expected_depth = shape.transactions(&genesis).iter().map(|tx| model.depth(&tx.id)).max().unwrap_or(1);
+    let actual_depth = dag.get_dag_stats().depth;
+    if expected_depth != actual_depth {
+        return Err(Divergence::new(step, "depth", expected_depth, actual_depth));
+    }
+    Ok(())
+}
+
+/// Fund the scenario's accounts in `database`, apply every transfer twice and
+/// compare results and balances with a `LedgerModel`
+///
+/// `database` should be empty; the second pass checks that replays change
+/// nothing.
+pub async fn check_ledger(database: &DatabaseManager, scenario: &LedgerScenario) -> Result<(), Divergence> {
+    let mut model = LedgerModel::new();
+    let addresses: Vec<String> = (0..scenario.balances.len()).map(|i| account_address(&account_key(i))).collect();
+    for (address, balance) in addresses.iter().zip(&scenario.balances) {
+        model.credit(address, *balance);
+        database.credit_account(address, *balance).await
+            .map_err(|e| Divergence::new(0, "funding", "credited", e.to_string()))?;
+    }
+
+    let transfers = scenario.transfers.iter().chain(&scenario.transfers);
+    for (step, transfer) in transfers.enumerate() {
+        let transaction = transfer.transaction();
+        let expected = model.apply(&transaction).ok();
+        let actual = database.apply_finalized_transaction(&transaction).await;
+        if expected != actual.as_ref().ok().copied() {
+            return Err(Divergence::new(step, "apply_finalized_transaction", expected, actual.map_err(|e| e.to_string())));
+        }
+
+        for address in &addresses {
+            let balance = database.get_balance(address).await
+                .map_err(|e| Divergence::new(step, "get_balance", "a balance", e.to_string()))?;
+            if balance != model.balance(address) {
+                return Err(Divergence::new(step, format!("balance of {}", address), model.balance(address), balance));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reserve every transfer against the starting balances, then release every
+/// other one, comparing `AccountState` with a `LedgerModel`
+pub fn check_reservations(scenario: &LedgerScenario) -> Result<(), Divergence> {
+    let state = AccountState::new();
+    let mut model = LedgerModel::new();
+    let addresses: Vec<String> = (0..scenario.balances.len()).map(|i| account_address(&account_key(i))).collect();
+    for (address, balance) in addresses.iter().zip(&scenario.balances) {
+        model.credit(address, *balance);
+    }
+
+    let transactions: Vec<_> = scenario.transfers.iter().map(|t| t.transaction()).collect();
+    let compare = |step: usize, state: &AccountState, model: &LedgerModel| {
+        for address in &addresses {
+            if state.reserved(address) != model.reserved(address) {
+                return Err(Divergence::new(step, format!("reserved by {}", address), model.reserved(address), state.reserved(address)));
+            }
+        }
+        if state.pending_count() != model.pending_count() {
+            return Err(Divergence::new(step, "pending count", model.pending_count(), state.pending_count()));
+        }
+        Ok(())
+    };
+
+    for (step, transaction) in transactions.iter().enumerate() {
+        let balance = model.balance(&account_address(&transaction.sender));
+        let expected = model.reserve(transaction).is_ok();
+        let actual = state.reserve(transaction, balance);
+        if expected != actual.is_ok() {
+            return Err(Divergence::new(step, "reserve", expected, actual.map_err(|e| e.to_string())));
+        }
+        compare(step, &state, &model)?;
+    }
+
+    for (step, transaction) in transactions.iter().enumerate().step_by(2) {
+        let step = transactions.len() + step;
+        let (expected, actual) = (model.release(&transaction.id), state.release(&transaction.id));
+        if expected != actual {
+            return Err(Divergence::new(step, "release", expected, actual));
+        }
+        compare(step, &state, &model)?;
+    }
+    Ok(())
+}
+
+/// Aggregate the scenario's votes into a checkpoint certificate and compare
+/// aggregation and quorum verification with a `ConsensusModel`
+///
+/// Committee BLS keys are derived from validator IDs. Only the classical
+/// path is checked; post-quantum signatures are placeholders.
+pub fn check_checkpoint_quorum(scenario: &CheckpointScenario) -> Result<(), Divergence> {
+    let model = ConsensusModel::new(&scenario.validators);
+    let key_for = |validator_id: &str| {
+        let mut seed = b"quantum-dag-testkit-checkpoint-key".to_vec();
+        seed.extend_from_slice(validator_id.as_bytes());
+        BlsKeypair::from_seed(&seed).map_err(|e| Divergence::new(0, "BLS key", "a key", e.to_string()))
+    };
+
+    let mut members = Vec::with_capacity(scenario.validators.len());
+    for validator in &scenario.validators {
+        members.push(CommitteeMember {
+            validator_id: validator.validator_id.clone(),
+            bls_public_key: key_for(&validator.validator_id)?.public_key(),
+            pqc_public_key: Vec::new(),
+            stake_amount: validator.stake,
+        });
+    }
+    let committee = CheckpointCommittee::new(SignatureType::Dilithium3, members);
+
+    let checkpoint_hash = vec![7u8; 32];
+    let payload = CheckpointCertificate::signing_payload(1, &checkpoint_hash, &committee);
+    let mut votes = Vec::with_capacity(scenario.votes.len());
+    for validator_id in &scenario.votes {
+        votes.push(CheckpointVote {
+            validator_id: validator_id.clone(),
+            bls_signature: key_for(validator_id)?.sign(&payload),
+            pqc_signature: NodeSignature {
+                signature_type: SignatureType::Dilithium3,
+                signature_data: vec![0u8; 8],
+                public_key: Vec::new(),
+                timestamp: 0,
+                nonce: 0,
+                threshold: None,
+            },
+        });
+    }
+
+    let expected = model.has_quorum(&scenario.votes);
+    let certificate = CheckpointCertificate::aggregate(1, checkpoint_hash, &committee, &votes);
+    let certificate = match (&expected, certificate) {
+        (Ok(_), Ok(certificate)) => certificate,
+        (Err(_), Err(_)) => return Ok(()),
+        (expected, actual) => {
+            return Err(Divergence::new(0, "aggregate", expected, actual.map(|_| "a certificate").map_err(|e| e.to_string())));
+        }
+    };
+
+    let expected = expected.unwrap_or(false);
+    let actual = certificate.verify_classical(&committee);
+    if expected != actual.is_ok() {
+        return Err(Divergence::new(1, "quorum", expected, actual.map_err(|e| e.to_string())));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::generators::{arb_checkpoint_scenario, arb_ledger_scenario};
+    use proptest::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_reservations_match_model(scenario in arb_ledger_scenario(4, 32)) {
+            prop_assert_eq!(check_reservations(&scenario), Ok(()));
+        }
+
+        #[test]
+        fn test_checkpoint_quorum_matches_model(scenario in arb_checkpoint_scenario(6, 8)) {
+            prop_assert_eq!(check_checkpoint_quorum(&scenario), Ok(()));
+        }
+    }
+}
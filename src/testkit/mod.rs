@@ -0,0 +1,60 @@
+//! Model-based test kit
+//!
+//! Enabled with the `testkit` feature. `generators` provides proptest
+//! strategies for random DAG shapes, validator sets, checkpoint vote
+//! sequences and transfer workloads. `model` holds small reference models of
+//! what the ledger, the DAG and checkpoint quorums are expected to do, written
+//! for clarity rather than speed. The `check_*` functions in `harness` drive
+//! a real engine and its model through the same scenario and report the first
+//! step where they disagree, so a property test reduces to:
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn reservations_match_model(scenario in arb_ledger_scenario(4, 32)) {
+//!         prop_assert_eq!(check_reservations(&scenario), Ok(()));
+//!     }
+//! }
+//! ```
+
+pub mod generators;
+pub mod harness;
+pub mod model;
+
+pub use generators::{
+    account_key, arb_checkpoint_scenario, arb_dag_shape, arb_ledger_scenario, arb_validator_set,
+    arb_vote_sequence, CheckpointScenario, DagShape, LedgerScenario, Transfer, ValidatorSpec,
+    MAX_ACCOUNTS, OUTSIDER,
+};
+pub use harness::{check_checkpoint_quorum, check_dag, check_ledger, check_reservations};
+pub use model::{ConsensusModel, DagModel, LedgerModel, ModelRejection};
+
+/// First point where an engine and its model disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Scenario step, e.g. the index of the transfer being applied
+    pub step: usize,
+    /// What was compared
+    pub what: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Divergence {
+    pub fn new(step: usize, what: impl Into<String>, expected: impl std::fmt::Debug, actual: impl std::fmt::Debug) -> Self {
+        Self {
+            step,
+            what: what.into(),
+            expected: format!("{:?}", expected),
+            actual: format!("{:?}", actual),
+        }
+    }
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "step {}: {} diverged, model {} but engine {}", self.step, self.what, self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for Divergence {}
@@ -0,0 +1,264 @@
+//! Reference models
+//!
+//! Each model states the expected behavior of an engine as directly as
+//! possible, with no caching, persistence or concurrency.
+
+use super::generators::ValidatorSpec;
+use crate::core::{account_address, Amount, Transaction};
+use crate::TransactionId;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Why a model refused an operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelRejection {
+    InsufficientBalance { available: Amount, required: Amount },
+    UnknownValidator(String),
+    DuplicateVote(String),
+    NoVotes,
+}
+
+/// Expected balances and reservations
+///
+/// Applying a transfer debits the sender the amount plus the fee and
+/// credits the receiver the amount, once per transaction ID. Reservations
+/// hold the amount plus the fee against the sender's balance.
+#[derive(Debug, Clone, Default)]
+pub struct LedgerModel {
+    balances: HashMap<String, Amount>,
+    applied: HashSet<TransactionId>,
+    reserved: HashMap<String, Amount>,
+    reservations: HashMap<TransactionId, (String, Amount)>,
+}
+
+impl LedgerModel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn balance(&self, address: &str) -> Amount {
+        self.balances.get(address).copied().unwrap_or(0)
+    }
+
+    pub fn credit(&mut self, address: &str, amount: Amount) {
+        let balance = self.balances.entry(address.to_string()).or_default();
+        *balance = balance.saturating_add(amount);
+    }
+
+    /// Apply a finalized transfer, returning `false` if it was already applied
+    pub fn apply(&mut self, transaction: &Transaction) -> Result<bool, ModelRejection> {
+        if self.applied.contains(&transaction.id) {
+            return Ok(false);
+        }
+        let sender = account_address(&transaction.sender);
+        let required = transaction.amount.saturating_add(transaction.fee);
+        let available = self.balance(&sender);
+        if available < required {
+            return Err(ModelRejection::InsufficientBalance { available, required });
+        }
+
+        self.balances.insert(sender, available - required);
+        self.credit(&account_address(&transaction.receiver), transaction.amount);
+        self.applied.insert(transaction.id.clone());
+        Ok(true)
+    }
+
+    /// Funds held by pending transfers from `address`
+    pub fn reserved(&self, address: &str) -> Amount {
+        self.reserved.get(address).copied().unwrap_or(0)
+    }
+
+    /// Hold a pending transfer's amount and fee against the sender's balance
+    pub fn reserve(&mut self, transaction: &Transaction) -> Result<(), ModelRejection> {
+        if self.reservations.contains_key(&transaction.id) {
+            return Ok(());
+        }
+        let sender = account_address(&transaction.sender);
+        let available = self.balance(&sender).saturating_sub(self.reserved(&sender));
+        let required = transaction.amount.saturating_add(transaction.fee);
+        if required > available {
+            return Err(ModelRejection::InsufficientBalance { available, required });
+        }
+
+        *self.reserved.entry(sender.clone()).or_default() += required;
+        self.reservations.insert(transaction.id.clone(), (sender, required));
+        Ok(())
+    }
+
+    /// Drop a reservation, returning whether there was one
+    pub fn release(&mut self, tx_id: &TransactionId) -> bool {
+        let Some((sender, amount)) = self.reservations.remove(tx_id) else {
+            return false;
+        };
+        if let Some(reserved) = self.reserved.get_mut(&sender) {
+            *reserved -= amount;
+        }
+        true
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.reservations.len()
+    }
+}
+
+/// Expected checkpoint quorum decisions
+///
+/// A set of votes is valid when it is non-empty, every voter is a member
+/// and nobody votes twice. It reaches quorum when the signers hold at least
+/// two thirds of the total stake.
+#[derive(Debug, Clone)]
+pub struct ConsensusModel {
+    stakes: BTreeMap<String, Amount>,
+}
+
+impl ConsensusModel {
+    pub fn new(validators: &[ValidatorSpec]) -> Self {
+        Self {
+            stakes: validators.iter().map(|v| (v.validator_id.clone(), v.stake)).collect(),
+        }
+    }
+
+    pub fn total_stake(&self) -> Amount {
+        self.stakes.values().sum()
+    }
+
+    /// Smallest signed stake that reaches quorum
+    pub fn required_stake(&self) -> Amount {
+        (self.total_stake() * 2).div_ceil(3).max(1)
+    }
+
+    /// Stake behind a set of votes
+    pub fn tally(&self, votes: &[String]) -> Result<Amount, ModelRejection> {
+        if votes.is_empty() {
+            return Err(ModelRejection::NoVotes);
+        }
+        let mut seen = HashSet::new();
+        let mut signed = 0;
+        for voter in votes {
+            let stake = self.stakes.get(voter).ok_or_else(|| ModelRejection::UnknownValidator(voter.clone()))?;
+            if !seen.insert(voter) {
+                return Err(ModelRejection::DuplicateVote(voter.clone()));
+            }
+            signed += stake;
+        }
+        Ok(signed)
+    }
+
+    pub fn has_quorum(&self, votes: &[String]) -> Result<bool, ModelRejection> {
+        Ok(self.tally(votes)? >= self.required_stake())
+    }
+}
+
+/// Expected DAG structure
+///
+/// Tips are the transactions nobody approves yet. A node's depth is its
+/// longest parent chain, genesis included. Its cumulative weight is its own
+/// weight plus the cumulative weight of each child, so an approver reachable
+/// along several paths counts once per path.
+#[derive(Debug, Clone)]
+pub struct DagModel {
+    children: HashMap<TransactionId, Vec<TransactionId>>,
+    depths: HashMap<TransactionId, usize>,
+    tips: HashSet<TransactionId>,
+}
+
+impl DagModel {
+    pub fn new(genesis: TransactionId) -> Self {
+        Self {
+            children: HashMap::from([(genesis.clone(), Vec::new())]),
+            depths: HashMap::from([(genesis.clone(), 1)]),
+            tips: HashSet::new(),
+        }
+    }
+
+    pub fn contains(&self, tx_id: &TransactionId) -> bool {
+        self.children.contains_key(tx_id)
+    }
+
+    /// Add a transaction, returning `false` if a parent is unknown or the
+    /// transaction is already present
+    pub fn insert(&mut self, transaction: &Transaction) -> bool {
+        if self.contains(&transaction.id) || !transaction.parents.iter().all(|p| self.contains(p)) {
+            return false;
+        }
+        let depth = transaction.parents.iter().map(|p| self.depths[p]).max().unwrap_or(0) + 1;
+        for parent in &transaction.parents {
+            self.children.get_mut(parent).expect("parent checked above").push(transaction.id.clone());
+            self.tips.remove(parent);
+        }
+        self.children.insert(transaction.id.clone(), Vec::new());
+        self.depths.insert(transaction.id.clone(), depth);
+        self.tips.insert(transaction.id.clone());
+        true
+    }
+
+    pub fn tips(&self) -> &HashSet<TransactionId> {
+        &self.tips
+    }
+
+    pub fn children(&self, tx_id: &TransactionId) -> &[TransactionId] {
+        self.children.get(tx_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn depth(&self, tx_id: &TransactionId) -> usize {
+        self.depths.get(tx_id).copied().unwrap_or(0)
+    }
+
+    /// Cumulative weight given each node's own weight
+    pub fn cumulative_weight(&self, tx_id: &TransactionId, own_weight: &impl Fn(&TransactionId) -> u64) -> u64 {
+        self.children(tx_id).iter()
+            .fold(own_weight(tx_id), |total, child| total.saturating_add(self.cumulative_weight(child, own_weight)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::generators::{DagShape, Transfer};
+
+    #[test]
+    fn test_ledger_model_applies_once_and_rejects_overdrafts() {
+        let transfer = Transfer { from: 0, to: 1, amount: 60, fee: 5, nonce: 0 }.transaction();
+        let sender = account_address(&transfer.sender);
+        let receiver = account_address(&transfer.receiver);
+
+        let mut ledger = LedgerModel::new();
+        ledger.credit(&sender, 100);
+        assert_eq!(ledger.apply(&transfer), Ok(true));
+        assert_eq!(ledger.apply(&transfer), Ok(false));
+        assert_eq!((ledger.balance(&sender), ledger.balance(&receiver)), (35, 60));
+
+        let overdraft = Transfer { from: 0, to: 1, amount: 35, fee: 1, nonce: 1 }.transaction();
+        assert_eq!(ledger.apply(&overdraft), Err(ModelRejection::InsufficientBalance { available: 35, required: 36 }));
+    }
+
+    #[test]
+    fn test_consensus_model_quorum() {
+        let validators: Vec<ValidatorSpec> = [100, 100, 100].iter().enumerate()
+            .map(|(i, stake)| ValidatorSpec { validator_id: format!("validator_{}", i), stake: *stake })
+            .collect();
+        let model = ConsensusModel::new(&validators);
+        let votes = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(model.has_quorum(&votes(&["validator_0", "validator_1"])), Ok(true));
+        assert_eq!(model.has_quorum(&votes(&["validator_0"])), Ok(false));
+        assert_eq!(model.tally(&votes(&["validator_0", "validator_0"])), Err(ModelRejection::DuplicateVote("validator_0".to_string())));
+        assert_eq!(model.tally(&[]), Err(ModelRejection::NoVotes));
+    }
+
+    #[test]
+    fn test_dag_model_counts_weight_per_path() {
+        let genesis = TransactionId::new();
+        // Diamond: 1 and 2 approve genesis, 3 approves both
+        let shape = DagShape { parents: vec![vec![0], vec![0], vec![1, 2]] };
+        let transactions = shape.transactions(&genesis);
+        let mut model = DagModel::new(genesis.clone());
+        for transaction in &transactions {
+            assert!(model.insert(transaction));
+        }
+
+        assert_eq!(model.tips(), &HashSet::from([transactions[2].id.clone()]));
+        assert_eq!(model.depth(&transactions[2].id), 3);
+        // 3 is reached through both 1 and 2
+        assert_eq!(model.cumulative_weight(&genesis, &|_| 1), 5);
+    }
+}
@@ -1,5 +1,11 @@
 //! Utility functions for the blockchain
 
+pub mod reload;
+pub use reload::{
+    is_hot_reloadable, watch_settings_file, ConfigChange, ConfigError, ConsensusSettings, DatabaseSettings, NetworkSettings,
+    NodeSettings, ReloadReport, SecuritySettings, HOT_RELOADABLE_FIELDS,
+};
+
 use crate::BlockchainError;
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -143,13 +149,13 @@ pub mod validation {
     }
 
     /// Validate an amount
-    pub fn validate_amount(amount: u64) -> Result<(), BlockchainError> {
+    pub fn validate_amount(amount: crate::core::Amount) -> Result<(), BlockchainError> {
         if amount == 0 {
             return Err(BlockchainError::Validation("Amount cannot be zero".to_string()));
         }
 
         // Check for reasonable maximum (prevent overflow)
-        if amount > u64::MAX / 1000 {
+        if amount > crate::core::Amount::MAX / 1000 {
             return Err(BlockchainError::Validation("Amount too large".to_string()));
         }
 
@@ -304,12 +310,21 @@ pub mod error {
                 crate::core::CoreError::InsufficientQuantumResistance => "Transaction has insufficient quantum resistance".to_string(),
                 crate::core::CoreError::InvalidTransactionStructure => "Transaction structure is invalid".to_string(),
                 crate::core::CoreError::Serialization(_) => "Failed to serialize transaction data".to_string(),
+                crate::core::CoreError::Backpressure(secs) => format!("Node is busy, retry in {} seconds", secs),
+                crate::core::CoreError::InvalidFilter(_) => "Address filter is invalid".to_string(),
+                crate::core::CoreError::MetadataTooLarge { max, .. } => format!("Transaction metadata exceeds {} bytes", max),
+                crate::core::CoreError::TooManyParents { max, .. } => format!("Transaction approves more than {} parents", max),
+                crate::core::CoreError::SignatureTooLarge { max, .. } => format!("Transaction signature exceeds {} bytes", max),
+                crate::core::CoreError::TransactionTooLarge { max, .. } => format!("Transaction exceeds {} bytes", max),
             },
             BlockchainError::Network(network_error) => match network_error {
                 crate::network::NetworkError::NotRunning => "Network layer is not running".to_string(),
                 crate::network::NetworkError::PeerNotFound(_) => "Peer not found".to_string(),
                 crate::network::NetworkError::ConnectionFailed(_) => "Failed to establish connection".to_string(),
                 crate::network::NetworkError::ProtocolError(_) => "Network protocol error".to_string(),
+                crate::network::NetworkError::MessageTooLarge(_, _) => "Message exceeds the size limit".to_string(),
+                crate::network::NetworkError::PeerThrottled(_) => "Peer is being rate limited".to_string(),
+                crate::network::NetworkError::PeerBanned(_) => "Peer is banned for misbehavior".to_string(),
             },
             BlockchainError::Consensus(consensus_error) => match consensus_error {
                 crate::consensus::ConsensusError::NoValidators => "No validators available".to_string(),
@@ -317,6 +332,10 @@ pub mod error {
                 crate::consensus::ConsensusError::InvalidTransaction => "Transaction is invalid for consensus".to_string(),
                 crate::consensus::ConsensusError::ValidatorNotFound(_) => "Validator not found".to_string(),
                 crate::consensus::ConsensusError::Timeout => "Consensus operation timed out".to_string(),
+                crate::consensus::ConsensusError::InvalidTrustAnchor(_) => "Trust anchor could not be verified".to_string(),
+                crate::consensus::ConsensusError::InvalidCheckpointCertificate(_) => "Checkpoint certificate could not be verified".to_string(),
+                crate::consensus::ConsensusError::InvalidSamplingConfig(_) => "Sampling confirmation is misconfigured".to_string(),
+                crate::consensus::ConsensusError::InvalidConfirmationThresholds(_) => "Confirmation thresholds are misconfigured".to_string(),
             },
             BlockchainError::Security(security_error) => match security_error {
                 crate::security::SecurityError::AddressBlocked(_) => "Address is blocked".to_string(),
@@ -326,6 +345,7 @@ pub mod error {
                 crate::security::SecurityError::ThreatDetected(_) => "Security threat detected".to_string(),
                 crate::security::SecurityError::KeyGenerationFailed(_) => "Failed to generate cryptographic keys".to_string(),
                 crate::security::SecurityError::EncryptionFailed(_) => "Encryption failed".to_string(),
+                crate::security::SecurityError::ComplianceDenied(_) => "Transaction denied by compliance screening".to_string(),
             },
             BlockchainError::Math(math_error) => match math_error {
                 crate::math::MathError::InvalidPrimeHash => "Invalid prime hash".to_string(),
@@ -335,8 +355,46 @@ pub mod error {
                 crate::math::MathError::PrimeGeneration(_) => "Failed to generate prime number".to_string(),
                 crate::math::MathError::Calculation(_) => "Mathematical calculation error".to_string(),
             },
+            BlockchainError::Config(config_error) => match config_error {
+                crate::utils::reload::ConfigError::InvalidValue(field, _) => format!("Invalid setting: {}", field),
+                crate::utils::reload::ConfigError::Load(_) => "Settings file could not be loaded".to_string(),
+            },
+            BlockchainError::Messaging(messaging_error) => match messaging_error {
+                crate::identity::MessagingError::UnknownContact(node_id) => format!("Unknown operator contact: {}", node_id),
+                crate::identity::MessagingError::MessageNotFound(_) => "Message not found".to_string(),
+                crate::identity::MessagingError::TooLarge(_, limit) => format!("Message exceeds {} bytes", limit),
+                crate::identity::MessagingError::Storage(_) => "Operator mailbox could not be saved".to_string(),
+                _ => "Operator message could not be processed".to_string(),
+            },
+            BlockchainError::Faucet(faucet_error) => match faucet_error {
+                crate::core::FaucetError::RateLimited(retry_after) => format!("Too many faucet requests, try again in {}s", retry_after),
+                crate::core::FaucetError::Exhausted => "The faucet is empty, try again later".to_string(),
+                crate::core::FaucetError::InvalidAddress(_) => "Invalid address".to_string(),
+                crate::core::FaucetError::Rejected(..) => "Faucet request was not verified".to_string(),
+                _ => "The faucet is not available".to_string(),
+            },
+            BlockchainError::Archival(archival_error) => match archival_error {
+                crate::storage::ArchivalError::NotAnchored(_) => "Archive has no finalized anchor".to_string(),
+                crate::storage::ArchivalError::Corrupt(_)
+                | crate::storage::ArchivalError::ContentMismatch { .. }
+                | crate::storage::ArchivalError::AnchorMismatch(_) => "Archive failed verification".to_string(),
+                _ => "Archive is not available".to_string(),
+            },
+            BlockchainError::SafeMode(safe_mode_error) => match safe_mode_error {
+                crate::core::SafeModeError::Halted(_) => "The node is in safe mode and not accepting transactions".to_string(),
+                crate::core::SafeModeError::UnauthorizedKey(_)
+                | crate::core::SafeModeError::InvalidSignature
+                | crate::core::SafeModeError::StaleAuthorization
+                | crate::core::SafeModeError::WrongHalt { .. } => "Resume authorization was rejected".to_string(),
+                _ => "Safe mode could not be changed".to_string(),
+            },
+            BlockchainError::Disclosure(disclosure_error) => match disclosure_error {
+                crate::identity::DisclosureError::UnknownAttribute(name) => format!("Unknown attribute: {}", name),
+                _ => "Disclosure proof is invalid".to_string(),
+            },
             BlockchainError::Io(_) => "Input/output error".to_string(),
             BlockchainError::Serialization(_) => "Serialization error".to_string(),
+            BlockchainError::Pruned(_) => "Transaction was pruned by this node; ask an archival node".to_string(),
             BlockchainError::Other(msg) => msg.clone(),
             BlockchainError::Validation(msg) => msg.clone(),
         }
@@ -355,8 +413,19 @@ pub mod logging {
         Ok(())
     }
 
+    /// Initialize logging so the level can be changed at runtime
+    ///
+    /// The logger itself passes every record and `log::set_max_level` decides
+    /// what is emitted, which is what a settings reload adjusts.
+    pub fn init_reloadable_logging(level: log::LevelFilter) {
+        env_logger::Builder::from_default_env()
+            .filter_level(log::LevelFilter::Trace)
+            .init();
+        log::set_max_level(level);
+    }
+
     /// Log transaction creation
-    pub fn log_transaction_created(tx_id: &str, amount: u64) {
+    pub fn log_transaction_created(tx_id: &str, amount: crate::core::Amount) {
         log::info!("📝 Transaction created: {} (amount: {})", tx_id, amount);
     }
 
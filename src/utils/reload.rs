@@ -0,0 +1,538 @@
+//! Config hot-reload
+//!
+//! `NodeSettings` is the node's settings document. A reload compares a new
+//! document with the running one field by field. Changes to fields matched by
+//! `HOT_RELOADABLE_FIELDS` are validated together and applied only if all of
+//! them are valid; every other change is reported as needing a restart and is
+//! not applied. Settings files are JSON and are polled for changes.
+
+use crate::{BlockchainConfig, ChecksumConfig, ConfirmationMode, ExpiryConfig, FeePolicy, IngestionConfig, MaintenanceConfig, OrphanConfig, PeerScoringConfig, PruningConfig, SignaturePolicy, SloConfig, StateCheckpointConfig, ThresholdTier, TransactionLimits, ValidationConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+/// Field paths, or section prefixes ending in `.`, that apply without a restart
+pub const HOT_RELOADABLE_FIELDS: &[&str] = &[
+    "log_level",
+    "ingestion.",
+    "peer_scoring.",
+    "max_filter_subscriptions",
+    "signature_policy.",
+    "validation.",
+    "submit_timeout_ms",
+    "identity_rotation_grace_secs",
+    "pruning.",
+    "expiry.",
+    "fees.",
+    "checksums.",
+    "slo.",
+    "limits.",
+    "orphans.",
+    "state_checkpoints.",
+    "maintenance.",
+];
+
+/// Node settings document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeSettings {
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    pub network: NetworkSettings,
+    pub consensus: ConsensusSettings,
+    pub security: SecuritySettings,
+    pub database: DatabaseSettings,
+    #[serde(default)]
+    pub ingestion: IngestionConfig,
+    #[serde(default)]
+    pub peer_scoring: PeerScoringConfig,
+    #[serde(default = "default_max_filter_subscriptions")]
+    pub max_filter_subscriptions: usize,
+    /// Signature scheme required per transaction value band
+    #[serde(default)]
+    pub signature_policy: SignaturePolicy,
+    /// Parallel transaction validation
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    /// Time budget for a synchronous submission, in milliseconds
+    #[serde(default = "default_submit_timeout_ms")]
+    pub submit_timeout_ms: u64,
+    /// How long a rotated-out identity's keys stay valid, in seconds
+    #[serde(default = "default_identity_rotation_grace_secs")]
+    pub identity_rotation_grace_secs: u64,
+    /// Removal of old finalized transactions from memory and storage
+    #[serde(default)]
+    pub pruning: PruningConfig,
+    /// Eviction of pending transactions that expire unapproved
+    #[serde(default)]
+    pub expiry: ExpiryConfig,
+    /// Minimum fees a transaction must pay
+    #[serde(default)]
+    pub fees: FeePolicy,
+    /// Which storage reads verify row checksums
+    #[serde(default)]
+    pub checksums: ChecksumConfig,
+    /// Consensus service level objectives
+    #[serde(default)]
+    pub slo: SloConfig,
+    /// Largest metadata, parent list, signature and transaction accepted
+    #[serde(default)]
+    pub limits: TransactionLimits,
+    /// Relayed transactions held while their parents are missing
+    #[serde(default)]
+    pub orphans: OrphanConfig,
+    /// Materialized balances and finalized tips, for fast sync
+    #[serde(default)]
+    pub state_checkpoints: StateCheckpointConfig,
+    /// Vacuum, analyze and WAL checkpoint jobs run while the node is quiet
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+}
+
+/// Network settings, applied at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    pub listen_addr: String,
+    pub bootstrap_nodes: Vec<String>,
+    pub max_peers: u32,
+}
+
+/// Consensus settings, applied at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsensusSettings {
+    pub block_time_ms: u64,
+    pub validator_count: u32,
+    pub prime_modulus: u64,
+    pub finality_threshold: f64,
+    pub fork_resolution_enabled: bool,
+    #[serde(default)]
+    pub confirmation: ConfirmationMode,
+    #[serde(default)]
+    pub confirmation_tiers: Vec<ThresholdTier>,
+}
+
+/// Security settings, applied at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecuritySettings {
+    pub quantum_resistance_level: u32,
+    pub signature_scheme: String,
+    pub key_rotation_interval_hours: u64,
+}
+
+/// Database settings, applied at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseSettings {
+    pub path: String,
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_max_filter_subscriptions() -> usize {
+    10_000
+}
+
+fn default_submit_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_identity_rotation_grace_secs() -> u64 {
+    86_400
+}
+
+/// A field whose value differs between two settings documents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Outcome of a reload
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReloadReport {
+    /// Changes now in effect
+    pub applied: Vec<ConfigChange>,
+    /// Changes ignored until the node restarts
+    pub restart_required: Vec<ConfigChange>,
+}
+
+/// Config reload errors
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Invalid value for {0}: {1}")]
+    InvalidValue(String, String),
+    #[error("Failed to load settings: {0}")]
+    Load(String),
+}
+
+impl NodeSettings {
+    /// Settings for a node started from `config`, with defaults for the rest
+    pub fn from_config(config: &BlockchainConfig) -> Self {
+        Self {
+            log_level: log::max_level().to_string().to_lowercase(),
+            network: NetworkSettings {
+                listen_addr: config.network.listen_addr.clone(),
+                bootstrap_nodes: config.network.bootstrap_nodes.clone(),
+                max_peers: config.network.max_peers,
+            },
+            consensus: ConsensusSettings {
+                block_time_ms: config.consensus.block_time_ms,
+                validator_count: config.consensus.validator_count,
+                prime_modulus: config.consensus.prime_modulus,
+                finality_threshold: config.consensus.finality_threshold,
+                fork_resolution_enabled: config.consensus.fork_resolution_enabled,
+                confirmation: config.consensus.confirmation.clone(),
+                confirmation_tiers: config.consensus.confirmation_tiers.clone(),
+            },
+            security: SecuritySettings {
+                quantum_resistance_level: config.security.quantum_resistance_level,
+                signature_scheme: config.security.signature_scheme.clone(),
+                key_rotation_interval_hours: config.security.key_rotation_interval_hours,
+            },
+            database: DatabaseSettings {
+                path: config.database.path.clone(),
+            },
+            ingestion: IngestionConfig::default(),
+            peer_scoring: PeerScoringConfig::default(),
+            max_filter_subscriptions: default_max_filter_subscriptions(),
+            signature_policy: SignaturePolicy::default(),
+            validation: ValidationConfig::default(),
+            submit_timeout_ms: default_submit_timeout_ms(),
+            identity_rotation_grace_secs: default_identity_rotation_grace_secs(),
+            pruning: PruningConfig::default(),
+            expiry: ExpiryConfig::default(),
+            fees: FeePolicy::default(),
+            checksums: ChecksumConfig::default(),
+            slo: SloConfig::default(),
+            limits: TransactionLimits::default(),
+            orphans: OrphanConfig::default(),
+            state_checkpoints: StateCheckpointConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+        }
+    }
+
+    /// Read a JSON settings file
+    pub fn load(path: &std::path::Path) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Load(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_str(&contents).map_err(|e| ConfigError::Load(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parsed log level
+    pub fn log_level_filter(&self) -> Result<log::LevelFilter, ConfigError> {
+        self.log_level
+            .parse()
+            .map_err(|_| ConfigError::InvalidValue("log_level".to_string(), format!("unknown level '{}'", self.log_level)))
+    }
+
+    /// Check the hot-reloadable fields
+    pub fn validate_hot(&self) -> Result<(), ConfigError> {
+        let invalid = |field: &str, reason: &str| Err(ConfigError::InvalidValue(field.to_string(), reason.to_string()));
+
+        self.log_level_filter()?;
+
+        if self.ingestion.max_pending == 0 {
+            return invalid("ingestion.max_pending", "must be greater than zero");
+        }
+        if self.ingestion.ticket_ttl_secs == 0 {
+            return invalid("ingestion.ticket_ttl_secs", "must be greater than zero");
+        }
+
+        let scoring = &self.peer_scoring;
+        if scoring.throttle_threshold <= 0.0 {
+            return invalid("peer_scoring.throttle_threshold", "must be positive");
+        }
+        if scoring.disconnect_threshold < scoring.throttle_threshold {
+            return invalid("peer_scoring.disconnect_threshold", "must not be below throttle_threshold");
+        }
+        if scoring.ban_threshold < scoring.disconnect_threshold {
+            return invalid("peer_scoring.ban_threshold", "must not be below disconnect_threshold");
+        }
+        if scoring.decay_per_minute < 0.0 {
+            return invalid("peer_scoring.decay_per_minute", "must not be negative");
+        }
+        if scoring.max_message_size == 0 {
+            return invalid("peer_scoring.max_message_size", "must be greater than zero");
+        }
+
+        if self.max_filter_subscriptions == 0 {
+            return invalid("max_filter_subscriptions", "must be greater than zero");
+        }
+
+        if let Err(reason) = self.signature_policy.validate() {
+            return invalid("signature_policy.bands", &reason);
+        }
+
+        if self.validation.workers == 0 {
+            return invalid("validation.workers", "must be greater than zero");
+        }
+
+        if self.submit_timeout_ms == 0 {
+            return invalid("submit_timeout_ms", "must be greater than zero");
+        }
+
+        if self.pruning.horizon_secs == 0 {
+            return invalid("pruning.horizon_secs", "must be greater than zero");
+        }
+        if self.pruning.interval_secs == 0 {
+            return invalid("pruning.interval_secs", "must be greater than zero");
+        }
+
+        if self.expiry.interval_secs == 0 {
+            return invalid("expiry.interval_secs", "must be greater than zero");
+        }
+        if self.expiry.max_pending_age_secs == Some(0) {
+            return invalid("expiry.max_pending_age_secs", "must be greater than zero");
+        }
+
+        if self.checksums.sample_percent > 100 {
+            return invalid("checksums.sample_percent", "must be at most 100");
+        }
+
+        if let Err((field, reason)) = self.slo.validate() {
+            return invalid(&field, &reason);
+        }
+
+        let limits = &self.limits;
+        if limits.max_parents == 0 {
+            return invalid("limits.max_parents", "must be greater than zero");
+        }
+        if limits.max_signature_bytes == 0 {
+            return invalid("limits.max_signature_bytes", "must be greater than zero");
+        }
+        if limits.max_encoded_bytes < limits.max_metadata_bytes {
+            return invalid("limits.max_encoded_bytes", "must not be below max_metadata_bytes");
+        }
+
+        if self.orphans.max_orphans == 0 {
+            return invalid("orphans.max_orphans", "must be greater than zero");
+        }
+        if self.orphans.ttl_secs == 0 {
+            return invalid("orphans.ttl_secs", "must be greater than zero");
+        }
+
+        if self.state_checkpoints.interval_secs == 0 {
+            return invalid("state_checkpoints.interval_secs", "must be greater than zero");
+        }
+        if self.state_checkpoints.keep == 0 {
+            return invalid("state_checkpoints.keep", "must be greater than zero");
+        }
+
+        if self.maintenance.check_interval_secs == 0 {
+            return invalid("maintenance.check_interval_secs", "must be greater than zero");
+        }
+
+        Ok(())
+    }
+
+    /// Split the differences from `self` to `proposed` into hot and restart-only changes
+    pub fn diff(&self, proposed: &NodeSettings) -> ReloadReport {
+        let mut changes = Vec::new();
+        let current = serde_json::to_value(self).unwrap_or(Value::Null);
+        let proposed = serde_json::to_value(proposed).unwrap_or(Value::Null);
+        collect_changes("", &current, &proposed, &mut changes);
+
+        let (applied, restart_required) = changes.into_iter().partition(|change| is_hot_reloadable(&change.field));
+        ReloadReport { applied, restart_required }
+    }
+
+    /// Settings with the hot-reloadable fields taken from `proposed`
+    pub fn with_hot_fields(&self, proposed: &NodeSettings) -> NodeSettings {
+        NodeSettings {
+            log_level: proposed.log_level.clone(),
+            ingestion: proposed.ingestion.clone(),
+            peer_scoring: proposed.peer_scoring.clone(),
+            max_filter_subscriptions: proposed.max_filter_subscriptions,
+            signature_policy: proposed.signature_policy.clone(),
+            validation: proposed.validation.clone(),
+            submit_timeout_ms: proposed.submit_timeout_ms,
+            identity_rotation_grace_secs: proposed.identity_rotation_grace_secs,
+            pruning: proposed.pruning.clone(),
+            expiry: proposed.expiry.clone(),
+            fees: proposed.fees.clone(),
+            checksums: proposed.checksums.clone(),
+            slo: proposed.slo.clone(),
+            limits: proposed.limits.clone(),
+            orphans: proposed.orphans.clone(),
+            state_checkpoints: proposed.state_checkpoints.clone(),
+            maintenance: proposed.maintenance.clone(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Whether changing `field` takes effect without a restart
+pub fn is_hot_reloadable(field: &str) -> bool {
+    HOT_RELOADABLE_FIELDS.iter().any(|hot| {
+        if hot.ends_with('.') {
+            field.starts_with(hot)
+        } else {
+            field == *hot
+        }
+    })
+}
+
+fn collect_changes(prefix: &str, old: &Value, new: &Value, changes: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (Value::Object(old_fields), Value::Object(new_fields)) => {
+            let mut keys: Vec<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let field = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                let old = old_fields.get(key).unwrap_or(&Value::Null);
+                let new = new_fields.get(key).unwrap_or(&Value::Null);
+                collect_changes(&field, old, new, changes);
+            }
+        },
+        _ if old != new => changes.push(ConfigChange {
+            field: prefix.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {},
+    }
+}
+
+/// Poll a settings file and hand each changed version to `on_change`
+///
+/// The file is loaded once straight away. Files that fail to parse are
+/// logged and skipped; the running settings stay in place.
+pub fn watch_settings_file<F, Fut>(path: PathBuf, interval: Duration, on_change: F) -> JoinHandle<()>
+where
+    F: Fn(NodeSettings) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<ReloadReport, crate::BlockchainError>> + Send,
+{
+    crate::spawn_instrumented(crate::Subsystem::Core, "settings_watcher", async move {
+        let mut last_modified: Option<SystemTime> = None;
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            let modified = match std::fs::metadata(&path).and_then(|meta| meta.modified()) {
+                Ok(modified) => modified,
+                Err(e) => {
+                    log::warn!("⚠️ Cannot stat settings file {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if last_modified == Some(modified) {
+                continue;
+            }
+            last_modified = Some(modified);
+
+            let settings = match NodeSettings::load(&path) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    log::error!("❌ {}", e);
+                    continue;
+                }
+            };
+
+            match on_change(settings).await {
+                Ok(report) => log_report(&report),
+                Err(e) => log::error!("❌ Settings reload rejected: {}", e),
+            }
+        }
+    })
+}
+
+fn log_report(report: &ReloadReport) {
+    for change in &report.applied {
+        log::info!("🔁 Applied {}: {} -> {}", change.field, change.old, change.new);
+    }
+    for change in &report.restart_required {
+        log::warn!("⏸️ {} changed to {} but needs a restart to take effect", change.field, change.new);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> NodeSettings {
+        NodeSettings {
+            log_level: "info".to_string(),
+            network: NetworkSettings {
+                listen_addr: "/ip4/127.0.0.1/tcp/0".to_string(),
+                bootstrap_nodes: vec![],
+                max_peers: 10,
+            },
+            consensus: ConsensusSettings {
+                block_time_ms: 5000,
+                validator_count: 3,
+                prime_modulus: 2147483647,
+                finality_threshold: 0.8,
+                fork_resolution_enabled: true,
+                confirmation: ConfirmationMode::Local,
+                confirmation_tiers: vec![],
+            },
+            security: SecuritySettings {
+                quantum_resistance_level: 128,
+                signature_scheme: "dilithium".to_string(),
+                key_rotation_interval_hours: 24,
+            },
+            database: DatabaseSettings {
+                path: "./test_db".to_string(),
+            },
+            ingestion: IngestionConfig::default(),
+            peer_scoring: PeerScoringConfig::default(),
+            max_filter_subscriptions: 10_000,
+            signature_policy: SignaturePolicy::default(),
+            validation: ValidationConfig { workers: 4 },
+            submit_timeout_ms: 10_000,
+            identity_rotation_grace_secs: 86_400,
+            pruning: PruningConfig::default(),
+            expiry: ExpiryConfig::default(),
+            fees: FeePolicy::default(),
+            checksums: ChecksumConfig::default(),
+            slo: SloConfig::default(),
+            limits: TransactionLimits::default(),
+            orphans: OrphanConfig::default(),
+            state_checkpoints: StateCheckpointConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_separates_hot_and_restart_fields() {
+        let current = settings();
+        let mut proposed = current.clone();
+        proposed.log_level = "debug".to_string();
+        proposed.ingestion.max_pending = 500;
+        proposed.consensus.block_time_ms = 1000;
+        proposed.network.bootstrap_nodes = vec!["/ip4/10.0.0.1/tcp/8999".to_string()];
+
+        let report = current.diff(&proposed);
+        let applied: Vec<&str> = report.applied.iter().map(|c| c.field.as_str()).collect();
+        let restart: Vec<&str> = report.restart_required.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(applied, ["ingestion.max_pending", "log_level"]);
+        assert_eq!(restart, ["consensus.block_time_ms", "network.bootstrap_nodes"]);
+
+        let next = current.with_hot_fields(&proposed);
+        assert_eq!(next.log_level, "debug");
+        assert_eq!(next.consensus.block_time_ms, 5000);
+        assert!(next.diff(&proposed).applied.is_empty());
+    }
+
+    #[test]
+    fn test_validate_hot_rejects_bad_values() {
+        let mut proposed = settings();
+        assert!(proposed.validate_hot().is_ok());
+
+        proposed.log_level = "chatty".to_string();
+        assert!(matches!(proposed.validate_hot(), Err(ConfigError::InvalidValue(field, _)) if field == "log_level"));
+
+        proposed.log_level = "warn".to_string();
+        proposed.peer_scoring.ban_threshold = 10.0;
+        assert!(matches!(
+            proposed.validate_hot(),
+            Err(ConfigError::InvalidValue(field, _)) if field == "peer_scoring.ban_threshold"
+        ));
+    }
+}